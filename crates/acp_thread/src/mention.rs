@@ -57,6 +57,33 @@ pub enum MentionUri {
     TerminalSelection {
         line_count: u32,
     },
+    TerminalOutput {
+        /// The saved session name or title of the terminal.
+        name: String,
+        /// The remote host, for SSH and Telnet terminals.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        host: Option<String>,
+        scope: TerminalOutputScope,
+    },
+}
+
+/// Which part of a terminal's output a [`MentionUri::TerminalOutput`] covers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub enum TerminalOutputScope {
+    LastLines(u32),
+    Selection,
+    LastCommand,
+}
+
+impl TerminalOutputScope {
+    pub fn label(&self) -> String {
+        match self {
+            Self::LastLines(1) => "last line".to_string(),
+            Self::LastLines(count) => format!("last {count} lines"),
+            Self::Selection => "selection".to_string(),
+            Self::LastCommand => "last command".to_string(),
+        }
+    }
 }
 
 impl MentionUri {
@@ -208,6 +235,33 @@ impl MentionUri {
                         .parse::<u32>()
                         .unwrap_or(0);
                     Ok(Self::TerminalSelection { line_count })
+                } else if path == "/agent/terminal-output" {
+                    let mut name = None;
+                    let mut host = None;
+                    let mut scope = None;
+                    let mut lines = None;
+                    for (key, value) in url.query_pairs() {
+                        match key.as_ref() {
+                            "name" => name = Some(value.into_owned()),
+                            "host" => host = Some(value.into_owned()),
+                            "scope" => scope = Some(value.into_owned()),
+                            "lines" => lines = Some(value.parse::<u32>()?),
+                            _ => bail!("invalid query parameter"),
+                        }
+                    }
+                    let scope = match scope.as_deref() {
+                        Some("lines") => {
+                            TerminalOutputScope::LastLines(lines.context("Missing line count")?)
+                        }
+                        Some("selection") => TerminalOutputScope::Selection,
+                        Some("last-command") => TerminalOutputScope::LastCommand,
+                        _ => bail!("invalid terminal output scope"),
+                    };
+                    Ok(Self::TerminalOutput {
+                        name: name.context("Missing terminal name")?,
+                        host,
+                        scope,
+                    })
                 } else {
                     bail!("invalid zed url: {:?}", input);
                 }
@@ -237,6 +291,9 @@ impl MentionUri {
                     format!("Terminal ({} lines)", line_count)
                 }
             }
+            MentionUri::TerminalOutput { name, scope, .. } => {
+                format!("{name} ({})", scope.label())
+            }
             MentionUri::Selection {
                 abs_path: path,
                 line_range,
@@ -259,7 +316,9 @@ impl MentionUri {
             MentionUri::TextThread { .. } => IconName::Thread.path().into(),
             MentionUri::Rule { .. } => IconName::Reader.path().into(),
             MentionUri::Diagnostics { .. } => IconName::Warning.path().into(),
-            MentionUri::TerminalSelection { .. } => IconName::Terminal.path().into(),
+            MentionUri::TerminalSelection { .. } | MentionUri::TerminalOutput { .. } => {
+                IconName::Terminal.path().into()
+            }
             MentionUri::Selection { .. } => IconName::Reader.path().into(),
             MentionUri::Fetch { .. } => IconName::ToolWeb.path().into(),
         }
@@ -360,6 +419,29 @@ impl MentionUri {
                     .append_pair("lines", &line_count.to_string());
                 url
             }
+            MentionUri::TerminalOutput { name, host, scope } => {
+                let mut url = Url::parse("zed:///agent/terminal-output").unwrap();
+                {
+                    let mut query = url.query_pairs_mut();
+                    query.append_pair("name", name);
+                    if let Some(host) = host {
+                        query.append_pair("host", host);
+                    }
+                    match scope {
+                        TerminalOutputScope::LastLines(count) => {
+                            query.append_pair("scope", "lines");
+                            query.append_pair("lines", &count.to_string());
+                        }
+                        TerminalOutputScope::Selection => {
+                            query.append_pair("scope", "selection");
+                        }
+                        TerminalOutputScope::LastCommand => {
+                            query.append_pair("scope", "last-command");
+                        }
+                    }
+                }
+                url
+            }
         }
     }
 }
@@ -683,4 +765,38 @@ mod tests {
         let parsed_single = MentionUri::parse(single_line_uri, PathStyle::local()).unwrap();
         assert_eq!(parsed_single.name(), "Terminal (1 line)");
     }
+
+    #[test]
+    fn test_parse_terminal_output_uri() {
+        let uri =
+            "zed:///agent/terminal-output?name=core-router&host=10.0.0.1&scope=lines&lines=100";
+        let parsed = MentionUri::parse(uri, PathStyle::local()).unwrap();
+        assert_eq!(
+            parsed,
+            MentionUri::TerminalOutput {
+                name: "core-router".to_string(),
+                host: Some("10.0.0.1".to_string()),
+                scope: TerminalOutputScope::LastLines(100),
+            }
+        );
+        assert_eq!(parsed.to_uri().to_string(), uri);
+        assert_eq!(parsed.name(), "core-router (last 100 lines)");
+
+        let local = MentionUri::TerminalOutput {
+            name: "zsh".to_string(),
+            host: None,
+            scope: TerminalOutputScope::LastCommand,
+        };
+        assert_eq!(
+            MentionUri::parse(local.to_uri().as_str(), PathStyle::local()).unwrap(),
+            local
+        );
+        assert!(
+            MentionUri::parse(
+                "zed:///agent/terminal-output?scope=selection",
+                PathStyle::local()
+            )
+            .is_err()
+        );
+    }
 }
@@ -46,6 +46,18 @@ pub fn extract_terminal_pattern_display(command: &str) -> Option<String> {
     extract_command_name(command)
 }
 
+/// Extracts a pattern matching every command on the session of a remote terminal tool
+/// input, which has the form `<session>: <command>`.
+pub fn extract_remote_terminal_pattern(input: &str) -> Option<String> {
+    let (session, _) = input.split_once(": ")?;
+    Some(format!("^{}: ", regex::escape(session)))
+}
+
+pub fn extract_remote_terminal_pattern_display(input: &str) -> Option<String> {
+    let (session, _) = input.split_once(": ")?;
+    Some(session.to_string())
+}
+
 pub fn extract_path_pattern(path: &str) -> Option<String> {
     let parent = Path::new(path).parent()?;
     let parent_str = normalize_separators(parent.to_str()?);
@@ -269,4 +281,17 @@ mod tests {
         assert_eq!(extract_copy_move_pattern("just/a/path.rs"), None);
         assert_eq!(extract_copy_move_pattern_display("just/a/path.rs"), None);
     }
+
+    #[test]
+    fn test_extract_remote_terminal_pattern() {
+        assert_eq!(
+            extract_remote_terminal_pattern("core-router (lab): show ip route"),
+            Some("^core\\-router \\(lab\\): ".to_string())
+        );
+        assert_eq!(
+            extract_remote_terminal_pattern_display("core-router (lab): show ip route"),
+            Some("core-router (lab)".to_string())
+        );
+        assert_eq!(extract_remote_terminal_pattern("show ip route"), None);
+    }
 }
@@ -2,7 +2,7 @@ use crate::{
     ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DbLanguageModel, DbThread,
     DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindPathTool, GrepTool,
     ListDirectoryTool, MovePathTool, NowTool, OpenTool, ProjectSnapshot, ReadFileTool,
-    RestoreFileFromDiskTool, SaveFileTool, StreamingEditFileTool, SubagentTool,
+    RemoteTerminalTool, RestoreFileFromDiskTool, SaveFileTool, StreamingEditFileTool, SubagentTool,
     SystemPromptTemplate, Template, Templates, TerminalTool, ThinkingTool, ToolPermissionDecision,
     WebSearchTool, decide_permission_from_settings,
 };
@@ -215,6 +215,7 @@ impl UserMessage {
         const OPEN_RULES_TAG: &str =
             "<rules>\nThe user has specified the following rules that should be applied:\n";
         const OPEN_DIAGNOSTICS_TAG: &str = "<diagnostics>";
+        const OPEN_TERMINAL_OUTPUT_TAG: &str = "<terminal_output>";
 
         let mut file_context = OPEN_FILES_TAG.to_string();
         let mut directory_context = OPEN_DIRECTORIES_TAG.to_string();
@@ -224,6 +225,7 @@ impl UserMessage {
         let mut fetch_context = OPEN_FETCH_TAG.to_string();
         let mut rules_context = OPEN_RULES_TAG.to_string();
         let mut diagnostics_context = OPEN_DIAGNOSTICS_TAG.to_string();
+        let mut terminal_output_context = OPEN_TERMINAL_OUTPUT_TAG.to_string();
 
         for chunk in &self.content {
             let chunk = match chunk {
@@ -319,6 +321,22 @@ impl UserMessage {
                             )
                             .ok();
                         }
+                        MentionUri::TerminalOutput { name, host, scope } => {
+                            let source = match host {
+                                Some(host) => format!("{name} on host {host}"),
+                                None => name.clone(),
+                            };
+                            write!(
+                                &mut terminal_output_context,
+                                "\nTerminal: {source} ({})\n{}",
+                                scope.label(),
+                                MarkdownCodeBlock {
+                                    tag: "console",
+                                    text: content
+                                }
+                            )
+                            .ok();
+                        }
                     }
 
                     language_model::MessageContent::Text(uri.as_link().to_string())
@@ -386,6 +404,13 @@ impl UserMessage {
                 .push(language_model::MessageContent::Text(diagnostics_context));
         }
 
+        if terminal_output_context.len() > OPEN_TERMINAL_OUTPUT_TAG.len() {
+            terminal_output_context.push_str("</terminal_output>\n");
+            message.content.push(language_model::MessageContent::Text(
+                terminal_output_context,
+            ));
+        }
+
         if message.content.len() > len_before_context {
             message.content.insert(
                 len_before_context,
@@ -699,6 +724,11 @@ impl ToolPermissionContext {
                     extract_url_pattern(value),
                     extract_url_pattern_display(value),
                 )
+            } else if tool_name == RemoteTerminalTool::NAME {
+                (
+                    extract_remote_terminal_pattern(value),
+                    extract_remote_terminal_pattern_display(value),
+                )
             } else {
                 (None, None)
             }
@@ -752,6 +782,8 @@ impl ToolPermissionContext {
             if let (Some(pattern), Some(display)) = (pattern, pattern_display) {
                 let button_text = if tool_name == TerminalTool::NAME {
                     format!("Always for `{}` commands", display)
+                } else if tool_name == RemoteTerminalTool::NAME {
+                    format!("Always for commands on `{}`", display)
                 } else {
                     format!("Always for `{}`", display)
                 };
@@ -1343,6 +1375,7 @@ impl Thread {
             TerminalTool::new(self.project.clone(), environment.clone()),
             allowed_tool_names.as_ref(),
         );
+        self.add_tool(RemoteTerminalTool::default(), allowed_tool_names.as_ref());
         self.add_tool(ThinkingTool, allowed_tool_names.as_ref());
         self.add_tool(WebSearchTool, allowed_tool_names.as_ref());
 
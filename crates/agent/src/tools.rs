@@ -12,6 +12,7 @@ mod move_path_tool;
 mod now_tool;
 mod open_tool;
 mod read_file_tool;
+mod remote_terminal_tool;
 mod restore_file_from_disk_tool;
 mod save_file_tool;
 mod streaming_edit_file_tool;
@@ -37,6 +38,7 @@ pub use move_path_tool::*;
 pub use now_tool::*;
 pub use open_tool::*;
 pub use read_file_tool::*;
+pub use remote_terminal_tool::*;
 pub use restore_file_from_disk_tool::*;
 pub use save_file_tool::*;
 pub use streaming_edit_file_tool::*;
@@ -126,6 +128,7 @@ tools! {
     NowTool,
     OpenTool,
     ReadFileTool,
+    RemoteTerminalTool,
     RestoreFileFromDiskTool,
     SaveFileTool,
     SubagentTool,
@@ -0,0 +1,570 @@
+use agent_client_protocol as acp;
+use agent_settings::AgentSettings;
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use futures::FutureExt as _;
+use gpui::{App, AppContext as _, AsyncApp, Entity, SharedString, Task};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::{cell::RefCell, sync::Arc, time::Duration};
+use terminal::{
+    ConnectionInfo, SessionConfig, SessionNode, SessionStoreEntity, Terminal,
+    remote_command::{
+        DEFAULT_PROMPT_REGEX, connect_to_session, is_at_prompt, wait_for_completion,
+        wait_until_at_prompt,
+    },
+    remote_terminals::OpenRemoteTerminals,
+};
+use uuid::Uuid;
+
+use crate::{
+    AgentTool, ToolCallEventStream, ToolPermissionDecision, decide_permission_from_settings,
+};
+
+const COMMAND_OUTPUT_LIMIT: usize = 16 * 1024;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a connection this tool opened stays open after its last command.
+const IDLE_CONNECTION_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Runs a command on a remote device over SSH or Telnet and returns the output it printed.
+///
+/// `session` is the name or tag of one of the user's saved sessions, or the host of a remote terminal the user already has open. An open terminal is reused, so state like the working directory or a configuration mode carries over between invocations. Saved sessions that aren't open are connected in the background and stay connected for a few minutes after the last invocation.
+///
+/// The command is typed at the device's prompt, so it must be valid there: network devices usually have their own CLI rather than a shell. It must be a single line without control characters; run several commands with separate invocations. An open terminal must be showing its prompt, not another program or a partly typed command. The tool returns once the prompt comes back, which it detects through shell integration (OSC 133) when the remote shell reports it, or otherwise by matching the last line against `prompt_regex`.
+///
+/// Do not run commands that never return to the prompt, such as `top`, `tail -f` or `ping` without a count. Turn off paging (e.g. `terminal length 0`) before commands with long output, since a pager waits for input.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RemoteTerminalToolInput {
+    /// The name or tag of a saved session, or the host of an open remote terminal.
+    pub session: String,
+    /// The command to type at the prompt, on a single line without control characters.
+    pub command: String,
+    /// Optional regex matching the device's prompt, used when the remote shell doesn't report when commands finish. Defaults to a line ending in `$`, `#`, `>` or `%`.
+    pub prompt_regex: Option<String>,
+    /// Optional maximum time (in milliseconds) to wait for the prompt to come back. Defaults to 30 seconds.
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Default)]
+pub struct RemoteTerminalTool {
+    /// Terminals this tool connected for sessions that weren't open, reused by later calls.
+    connections: RefCell<HashMap<Uuid, Connection>>,
+}
+
+/// A terminal this tool connected, which isn't shown to the user and so is closed once it has
+/// sat idle for [`IDLE_CONNECTION_TIMEOUT`].
+struct Connection {
+    terminal: Entity<Terminal>,
+    /// Dropping the terminal closes the connection. Unset while a command is running.
+    _close_when_idle: Option<Task<()>>,
+}
+
+impl RemoteTerminalTool {
+    fn resolve_target(&self, name: &str, cx: &App) -> Result<Target> {
+        let open_terminals = OpenRemoteTerminals::terminals(cx);
+        let connected = |terminal: &Entity<Terminal>| terminal.read(cx).is_connected();
+
+        let sessions = SessionStoreEntity::try_global(cx)
+            .map(|store| {
+                store
+                    .read(cx)
+                    .store()
+                    .find_sessions(name)
+                    .into_iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        match sessions.as_slice() {
+            [] => {}
+            [session] => {
+                let open = open_terminals
+                    .iter()
+                    .find(|terminal| {
+                        connected(terminal)
+                            && terminal
+                                .read(cx)
+                                .connection_info()
+                                .and_then(ConnectionInfo::session_id)
+                                == Some(session.id)
+                    })
+                    .cloned()
+                    .or_else(|| {
+                        self.connections
+                            .borrow()
+                            .get(&session.id)
+                            .map(|connection| connection.terminal.clone())
+                            .filter(|terminal| connected(terminal))
+                    });
+                return Ok(match open {
+                    Some(terminal) => Target::Open {
+                        terminal,
+                        label: session.name.clone(),
+                    },
+                    None => Target::Saved(session.clone()),
+                });
+            }
+            sessions => {
+                let names = sessions
+                    .iter()
+                    .map(|session| format!("\"{}\"", session.name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                anyhow::bail!(
+                    "\"{name}\" matches several saved sessions ({names}). Use one of their names instead."
+                );
+            }
+        }
+
+        // Terminals the user connected without saving a session, by host. Not by title,
+        // which the remote side sets and could make look like another session.
+        open_terminals
+            .into_iter()
+            .find(|terminal| {
+                let terminal = terminal.read(cx);
+                terminal.is_connected()
+                    && terminal
+                        .connection_info()
+                        .is_some_and(|info| info.host() == name)
+            })
+            .map(|terminal| {
+                let label = open_terminal_label(terminal.read(cx), cx);
+                Target::Open { terminal, label }
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "There is no saved session or open remote terminal named \"{name}\". \
+                     Ask the user which session to use."
+                )
+            })
+    }
+
+    async fn connect(
+        &self,
+        session: &SessionConfig,
+        cx: &mut AsyncApp,
+    ) -> Result<Entity<Terminal>> {
+        let builder = cx
+            .update(|cx| connect_to_session(session, 0, cx))
+            .await
+            .with_context(|| format!("connecting to \"{}\"", session.name))?;
+        let terminal = cx.new(|cx| builder.subscribe(cx));
+        self.connections.borrow_mut().insert(
+            session.id,
+            Connection {
+                terminal: terminal.clone(),
+                _close_when_idle: None,
+            },
+        );
+        Ok(terminal)
+    }
+
+    /// Keeps the connection this tool opened for `terminal`, if any, open while a command runs.
+    fn keep_open(&self, terminal: &Entity<Terminal>) {
+        if let Some(connection) = self
+            .connections
+            .borrow_mut()
+            .values_mut()
+            .find(|connection| &connection.terminal == terminal)
+        {
+            connection._close_when_idle = None;
+        }
+    }
+
+    /// Closes the connection this tool opened for `terminal`, if any, once it's been idle
+    /// for [`IDLE_CONNECTION_TIMEOUT`].
+    fn close_when_idle(self: &Arc<Self>, terminal: &Entity<Terminal>, cx: &mut AsyncApp) {
+        let mut connections = self.connections.borrow_mut();
+        let Some((&session_id, connection)) = connections
+            .iter_mut()
+            .find(|(_, connection)| &connection.terminal == terminal)
+        else {
+            return;
+        };
+        let this = Arc::downgrade(self);
+        let timer = cx.background_executor().timer(IDLE_CONNECTION_TIMEOUT);
+        connection._close_when_idle = Some(cx.spawn(async move |_| {
+            timer.await;
+            if let Some(this) = this.upgrade() {
+                this.connections.borrow_mut().remove(&session_id);
+            }
+        }));
+    }
+}
+
+/// How an open terminal is named in permission rules: after the saved session it was
+/// opened from, or else its host. Never after its title, which the remote side sets.
+fn open_terminal_label(terminal: &Terminal, cx: &App) -> String {
+    let Some(info) = terminal.connection_info() else {
+        return String::new();
+    };
+    info.session_id()
+        .and_then(|id| {
+            let store = SessionStoreEntity::try_global(cx)?;
+            match store.read(cx).store().find_node(id)? {
+                SessionNode::Session(session) => Some(session.name.clone()),
+                SessionNode::Group(_) => None,
+            }
+        })
+        .unwrap_or_else(|| info.host().to_string())
+}
+
+enum Target {
+    Open {
+        terminal: Entity<Terminal>,
+        label: String,
+    },
+    Saved(SessionConfig),
+}
+
+impl Target {
+    fn label(&self) -> &str {
+        match self {
+            Target::Open { label, .. } => label,
+            Target::Saved(session) => &session.name,
+        }
+    }
+}
+
+impl AgentTool for RemoteTerminalTool {
+    type Input = RemoteTerminalToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "remote_terminal";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Execute
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("{}: {}", input.session, input.command).into()
+        } else {
+            "Run command on remote device".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        // Every line typed is a command of its own, which the permission rules would only
+        // see as part of the first one. Other control characters reach the device as keys
+        // such as Ctrl-C or Escape, which the rules can't see at all.
+        if input.command.chars().any(|c| c.is_ascii_control()) {
+            return Task::ready(Err(anyhow!(
+                "The command must be a single line without control characters. \
+                 Run each line with a separate invocation."
+            )));
+        }
+        let prompt = match Regex::new(
+            input
+                .prompt_regex
+                .as_deref()
+                .unwrap_or(DEFAULT_PROMPT_REGEX),
+        ) {
+            Ok(prompt) => prompt,
+            Err(error) => return Task::ready(Err(anyhow!("Invalid prompt_regex: {error}"))),
+        };
+        let target = match self.resolve_target(&input.session, cx) {
+            Ok(target) => target,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        // Rules match `<session name>: <command>`, so they can allow or deny commands
+        // per session, e.g. `^lab-switch: ` or `^core-router: (reload|erase)\b`.
+        let permission_input = format!("{}: {}", target.label(), input.command);
+        let settings = AgentSettings::get_global(cx);
+        let decision = decide_permission_from_settings(
+            Self::NAME,
+            std::slice::from_ref(&permission_input),
+            settings,
+        );
+
+        let authorize = match decision {
+            ToolPermissionDecision::Allow => None,
+            ToolPermissionDecision::Deny(reason) => {
+                return Task::ready(Err(anyhow!("{}", reason)));
+            }
+            ToolPermissionDecision::Confirm => {
+                let context = crate::ToolPermissionContext {
+                    tool_name: Self::NAME.to_string(),
+                    input_values: vec![permission_input.clone()],
+                };
+                Some(event_stream.authorize(permission_input, context, cx))
+            }
+        };
+        cx.spawn(async move |cx| {
+            if let Some(authorize) = authorize {
+                authorize.await?;
+            }
+
+            let timeout = input
+                .timeout_ms
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_TIMEOUT);
+            let terminal = match target {
+                Target::Open { terminal, label } => {
+                    self.keep_open(&terminal);
+                    // Whatever is on screen would receive the keystrokes otherwise.
+                    let at_prompt = terminal.read_with(cx, |terminal, _| {
+                        is_at_prompt(terminal, &prompt)
+                    });
+                    if !at_prompt {
+                        self.close_when_idle(&terminal, cx);
+                        anyhow::bail!(
+                            "\"{label}\" isn't showing a prompt matching `{}`. Another program \
+                             or a partly typed command may be on screen; ask the user to return \
+                             the terminal to its prompt.",
+                            prompt.as_str(),
+                        );
+                    }
+                    terminal
+                }
+                Target::Saved(session) => {
+                    let terminal = self.connect(&session, cx).await?;
+                    // Let the login banner and the first prompt arrive before typing.
+                    let at_prompt =
+                        wait_until_at_prompt(terminal.clone(), prompt.clone(), cx.clone());
+                    let error = futures::select! {
+                        _ = at_prompt.fuse() => None,
+                        _ = cx.background_executor().timer(timeout).fuse() => Some(anyhow!(
+                            "Connected to \"{}\", but no prompt matching `{}` appeared within {} ms.",
+                            session.name,
+                            prompt.as_str(),
+                            timeout.as_millis(),
+                        )),
+                        _ = event_stream.cancelled_by_user().fuse() => Some(anyhow!(
+                            "The user stopped the command before it was sent."
+                        )),
+                    };
+                    if let Some(error) = error {
+                        self.close_when_idle(&terminal, cx);
+                        return Err(error);
+                    }
+                    terminal
+                }
+            };
+
+            let sent_line = terminal.update(cx, |terminal, _| {
+                let line = terminal.cursor_line();
+                terminal.input(format!("{}\r", input.command).into_bytes());
+                line
+            });
+
+            let finished =
+                wait_for_completion(terminal.clone(), sent_line, prompt.clone(), cx.clone());
+            let mut timed_out = false;
+            let mut user_stopped = false;
+            let completion = futures::select! {
+                completion = finished.fuse() => Some(completion),
+                _ = cx.background_executor().timer(timeout).fuse() => {
+                    timed_out = true;
+                    None
+                }
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    user_stopped = true;
+                    None
+                }
+            };
+
+            self.close_when_idle(&terminal, cx);
+
+            let output = terminal.read_with(cx, |terminal, _| {
+                let last_line = completion
+                    .as_ref()
+                    .map_or(terminal.cursor_line(), |completion| completion.last_output_line);
+                terminal.text_in_lines(sent_line + 1..=last_line)
+            });
+            Ok(process_content(
+                &output,
+                &input.command,
+                completion.and_then(|completion| completion.exit_code),
+                timed_out,
+                user_stopped || event_stream.was_cancelled_by_user(),
+            ))
+        })
+    }
+}
+
+fn process_content(
+    output: &str,
+    command: &str,
+    exit_code: Option<i32>,
+    timed_out: bool,
+    user_stopped: bool,
+) -> String {
+    let output = output.trim();
+    let is_empty = output.is_empty();
+    let content = if output.len() > COMMAND_OUTPUT_LIMIT {
+        let end = output.floor_char_boundary(COMMAND_OUTPUT_LIMIT);
+        format!(
+            "Command output too long. The first {end} bytes:\n\n```\n{}\n```",
+            &output[..end]
+        )
+    } else {
+        format!("```\n{output}\n```")
+    };
+
+    if user_stopped {
+        if is_empty {
+            "The user stopped waiting for this command. No output was captured.\n\n\
+            Ask them what they would like to do next rather than retrying."
+                .to_string()
+        } else {
+            format!(
+                "The user stopped waiting for this command. Output captured so far:\n\n{content}\n\n\
+                Ask them what they would like to do next rather than retrying."
+            )
+        }
+    } else if timed_out {
+        let note = "The command may still be running on the device; later commands are typed \
+                    into the same terminal.";
+        if is_empty {
+            format!(
+                "The prompt did not come back after \"{command}\". No output was captured. {note}"
+            )
+        } else {
+            format!(
+                "The prompt did not come back after \"{command}\". Output captured so far:\n\n{content}\n\n{note}"
+            )
+        }
+    } else {
+        match exit_code {
+            Some(0) | None if is_empty => "Command finished with no output.".to_string(),
+            Some(0) | None => content,
+            Some(exit_code) if is_empty => {
+                format!("Command \"{command}\" failed with exit code {exit_code}.")
+            }
+            Some(exit_code) => {
+                format!("Command \"{command}\" failed with exit code {exit_code}.\n\n{content}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use settings::SettingsStore;
+    use terminal::{SessionStore, SshSessionConfig};
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+
+            let mut session =
+                SessionConfig::new_ssh("core-router", SshSessionConfig::new("10.0.0.1", 22));
+            session.tags.push("routers".to_string());
+            let mut store = SessionStore::new();
+            store.add_node(SessionNode::Session(session), None);
+            SessionStoreEntity::init_test(store, cx);
+
+            let mut settings = AgentSettings::get_global(cx).clone();
+            settings.tool_permissions.tools.insert(
+                RemoteTerminalTool::NAME.into(),
+                agent_settings::ToolRules {
+                    default: Some(settings::ToolPermissionMode::Allow),
+                    always_allow: vec![],
+                    always_deny: vec![
+                        agent_settings::CompiledRegex::new(r"^core-router: reload\b", false)
+                            .unwrap(),
+                    ],
+                    always_confirm: vec![],
+                    invalid_patterns: vec![],
+                },
+            );
+            AgentSettings::override_global(settings, cx);
+        });
+    }
+
+    async fn run(session: &str, command: &str, cx: &mut TestAppContext) -> Result<String> {
+        let tool = Arc::new(RemoteTerminalTool::default());
+        let (event_stream, _rx) = ToolCallEventStream::test();
+        cx.update(|cx| {
+            tool.run(
+                RemoteTerminalToolInput {
+                    session: session.to_string(),
+                    command: command.to_string(),
+                    prompt_regex: None,
+                    timeout_ms: None,
+                },
+                event_stream,
+                cx,
+            )
+        })
+        .await
+    }
+
+    #[gpui::test]
+    async fn test_permission_rules_match_the_resolved_session(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        // However the session is referred to, rules see its saved name.
+        for session in ["core-router", "Core-Router", "routers"] {
+            let error = run(session, "reload", cx).await.unwrap_err().to_string();
+            assert!(
+                error.to_lowercase().contains("blocked"),
+                "{session}: {error}"
+            );
+        }
+
+        // A denied command can't ride along on another line.
+        let error = run("core-router", "show version\rreload", cx)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("single line"), "{error}");
+        let error = run("core-router", "show version\nreload", cx)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("single line"), "{error}");
+        // Nor can keys such as Ctrl-C, Ctrl-Z or Escape be typed.
+        for command in [
+            "show version\x03",
+            "\x1areload",
+            "show \x1b[A",
+            "\x04",
+            "show\x7f",
+        ] {
+            let error = run("core-router", command, cx)
+                .await
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains("control characters"), "{command:?}: {error}");
+        }
+
+        let error = run("edge-router", "show version", cx)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("no saved session"), "{error}");
+    }
+
+    #[test]
+    fn test_process_content_reports_exit_code_and_timeout() {
+        assert_eq!(
+            process_content("No such file\n", "cat x", Some(2), false, false),
+            "Command \"cat x\" failed with exit code 2.\n\n```\nNo such file\n```"
+        );
+        assert_eq!(
+            process_content("", "write memory", None, false, false),
+            "Command finished with no output."
+        );
+        assert!(
+            process_content("partial", "ping 10.0.0.1", None, true, false)
+                .starts_with("The prompt did not come back after \"ping 10.0.0.1\"")
+        );
+    }
+}
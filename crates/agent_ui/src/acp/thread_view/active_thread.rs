@@ -7430,7 +7430,7 @@ pub(crate) fn open_link(
                 cx.open_url(url.as_str());
             }
             MentionUri::Diagnostics { .. } => {}
-            MentionUri::TerminalSelection { .. } => {}
+            MentionUri::TerminalSelection { .. } | MentionUri::TerminalOutput { .. } => {}
         })
     } else {
         cx.open_url(&url);
@@ -5,7 +5,7 @@ use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
 use crate::acp::AcpThreadHistory;
-use acp_thread::{AgentSessionInfo, MentionUri};
+use acp_thread::{AgentSessionInfo, MentionUri, TerminalOutputScope};
 use anyhow::Result;
 use editor::{
     CompletionProvider, Editor, ExcerptId, code_context_menus::COMPLETION_MENU_MAX_WIDTH,
@@ -26,6 +26,8 @@ use prompt_store::{PromptStore, UserPromptId};
 use rope::Point;
 use settings::{Settings, TerminalDockPosition};
 use terminal::terminal_settings::TerminalSettings;
+use terminal::{SessionStoreEntity, Terminal};
+use terminal_view::TerminalView;
 use terminal_view::terminal_panel::TerminalPanel;
 use text::{Anchor, ToOffset as _, ToPoint as _};
 use ui::IconName;
@@ -63,6 +65,7 @@ pub(crate) enum PromptContextType {
     Thread,
     Rules,
     Diagnostics,
+    Terminal,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -101,6 +104,7 @@ impl TryFrom<&str> for PromptContextType {
             "thread" => Ok(Self::Thread),
             "rule" => Ok(Self::Rules),
             "diagnostics" => Ok(Self::Diagnostics),
+            "terminal" => Ok(Self::Terminal),
             _ => Err(format!("Invalid context picker mode: {}", value)),
         }
     }
@@ -115,6 +119,7 @@ impl PromptContextType {
             Self::Thread => "thread",
             Self::Rules => "rule",
             Self::Diagnostics => "diagnostics",
+            Self::Terminal => "terminal",
         }
     }
 
@@ -126,6 +131,7 @@ impl PromptContextType {
             Self::Thread => "Threads",
             Self::Rules => "Rules",
             Self::Diagnostics => "Diagnostics",
+            Self::Terminal => "Terminals",
         }
     }
 
@@ -137,6 +143,7 @@ impl PromptContextType {
             Self::Thread => IconName::Thread,
             Self::Rules => IconName::Reader,
             Self::Diagnostics => IconName::Warning,
+            Self::Terminal => IconName::Terminal,
         }
     }
 }
@@ -774,6 +781,118 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
         }
     }
 
+    fn completions_for_terminals(
+        query: &str,
+        source_range: Range<Anchor>,
+        editor: WeakEntity<Editor>,
+        mention_set: WeakEntity<MentionSet>,
+        workspace: &Entity<Workspace>,
+        cx: &mut App,
+    ) -> Vec<Completion> {
+        let (query, line_count) = parse_terminal_query(query);
+        let query = query.to_lowercase();
+        let mut completions = Vec::new();
+        for terminal_view in open_terminal_views(workspace, cx) {
+            let terminal = terminal_view.read(cx).terminal().clone();
+            let (name, host) = terminal_name_and_host(terminal.read(cx), cx);
+            let matches_query = name.to_lowercase().contains(&query)
+                || host
+                    .as_ref()
+                    .is_some_and(|host| host.to_lowercase().contains(&query));
+            if !matches_query {
+                continue;
+            }
+
+            let mut scopes = vec![TerminalOutputScope::LastLines(line_count)];
+            if terminal
+                .read(cx)
+                .last_content
+                .selection_text
+                .as_ref()
+                .is_some_and(|text| !text.is_empty())
+            {
+                scopes.push(TerminalOutputScope::Selection);
+            }
+            if terminal.read(cx).last_command_text().is_some() {
+                scopes.push(TerminalOutputScope::LastCommand);
+            }
+
+            for scope in scopes {
+                completions.push(Self::completion_for_terminal_output(
+                    terminal.downgrade(),
+                    name.clone(),
+                    host.clone(),
+                    scope,
+                    source_range.clone(),
+                    editor.clone(),
+                    mention_set.clone(),
+                    cx,
+                ));
+            }
+        }
+        completions
+    }
+
+    fn completion_for_terminal_output(
+        terminal: WeakEntity<Terminal>,
+        name: String,
+        host: Option<String>,
+        scope: TerminalOutputScope,
+        source_range: Range<Anchor>,
+        editor: WeakEntity<Editor>,
+        mention_set: WeakEntity<MentionSet>,
+        cx: &mut App,
+    ) -> Completion {
+        let uri = MentionUri::TerminalOutput { name, host, scope };
+        let label = build_code_label_for_terminal(&uri, cx);
+        let icon_path = uri.icon_path(cx);
+        let crease_text: SharedString = uri.name().into();
+        let new_text = format!("{} ", uri.as_link());
+        let content_len = new_text.len() - 1;
+        let start = source_range.start;
+        Completion {
+            replace_range: source_range,
+            new_text,
+            label,
+            documentation: None,
+            source: project::CompletionSource::Custom,
+            icon_path: Some(icon_path),
+            match_start: None,
+            snippet_deduplication_key: None,
+            insert_text_mode: None,
+            confirm: Some(Arc::new(move |_, window, cx| {
+                let terminal = terminal.clone();
+                let uri = uri.clone();
+                let editor = editor.clone();
+                let mention_set = mention_set.clone();
+                let crease_text = crease_text.clone();
+                window.defer(cx, move |window, cx| {
+                    let (Some(editor), Some(terminal)) = (editor.upgrade(), terminal.upgrade())
+                    else {
+                        return;
+                    };
+                    // Read the output now rather than when the menu opened, so it's current.
+                    let content = terminal_output(terminal.read(cx), scope);
+                    mention_set
+                        .update(cx, |mention_set, cx| {
+                            mention_set.confirm_mention_with_content(
+                                crease_text,
+                                start,
+                                content_len,
+                                uri,
+                                content,
+                                editor,
+                                window,
+                                cx,
+                            )
+                        })
+                        .ok();
+                });
+                false
+            })),
+        }
+    }
+
     fn search_slash_commands(&self, query: String, cx: &mut App) -> Task<Vec<AvailableCommand>> {
         let commands = self.source.available_commands(cx);
         if commands.is_empty() {
@@ -875,7 +994,9 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
                 }
             }
 
-            Some(PromptContextType::Diagnostics) => Task::ready(Vec::new()),
+            Some(PromptContextType::Diagnostics) | Some(PromptContextType::Terminal) => {
+                Task::ready(Vec::new())
+            }
 
             None if query.is_empty() => {
                 let recent_task = self.recent_context_picker_entries(&workspace, cx);
@@ -1089,6 +1210,14 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
             }
         }
 
+        if self
+            .source
+            .supports_context(PromptContextType::Terminal, cx)
+            && !open_terminal_views(workspace, cx).is_empty()
+        {
+            entries.push(PromptContextEntry::Mode(PromptContextType::Terminal));
+        }
+
         entries
     }
 }
@@ -1193,6 +1322,23 @@ impl<T: PromptCompletionProviderDelegate> CompletionProvider for PromptCompletio
                 })
             }
             PromptCompletion::Mention(MentionCompletion { mode, argument, .. }) => {
+                if let Some(PromptContextType::Terminal) = mode {
+                    let completions = Self::completions_for_terminals(
+                        argument.as_deref().unwrap_or_default(),
+                        source_range.clone(),
+                        editor.clone(),
+                        mention_set.clone(),
+                        &workspace,
+                        cx,
+                    );
+                    return Task::ready(Ok(vec![CompletionResponse {
+                        completions,
+                        display_options: CompletionDisplayOptions::default(),
+                        // Filtered by the argument here, so query again as it changes.
+                        is_incomplete: true,
+                    }]));
+                }
+
                 if let Some(PromptContextType::Diagnostics) = mode {
                     if argument.is_some() {
                         return Task::ready(Ok(Vec::new()));
@@ -2006,6 +2152,92 @@ fn build_code_label_for_path(
     label.build()
 }
 
+/// How many lines a terminal output mention includes by default.
+const TERMINAL_OUTPUT_LINES: u32 = 100;
+
+/// Splits the argument of a terminal mention into the query for the terminal and
+/// how many lines of its output to include, which can be given as a `:N` suffix,
+/// e.g. `@terminal build:500`.
+fn parse_terminal_query(argument: &str) -> (&str, u32) {
+    if let Some((query, line_count)) = argument.rsplit_once(':')
+        && let Ok(line_count) = line_count.parse::<u32>()
+        && line_count > 0
+    {
+        return (query, line_count);
+    }
+    (argument, TERMINAL_OUTPUT_LINES)
+}
+
+/// Returns the terminal views in the center panes and the terminal panel.
+fn open_terminal_views(workspace: &Entity<Workspace>, cx: &App) -> Vec<Entity<TerminalView>> {
+    let workspace = workspace.read(cx);
+    let mut views = workspace
+        .items_of_type::<TerminalView>(cx)
+        .collect::<Vec<_>>();
+    if let Some(panel) = workspace.panel::<TerminalPanel>(cx) {
+        for view in panel.read(cx).terminal_views(cx) {
+            if !views.contains(&view) {
+                views.push(view);
+            }
+        }
+    }
+    views
+}
+
+/// The name to show for a terminal, preferring the saved session it was opened
+/// from, and the host it's connected to, if any.
+fn terminal_name_and_host(terminal: &Terminal, cx: &App) -> (String, Option<String>) {
+    let Some(connection_info) = terminal.connection_info() else {
+        return (terminal.title(true), None);
+    };
+    let session_name = connection_info.session_id().and_then(|session_id| {
+        let store = SessionStoreEntity::try_global(cx)?;
+        let name = store
+            .read(cx)
+            .store()
+            .find_node(session_id)?
+            .name()
+            .to_string();
+        Some(name)
+    });
+    (
+        session_name.unwrap_or_else(|| terminal.title(true)),
+        Some(connection_info.host().to_string()),
+    )
+}
+
+/// The text of a terminal output mention. Terminal text is read from the grid, so
+/// it has no ANSI escape sequences.
+fn terminal_output(terminal: &Terminal, scope: TerminalOutputScope) -> String {
+    match scope {
+        TerminalOutputScope::LastLines(count) => terminal.last_lines_text(count),
+        TerminalOutputScope::Selection => terminal
+            .last_content
+            .selection_text
+            .clone()
+            .unwrap_or_default(),
+        TerminalOutputScope::LastCommand => terminal.last_command_text().unwrap_or_default(),
+    }
+}
+
+fn build_code_label_for_terminal(uri: &MentionUri, cx: &App) -> CodeLabel {
+    let variable_highlight_id = cx
+        .theme()
+        .syntax()
+        .highlight_id("variable")
+        .map(HighlightId);
+    let mut label = CodeLabelBuilder::default();
+    label.push_str(&uri.name(), None);
+    if let MentionUri::TerminalOutput {
+        host: Some(host), ..
+    } = uri
+    {
+        label.push_str(" ", None);
+        label.push_str(host, variable_highlight_id);
+    }
+    label.build()
+}
+
 /// Returns terminal selections from all terminal views if the terminal panel is open.
 fn terminal_selections_if_panel_open(workspace: &Entity<Workspace>, cx: &App) -> Vec<String> {
     let Some(panel) = workspace.read(cx).panel::<TerminalPanel>(cx) else {
@@ -2167,6 +2399,25 @@ mod tests {
         assert_eq!(SlashCommandCompletion::try_parse("/ ", 0), None);
     }
 
+    #[test]
+    fn test_terminal_query_parse() {
+        assert_eq!(parse_terminal_query(""), ("", TERMINAL_OUTPUT_LINES));
+        assert_eq!(
+            parse_terminal_query("build"),
+            ("build", TERMINAL_OUTPUT_LINES)
+        );
+        assert_eq!(parse_terminal_query("build:500"), ("build", 500));
+        assert_eq!(parse_terminal_query(":25"), ("", 25));
+        assert_eq!(
+            parse_terminal_query("build:0"),
+            ("build:0", TERMINAL_OUTPUT_LINES)
+        );
+        assert_eq!(
+            parse_terminal_query("build:"),
+            ("build:", TERMINAL_OUTPUT_LINES)
+        );
+    }
+
     #[test]
     fn test_mention_completion_parse() {
         let supported_modes = vec![PromptContextType::File, PromptContextType::Symbol];
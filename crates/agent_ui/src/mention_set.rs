@@ -149,7 +149,8 @@ impl MentionSet {
             } => self.confirm_mention_for_diagnostics(include_errors, include_warnings, cx),
             MentionUri::PastedImage
             | MentionUri::Selection { .. }
-            | MentionUri::TerminalSelection { .. } => {
+            | MentionUri::TerminalSelection { .. }
+            | MentionUri::TerminalOutput { .. } => {
                 Task::ready(Err(anyhow!("Unsupported mention URI type for paste")))
             }
         }
@@ -286,7 +287,7 @@ impl MentionSet {
                 debug_panic!("unexpected selection URI");
                 Task::ready(Err(anyhow!("unexpected selection URI")))
             }
-            MentionUri::TerminalSelection { .. } => {
+            MentionUri::TerminalSelection { .. } | MentionUri::TerminalOutput { .. } => {
                 debug_panic!("unexpected terminal URI");
                 Task::ready(Err(anyhow!("unexpected terminal URI")))
             }
@@ -313,6 +314,47 @@ impl MentionSet {
         })
     }
 
+    /// Like [`Self::confirm_mention_completion`], for mentions whose content was read
+    /// when the completion was accepted, such as terminal output.
+    pub fn confirm_mention_with_content(
+        &mut self,
+        crease_text: SharedString,
+        start: text::Anchor,
+        content_len: usize,
+        mention_uri: MentionUri,
+        content: String,
+        editor: Entity<Editor>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = editor.update(cx, |editor, cx| editor.snapshot(window, cx));
+        let Some(start_anchor) = snapshot.buffer_snapshot().as_singleton_anchor(start) else {
+            return;
+        };
+        let Some((crease_id, _tx)) = insert_crease_for_mention(
+            start_anchor.excerpt_id,
+            start,
+            content_len,
+            crease_text,
+            mention_uri.icon_path(cx),
+            None,
+            editor,
+            window,
+            cx,
+        ) else {
+            return;
+        };
+        self.insert_mention(
+            crease_id,
+            mention_uri,
+            Task::ready(Ok(Mention::Text {
+                content,
+                tracked_buffers: Vec::new(),
+            }))
+            .shared(),
+        );
+    }
+
     pub fn confirm_mention_for_file(
         &self,
         abs_path: PathBuf,
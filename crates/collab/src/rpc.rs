@@ -464,6 +464,15 @@ impl Server {
             .add_request_handler(forward_mutating_project_request::<proto::GetBlobContent>)
             .add_request_handler(forward_mutating_project_request::<proto::GitCreateBranch>)
             .add_request_handler(forward_mutating_project_request::<proto::GitChangeBranch>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitCherryPick>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitCherryPickRange>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitBisectStart>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitBisectMark>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitBisectRun>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitBisectReset>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitRevert>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitCreateTag>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitDeleteTag>)
             .add_request_handler(forward_mutating_project_request::<proto::GitCreateRemote>)
             .add_request_handler(forward_mutating_project_request::<proto::GitRemoveRemote>)
             .add_request_handler(forward_mutating_project_request::<proto::CheckForPushedCommits>)
@@ -471,6 +480,10 @@ impl Server {
             .add_message_handler(update_context)
             .add_request_handler(forward_mutating_project_request::<proto::ToggleLspLogs>)
             .add_message_handler(broadcast_project_message_from_host::<proto::LanguageServerLog>)
+            .add_message_handler(broadcast_project_message_from_host::<proto::UpdateSharedTerminal>)
+            .add_message_handler(broadcast_project_message_from_host::<proto::UnshareTerminal>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetSharedTerminals>)
+            .add_request_handler(forward_mutating_project_request::<proto::SharedTerminalInput>)
             .add_request_handler(share_agent_thread)
             .add_request_handler(get_shared_agent_thread)
             .add_request_handler(forward_project_search_chunk);
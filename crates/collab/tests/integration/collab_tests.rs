@@ -16,6 +16,7 @@ mod random_channel_buffer_tests;
 mod random_project_collaboration_tests;
 mod randomized_test_helpers;
 mod remote_editing_collaboration_tests;
+mod shared_terminal_tests;
 mod test_server;
 
 pub use randomized_test_helpers::{
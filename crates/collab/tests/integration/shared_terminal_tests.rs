@@ -0,0 +1,140 @@
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use call::ActiveCall;
+use gpui::{AppContext as _, BackgroundExecutor, Entity, TestAppContext};
+use rpc::proto;
+use serde_json::json;
+use terminal::{
+    Terminal, TerminalBuilder,
+    terminal_settings::{AlternateScroll, CursorShape},
+};
+use terminal_view::shared_terminals::{self, SharedTerminals, SharedTerminalsEvent};
+use util::{path, paths::PathStyle};
+
+use crate::TestServer;
+
+#[gpui::test]
+async fn test_sharing_terminals(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    shared_terminals::init(client_a.client());
+    shared_terminals::init(client_b.client());
+
+    client_a
+        .fs()
+        .insert_tree(path!("/a"), json!({ "a.txt": "a" }))
+        .await;
+    let (project_a, _) = client_a.build_local_project(path!("/a"), cx_a).await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+
+    let shared_terminals_a = cx_a.new(|cx| SharedTerminals::new(project_a.clone(), cx));
+    let shared_terminals_b = cx_b.new(|cx| SharedTerminals::new(project_b.clone(), cx));
+    let shown_terminals_b = Rc::new(RefCell::new(Vec::<Entity<Terminal>>::new()));
+    let _subscription = cx_b.update(|cx| {
+        let shown_terminals_b = shown_terminals_b.clone();
+        cx.subscribe(&shared_terminals_b, move |_, event, _| match event {
+            SharedTerminalsEvent::TerminalShared(terminal) => {
+                shown_terminals_b.borrow_mut().push(terminal.clone())
+            }
+            SharedTerminalsEvent::TerminalUnshared(terminal) => shown_terminals_b
+                .borrow_mut()
+                .retain(|shown| shown != terminal),
+        })
+    });
+
+    let terminal_a = cx_a.new(|cx| {
+        let mut terminal = TerminalBuilder::new_display_only(
+            CursorShape::default(),
+            AlternateScroll::On,
+            None,
+            0,
+            cx.background_executor(),
+            PathStyle::local(),
+        )
+        .unwrap()
+        .subscribe(cx);
+        terminal.set_title_override(Some("build".into()), cx);
+        terminal.write_output(b"$ make\n", cx);
+        terminal
+    });
+    let terminal_id = terminal_a.entity_id().as_u64();
+
+    // Sharing a terminal sends guests a snapshot of its screen, which is read-only at first.
+    shared_terminals_a
+        .update(cx_a, |shared_terminals, cx| {
+            shared_terminals.toggle_sharing(&terminal_a, cx)
+        })
+        .unwrap();
+    executor.run_until_parked();
+    let terminal_b = shown_terminals_b
+        .borrow()
+        .first()
+        .cloned()
+        .expect("guest should show the shared terminal");
+    assert_eq!(
+        terminal_b.read_with(cx_b, |terminal, _| terminal.shared_screen()),
+        terminal_a.read_with(cx_a, |terminal, _| terminal.shared_screen())
+    );
+    assert_eq!(
+        terminal_b.read_with(cx_b, |terminal, _| terminal.title(false)),
+        "build (read-only)"
+    );
+
+    // Later output is sent as the lines that changed.
+    terminal_a.update(cx_a, |terminal, cx| terminal.write_output(b"done\n", cx));
+    executor.advance_clock(Duration::from_millis(100));
+    executor.run_until_parked();
+    assert_eq!(
+        terminal_b.read_with(cx_b, |terminal, _| terminal.shared_screen()),
+        terminal_a.read_with(cx_a, |terminal, _| terminal.shared_screen())
+    );
+
+    // Guests can't type into a read-only terminal.
+    let input = proto::SharedTerminalInput {
+        project_id,
+        terminal_id,
+        input: b"ls\r".to_vec(),
+    };
+    client_b.client().request(input.clone()).await.unwrap_err();
+
+    shared_terminals_a.update(cx_a, |shared_terminals, cx| {
+        shared_terminals.toggle_writable(&terminal_a, cx)
+    });
+    executor.run_until_parked();
+    assert_eq!(
+        terminal_b.read_with(cx_b, |terminal, _| terminal.title(false)),
+        "build"
+    );
+    client_b.client().request(input).await.unwrap();
+
+    // Unsharing the terminal removes it from the guest's panel.
+    shared_terminals_a
+        .update(cx_a, |shared_terminals, cx| {
+            shared_terminals.toggle_sharing(&terminal_a, cx)
+        })
+        .unwrap();
+    executor.run_until_parked();
+    assert!(shown_terminals_b.borrow().is_empty());
+    client_b
+        .client()
+        .request(proto::SharedTerminalInput {
+            project_id,
+            terminal_id,
+            input: b"ls\r".to_vec(),
+        })
+        .await
+        .unwrap_err();
+}
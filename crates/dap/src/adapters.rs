@@ -395,6 +395,12 @@ pub trait DebugAdapter: 'static + Send + Sync {
     fn prefer_thread_name(&self) -> bool {
         false
     }
+
+    /// Whether the adapter can open the core dump named by `coreFile` instead of running the
+    /// program.
+    fn supports_core_dumps(&self) -> bool {
+        false
+    }
 }
 
 #[cfg(any(test, feature = "test-support"))]
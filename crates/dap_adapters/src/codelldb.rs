@@ -10,7 +10,10 @@ use serde_json::Value;
 use task::{DebugRequest, DebugScenario, ZedDebugConfig};
 use util::fs::remove_matching;
 
-use crate::*;
+use crate::{
+    ssh_host::{self, SshHost},
+    *,
+};
 
 #[derive(Default)]
 pub(crate) struct CodeLldbDebugAdapter {
@@ -133,8 +136,12 @@ impl DebugAdapter for CodeLldbDebugAdapter {
         })
     }
 
+    fn supports_core_dumps(&self) -> bool {
+        true
+    }
+
     fn dap_schema(&self) -> serde_json::Value {
-        json!({
+        let mut schema = json!({
             "properties": {
                 "request": {
                     "type": "string",
@@ -249,6 +256,10 @@ impl DebugAdapter for CodeLldbDebugAdapter {
                         }
                     }
                 },
+                "coreFile": {
+                    "type": "string",
+                    "description": "A core dump of `program` to inspect instead of running it. With `sshHost`, the core dump is fetched from that host."
+                },
                 "relativePathBase": {
                     "type": "string",
                     "description": "Base directory used for resolution of relative source paths. Defaults to the workspace folder"
@@ -321,7 +332,9 @@ impl DebugAdapter for CodeLldbDebugAdapter {
                     }
                 }
             ]
-        })
+        });
+        ssh_host::add_schema_properties(schema.get_mut("properties"));
+        schema
     }
 
     async fn get_binary(
@@ -331,7 +344,7 @@ impl DebugAdapter for CodeLldbDebugAdapter {
         user_installed_path: Option<PathBuf>,
         user_args: Option<Vec<String>>,
         user_env: Option<HashMap<String, String>>,
-        _: &mut AsyncApp,
+        cx: &mut AsyncApp,
     ) -> Result<DebugAdapterBinary> {
         let mut command = user_installed_path
             .map(|p| p.to_string_lossy().into_owned())
@@ -395,6 +408,87 @@ impl DebugAdapter for CodeLldbDebugAdapter {
             }
         }
 
+        let remote_host = SshHost::from_config(&config.config);
+        // A core dump is opened in place of running the program, and can only be inspected.
+        if let Some(core_file) = config.config.get("coreFile").and_then(Value::as_str) {
+            let core_file = match &remote_host {
+                Some(host) => host
+                    .fetch_file(core_file, delegate, cx)
+                    .await?
+                    .to_string_lossy()
+                    .into_owned(),
+                None => core_file.to_string(),
+            };
+            if let Some(config_obj) = json_config.as_object_mut() {
+                open_core_dump(config_obj, &core_file)?;
+            }
+        } else if let Some(host) = &remote_host {
+            // On an SSH host, the program runs under lldb-server there, and CodeLLDB connects
+            // to it as a remote target.
+            let server_path = host.server_path("lldb-server");
+            let target_args = if let Some(pid) = config.config.get("pid") {
+                let pid = match pid {
+                    Value::String(pid) => pid.clone(),
+                    pid => pid.to_string(),
+                };
+                vec!["--attach".to_string(), pid]
+            } else {
+                let mut target_args = vec![
+                    "--".to_string(),
+                    host.remote_program(&config.config)
+                        .context("No program to run on the SSH host")?,
+                ];
+                target_args.extend(ssh_host::program_args(&config.config));
+                target_args
+            };
+            let local_port = host
+                .start_server(
+                    move |address| {
+                        let mut server_command =
+                            vec![server_path, "gdbserver".to_string(), address];
+                        server_command.extend(target_args);
+                        server_command
+                    },
+                    delegate,
+                    cx,
+                )
+                .await?;
+
+            if let Some(config_obj) = json_config.as_object_mut() {
+                for key in ["pid", "args", "env"] {
+                    config_obj.remove(key);
+                }
+                // The local program is only used for its symbols.
+                if let Some(program) = config_obj.remove("program")
+                    && let Some(program) = program.as_str()
+                {
+                    config_obj.insert(
+                        "targetCreateCommands".into(),
+                        json!([format!("target create {program:?}")]),
+                    );
+                }
+                config_obj.insert("request".into(), "launch".into());
+                config_obj.insert(
+                    "processCreateCommands".into(),
+                    json!([format!("gdb-remote 127.0.0.1:{local_port}")]),
+                );
+            }
+        }
+        if let Some(host) = &remote_host
+            && let Some(source_map) = json_config.as_object_mut().and_then(|config_obj| {
+                config_obj
+                    .entry("sourceMap")
+                    .or_insert_with(|| json!({}))
+                    .as_object_mut()
+            })
+        {
+            for (remote, local) in &host.path_map {
+                source_map
+                    .entry(remote.clone())
+                    .or_insert_with(|| local.clone().into());
+            }
+        }
+
         Ok(DebugAdapterBinary {
             command: Some(command.unwrap()),
             cwd: Some(delegate.worktree_root_path().to_path_buf()),
@@ -424,3 +518,54 @@ impl DebugAdapter for CodeLldbDebugAdapter {
         })
     }
 }
+
+/// Rewrites a configuration to open `core_file` as a target instead of running the program.
+fn open_core_dump(config_obj: &mut serde_json::Map<String, Value>, core_file: &str) -> Result<()> {
+    let program = config_obj
+        .remove("program")
+        .and_then(|program| program.as_str().map(ToOwned::to_owned))
+        .context("Debugging a core dump needs the program that crashed")?;
+    for key in ["pid", "args", "env"] {
+        config_obj.remove(key);
+    }
+    config_obj.insert("request".into(), "launch".into());
+    config_obj.insert("coreFile".into(), core_file.into());
+    config_obj.insert(
+        "targetCreateCommands".into(),
+        json!([format!("target create {program:?} --core {core_file:?}")]),
+    );
+    config_obj.insert("processCreateCommands".into(), json!([]));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_core_dump() {
+        let mut config = json!({
+            "request": "attach",
+            "program": "/home/me/app",
+            "pid": 1234,
+            "args": ["--verbose"],
+            "env": { "KEY": "VALUE" },
+            "coreFile": "/var/crash/core.app",
+            "cwd": "/home/me",
+        });
+        open_core_dump(config.as_object_mut().unwrap(), "/tmp/core.app").unwrap();
+        assert_eq!(
+            config,
+            json!({
+                "request": "launch",
+                "coreFile": "/tmp/core.app",
+                "cwd": "/home/me",
+                "targetCreateCommands": ["target create \"/home/me/app\" --core \"/tmp/core.app\""],
+                "processCreateCommands": [],
+            })
+        );
+
+        let mut config = json!({ "coreFile": "/var/crash/core.app" });
+        assert!(open_core_dump(config.as_object_mut().unwrap(), "/var/crash/core.app").is_err());
+    }
+}
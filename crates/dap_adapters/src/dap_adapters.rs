@@ -3,6 +3,7 @@ mod gdb;
 mod go;
 mod javascript;
 mod python;
+mod ssh_host;
 
 #[cfg(test)]
 use std::path::PathBuf;
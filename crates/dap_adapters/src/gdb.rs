@@ -3,10 +3,14 @@ use async_trait::async_trait;
 use collections::HashMap;
 use dap::{StartDebuggingRequestArguments, adapters::DebugTaskDefinition};
 use gpui::AsyncApp;
+use serde_json::Value;
 use std::ffi::OsStr;
 use task::{DebugScenario, ZedDebugConfig};
 
-use crate::*;
+use crate::{
+    ssh_host::{self, SshHost},
+    *,
+};
 
 #[derive(Default)]
 pub(crate) struct GdbDebugAdapter;
@@ -72,7 +76,7 @@ impl DebugAdapter for GdbDebugAdapter {
     }
 
     fn dap_schema(&self) -> serde_json::Value {
-        json!({
+        let mut schema = json!({
             "oneOf": [
                 {
                     "allOf": [
@@ -131,6 +135,10 @@ impl DebugAdapter for GdbDebugAdapter {
                                     "type": "boolean",
                                     "description": "When true, GDB will set a temporary breakpoint at the program's first instruction, like the 'starti' command.",
                                     "default": false
+                                },
+                                "coreFile": {
+                                    "type": "string",
+                                    "description": "A core dump of `program` to inspect instead of running it. With `sshHost`, the core dump is fetched from that host."
                                 }
                             },
                             "required": ["program"]
@@ -171,7 +179,14 @@ impl DebugAdapter for GdbDebugAdapter {
                     ]
                 }
             ]
-        })
+        });
+        ssh_host::add_schema_properties(schema.pointer_mut("/oneOf/0/allOf/1/properties"));
+        ssh_host::add_schema_properties(schema.pointer_mut("/oneOf/1/allOf/1/properties"));
+        schema
+    }
+
+    fn supports_core_dumps(&self) -> bool {
+        true
     }
 
     async fn get_binary(
@@ -181,7 +196,7 @@ impl DebugAdapter for GdbDebugAdapter {
         user_installed_path: Option<std::path::PathBuf>,
         user_args: Option<Vec<String>>,
         user_env: Option<HashMap<String, String>>,
-        _: &mut AsyncApp,
+        cx: &mut AsyncApp,
     ) -> Result<DebugAdapterBinary> {
         // Try to get gdb_path from config
         let gdb_path_from_config = config
@@ -212,7 +227,7 @@ impl DebugAdapter for GdbDebugAdapter {
         };
 
         // Arguments: use gdb_args from config if present, else user_args, else default
-        let gdb_args = {
+        let mut gdb_args = {
             let args = config
                 .config
                 .get("gdb_args")
@@ -250,6 +265,64 @@ impl DebugAdapter for GdbDebugAdapter {
 
         base_env.extend(config_env);
 
+        let remote_host = SshHost::from_config(&config.config);
+        // A core dump is opened in place of running the program, and can only be inspected.
+        if let Some(core_file) = config.config.get("coreFile").and_then(Value::as_str) {
+            let core_file = match &remote_host {
+                Some(host) => host
+                    .fetch_file(core_file, delegate, cx)
+                    .await?
+                    .to_string_lossy()
+                    .into_owned(),
+                None => core_file.to_string(),
+            };
+            if let Some(configuration) = configuration.as_object_mut() {
+                open_core_dump(configuration, &core_file)?;
+            }
+            if let Some(host) = &remote_host {
+                gdb_args.extend(host.gdb_substitute_path_args());
+            }
+        } else if let Some(host) = &remote_host {
+            // On an SSH host, the program runs under gdbserver there, and the local GDB attaches
+            // to it as a remote target.
+            let server_path = host.server_path("gdbserver");
+            // gdbserver takes the address before the program, but after `--attach`.
+            let (leading_args, trailing_args) = if let Some(pid) = config.config.get("pid") {
+                let pid = match pid {
+                    Value::String(pid) => pid.clone(),
+                    pid => pid.to_string(),
+                };
+                (vec!["--attach".to_string()], vec![pid])
+            } else {
+                let mut program_and_args = vec![
+                    host.remote_program(&config.config)
+                        .context("No program to run on the SSH host")?,
+                ];
+                program_and_args.extend(ssh_host::program_args(&config.config));
+                (Vec::new(), program_and_args)
+            };
+            let local_port = host
+                .start_server(
+                    move |address| {
+                        let mut server_command = vec![server_path, "--once".to_string()];
+                        server_command.extend(leading_args);
+                        server_command.push(address);
+                        server_command.extend(trailing_args);
+                        server_command
+                    },
+                    delegate,
+                    cx,
+                )
+                .await?;
+
+            if let Some(configuration) = configuration.as_object_mut() {
+                configuration.insert("request".into(), "attach".into());
+                configuration.insert("target".into(), format!("127.0.0.1:{local_port}").into());
+                configuration.remove("pid");
+            }
+            gdb_args.extend(host.gdb_substitute_path_args());
+        }
+
         Ok(DebugAdapterBinary {
             command: Some(gdb_path),
             arguments: gdb_args,
@@ -257,9 +330,62 @@ impl DebugAdapter for GdbDebugAdapter {
             cwd: Some(delegate.worktree_root_path().to_path_buf()),
             connection: None,
             request_args: StartDebuggingRequestArguments {
-                request: self.request_kind(&config.config).await?,
+                request: self.request_kind(&configuration).await?,
                 configuration,
             },
         })
     }
 }
+
+/// Rewrites a configuration to have GDB open `core_file` for the program that crashed, instead
+/// of running the program.
+fn open_core_dump(
+    configuration: &mut serde_json::Map<String, Value>,
+    core_file: &str,
+) -> Result<()> {
+    if !configuration.get("program").is_some_and(Value::is_string) {
+        bail!("Debugging a core dump needs the program that crashed");
+    }
+    for key in ["pid", "target", "args", "env"] {
+        configuration.remove(key);
+    }
+    // Nothing is launched, GDB only loads the program and its core dump.
+    configuration.insert("request".into(), "attach".into());
+    configuration.insert("coreFile".into(), core_file.into());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_core_dump() {
+        let mut configuration = json!({
+            "request": "launch",
+            "program": "/home/me/app",
+            "args": ["--verbose"],
+            "coreFile": "/var/crash/core.app",
+            "cwd": "/home/me",
+        });
+        open_core_dump(configuration.as_object_mut().unwrap(), "/tmp/core.app").unwrap();
+        assert_eq!(
+            configuration,
+            json!({
+                "request": "attach",
+                "program": "/home/me/app",
+                "coreFile": "/tmp/core.app",
+                "cwd": "/home/me",
+            })
+        );
+
+        let mut configuration = json!({ "coreFile": "/var/crash/core.app" });
+        assert!(
+            open_core_dump(
+                configuration.as_object_mut().unwrap(),
+                "/var/crash/core.app"
+            )
+            .is_err()
+        );
+    }
+}
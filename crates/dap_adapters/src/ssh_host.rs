@@ -0,0 +1,307 @@
+//! Debugging a process on a host from the session store, without opening a remote project.
+//!
+//! When a debug task names an `sshHost`, the adapter still runs locally, but the program runs
+//! under `gdbserver` or `lldb-server` on the host. That server is started over the host's pooled
+//! SSH session, which terminals on the host share, and the adapter reaches it through a local
+//! port forwarded over the same session.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use gpui::AsyncApp;
+use gpui_tokio::Tokio;
+use serde_json::{Value, json};
+use terminal::{
+    ProtocolConfig, SessionStoreEntity, SshConfig,
+    connection::ssh::{SshSessionManager, start_forwarded_server},
+};
+use util::shell::ShellKind;
+
+use crate::*;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct SshHost {
+    /// The name or tag of the saved session to connect through.
+    name: String,
+    /// The port the debug server listens on, on the host. A free one is picked if unset.
+    server_port: Option<u16>,
+    server_path: Option<String>,
+    /// Prefixes of paths on the host, each with the local path it corresponds to.
+    pub(crate) path_map: Vec<(String, String)>,
+}
+
+impl SshHost {
+    /// Reads the SSH host fields of a debug task, if it has an `sshHost`.
+    pub(crate) fn from_config(config: &Value) -> Option<Self> {
+        let name = config.get("sshHost")?.as_str()?.to_string();
+        let server_port = config
+            .get("sshServerPort")
+            .and_then(Value::as_u64)
+            .and_then(|port| u16::try_from(port).ok());
+        let server_path = config
+            .get("sshServerPath")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        let path_map = config
+            .get("pathMap")
+            .and_then(Value::as_object)
+            .map(|path_map| {
+                path_map
+                    .iter()
+                    .filter_map(|(remote, local)| Some((remote.clone(), local.as_str()?.into())))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            name,
+            server_port,
+            server_path,
+            path_map,
+        })
+    }
+
+    /// The debug server to run on the host, unless `sshServerPath` names another.
+    pub(crate) fn server_path(&self, default: &str) -> String {
+        self.server_path
+            .clone()
+            .unwrap_or_else(|| default.to_string())
+    }
+
+    /// The program to run on the host: `remoteProgram`, or else `program` mapped onto the host
+    /// through the path map.
+    pub(crate) fn remote_program(&self, config: &Value) -> Option<String> {
+        if let Some(program) = config.get("remoteProgram").and_then(Value::as_str) {
+            return Some(program.to_string());
+        }
+        let program = config.get("program")?.as_str()?;
+        let remote_program = self.path_map.iter().find_map(|(remote, local)| {
+            let rest = program.strip_prefix(local.as_str())?;
+            Some(format!("{remote}{rest}"))
+        });
+        Some(remote_program.unwrap_or_else(|| program.to_string()))
+    }
+
+    /// Starts a debug server on the host, returning the local port that's forwarded to it.
+    ///
+    /// `server_command` is given the address the server should listen on, as seen from the
+    /// host.
+    pub(crate) async fn start_server(
+        &self,
+        server_command: impl FnOnce(String) -> Vec<String> + Send + 'static,
+        delegate: &Arc<dyn DapDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<u16> {
+        let ssh_config = self.ssh_config(cx)?;
+        let server_port = self.server_port;
+        let (local_port, command) = cx
+            .update(|cx| {
+                Tokio::spawn_result(cx, async move {
+                    let session = SshSessionManager::global()
+                        .get_or_create_session(&ssh_config)
+                        .await?;
+                    let server_port = match server_port {
+                        Some(port) => port,
+                        None => session.free_remote_port().await?,
+                    };
+                    let command = server_command(format!("127.0.0.1:{server_port}"))
+                        .iter()
+                        .map(|arg| {
+                            ShellKind::Posix
+                                .try_quote(arg)
+                                .with_context(|| format!("failed to quote {arg:?}"))
+                        })
+                        .collect::<Result<Vec<_>>>()?
+                        .join(" ");
+                    let local_port =
+                        start_forwarded_server(session, command.clone(), server_port).await?;
+                    anyhow::Ok((local_port, command))
+                })
+            })
+            .await?;
+        delegate.output_to_console(format!("Started `{command}` on {}", self.name));
+        Ok(local_port)
+    }
+
+    /// GDB commands that map the host's source paths to local ones, as `-ex` arguments.
+    pub(crate) fn gdb_substitute_path_args(&self) -> Vec<String> {
+        fn quote(path: &str) -> String {
+            format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+
+        self.path_map
+            .iter()
+            .flat_map(|(remote, local)| {
+                [
+                    "-ex".to_string(),
+                    format!("set substitute-path {} {}", quote(remote), quote(local)),
+                ]
+            })
+            .collect()
+    }
+
+    /// Copies the file at `remote_path` on the host to a local temporary directory, returning
+    /// the local copy.
+    pub(crate) async fn fetch_file(
+        &self,
+        remote_path: &str,
+        delegate: &Arc<dyn DapDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<PathBuf> {
+        let ssh_config = self.ssh_config(cx)?;
+        let file_name = Path::new(remote_path)
+            .file_name()
+            .with_context(|| format!("{remote_path:?} isn't a file"))?;
+        let local_path = paths::temp_dir()
+            .join("ssh-downloads")
+            .join(self.name.replace(['/', '\\'], "_"))
+            .join(file_name);
+
+        delegate.output_to_console(format!("Fetching {remote_path} from {}...", self.name));
+        let remote_path = remote_path.to_string();
+        let download_path = local_path.clone();
+        cx.update(|cx| {
+            Tokio::spawn_result(cx, async move {
+                if let Some(parent) = download_path.parent() {
+                    smol::fs::create_dir_all(parent).await?;
+                }
+                let session = SshSessionManager::global()
+                    .get_or_create_session(&ssh_config)
+                    .await?;
+                session.download(&remote_path, &download_path).await
+            })
+        })
+        .await?;
+        Ok(local_path)
+    }
+
+    fn ssh_config(&self, cx: &mut AsyncApp) -> Result<SshConfig> {
+        cx.update(|cx| {
+            let session_store = SessionStoreEntity::try_global(cx)?;
+            session_store
+                .read(cx)
+                .store()
+                .find_sessions(&self.name)
+                .into_iter()
+                .find_map(|session| match &session.protocol {
+                    ProtocolConfig::Ssh(config) => Some(SshConfig::from(config)),
+                    ProtocolConfig::Telnet(_) => None,
+                })
+        })
+        .with_context(|| format!("no saved SSH session named {:?}", self.name))
+    }
+}
+
+/// The arguments a launch or attach request passes to the program.
+pub(crate) fn program_args(config: &Value) -> Vec<String> {
+    config
+        .get("args")
+        .and_then(Value::as_array)
+        .map(|args| {
+            args.iter()
+                .filter_map(|arg| arg.as_str().map(ToOwned::to_owned))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Adds the SSH host fields to the `properties` of a debug task schema.
+pub(crate) fn add_schema_properties(properties: Option<&mut Value>) {
+    let Some(properties) = properties.and_then(Value::as_object_mut) else {
+        return;
+    };
+    properties.insert(
+        "sshHost".into(),
+        json!({
+            "type": "string",
+            "description": "The name or tag of a saved SSH session to debug on. The program runs under a debug server on that host, reached over the session's SSH connection."
+        }),
+    );
+    properties.insert(
+        "sshServerPort".into(),
+        json!({
+            "type": "integer",
+            "description": "The port the debug server listens on, on the SSH host. A free port is picked by default."
+        }),
+    );
+    properties.insert(
+        "sshServerPath".into(),
+        json!({
+            "type": "string",
+            "description": "The debug server to run on the SSH host, if it isn't on the host's PATH"
+        }),
+    );
+    properties.insert(
+        "remoteProgram".into(),
+        json!({
+            "type": "string",
+            "description": "The program to run on the SSH host. Defaults to `program`, mapped onto the host through `pathMap`."
+        }),
+    );
+    properties.insert(
+        "pathMap".into(),
+        json!({
+            "type": "object",
+            "description": "Maps source paths on the SSH host to local paths",
+            "additionalProperties": {
+                "type": "string"
+            }
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ssh_host_from_config() {
+        assert_eq!(
+            SshHost::from_config(&json!({ "program": "/bin/true" })),
+            None
+        );
+
+        let config = json!({
+            "program": "/home/me/project/target/debug/app",
+            "sshHost": "build-box",
+            "sshServerPort": 4000,
+            "pathMap": { "/srv/project": "/home/me/project" }
+        });
+        let host = SshHost::from_config(&config).unwrap();
+        assert_eq!(host.server_port, Some(4000));
+        assert_eq!(host.server_path("gdbserver"), "gdbserver");
+        assert_eq!(
+            host.path_map,
+            vec![("/srv/project".to_string(), "/home/me/project".to_string())]
+        );
+        assert_eq!(
+            host.remote_program(&config).as_deref(),
+            Some("/srv/project/target/debug/app")
+        );
+
+        let config = json!({
+            "program": "/home/me/project/target/debug/app",
+            "remoteProgram": "/opt/app",
+            "sshHost": "build-box"
+        });
+        let host = SshHost::from_config(&config).unwrap();
+        assert_eq!(host.server_port, None);
+        assert_eq!(host.remote_program(&config).as_deref(), Some("/opt/app"));
+    }
+
+    #[test]
+    fn test_gdb_substitute_path_args() {
+        let config = json!({
+            "sshHost": "build-box",
+            "pathMap": { "/srv/my project": r#"C:\src\"quoted""# }
+        });
+        let host = SshHost::from_config(&config).unwrap();
+        assert_eq!(
+            host.gdb_substitute_path_args(),
+            vec![
+                "-ex".to_string(),
+                r#"set substitute-path "/srv/my project" "C:\\src\\\"quoted\"""#.to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use dap::{DapRegistry, adapters::DebugAdapterName};
+use gpui::{App, Context, Window};
+use serde_json::json;
+use task::DebugScenario;
+use ui::{ContextMenu, DropdownMenu, prelude::*};
+use workspace::{InvalidInput, TextPromptModal, Workspace};
+
+use crate::debugger_panel::DebugPanel;
+
+/// Asks for the program and core dump to debug post-mortem, the saved SSH host the core dump
+/// is on, if it isn't local, and the debugger to open it with.
+pub(crate) fn toggle(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+    let weak_workspace = cx.weak_entity();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        let adapter_picker = cx.new(|cx| AdapterPicker::new(cx));
+        let on_confirm = {
+            let adapter_picker = adapter_picker.clone();
+            move |texts: Vec<String>, window: &mut Window, cx: &mut App| {
+                let (program, core_file, ssh_host) = (&texts[0], &texts[1], &texts[2]);
+                if program.is_empty() {
+                    return Err(InvalidInput::new(0, "Enter the executable that crashed"));
+                }
+                if core_file.is_empty() {
+                    return Err(InvalidInput::new(1, "Enter the core file"));
+                }
+                let Some(adapter) = adapter_picker.read(cx).adapter.clone() else {
+                    return Err(InvalidInput::new(0, "No debugger can open core dumps"));
+                };
+                let Some(panel) = weak_workspace
+                    .update(cx, |workspace, cx| workspace.panel::<DebugPanel>(cx))
+                    .ok()
+                    .flatten()
+                else {
+                    return Ok(());
+                };
+
+                let label = std::path::Path::new(core_file)
+                    .file_name()
+                    .map_or(core_file.as_str().into(), |name| name.to_string_lossy());
+                let label = format!("Core dump {label}");
+                let mut config = json!({
+                    "request": "launch",
+                    "program": program,
+                    "coreFile": core_file,
+                });
+                if !ssh_host.is_empty() {
+                    config["sshHost"] = ssh_host.as_str().into();
+                }
+                let scenario = DebugScenario {
+                    adapter: adapter.0,
+                    label: label.into(),
+                    build: None,
+                    config,
+                    tcp_connection: None,
+                };
+
+                panel.update(cx, |panel, cx| {
+                    panel.start_session(scenario, Default::default(), None, None, window, cx);
+                });
+                Ok(())
+            }
+        };
+
+        TextPromptModal::new(
+            "Debug Core Dump",
+            &[
+                "Executable that crashed",
+                "Core file",
+                "SSH host the core file is on (optional)",
+            ],
+            on_confirm,
+            window,
+            cx,
+        )
+        .icon(IconName::Debug)
+        .accessory(adapter_picker)
+    });
+}
+
+/// Picks the debugger to open the core dump with, among those that can.
+struct AdapterPicker {
+    adapters: Vec<DebugAdapterName>,
+    adapter: Option<DebugAdapterName>,
+}
+
+impl AdapterPicker {
+    fn new(cx: &App) -> Self {
+        let registry = DapRegistry::global(cx);
+        let adapters: Vec<DebugAdapterName> = registry
+            .enumerate_adapters::<Vec<_>>()
+            .into_iter()
+            .filter(|name| {
+                registry
+                    .adapter(&name.0)
+                    .is_some_and(|adapter| adapter.supports_core_dumps())
+            })
+            .collect();
+        Self {
+            adapter: adapters.first().cloned(),
+            adapters,
+        }
+    }
+}
+
+impl Render for AdapterPicker {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let weak = cx.weak_entity();
+        let adapters = self.adapters.clone();
+        let label = self.adapter.as_ref().map_or_else(
+            || "No debugger can open core dumps".into(),
+            |adapter| adapter.0.clone(),
+        );
+
+        DropdownMenu::new(
+            "core-dump-adapter-picker",
+            label,
+            ContextMenu::build(window, cx, move |mut menu, _, _| {
+                for adapter in adapters {
+                    let weak = weak.clone();
+                    menu = menu.entry(adapter.0.clone(), None, move |_, cx| {
+                        weak.update(cx, |this, cx| {
+                            this.adapter = Some(adapter.clone());
+                            cx.notify();
+                        })
+                        .ok();
+                    });
+                }
+                menu
+            }),
+        )
+        .style(ui::DropdownStyle::Outlined)
+        .attach(gpui::Corner::BottomLeft)
+    }
+}
@@ -383,6 +383,11 @@ impl DebugPanel {
         let adapter = curr_session.read(cx).adapter();
         let binary = curr_session.read(cx).binary().cloned().unwrap();
         let task_context = curr_session.read(cx).task_context().clone();
+        let instruction_breakpoints = curr_session
+            .read(cx)
+            .instruction_breakpoints()
+            .cloned()
+            .collect::<Vec<_>>();
 
         let curr_session_id = curr_session.read(cx).session_id();
         self.sessions_with_children
@@ -398,6 +403,7 @@ impl DebugPanel {
                 let session = dap_store.new_session(label, adapter, task_context, None, quirks, cx);
 
                 let task = session.update(cx, |session, cx| {
+                    session.set_instruction_breakpoints(instruction_breakpoints);
                     session.boot(binary, worktree, dap_store_handle.downgrade(), cx)
                 });
                 (session, task)
@@ -672,6 +678,17 @@ impl DebugPanel {
             .map(|session| session.read(cx).running_state())
             .and_then(|state| state.read(cx).thread_status(cx))
             .unwrap_or(project::debugger::session::ThreadStatus::Exited);
+        // A core dump can be inspected, but there's no process to resume or step.
+        let can_resume = thread_status == ThreadStatus::Stopped
+            && !active_session.as_ref().is_some_and(|session| {
+                session
+                    .read(cx)
+                    .running_state()
+                    .read(cx)
+                    .session()
+                    .read(cx)
+                    .is_post_mortem()
+            });
 
         Some(
             div.w_full()
@@ -731,7 +748,7 @@ impl DebugPanel {
                                                     running_state,
                                                     |this, _, _window, cx| this.continue_thread(cx),
                                                 ))
-                                                .disabled(thread_status != ThreadStatus::Stopped)
+                                                .disabled(!can_resume)
                                                 .tooltip({
                                                     let focus_handle = focus_handle.clone();
                                                     move |_window, cx| {
@@ -755,7 +772,7 @@ impl DebugPanel {
                                                     this.step_over(cx);
                                                 },
                                             ))
-                                            .disabled(thread_status != ThreadStatus::Stopped)
+                                            .disabled(!can_resume)
                                             .tooltip({
                                                 let focus_handle = focus_handle.clone();
                                                 move |_window, cx| {
@@ -777,7 +794,7 @@ impl DebugPanel {
                                                     this.step_in(cx);
                                                 },
                                             ))
-                                            .disabled(thread_status != ThreadStatus::Stopped)
+                                            .disabled(!can_resume)
                                             .tooltip({
                                                 let focus_handle = focus_handle.clone();
                                                 move |_window, cx| {
@@ -799,7 +816,7 @@ impl DebugPanel {
                                                     this.step_out(cx);
                                                 },
                                             ))
-                                            .disabled(thread_status != ThreadStatus::Stopped)
+                                            .disabled(!can_resume)
                                             .tooltip({
                                                 let focus_handle = focus_handle.clone();
                                                 move |_window, cx| {
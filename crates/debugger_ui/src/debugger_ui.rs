@@ -18,6 +18,7 @@ use zed_actions::ToggleFocus;
 use zed_actions::debugger::OpenOnboardingModal;
 
 pub mod attach_modal;
+mod core_dump_modal;
 pub mod debugger_panel;
 mod dropdown_menus;
 mod new_process_modal;
@@ -35,6 +36,8 @@ actions!(
     [
         /// Starts a new debugging session.
         Start,
+        /// Opens a core dump for post-mortem debugging.
+        DebugCoreDump,
         /// Continues execution until the next breakpoint.
         Continue,
         /// Detaches the debugger from the running process.
@@ -53,6 +56,10 @@ actions!(
         StepOut,
         /// Steps back to the previous statement.
         StepBack,
+        /// Steps into the next machine instruction, following calls.
+        StepIntoInstruction,
+        /// Steps over the next machine instruction.
+        StepOverInstruction,
         /// Stops the debugging session.
         Stop,
         /// Toggles whether to ignore all breakpoints.
@@ -124,6 +131,9 @@ pub fn init(cx: &mut App) {
             .register_action(|workspace: &mut Workspace, _: &Start, window, cx| {
                 NewProcessModal::show(workspace, window, NewProcessMode::Debug, None, cx);
             })
+            .register_action(|workspace: &mut Workspace, _: &DebugCoreDump, window, cx| {
+                core_dump_modal::toggle(workspace, window, cx);
+            })
             .register_action(|workspace: &mut Workspace, _: &Rerun, window, cx| {
                 let Some(debug_panel) = workspace.panel::<DebugPanel>(cx) else {
                     return;
@@ -163,6 +173,8 @@ pub fn init(cx: &mut App) {
 
                 let caps = running_state.capabilities(cx);
                 let supports_step_back = caps.supports_step_back.unwrap_or_default();
+                let supports_stepping_granularity =
+                    caps.supports_stepping_granularity.unwrap_or_default();
                 let supports_detach = running_state.session().read(cx).is_attached();
                 let status = running_state.thread_status(cx);
 
@@ -200,6 +212,24 @@ pub fn init(cx: &mut App) {
                             active_item.update(cx, |item, cx| item.step_back(cx)).ok();
                         })
                     })
+                    .when(supports_stepping_granularity, |div| {
+                        div.on_action({
+                            let active_item = active_item.clone();
+                            move |_: &StepIntoInstruction, _, cx| {
+                                active_item
+                                    .update(cx, |item, cx| item.step_in_instruction(cx))
+                                    .ok();
+                            }
+                        })
+                        .on_action({
+                            let active_item = active_item.clone();
+                            move |_: &StepOverInstruction, _, cx| {
+                                active_item
+                                    .update(cx, |item, cx| item.step_over_instruction(cx))
+                                    .ok();
+                            }
+                        })
+                    })
                     .on_action({
                         let active_item = active_item.clone();
                         move |_: &Continue, _, cx| {
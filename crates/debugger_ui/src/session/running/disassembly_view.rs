@@ -0,0 +1,519 @@
+use std::{ops::Range, path::Path, sync::Arc};
+
+use collections::{HashMap, HashSet};
+use dap::DisassembledInstruction;
+use fs::Fs as _;
+use gpui::{
+    Action as _, AnyElement, Entity, FocusHandle, Focusable, ScrollStrategy, Subscription, Task,
+    UniformListScrollHandle, WeakEntity, uniform_list,
+};
+use project::debugger::{dap_command::DisassembleSpan, session::Session};
+use ui::{Tooltip, WithScrollbar, prelude::*};
+use util::ResultExt;
+use workspace::Workspace;
+
+use crate::{StepIntoInstruction, StepOverInstruction};
+
+use super::stack_frame_list::{StackFrameList, StackFrameListEvent};
+
+enum DisassemblyRow {
+    LoadEarlier,
+    /// The source line that the instructions below it were compiled from.
+    Source {
+        path: Arc<str>,
+        line: u32,
+    },
+    Instruction(usize),
+    LoadLater,
+}
+
+/// Shows the machine instructions around the instruction pointer of the selected stack frame,
+/// interleaved with the source lines they came from when the adapter reports them.
+pub(crate) struct DisassemblyView {
+    session: Entity<Session>,
+    stack_frame_list: Entity<StackFrameList>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    instructions: Vec<DisassembledInstruction>,
+    rows: Vec<DisassemblyRow>,
+    /// The lines of each source file, or `None` while it's loading or if it couldn't be read.
+    source_lines: HashMap<Arc<str>, Option<Arc<[SharedString]>>>,
+    instruction_pointer: Option<String>,
+    _load_task: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DisassemblyView {
+    pub(crate) fn new(
+        session: Entity<Session>,
+        workspace: WeakEntity<Workspace>,
+        stack_frame_list: Entity<StackFrameList>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let _subscriptions = vec![
+            cx.subscribe(&stack_frame_list, |this, _, event, cx| match event {
+                StackFrameListEvent::SelectedStackFrameChanged(_)
+                | StackFrameListEvent::BuiltEntries => this.follow_opened_stack_frame(cx),
+            }),
+            cx.observe(&session, |_, _, cx| cx.notify()),
+        ];
+
+        Self {
+            session,
+            stack_frame_list,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            instructions: Vec::new(),
+            rows: Vec::new(),
+            source_lines: HashMap::default(),
+            instruction_pointer: None,
+            _load_task: None,
+            _subscriptions,
+        }
+    }
+
+    fn follow_opened_stack_frame(&mut self, cx: &mut Context<Self>) {
+        let instruction_pointer = self
+            .stack_frame_list
+            .update(cx, |stack_frame_list, cx| {
+                stack_frame_list.opened_dap_stack_frame(cx)
+            })
+            .and_then(|stack_frame| stack_frame.instruction_pointer_reference);
+        self.instruction_pointer = instruction_pointer.clone();
+
+        let Some(instruction_pointer) = instruction_pointer else {
+            cx.notify();
+            return;
+        };
+        if self.current_instruction_ix().is_some() {
+            self.scroll_to_instruction_pointer();
+            cx.notify();
+        } else {
+            self.disassemble(instruction_pointer, DisassembleSpan::Around, cx);
+        }
+    }
+
+    fn disassemble(
+        &mut self,
+        memory_reference: String,
+        span: DisassembleSpan,
+        cx: &mut Context<Self>,
+    ) {
+        let instructions = self.session.update(cx, |session, cx| {
+            session.disassemble(memory_reference, span, cx)
+        });
+        self._load_task = Some(cx.spawn(async move |this, cx| {
+            let Some(instructions) = instructions.await else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.insert_instructions(instructions, span, cx);
+            })
+            .ok();
+        }));
+    }
+
+    fn insert_instructions(
+        &mut self,
+        instructions: Vec<DisassembledInstruction>,
+        span: DisassembleSpan,
+        cx: &mut Context<Self>,
+    ) {
+        let known_addresses = self
+            .instructions
+            .iter()
+            .map(|instruction| instruction.address.clone())
+            .collect::<HashSet<_>>();
+        let new_instructions = instructions
+            .into_iter()
+            .filter(|instruction| !known_addresses.contains(&instruction.address));
+
+        match span {
+            DisassembleSpan::Around => {
+                self.instructions = new_instructions.collect();
+            }
+            DisassembleSpan::Before => {
+                let mut instructions = new_instructions.collect::<Vec<_>>();
+                instructions.append(&mut self.instructions);
+                self.instructions = instructions;
+            }
+            DisassembleSpan::After => self.instructions.extend(new_instructions),
+        }
+        self.rebuild_rows(cx);
+
+        if span == DisassembleSpan::Around {
+            self.scroll_to_instruction_pointer();
+        }
+        cx.notify();
+    }
+
+    fn rebuild_rows(&mut self, cx: &mut Context<Self>) {
+        self.rows.clear();
+        if self.instructions.is_empty() {
+            return;
+        }
+
+        self.rows.push(DisassemblyRow::LoadEarlier);
+        // Adapters only report an instruction's location when it differs from the previous one.
+        let mut path: Option<Arc<str>> = None;
+        let mut line = None;
+        let mut paths = Vec::new();
+        for (ix, instruction) in self.instructions.iter().enumerate() {
+            let previous = (path.clone(), line);
+            if let Some(location) = &instruction.location {
+                path = location.path.as_deref().map(Arc::from);
+            }
+            if let Some(instruction_line) =
+                instruction.line.and_then(|line| u32::try_from(line).ok())
+            {
+                line = Some(instruction_line);
+            }
+            if (path.clone(), line) != previous
+                && let (Some(path), Some(line)) = (&path, line)
+            {
+                self.rows.push(DisassemblyRow::Source {
+                    path: path.clone(),
+                    line,
+                });
+                paths.push(path.clone());
+            }
+            self.rows.push(DisassemblyRow::Instruction(ix));
+        }
+        self.rows.push(DisassemblyRow::LoadLater);
+
+        for path in paths {
+            self.load_source(path, cx);
+        }
+    }
+
+    fn load_source(&mut self, path: Arc<str>, cx: &mut Context<Self>) {
+        if self.source_lines.contains_key(&path) {
+            return;
+        }
+        self.source_lines.insert(path.clone(), None);
+
+        // The adapter reports paths on the debuggee's machine, which we can only read locally.
+        let Some(fs) = self
+            .workspace
+            .read_with(cx, |workspace, cx| {
+                let project = workspace.project().read(cx);
+                project.is_local().then(|| project.fs().clone())
+            })
+            .ok()
+            .flatten()
+        else {
+            return;
+        };
+
+        cx.spawn(async move |this, cx| {
+            let Some(text) = fs.load(Path::new(path.as_ref())).await.log_err() else {
+                return;
+            };
+            let lines = text
+                .lines()
+                .map(|line| SharedString::from(line.to_string()))
+                .collect::<Arc<[_]>>();
+            this.update(cx, |this, cx| {
+                this.source_lines.insert(path, Some(lines));
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn current_instruction_ix(&self) -> Option<usize> {
+        let instruction_pointer = self.instruction_pointer.as_deref()?;
+        self.instructions
+            .iter()
+            .position(|instruction| same_address(&instruction.address, instruction_pointer))
+    }
+
+    fn scroll_to_instruction_pointer(&self) {
+        let Some(instruction_ix) = self.current_instruction_ix() else {
+            return;
+        };
+        if let Some(row_ix) = self
+            .rows
+            .iter()
+            .position(|row| matches!(row, DisassemblyRow::Instruction(ix) if *ix == instruction_ix))
+        {
+            self.scroll_handle
+                .scroll_to_item(row_ix, ScrollStrategy::Center);
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn instruction_addresses(&self) -> Vec<String> {
+        self.instructions
+            .iter()
+            .map(|instruction| instruction.address.clone())
+            .collect()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn current_instruction_address(&self) -> Option<String> {
+        let ix = self.current_instruction_ix()?;
+        Some(self.instructions[ix].address.clone())
+    }
+
+    pub(crate) fn load_more(&mut self, span: DisassembleSpan, cx: &mut Context<Self>) {
+        let instruction = match span {
+            DisassembleSpan::Before => self.instructions.first(),
+            DisassembleSpan::After => self.instructions.last(),
+            DisassembleSpan::Around => None,
+        };
+        if let Some(instruction) = instruction {
+            self.disassemble(instruction.address.clone(), span, cx);
+        }
+    }
+
+    fn render_row(&self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        match &self.rows[ix] {
+            DisassemblyRow::LoadEarlier => {
+                self.render_load_more(ix, "Load Earlier Instructions", DisassembleSpan::Before, cx)
+            }
+            DisassemblyRow::LoadLater => {
+                self.render_load_more(ix, "Load Later Instructions", DisassembleSpan::After, cx)
+            }
+            DisassemblyRow::Source { path, line } => self.render_source(path, *line, cx),
+            DisassemblyRow::Instruction(instruction_ix) => {
+                self.render_instruction(*instruction_ix, cx)
+            }
+        }
+    }
+
+    fn render_load_more(
+        &self,
+        ix: usize,
+        label: &'static str,
+        span: DisassembleSpan,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        h_flex()
+            .w_full()
+            .justify_center()
+            .child(
+                Button::new(("disassembly-load-more", ix), label)
+                    .label_size(LabelSize::Small)
+                    .on_click(cx.listener(move |this, _, _, cx| this.load_more(span, cx))),
+            )
+            .into_any_element()
+    }
+
+    fn render_source(&self, path: &Arc<str>, line: u32, cx: &App) -> AnyElement {
+        let text = self
+            .source_lines
+            .get(path)
+            .and_then(|lines| {
+                lines
+                    .as_ref()?
+                    .get(line.saturating_sub(1) as usize)
+                    .cloned()
+            })
+            .unwrap_or_else(|| {
+                let file_name = Path::new(path.as_ref())
+                    .file_name()
+                    .map(|file_name| file_name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.to_string());
+                format!("{file_name}:{line}").into()
+            });
+
+        h_flex()
+            .w_full()
+            .pl_6()
+            .child(
+                Label::new(text)
+                    .buffer_font(cx)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .truncate(),
+            )
+            .into_any_element()
+    }
+
+    fn render_instruction(&self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let instruction = &self.instructions[ix];
+        let address = instruction.address.clone();
+        let is_current = self.current_instruction_ix() == Some(ix);
+        let has_breakpoint = self
+            .session
+            .read(cx)
+            .has_instruction_breakpoint(&instruction.address);
+
+        let gutter = if has_breakpoint {
+            Some(Icon::new(IconName::DebugBreakpoint).color(Color::Debugger))
+        } else if is_current {
+            Some(Icon::new(IconName::ArrowRight).color(Color::Accent))
+        } else {
+            None
+        };
+
+        h_flex()
+            .w_full()
+            .gap_2()
+            .when(is_current, |this| {
+                this.bg(cx.theme().colors().editor_debugger_active_line_background)
+            })
+            .child(
+                div()
+                    .id(("disassembly-breakpoint", ix))
+                    .flex_none()
+                    .w_4()
+                    .cursor_pointer()
+                    .children(gutter.map(|icon| icon.size(IconSize::XSmall)))
+                    .tooltip(Tooltip::text("Toggle Instruction Breakpoint"))
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.session.update(cx, |session, cx| {
+                            session.toggle_instruction_breakpoint(address.clone(), cx);
+                        });
+                    })),
+            )
+            .child(
+                Label::new(instruction.address.clone())
+                    .buffer_font(cx)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .when_some(instruction.symbol.clone(), |this, symbol| {
+                this.child(
+                    Label::new(format!("<{symbol}>"))
+                        .buffer_font(cx)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .child(
+                Label::new(instruction.instruction.clone())
+                    .buffer_font(cx)
+                    .size(LabelSize::Small)
+                    .truncate(),
+            )
+            .into_any_element()
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle.clone();
+        h_flex()
+            .w_full()
+            .gap_1()
+            .p_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                IconButton::new("disassembly-step-over", IconName::DebugStepOver)
+                    .icon_size(IconSize::Small)
+                    .on_click(|_, window, cx| {
+                        window.dispatch_action(StepOverInstruction.boxed_clone(), cx)
+                    })
+                    .tooltip({
+                        let focus_handle = focus_handle.clone();
+                        move |_window, cx| {
+                            Tooltip::for_action_in(
+                                "Step Over Instruction",
+                                &StepOverInstruction,
+                                &focus_handle,
+                                cx,
+                            )
+                        }
+                    }),
+            )
+            .child(
+                IconButton::new("disassembly-step-into", IconName::DebugStepInto)
+                    .icon_size(IconSize::Small)
+                    .on_click(|_, window, cx| {
+                        window.dispatch_action(StepIntoInstruction.boxed_clone(), cx)
+                    })
+                    .tooltip(move |_window, cx| {
+                        Tooltip::for_action_in(
+                            "Step Into Instruction",
+                            &StepIntoInstruction,
+                            &focus_handle,
+                            cx,
+                        )
+                    }),
+            )
+    }
+
+    fn render_list(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
+        uniform_list(
+            "disassembly-view",
+            self.rows.len(),
+            cx.processor(|this, range: Range<usize>, _window, cx| {
+                range.map(|ix| this.render_row(ix, cx)).collect()
+            }),
+        )
+        .track_scroll(&self.scroll_handle)
+        .size_full()
+    }
+}
+
+/// Whether two memory references name the same address, even if the adapter formats
+/// them differently (e.g. with or without leading zeros).
+fn same_address(a: &str, b: &str) -> bool {
+    fn parse(address: &str) -> Option<u64> {
+        let address = address.trim();
+        let digits = address
+            .strip_prefix("0x")
+            .or_else(|| address.strip_prefix("0X"))?;
+        u64::from_str_radix(digits, 16).ok()
+    }
+
+    match (parse(a), parse(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+impl Focusable for DisassemblyView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for DisassemblyView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .child(self.render_toolbar(cx))
+            .child(if self.rows.is_empty() {
+                h_flex()
+                    .size_full()
+                    .justify_center()
+                    .child(
+                        Label::new("No instructions to show")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .into_any_element()
+            } else {
+                div()
+                    .size_full()
+                    .p_1()
+                    .child(self.render_list(cx))
+                    .vertical_scrollbar_for(&self.scroll_handle, window, cx)
+                    .into_any_element()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::same_address;
+
+    #[test]
+    fn test_same_address() {
+        assert!(same_address("0x1000", "0x0000000000001000"));
+        assert!(same_address("0X1000", "0x1000"));
+        assert!(same_address(" 0x1000", "0x1000"));
+        assert!(!same_address("0x1000", "0x1004"));
+
+        // References that aren't hex addresses are only equal to themselves.
+        assert!(same_address("main+4", "main+4"));
+        assert!(!same_address("main+4", "0x1000"));
+        assert!(!same_address("4096", "0x1000"));
+    }
+}
@@ -459,8 +459,11 @@ impl MemoryView {
     fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
         let selection = self.view_state().selection.clone();
         if let Some(SelectedMemoryRange::DragComplete(drag)) = selection {
-            // Go into memory writing mode.
+            // Go into memory writing mode, unless we're looking at a core dump.
             if !self.is_writing_memory {
+                if self.session.read(cx).is_post_mortem() {
+                    return;
+                }
                 let should_return = self.session.update(cx, |session, cx| {
                     if !session
                         .capabilities()
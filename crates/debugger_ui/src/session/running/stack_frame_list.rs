@@ -218,6 +218,15 @@ impl StackFrameList {
         self.opened_stack_frame_id
     }
 
+    pub(crate) fn opened_dap_stack_frame(&self, cx: &mut App) -> Option<dap::StackFrame> {
+        let opened_stack_frame_id = self.opened_stack_frame_id?;
+        self.stack_frames(cx)
+            .ok()?
+            .into_iter()
+            .find(|stack_frame| stack_frame.dap.id == opened_stack_frame_id)
+            .map(|stack_frame| stack_frame.dap)
+    }
+
     pub(super) fn schedule_refresh(
         &mut self,
         select_first: bool,
@@ -896,6 +896,9 @@ impl VariableList {
     }
 
     fn edit_variable(&mut self, _: &EditVariable, window: &mut Window, cx: &mut Context<Self>) {
+        if self.session.read(cx).is_post_mortem() {
+            return;
+        }
         let Some(selection) = self.selection.as_ref() else {
             return;
         };
@@ -1472,6 +1472,57 @@ async fn test_we_send_arguments_from_user_config(
     );
 }
 
+#[gpui::test]
+async fn test_post_mortem_session_cant_be_resumed(
+    executor: BackgroundExecutor,
+    cx: &mut TestAppContext,
+) {
+    init_test(cx);
+
+    let fs = FakeFs::new(executor.clone());
+    let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+    let workspace = init_test_workspace(&project, cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+
+    let session = start_debug_session(&workspace, cx, |_| {}).unwrap();
+    session.update(cx, |session, _| assert!(!session.is_post_mortem()));
+
+    let debug_definition = DebugTaskDefinition {
+        adapter: "fake-adapter".into(),
+        config: json!({
+            "request": "launch",
+            "program": "main.rs",
+            "coreFile": "/var/crash/core.main",
+        }),
+        label: "core dump".into(),
+        tcp_connection: None,
+    };
+    let session = start_debug_session_with(&workspace, cx, debug_definition, |_| {}).unwrap();
+    let client = session.update(cx, |session, _| session.adapter_client().unwrap());
+    session.update(cx, |session, _| assert!(session.is_post_mortem()));
+
+    let continue_called = Arc::new(AtomicBool::new(false));
+    client.on_request::<Continue, _>({
+        let continue_called = continue_called.clone();
+        move |_, _| {
+            continue_called.store(true, Ordering::SeqCst);
+            Ok(dap::ContinueResponse {
+                all_threads_continued: Some(true),
+            })
+        }
+    });
+
+    session.update(cx, |session, cx| {
+        session.continue_thread(ThreadId(1), cx);
+    });
+    cx.run_until_parked();
+
+    assert!(
+        !continue_called.load(Ordering::SeqCst),
+        "A core dump can't be resumed"
+    );
+}
+
 #[gpui::test]
 async fn test_active_debug_line_setting(executor: BackgroundExecutor, cx: &mut TestAppContext) {
     init_test(cx);
@@ -0,0 +1,215 @@
+use crate::tests::{
+    active_debug_session_panel, init_test, init_test_workspace, start_debug_session,
+};
+use dap::{
+    StackFrame,
+    requests::{Disassemble, Initialize, Scopes, SetInstructionBreakpoints, StackTrace, Threads},
+};
+use gpui::{BackgroundExecutor, TestAppContext, VisualTestContext};
+use project::{FakeFs, Project, debugger::dap_command::DisassembleSpan};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use util::path;
+
+fn instruction(address: &str, text: &str) -> dap::DisassembledInstruction {
+    serde_json::from_value(json!({
+        "address": address,
+        "instruction": text,
+    }))
+    .unwrap()
+}
+
+#[gpui::test]
+async fn test_disassembly_view(executor: BackgroundExecutor, cx: &mut TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(executor.clone());
+    let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+    let workspace = init_test_workspace(&project, cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+
+    let session = start_debug_session(&workspace, cx, |client| {
+        client.on_request::<Initialize, _>(move |_, _| {
+            Ok(dap::Capabilities {
+                supports_disassemble_request: Some(true),
+                supports_instruction_breakpoints: Some(true),
+                ..Default::default()
+            })
+        });
+    })
+    .unwrap();
+    let client = session.update(cx, |session, _| session.adapter_client().unwrap());
+
+    client.on_request::<Threads, _>(move |_, _| {
+        Ok(dap::ThreadsResponse {
+            threads: vec![dap::Thread {
+                id: 1,
+                name: "Thread 1".into(),
+            }],
+        })
+    });
+    client.on_request::<Scopes, _>(move |_, _| Ok(dap::ScopesResponse { scopes: vec![] }));
+    client.on_request::<StackTrace, _>(move |_, _| {
+        Ok(dap::StackTraceResponse {
+            stack_frames: vec![StackFrame {
+                id: 1,
+                name: "main".into(),
+                source: None,
+                line: 0,
+                column: 0,
+                end_line: None,
+                end_column: None,
+                can_restart: None,
+                // Formatted differently from the disassembled addresses on purpose.
+                instruction_pointer_reference: Some("0x0000000000001008".into()),
+                module_id: None,
+                presentation_hint: None,
+            }],
+            total_frames: None,
+        })
+    });
+
+    let disassemble_requests = Arc::new(Mutex::new(Vec::new()));
+    client.on_request::<Disassemble, _>({
+        let disassemble_requests = disassemble_requests.clone();
+        move |_, args| {
+            disassemble_requests
+                .lock()
+                .unwrap()
+                .push((args.memory_reference.clone(), args.instruction_offset));
+            let instructions = match args.instruction_offset {
+                Some(-32) => vec![
+                    instruction("0x1000", "push rbp"),
+                    instruction("0x1004", "mov rbp, rsp"),
+                    instruction("0x1008", "call foo"),
+                    instruction("0x100c", "pop rbp"),
+                ],
+                // Adapters may repeat the instruction the request was relative to.
+                Some(1) => vec![
+                    instruction("0x100c", "pop rbp"),
+                    instruction("0x1010", "ret"),
+                ],
+                offset => panic!("unexpected instruction offset {offset:?}"),
+            };
+            Ok(dap::DisassembleResponse { instructions })
+        }
+    });
+
+    let instruction_breakpoint_requests = Arc::new(Mutex::new(Vec::new()));
+    client.on_request::<SetInstructionBreakpoints, _>({
+        let instruction_breakpoint_requests = instruction_breakpoint_requests.clone();
+        move |_, args| {
+            instruction_breakpoint_requests.lock().unwrap().push(
+                args.breakpoints
+                    .iter()
+                    .map(|breakpoint| breakpoint.instruction_reference.clone())
+                    .collect::<Vec<_>>(),
+            );
+            Ok(dap::SetInstructionBreakpointsResponse {
+                breakpoints: Vec::new(),
+            })
+        }
+    });
+
+    client
+        .fake_event(dap::messages::Events::Stopped(dap::StoppedEvent {
+            reason: dap::StoppedEventReason::Pause,
+            description: None,
+            thread_id: Some(1),
+            preserve_focus_hint: None,
+            text: None,
+            all_threads_stopped: None,
+            hit_breakpoint_ids: None,
+        }))
+        .await;
+
+    cx.run_until_parked();
+
+    active_debug_session_panel(workspace, cx).update(cx, |session, cx| {
+        session.running_state().update(cx, |running_state, cx| {
+            running_state
+                .session()
+                .update(cx, |session, cx| session.threads(cx));
+        });
+    });
+
+    cx.run_until_parked();
+
+    let running_state =
+        active_debug_session_panel(workspace, cx).update_in(cx, |session, window, cx| {
+            session.running_state().update(cx, |running_state, cx| {
+                running_state.select_current_thread(
+                    &running_state
+                        .session()
+                        .update(cx, |session, cx| session.threads(cx)),
+                    window,
+                    cx,
+                );
+            });
+            session.running_state().clone()
+        });
+
+    cx.run_until_parked();
+
+    let disassembly_view = running_state.update(cx, |running_state, _| {
+        running_state.disassembly_view().clone()
+    });
+
+    assert_eq!(
+        disassemble_requests.lock().unwrap().first(),
+        Some(&("0x0000000000001008".to_string(), Some(-32)))
+    );
+    disassembly_view.update(cx, |view, _| {
+        assert_eq!(
+            view.instruction_addresses(),
+            vec!["0x1000", "0x1004", "0x1008", "0x100c"]
+        );
+        assert_eq!(
+            view.current_instruction_address().as_deref(),
+            Some("0x1008")
+        );
+    });
+
+    disassembly_view.update(cx, |view, cx| view.load_more(DisassembleSpan::After, cx));
+    cx.run_until_parked();
+
+    assert_eq!(
+        disassemble_requests.lock().unwrap().last(),
+        Some(&("0x100c".to_string(), Some(1)))
+    );
+    disassembly_view.update(cx, |view, _| {
+        assert_eq!(
+            view.instruction_addresses(),
+            vec!["0x1000", "0x1004", "0x1008", "0x100c", "0x1010"],
+            "the repeated instruction shouldn't be added twice"
+        );
+    });
+
+    session.update(cx, |session, cx| {
+        session.toggle_instruction_breakpoint("0x1004".into(), cx);
+    });
+    cx.run_until_parked();
+
+    session.update(cx, |session, _| {
+        assert!(session.has_instruction_breakpoint("0x1004"));
+    });
+    assert_eq!(
+        *instruction_breakpoint_requests.lock().unwrap(),
+        vec![vec!["0x1004".to_string()]]
+    );
+
+    session.update(cx, |session, cx| {
+        session.toggle_instruction_breakpoint("0x1010".into(), cx);
+        session.toggle_instruction_breakpoint("0x1004".into(), cx);
+    });
+    cx.run_until_parked();
+
+    session.update(cx, |session, _| {
+        assert!(!session.has_instruction_breakpoint("0x1004"));
+        assert!(session.has_instruction_breakpoint("0x1010"));
+    });
+    assert_eq!(
+        instruction_breakpoint_requests.lock().unwrap().last(),
+        Some(&vec!["0x1010".to_string()])
+    );
+}
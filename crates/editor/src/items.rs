@@ -1452,6 +1452,20 @@ fn clip_ranges<'a>(
         .collect()
 }
 
+/// Structural replacements depend on the syntax tree around a match, so they are
+/// computed against the buffer that contains it.
+fn structural_replacement(
+    snapshot: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+) -> Option<String> {
+    let (buffer, buffer_range, _) = snapshot
+        .range_to_buffer_ranges(range.clone())
+        .into_iter()
+        .next()?;
+    query.structural_replacement(buffer, buffer_range.start.0..buffer_range.end.0)
+}
+
 impl EventEmitter<SearchEvent> for Editor {}
 
 impl Editor {
@@ -1673,6 +1687,14 @@ impl SearchableItem for Editor {
     ) {
         let text = self.buffer.read(cx);
         let text = text.snapshot(cx);
+        if query.is_structural() {
+            if let Some(replacement) = structural_replacement(&text, identifier, query) {
+                self.transact(window, cx, |this, _, cx| {
+                    this.edit([(identifier.clone(), Arc::<str>::from(replacement))], cx);
+                });
+            }
+            return;
+        }
         let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
         let text: Cow<_> = if text.len() == 1 {
             text.first().cloned().unwrap().into()
@@ -1699,9 +1721,16 @@ impl SearchableItem for Editor {
         let text = text.snapshot(cx);
         let mut edits = vec![];
 
-        // A regex might have replacement variables so we cannot apply
-        // the same replacement to all matches
-        if query.is_regex() {
+        // A regex or structural query might have replacement variables so we
+        // cannot apply the same replacement to all matches
+        if query.is_structural() {
+            edits = matches
+                .filter_map(|m| {
+                    structural_replacement(&text, m, query)
+                        .map(|replacement| (m.clone(), Arc::<str>::from(replacement)))
+                })
+                .collect();
+        } else if query.is_regex() {
             edits = matches
                 .filter_map(|m| {
                     let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
@@ -4,16 +4,20 @@ use collections::{HashMap, HashSet};
 use futures::future::{self, BoxFuture, join_all};
 use git::{
     Oid, RunHook,
+    bisect::{BisectProgress, BisectTerm},
     blame::Blame,
+    rebase::{RebaseControl, RebaseProgress, RebaseTodoEntry},
     repository::{
         AskPassDelegate, Branch, CommitDataReader, CommitDetails, CommitOptions, FetchOptions,
         GRAPH_CHUNK_SIZE, GitRepository, GitRepositoryCheckpoint, InitialGraphCommitData, LogOrder,
         LogSource, PushOptions, Remote, RepoPath, ResetMode, Worktree,
     },
+    signing::CommitSignature,
     status::{
         DiffTreeType, FileStatus, GitStatus, StatusCode, TrackedStatus, TreeDiff, TreeDiffStatus,
         UnmergedStatus,
     },
+    submodule::{Submodule, SubmoduleCommand},
 };
 use gpui::{AsyncApp, BackgroundExecutor, SharedString, Task};
 use ignore::gitignore::GitignoreBuilder;
@@ -73,6 +77,31 @@ impl FakeGitRepositoryState {
     }
 }
 
+impl FakeGitRepositoryState {
+    /// Resolves a ref name or a sha of one of the graph commits to a sha.
+    fn resolve_commit(&self, commit: &str) -> Option<String> {
+        if let Some(sha) = self.refs.get(commit) {
+            return Some(sha.clone());
+        }
+        self.graph_commits
+            .iter()
+            .any(|graph_commit| graph_commit.sha.to_string() == commit)
+            .then(|| commit.to_string())
+    }
+
+    fn check_can_apply_commits(&self, operation: &str, commits: &[String]) -> Result<()> {
+        if !self.unmerged_paths.is_empty() {
+            bail!("{operation} is not possible because you have unmerged files");
+        }
+        for commit in commits {
+            if self.resolve_commit(commit).is_none() {
+                bail!("bad revision '{commit}'");
+            }
+        }
+        Ok(())
+    }
+}
+
 impl FakeGitRepository {
     fn with_state_async<F, T>(&self, write: bool, f: F) -> BoxFuture<'static, Result<T>>
     where
@@ -130,6 +159,23 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
+    fn compare_commits(
+        &self,
+        base: String,
+        head: String,
+        _cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<git::repository::CommitDiff>> {
+        // The fake repository doesn't keep commit trees, so there is nothing to diff.
+        self.with_state_async(false, move |state| {
+            for commit in [&base, &head] {
+                if state.resolve_commit(commit).is_none() {
+                    bail!("unknown revision: {commit}");
+                }
+            }
+            Ok(git::repository::CommitDiff { files: Vec::new() })
+        })
+    }
+
     fn set_index_text(
         &self,
         path: RepoPath,
@@ -454,6 +500,66 @@ impl GitRepository for FakeGitRepository {
         })
     }
 
+    fn cherry_pick(
+        &self,
+        commits: Vec<String>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(true, move |state| {
+            state.check_can_apply_commits("cherry-pick", &commits)
+        })
+    }
+
+    fn cherry_pick_range(
+        &self,
+        first: String,
+        last: String,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(true, move |state| {
+            state.check_can_apply_commits("cherry-pick", &[first, last])
+        })
+    }
+
+    fn revert(
+        &self,
+        commit: String,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(true, move |state| {
+            state.check_can_apply_commits("revert", &[commit])
+        })
+    }
+
+    fn create_tag(
+        &self,
+        name: String,
+        commit: String,
+        _message: Option<String>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(true, move |state| {
+            let sha = state
+                .resolve_commit(&commit)
+                .with_context(|| format!("unknown revision: {commit}"))?;
+            let tag_ref = format!("refs/tags/{name}");
+            if state.refs.contains_key(&tag_ref) {
+                bail!("tag '{name}' already exists");
+            }
+            state.refs.insert(tag_ref, sha);
+            Ok(())
+        })
+    }
+
+    fn delete_tag(&self, name: String) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(true, move |state| {
+            if state.refs.remove(&format!("refs/tags/{name}")).is_none() {
+                bail!("tag '{name}' not found");
+            }
+            Ok(())
+        })
+    }
+
     fn blame(
         &self,
         path: RepoPath,
@@ -568,6 +674,87 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
+    fn rebase_todo(&self, _base: String) -> BoxFuture<'_, Result<Vec<RebaseTodoEntry>>> {
+        unimplemented!()
+    }
+
+    fn rebase_interactive(
+        &self,
+        _base: String,
+        _entries: Vec<RebaseTodoEntry>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn rebase_control(
+        &self,
+        _control: RebaseControl,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn rebase_progress(&self) -> BoxFuture<'_, Option<RebaseProgress>> {
+        async { None }.boxed()
+    }
+
+    fn bisect_start(
+        &self,
+        _good: String,
+        _bad: String,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<Option<SharedString>>> {
+        unimplemented!()
+    }
+
+    fn bisect_mark(
+        &self,
+        _term: BisectTerm,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<Option<SharedString>>> {
+        unimplemented!()
+    }
+
+    fn bisect_run(
+        &self,
+        _command: String,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<Option<SharedString>>> {
+        unimplemented!()
+    }
+
+    fn bisect_reset(&self, _env: Arc<HashMap<String, String>>) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn bisect_progress(&self) -> BoxFuture<'_, Option<BisectProgress>> {
+        async { None }.boxed()
+    }
+
+    fn submodules(&self) -> BoxFuture<'_, Result<Vec<Submodule>>> {
+        async { Ok(Vec::new()) }.boxed()
+    }
+
+    fn submodule_command(
+        &self,
+        _command: SubmoduleCommand,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn verify_commit(&self, _sha: String) -> BoxFuture<'_, Result<Option<CommitSignature>>> {
+        async { Ok(None) }.boxed()
+    }
+
+    fn verify_commits(
+        &self,
+        _shas: Vec<Oid>,
+    ) -> BoxFuture<'_, Result<HashMap<Oid, CommitSignature>>> {
+        async { Ok(HashMap::default()) }.boxed()
+    }
+
     fn commit(
         &self,
         _message: gpui::SharedString,
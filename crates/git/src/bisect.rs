@@ -0,0 +1,158 @@
+//! `git bisect` sessions. Git keeps the state of a bisect in `BISECT_LOG` and the
+//! refs under `refs/bisect/`, so it is read straight from the git dir.
+
+use std::{path::Path, str::FromStr};
+
+use collections::HashSet;
+use gpui::SharedString;
+
+use crate::Oid;
+
+/// How a commit is marked while bisecting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BisectTerm {
+    Good,
+    Bad,
+    Skip,
+}
+
+impl BisectTerm {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Self::Good => "good",
+            Self::Bad => "bad",
+            Self::Skip => "skip",
+        }
+    }
+}
+
+/// Where a bisect in progress has got to.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BisectProgress {
+    /// The newest commit marked bad, if one has been marked yet.
+    pub bad: Option<Oid>,
+    pub good: Vec<Oid>,
+    pub skipped: Vec<Oid>,
+    /// The commits that may still be the first bad one, including `bad` itself.
+    pub remaining: HashSet<Oid>,
+}
+
+impl BisectProgress {
+    /// Reads the marked commits from `git_dir`. `remaining` is left empty, since it
+    /// takes a `git rev-list` to compute.
+    pub fn read(git_dir: &Path) -> Option<Self> {
+        if !is_bisecting(git_dir) {
+            return None;
+        }
+
+        let read_oid = |path: &Path| {
+            std::fs::read_to_string(path)
+                .ok()
+                .and_then(|contents| Oid::from_str(contents.trim()).ok())
+        };
+        let refs_dir = git_dir.join("refs").join("bisect");
+        let mut progress = Self {
+            bad: read_oid(&refs_dir.join("bad")),
+            ..Self::default()
+        };
+        for entry in std::fs::read_dir(&refs_dir).into_iter().flatten().flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with("good-") {
+                progress.good.extend(read_oid(&entry.path()));
+            } else if name.starts_with("skip-") {
+                progress.skipped.extend(read_oid(&entry.path()));
+            }
+        }
+        progress.good.sort_by_key(|oid| oid.to_string());
+        progress.skipped.sort_by_key(|oid| oid.to_string());
+        Some(progress)
+    }
+
+    /// The first bad commit, once every other candidate has been ruled out.
+    pub fn first_bad(&self) -> Option<Oid> {
+        let bad = self.bad?;
+        (self.remaining.len() == 1 && self.remaining.contains(&bad)).then_some(bad)
+    }
+
+    /// The number of commits left to test, not counting skipped ones.
+    pub fn untested_count(&self) -> usize {
+        self.remaining
+            .iter()
+            .filter(|oid| Some(**oid) != self.bad && !self.skipped.contains(oid))
+            .count()
+    }
+}
+
+/// Whether a bisect is in progress in `git_dir`. Git writes `BISECT_LOG` when a
+/// bisect starts and removes it on reset.
+pub fn is_bisecting(git_dir: &Path) -> bool {
+    git_dir.join("BISECT_LOG").is_file()
+}
+
+/// Finds the commit git reports with "<sha> is the first bad commit" once a bisect
+/// has narrowed the range down to one commit.
+pub fn first_bad_commit(output: &str) -> Option<SharedString> {
+    output.lines().find_map(|line| {
+        let sha = line.trim().strip_suffix(" is the first bad commit")?;
+        Oid::from_str(sha).ok()?;
+        Some(SharedString::from(sha.to_string()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_bad_commit() {
+        let output = "\
+            Bisecting: 0 revisions left to test after this (roughly 0 steps)\n\
+            0123456789abcdef0123456789abcdef01234567 is the first bad commit\n\
+            commit 0123456789abcdef0123456789abcdef01234567\n";
+        assert_eq!(
+            first_bad_commit(output).as_deref(),
+            Some("0123456789abcdef0123456789abcdef01234567")
+        );
+        assert_eq!(
+            first_bad_commit("Bisecting: 3 revisions left to test after this (roughly 2 steps)"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_progress() {
+        let git_dir = tempfile::tempdir().unwrap();
+        assert_eq!(BisectProgress::read(git_dir.path()), None);
+
+        let bad = "1111111111111111111111111111111111111111";
+        let good = "2222222222222222222222222222222222222222";
+        let skipped = "3333333333333333333333333333333333333333";
+        let refs_dir = git_dir.path().join("refs").join("bisect");
+        std::fs::create_dir_all(&refs_dir).unwrap();
+        std::fs::write(git_dir.path().join("BISECT_LOG"), "git bisect start\n").unwrap();
+        std::fs::write(refs_dir.join("bad"), format!("{bad}\n")).unwrap();
+        std::fs::write(refs_dir.join(format!("good-{good}")), format!("{good}\n")).unwrap();
+        std::fs::write(
+            refs_dir.join(format!("skip-{skipped}")),
+            format!("{skipped}\n"),
+        )
+        .unwrap();
+
+        let mut progress = BisectProgress::read(git_dir.path()).unwrap();
+        assert_eq!(progress.bad, Some(Oid::from_str(bad).unwrap()));
+        assert_eq!(progress.good, vec![Oid::from_str(good).unwrap()]);
+        assert_eq!(progress.skipped, vec![Oid::from_str(skipped).unwrap()]);
+        assert_eq!(progress.first_bad(), None);
+
+        progress.remaining = [bad, skipped]
+            .into_iter()
+            .map(|sha| Oid::from_str(sha).unwrap())
+            .collect();
+        assert_eq!(progress.untested_count(), 0);
+        assert_eq!(progress.first_bad(), None);
+
+        progress.remaining = HashSet::from_iter([Oid::from_str(bad).unwrap()]);
+        assert_eq!(progress.first_bad(), Some(Oid::from_str(bad).unwrap()));
+    }
+}
@@ -1,10 +1,14 @@
+pub mod bisect;
 pub mod blame;
 pub mod commit;
 mod hosting_provider;
+pub mod rebase;
 mod remote;
 pub mod repository;
+pub mod signing;
 pub mod stash;
 pub mod status;
+pub mod submodule;
 
 pub use crate::hosting_provider::*;
 pub use crate::remote::*;
@@ -101,9 +105,51 @@ actions!(
         Clone,
         /// Adds a file to .gitignore.
         AddToGitignore,
+        /// Continues the rebase in progress.
+        RebaseContinue,
+        /// Skips the commit the rebase in progress stopped at.
+        RebaseSkip,
+        /// Aborts the rebase in progress.
+        RebaseAbort,
+        /// Marks the commit being tested by the bisect in progress as good.
+        BisectGood,
+        /// Marks the commit being tested by the bisect in progress as bad.
+        BisectBad,
+        /// Skips the commit being tested by the bisect in progress.
+        BisectSkip,
+        /// Runs a task on each commit until the bisect in progress finds the first bad one.
+        BisectRun,
+        /// Ends the bisect in progress and checks out the original branch again.
+        BisectReset,
+        /// Initializes the submodules declared in `.gitmodules`.
+        SubmoduleInit,
+        /// Checks out the commits the repository records for its submodules.
+        SubmoduleUpdate,
+        /// Updates submodule remote URLs to match `.gitmodules`.
+        SubmoduleSync,
     ]
 );
 
+/// Opens the interactive rebase editor.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, JsonSchema, Action)]
+#[action(namespace = git)]
+#[serde(deny_unknown_fields)]
+pub struct RebaseInteractive {
+    /// The commit to rebase onto. Commits after it up to HEAD are listed.
+    pub base: String,
+}
+
+/// Starts bisecting the commits between a good and a bad commit.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, JsonSchema, Action)]
+#[action(namespace = git)]
+#[serde(deny_unknown_fields)]
+pub struct BisectStart {
+    /// A commit known to be good.
+    pub good: String,
+    /// A commit known to be bad.
+    pub bad: String,
+}
+
 /// Renames a git branch.
 #[derive(Clone, Debug, Default, PartialEq, Deserialize, JsonSchema, Action)]
 #[action(namespace = git)]
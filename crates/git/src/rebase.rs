@@ -0,0 +1,286 @@
+//! Interactive rebases. The todo list is chosen up front and handed to
+//! `git rebase -i` through `GIT_SEQUENCE_EDITOR`, so git never waits on an editor.
+
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use gpui::SharedString;
+
+/// Directory inside the git dir holding the todo list and reworded messages of a
+/// rebase started from Zed.
+pub const REBASE_SCRATCH_DIR: &str = "zed-rebase";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RebaseAction {
+    #[default]
+    Pick,
+    Reword,
+    Edit,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl RebaseAction {
+    pub const ALL: [Self; 6] = [
+        Self::Pick,
+        Self::Reword,
+        Self::Edit,
+        Self::Squash,
+        Self::Fixup,
+        Self::Drop,
+    ];
+
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Self::Pick => "pick",
+            Self::Reword => "reword",
+            Self::Edit => "edit",
+            Self::Squash => "squash",
+            Self::Fixup => "fixup",
+            Self::Drop => "drop",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Pick => "Pick",
+            Self::Reword => "Reword",
+            Self::Edit => "Edit",
+            Self::Squash => "Squash",
+            Self::Fixup => "Fixup",
+            Self::Drop => "Drop",
+        }
+    }
+
+    /// Whether the action folds the commit into the one before it.
+    pub fn melds(self) -> bool {
+        matches!(self, Self::Squash | Self::Fixup)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebaseTodoEntry {
+    pub sha: SharedString,
+    pub subject: SharedString,
+    /// The commit's full message, which rewording starts from.
+    pub original_message: String,
+    pub action: RebaseAction,
+    /// The new message of a reworded commit. Reworded commits without one keep
+    /// their message.
+    pub message: Option<String>,
+}
+
+impl RebaseTodoEntry {
+    pub fn new(sha: impl Into<SharedString>, subject: impl Into<SharedString>) -> Self {
+        let subject = subject.into();
+        Self {
+            sha: sha.into(),
+            original_message: subject.to_string(),
+            subject,
+            action: RebaseAction::Pick,
+            message: None,
+        }
+    }
+}
+
+/// What to do with a rebase that stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebaseControl {
+    Continue,
+    Skip,
+    Abort,
+}
+
+impl RebaseControl {
+    pub fn flag(self) -> &'static str {
+        match self {
+            Self::Continue => "--continue",
+            Self::Skip => "--skip",
+            Self::Abort => "--abort",
+        }
+    }
+}
+
+/// Where a rebase that is in progress has got to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebaseProgress {
+    /// The branch being rebased, or `None` for a detached HEAD.
+    pub branch: Option<SharedString>,
+    pub onto: Option<SharedString>,
+    /// The 1-based number of the todo step git stopped at.
+    pub step: usize,
+    pub total: usize,
+    /// The commit git stopped at, for conflicts and `edit` steps.
+    pub stopped_at: Option<SharedString>,
+}
+
+impl RebaseProgress {
+    /// Reads the state git keeps in `rebase-merge` (or `rebase-apply`) inside `git_dir`.
+    pub fn read(git_dir: &Path) -> Option<Self> {
+        let read = |dir: &Path, name: &str| {
+            std::fs::read_to_string(dir.join(name))
+                .ok()
+                .map(|contents| contents.trim().to_string())
+                .filter(|contents| !contents.is_empty())
+        };
+
+        let merge_dir = git_dir.join("rebase-merge");
+        let apply_dir = git_dir.join("rebase-apply");
+        let (dir, step, total) = if merge_dir.is_dir() {
+            (
+                merge_dir.clone(),
+                read(&merge_dir, "msgnum"),
+                read(&merge_dir, "end"),
+            )
+        } else if apply_dir.is_dir() {
+            (
+                apply_dir.clone(),
+                read(&apply_dir, "next"),
+                read(&apply_dir, "last"),
+            )
+        } else {
+            return None;
+        };
+
+        let branch = read(&dir, "head-name").map(|head| {
+            SharedString::from(
+                head.strip_prefix("refs/heads/")
+                    .unwrap_or(head.as_str())
+                    .to_string(),
+            )
+        });
+        Some(Self {
+            branch: branch.filter(|branch| branch != "detached HEAD"),
+            onto: read(&dir, "onto").map(SharedString::from),
+            step: step.and_then(|step| step.parse().ok()).unwrap_or(0),
+            total: total.and_then(|total| total.parse().ok()).unwrap_or(0),
+            stopped_at: read(&dir, "stopped-sha").map(SharedString::from),
+        })
+    }
+}
+
+/// Parses `git log --format=%H%x00%s%x00%B%x1e` output into todo entries.
+pub fn parse_todo_log(output: &str) -> Vec<RebaseTodoEntry> {
+    output
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(3, '\0');
+            let sha = fields.next().filter(|sha| !sha.is_empty())?;
+            let subject = fields.next()?;
+            let mut entry = RebaseTodoEntry::new(sha.to_string(), subject.to_string());
+            if let Some(message) = fields.next().map(str::trim_end)
+                && !message.is_empty()
+            {
+                entry.original_message = message.to_string();
+            }
+            Some(entry)
+        })
+        .collect()
+}
+
+/// Builds the todo list git runs. Reworded commits are picked and then amended
+/// with the message stored at `message_path(sha)`.
+pub fn todo_list(entries: &[RebaseTodoEntry], message_path: impl Fn(&str) -> PathBuf) -> String {
+    let mut todo = String::new();
+    for entry in entries {
+        match (entry.action, &entry.message) {
+            (RebaseAction::Reword, Some(_)) => {
+                writeln!(todo, "pick {} {}", entry.sha, entry.subject).ok();
+                writeln!(
+                    todo,
+                    "exec git commit --amend --only --no-verify --quiet --file={}",
+                    shell_quote(&message_path(&entry.sha).to_string_lossy())
+                )
+                .ok();
+            }
+            (action, _) => {
+                writeln!(todo, "{} {} {}", action.keyword(), entry.sha, entry.subject).ok();
+            }
+        }
+    }
+    todo
+}
+
+/// Quotes `text` for the POSIX shell git runs editors and `exec` lines with.
+pub fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_todo_list() {
+        let mut entries = vec![
+            RebaseTodoEntry::new("aaaaaaa", "First"),
+            RebaseTodoEntry::new("bbbbbbb", "Second"),
+            RebaseTodoEntry::new("ccccccc", "Third"),
+            RebaseTodoEntry::new("ddddddd", "Fourth"),
+        ];
+        entries[1].action = RebaseAction::Reword;
+        entries[1].message = Some("Better second".to_string());
+        entries[2].action = RebaseAction::Fixup;
+        entries[3].action = RebaseAction::Drop;
+
+        let todo = todo_list(&entries, |sha| PathBuf::from(format!("/tmp/it's/{sha}")));
+        assert_eq!(
+            todo,
+            "pick aaaaaaa First\n\
+             pick bbbbbbb Second\n\
+             exec git commit --amend --only --no-verify --quiet --file='/tmp/it'\\''s/bbbbbbb'\n\
+             fixup ccccccc Third\n\
+             drop ddddddd Fourth\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_todo_log() {
+        let output = "aaaaaaa\0First\0First\n\nWith a body.\n\x1e\n\
+                      bbbbbbb\0Second\0Second\n\x1e";
+        let entries = parse_todo_log(output);
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (
+                    entry.sha.as_ref(),
+                    entry.subject.as_ref(),
+                    entry.original_message.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("aaaaaaa", "First", "First\n\nWith a body."),
+                ("bbbbbbb", "Second", "Second"),
+            ]
+        );
+        assert!(parse_todo_log("").is_empty());
+    }
+
+    #[test]
+    fn test_read_progress() {
+        let git_dir = tempfile::tempdir().unwrap();
+        assert_eq!(RebaseProgress::read(git_dir.path()), None);
+
+        let merge_dir = git_dir.path().join("rebase-merge");
+        std::fs::create_dir(&merge_dir).unwrap();
+        std::fs::write(merge_dir.join("head-name"), "refs/heads/feature\n").unwrap();
+        std::fs::write(merge_dir.join("onto"), "1234567\n").unwrap();
+        std::fs::write(merge_dir.join("msgnum"), "2\n").unwrap();
+        std::fs::write(merge_dir.join("end"), "5\n").unwrap();
+        std::fs::write(merge_dir.join("stopped-sha"), "abcdef0\n").unwrap();
+
+        assert_eq!(
+            RebaseProgress::read(git_dir.path()),
+            Some(RebaseProgress {
+                branch: Some("feature".into()),
+                onto: Some("1234567".into()),
+                step: 2,
+                total: 5,
+                stopped_at: Some("abcdef0".into()),
+            })
+        );
+    }
+}
@@ -1,6 +1,13 @@
+use crate::bisect::{self, BisectProgress, BisectTerm};
 use crate::commit::parse_git_diff_name_status;
+use crate::rebase::{
+    self, REBASE_SCRATCH_DIR, RebaseAction, RebaseControl, RebaseProgress, RebaseTodoEntry,
+    shell_quote,
+};
+use crate::signing::{self, CommitSignature};
 use crate::stash::GitStash;
 use crate::status::{DiffTreeType, GitStatus, StatusCode, TreeDiff};
+use crate::submodule::{self, Submodule, SubmoduleCommand};
 use crate::{Oid, RunHook, SHORT_SHA_LENGTH};
 use anyhow::{Context as _, Result, anyhow, bail};
 use collections::HashMap;
@@ -620,6 +627,41 @@ pub trait GitRepository: Send + Sync {
 
     fn delete_branch(&self, name: String) -> BoxFuture<'_, Result<()>>;
 
+    /// Applies the given commits or commit ranges on top of HEAD.
+    fn cherry_pick(
+        &self,
+        commits: Vec<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Applies every commit from the older of `first` and `last` up to the newer one,
+    /// both included. The two commits must be on the same line of history.
+    fn cherry_pick_range(
+        &self,
+        first: String,
+        last: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Merge commits are reverted against their first parent, undoing the changes
+    /// the merge brought in.
+    fn revert(
+        &self,
+        commit: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Creates an annotated tag when `message` is given, and a lightweight tag otherwise.
+    fn create_tag(
+        &self,
+        name: String,
+        commit: String,
+        message: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    fn delete_tag(&self, name: String) -> BoxFuture<'_, Result<()>>;
+
     fn worktrees(&self) -> BoxFuture<'_, Result<Vec<Worktree>>>;
 
     fn create_worktree(
@@ -646,6 +688,13 @@ pub trait GitRepository: Send + Sync {
     fn show(&self, commit: String) -> BoxFuture<'_, Result<CommitDetails>>;
 
     fn load_commit(&self, commit: String, cx: AsyncApp) -> BoxFuture<'_, Result<CommitDiff>>;
+    /// Loads the changes between two commits, as if `head` were a commit on top of `base`.
+    fn compare_commits(
+        &self,
+        base: String,
+        head: String,
+        cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<CommitDiff>>;
     fn blame(
         &self,
         path: RepoPath,
@@ -722,6 +771,83 @@ pub trait GitRepository: Send + Sync {
         env: Arc<HashMap<String, String>>,
     ) -> BoxFuture<'_, Result<()>>;
 
+    /// Returns the commits an interactive rebase onto `base` would replay, oldest first.
+    fn rebase_todo(&self, base: String) -> BoxFuture<'_, Result<Vec<RebaseTodoEntry>>>;
+
+    /// Starts an interactive rebase onto `base` that runs `entries` in order.
+    ///
+    /// Stopping for conflicts or an `edit` step isn't an error; see [`Self::rebase_progress`].
+    fn rebase_interactive(
+        &self,
+        base: String,
+        entries: Vec<RebaseTodoEntry>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Continues, skips the current step of, or aborts the rebase in progress.
+    fn rebase_control(
+        &self,
+        control: RebaseControl,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Returns how far the rebase in progress has got, if there is one.
+    fn rebase_progress(&self) -> BoxFuture<'_, Option<RebaseProgress>>;
+
+    /// Starts bisecting between `good` and `bad` and checks out the first commit to test.
+    ///
+    /// Each bisect step returns the first bad commit once git has found it.
+    fn bisect_start(
+        &self,
+        good: String,
+        bad: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<Option<SharedString>>>;
+
+    /// Marks the commit checked out by the bisect in progress and checks out the next one.
+    fn bisect_mark(
+        &self,
+        term: BisectTerm,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<Option<SharedString>>>;
+
+    /// Runs `command` on each commit the bisect in progress checks out, treating exit
+    /// code 0 as good, 125 as skip and anything else as bad.
+    ///
+    /// Git runs the command line with its own `sh`, from the root of the working tree.
+    fn bisect_run(
+        &self,
+        command: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<Option<SharedString>>>;
+
+    /// Ends the bisect in progress and checks out the commit it started from.
+    fn bisect_reset(&self, env: Arc<HashMap<String, String>>) -> BoxFuture<'_, Result<()>>;
+
+    /// Returns the bisect in progress, if there is one.
+    fn bisect_progress(&self) -> BoxFuture<'_, Option<BisectProgress>>;
+
+    /// Lists the submodules declared in `.gitmodules`, with their status.
+    fn submodules(&self) -> BoxFuture<'_, Result<Vec<Submodule>>>;
+
+    /// Initializes, updates or syncs the repository's submodules.
+    fn submodule_command(
+        &self,
+        command: SubmoduleCommand,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Checks the signature of commit `sha` with `git verify-commit`, returning `None` if
+    /// it isn't signed.
+    fn verify_commit(&self, sha: String) -> BoxFuture<'_, Result<Option<CommitSignature>>>;
+
+    /// Checks the signatures of several commits with a single `git log`, leaving unsigned
+    /// commits out of the result.
+    fn verify_commits(
+        &self,
+        shas: Vec<Oid>,
+    ) -> BoxFuture<'_, Result<HashMap<Oid, CommitSignature>>>;
+
     fn push(
         &self,
         branch_name: String,
@@ -832,9 +958,20 @@ pub struct RealGitRepository {
     pub system_git_binary_path: Option<PathBuf>,
     pub any_git_binary_path: PathBuf,
     any_git_binary_help_output: Arc<Mutex<Option<SharedString>>>,
+    #[cfg(unix)]
+    gpg_signing: Arc<Mutex<GpgSigningCache>>,
     executor: BackgroundExecutor,
 }
 
+/// The `gpg.program` wrapper commits are signed with, kept until one of the config
+/// files it was derived from changes.
+#[cfg(unix)]
+#[derive(Default)]
+struct GpgSigningCache {
+    config_mtimes: Option<Vec<Option<std::time::SystemTime>>>,
+    script_path: Option<PathBuf>,
+}
+
 impl RealGitRepository {
     pub fn new(
         dotgit_path: &Path,
@@ -851,7 +988,54 @@ impl RealGitRepository {
             any_git_binary_path,
             executor,
             any_git_binary_help_output: Arc::new(Mutex::new(None)),
+            #[cfg(unix)]
+            gpg_signing: Arc::default(),
+        })
+    }
+
+    /// Returns the `gpg.program` wrapper to sign commits with, or `None` if commits aren't
+    /// signed with OpenPGP. The config is only read again, and the wrapper only rewritten,
+    /// after one of the config files changes.
+    #[cfg(unix)]
+    fn gpg_program_script(&self) -> Result<Option<PathBuf>> {
+        let repo = self.repository.lock();
+        let config_mtimes = [
+            Some(repo.commondir().join("config")),
+            git2::Config::find_global().ok(),
+            git2::Config::find_xdg().ok(),
+            git2::Config::find_system().ok(),
+        ]
+        .into_iter()
+        .map(|path| {
+            std::fs::metadata(path?)
+                .and_then(|metadata| metadata.modified())
+                .ok()
         })
+        .collect::<Vec<_>>();
+
+        let mut cache = self.gpg_signing.lock();
+        if cache.config_mtimes.as_ref() == Some(&config_mtimes)
+            && cache
+                .script_path
+                .as_ref()
+                .is_none_or(|script_path| script_path.exists())
+        {
+            return Ok(cache.script_path.clone());
+        }
+
+        let script_path = match gpg_signing_program(&repo.config()?.snapshot()?) {
+            Some(gpg_program) => {
+                let script_path = repo.path().join(signing::GPG_PROGRAM_SCRIPT);
+                write_gpg_program_script(&script_path, &gpg_program)?;
+                Some(script_path)
+            }
+            None => None,
+        };
+        *cache = GpgSigningCache {
+            config_mtimes: Some(config_mtimes),
+            script_path: script_path.clone(),
+        };
+        Ok(script_path)
     }
 
     fn working_directory(&self) -> Result<PathBuf> {
@@ -1013,108 +1197,63 @@ impl GitRepository for RealGitRepository {
                 .context("starting git show process")?;
 
             let show_stdout = String::from_utf8_lossy(&show_output.stdout);
-            let changes = parse_git_diff_name_status(&show_stdout);
             let parent_sha = format!("{}^", commit);
+            load_commit_files(
+                &git_binary_path,
+                &working_directory,
+                &show_stdout,
+                &parent_sha,
+                &commit,
+            )
+            .await
+        })
+        .boxed()
+    }
 
-            let mut cat_file_process = util::command::new_smol_command(&git_binary_path)
+    fn compare_commits(
+        &self,
+        base: String,
+        head: String,
+        cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<CommitDiff>> {
+        let Some(working_directory) = self.repository.lock().workdir().map(ToOwned::to_owned)
+        else {
+            return future::ready(Err(anyhow!("no working directory"))).boxed();
+        };
+        let git_binary_path = self.any_git_binary_path.clone();
+        cx.background_spawn(async move {
+            let diff_output = util::command::new_smol_command(&git_binary_path)
                 .current_dir(&working_directory)
-                .args(["--no-optional-locks", "cat-file", "--batch=%(objectsize)"])
-                .stdin(Stdio::piped())
+                .args([
+                    "--no-optional-locks",
+                    "diff",
+                    "-z",
+                    "--no-renames",
+                    "--name-status",
+                ])
+                .arg(&base)
+                .arg(&head)
+                .stdin(Stdio::null())
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
-                .spawn()
-                .context("starting git cat-file process")?;
-
-            let mut files = Vec::<CommitFile>::new();
-            let mut stdin = BufWriter::with_capacity(512, cat_file_process.stdin.take().unwrap());
-            let mut stdout = BufReader::new(cat_file_process.stdout.take().unwrap());
-            let mut info_line = String::new();
-            let mut newline = [b'\0'];
-            for (path, status_code) in changes {
-                // git-show outputs `/`-delimited paths even on Windows.
-                let Some(rel_path) = RelPath::unix(path).log_err() else {
-                    continue;
-                };
-
-                match status_code {
-                    StatusCode::Modified => {
-                        stdin.write_all(commit.as_bytes()).await?;
-                        stdin.write_all(b":").await?;
-                        stdin.write_all(path.as_bytes()).await?;
-                        stdin.write_all(b"\n").await?;
-                        stdin.write_all(parent_sha.as_bytes()).await?;
-                        stdin.write_all(b":").await?;
-                        stdin.write_all(path.as_bytes()).await?;
-                        stdin.write_all(b"\n").await?;
-                    }
-                    StatusCode::Added => {
-                        stdin.write_all(commit.as_bytes()).await?;
-                        stdin.write_all(b":").await?;
-                        stdin.write_all(path.as_bytes()).await?;
-                        stdin.write_all(b"\n").await?;
-                    }
-                    StatusCode::Deleted => {
-                        stdin.write_all(parent_sha.as_bytes()).await?;
-                        stdin.write_all(b":").await?;
-                        stdin.write_all(path.as_bytes()).await?;
-                        stdin.write_all(b"\n").await?;
-                    }
-                    _ => continue,
-                }
-                stdin.flush().await?;
-
-                info_line.clear();
-                stdout.read_line(&mut info_line).await?;
-
-                let len = info_line.trim_end().parse().with_context(|| {
-                    format!("invalid object size output from cat-file {info_line}")
-                })?;
-                let mut text_bytes = vec![0; len];
-                stdout.read_exact(&mut text_bytes).await?;
-                stdout.read_exact(&mut newline).await?;
-
-                let mut old_text = None;
-                let mut new_text = None;
-                let mut is_binary = is_binary_content(&text_bytes);
-                let text = if is_binary {
-                    String::new()
-                } else {
-                    String::from_utf8_lossy(&text_bytes).to_string()
-                };
-
-                match status_code {
-                    StatusCode::Modified => {
-                        info_line.clear();
-                        stdout.read_line(&mut info_line).await?;
-                        let len = info_line.trim_end().parse().with_context(|| {
-                            format!("invalid object size output from cat-file {}", info_line)
-                        })?;
-                        let mut parent_bytes = vec![0; len];
-                        stdout.read_exact(&mut parent_bytes).await?;
-                        stdout.read_exact(&mut newline).await?;
-                        is_binary = is_binary || is_binary_content(&parent_bytes);
-                        if is_binary {
-                            old_text = Some(String::new());
-                            new_text = Some(String::new());
-                        } else {
-                            old_text = Some(String::from_utf8_lossy(&parent_bytes).to_string());
-                            new_text = Some(text);
-                        }
-                    }
-                    StatusCode::Added => new_text = Some(text),
-                    StatusCode::Deleted => old_text = Some(text),
-                    _ => continue,
-                }
-
-                files.push(CommitFile {
-                    path: RepoPath(Arc::from(rel_path)),
-                    old_text,
-                    new_text,
-                    is_binary,
-                })
-            }
+                .output()
+                .await
+                .context("starting git diff process")?;
+            anyhow::ensure!(
+                diff_output.status.success(),
+                "Failed to compare commits:\n{}",
+                String::from_utf8_lossy(&diff_output.stderr),
+            );
 
-            Ok(CommitDiff { files })
+            let diff_stdout = String::from_utf8_lossy(&diff_output.stdout);
+            load_commit_files(
+                &git_binary_path,
+                &working_directory,
+                &diff_stdout,
+                &base,
+                &head,
+            )
+            .await
         })
         .boxed()
     }
@@ -1702,6 +1841,160 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
+    fn cherry_pick(
+        &self,
+        commits: Vec<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
+        self.executor
+            .spawn(async move {
+                let mut args = vec!["cherry-pick".to_string()];
+                args.extend(commits);
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs((*env).clone())
+                    .run(&args)
+                    .await?;
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
+
+    fn cherry_pick_range(
+        &self,
+        first: String,
+        last: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
+        self.executor
+            .spawn(async move {
+                let git = GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs((*env).clone());
+                // `merge-base --is-ancestor` exits with 1 when the answer is no.
+                let (older, newer) = if git
+                    .run(["merge-base", "--is-ancestor", &first, &last])
+                    .await
+                    .is_ok()
+                {
+                    (first, last)
+                } else if git
+                    .run(["merge-base", "--is-ancestor", &last, &first])
+                    .await
+                    .is_ok()
+                {
+                    (last, first)
+                } else {
+                    bail!("{first} and {last} are not on the same line of history");
+                };
+
+                let older_parent = format!("{older}^");
+                if git
+                    .run(["rev-parse", "--verify", "--quiet", &older_parent])
+                    .await
+                    .is_ok()
+                {
+                    git.run(["cherry-pick", &format!("{older_parent}..{newer}")])
+                        .await?;
+                } else {
+                    // A root commit has no parent to start the range from, so list
+                    // the commits explicitly instead.
+                    let descendants = git
+                        .run(["rev-list", "--reverse", &format!("{older}..{newer}")])
+                        .await?;
+                    let mut args = vec!["cherry-pick".to_string(), older];
+                    args.extend(descendants.lines().map(ToOwned::to_owned));
+                    git.run(&args).await?;
+                }
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
+
+    fn revert(
+        &self,
+        commit: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
+        self.executor
+            .spawn(async move {
+                let git = GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs((*env).clone());
+                let parents = git
+                    .run(["rev-list", "--parents", "-n", "1", &commit])
+                    .await?;
+                if parents.split_whitespace().count() > 2 {
+                    git.run(["revert", "--no-edit", "--mainline", "1", &commit])
+                        .await?;
+                } else {
+                    git.run(["revert", "--no-edit", &commit]).await?;
+                }
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
+
+    fn create_tag(
+        &self,
+        name: String,
+        commit: String,
+        message: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
+        self.executor
+            .spawn(async move {
+                let git = GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs((*env).clone());
+                // `--` keeps a name like `-f` from being read as an option.
+                match message {
+                    Some(message) => {
+                        git.run(&[
+                            "tag",
+                            "--annotate",
+                            "--message",
+                            &message,
+                            "--",
+                            &name,
+                            &commit,
+                        ])
+                        .await?
+                    }
+                    None => git.run(&["tag", "--", &name, &commit]).await?,
+                };
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
+
+    fn delete_tag(&self, name: String) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
+        self.executor
+            .spawn(async move {
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .run(&["tag", "--delete", "--", &name])
+                    .await?;
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
+
     fn blame(
         &self,
         path: RepoPath,
@@ -2018,33 +2311,342 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
-    fn commit(
+    fn rebase_todo(&self, base: String) -> BoxFuture<'_, Result<Vec<RebaseTodoEntry>>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let range = format!("{base}..HEAD");
+                let output = GitBinary::new(git_binary_path, working_directory?, executor)
+                    .run([
+                        "log",
+                        "--reverse",
+                        "--no-merges",
+                        "--format=%H%x00%s%x00%B%x1e",
+                        range.as_str(),
+                    ])
+                    .await?;
+                Ok(rebase::parse_todo_log(&output))
+            })
+            .boxed()
+    }
+
+    fn rebase_interactive(
         &self,
-        message: SharedString,
-        name_and_email: Option<(SharedString, SharedString)>,
-        options: CommitOptions,
-        ask_pass: AskPassDelegate,
+        base: String,
+        entries: Vec<RebaseTodoEntry>,
         env: Arc<HashMap<String, String>>,
     ) -> BoxFuture<'_, Result<()>> {
-        let working_directory = self.working_directory();
         let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let git_dir = self.path();
         let executor = self.executor.clone();
-        // Note: Do not spawn this command on the background thread, it might pop open the credential helper
-        // which we want to block on.
-        async move {
-            let mut cmd = new_smol_command(git_binary_path);
-            cmd.current_dir(&working_directory?)
-                .envs(env.iter())
-                .args(["commit", "--quiet", "-m"])
-                .arg(&message.to_string())
-                .arg("--cleanup=strip")
-                .arg("--no-verify")
-                .stdout(smol::process::Stdio::piped())
-                .stderr(smol::process::Stdio::piped());
-
-            if options.amend {
-                cmd.arg("--amend");
-            }
+        self.executor
+            .spawn(async move {
+                let scratch_dir = git_dir.join(REBASE_SCRATCH_DIR);
+                std::fs::remove_dir_all(&scratch_dir).ok();
+                std::fs::create_dir_all(&scratch_dir)?;
+                for entry in &entries {
+                    if entry.action == RebaseAction::Reword
+                        && let Some(message) = &entry.message
+                    {
+                        std::fs::write(scratch_dir.join(entry.sha.as_str()), message)?;
+                    }
+                }
+                let todo_path = scratch_dir.join("git-rebase-todo");
+                std::fs::write(
+                    &todo_path,
+                    rebase::todo_list(&entries, |sha| scratch_dir.join(sha)),
+                )?;
+
+                let mut env = rebase_env(&env);
+                env.insert(
+                    "GIT_SEQUENCE_EDITOR".into(),
+                    format!("cp {}", shell_quote(&todo_path.to_string_lossy())),
+                );
+                let git = GitBinary::new(git_binary_path, working_directory?, executor).envs(env);
+                let result = git.run(["rebase", "--interactive", base.as_str()]).await;
+                rebase_result(result, &git, &git_dir).await
+            })
+            .boxed()
+    }
+
+    fn rebase_control(
+        &self,
+        control: RebaseControl,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let git_dir = self.path();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let git = GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs(rebase_env(&env));
+                if control == RebaseControl::Continue && has_unmerged_paths(&git).await? {
+                    anyhow::bail!("Resolve all conflicts before continuing the rebase");
+                }
+                let result = git.run(["rebase", control.flag()]).await;
+                rebase_result(result, &git, &git_dir).await
+            })
+            .boxed()
+    }
+
+    fn rebase_progress(&self) -> BoxFuture<'_, Option<RebaseProgress>> {
+        let git_dir = self.path();
+        self.executor
+            .spawn(async move { RebaseProgress::read(&git_dir) })
+            .boxed()
+    }
+
+    fn bisect_start(
+        &self,
+        good: String,
+        bad: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<Option<SharedString>>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let output = GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs((*env).clone())
+                    .run(["bisect", "start", bad.as_str(), good.as_str(), "--"])
+                    .await?;
+                Ok(bisect::first_bad_commit(&output))
+            })
+            .boxed()
+    }
+
+    fn bisect_mark(
+        &self,
+        term: BisectTerm,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<Option<SharedString>>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let output = GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs((*env).clone())
+                    .run(["bisect", term.keyword()])
+                    .await?;
+                Ok(bisect::first_bad_commit(&output))
+            })
+            .boxed()
+    }
+
+    fn bisect_run(
+        &self,
+        command: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<Option<SharedString>>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let output = GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs((*env).clone())
+                    .run(["bisect", "run", command.as_str()])
+                    .await?;
+                Ok(bisect::first_bad_commit(&output))
+            })
+            .boxed()
+    }
+
+    fn bisect_reset(&self, env: Arc<HashMap<String, String>>) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs((*env).clone())
+                    .run(["bisect", "reset"])
+                    .await?;
+                Ok(())
+            })
+            .boxed()
+    }
+
+    fn submodules(&self) -> BoxFuture<'_, Result<Vec<Submodule>>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                if !working_directory.join(".gitmodules").is_file() {
+                    return Ok(Vec::new());
+                }
+                let git = GitBinary::new(git_binary_path, working_directory.clone(), executor);
+                let mut submodules =
+                    submodule::parse_submodule_status(&git.run(["submodule", "status"]).await?);
+                if submodules.is_empty() {
+                    return Ok(submodules);
+                }
+
+                let mut args = vec![
+                    "status".to_string(),
+                    "--porcelain=v2".to_string(),
+                    "-z".to_string(),
+                    "--ignore-submodules=none".to_string(),
+                    "--".to_string(),
+                ];
+                args.extend(
+                    submodules
+                        .iter()
+                        .map(|submodule| submodule.path.as_unix_str().to_string()),
+                );
+                let dirty = submodule::dirty_submodules(&git.run(args).await?);
+                for submodule in &mut submodules {
+                    submodule.dirty = dirty.contains(&submodule.path);
+                    submodule.detached = submodule.initialized
+                        && submodule::has_detached_head(
+                            &working_directory.join(submodule.path.as_std_path()),
+                        );
+                }
+                Ok(submodules)
+            })
+            .boxed()
+    }
+
+    fn submodule_command(
+        &self,
+        command: SubmoduleCommand,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .envs((*env).clone())
+                    .run(command.args())
+                    .await?;
+                Ok(())
+            })
+            .boxed()
+    }
+
+    fn verify_commit(&self, sha: String) -> BoxFuture<'_, Result<Option<CommitSignature>>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let git = GitBinary::new(git_binary_path, working_directory?, executor);
+                // This fails for unsigned commits and bad signatures alike, so the result is
+                // read from the output rather than the exit status.
+                let output = git
+                    .build_command(["verify-commit", "--raw", sha.as_str()])
+                    .output()
+                    .await?;
+                Ok(signing::parse_verify_commit(&String::from_utf8_lossy(
+                    &output.stderr,
+                )))
+            })
+            .boxed()
+    }
+
+    fn verify_commits(
+        &self,
+        shas: Vec<Oid>,
+    ) -> BoxFuture<'_, Result<HashMap<Oid, CommitSignature>>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let git = GitBinary::new(git_binary_path, working_directory?, executor);
+                let output = git
+                    .run(
+                        [
+                            "log".to_string(),
+                            "--no-walk=unsorted".to_string(),
+                            "--format=%H%x00%G?%x00%GS".to_string(),
+                        ]
+                        .into_iter()
+                        .chain(shas.iter().map(|sha| sha.to_string())),
+                    )
+                    .await?;
+                Ok(signing::parse_signature_statuses(&output))
+            })
+            .boxed()
+    }
+
+    fn bisect_progress(&self) -> BoxFuture<'_, Option<BisectProgress>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let git_dir = self.path();
+        if !bisect::is_bisecting(&git_dir) {
+            return future::ready(None).boxed();
+        }
+        let executor = self.executor.clone();
+        self.executor
+            .spawn(async move {
+                let mut progress = BisectProgress::read(&git_dir)?;
+                if let Some(bad) = progress.bad {
+                    let mut args = vec![bad.to_string(), "--not".to_string()];
+                    args.extend(progress.good.iter().map(|good| good.to_string()));
+                    let output = GitBinary::new(git_binary_path, working_directory.ok()?, executor)
+                        .run(["rev-list".to_string()].into_iter().chain(args))
+                        .await
+                        .log_err()?;
+                    progress.remaining = output
+                        .lines()
+                        .filter_map(|line| Oid::from_str(line.trim()).ok())
+                        .collect();
+                }
+                Some(progress)
+            })
+            .boxed()
+    }
+
+    fn commit(
+        &self,
+        message: SharedString,
+        name_and_email: Option<(SharedString, SharedString)>,
+        options: CommitOptions,
+        ask_pass: AskPassDelegate,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.any_git_binary_path.clone();
+        #[cfg(unix)]
+        let gpg_program_script = self.gpg_program_script();
+        let executor = self.executor.clone();
+        // Note: Do not spawn this command on the background thread, it might pop open the credential helper
+        // which we want to block on.
+        async move {
+            let working_directory = working_directory?;
+            let mut cmd = new_smol_command(&git_binary_path);
+            cmd.current_dir(&working_directory).envs(env.iter());
+
+            // gpg would ask for the signing key's passphrase on a terminal we don't have, so
+            // sign through a wrapper that asks with askpass instead.
+            #[cfg(unix)]
+            if let Some(script_path) = gpg_program_script? {
+                cmd.arg("-c")
+                    .arg(format!("gpg.program={}", script_path.display()));
+            }
+
+            cmd.args(["commit", "--quiet", "-m"])
+                .arg(&message.to_string())
+                .arg("--cleanup=strip")
+                .arg("--no-verify")
+                .stdout(smol::process::Stdio::piped())
+                .stderr(smol::process::Stdio::piped());
+
+            if options.amend {
+                cmd.arg("--amend");
+            }
 
             if options.signoff {
                 cmd.arg("--signoff");
@@ -2987,6 +3589,228 @@ struct GitBinaryCommandError {
     status: ExitStatus,
 }
 
+/// Loads the old and new contents of the files listed in `name_status` (the `-z`
+/// output of `git diff --name-status`) from `old_rev` and `new_rev`.
+async fn load_commit_files(
+    git_binary_path: &Path,
+    working_directory: &Path,
+    name_status: &str,
+    old_rev: &str,
+    new_rev: &str,
+) -> Result<CommitDiff> {
+    let changes = parse_git_diff_name_status(name_status);
+    let gitlinks = if working_directory.join(".gitmodules").is_file() {
+        let raw_output = util::command::new_smol_command(git_binary_path)
+            .current_dir(working_directory)
+            .args([
+                "--no-optional-locks",
+                "diff",
+                "--raw",
+                "-z",
+                "--no-renames",
+                "--no-abbrev",
+                old_rev,
+                new_rev,
+            ])
+            .output()
+            .await
+            .context("starting git diff process")?;
+        submodule::gitlink_changes(&String::from_utf8_lossy(&raw_output.stdout))
+    } else {
+        HashMap::default()
+    };
+    let mut cat_file_process = util::command::new_smol_command(git_binary_path)
+        .current_dir(working_directory)
+        .args(["--no-optional-locks", "cat-file", "--batch=%(objectsize)"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("starting git cat-file process")?;
+
+    let mut files = Vec::<CommitFile>::new();
+    let mut stdin = BufWriter::with_capacity(512, cat_file_process.stdin.take().unwrap());
+    let mut stdout = BufReader::new(cat_file_process.stdout.take().unwrap());
+    let mut info_line = String::new();
+    let mut newline = [b'\0'];
+    for (path, status_code) in changes {
+        // git outputs `/`-delimited paths even on Windows.
+        let Some(rel_path) = RelPath::unix(path).log_err() else {
+            continue;
+        };
+
+        // Submodule pointers aren't objects of this repository, so they're described
+        // by the range of submodule commits they moved across instead.
+        if let Some((old_sha, new_sha)) = gitlinks.get(path) {
+            let summary = util::command::new_smol_command(git_binary_path)
+                .current_dir(working_directory)
+                .args([
+                    "--no-optional-locks",
+                    "diff",
+                    "--submodule=log",
+                    old_rev,
+                    new_rev,
+                    "--",
+                    path,
+                ])
+                .output()
+                .await
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+                .unwrap_or_default();
+            files.push(CommitFile {
+                path: RepoPath(Arc::from(rel_path)),
+                old_text: old_sha.map(|sha| format!("Subproject commit {sha}\n")),
+                new_text: new_sha.map(|sha| format!("Subproject commit {sha}\n{summary}")),
+                is_binary: false,
+            });
+            continue;
+        }
+
+        match status_code {
+            StatusCode::Modified => {
+                stdin.write_all(new_rev.as_bytes()).await?;
+                stdin.write_all(b":").await?;
+                stdin.write_all(path.as_bytes()).await?;
+                stdin.write_all(b"\n").await?;
+                stdin.write_all(old_rev.as_bytes()).await?;
+                stdin.write_all(b":").await?;
+                stdin.write_all(path.as_bytes()).await?;
+                stdin.write_all(b"\n").await?;
+            }
+            StatusCode::Added => {
+                stdin.write_all(new_rev.as_bytes()).await?;
+                stdin.write_all(b":").await?;
+                stdin.write_all(path.as_bytes()).await?;
+                stdin.write_all(b"\n").await?;
+            }
+            StatusCode::Deleted => {
+                stdin.write_all(old_rev.as_bytes()).await?;
+                stdin.write_all(b":").await?;
+                stdin.write_all(path.as_bytes()).await?;
+                stdin.write_all(b"\n").await?;
+            }
+            _ => continue,
+        }
+        stdin.flush().await?;
+
+        info_line.clear();
+        stdout.read_line(&mut info_line).await?;
+
+        let len = info_line
+            .trim_end()
+            .parse()
+            .with_context(|| format!("invalid object size output from cat-file {info_line}"))?;
+        let mut text_bytes = vec![0; len];
+        stdout.read_exact(&mut text_bytes).await?;
+        stdout.read_exact(&mut newline).await?;
+
+        let mut old_text = None;
+        let mut new_text = None;
+        let mut is_binary = is_binary_content(&text_bytes);
+        let text = if is_binary {
+            String::new()
+        } else {
+            String::from_utf8_lossy(&text_bytes).to_string()
+        };
+
+        match status_code {
+            StatusCode::Modified => {
+                info_line.clear();
+                stdout.read_line(&mut info_line).await?;
+                let len = info_line.trim_end().parse().with_context(|| {
+                    format!("invalid object size output from cat-file {}", info_line)
+                })?;
+                let mut parent_bytes = vec![0; len];
+                stdout.read_exact(&mut parent_bytes).await?;
+                stdout.read_exact(&mut newline).await?;
+                is_binary = is_binary || is_binary_content(&parent_bytes);
+                if is_binary {
+                    old_text = Some(String::new());
+                    new_text = Some(String::new());
+                } else {
+                    old_text = Some(String::from_utf8_lossy(&parent_bytes).to_string());
+                    new_text = Some(text);
+                }
+            }
+            StatusCode::Added => new_text = Some(text),
+            StatusCode::Deleted => old_text = Some(text),
+            _ => continue,
+        }
+
+        files.push(CommitFile {
+            path: RepoPath(Arc::from(rel_path)),
+            old_text,
+            new_text,
+            is_binary,
+        })
+    }
+
+    Ok(CommitDiff { files })
+}
+
+/// The environment for rebase commands: squashed commits keep the combined message
+/// instead of waiting on an editor.
+fn rebase_env(env: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut env = env.clone();
+    env.insert("GIT_EDITOR".into(), "true".into());
+    env
+}
+
+/// A rebase that stops for conflicts exits with an error, but the conflicts are
+/// reported through the rebase progress instead. Other failures, like an `exec` step
+/// failing, are still errors.
+async fn rebase_result(result: Result<String>, git: &GitBinary, git_dir: &Path) -> Result<()> {
+    let in_progress = RebaseProgress::read(git_dir).is_some();
+    if !in_progress {
+        std::fs::remove_dir_all(git_dir.join(REBASE_SCRATCH_DIR)).ok();
+    }
+    match result {
+        Err(error) if in_progress => {
+            if has_unmerged_paths(git).await? {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+        result => result.map(|_| ()),
+    }
+}
+
+async fn has_unmerged_paths(git: &GitBinary) -> Result<bool> {
+    let unmerged = git.run(["ls-files", "--unmerged"]).await?;
+    Ok(!unmerged.trim().is_empty())
+}
+
+/// The OpenPGP program git signs commits with, if `commit.gpgsign` is set.
+#[cfg(unix)]
+fn gpg_signing_program(config: &git2::Config) -> Option<String> {
+    if !config.get_bool("commit.gpgsign").unwrap_or(false) {
+        return None;
+    }
+    let format = config.get_string("gpg.format").unwrap_or_default();
+    if signing::SigningFormat::from_config(&format) != signing::SigningFormat::OpenPgp {
+        return None;
+    }
+    let program = config
+        .get_string("gpg.openpgp.program")
+        .or_else(|_| config.get_string("gpg.program"))
+        .unwrap_or_else(|_| "gpg".to_string());
+    Some(program.trim().to_string())
+}
+
+#[cfg(unix)]
+fn write_gpg_program_script(script_path: &Path, gpg_program: &str) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let script = signing::gpg_program_script(gpg_program);
+    if std::fs::read_to_string(script_path).is_ok_and(|existing| existing == script) {
+        return Ok(());
+    }
+    std::fs::write(script_path, script)?;
+    std::fs::set_permissions(script_path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
 async fn run_git_command(
     env: Arc<HashMap<String, String>>,
     ask_pass: AskPassDelegate,
@@ -3319,6 +4143,344 @@ mod tests {
         // );
     }
 
+    async fn commit_file(
+        repo: &RealGitRepository,
+        name: &str,
+        contents: &str,
+        message: &str,
+        cx: &mut TestAppContext,
+    ) -> String {
+        let work_directory = repo.working_directory().unwrap();
+        smol::fs::write(work_directory.join(name), contents)
+            .await
+            .unwrap();
+        repo.stage_paths(vec![repo_path(name)], Arc::new(HashMap::default()))
+            .await
+            .unwrap();
+        repo.commit(
+            message.to_string().into(),
+            None,
+            CommitOptions::default(),
+            AskPassDelegate::new(&mut cx.to_async(), |_, _, _| {}),
+            Arc::new(checkpoint_author_envs()),
+        )
+        .await
+        .unwrap();
+        repo.head_sha().await.unwrap()
+    }
+
+    #[gpui::test]
+    async fn test_interactive_rebase_rewords_and_drops(cx: &mut TestAppContext) {
+        let (repo_dir, repo) = init_test_repository(cx);
+
+        let base = commit_file(&repo, "a", "a", "Initial", cx).await;
+        commit_file(&repo, "b", "b", "Second\n\nWith a body.", cx).await;
+        commit_file(&repo, "c", "c", "Third", cx).await;
+
+        let mut entries = repo.rebase_todo(base.clone()).await.unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.subject.as_ref(), entry.original_message.as_str()))
+                .collect::<Vec<_>>(),
+            [("Second", "Second\n\nWith a body."), ("Third", "Third")]
+        );
+
+        entries[0].action = RebaseAction::Reword;
+        entries[0].message = Some("Reworded\n\nWith a new body.".to_string());
+        entries[1].action = RebaseAction::Drop;
+        repo.rebase_interactive(base.clone(), entries, Arc::new(checkpoint_author_envs()))
+            .await
+            .unwrap();
+
+        assert_eq!(repo.rebase_progress().await, None);
+        assert!(
+            !repo_dir
+                .path()
+                .join(".git")
+                .join(REBASE_SCRATCH_DIR)
+                .exists()
+        );
+        let git_repo = git2::Repository::open(repo_dir.path()).unwrap();
+        let head = git_repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(
+            head.message().unwrap().trim_end(),
+            "Reworded\n\nWith a new body."
+        );
+        assert_eq!(head.parent_id(0).unwrap().to_string(), base);
+        assert!(!repo_dir.path().join("c").exists());
+    }
+
+    #[gpui::test]
+    async fn test_interactive_rebase_stops_for_conflicts(cx: &mut TestAppContext) {
+        let (repo_dir, repo) = init_test_repository(cx);
+        let env = Arc::new(checkpoint_author_envs());
+
+        let base = commit_file(&repo, "file", "0\n", "Initial", cx).await;
+        commit_file(&repo, "file", "1\n", "One", cx).await;
+        commit_file(&repo, "file", "2\n", "Two", cx).await;
+
+        // Replaying the second change before the first conflicts.
+        let mut entries = repo.rebase_todo(base.clone()).await.unwrap();
+        entries.reverse();
+        repo.rebase_interactive(base, entries, env.clone())
+            .await
+            .unwrap();
+        let progress = repo.rebase_progress().await.unwrap();
+        assert_eq!((progress.step, progress.total), (1, 2));
+
+        let error = repo
+            .rebase_control(RebaseControl::Continue, env.clone())
+            .await
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("Resolve all conflicts"),
+            "{error}"
+        );
+
+        repo.rebase_control(RebaseControl::Abort, env)
+            .await
+            .unwrap();
+        assert_eq!(repo.rebase_progress().await, None);
+        assert_eq!(
+            std::fs::read_to_string(repo_dir.path().join("file")).unwrap(),
+            "2\n"
+        );
+    }
+
+    fn init_test_repository(cx: &mut TestAppContext) -> (tempfile::TempDir, RealGitRepository) {
+        disable_git_global_config();
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo = RealGitRepository::new(
+            &repo_dir.path().join(".git"),
+            None,
+            Some("git".into()),
+            cx.executor(),
+        )
+        .unwrap();
+        (repo_dir, repo)
+    }
+
+    fn git_binary(repo_dir: &Path, cx: &mut TestAppContext) -> GitBinary {
+        GitBinary::new("git".into(), repo_dir.to_path_buf(), cx.executor())
+            .envs(checkpoint_author_envs())
+    }
+
+    fn commit_summary(repo_dir: &Path, revision: &str) -> String {
+        let git_repo = git2::Repository::open(repo_dir).unwrap();
+        let commit = git_repo
+            .revparse_single(revision)
+            .unwrap()
+            .peel_to_commit()
+            .unwrap();
+        commit.summary().unwrap().to_string()
+    }
+
+    fn current_branch(repo_dir: &Path) -> String {
+        let git_repo = git2::Repository::open(repo_dir).unwrap();
+        let head = git_repo.head().unwrap();
+        head.shorthand().unwrap().to_string()
+    }
+
+    #[gpui::test]
+    async fn test_cherry_pick_and_revert(cx: &mut TestAppContext) {
+        let (repo_dir, repo) = init_test_repository(cx);
+        let env = Arc::new(checkpoint_author_envs());
+
+        let root = commit_file(&repo, "a", "a", "Initial", cx).await;
+        let main_branch = current_branch(repo_dir.path());
+        repo.create_branch("feature".into(), None).await.unwrap();
+        let b = commit_file(&repo, "b", "b", "Add b", cx).await;
+        let c = commit_file(&repo, "c", "c", "Add c", cx).await;
+        let d = commit_file(&repo, "d", "d", "Add d", cx).await;
+        repo.change_branch(main_branch).await.unwrap();
+
+        // The range is ordered by history, whichever end comes first.
+        repo.cherry_pick_range(d, c.clone(), env.clone())
+            .await
+            .unwrap();
+        assert_eq!(commit_summary(repo_dir.path(), "HEAD"), "Add d");
+        assert_eq!(commit_summary(repo_dir.path(), "HEAD~1"), "Add c");
+        assert_eq!(commit_summary(repo_dir.path(), "HEAD~2"), "Initial");
+        assert!(!repo_dir.path().join("b").exists());
+
+        repo.cherry_pick(vec![b], env.clone()).await.unwrap();
+        assert_eq!(commit_summary(repo_dir.path(), "HEAD"), "Add b");
+        assert!(repo_dir.path().join("b").exists());
+
+        repo.revert("HEAD".into(), env.clone()).await.unwrap();
+        assert_eq!(commit_summary(repo_dir.path(), "HEAD"), "Revert \"Add b\"");
+        assert!(!repo_dir.path().join("b").exists());
+
+        // A range starting at the root commit has no parent to start from.
+        git_binary(repo_dir.path(), cx)
+            .run(["switch", "--orphan", "other"])
+            .await
+            .unwrap();
+        let other_root = commit_file(&repo, "other", "other", "Other root", cx).await;
+        repo.cherry_pick_range(root, c.clone(), env.clone())
+            .await
+            .unwrap();
+        assert_eq!(commit_summary(repo_dir.path(), "HEAD"), "Add c");
+        assert_eq!(commit_summary(repo_dir.path(), "HEAD~1"), "Add b");
+        assert_eq!(commit_summary(repo_dir.path(), "HEAD~2"), "Initial");
+        assert_eq!(commit_summary(repo_dir.path(), "HEAD~3"), "Other root");
+
+        let error = repo
+            .cherry_pick_range(c, other_root, env)
+            .await
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("not on the same line of history"),
+            "{error}"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_revert_merge_commit(cx: &mut TestAppContext) {
+        let (repo_dir, repo) = init_test_repository(cx);
+
+        commit_file(&repo, "a", "a", "Initial", cx).await;
+        let main_branch = current_branch(repo_dir.path());
+        repo.create_branch("feature".into(), None).await.unwrap();
+        commit_file(&repo, "b", "b", "Add b", cx).await;
+        repo.change_branch(main_branch).await.unwrap();
+        commit_file(&repo, "c", "c", "Add c", cx).await;
+        git_binary(repo_dir.path(), cx)
+            .run(["merge", "--no-ff", "--no-edit", "feature"])
+            .await
+            .unwrap();
+
+        repo.revert("HEAD".into(), Arc::new(checkpoint_author_envs()))
+            .await
+            .unwrap();
+        assert!(commit_summary(repo_dir.path(), "HEAD").starts_with("Revert \"Merge"));
+        assert!(!repo_dir.path().join("b").exists());
+        assert!(repo_dir.path().join("c").exists());
+    }
+
+    #[gpui::test]
+    async fn test_create_and_delete_tags(cx: &mut TestAppContext) {
+        let (repo_dir, repo) = init_test_repository(cx);
+        let git_repo = git2::Repository::open(repo_dir.path()).unwrap();
+        // Annotated tags need a tagger, and tags don't take the commit environment.
+        let mut config = git_repo.config().unwrap();
+        config.set_str("user.name", "Zed").unwrap();
+        config.set_str("user.email", "hi@zed.dev").unwrap();
+
+        let first = commit_file(&repo, "a", "a", "Initial", cx).await;
+        let second = commit_file(&repo, "b", "b", "Second", cx).await;
+        let env = Arc::new(HashMap::default());
+
+        repo.create_tag("v1".into(), first.clone(), None, env.clone())
+            .await
+            .unwrap();
+        repo.create_tag(
+            "v2".into(),
+            second.clone(),
+            Some("Release two".into()),
+            env.clone(),
+        )
+        .await
+        .unwrap();
+
+        let v1 = git_repo.find_reference("refs/tags/v1").unwrap();
+        assert!(v1.peel_to_tag().is_err());
+        assert_eq!(v1.peel_to_commit().unwrap().id().to_string(), first);
+        let v2 = git_repo
+            .find_reference("refs/tags/v2")
+            .unwrap()
+            .peel_to_tag()
+            .unwrap();
+        assert_eq!(v2.message().unwrap().trim_end(), "Release two");
+        assert_eq!(v2.target_id().to_string(), second);
+
+        assert!(
+            repo.create_tag("v1".into(), second.clone(), None, env.clone())
+                .await
+                .is_err()
+        );
+        // Names that look like options are rejected rather than parsed as flags.
+        assert!(
+            repo.create_tag("-f".into(), second.clone(), None, env.clone())
+                .await
+                .is_err()
+        );
+        assert!(
+            git_repo
+                .find_reference(&format!("refs/tags/{second}"))
+                .is_err()
+        );
+
+        repo.delete_tag("v1".into()).await.unwrap();
+        assert!(git_repo.find_reference("refs/tags/v1").is_err());
+        assert!(repo.delete_tag("v1".into()).await.is_err());
+        assert!(repo.delete_tag("-d".into()).await.is_err());
+    }
+
+    #[gpui::test]
+    async fn test_compare_commits(cx: &mut TestAppContext) {
+        let (repo_dir, repo) = init_test_repository(cx);
+
+        commit_file(&repo, "a", "1\n", "Initial", cx).await;
+        let base = commit_file(&repo, "b", "b\n", "Add b", cx).await;
+        commit_file(&repo, "a", "2\n", "Middle", cx).await;
+        std::fs::remove_file(repo_dir.path().join("b")).unwrap();
+        std::fs::write(repo_dir.path().join("c"), "c\n").unwrap();
+        repo.stage_paths(
+            vec![repo_path("b"), repo_path("c")],
+            Arc::new(HashMap::default()),
+        )
+        .await
+        .unwrap();
+        let head = commit_file(&repo, "a", "3\n", "Head", cx).await;
+
+        let files = |diff: CommitDiff| {
+            let mut files = diff
+                .files
+                .into_iter()
+                .map(|file| {
+                    (
+                        file.path.as_unix_str().to_string(),
+                        file.old_text,
+                        file.new_text,
+                    )
+                })
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+        let text = |text: &str| Some(text.to_string());
+
+        let comparison = repo
+            .compare_commits(base, head.clone(), cx.to_async())
+            .await
+            .unwrap();
+        assert_eq!(
+            files(comparison),
+            [
+                ("a".to_string(), text("1\n"), text("3\n")),
+                ("b".to_string(), text("b\n"), None),
+                ("c".to_string(), None, text("c\n")),
+            ]
+        );
+
+        let commit = repo.load_commit(head, cx.to_async()).await.unwrap();
+        assert_eq!(
+            files(commit),
+            [
+                ("a".to_string(), text("2\n"), text("3\n")),
+                ("b".to_string(), text("b\n"), None),
+                ("c".to_string(), None, text("c\n")),
+            ]
+        );
+    }
+
     #[gpui::test]
     async fn test_checkpoint_empty_repo(cx: &mut TestAppContext) {
         disable_git_global_config();
@@ -0,0 +1,265 @@
+//! Commit signatures: making GPG signing work without a terminal, and reading the
+//! output of `git verify-commit --raw`.
+
+use collections::HashMap;
+use gpui::SharedString;
+
+use crate::{Oid, rebase::shell_quote};
+
+/// The name of the `gpg.program` wrapper written to the git dir when signing commits.
+pub const GPG_PROGRAM_SCRIPT: &str = "zed-gpg-sign";
+
+/// How trustworthy a commit's signature is, from best to worst.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SignatureStatus {
+    /// The signature is good and made by a known key.
+    Verified,
+    /// The signature couldn't be checked, e.g. because the key isn't known.
+    Unknown,
+    /// The signature is bad, or made by an expired or revoked key.
+    Unverified,
+}
+
+impl SignatureStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Verified => "Verified",
+            Self::Unknown => "Unknown",
+            Self::Unverified => "Unverified",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitSignature {
+    pub status: SignatureStatus,
+    /// Who signed the commit, as the key's user ID or the SSH principal.
+    pub signer: Option<SharedString>,
+}
+
+/// The value of `gpg.format`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SigningFormat {
+    #[default]
+    OpenPgp,
+    X509,
+    Ssh,
+}
+
+impl SigningFormat {
+    pub fn from_config(value: &str) -> Self {
+        match value.trim() {
+            "ssh" => Self::Ssh,
+            "x509" => Self::X509,
+            _ => Self::OpenPgp,
+        }
+    }
+}
+
+/// A `gpg.program` that signs with `gpg_program` in batch mode, asking for the key's
+/// passphrase through `$GIT_ASKPASS` when gpg-agent doesn't have it cached.
+///
+/// SSH signing doesn't need this, since `ssh-keygen` already uses `SSH_ASKPASS`.
+pub fn gpg_program_script(gpg_program: &str) -> String {
+    let gpg = shell_quote(gpg_program);
+    format!(
+        r#"#!/bin/sh
+dir=$(mktemp -d) || exit 1
+trap 'rm -rf "$dir"' EXIT
+cat > "$dir/payload"
+if {gpg} --batch --pinentry-mode error "$@" < "$dir/payload" 2> "$dir/status"; then
+    cat "$dir/status" >&2
+    exit 0
+fi
+[ -n "$GIT_ASKPASS" ] || exit 1
+passphrase=$("$GIT_ASKPASS" "Passphrase for signing key:") || exit 1
+# printf is a builtin, so the passphrase is piped to gpg without touching the disk or
+# showing up in a process listing. The payload is read from fd 3 instead of stdin.
+printf '%s\n' "$passphrase" |
+    {gpg} --batch --pinentry-mode loopback --passphrase-fd 0 --enable-special-filenames "$@" '-&3' 3< "$dir/payload"
+"#
+    )
+}
+
+/// Reads the output of `git verify-commit --raw`, which is GPG's status lines for
+/// OpenPGP and X.509 signatures and `ssh-keygen`'s messages for SSH ones.
+///
+/// Returns `None` for commits that aren't signed.
+pub fn parse_verify_commit(output: &str) -> Option<CommitSignature> {
+    let mut signature: Option<CommitSignature> = None;
+    for line in output.lines() {
+        let line = line.trim();
+        let (status, signer) = if let Some(status_line) = line.strip_prefix("[GNUPG:] ") {
+            let (keyword, rest) = status_line.split_once(' ').unwrap_or((status_line, ""));
+            // These are followed by the key ID and then the user ID.
+            let user_id = rest
+                .split_once(' ')
+                .map(|(_, user_id)| SharedString::from(user_id.to_string()));
+            match keyword {
+                "GOODSIG" => (SignatureStatus::Verified, user_id),
+                "BADSIG" | "EXPSIG" | "EXPKEYSIG" | "REVKEYSIG" => {
+                    (SignatureStatus::Unverified, user_id)
+                }
+                "ERRSIG" | "NO_PUBKEY" => (SignatureStatus::Unknown, None),
+                _ => continue,
+            }
+        } else if let Some(rest) = line.strip_prefix("Good \"git\" signature") {
+            let signer = rest
+                .strip_prefix(" for ")
+                .and_then(|rest| rest.split_once(" with "))
+                .map(|(principal, _)| SharedString::from(principal.to_string()));
+            (SignatureStatus::Verified, signer)
+        } else if line.starts_with("No principal matched")
+            || line.contains("gpg.ssh.allowedSignersFile needs to be configured")
+        {
+            (SignatureStatus::Unknown, None)
+        } else if line.starts_with("Could not verify signature")
+            || line.starts_with("Signature verification failed")
+        {
+            (SignatureStatus::Unverified, None)
+        } else {
+            continue;
+        };
+
+        // The worst status wins, keeping the signer from whichever line named one.
+        match &mut signature {
+            Some(signature) => {
+                signature.status = signature.status.max(status);
+                if signature.signer.is_none() {
+                    signature.signer = signer;
+                }
+            }
+            None => signature = Some(CommitSignature { status, signer }),
+        }
+    }
+    signature
+}
+
+/// Reads the output of `git log --format=%H%x00%G?%x00%GS`, leaving out commits that
+/// aren't signed.
+pub fn parse_signature_statuses(output: &str) -> HashMap<Oid, CommitSignature> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let sha = fields.next()?.trim().parse::<Oid>().ok()?;
+            // `U` is a good signature from a key that isn't trusted, which `GOODSIG` also
+            // reports as verified.
+            let status = match fields.next()? {
+                "G" | "U" => SignatureStatus::Verified,
+                "B" | "X" | "Y" | "R" => SignatureStatus::Unverified,
+                "E" => SignatureStatus::Unknown,
+                _ => return None,
+            };
+            let signer = fields
+                .next()
+                .map(str::trim)
+                .filter(|signer| !signer.is_empty())
+                .map(|signer| SharedString::from(signer.to_string()));
+            Some((sha, CommitSignature { status, signer }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gpg_verify_commit() {
+        let output = concat!(
+            "[GNUPG:] NEWSIG\n",
+            "[GNUPG:] KEY_CONSIDERED 0123456789ABCDEF0123456789ABCDEF01234567 0\n",
+            "[GNUPG:] SIG_ID abcdef 2024-01-01 1704067200\n",
+            "[GNUPG:] GOODSIG 0123456789ABCDEF Jane Doe <jane@example.com>\n",
+            "[GNUPG:] VALIDSIG 0123456789ABCDEF0123456789ABCDEF01234567 2024-01-01\n",
+            "[GNUPG:] TRUST_ULTIMATE 0 pgp\n",
+        );
+        assert_eq!(
+            parse_verify_commit(output),
+            Some(CommitSignature {
+                status: SignatureStatus::Verified,
+                signer: Some("Jane Doe <jane@example.com>".into()),
+            })
+        );
+
+        let output = concat!(
+            "[GNUPG:] NEWSIG\n",
+            "[GNUPG:] ERRSIG 0123456789ABCDEF 1 10 00 1704067200 9 -\n",
+            "[GNUPG:] NO_PUBKEY 0123456789ABCDEF\n",
+        );
+        assert_eq!(
+            parse_verify_commit(output).map(|signature| signature.status),
+            Some(SignatureStatus::Unknown)
+        );
+
+        let output = "[GNUPG:] EXPKEYSIG 0123456789ABCDEF Jane Doe <jane@example.com>\n";
+        assert_eq!(
+            parse_verify_commit(output).map(|signature| signature.status),
+            Some(SignatureStatus::Unverified)
+        );
+
+        assert_eq!(parse_verify_commit("error: no signature found\n"), None);
+    }
+
+    #[test]
+    fn test_parse_ssh_verify_commit() {
+        let output = "Good \"git\" signature for jane@example.com with ED25519 key SHA256:abc\n";
+        assert_eq!(
+            parse_verify_commit(output),
+            Some(CommitSignature {
+                status: SignatureStatus::Verified,
+                signer: Some("jane@example.com".into()),
+            })
+        );
+
+        let output = concat!(
+            "Good \"git\" signature with ED25519 key SHA256:abc\n",
+            "No principal matched.\n",
+        );
+        assert_eq!(
+            parse_verify_commit(output),
+            Some(CommitSignature {
+                status: SignatureStatus::Unknown,
+                signer: None,
+            })
+        );
+
+        let output = "error: gpg.ssh.allowedSignersFile needs to be configured and exist for ssh signature verification\n";
+        assert_eq!(
+            parse_verify_commit(output).map(|signature| signature.status),
+            Some(SignatureStatus::Unknown)
+        );
+    }
+
+    #[test]
+    fn test_parse_signature_statuses() {
+        let good = "1111111111111111111111111111111111111111";
+        let bad = "2222222222222222222222222222222222222222";
+        let missing_key = "3333333333333333333333333333333333333333";
+        let unsigned = "4444444444444444444444444444444444444444";
+        let output = format!(
+            "{good}\0G\0Jane Doe <jane@example.com>\n{bad}\0B\0Jane Doe <jane@example.com>\n{missing_key}\0E\0\n{unsigned}\0N\0\n"
+        );
+        let signatures = parse_signature_statuses(&output);
+        assert_eq!(signatures.len(), 3);
+        assert_eq!(
+            signatures[&good.parse::<Oid>().unwrap()],
+            CommitSignature {
+                status: SignatureStatus::Verified,
+                signer: Some("Jane Doe <jane@example.com>".into()),
+            }
+        );
+        assert_eq!(
+            signatures[&bad.parse::<Oid>().unwrap()].status,
+            SignatureStatus::Unverified
+        );
+        assert_eq!(
+            signatures[&missing_key.parse::<Oid>().unwrap()],
+            CommitSignature {
+                status: SignatureStatus::Unknown,
+                signer: None,
+            }
+        );
+    }
+}
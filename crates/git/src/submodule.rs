@@ -0,0 +1,267 @@
+//! Submodules of a repository, as reported by `git submodule status` and
+//! `git status --porcelain=v2`.
+
+use std::{path::Path, str::FromStr};
+
+use collections::{HashMap, HashSet};
+
+use crate::{Oid, repository::RepoPath};
+
+/// The mode git records submodules (gitlinks) with in trees.
+const GITLINK_MODE: &str = "160000";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmoduleCommand {
+    Init,
+    Update,
+    Sync,
+}
+
+impl SubmoduleCommand {
+    pub fn args(self) -> &'static [&'static str] {
+        match self {
+            Self::Init => &["submodule", "init"],
+            Self::Update => &["submodule", "update", "--init", "--recursive"],
+            Self::Sync => &["submodule", "sync", "--recursive"],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Submodule {
+    pub path: RepoPath,
+    /// The commit the superproject records for the submodule.
+    pub sha: Oid,
+    pub initialized: bool,
+    /// Whether the commit checked out in the submodule differs from the recorded one.
+    pub out_of_date: bool,
+    pub conflicted: bool,
+    /// Whether the submodule has uncommitted or untracked changes.
+    pub dirty: bool,
+    /// Whether the submodule's HEAD isn't on a branch.
+    pub detached: bool,
+}
+
+impl Submodule {
+    /// Describes what needs attention, e.g. "dirty, out of date", or `None` for a clean
+    /// submodule on a branch.
+    pub fn describe(&self) -> Option<String> {
+        if !self.initialized {
+            return Some("not initialized".to_string());
+        }
+        let labels = [
+            (self.conflicted, "conflicted"),
+            (self.dirty, "dirty"),
+            (self.out_of_date, "out of date"),
+            (self.detached, "detached"),
+        ]
+        .into_iter()
+        .filter_map(|(is_set, label)| is_set.then_some(label))
+        .collect::<Vec<_>>();
+        (!labels.is_empty()).then(|| labels.join(", "))
+    }
+}
+
+/// Parses `git submodule status`, whose lines look like `+<sha> <path> (<describe>)`.
+pub fn parse_submodule_status(output: &str) -> Vec<Submodule> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut chars = line.chars();
+            let flag = chars.next()?;
+            let (sha, rest) = chars.as_str().split_once(' ')?;
+            let path = match rest.rfind(" (") {
+                Some(ix) if rest.ends_with(')') => &rest[..ix],
+                _ => rest,
+            };
+            Some(Submodule {
+                path: RepoPath::new(path).ok()?,
+                sha: Oid::from_str(sha).ok()?,
+                initialized: flag != '-',
+                out_of_date: flag == '+',
+                conflicted: flag == 'U',
+                dirty: false,
+                detached: false,
+            })
+        })
+        .collect()
+}
+
+/// Finds the submodules with modified or untracked files in the output of
+/// `git status --porcelain=v2 -z`.
+pub fn dirty_submodules(output: &str) -> HashSet<RepoPath> {
+    let mut dirty = HashSet::default();
+    let mut entries = output.split('\0');
+    while let Some(entry) = entries.next() {
+        let fields = entry.splitn(9, ' ').collect::<Vec<_>>();
+        match fields.as_slice() {
+            ["1", _, submodule_state, .., path] if fields.len() == 9 => {
+                let state = submodule_state.as_bytes();
+                if state.len() == 4
+                    && state[0] == b'S'
+                    && (state[2] == b'M' || state[3] == b'U')
+                    && let Ok(path) = RepoPath::new(*path)
+                {
+                    dirty.insert(path);
+                }
+            }
+            // Renames are followed by the path they were renamed from.
+            ["2", ..] => {
+                entries.next();
+            }
+            _ => {}
+        }
+    }
+    dirty
+}
+
+/// Whether the submodule checked out at `submodule_dir` has a detached HEAD.
+pub fn has_detached_head(submodule_dir: &Path) -> bool {
+    let dot_git = submodule_dir.join(".git");
+    let git_dir = match std::fs::read_to_string(&dot_git) {
+        Ok(contents) => match contents.trim().strip_prefix("gitdir:") {
+            Some(git_dir) => submodule_dir.join(git_dir.trim()),
+            None => return false,
+        },
+        Err(_) => dot_git,
+    };
+    std::fs::read_to_string(git_dir.join("HEAD"))
+        .is_ok_and(|head| !head.trim_start().starts_with("ref:"))
+}
+
+/// Finds the submodule pointers changed in the output of `git diff --raw -z --no-abbrev`,
+/// with the commits they pointed at before and after.
+pub fn gitlink_changes(output: &str) -> HashMap<String, (Option<Oid>, Option<Oid>)> {
+    let mut changes = HashMap::default();
+    let mut entries = output.split('\0');
+    while let Some(entry) = entries.next() {
+        let Some(entry) = entry.strip_prefix(':') else {
+            continue;
+        };
+        let Some(path) = entries.next() else {
+            break;
+        };
+        let [old_mode, new_mode, old_sha, new_sha, ..] = entry.split(' ').collect::<Vec<_>>()[..]
+        else {
+            continue;
+        };
+        if old_mode != GITLINK_MODE && new_mode != GITLINK_MODE {
+            continue;
+        }
+        let sha = |mode: &str, sha: &str| {
+            if mode == GITLINK_MODE {
+                Oid::from_str(sha).ok()
+            } else {
+                None
+            }
+        };
+        changes.insert(
+            path.to_string(),
+            (sha(old_mode, old_sha), sha(new_mode, new_sha)),
+        );
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_submodule_status() {
+        let output = concat!(
+            " 1111111111111111111111111111111111111111 vendor/clean (v1.0)\n",
+            "+2222222222222222222222222222222222222222 vendor/with space (heads/main)\n",
+            "-3333333333333333333333333333333333333333 vendor/uninitialized\n",
+        );
+        let submodules = parse_submodule_status(output);
+        assert_eq!(
+            submodules
+                .iter()
+                .map(|submodule| (
+                    submodule.path.as_unix_str().to_string(),
+                    submodule.describe()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("vendor/clean".to_string(), None),
+                (
+                    "vendor/with space".to_string(),
+                    Some("out of date".to_string())
+                ),
+                (
+                    "vendor/uninitialized".to_string(),
+                    Some("not initialized".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dirty_submodules() {
+        let sha = "1111111111111111111111111111111111111111";
+        let output = [
+            format!("1 .M SCM. 160000 160000 160000 {sha} {sha} vendor/modified"),
+            format!("1 .M S..U 160000 160000 160000 {sha} {sha} vendor/untracked"),
+            format!("1 .M SC.. 160000 160000 160000 {sha} {sha} vendor/moved"),
+            format!("2 R. N... 100644 100644 100644 {sha} {sha} R100 renamed"),
+            "vendor/original".to_string(),
+            "? untracked.txt".to_string(),
+        ]
+        .join("\0");
+        let mut dirty = dirty_submodules(&output)
+            .into_iter()
+            .map(|path| path.as_unix_str().to_string())
+            .collect::<Vec<_>>();
+        dirty.sort();
+        assert_eq!(dirty, vec!["vendor/modified", "vendor/untracked"]);
+    }
+
+    #[test]
+    fn test_gitlink_changes() {
+        let old = "1111111111111111111111111111111111111111";
+        let new = "2222222222222222222222222222222222222222";
+        let zero = "0000000000000000000000000000000000000000";
+        let output = [
+            format!(":160000 160000 {old} {new} M"),
+            "vendor/lib".to_string(),
+            format!(":100644 100644 {old} {new} M"),
+            "src/main.rs".to_string(),
+            format!(":000000 160000 {zero} {new} A"),
+            "vendor/added".to_string(),
+        ]
+        .join("\0");
+        let changes = gitlink_changes(&output);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            changes["vendor/lib"],
+            (
+                Some(Oid::from_str(old).unwrap()),
+                Some(Oid::from_str(new).unwrap())
+            )
+        );
+        assert_eq!(
+            changes["vendor/added"],
+            (None, Some(Oid::from_str(new).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_has_detached_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let submodule_dir = dir.path().join("sub");
+        let git_dir = dir.path().join("modules").join("sub");
+        std::fs::create_dir_all(&submodule_dir).unwrap();
+        std::fs::create_dir_all(&git_dir).unwrap();
+        std::fs::write(submodule_dir.join(".git"), "gitdir: ../modules/sub\n").unwrap();
+
+        std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        assert!(!has_detached_head(&submodule_dir));
+        std::fs::write(
+            git_dir.join("HEAD"),
+            "1111111111111111111111111111111111111111\n",
+        )
+        .unwrap();
+        assert!(has_detached_head(&submodule_dir));
+    }
+}
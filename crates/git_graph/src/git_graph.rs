@@ -2,15 +2,24 @@ use collections::{BTreeMap, HashMap};
 use feature_flags::{FeatureFlag, FeatureFlagAppExt as _};
 use git::{
     BuildCommitPermalinkParams, GitHostingProviderRegistry, GitRemote, Oid, ParsedGitRemote,
+    bisect::BisectProgress,
     parse_git_remote_url,
     repository::{CommitDiff, InitialGraphCommitData, LogOrder, LogSource},
+    signing::CommitSignature,
+};
+use git_ui::{
+    commit_tooltip::CommitAvatar,
+    commit_view::CommitView,
+    create_ref_modal::{RefKind, create_ref_modal},
+    multi_diff_view::MultiDiffView,
+    signature_badge::signature_badge,
 };
-use git_ui::commit_tooltip::CommitAvatar;
 use gpui::{
-    AnyElement, App, Bounds, ClipboardItem, Context, Corner, DefiniteLength, ElementId, Entity,
-    EventEmitter, FocusHandle, Focusable, FontWeight, Hsla, InteractiveElement, ParentElement,
-    PathBuilder, Pixels, Point, Render, ScrollWheelEvent, SharedString, Styled, Subscription, Task,
-    WeakEntity, Window, actions, anchored, deferred, point, px,
+    Action, AnyElement, App, Bounds, ClipboardItem, Context, Corner, DefiniteLength, DismissEvent,
+    ElementId, Entity, EventEmitter, FocusHandle, Focusable, FontWeight, Hsla, InteractiveElement,
+    MouseButton, MouseDownEvent, ParentElement, PathBuilder, Pixels, Point, Render,
+    ScrollWheelEvent, SharedString, Styled, Subscription, Task, WeakEntity, Window, actions,
+    anchored, deferred, point, px,
 };
 use project::{
     Project,
@@ -28,6 +37,7 @@ use ui::{
 use workspace::{
     Workspace,
     item::{Item, ItemEvent, SerializableItem},
+    notifications::DetachAndPromptErr,
 };
 
 pub struct GitGraphFeatureFlag;
@@ -585,6 +595,11 @@ pub struct GitGraph {
     horizontal_scroll_offset: Pixels,
     graph_viewport_width: Pixels,
     selected_entry_idx: Option<usize>,
+    /// The commit marked as the base of a comparison or of a cherry-picked range.
+    marked_entry_idx: Option<usize>,
+    /// Signatures of the commits that have been scrolled into view, `None` while being verified
+    /// and for unsigned commits.
+    commit_signatures: HashMap<Oid, Option<CommitSignature>>,
     log_source: LogSource,
     log_order: LogOrder,
     selected_commit_diff: Option<CommitDiff>,
@@ -624,6 +639,8 @@ impl GitGraph {
             }
             GitStoreEvent::ActiveRepositoryChanged(_) => {
                 this.graph_data.clear();
+                this.marked_entry_idx = None;
+                this.commit_signatures.clear();
                 cx.notify();
             }
             _ => {}
@@ -671,6 +688,8 @@ impl GitGraph {
             horizontal_scroll_offset: px(0.),
             graph_viewport_width: px(88.),
             selected_entry_idx: None,
+            marked_entry_idx: None,
+            commit_signatures: HashMap::default(),
             selected_commit_diff: None,
             log_source,
             log_order,
@@ -701,6 +720,7 @@ impl GitGraph {
             }
             RepositoryEvent::BranchChanged => {
                 self.graph_data.clear();
+                self.marked_entry_idx = None;
                 cx.notify();
             }
             _ => {}
@@ -754,6 +774,7 @@ impl GitGraph {
                         repository.fetch_commit_data(commit.data.sha, cx);
                     });
             });
+            self.verify_signatures(range.clone(), repository, cx);
         }
 
         range
@@ -794,7 +815,12 @@ impl GitGraph {
                     .copied()
                     .unwrap_or_else(|| accent_colors.0.first().copied().unwrap_or_default());
                 let is_selected = self.selected_entry_idx == Some(idx);
-                let text_color = if is_selected {
+                let bisect_marker = repository
+                    .read(cx)
+                    .bisect
+                    .as_ref()
+                    .and_then(|bisect| bisect_marker(bisect, commit.data.sha));
+                let text_color = if is_selected || bisect_marker.is_some() {
                     Color::Default
                 } else {
                     Color::Muted
@@ -819,6 +845,26 @@ impl GitGraph {
                                             .map(|name| self.render_badge(name, accent_color)),
                                     )
                                 }))
+                                .children(bisect_marker.map(|(label, color)| {
+                                    Label::new(label)
+                                        .size(LabelSize::XSmall)
+                                        .color(color)
+                                        .single_line()
+                                }))
+                                .children(
+                                    self.commit_signatures
+                                        .get(&commit.data.sha)
+                                        .and_then(Option::as_ref)
+                                        .map(|signature| {
+                                            signature_badge(
+                                                ElementId::NamedInteger(
+                                                    "commit-signature".into(),
+                                                    idx as u64,
+                                                ),
+                                                signature,
+                                            )
+                                        }),
+                                )
                                 .child(
                                     Label::new(subject)
                                         .color(text_color)
@@ -844,6 +890,47 @@ impl GitGraph {
             .collect()
     }
 
+    /// Checks the signatures of the commits in `range` that haven't been checked yet, all
+    /// with one git invocation.
+    fn verify_signatures(
+        &mut self,
+        range: Range<usize>,
+        repository: &Entity<Repository>,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.project.read(cx).git_store().read(cx).is_local() {
+            return;
+        }
+        let commit_count = self.graph_data.commits.len();
+        let shas = self.graph_data.commits
+            [range.start.min(commit_count)..range.end.min(commit_count)]
+            .iter()
+            .map(|commit| commit.data.sha)
+            .filter(|sha| !self.commit_signatures.contains_key(sha))
+            .collect::<Vec<_>>();
+        if shas.is_empty() {
+            return;
+        }
+        for sha in &shas {
+            self.commit_signatures.insert(*sha, None);
+        }
+        let signatures = repository.update(cx, |repository, _| repository.verify_commits(shas));
+        cx.spawn(async move |this, cx| {
+            if let Ok(Ok(signatures)) = signatures.await {
+                this.update(cx, |this, cx| {
+                    this.commit_signatures.extend(
+                        signatures
+                            .into_iter()
+                            .map(|(sha, signature)| (sha, Some(signature))),
+                    );
+                    cx.notify();
+                })
+                .ok();
+            }
+        })
+        .detach();
+    }
+
     fn select_entry(&mut self, idx: usize, cx: &mut Context<Self>) {
         if self.selected_entry_idx == Some(idx) {
             return;
@@ -880,6 +967,352 @@ impl GitGraph {
         cx.notify();
     }
 
+    fn deploy_context_menu(
+        &mut self,
+        position: Point<Pixels>,
+        idx: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(commit) = self.graph_data.commits.get(idx) else {
+            return;
+        };
+        let sha = commit.data.sha.to_string();
+        let tags = commit
+            .data
+            .ref_names
+            .iter()
+            .filter_map(|name| name.strip_prefix("tag: "))
+            .map(|tag| tag.to_string())
+            .collect::<Vec<_>>();
+        let marked = self
+            .marked_entry_idx
+            .filter(|marked_idx| *marked_idx != idx)
+            .and_then(|marked_idx| self.graph_data.commits.get(marked_idx))
+            .map(|marked| marked.data.sha.to_string());
+        let is_marked = self.marked_entry_idx == Some(idx);
+        let is_bisecting = self
+            .active_repository(cx)
+            .is_some_and(|repository| repository.read(cx).bisect.is_some());
+        let this = cx.weak_entity();
+        self.select_entry(idx, cx);
+
+        let context_menu = ContextMenu::build(window, cx, |context_menu, _, _| {
+            let mut context_menu = context_menu
+                .context(self.focus_handle.clone())
+                .action(
+                    "Interactive Rebase onto This Commit",
+                    git::RebaseInteractive { base: sha.clone() }.boxed_clone(),
+                )
+                .separator()
+                .entry("Cherry-Pick Commit", None, {
+                    let this = this.clone();
+                    let sha = sha.clone();
+                    move |window, cx| {
+                        this.update(cx, |this, cx| {
+                            this.cherry_pick(vec![sha.clone()], window, cx)
+                        })
+                        .ok();
+                    }
+                })
+                .entry("Revert Commit", None, {
+                    let this = this.clone();
+                    let sha = sha.clone();
+                    move |window, cx| {
+                        this.update(cx, |this, cx| this.revert(sha.clone(), window, cx))
+                            .ok();
+                    }
+                })
+                .separator();
+            for (label, kind) in [
+                ("Create Branch at Commit…", RefKind::Branch),
+                ("Create Tag…", RefKind::Tag),
+                ("Create Annotated Tag…", RefKind::AnnotatedTag),
+            ] {
+                let this = this.clone();
+                let sha = sha.clone();
+                context_menu = context_menu.entry(label, None, move |window, cx| {
+                    this.update(cx, |this, cx| {
+                        this.create_ref(kind, sha.clone(), window, cx)
+                    })
+                    .ok();
+                });
+            }
+            for tag in tags {
+                let this = this.clone();
+                context_menu =
+                    context_menu.entry(format!("Delete Tag {tag}"), None, move |window, cx| {
+                        this.update(cx, |this, cx| this.delete_tag(tag.clone(), window, cx))
+                            .ok();
+                    });
+            }
+
+            context_menu = context_menu.separator().entry(
+                if is_marked {
+                    "Unmark Commit"
+                } else {
+                    "Mark Commit for Comparison"
+                },
+                None,
+                {
+                    let this = this.clone();
+                    move |_, cx| {
+                        this.update(cx, |this, cx| {
+                            this.marked_entry_idx = (!is_marked).then_some(idx);
+                            cx.notify();
+                        })
+                        .ok();
+                    }
+                },
+            );
+            if let Some(marked) = marked {
+                let marked_is_older = self.marked_entry_idx > Some(idx);
+                let (older, newer) = if marked_is_older {
+                    (marked.clone(), sha.clone())
+                } else {
+                    (sha.clone(), marked.clone())
+                };
+                context_menu = context_menu
+                    .entry("Compare with Marked Commit", None, {
+                        let this = this.clone();
+                        let (older, newer) = (older.clone(), newer.clone());
+                        move |window, cx| {
+                            this.update(cx, |this, cx| {
+                                this.compare_commits(older.clone(), newer.clone(), window, cx)
+                            })
+                            .ok();
+                        }
+                    })
+                    .entry("Cherry-Pick Range from Marked Commit", None, {
+                        let this = this.clone();
+                        let (older, newer) = (older.clone(), newer.clone());
+                        move |window, cx| {
+                            this.update(cx, |this, cx| {
+                                this.cherry_pick_range(older.clone(), newer.clone(), window, cx)
+                            })
+                            .ok();
+                        }
+                    });
+                if !is_bisecting {
+                    context_menu = context_menu.action(
+                        "Bisect Between Marked Commit and This One",
+                        git::BisectStart {
+                            good: older,
+                            bad: newer,
+                        }
+                        .boxed_clone(),
+                    );
+                }
+            }
+            context_menu
+        });
+        let subscription = cx.subscribe_in(
+            &context_menu,
+            window,
+            |this, _, _: &DismissEvent, window, cx| {
+                if this.context_menu.as_ref().is_some_and(|context_menu| {
+                    context_menu.0.focus_handle(cx).contains_focused(window, cx)
+                }) {
+                    window.focus(&this.focus_handle, cx);
+                }
+                this.context_menu.take();
+                cx.notify();
+            },
+        );
+        self.context_menu = Some((context_menu, position, subscription));
+        cx.notify();
+    }
+
+    fn render_bisect_toolbar(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let repository = self.active_repository(cx)?;
+        let (first_bad, has_bad, untested_count) = {
+            let bisect = repository.read(cx).bisect.as_ref()?;
+            (
+                bisect.first_bad(),
+                bisect.bad.is_some(),
+                bisect.untested_count(),
+            )
+        };
+
+        let status = match first_bad {
+            Some(first_bad) => format!("First bad commit: {}", first_bad.display_short()),
+            None if !has_bad => "Bisecting: mark a bad commit to narrow the range".to_string(),
+            None => {
+                let steps = usize::BITS - untested_count.leading_zeros();
+                format!("Bisecting: {untested_count} commits left to test (roughly {steps} steps)")
+            }
+        };
+        let mark_button = |id: &'static str, label: &'static str, action: Box<dyn Action>| {
+            Button::new(id, label)
+                .style(ButtonStyle::Outlined)
+                .size(ButtonSize::Compact)
+                .disabled(first_bad.is_some())
+                .on_click(move |_, window, cx| window.dispatch_action(action.boxed_clone(), cx))
+        };
+
+        Some(
+            h_flex()
+                .px_2()
+                .py_1()
+                .gap_2()
+                .justify_between()
+                .border_b_1()
+                .border_color(cx.theme().colors().border)
+                .bg(cx.theme().status().info_background)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(Icon::new(IconName::GitBranch).size(IconSize::Small))
+                        .child(Label::new(status).size(LabelSize::Small)),
+                )
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .when_some(first_bad, |this, first_bad| {
+                            this.child(
+                                Button::new("bisect-open-first-bad", "Open Commit")
+                                    .style(ButtonStyle::Filled)
+                                    .size(ButtonSize::Compact)
+                                    .on_click({
+                                        let repository = repository.downgrade();
+                                        move |_, window, cx| {
+                                            let Some(workspace) =
+                                                window.root::<Workspace>().flatten()
+                                            else {
+                                                return;
+                                            };
+                                            CommitView::open(
+                                                first_bad.to_string(),
+                                                repository.clone(),
+                                                workspace.downgrade(),
+                                                None,
+                                                None,
+                                                window,
+                                                cx,
+                                            );
+                                        }
+                                    }),
+                            )
+                        })
+                        .child(mark_button(
+                            "bisect-good",
+                            "Good",
+                            git::BisectGood.boxed_clone(),
+                        ))
+                        .child(mark_button(
+                            "bisect-bad",
+                            "Bad",
+                            git::BisectBad.boxed_clone(),
+                        ))
+                        .child(mark_button(
+                            "bisect-skip",
+                            "Skip",
+                            git::BisectSkip.boxed_clone(),
+                        ))
+                        .child(mark_button(
+                            "bisect-run",
+                            "Run Task…",
+                            git::BisectRun.boxed_clone(),
+                        ))
+                        .child(
+                            Button::new("bisect-reset", "Reset")
+                                .size(ButtonSize::Compact)
+                                .on_click(|_, window, cx| {
+                                    window.dispatch_action(git::BisectReset.boxed_clone(), cx)
+                                }),
+                        ),
+                ),
+        )
+    }
+
+    fn active_repository(&self, cx: &App) -> Option<Entity<Repository>> {
+        self.project.read(cx).active_repository(cx)
+    }
+
+    fn cherry_pick(&mut self, commits: Vec<String>, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repository) = self.active_repository(cx) else {
+            return;
+        };
+        let cherry_pick = repository.update(cx, |repository, _| repository.cherry_pick(commits));
+        cx.spawn(async move |_, _| cherry_pick.await?)
+            .detach_and_prompt_err("Failed to cherry-pick", window, cx, |_, _, _| None);
+    }
+
+    fn cherry_pick_range(
+        &mut self,
+        first: String,
+        last: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(repository) = self.active_repository(cx) else {
+            return;
+        };
+        let cherry_pick = repository.update(cx, |repository, _| {
+            repository.cherry_pick_range(first, last)
+        });
+        cx.spawn(async move |_, _| cherry_pick.await?)
+            .detach_and_prompt_err("Failed to cherry-pick", window, cx, |_, _, _| None);
+    }
+
+    fn revert(&mut self, commit: String, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repository) = self.active_repository(cx) else {
+            return;
+        };
+        let revert = repository.update(cx, |repository, _| repository.revert(commit));
+        cx.spawn(async move |_, _| revert.await?)
+            .detach_and_prompt_err("Failed to revert commit", window, cx, |_, _, _| None);
+    }
+
+    fn delete_tag(&mut self, name: String, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repository) = self.active_repository(cx) else {
+            return;
+        };
+        let delete = repository.update(cx, |repository, _| repository.delete_tag(name));
+        cx.spawn(async move |_, _| delete.await?)
+            .detach_and_prompt_err("Failed to delete tag", window, cx, |_, _, _| None);
+    }
+
+    fn create_ref(
+        &mut self,
+        kind: RefKind,
+        commit: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(repository) = self.active_repository(cx) else {
+            return;
+        };
+        let Some(workspace) = window.root::<Workspace>().flatten() else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(window, cx, |window, cx| {
+                create_ref_modal(kind, commit, repository, window, cx)
+            });
+        });
+    }
+
+    fn compare_commits(
+        &mut self,
+        base: String,
+        head: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(repository) = self.active_repository(cx) else {
+            return;
+        };
+        let Some(workspace) = window.root::<Workspace>().flatten() else {
+            return;
+        };
+        let open = workspace.update(cx, |workspace, cx| {
+            MultiDiffView::open_commit_comparison(repository, base, head, workspace, window, cx)
+        });
+        cx.spawn(async move |_, _| open.await.map(|_| ()))
+            .detach_and_prompt_err("Failed to compare commits", window, cx, |_, _, _| None);
+    }
+
     fn get_remote(
         &self,
         repository: &Repository,
@@ -1094,7 +1527,15 @@ impl GitGraph {
                                                     copy_sha.to_string(),
                                                 ));
                                             })
-                                    }),
+                                    })
+                                    .children(
+                                        self.commit_signatures
+                                            .get(&commit_entry.data.sha)
+                                            .and_then(Option::as_ref)
+                                            .map(|signature| {
+                                                signature_badge("detail-signature", signature)
+                                            }),
+                                    ),
                             )
                             .when_some(remote.clone(), |this, remote| {
                                 let provider_name = remote.host.name();
@@ -1540,6 +1981,7 @@ impl Render for GitGraph {
                 .child({
                     let row_height = self.row_height;
                     let selected_entry_idx = self.selected_entry_idx;
+                    let marked_entry_idx = self.marked_entry_idx;
                     let weak_self = cx.weak_entity();
                     div().flex_1().size_full().child(
                         Table::new(4)
@@ -1574,17 +2016,40 @@ impl Render for GitGraph {
                             )
                             .map_row(move |(index, row), _window, cx| {
                                 let is_selected = selected_entry_idx == Some(index);
+                                let is_marked = marked_entry_idx == Some(index);
                                 let weak = weak_self.clone();
                                 row.h(row_height)
                                     .when(is_selected, |row| {
                                         row.bg(cx.theme().colors().element_selected)
                                     })
-                                    .on_click(move |_, _, cx| {
-                                        weak.update(cx, |this, cx| {
-                                            this.select_entry(index, cx);
-                                        })
-                                        .ok();
+                                    .when(is_marked, |row| {
+                                        row.border_1()
+                                            .border_color(cx.theme().colors().border_focused)
                                     })
+                                    .on_click({
+                                        let weak = weak.clone();
+                                        move |_, _, cx| {
+                                            weak.update(cx, |this, cx| {
+                                                this.select_entry(index, cx);
+                                            })
+                                            .ok();
+                                        }
+                                    })
+                                    .on_mouse_down(
+                                        MouseButton::Right,
+                                        move |event: &MouseDownEvent, window, cx| {
+                                            weak.update(cx, |this, cx| {
+                                                this.deploy_context_menu(
+                                                    event.position,
+                                                    index,
+                                                    window,
+                                                    cx,
+                                                );
+                                            })
+                                            .ok();
+                                            cx.stop_propagation();
+                                        },
+                                    )
                                     .into_any_element()
                             })
                             .uniform_list(
@@ -1599,12 +2064,13 @@ impl Render for GitGraph {
                 })
         };
 
-        div()
+        v_flex()
             .size_full()
             .bg(cx.theme().colors().editor_background)
             .key_context("GitGraph")
             .track_focus(&self.focus_handle)
-            .child(content)
+            .children(self.render_bisect_toolbar(cx))
+            .child(div().flex_1().min_h_0().w_full().child(content))
             .children(self.context_menu.as_ref().map(|(menu, position, _)| {
                 deferred(
                     anchored()
@@ -1617,6 +2083,23 @@ impl Render for GitGraph {
     }
 }
 
+/// Labels a commit by the part it plays in the bisect in progress.
+fn bisect_marker(bisect: &BisectProgress, sha: Oid) -> Option<(&'static str, Color)> {
+    if bisect.first_bad() == Some(sha) {
+        Some(("first bad", Color::Error))
+    } else if bisect.bad == Some(sha) {
+        Some(("bad", Color::Error))
+    } else if bisect.good.contains(&sha) {
+        Some(("good", Color::Success))
+    } else if bisect.skipped.contains(&sha) {
+        Some(("skipped", Color::Muted))
+    } else if bisect.remaining.contains(&sha) {
+        Some(("untested", Color::Warning))
+    } else {
+        None
+    }
+}
+
 impl EventEmitter<ItemEvent> for GitGraph {}
 
 impl Focusable for GitGraph {
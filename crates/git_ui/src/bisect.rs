@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use futures::channel::oneshot;
+use git::{bisect::BisectTerm, rebase::shell_quote};
+use gpui::{Context, Entity, SharedString, Window};
+use project::git_store::Repository;
+use task::{Shell, ShellBuilder, SpawnInTerminal};
+use workspace::{Workspace, notifications::DetachAndPromptErr};
+
+use crate::{commit_view::CommitView, picker_prompt};
+
+pub fn start(
+    good: String,
+    bad: String,
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    let step = repository.update(cx, |repository, _| repository.bisect_start(good, bad));
+    finish_step(step, repository, "Failed to start bisecting", window, cx);
+}
+
+pub fn mark(
+    term: BisectTerm,
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    let step = repository.update(cx, |repository, _| repository.bisect_mark(term));
+    finish_step(step, repository, "Failed to mark commit", window, cx);
+}
+
+/// Asks for a task and lets `git bisect run` run it on each remaining commit.
+pub fn run(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+    let project = workspace.project().read(cx);
+    let Some(repository) = project.active_repository(cx) else {
+        return;
+    };
+    let Some(inventory) = project.task_store().read(cx).task_inventory().cloned() else {
+        return;
+    };
+    let is_local = project.is_local();
+    let is_windows = project.path_style(cx).is_windows();
+    let task_contexts = tasks_ui::task_contexts(workspace, window, cx);
+    let workspace_handle = workspace.weak_handle();
+
+    cx.spawn_in(window, async move |workspace, cx| {
+        let task_contexts = Arc::new(task_contexts.await);
+        let (used_tasks, current_tasks) = inventory
+            .update(cx, |inventory, cx| {
+                inventory.used_and_current_resolved_tasks(task_contexts, cx)
+            })
+            .await;
+        let tasks = used_tasks
+            .into_iter()
+            .chain(current_tasks)
+            .map(|(_, task)| task.resolved)
+            .collect::<Vec<_>>();
+        anyhow::ensure!(!tasks.is_empty(), "No tasks are defined to bisect with");
+        let labels = tasks
+            .iter()
+            .map(|task| SharedString::from(task.label.clone()))
+            .collect();
+        let selection = workspace.update_in(cx, |_, window, cx| {
+            picker_prompt::prompt(
+                "Task to run on each commit",
+                labels,
+                workspace_handle,
+                window,
+                cx,
+            )
+        })?;
+        let Some(ix) = selection.await else {
+            return Ok(());
+        };
+
+        let command = bisect_command(&tasks[ix], is_local, is_windows)?;
+        let step = repository.update(cx, |repository, _| repository.bisect_run(command));
+        workspace.update_in(cx, |_, window, cx| {
+            finish_step(step, repository, "Bisect run failed", window, cx);
+        })
+    })
+    .detach_and_prompt_err("Failed to run bisect", window, cx, |_, _, _| None);
+}
+
+pub fn reset(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    let reset = repository.update(cx, |repository, _| repository.bisect_reset());
+    cx.spawn(async move |_, _| reset.await?)
+        .detach_and_prompt_err("Failed to reset bisect", window, cx, |_, _, _| None);
+}
+
+/// Opens the first bad commit once a bisect step reports it.
+fn finish_step(
+    step: oneshot::Receiver<Result<Option<SharedString>>>,
+    repository: Entity<Repository>,
+    error_message: &'static str,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    cx.spawn_in(window, async move |workspace, cx| {
+        let Some(first_bad) = step.await?? else {
+            return Ok(());
+        };
+        workspace.update_in(cx, |workspace, window, cx| {
+            CommitView::open(
+                first_bad.to_string(),
+                repository.downgrade(),
+                workspace.weak_handle(),
+                None,
+                None,
+                window,
+                cx,
+            );
+        })
+    })
+    .detach_and_prompt_err(error_message, window, cx, |_, _, _| None);
+}
+
+/// Turns a task into a command line for `git bisect run`, which git hands to its own
+/// `sh` from the root of the working tree. The task itself still runs in the shell it
+/// would get in a terminal.
+fn bisect_command(task: &SpawnInTerminal, is_local: bool, is_windows: bool) -> Result<String> {
+    let command = task
+        .command
+        .clone()
+        .with_context(|| format!("Task {:?} has no command", task.label))?;
+    // The system shell of a remote host isn't known here, so leave the task to `sh`.
+    let shell = match &task.shell {
+        Shell::System if !is_local => Shell::Program("sh".to_string()),
+        shell => shell.clone(),
+    };
+    let (program, args) = ShellBuilder::new(&shell, is_windows)
+        .non_interactive()
+        .build(Some(command), &task.args);
+
+    let mut bisect_command = String::new();
+    if let Some(cwd) = &task.cwd {
+        bisect_command.push_str(&format!("cd {} && ", shell_quote(&cwd.to_string_lossy())));
+    }
+    for (name, value) in &task.env {
+        bisect_command.push_str(&format!("{name}={} ", shell_quote(value)));
+    }
+    bisect_command.push_str(&shell_quote(&program));
+    for arg in &args {
+        bisect_command.push(' ');
+        bisect_command.push_str(&shell_quote(arg));
+    }
+    Ok(bisect_command)
+}
@@ -4,6 +4,7 @@ use collections::HashMap;
 use editor::display_map::{BlockPlacement, BlockProperties, BlockStyle};
 use editor::{Addon, Editor, EditorEvent, ExcerptRange, MultiBuffer, multibuffer_context_lines};
 use git::repository::{CommitDetails, CommitDiff, RepoPath, is_binary_content};
+use git::signing::CommitSignature;
 use git::status::{FileStatus, StatusCode, TrackedStatus};
 use git::{
     BuildCommitPermalinkParams, GitHostingProviderRegistry, GitRemote, ParsedGitRemote,
@@ -41,6 +42,7 @@ use workspace::{
 
 use crate::commit_tooltip::CommitAvatar;
 use crate::git_panel::GitPanel;
+use crate::signature_badge::signature_badge;
 
 actions!(git, [ApplyCurrentStash, PopCurrentStash, DropCurrentStash,]);
 
@@ -66,6 +68,7 @@ pub struct CommitView {
     multibuffer: Entity<MultiBuffer>,
     repository: Entity<Repository>,
     remote: Option<GitRemote>,
+    signature: Option<CommitSignature>,
 }
 
 struct GitBlob {
@@ -388,6 +391,21 @@ impl CommitView {
             })
         });
 
+        if stash.is_none() && project.read(cx).git_store().read(cx).is_local() {
+            let signature = repository.update(cx, |repository, _| {
+                repository.verify_commit(commit.sha.to_string())
+            });
+            cx.spawn(async move |this, cx| {
+                let signature = signature.await.ok()?.log_err()?;
+                this.update(cx, |this, cx| {
+                    this.signature = signature;
+                    cx.notify();
+                })
+                .ok()
+            })
+            .detach();
+        }
+
         Self {
             commit,
             editor,
@@ -395,6 +413,7 @@ impl CommitView {
             stash,
             repository,
             remote,
+            signature: None,
         }
     }
 
@@ -601,7 +620,10 @@ impl CommitView {
                                                     commit_sha.to_string(),
                                                 ));
                                             })
-                                    }),
+                                    })
+                                    .children(self.signature.as_ref().map(|signature| {
+                                        signature_badge("commit-signature", signature)
+                                    })),
                             )
                             .child(
                                 h_flex()
@@ -1070,6 +1092,7 @@ impl Item for CommitView {
                 stash: self.stash,
                 repository: self.repository.clone(),
                 remote: self.remote.clone(),
+                signature: self.signature.clone(),
             }
         })))
     }
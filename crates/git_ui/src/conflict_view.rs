@@ -4,15 +4,19 @@ use editor::{
     Editor, EditorEvent, ExcerptId, MultiBuffer, RowHighlightOptions,
     display_map::{BlockContext, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId},
 };
+use git::rebase::RebaseControl;
 use gpui::{
     App, Context, Entity, InteractiveElement as _, ParentElement as _, Subscription, Task,
     WeakEntity,
 };
 use language::{Anchor, Buffer, BufferId};
-use project::{ConflictRegion, ConflictSet, ConflictSetUpdate, ProjectItem as _};
+use project::{
+    ConflictRegion, ConflictSet, ConflictSetUpdate, ProjectItem as _, git_store::Repository,
+};
 use std::{ops::Range, sync::Arc};
 use ui::{ActiveTheme, Element as _, Styled, Window, prelude::*};
 use util::{ResultExt as _, debug_panic, maybe};
+use workspace::notifications::DetachAndPromptErr as _;
 
 pub(crate) struct ConflictAddon {
     buffers: HashMap<BufferId, BufferConflicts>,
@@ -367,6 +371,7 @@ fn render_conflict_buttons(
     editor: WeakEntity<Editor>,
     cx: &mut BlockContext,
 ) -> AnyElement {
+    let rebasing_repository = rebasing_repository(conflict, &editor, cx);
     h_flex()
         .id(cx.block_id)
         .h(cx.line_height)
@@ -434,9 +439,66 @@ fn render_conflict_buttons(
                     }
                 }),
         )
+        .when_some(rebasing_repository, |this, repository| {
+            this.child(div().w_2())
+                .child(rebase_button(
+                    "rebase-continue",
+                    "Continue Rebase",
+                    RebaseControl::Continue,
+                    repository.clone(),
+                ))
+                .child(rebase_button(
+                    "rebase-skip",
+                    "Skip Commit",
+                    RebaseControl::Skip,
+                    repository.clone(),
+                ))
+                .child(rebase_button(
+                    "rebase-abort",
+                    "Abort Rebase",
+                    RebaseControl::Abort,
+                    repository,
+                ))
+        })
         .into_any()
 }
 
+/// The repository the conflicted buffer belongs to, if it's in the middle of a rebase.
+fn rebasing_repository(
+    conflict: &ConflictRegion,
+    editor: &WeakEntity<Editor>,
+    cx: &App,
+) -> Option<Entity<Repository>> {
+    let buffer_id = conflict.ours.end.buffer_id?;
+    let project = editor.upgrade()?.read(cx).project()?.clone();
+    let (repository, _) = project
+        .read(cx)
+        .git_store()
+        .read(cx)
+        .repository_and_path_for_buffer_id(buffer_id, cx)?;
+    repository.read(cx).rebase.is_some().then_some(repository)
+}
+
+fn rebase_button(
+    id: &'static str,
+    label: &'static str,
+    control: RebaseControl,
+    repository: Entity<Repository>,
+) -> Button {
+    Button::new(id, label)
+        .label_size(LabelSize::Small)
+        .on_click(move |_, window, cx| {
+            let rebase = repository.update(cx, |repository, _| repository.rebase_control(control));
+            cx.background_spawn(async move { rebase.await? })
+                .detach_and_prompt_err(
+                    &format!("Failed to run git rebase {}", control.flag()),
+                    window,
+                    cx,
+                    |_, _, _| None,
+                );
+        })
+}
+
 pub(crate) fn resolve_conflict(
     editor: WeakEntity<Editor>,
     excerpt_id: ExcerptId,
@@ -0,0 +1,88 @@
+use anyhow::anyhow;
+use git::SHORT_SHA_LENGTH;
+use gpui::{Context, Entity, Window};
+use project::git_store::Repository;
+use ui::prelude::*;
+use workspace::{InvalidInput, TextPromptModal, notifications::DetachAndPromptErr};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefKind {
+    Branch,
+    Tag,
+    AnnotatedTag,
+}
+
+impl RefKind {
+    fn title(self) -> &'static str {
+        match self {
+            Self::Branch => "Create Branch",
+            Self::Tag => "Create Tag",
+            Self::AnnotatedTag => "Create Annotated Tag",
+        }
+    }
+}
+
+/// Asks for the name (and, for annotated tags, the message) of a branch or tag to
+/// create at a commit.
+pub fn create_ref_modal(
+    kind: RefKind,
+    commit: String,
+    repo: Entity<Repository>,
+    window: &mut Window,
+    cx: &mut Context<TextPromptModal>,
+) -> TextPromptModal {
+    let short_sha = &commit[..SHORT_SHA_LENGTH.min(commit.len())];
+    let title = format!("{} at {short_sha}", kind.title());
+    let placeholders: &[&str] = match kind {
+        RefKind::Branch => &["Branch name"],
+        RefKind::Tag => &["Tag name"],
+        RefKind::AnnotatedTag => &["Tag name", "Tag message"],
+    };
+
+    TextPromptModal::new(
+        title,
+        placeholders,
+        move |mut texts, window, cx| {
+            let message = (kind == RefKind::AnnotatedTag).then(|| texts.remove(1));
+            let name = texts.remove(0);
+            if name.is_empty() {
+                return Err(InvalidInput::new(
+                    0,
+                    if kind == RefKind::Branch {
+                        "Enter a branch name"
+                    } else {
+                        "Enter a tag name"
+                    },
+                ));
+            }
+            if name.starts_with('-') {
+                return Err(InvalidInput::new(0, "Names can't start with \"-\""));
+            }
+            if message.as_ref().is_some_and(|message| message.is_empty()) {
+                return Err(InvalidInput::new(1, "Enter a tag message"));
+            }
+
+            let commit = commit.clone();
+            let (task, error_message) = repo.update(cx, |repo, _| match kind {
+                RefKind::Branch => (
+                    repo.create_branch(name, Some(commit)),
+                    "Failed to create branch",
+                ),
+                RefKind::Tag | RefKind::AnnotatedTag => (
+                    repo.create_tag(name, commit, message),
+                    "Failed to create tag",
+                ),
+            });
+            cx.spawn(async move |_| match task.await {
+                Ok(Ok(())) => Ok(()),
+                Ok(Err(error)) => Err(error),
+                Err(_) => Err(anyhow!("Operation was canceled")),
+            })
+            .detach_and_prompt_err(error_message, window, cx, |_, _, _| None);
+            Ok(())
+        },
+        window,
+        cx,
+    )
+    .icon(IconName::GitBranch)
+}
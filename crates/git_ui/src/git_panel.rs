@@ -29,10 +29,12 @@ use git::repository::{
 };
 use git::stash::GitStash;
 use git::status::StageStatus;
+use git::submodule::SubmoduleCommand;
 use git::{Amend, Signoff, ToggleStaged, repository::RepoPath, status::FileStatus};
 use git::{
     ExpandCommitEditor, GitHostingProviderRegistry, RestoreTrackedFiles, StageAll, StashAll,
-    StashApply, StashPop, TrashUntrackedFiles, UnstageAll,
+    StashApply, StashPop, SubmoduleInit, SubmoduleSync, SubmoduleUpdate, TrashUntrackedFiles,
+    UnstageAll,
 };
 use gpui::{
     Action, AsyncApp, AsyncWindowContext, Bounds, ClickEvent, Corner, DismissEvent, Empty, Entity,
@@ -139,6 +141,7 @@ struct GitMenuState {
     has_new_changes: bool,
     sort_by_path: bool,
     has_stash_items: bool,
+    has_submodules: bool,
     tree_view: bool,
 }
 
@@ -172,6 +175,12 @@ fn git_panel_context_menu(
             .separator()
             .action("Open Diff", project_diff::Diff.boxed_clone())
             .separator()
+            .when(state.has_submodules, |this| {
+                this.action("Initialize Submodules", SubmoduleInit.boxed_clone())
+                    .action("Update Submodules", SubmoduleUpdate.boxed_clone())
+                    .action("Sync Submodule URLs", SubmoduleSync.boxed_clone())
+                    .separator()
+            })
             .action_disabled_when(
                 !state.has_tracked_changes,
                 "Discard Tracked Changes",
@@ -728,6 +737,7 @@ impl GitPanel {
                         _,
                         RepositoryEvent::StatusesChanged
                         | RepositoryEvent::BranchChanged
+                        | RepositoryEvent::RebaseChanged
                         | RepositoryEvent::MergeHeadsChanged,
                         true,
                     )
@@ -1958,6 +1968,49 @@ impl GitPanel {
         .detach();
     }
 
+    pub(crate) fn rebase_control(&mut self, control: RebaseControl, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+
+        let rebase_task = active_repository.update(cx, |repo, _| repo.rebase_control(control));
+        cx.spawn(async move |this, cx| {
+            let result = rebase_task.await;
+            this.update(cx, |this, cx| {
+                if let Err(e) = result
+                    .map_err(anyhow::Error::from)
+                    .and_then(|result| result)
+                {
+                    this.show_error_toast(format!("rebase {}", control.flag()), e, cx);
+                }
+                cx.notify();
+            })
+        })
+        .detach();
+    }
+
+    pub(crate) fn submodule_command(&mut self, command: SubmoduleCommand, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+
+        let submodule_task =
+            active_repository.update(cx, |repo, _| repo.submodule_command(command));
+        cx.spawn(async move |this, cx| {
+            let result = submodule_task.await;
+            this.update(cx, |this, cx| {
+                if let Err(e) = result
+                    .map_err(anyhow::Error::from)
+                    .and_then(|result| result)
+                {
+                    this.show_error_toast(command.args().join(" "), e, cx);
+                }
+                cx.notify();
+            })
+        })
+        .detach();
+    }
+
     pub fn stash_apply(&mut self, _: &StashApply, _window: &mut Window, cx: &mut Context<Self>) {
         let Some(active_repository) = self.active_repository.clone() else {
             return;
@@ -3875,12 +3928,19 @@ impl GitPanel {
         }
     }
 
+    fn has_submodules(&self, cx: &App) -> bool {
+        self.active_repository
+            .as_ref()
+            .is_some_and(|repository| !repository.read(cx).submodules.is_empty())
+    }
+
     fn item_width_estimate(path: usize, file_name: usize, depth: usize) -> usize {
         path + file_name + depth * 2
     }
 
-    fn render_overflow_menu(&self, id: impl Into<ElementId>) -> impl IntoElement {
+    fn render_overflow_menu(&self, id: impl Into<ElementId>, cx: &App) -> impl IntoElement {
         let focus_handle = self.focus_handle.clone();
+        let has_submodules = self.has_submodules(cx);
         let has_tracked_changes = self.has_tracked_changes();
         let has_staged_changes = self.has_staged_changes();
         let has_unstaged_changes = self.has_unstaged_changes();
@@ -3903,6 +3963,7 @@ impl GitPanel {
                         has_new_changes,
                         sort_by_path: GitPanelSettings::get_global(cx).sort_by_path,
                         has_stash_items,
+                        has_submodules,
                         tree_view: GitPanelSettings::get_global(cx).tree_view,
                     },
                     window,
@@ -4173,7 +4234,7 @@ impl GitPanel {
                 .child(
                     h_flex()
                         .gap_1()
-                        .child(self.render_overflow_menu("overflow_menu"))
+                        .child(self.render_overflow_menu("overflow_menu", cx))
                         .child(
                             panel_filled_button(text)
                                 .tooltip(Tooltip::for_action_title_in(
@@ -4421,6 +4482,61 @@ impl GitPanel {
             ))
     }
 
+    fn render_rebase_banner(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let rebase = self.active_repository.as_ref()?.read(cx).rebase.clone()?;
+        let branch = rebase.branch.unwrap_or_else(|| "HEAD".into());
+        let mut title = match rebase.onto {
+            Some(onto) => format!(
+                "Rebasing {branch} onto {}",
+                &onto[..git::SHORT_SHA_LENGTH.min(onto.len())]
+            ),
+            None => format!("Rebasing {branch}"),
+        };
+        if rebase.total > 0 {
+            title.push_str(&format!(" ({}/{})", rebase.step, rebase.total));
+        }
+
+        Some(
+            h_flex()
+                .py_1p5()
+                .px_2()
+                .gap_1p5()
+                .justify_between()
+                .border_b_1()
+                .border_color(cx.theme().colors().border.opacity(0.8))
+                .bg(cx.theme().status().warning_background)
+                .child(
+                    h_flex()
+                        .min_w_0()
+                        .gap_1p5()
+                        .child(
+                            Icon::new(IconName::Warning)
+                                .size(IconSize::Small)
+                                .color(Color::Warning),
+                        )
+                        .child(Label::new(title).size(LabelSize::Small).truncate()),
+                )
+                .child(
+                    h_flex()
+                        .flex_none()
+                        .gap_1()
+                        .child(
+                            panel_button("Continue").on_click(cx.listener(|this, _, _, cx| {
+                                this.rebase_control(RebaseControl::Continue, cx)
+                            })),
+                        )
+                        .child(panel_button("Skip").on_click(cx.listener(|this, _, _, cx| {
+                            this.rebase_control(RebaseControl::Skip, cx)
+                        })))
+                        .child(
+                            panel_button("Abort").on_click(cx.listener(|this, _, _, cx| {
+                                this.rebase_control(RebaseControl::Abort, cx)
+                            })),
+                        ),
+                ),
+        )
+    }
+
     fn render_pending_amend(&self, cx: &mut Context<Self>) -> impl IntoElement {
         h_flex()
             .py_1p5()
@@ -4874,6 +4990,7 @@ impl GitPanel {
                 has_new_changes: self.new_count > 0,
                 sort_by_path: GitPanelSettings::get_global(cx).sort_by_path,
                 has_stash_items: self.stash_entries.entries.len() > 0,
+                has_submodules: self.has_submodules(cx),
                 tree_view: GitPanelSettings::get_global(cx).tree_view,
             },
             window,
@@ -5020,7 +5137,17 @@ impl GitPanel {
                         status.is_deleted(),
                     ))
                 }
-            });
+            })
+            .children(repo.submodule_for_path(&entry.repo_path).map(|submodule| {
+                let description = match submodule.describe() {
+                    Some(status) => format!("submodule, {status}"),
+                    None => "submodule".to_string(),
+                };
+                Label::new(description)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .single_line()
+            }));
 
         h_flex()
             .id(id)
@@ -5498,6 +5625,7 @@ impl Render for GitPanel {
                 v_flex()
                     .size_full()
                     .children(self.render_panel_header(window, cx))
+                    .children(self.render_rebase_banner(cx))
                     .map(|this| {
                         if let Some(repo) = self.active_repository.clone()
                             && has_entries
@@ -15,8 +15,11 @@ mod blame_ui;
 pub mod clone;
 
 use git::{
+    bisect::BisectTerm,
+    rebase::RebaseControl,
     repository::{Branch, Upstream, UpstreamTracking, UpstreamTrackingStatus},
     status::{FileStatus, StatusCode, UnmergedStatus, UnmergedStatusCode},
+    submodule::SubmoduleCommand,
 };
 use gpui::{
     Action, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, SharedString,
@@ -30,25 +33,31 @@ use ui::prelude::*;
 use workspace::{ModalView, Workspace, notifications::DetachAndPromptErr};
 use zed_actions;
 
-use crate::{git_panel::GitPanel, text_diff_view::TextDiffView};
+use crate::{
+    git_panel::GitPanel, interactive_rebase::InteractiveRebaseView, text_diff_view::TextDiffView,
+};
 
 mod askpass_modal;
+mod bisect;
 pub mod branch_picker;
 mod commit_modal;
 pub mod commit_tooltip;
 pub mod commit_view;
 mod conflict_view;
+pub mod create_ref_modal;
 pub mod file_diff_view;
 pub mod file_history_view;
 pub mod git_panel;
 mod git_panel_settings;
 pub mod git_picker;
+pub mod interactive_rebase;
 pub mod multi_diff_view;
 pub mod onboarding;
 pub mod picker_prompt;
 pub mod project_diff;
 pub(crate) mod remote_output;
 pub mod repository_selector;
+pub mod signature_badge;
 pub mod stash_picker;
 pub mod text_diff_view;
 pub mod worktree_picker;
@@ -148,6 +157,81 @@ pub fn init(cx: &mut App) {
                     panel.pull(true, window, cx);
                 });
             });
+            workspace.register_action(|workspace, action: &git::RebaseInteractive, window, cx| {
+                InteractiveRebaseView::open(action.base.clone(), workspace, window, cx);
+            });
+            workspace.register_action(|workspace, _: &git::RebaseContinue, _, cx| {
+                let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| {
+                    panel.rebase_control(RebaseControl::Continue, cx);
+                });
+            });
+            workspace.register_action(|workspace, _: &git::RebaseSkip, _, cx| {
+                let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| {
+                    panel.rebase_control(RebaseControl::Skip, cx);
+                });
+            });
+            workspace.register_action(|workspace, _: &git::RebaseAbort, _, cx| {
+                let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| {
+                    panel.rebase_control(RebaseControl::Abort, cx);
+                });
+            });
+            workspace.register_action(|workspace, action: &git::BisectStart, window, cx| {
+                bisect::start(
+                    action.good.clone(),
+                    action.bad.clone(),
+                    workspace,
+                    window,
+                    cx,
+                );
+            });
+            workspace.register_action(|workspace, _: &git::BisectGood, window, cx| {
+                bisect::mark(BisectTerm::Good, workspace, window, cx);
+            });
+            workspace.register_action(|workspace, _: &git::BisectBad, window, cx| {
+                bisect::mark(BisectTerm::Bad, workspace, window, cx);
+            });
+            workspace.register_action(|workspace, _: &git::BisectSkip, window, cx| {
+                bisect::mark(BisectTerm::Skip, workspace, window, cx);
+            });
+            workspace.register_action(|workspace, _: &git::BisectRun, window, cx| {
+                bisect::run(workspace, window, cx);
+            });
+            workspace.register_action(|workspace, _: &git::BisectReset, window, cx| {
+                bisect::reset(workspace, window, cx);
+            });
+            workspace.register_action(|workspace, _: &git::SubmoduleInit, _, cx| {
+                let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| {
+                    panel.submodule_command(SubmoduleCommand::Init, cx);
+                });
+            });
+            workspace.register_action(|workspace, _: &git::SubmoduleUpdate, _, cx| {
+                let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| {
+                    panel.submodule_command(SubmoduleCommand::Update, cx);
+                });
+            });
+            workspace.register_action(|workspace, _: &git::SubmoduleSync, _, cx| {
+                let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                    return;
+                };
+                panel.update(cx, |panel, cx| {
+                    panel.submodule_command(SubmoduleCommand::Sync, cx);
+                });
+            });
         }
         workspace.register_action(|workspace, action: &git::StashAll, window, cx| {
             let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
@@ -0,0 +1,381 @@
+use collections::HashMap;
+use editor::Editor;
+use git::{
+    SHORT_SHA_LENGTH,
+    rebase::{RebaseAction, RebaseTodoEntry},
+};
+use gpui::{
+    AnyElement, App, Context, Entity, EventEmitter, FocusHandle, Focusable, IntoElement, Render,
+    SharedString, WeakEntity, Window,
+};
+use project::git_store::Repository;
+use ui::{ContextMenu, DropdownMenu, prelude::*};
+use workspace::{
+    Workspace,
+    item::{Item, ItemEvent},
+    notifications::DetachAndPromptErr,
+};
+
+/// Lists the commits between a base and HEAD so they can be reordered, squashed,
+/// reworded or dropped before running `git rebase --interactive`.
+pub struct InteractiveRebaseView {
+    base: String,
+    entries: Vec<RebaseTodoEntry>,
+    message_editors: HashMap<SharedString, Entity<Editor>>,
+    repository: WeakEntity<Repository>,
+    focus_handle: FocusHandle,
+    rebasing: bool,
+}
+
+#[derive(Clone)]
+struct DraggedRebaseEntry {
+    ix: usize,
+    subject: SharedString,
+}
+
+impl Render for DraggedRebaseEntry {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .rounded_sm()
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().elevated_surface_background)
+            .child(
+                Icon::new(IconName::Menu)
+                    .size(IconSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(Label::new(self.subject.clone()).size(LabelSize::Small))
+    }
+}
+
+impl InteractiveRebaseView {
+    pub fn open(
+        base: String,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+            return;
+        };
+        let todo = repository.update(cx, |repository, _| repository.rebase_todo(base.clone()));
+
+        cx.spawn_in(window, async move |workspace, cx| {
+            let entries = todo.await??;
+            workspace.update_in(cx, |workspace, window, cx| {
+                let view = cx.new(|cx| Self::new(base, entries, repository.downgrade(), cx));
+                workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+            })
+        })
+        .detach_and_prompt_err(
+            "Failed to list commits to rebase",
+            window,
+            cx,
+            |_, _, _| None,
+        );
+    }
+
+    fn new(
+        base: String,
+        entries: Vec<RebaseTodoEntry>,
+        repository: WeakEntity<Repository>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self {
+            base,
+            entries,
+            message_editors: HashMap::default(),
+            repository,
+            focus_handle: cx.focus_handle(),
+            rebasing: false,
+        }
+    }
+
+    fn set_action(
+        &mut self,
+        sha: &SharedString,
+        action: RebaseAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(entry) = self.entries.iter_mut().find(|entry| &entry.sha == sha) else {
+            return;
+        };
+        entry.action = action;
+        if action == RebaseAction::Reword && !self.message_editors.contains_key(sha) {
+            let message = entry.original_message.clone();
+            let editor = cx.new(|cx| {
+                let mut editor = Editor::auto_height(1, 8, window, cx);
+                editor.set_text(message, window, cx);
+                editor
+            });
+            self.message_editors.insert(sha.clone(), editor);
+        }
+        cx.notify();
+    }
+
+    fn move_entry(&mut self, from: usize, to: usize, cx: &mut Context<Self>) {
+        if from == to || from >= self.entries.len() || to >= self.entries.len() {
+            return;
+        }
+        let entry = self.entries.remove(from);
+        self.entries.insert(to, entry);
+        cx.notify();
+    }
+
+    /// Git refuses to squash or fixup a commit with nothing before it to meld into.
+    fn validation_error(&self) -> Option<&'static str> {
+        let first = self
+            .entries
+            .iter()
+            .find(|entry| entry.action != RebaseAction::Drop)?;
+        first
+            .action
+            .melds()
+            .then_some("The first commit can't be squashed or fixed up")
+    }
+
+    fn start_rebase(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.rebasing || self.validation_error().is_some() {
+            return;
+        }
+        let Some(repository) = self.repository.upgrade() else {
+            return;
+        };
+
+        let entries = self
+            .entries
+            .iter()
+            .cloned()
+            .map(|mut entry| {
+                if entry.action == RebaseAction::Reword {
+                    entry.message = self
+                        .message_editors
+                        .get(&entry.sha)
+                        .map(|editor| editor.read(cx).text(cx))
+                        .filter(|message| !message.trim().is_empty());
+                }
+                entry
+            })
+            .collect();
+        let rebase = repository.update(cx, |repository, _| {
+            repository.rebase_interactive(self.base.clone(), entries)
+        });
+
+        self.rebasing = true;
+        cx.notify();
+        cx.spawn_in(window, async move |this, cx| {
+            let result = rebase
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result);
+            this.update(cx, |this, cx| {
+                this.rebasing = false;
+                if result.is_ok() {
+                    cx.emit(ItemEvent::CloseItem);
+                }
+                cx.notify();
+            })?;
+            result
+        })
+        .detach_and_prompt_err("Failed to rebase", window, cx, |_, _, _| None);
+    }
+
+    fn render_entry(
+        &self,
+        ix: usize,
+        entry: &RebaseTodoEntry,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let this = cx.weak_entity();
+        let sha = entry.sha.clone();
+        let current_action = entry.action;
+        let action_menu = ContextMenu::build(window, cx, |mut menu, _, _| {
+            for action in RebaseAction::ALL {
+                let this = this.clone();
+                let sha = sha.clone();
+                menu = menu.toggleable_entry(
+                    action.label(),
+                    current_action == action,
+                    IconPosition::Start,
+                    None,
+                    move |window, cx| {
+                        this.update(cx, |this, cx| this.set_action(&sha, action, window, cx))
+                            .ok();
+                    },
+                );
+            }
+            menu
+        });
+        let short_sha = &entry.sha[..SHORT_SHA_LENGTH.min(entry.sha.len())];
+        let is_dropped = entry.action == RebaseAction::Drop;
+
+        v_flex()
+            .id(("rebase-entry", ix))
+            .w_full()
+            .px_3()
+            .py_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .on_drag(
+                DraggedRebaseEntry {
+                    ix,
+                    subject: entry.subject.clone(),
+                },
+                |dragged, _, _, cx| cx.new(|_| dragged.clone()),
+            )
+            .drag_over::<DraggedRebaseEntry>(|style, _, _, cx| {
+                style.bg(cx.theme().colors().drop_target_background)
+            })
+            .on_drop(
+                cx.listener(move |this, dragged: &DraggedRebaseEntry, _, cx| {
+                    this.move_entry(dragged.ix, ix, cx);
+                }),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Icon::new(IconName::Menu)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        div().w(rems(6.5)).flex_none().child(
+                            DropdownMenu::new(
+                                ("rebase-action", ix),
+                                entry.action.label(),
+                                action_menu,
+                            )
+                            .trigger_size(ButtonSize::Compact),
+                        ),
+                    )
+                    .child(
+                        Label::new(short_sha.to_string())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .buffer_font(cx),
+                    )
+                    .child(
+                        Label::new(entry.subject.clone())
+                            .size(LabelSize::Small)
+                            .color(if is_dropped {
+                                Color::Disabled
+                            } else {
+                                Color::Default
+                            })
+                            .when(is_dropped, |label| label.strikethrough())
+                            .truncate(),
+                    ),
+            )
+            .when(entry.action == RebaseAction::Reword, |this| {
+                this.children(self.message_editors.get(&entry.sha).map(|editor| {
+                    div()
+                        .ml_6()
+                        .p_1()
+                        .rounded_sm()
+                        .border_1()
+                        .border_color(cx.theme().colors().border)
+                        .child(editor.clone())
+                }))
+            })
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<ItemEvent> for InteractiveRebaseView {}
+
+impl Focusable for InteractiveRebaseView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for InteractiveRebaseView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let validation_error = self.validation_error();
+        let entries = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(ix, entry)| self.render_entry(ix, entry, window, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .id("interactive_rebase_view")
+            .key_context("InteractiveRebaseView")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .h(rems_from_px(41.))
+                    .pl_3()
+                    .pr_2()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new(format!(
+                            "Rebase {} commits onto {}",
+                            self.entries.len(),
+                            &self.base[..SHORT_SHA_LENGTH.min(self.base.len())]
+                        ))
+                        .color(Color::Muted),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .children(validation_error.map(|error| {
+                                Label::new(error).size(LabelSize::Small).color(Color::Error)
+                            }))
+                            .child(
+                                Button::new("start-rebase", "Start Rebase")
+                                    .style(ButtonStyle::Filled)
+                                    .disabled(
+                                        self.rebasing
+                                            || validation_error.is_some()
+                                            || self.entries.is_empty(),
+                                    )
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.start_rebase(window, cx);
+                                    })),
+                            ),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .id("rebase-entries")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .children(entries),
+            )
+    }
+}
+
+impl Item for InteractiveRebaseView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Interactive Rebase".into()
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::ListTodo))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("interactive rebase")
+    }
+}
@@ -7,7 +7,7 @@ use gpui::{
 };
 use language::{Buffer, Capability, OffsetRangeExt};
 use multi_buffer::PathKey;
-use project::Project;
+use project::{Project, git_store::Repository};
 use std::{
     any::{Any, TypeId},
     path::{Path, PathBuf},
@@ -185,36 +185,117 @@ impl MultiDiffView {
             let (entries, common_root) = load_entries(diff_pairs, &project, cx).await?;
 
             workspace.update_in(cx, |workspace, window, cx| {
-                let multibuffer = cx.new(|cx| {
-                    let mut multibuffer = MultiBuffer::new(Capability::ReadWrite);
-                    multibuffer.set_all_diff_hunks_expanded(cx);
-                    multibuffer
-                });
+                Self::add_to_workspace(
+                    entries,
+                    &common_root,
+                    Capability::ReadWrite,
+                    project,
+                    context_lines,
+                    workspace,
+                    window,
+                    cx,
+                )
+            })
+        })
+    }
 
-                let file_count = entries.len();
-                for entry in entries {
-                    register_entry(&multibuffer, entry, &common_root, context_lines, cx);
-                }
+    /// Opens the changes between two commits of `repository`, as if `head` were a
+    /// commit on top of `base`.
+    pub fn open_commit_comparison(
+        repository: Entity<Repository>,
+        base: String,
+        head: String,
+        workspace: &Workspace,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        let project = workspace.project().clone();
+        let language_registry = project.read(cx).languages().clone();
+        let workspace = workspace.weak_handle();
+        let context_lines = multibuffer_context_lines(cx);
+        let commit_diff =
+            repository.update(cx, |repository, _| repository.compare_commits(base, head));
 
-                let diff_view = cx.new(|cx| {
-                    Self::new(multibuffer.clone(), project.clone(), file_count, window, cx)
-                });
+        window.spawn(cx, async move |cx| {
+            let commit_diff = commit_diff.await??;
 
-                let pane = workspace.active_pane();
-                pane.update(cx, |pane, cx| {
-                    pane.add_item(Box::new(diff_view.clone()), true, true, None, window, cx);
+            let mut entries = Vec::with_capacity(commit_diff.files.len());
+            for (index, file) in commit_diff.files.into_iter().enumerate() {
+                if file.is_binary {
+                    continue;
+                }
+                let new_path = file.path.as_std_path().to_path_buf();
+                let language = language_registry
+                    .load_language_for_file_path(&new_path)
+                    .await
+                    .ok();
+                let old_buffer = cx.new(|cx| Buffer::local(file.old_text.unwrap_or_default(), cx));
+                let new_buffer = cx.new(|cx| {
+                    let mut buffer = Buffer::local(file.new_text.unwrap_or_default(), cx);
+                    buffer.set_language(language, cx);
+                    buffer
                 });
-
-                // Hide the left dock (file explorer) for a cleaner diff view
-                workspace.left_dock().update(cx, |dock, cx| {
-                    dock.set_open(false, window, cx);
+                let diff = build_buffer_diff(&old_buffer, &new_buffer, cx).await?;
+                entries.push(Entry {
+                    index,
+                    new_path,
+                    new_buffer,
+                    diff,
                 });
+            }
 
-                diff_view
+            workspace.update_in(cx, |workspace, window, cx| {
+                Self::add_to_workspace(
+                    entries,
+                    &Some(PathBuf::new()),
+                    Capability::ReadOnly,
+                    project,
+                    context_lines,
+                    workspace,
+                    window,
+                    cx,
+                )
             })
         })
     }
 
+    fn add_to_workspace(
+        entries: Vec<Entry>,
+        common_root: &Option<PathBuf>,
+        capability: Capability,
+        project: Entity<Project>,
+        context_lines: u32,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::new(capability);
+            multibuffer.set_all_diff_hunks_expanded(cx);
+            multibuffer
+        });
+
+        let file_count = entries.len();
+        for entry in entries {
+            register_entry(&multibuffer, entry, common_root, context_lines, cx);
+        }
+
+        let diff_view =
+            cx.new(|cx| Self::new(multibuffer.clone(), project.clone(), file_count, window, cx));
+
+        let pane = workspace.active_pane();
+        pane.update(cx, |pane, cx| {
+            pane.add_item(Box::new(diff_view.clone()), true, true, None, window, cx);
+        });
+
+        // Hide the left dock (file explorer) for a cleaner diff view
+        workspace.left_dock().update(cx, |dock, cx| {
+            dock.set_open(false, window, cx);
+        });
+
+        diff_view
+    }
+
     fn new(
         multibuffer: Entity<MultiBuffer>,
         project: Entity<Project>,
@@ -3,7 +3,10 @@ use git::status::{FileStatus, StatusCode, TrackedStatus, UnmergedStatus, Unmerge
 use gpui::{App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task, WeakEntity};
 use itertools::Itertools;
 use picker::{Picker, PickerDelegate, PickerEditorPosition};
-use project::{Project, git_store::Repository};
+use project::{
+    Project,
+    git_store::{GitStore, Repository},
+};
 use std::sync::Arc;
 use ui::{ListItem, ListItemSpacing, prelude::*};
 use workspace::{ModalView, Workspace};
@@ -65,6 +68,7 @@ impl RepositorySelector {
             .unwrap_or(0);
         let delegate = RepositorySelectorDelegate {
             repository_selector: cx.entity().downgrade(),
+            git_store,
             repository_entries,
             filtered_repositories,
             active_repository,
@@ -134,6 +138,7 @@ impl ModalView for RepositorySelector {}
 
 pub struct RepositorySelectorDelegate {
     repository_selector: WeakEntity<RepositorySelector>,
+    git_store: Entity<GitStore>,
     repository_entries: Vec<Entity<Repository>>,
     filtered_repositories: Vec<Entity<Repository>>,
     active_repository: Option<Entity<Repository>>,
@@ -267,6 +272,13 @@ impl PickerDelegate for RepositorySelectorDelegate {
             .active_repository
             .as_ref()
             .is_some_and(|active| active == repo_info);
+        // Initialized submodules are discovered as repositories of their own, so they
+        // can be selected like any other; mark them with the repository containing them.
+        let parent_name = self
+            .git_store
+            .read(cx)
+            .submodule_for_abs_path(&repo.work_directory_abs_path, cx)
+            .map(|(parent, _)| parent.read(cx).display_name());
 
         let mut item = ListItem::new(ix)
             .inset(true)
@@ -276,6 +288,13 @@ impl PickerDelegate for RepositorySelectorDelegate {
                 h_flex()
                     .gap_1()
                     .child(Label::new(display_name))
+                    .when_some(parent_name, |this, parent_name| {
+                        this.child(
+                            Label::new(format!("submodule of {parent_name}"))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    })
                     .when(is_active, |this| {
                         this.child(
                             Icon::new(IconName::Check)
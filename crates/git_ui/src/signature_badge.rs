@@ -0,0 +1,25 @@
+use git::signing::{CommitSignature, SignatureStatus};
+use ui::{Tooltip, prelude::*};
+
+/// Shows whether a commit's signature checks out, naming the signer in a tooltip.
+pub fn signature_badge(id: impl Into<ElementId>, signature: &CommitSignature) -> AnyElement {
+    let (icon, color) = match signature.status {
+        SignatureStatus::Verified => (IconName::ShieldCheck, Color::Success),
+        SignatureStatus::Unknown => (IconName::Info, Color::Muted),
+        SignatureStatus::Unverified => (IconName::XCircle, Color::Warning),
+    };
+    let label = signature.status.label();
+    let tooltip: SharedString = match (&signature.signer, signature.status) {
+        (Some(signer), _) => format!("{label} signature by {signer}").into(),
+        (None, SignatureStatus::Unknown) => "Signed with a key that isn't known".into(),
+        (None, _) => format!("{label} signature").into(),
+    };
+
+    h_flex()
+        .id(id)
+        .gap_0p5()
+        .child(Icon::new(icon).size(IconSize::XSmall).color(color))
+        .child(Label::new(label).size(LabelSize::XSmall).color(color))
+        .tooltip(Tooltip::text(tooltip))
+        .into_any_element()
+}
@@ -9,11 +9,12 @@ use anyhow::anyhow;
 use image::RgbaImage;
 use std::{future::Future, rc::Rc, sync::Arc, time::Duration};
 
-/// A test context that uses real macOS rendering instead of mocked rendering.
+/// A test context that uses real rendering instead of mocked rendering.
 /// This is used for visual tests that need to capture actual screenshots.
 ///
 /// Unlike `TestAppContext` which uses `TestPlatform` with mocked rendering,
-/// `VisualTestAppContext` uses the real `MacPlatform` to produce actual rendered output.
+/// `VisualTestAppContext` uses a `VisualTestPlatform` to produce actual rendered output:
+/// the real `MacPlatform` on macOS, and a CPU rasterizer with the real text system on Linux.
 ///
 /// Windows created through this context are positioned off-screen (at coordinates like -10000, -10000)
 /// so they are invisible to the user but still fully rendered by the compositor.
@@ -32,11 +33,11 @@ pub struct VisualTestAppContext {
 }
 
 impl VisualTestAppContext {
-    /// Creates a new `VisualTestAppContext` with real macOS platform rendering
+    /// Creates a new `VisualTestAppContext` with real platform rendering
     /// but deterministic task scheduling via TestDispatcher.
     ///
     /// This provides:
-    /// - Real Metal/compositor rendering for accurate screenshots
+    /// - Real rendering for accurate screenshots (Metal on macOS, CPU on Linux)
     /// - Deterministic task scheduling via TestDispatcher
     /// - Controllable time via `advance_clock`
     ///
@@ -57,7 +58,7 @@ impl VisualTestAppContext {
             .and_then(|s| s.parse().ok())
             .unwrap_or(0);
 
-        // Create a visual test platform that combines real Mac rendering
+        // Create a visual test platform that combines real rendering
         // with controllable TestDispatcher for deterministic task scheduling
         let platform = Rc::new(VisualTestPlatform::new(seed));
 
@@ -375,8 +376,8 @@ impl VisualTestAppContext {
 
     /// Captures a screenshot of the specified window using direct texture capture.
     ///
-    /// This renders the scene to a Metal texture and reads the pixels directly,
-    /// which does not require the window to be visible on screen.
+    /// This renders the scene to a Metal texture, or rasterizes it on the CPU on Linux, and
+    /// reads the pixels directly, which does not require the window to be visible on screen.
     #[cfg(any(test, feature = "test-support"))]
     pub fn capture_screenshot(&mut self, window: AnyWindowHandle) -> Result<RgbaImage> {
         self.update_window(window, |_, window, _cx| window.render_to_image())?
@@ -483,15 +484,16 @@ mod tests {
     use crate::Empty;
     use std::cell::RefCell;
 
-    // Note: All VisualTestAppContext tests are ignored by default because they require
+    // Note: All VisualTestAppContext tests are ignored on macOS because they require
     // the macOS main thread. Standard Rust tests run on worker threads, which causes
-    // SIGABRT when interacting with macOS AppKit/Cocoa APIs.
+    // SIGABRT when interacting with macOS AppKit/Cocoa APIs. The Linux platform has no
+    // such requirement, so they run there by default.
     //
-    // To run these tests, use:
+    // To run these tests on macOS, use:
     // cargo test -p gpui visual_test_context -- --ignored --test-threads=1
 
     #[test]
-    #[ignore] // Requires macOS main thread
+    #[cfg_attr(target_os = "macos", ignore)] // Requires macOS main thread
     fn test_foreground_tasks_run_with_run_until_parked() {
         let mut cx = VisualTestAppContext::new();
 
@@ -520,7 +522,7 @@ mod tests {
     }
 
     #[test]
-    #[ignore] // Requires macOS main thread
+    #[cfg_attr(target_os = "macos", ignore)] // Requires macOS main thread
     fn test_advance_clock_triggers_delayed_tasks() {
         let mut cx = VisualTestAppContext::new();
 
@@ -551,7 +553,7 @@ mod tests {
     }
 
     #[test]
-    #[ignore] // Requires macOS main thread - window creation fails on test threads
+    #[cfg_attr(target_os = "macos", ignore)] // Requires macOS main thread - window creation fails on test threads
     fn test_window_spawn_uses_test_dispatcher() {
         let mut cx = VisualTestAppContext::new();
 
@@ -20,7 +20,16 @@ mod blade;
 #[cfg(any(test, feature = "test-support"))]
 mod test;
 
-#[cfg(all(target_os = "macos", any(test, feature = "test-support")))]
+#[cfg(all(
+    any(test, feature = "test-support"),
+    any(
+        target_os = "macos",
+        all(
+            any(target_os = "linux", target_os = "freebsd"),
+            any(feature = "wayland", feature = "x11")
+        )
+    )
+))]
 mod visual_test;
 
 #[cfg(target_os = "windows")]
@@ -93,7 +102,16 @@ pub use linux::layer_shell;
 #[cfg(any(test, feature = "test-support"))]
 pub use test::{TestDispatcher, TestScreenCaptureSource, TestScreenCaptureStream};
 
-#[cfg(all(target_os = "macos", any(test, feature = "test-support")))]
+#[cfg(all(
+    any(test, feature = "test-support"),
+    any(
+        target_os = "macos",
+        all(
+            any(target_os = "linux", target_os = "freebsd"),
+            any(feature = "wayland", feature = "x11")
+        )
+    )
+))]
 pub use visual_test::VisualTestPlatform;
 
 /// Returns a background executor for the current platform.
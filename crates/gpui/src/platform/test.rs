@@ -1,6 +1,7 @@
 mod dispatcher;
 mod display;
 mod platform;
+mod software_renderer;
 mod window;
 
 pub use dispatcher::*;
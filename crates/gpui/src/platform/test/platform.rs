@@ -10,7 +10,7 @@ use collections::VecDeque;
 use futures::channel::oneshot;
 use parking_lot::Mutex;
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     path::{Path, PathBuf},
     rc::{Rc, Weak},
     sync::Arc,
@@ -39,6 +39,8 @@ pub(crate) struct TestPlatform {
     pub opened_url: RefCell<Option<String>>,
     pub text_system: Arc<dyn PlatformTextSystem>,
     pub expect_restart: RefCell<Option<oneshot::Sender<Option<PathBuf>>>>,
+    /// Whether windows keep the pixels of their sprites, to rasterize scenes on the CPU.
+    software_rendering: Cell<bool>,
     #[cfg(target_os = "windows")]
     bitmap_factory: std::mem::ManuallyDrop<IWICImagingFactory>,
     weak: Weak<Self>,
@@ -126,9 +128,16 @@ impl TestPlatform {
             #[cfg(target_os = "windows")]
             bitmap_factory,
             text_system,
+            software_rendering: Cell::new(false),
         })
     }
 
+    /// Makes windows opened from now on keep what they need to render their scenes in software,
+    /// for screenshots.
+    pub(crate) fn enable_software_rendering(&self) {
+        self.software_rendering.set(true);
+    }
+
     pub(crate) fn simulate_new_path_selection(
         &self,
         select_path: impl FnOnce(&std::path::Path) -> Option<std::path::PathBuf>,
@@ -325,6 +334,7 @@ impl Platform for TestPlatform {
             params,
             self.weak.clone(),
             self.active_display.clone(),
+            self.software_rendering.get(),
         );
         Ok(Box::new(window))
     }
@@ -0,0 +1,723 @@
+//! Rasterizes a [`Scene`] on the CPU, so test windows can be captured without a GPU.
+//!
+//! The shading follows the Blade shaders closely enough for screenshot tests, but isn't
+//! identical to them: blending happens in sRGB, gradients are always interpolated in sRGB,
+//! dashed borders are drawn solid, sprites are sampled without filtering, and surfaces aren't
+//! drawn at all.
+
+use crate::{
+    AtlasTextureId, AtlasTextureKind, AtlasTile, Background, BackgroundTag, Bounds, Corners,
+    DevicePixels, MonochromeSprite, Path, PolychromeSprite, PrimitiveBatch, Quad, Rgba,
+    ScaledPixels, Scene, Shadow, Size, SubpixelSprite, TransformationMatrix, Underline,
+};
+use collections::HashMap;
+use image::RgbaImage;
+use std::f32::consts::{PI, SQRT_2};
+
+/// How many samples are taken along each axis of a pixel when rasterizing paths.
+const PATH_SUBSAMPLES: usize = 4;
+
+const GRAYSCALE_FACTORS: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// The pixels of an atlas texture, as uploaded by the text system or the image cache.
+pub(crate) struct SoftwareTexture {
+    pub(crate) size: Size<DevicePixels>,
+    pub(crate) bytes: Vec<u8>,
+}
+
+impl SoftwareTexture {
+    /// Returns the bytes of the texel under `(x, y)`, a point within `bounds` that the tile is
+    /// stretched over.
+    fn sample(&self, tile: &AtlasTile, bounds: &Rect, x: f32, y: f32) -> Option<&[u8]> {
+        let bytes_per_pixel = match tile.texture_id.kind {
+            AtlasTextureKind::Monochrome => 1,
+            AtlasTextureKind::Polychrome | AtlasTextureKind::Subpixel => 4,
+        };
+        let tile_origin = tile.bounds.origin;
+        let tile_size = tile.bounds.size;
+        let u = (x - bounds.left) / bounds.width;
+        let v = (y - bounds.top) / bounds.height;
+        let column = tile_origin.x.0 + ((u * tile_size.width.0 as f32) as i32);
+        let row = tile_origin.y.0 + ((v * tile_size.height.0 as f32) as i32);
+        if column < 0 || row < 0 || column >= self.size.width.0 || row >= self.size.height.0 {
+            return None;
+        }
+        let start = (row as usize * self.size.width.0 as usize + column as usize) * bytes_per_pixel;
+        self.bytes.get(start..start + bytes_per_pixel)
+    }
+}
+
+/// Draws `scene` onto a transparent image of the given size.
+pub(crate) fn render_scene(
+    scene: &Scene,
+    textures: &HashMap<AtlasTextureId, SoftwareTexture>,
+    size: Size<DevicePixels>,
+) -> RgbaImage {
+    let mut canvas = Canvas::new(size.width.0.max(0) as usize, size.height.0.max(0) as usize);
+    for batch in scene.batches() {
+        match batch {
+            PrimitiveBatch::Shadows(range) => {
+                for shadow in &scene.shadows[range] {
+                    canvas.draw_shadow(shadow);
+                }
+            }
+            PrimitiveBatch::Quads(range) => {
+                for quad in &scene.quads[range] {
+                    canvas.draw_quad(quad);
+                }
+            }
+            PrimitiveBatch::Paths(range) => {
+                for path in &scene.paths[range] {
+                    canvas.draw_path(path);
+                }
+            }
+            PrimitiveBatch::Underlines(range) => {
+                for underline in &scene.underlines[range] {
+                    canvas.draw_underline(underline);
+                }
+            }
+            PrimitiveBatch::MonochromeSprites { texture_id, range } => {
+                let Some(texture) = textures.get(&texture_id) else {
+                    continue;
+                };
+                for sprite in &scene.monochrome_sprites[range] {
+                    canvas.draw_monochrome_sprite(sprite, texture);
+                }
+            }
+            PrimitiveBatch::SubpixelSprites { texture_id, range } => {
+                let Some(texture) = textures.get(&texture_id) else {
+                    continue;
+                };
+                for sprite in &scene.subpixel_sprites[range] {
+                    canvas.draw_subpixel_sprite(sprite, texture);
+                }
+            }
+            PrimitiveBatch::PolychromeSprites { texture_id, range } => {
+                let Some(texture) = textures.get(&texture_id) else {
+                    continue;
+                };
+                for sprite in &scene.polychrome_sprites[range] {
+                    canvas.draw_polychrome_sprite(sprite, texture);
+                }
+            }
+            PrimitiveBatch::Surfaces(_) => {}
+        }
+    }
+    canvas.into_image()
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Rect {
+    left: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+}
+
+impl Rect {
+    fn bounding(points: &[(f32, f32)]) -> Self {
+        let left = points.iter().map(|p| p.0).fold(f32::INFINITY, f32::min);
+        let right = points.iter().map(|p| p.0).fold(f32::NEG_INFINITY, f32::max);
+        let top = points.iter().map(|p| p.1).fold(f32::INFINITY, f32::min);
+        let bottom = points.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+        Self {
+            left,
+            top,
+            width: right - left,
+            height: bottom - top,
+        }
+    }
+
+    fn right(&self) -> f32 {
+        self.left + self.width
+    }
+
+    fn bottom(&self) -> f32 {
+        self.top + self.height
+    }
+
+    fn center(&self) -> (f32, f32) {
+        (self.left + self.width / 2., self.top + self.height / 2.)
+    }
+
+    fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.left && x < self.right() && y >= self.top && y < self.bottom()
+    }
+
+    fn dilate(&self, amount: f32) -> Self {
+        Self {
+            left: self.left - amount,
+            top: self.top - amount,
+            width: self.width + amount * 2.,
+            height: self.height + amount * 2.,
+        }
+    }
+
+    fn corners(&self) -> [(f32, f32); 4] {
+        [
+            (self.left, self.top),
+            (self.right(), self.top),
+            (self.right(), self.bottom()),
+            (self.left, self.bottom()),
+        ]
+    }
+}
+
+impl From<&Bounds<ScaledPixels>> for Rect {
+    fn from(bounds: &Bounds<ScaledPixels>) -> Self {
+        Self {
+            left: bounds.origin.x.0,
+            top: bounds.origin.y.0,
+            width: bounds.size.width.0,
+            height: bounds.size.height.0,
+        }
+    }
+}
+
+/// An image being drawn, holding premultiplied colors.
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0.; 4]; width * height],
+        }
+    }
+
+    /// Blends the premultiplied color `shade` returns over each pixel whose center lies within
+    /// both `area` and `clip`.
+    fn fill(&mut self, area: Rect, clip: &Rect, mut shade: impl FnMut(f32, f32) -> [f32; 4]) {
+        let left = area.left.max(clip.left).max(0.).floor() as usize;
+        let top = area.top.max(clip.top).max(0.).floor() as usize;
+        let right = (area.right().min(clip.right()).max(0.).ceil() as usize).min(self.width);
+        let bottom = (area.bottom().min(clip.bottom()).max(0.).ceil() as usize).min(self.height);
+        for row in top..bottom {
+            for column in left..right {
+                let (x, y) = (column as f32 + 0.5, row as f32 + 0.5);
+                if !area.contains(x, y) || !clip.contains(x, y) {
+                    continue;
+                }
+                let source = shade(x, y);
+                let source_alpha = source[3].min(1.);
+                if source_alpha <= 0. {
+                    continue;
+                }
+                let destination = &mut self.pixels[row * self.width + column];
+                for (destination, source) in destination.iter_mut().zip(source) {
+                    *destination = source + *destination * (1. - source_alpha);
+                }
+            }
+        }
+    }
+
+    fn draw_shadow(&mut self, shadow: &Shadow) {
+        let bounds = Rect::from(&shadow.bounds);
+        let clip = Rect::from(&shadow.content_mask.bounds);
+        let blur_radius = shadow.blur_radius.0;
+        let color = shadow.color.to_rgb();
+        self.fill(bounds.dilate(3. * blur_radius), &clip, |x, y| {
+            let corner_radius = pick_corner_radius(&bounds, &shadow.corner_radii, x, y);
+            let distance = rounded_rect_distance(&bounds, corner_radius, x, y);
+            let alpha = if blur_radius > 0. {
+                0.5 - 0.5 * erf(distance / (SQRT_2 * blur_radius))
+            } else {
+                coverage(distance)
+            };
+            premultiply(color, alpha)
+        });
+    }
+
+    fn draw_quad(&mut self, quad: &Quad) {
+        let bounds = Rect::from(&quad.bounds);
+        let clip = Rect::from(&quad.content_mask.bounds);
+        let widths = &quad.border_widths;
+        let (top, right, bottom, left) =
+            (widths.top.0, widths.right.0, widths.bottom.0, widths.left.0);
+        let has_border = top > 0. || right > 0. || bottom > 0. || left > 0.;
+        let inner = Rect {
+            left: bounds.left + left,
+            top: bounds.top + top,
+            width: bounds.width - left - right,
+            height: bounds.height - top - bottom,
+        };
+        let border_color = premultiply(quad.border_color.to_rgb(), 1.);
+        let (center_x, center_y) = bounds.center();
+
+        self.fill(bounds, &clip, |x, y| {
+            let corner_radius = pick_corner_radius(&bounds, &quad.corner_radii, x, y);
+            let outer = coverage(rounded_rect_distance(&bounds, corner_radius, x, y));
+            let background = premultiply(background_color(&quad.background, &bounds, x, y), 1.);
+            if !has_border {
+                return scale(background, outer);
+            }
+
+            let inner_coverage = if inner.width > 0. && inner.height > 0. {
+                let horizontal = if x < center_x { left } else { right };
+                let vertical = if y < center_y { top } else { bottom };
+                let inner_radius = (corner_radius - horizontal.max(vertical)).max(0.);
+                coverage(rounded_rect_distance(&inner, inner_radius, x, y))
+            } else {
+                0.
+            };
+            let color = std::array::from_fn(|channel| {
+                border_color[channel]
+                    + (background[channel] - border_color[channel]) * inner_coverage
+            });
+            scale(color, outer)
+        });
+    }
+
+    fn draw_path(&mut self, path: &Path<ScaledPixels>) {
+        let bounds = Rect::from(&path.bounds);
+        let clip = Rect::from(&path.content_mask.bounds);
+        for triangle in path.vertices.chunks_exact(3) {
+            let points = [0, 1, 2].map(|ix| {
+                let position = triangle[ix].xy_position;
+                (position.x.0, position.y.0)
+            });
+            let st = [0, 1, 2].map(|ix| triangle[ix].st_position);
+            self.fill(Rect::bounding(&points).dilate(0.5), &clip, |x, y| {
+                let mut hits = 0;
+                for row in 0..PATH_SUBSAMPLES {
+                    for column in 0..PATH_SUBSAMPLES {
+                        let sample_x = x - 0.5 + (column as f32 + 0.5) / PATH_SUBSAMPLES as f32;
+                        let sample_y = y - 0.5 + (row as f32 + 0.5) / PATH_SUBSAMPLES as f32;
+                        let Some(weights) = barycentric(&points, sample_x, sample_y) else {
+                            continue;
+                        };
+                        let s = weights[0] * st[0].x + weights[1] * st[1].x + weights[2] * st[2].x;
+                        let t = weights[0] * st[0].y + weights[1] * st[1].y + weights[2] * st[2].y;
+                        if s * s - t <= 0. {
+                            hits += 1;
+                        }
+                    }
+                }
+                let alpha = hits as f32 / (PATH_SUBSAMPLES * PATH_SUBSAMPLES) as f32;
+                premultiply(background_color(&path.color, &bounds, x, y), alpha)
+            });
+        }
+    }
+
+    fn draw_underline(&mut self, underline: &Underline) {
+        const WAVE_FREQUENCY: f32 = 2.0;
+        const WAVE_HEIGHT_RATIO: f32 = 0.8;
+
+        let bounds = Rect::from(&underline.bounds);
+        let clip = Rect::from(&underline.content_mask.bounds);
+        let color = underline.color.to_rgb();
+        let thickness = underline.thickness.0;
+        let wavy = underline.wavy & 0xFF != 0;
+        self.fill(bounds, &clip, |x, y| {
+            if !wavy {
+                return premultiply(color, 1.);
+            }
+            let height = bounds.height;
+            let s = (x - bounds.left) / height;
+            let t = (y - bounds.top) / height - 0.5;
+            let frequency = PI * WAVE_FREQUENCY * thickness / height;
+            let amplitude = (thickness * WAVE_HEIGHT_RATIO) / height;
+            let sine = (s * frequency).sin() * amplitude;
+            let slope = (s * frequency).cos() * amplitude * frequency;
+            let distance = (t - sine) / (1. + slope * slope).sqrt() * height;
+            let half_thickness = thickness / 2.;
+            let alpha =
+                (0.5 - (-(distance + half_thickness)).max(distance - half_thickness)).clamp(0., 1.);
+            premultiply(color, alpha)
+        });
+    }
+
+    fn draw_monochrome_sprite(&mut self, sprite: &MonochromeSprite, texture: &SoftwareTexture) {
+        let color = sprite.color.to_rgb();
+        self.draw_sprite(
+            &sprite.bounds,
+            &sprite.content_mask.bounds,
+            &sprite.transformation,
+            |bounds, x, y| {
+                let alpha = texture.sample(&sprite.tile, bounds, x, y)?[0] as f32 / 255.;
+                Some(premultiply(color, alpha))
+            },
+        );
+    }
+
+    fn draw_subpixel_sprite(&mut self, sprite: &SubpixelSprite, texture: &SoftwareTexture) {
+        let color = sprite.color.to_rgb();
+        self.draw_sprite(
+            &sprite.bounds,
+            &sprite.content_mask.bounds,
+            &sprite.transformation,
+            |bounds, x, y| {
+                // Without per-channel blending, use the average coverage of the subpixels.
+                let texel = texture.sample(&sprite.tile, bounds, x, y)?;
+                let alpha = (texel[0] as f32 + texel[1] as f32 + texel[2] as f32) / (3. * 255.);
+                Some(premultiply(color, alpha))
+            },
+        );
+    }
+
+    fn draw_polychrome_sprite(&mut self, sprite: &PolychromeSprite, texture: &SoftwareTexture) {
+        self.draw_sprite(
+            &sprite.bounds,
+            &sprite.content_mask.bounds,
+            &TransformationMatrix::unit(),
+            |bounds, x, y| {
+                let texel = texture.sample(&sprite.tile, bounds, x, y)?;
+                let mut color = Rgba {
+                    r: texel[2] as f32 / 255.,
+                    g: texel[1] as f32 / 255.,
+                    b: texel[0] as f32 / 255.,
+                    a: texel[3] as f32 / 255.,
+                };
+                if sprite.grayscale {
+                    let gray = color.r * GRAYSCALE_FACTORS[0]
+                        + color.g * GRAYSCALE_FACTORS[1]
+                        + color.b * GRAYSCALE_FACTORS[2];
+                    color = Rgba {
+                        r: gray,
+                        g: gray,
+                        b: gray,
+                        a: color.a,
+                    };
+                }
+                let corner_radius = pick_corner_radius(bounds, &sprite.corner_radii, x, y);
+                let distance = rounded_rect_distance(bounds, corner_radius, x, y);
+                Some(premultiply(color, sprite.opacity * coverage(distance)))
+            },
+        );
+    }
+
+    /// Fills the pixels covered by a sprite, after `transformation`, with the colors `shade`
+    /// returns for points within the untransformed `bounds`.
+    fn draw_sprite(
+        &mut self,
+        bounds: &Bounds<ScaledPixels>,
+        clip: &Bounds<ScaledPixels>,
+        transformation: &TransformationMatrix,
+        mut shade: impl FnMut(&Rect, f32, f32) -> Option<[f32; 4]>,
+    ) {
+        let bounds = Rect::from(bounds);
+        let clip = Rect::from(clip);
+        let [[a, b], [c, d]] = transformation.rotation_scale;
+        let [translate_x, translate_y] = transformation.translation;
+        let determinant = a * d - b * c;
+        if determinant.abs() < f32::EPSILON {
+            return;
+        }
+        let area = Rect::bounding(
+            &bounds
+                .corners()
+                .map(|(x, y)| (a * x + b * y + translate_x, c * x + d * y + translate_y)),
+        );
+        self.fill(area, &clip, |x, y| {
+            let (x, y) = (x - translate_x, y - translate_y);
+            let local_x = (d * x - b * y) / determinant;
+            let local_y = (a * y - c * x) / determinant;
+            if !bounds.contains(local_x, local_y) {
+                return [0.; 4];
+            }
+            shade(&bounds, local_x, local_y).unwrap_or([0.; 4])
+        });
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let mut image = RgbaImage::new(self.width as u32, self.height as u32);
+        for (pixel, color) in image.pixels_mut().zip(self.pixels) {
+            let alpha = color[3].clamp(0., 1.);
+            let unpremultiply = |channel: f32| {
+                let value = if alpha > 0. { channel / alpha } else { 0. };
+                (value.clamp(0., 1.) * 255.).round() as u8
+            };
+            pixel.0 = [
+                unpremultiply(color[0]),
+                unpremultiply(color[1]),
+                unpremultiply(color[2]),
+                (alpha * 255.).round() as u8,
+            ];
+        }
+        image
+    }
+}
+
+fn premultiply(color: Rgba, alpha: f32) -> [f32; 4] {
+    let alpha = (color.a * alpha).clamp(0., 1.);
+    [color.r * alpha, color.g * alpha, color.b * alpha, alpha]
+}
+
+fn scale(color: [f32; 4], factor: f32) -> [f32; 4] {
+    color.map(|channel| channel * factor)
+}
+
+/// How much of a pixel is covered by a shape, given the signed distance of its center to the
+/// shape's edge.
+fn coverage(distance: f32) -> f32 {
+    (0.5 - distance).clamp(0., 1.)
+}
+
+fn pick_corner_radius(bounds: &Rect, radii: &Corners<ScaledPixels>, x: f32, y: f32) -> f32 {
+    let (center_x, center_y) = bounds.center();
+    match (x < center_x, y < center_y) {
+        (true, true) => radii.top_left.0,
+        (false, true) => radii.top_right.0,
+        (false, false) => radii.bottom_right.0,
+        (true, false) => radii.bottom_left.0,
+    }
+}
+
+/// The signed distance from `(x, y)` to the edge of a rounded rectangle: positive outside,
+/// negative inside.
+fn rounded_rect_distance(bounds: &Rect, corner_radius: f32, x: f32, y: f32) -> f32 {
+    let (center_x, center_y) = bounds.center();
+    let corner_x = (x - center_x).abs() - bounds.width / 2. + corner_radius;
+    let corner_y = (y - center_y).abs() - bounds.height / 2. + corner_radius;
+    if corner_radius == 0. {
+        return corner_x.max(corner_y);
+    }
+    let outside = corner_x.max(0.).hypot(corner_y.max(0.));
+    let inside = corner_x.max(corner_y).min(0.);
+    outside + inside - corner_radius
+}
+
+/// The same approximation of the error function the shaders use for blurred shadows.
+fn erf(value: f32) -> f32 {
+    let magnitude = value.abs();
+    let r1 = 1.
+        + (0.278393 + (0.230389 + (0.000972 + 0.078108 * magnitude) * magnitude) * magnitude)
+            * magnitude;
+    let r2 = r1 * r1;
+    value.signum() * (1. - 1. / (r2 * r2))
+}
+
+fn background_color(background: &Background, bounds: &Rect, x: f32, y: f32) -> Rgba {
+    let solid = background.solid.to_rgb();
+    match background.tag {
+        BackgroundTag::Solid => solid,
+        BackgroundTag::LinearGradient => {
+            // -90 degrees to match the CSS gradient angle.
+            let angle = background.gradient_angle_or_pattern_height;
+            let radians = (angle % 360. - 90.).to_radians();
+            let (mut direction_x, mut direction_y) = (radians.cos(), radians.sin());
+            // Expand the short side to be the same as the long side.
+            if bounds.width > bounds.height {
+                direction_y *= bounds.height / bounds.width;
+            } else {
+                direction_x *= bounds.width / bounds.height;
+            }
+
+            let (center_x, center_y) = bounds.center();
+            let length = direction_x.hypot(direction_y);
+            let mut t = ((x - center_x) * direction_x + (y - center_y) * direction_y) / length;
+            t = if direction_x.abs() > direction_y.abs() {
+                (t + bounds.width / 2.) / bounds.width
+            } else {
+                (t + bounds.height / 2.) / bounds.height
+            };
+
+            let [stop0, stop1] = background.colors;
+            let t = ((t - stop0.percentage) / (stop1.percentage - stop0.percentage)).clamp(0., 1.);
+            let (from, to) = (stop0.color.to_rgb(), stop1.color.to_rgb());
+            Rgba {
+                r: from.r + (to.r - from.r) * t,
+                g: from.g + (to.g - from.g) * t,
+                b: from.b + (to.b - from.b) * t,
+                a: from.a + (to.a - from.a) * t,
+            }
+        }
+        BackgroundTag::PatternSlash => {
+            let encoded = background.gradient_angle_or_pattern_height;
+            let pattern_width = (encoded / 65535.) / 255.;
+            let pattern_interval = (encoded % 65535.) / 255.;
+            let pattern_height = pattern_width + pattern_interval;
+            let stripe_angle = PI / 4.;
+            let pattern_period = pattern_height * stripe_angle.sin();
+            let (relative_x, relative_y) = (x - bounds.left, y - bounds.top);
+            let rotated_x = stripe_angle.cos() * relative_x - stripe_angle.sin() * relative_y;
+            let pattern = rotated_x % pattern_period;
+            let distance = pattern.min(pattern_period - pattern)
+                - pattern_period * (pattern_width / pattern_height) / 2.;
+            Rgba {
+                a: solid.a * coverage(distance),
+                ..solid
+            }
+        }
+        BackgroundTag::Checkerboard => {
+            let size = background.gradient_angle_or_pattern_height;
+            let column = ((x - bounds.left) / size).floor();
+            let row = ((y - bounds.top) / size).floor();
+            Rgba {
+                a: solid.a * ((column + row) % 2.).clamp(0., 1.),
+                ..solid
+            }
+        }
+    }
+}
+
+/// The barycentric weights of `(x, y)` in a triangle, if it lies within it.
+fn barycentric(triangle: &[(f32, f32); 3], x: f32, y: f32) -> Option<[f32; 3]> {
+    let [(ax, ay), (bx, by), (cx, cy)] = *triangle;
+    let determinant = (by - cy) * (ax - cx) + (cx - bx) * (ay - cy);
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+    let w0 = ((by - cy) * (x - cx) + (cx - bx) * (y - cy)) / determinant;
+    let w1 = ((cy - ay) * (x - cx) + (ax - cx) * (y - cy)) / determinant;
+    let w2 = 1. - w0 - w1;
+    (w0 >= 0. && w1 >= 0. && w2 >= 0.).then_some([w0, w1, w2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ContentMask, Edges, Hsla, Point, bounds, point, px, rgb, size, solid_background};
+
+    fn scaled_bounds(x: f32, y: f32, width: f32, height: f32) -> Bounds<ScaledPixels> {
+        bounds(point(px(x), px(y)), size(px(width), px(height))).scale(1.)
+    }
+
+    fn render(scene: &mut Scene, width: i32, height: i32) -> RgbaImage {
+        scene.finish();
+        render_scene(
+            scene,
+            &HashMap::default(),
+            size(DevicePixels(width), DevicePixels(height)),
+        )
+    }
+
+    #[track_caller]
+    fn assert_pixel(image: &RgbaImage, x: u32, y: u32, expected: [u8; 4]) {
+        let actual = image.get_pixel(x, y).0;
+        let matches = actual
+            .iter()
+            .zip(expected)
+            .all(|(actual, expected)| actual.abs_diff(expected) <= 1);
+        assert!(
+            matches,
+            "pixel ({x}, {y}) was {actual:?}, expected {expected:?}"
+        );
+    }
+
+    fn quad(bounds: Bounds<ScaledPixels>, color: Hsla) -> Quad {
+        Quad {
+            order: 0,
+            border_style: Default::default(),
+            bounds,
+            content_mask: ContentMask {
+                bounds: scaled_bounds(0., 0., 100., 100.),
+            },
+            background: solid_background(color),
+            border_color: Default::default(),
+            corner_radii: Default::default(),
+            border_widths: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_quads_are_blended_in_order() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(quad(scaled_bounds(0., 0., 10., 10.), rgb(0xff0000).into()));
+        scene.insert_primitive(quad(
+            scaled_bounds(5., 0., 5., 10.),
+            Hsla {
+                a: 0.5,
+                ..rgb(0x0000ff).into()
+            },
+        ));
+        let image = render(&mut scene, 10, 10);
+
+        assert_pixel(&image, 2, 5, [255, 0, 0, 255]);
+        assert_pixel(&image, 7, 5, [128, 0, 128, 255]);
+    }
+
+    #[test]
+    fn test_quad_borders_and_clipping() {
+        let mut scene = Scene::default();
+        let mut bordered = quad(scaled_bounds(0., 0., 20., 20.), rgb(0xffffff).into());
+        bordered.border_color = rgb(0x000000).into();
+        bordered.border_widths = Edges::all(px(2.)).scale(1.);
+        bordered.content_mask.bounds = scaled_bounds(0., 0., 20., 10.);
+        scene.insert_primitive(bordered);
+        let image = render(&mut scene, 20, 20);
+
+        assert_pixel(&image, 0, 5, [0, 0, 0, 255]);
+        assert_pixel(&image, 10, 5, [255, 255, 255, 255]);
+        assert_pixel(&image, 10, 15, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_rounded_corners_are_antialiased() {
+        let mut scene = Scene::default();
+        let mut rounded = quad(scaled_bounds(0., 0., 20., 20.), rgb(0xffffff).into());
+        rounded.corner_radii = Corners::all(px(10.)).scale(1.);
+        scene.insert_primitive(rounded);
+        let image = render(&mut scene, 20, 20);
+
+        assert_eq!(image.get_pixel(0, 0).0[3], 0);
+        assert_pixel(&image, 10, 10, [255, 255, 255, 255]);
+        let edge_alpha = image.get_pixel(2, 3).0[3];
+        assert!(edge_alpha > 0 && edge_alpha < 255, "alpha was {edge_alpha}");
+    }
+
+    #[test]
+    fn test_paths_are_filled() {
+        let mut scene = Scene::default();
+        let mut path = Path::new(point(px(0.), px(0.)));
+        path.line_to(point(px(20.), px(0.)));
+        path.line_to(point(px(0.), px(20.)));
+        path.content_mask = ContentMask {
+            bounds: bounds(Point::default(), size(px(20.), px(20.))),
+        };
+        path.color = solid_background(rgb(0x00ff00));
+        scene.insert_primitive(path.scale(1.));
+        let image = render(&mut scene, 20, 20);
+
+        assert_pixel(&image, 2, 2, [0, 255, 0, 255]);
+        assert_eq!(image.get_pixel(17, 17).0[3], 0);
+    }
+
+    #[test]
+    fn test_monochrome_sprites_are_tinted() {
+        let texture_id = AtlasTextureId {
+            index: 1,
+            kind: AtlasTextureKind::Monochrome,
+        };
+        let tile = AtlasTile {
+            texture_id,
+            tile_id: crate::TileId(2),
+            padding: 0,
+            bounds: Bounds {
+                origin: Default::default(),
+                size: size(DevicePixels(2), DevicePixels(1)),
+            },
+        };
+        let mut textures = HashMap::default();
+        textures.insert(
+            texture_id,
+            SoftwareTexture {
+                size: tile.bounds.size,
+                bytes: vec![255, 0],
+            },
+        );
+
+        let mut scene = Scene::default();
+        scene.insert_primitive(MonochromeSprite {
+            order: 0,
+            pad: 0,
+            bounds: scaled_bounds(0., 0., 2., 1.),
+            content_mask: ContentMask {
+                bounds: scaled_bounds(0., 0., 2., 1.),
+            },
+            color: rgb(0x0000ff).into(),
+            tile,
+            transformation: TransformationMatrix::unit(),
+        });
+        scene.finish();
+        let image = render_scene(&scene, &textures, size(DevicePixels(2), DevicePixels(1)));
+
+        assert_pixel(&image, 0, 0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(1, 0).0[3], 0);
+    }
+}
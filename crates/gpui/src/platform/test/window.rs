@@ -1,10 +1,13 @@
+use super::software_renderer::{SoftwareTexture, render_scene};
 use crate::{
-    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DispatchEventResult, GpuSpecs,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, PromptButton, RequestFrameOptions, Size, TestPlatform, TileId, WindowAppearance,
-    WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowParams,
+    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DevicePixels,
+    DispatchEventResult, GpuSpecs, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
+    PlatformInputHandler, PlatformWindow, Point, PromptButton, RequestFrameOptions, Scene, Size,
+    TestPlatform, TileId, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
+    WindowControlArea, WindowParams,
 };
 use collections::HashMap;
+use image::RgbaImage;
 use parking_lot::Mutex;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use std::{
@@ -19,7 +22,7 @@ pub(crate) struct TestWindowState {
     pub(crate) title: Option<String>,
     pub(crate) edited: bool,
     platform: Weak<TestPlatform>,
-    sprite_atlas: Arc<dyn PlatformAtlas>,
+    sprite_atlas: Arc<TestAtlas>,
     pub(crate) should_close_handler: Option<Box<dyn FnMut() -> bool>>,
     hit_test_window_control_callback: Option<Box<dyn FnMut() -> Option<WindowControlArea>>>,
     input_callback: Option<Box<dyn FnMut(PlatformInput) -> DispatchEventResult>>,
@@ -56,13 +59,14 @@ impl TestWindow {
         params: WindowParams,
         platform: Weak<TestPlatform>,
         display: Rc<dyn PlatformDisplay>,
+        software_rendering: bool,
     ) -> Self {
         Self(Rc::new(Mutex::new(TestWindowState {
             bounds: params.bounds,
             display,
             platform,
             handle,
-            sprite_atlas: Arc::new(TestAtlas::new()),
+            sprite_atlas: Arc::new(TestAtlas::new(software_rendering)),
             title: Default::default(),
             edited: false,
             should_close_handler: None,
@@ -276,6 +280,21 @@ impl PlatformWindow for TestWindow {
 
     fn draw(&self, _scene: &crate::Scene) {}
 
+    fn render_to_image(&self, scene: &Scene) -> anyhow::Result<RgbaImage> {
+        let size = self.content_size().scale(self.scale_factor());
+        let size = Size {
+            width: DevicePixels(size.width.0.round() as i32),
+            height: DevicePixels(size.height.0.round() as i32),
+        };
+        let atlas = self.0.lock().sprite_atlas.clone();
+        let state = atlas.0.lock();
+        anyhow::ensure!(
+            state.software_rendering,
+            "software rendering isn't enabled for this window"
+        );
+        Ok(render_scene(scene, &state.textures, size))
+    }
+
     fn sprite_atlas(&self) -> sync::Arc<dyn crate::PlatformAtlas> {
         self.0.lock().sprite_atlas.clone()
     }
@@ -307,15 +326,21 @@ impl PlatformWindow for TestWindow {
 pub(crate) struct TestAtlasState {
     next_id: u32,
     tiles: HashMap<AtlasKey, AtlasTile>,
+    /// Whether to keep the pixels of tiles, which only the software renderer needs.
+    software_rendering: bool,
+    /// The pixels of each tile, which lives alone in its own texture.
+    textures: HashMap<AtlasTextureId, SoftwareTexture>,
 }
 
 pub(crate) struct TestAtlas(Mutex<TestAtlasState>);
 
 impl TestAtlas {
-    pub fn new() -> Self {
+    pub fn new(software_rendering: bool) -> Self {
         TestAtlas(Mutex::new(TestAtlasState {
             next_id: 0,
             tiles: HashMap::default(),
+            software_rendering,
+            textures: HashMap::default(),
         }))
     }
 }
@@ -334,7 +359,7 @@ impl PlatformAtlas for TestAtlas {
         }
         drop(state);
 
-        let Some((size, _)) = build()? else {
+        let Some((size, bytes)) = build()? else {
             return Ok(None);
         };
 
@@ -344,13 +369,23 @@ impl PlatformAtlas for TestAtlas {
         state.next_id += 1;
         let tile_id = state.next_id;
 
+        let texture_id = AtlasTextureId {
+            index: texture_id,
+            kind: key.texture_kind(),
+        };
+        if state.software_rendering {
+            state.textures.insert(
+                texture_id,
+                SoftwareTexture {
+                    size,
+                    bytes: bytes.into_owned(),
+                },
+            );
+        }
         state.tiles.insert(
             key.clone(),
             crate::AtlasTile {
-                texture_id: AtlasTextureId {
-                    index: texture_id,
-                    kind: crate::AtlasTextureKind::Monochrome,
-                },
+                texture_id,
                 tile_id: TileId(tile_id),
                 padding: 0,
                 bounds: crate::Bounds {
@@ -365,6 +400,8 @@ impl PlatformAtlas for TestAtlas {
 
     fn remove(&self, key: &AtlasKey) {
         let mut state = self.0.lock();
-        state.tiles.remove(key);
+        if let Some(tile) = state.tiles.remove(key) {
+            state.textures.remove(&tile.texture_id);
+        }
     }
 }
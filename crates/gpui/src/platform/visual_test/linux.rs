@@ -0,0 +1,237 @@
+//! The Linux visual test platform, which renders on the CPU.
+
+#[cfg(feature = "screen-capture")]
+use crate::ScreenCaptureSource;
+use crate::{
+    AnyWindowHandle, BackgroundExecutor, ClipboardItem, CosmicTextSystem, CursorStyle,
+    ForegroundExecutor, Keymap, Menu, MenuItem, OwnedMenu, PathPromptOptions, Platform,
+    PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem,
+    PlatformWindow, Task, TestDispatcher, TestPlatform, WindowAppearance, WindowParams,
+};
+use anyhow::Result;
+use futures::channel::oneshot;
+
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
+
+/// A platform that combines CPU rendering with controllable TestDispatcher.
+///
+/// Windows are test windows whose scenes are rasterized in software when captured, so visual
+/// tests run on machines without a GPU or a display server. Text is shaped and rasterized by
+/// the same text system Zed uses on Linux, so screenshots show real glyphs.
+pub struct VisualTestPlatform {
+    dispatcher: TestDispatcher,
+    test_platform: Rc<TestPlatform>,
+    text_system: Arc<dyn PlatformTextSystem>,
+}
+
+impl VisualTestPlatform {
+    /// Creates a new VisualTestPlatform with the given random seed.
+    ///
+    /// The seed is used for deterministic random number generation in the TestDispatcher.
+    pub fn new(seed: u64) -> Self {
+        let dispatcher = TestDispatcher::new(seed);
+        let arc_dispatcher = Arc::new(dispatcher.clone());
+
+        let background_executor = BackgroundExecutor::new(arc_dispatcher.clone());
+        let foreground_executor = ForegroundExecutor::new(arc_dispatcher);
+
+        let test_platform = TestPlatform::new(background_executor, foreground_executor);
+        test_platform.enable_software_rendering();
+
+        Self {
+            dispatcher,
+            test_platform,
+            text_system: Arc::new(CosmicTextSystem::new()),
+        }
+    }
+
+    /// Returns a reference to the TestDispatcher for controlling task scheduling and time.
+    pub fn dispatcher(&self) -> &TestDispatcher {
+        &self.dispatcher
+    }
+}
+
+impl Platform for VisualTestPlatform {
+    fn background_executor(&self) -> BackgroundExecutor {
+        self.test_platform.background_executor()
+    }
+
+    fn foreground_executor(&self) -> ForegroundExecutor {
+        self.test_platform.foreground_executor()
+    }
+
+    fn text_system(&self) -> Arc<dyn PlatformTextSystem> {
+        self.text_system.clone()
+    }
+
+    fn run(&self, _on_finish_launching: Box<dyn 'static + FnOnce()>) {
+        panic!("VisualTestPlatform::run should not be called in tests")
+    }
+
+    fn quit(&self) {}
+
+    fn restart(&self, _binary_path: Option<PathBuf>) {}
+
+    fn activate(&self, _ignoring_other_apps: bool) {}
+
+    fn hide(&self) {}
+
+    fn hide_other_apps(&self) {}
+
+    fn unhide_other_apps(&self) {}
+
+    fn displays(&self) -> Vec<Rc<dyn PlatformDisplay>> {
+        self.test_platform.displays()
+    }
+
+    fn primary_display(&self) -> Option<Rc<dyn PlatformDisplay>> {
+        self.test_platform.primary_display()
+    }
+
+    fn active_window(&self) -> Option<AnyWindowHandle> {
+        self.test_platform.active_window()
+    }
+
+    #[cfg(feature = "screen-capture")]
+    fn is_screen_capture_supported(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "screen-capture")]
+    fn screen_capture_sources(
+        &self,
+    ) -> oneshot::Receiver<Result<Vec<Rc<dyn ScreenCaptureSource>>>> {
+        let (tx, rx) = oneshot::channel();
+        tx.send(Ok(Vec::new())).ok();
+        rx
+    }
+
+    fn open_window(
+        &self,
+        handle: AnyWindowHandle,
+        options: WindowParams,
+    ) -> Result<Box<dyn PlatformWindow>> {
+        self.test_platform.open_window(handle, options)
+    }
+
+    fn window_appearance(&self) -> WindowAppearance {
+        self.test_platform.window_appearance()
+    }
+
+    fn open_url(&self, _url: &str) {}
+
+    fn on_open_urls(&self, _callback: Box<dyn FnMut(Vec<String>)>) {}
+
+    fn register_url_scheme(&self, _url: &str) -> Task<Result<()>> {
+        Task::ready(Ok(()))
+    }
+
+    fn prompt_for_paths(
+        &self,
+        _options: PathPromptOptions,
+    ) -> oneshot::Receiver<Result<Option<Vec<PathBuf>>>> {
+        let (tx, rx) = oneshot::channel();
+        tx.send(Ok(None)).ok();
+        rx
+    }
+
+    fn prompt_for_new_path(
+        &self,
+        _directory: &Path,
+        _suggested_name: Option<&str>,
+    ) -> oneshot::Receiver<Result<Option<PathBuf>>> {
+        let (tx, rx) = oneshot::channel();
+        tx.send(Ok(None)).ok();
+        rx
+    }
+
+    fn can_select_mixed_files_and_dirs(&self) -> bool {
+        true
+    }
+
+    fn reveal_path(&self, _path: &Path) {}
+
+    fn open_with_system(&self, _path: &Path) {}
+
+    fn on_quit(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn on_reopen(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn set_menus(&self, _menus: Vec<Menu>, _keymap: &Keymap) {}
+
+    fn get_menus(&self) -> Option<Vec<OwnedMenu>> {
+        None
+    }
+
+    fn set_dock_menu(&self, _menu: Vec<MenuItem>, _keymap: &Keymap) {}
+
+    fn on_app_menu_action(&self, _callback: Box<dyn FnMut(&dyn crate::Action)>) {}
+
+    fn on_will_open_app_menu(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn on_validate_app_menu_command(&self, _callback: Box<dyn FnMut(&dyn crate::Action) -> bool>) {}
+
+    fn app_path(&self) -> Result<PathBuf> {
+        Ok(std::env::current_exe()?)
+    }
+
+    fn path_for_auxiliary_executable(&self, name: &str) -> Result<PathBuf> {
+        anyhow::bail!("VisualTestPlatform has no auxiliary executables, requested {name:?}")
+    }
+
+    fn set_cursor_style(&self, style: CursorStyle) {
+        self.test_platform.set_cursor_style(style)
+    }
+
+    fn should_auto_hide_scrollbars(&self) -> bool {
+        self.test_platform.should_auto_hide_scrollbars()
+    }
+
+    fn read_from_clipboard(&self) -> Option<ClipboardItem> {
+        self.test_platform.read_from_clipboard()
+    }
+
+    fn write_to_clipboard(&self, item: ClipboardItem) {
+        self.test_platform.write_to_clipboard(item)
+    }
+
+    fn read_from_primary(&self) -> Option<ClipboardItem> {
+        self.test_platform.read_from_primary()
+    }
+
+    fn write_to_primary(&self, item: ClipboardItem) {
+        self.test_platform.write_to_primary(item)
+    }
+
+    fn write_credentials(&self, _url: &str, _username: &str, _password: &[u8]) -> Task<Result<()>> {
+        Task::ready(Ok(()))
+    }
+
+    fn read_credentials(&self, _url: &str) -> Task<Result<Option<(String, Vec<u8>)>>> {
+        Task::ready(Ok(None))
+    }
+
+    fn delete_credentials(&self, _url: &str) -> Task<Result<()>> {
+        Task::ready(Ok(()))
+    }
+
+    fn keyboard_layout(&self) -> Box<dyn PlatformKeyboardLayout> {
+        self.test_platform.keyboard_layout()
+    }
+
+    fn keyboard_mapper(&self) -> Rc<dyn PlatformKeyboardMapper> {
+        self.test_platform.keyboard_mapper()
+    }
+
+    fn on_keyboard_layout_change(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn thermal_state(&self) -> crate::ThermalState {
+        crate::ThermalState::Nominal
+    }
+
+    fn on_thermal_state_change(&self, _callback: Box<dyn FnMut()>) {}
+}
@@ -25,6 +25,11 @@
 //!   assert!(true)
 //! }
 //! ```
+
+mod screenshot;
+
+pub use screenshot::*;
+
 use crate::{Entity, Subscription, TestAppContext, TestDispatcher};
 use futures::StreamExt as _;
 use smol::channel;
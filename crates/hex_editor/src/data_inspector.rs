@@ -0,0 +1,147 @@
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+/// The byte order used to read multi-byte values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub fn label(self) -> &'static str {
+        match self {
+            Endianness::Little => "Little Endian",
+            Endianness::Big => "Big Endian",
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            Endianness::Little => Endianness::Big,
+            Endianness::Big => Endianness::Little,
+        }
+    }
+}
+
+/// Seconds between the Windows `FILETIME` epoch, 1601-01-01, and the Unix epoch.
+const FILETIME_UNIX_EPOCH_SECONDS: i64 = 11_644_473_600;
+
+/// Decodes the bytes at the start of `bytes` as each of the common value types, skipping types
+/// that need more bytes than there are.
+pub(crate) fn inspect(bytes: &[u8], endianness: Endianness) -> Vec<(&'static str, String)> {
+    let mut values = Vec::new();
+    let Some(&byte) = bytes.first() else {
+        return values;
+    };
+    values.push(("binary", format!("{byte:08b}")));
+    values.push(("u8", byte.to_string()));
+    values.push(("i8", (byte as i8).to_string()));
+    if let Some(bytes) = read::<2>(bytes, endianness) {
+        values.push(("u16", u16::from_le_bytes(bytes).to_string()));
+        values.push(("i16", i16::from_le_bytes(bytes).to_string()));
+    }
+    if let Some(bytes) = read::<4>(bytes, endianness) {
+        values.push(("u32", u32::from_le_bytes(bytes).to_string()));
+        values.push(("i32", i32::from_le_bytes(bytes).to_string()));
+        values.push(("f32", f32::from_le_bytes(bytes).to_string()));
+        values.push((
+            "time_t (32-bit)",
+            format_timestamp(i32::from_le_bytes(bytes).into()),
+        ));
+    }
+    if let Some(bytes) = read::<8>(bytes, endianness) {
+        values.push(("u64", u64::from_le_bytes(bytes).to_string()));
+        values.push(("i64", i64::from_le_bytes(bytes).to_string()));
+        values.push(("f64", f64::from_le_bytes(bytes).to_string()));
+        values.push((
+            "time_t (64-bit)",
+            format_timestamp(i64::from_le_bytes(bytes)),
+        ));
+        let filetime = u64::from_le_bytes(bytes) / 10_000_000;
+        values.push((
+            "FILETIME",
+            format_timestamp(filetime as i64 - FILETIME_UNIX_EPOCH_SECONDS),
+        ));
+    }
+    if let Some(character) = (1..=4).find_map(|len| {
+        let prefix = bytes.get(..len)?;
+        std::str::from_utf8(prefix).ok()?.chars().next()
+    }) {
+        values.push(("UTF-8", format!("{character:?}")));
+    }
+    values
+}
+
+/// Reads the first `N` bytes, reordered so that they can be decoded as little endian.
+fn read<const N: usize>(bytes: &[u8], endianness: Endianness) -> Option<[u8; N]> {
+    let mut bytes: [u8; N] = bytes.get(..N)?.try_into().ok()?;
+    if endianness == Endianness::Big {
+        bytes.reverse();
+    }
+    Some(bytes)
+}
+
+fn format_timestamp(seconds: i64) -> String {
+    OffsetDateTime::from_unix_timestamp(seconds)
+        .ok()
+        .and_then(|datetime| datetime.format(&Rfc3339).ok())
+        .unwrap_or_else(|| "Out of range".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(values: &[(&'static str, String)], label: &str) -> Option<String> {
+        values
+            .iter()
+            .find(|(value_label, _)| *value_label == label)
+            .map(|(_, value)| value.clone())
+    }
+
+    #[test]
+    fn test_inspect_integers() {
+        let bytes = [0xFE, 0xFF, 0x00, 0x01];
+
+        let values = inspect(&bytes, Endianness::Little);
+        assert_eq!(value(&values, "binary").unwrap(), "11111110");
+        assert_eq!(value(&values, "u8").unwrap(), "254");
+        assert_eq!(value(&values, "i8").unwrap(), "-2");
+        assert_eq!(value(&values, "u16").unwrap(), "65534");
+        assert_eq!(value(&values, "i16").unwrap(), "-2");
+        assert_eq!(value(&values, "u32").unwrap(), "16842750");
+        assert_eq!(value(&values, "u64"), None);
+
+        let values = inspect(&bytes, Endianness::Big);
+        assert_eq!(value(&values, "u16").unwrap(), "65279");
+        assert_eq!(value(&values, "u32").unwrap(), "4278124545");
+        assert_eq!(value(&values, "i32").unwrap(), "-16842751");
+    }
+
+    #[test]
+    fn test_inspect_floats_and_timestamps() {
+        let values = inspect(&1.5f64.to_be_bytes(), Endianness::Big);
+        assert_eq!(value(&values, "f64").unwrap(), "1.5");
+
+        let values = inspect(&1_000_000_000u32.to_le_bytes(), Endianness::Little);
+        assert_eq!(
+            value(&values, "time_t (32-bit)").unwrap(),
+            "2001-09-09T01:46:40Z"
+        );
+
+        let filetime = (1_000_000_000 + FILETIME_UNIX_EPOCH_SECONDS as u64) * 10_000_000;
+        let values = inspect(&filetime.to_le_bytes(), Endianness::Little);
+        assert_eq!(value(&values, "FILETIME").unwrap(), "2001-09-09T01:46:40Z");
+    }
+
+    #[test]
+    fn test_inspect_utf8() {
+        let values = inspect("é!".as_bytes(), Endianness::Little);
+        assert_eq!(value(&values, "UTF-8").unwrap(), "'é'");
+
+        let values = inspect(&[0xFF], Endianness::Little);
+        assert_eq!(value(&values, "UTF-8"), None);
+        assert_eq!(inspect(&[], Endianness::Little), Vec::new());
+    }
+}
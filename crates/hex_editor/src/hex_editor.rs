@@ -0,0 +1,1093 @@
+mod data_inspector;
+mod hex_file;
+mod hex_search;
+
+use std::ops::Range;
+
+use editor::{Editor, EditorEvent, items::entry_git_aware_label_color};
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, App, Context, Div, Entity, EventEmitter, FocusHandle, Focusable,
+    InteractiveElement, IntoElement, KeyDownEvent, MouseButton, ParentElement, Pixels, Render,
+    ScrollWheelEvent, Styled, Task, Window, actions, canvas, div, px,
+};
+use project::{Project, ProjectItem as _};
+use settings::Settings;
+use theme::ThemeSettings;
+use ui::{Tooltip, prelude::*};
+use util::paths::PathExt;
+use workspace::{
+    ItemSettings, Pane, WorkspaceId,
+    item::{Item, ItemEvent, ProjectItem, SaveOptions, TabContentParams},
+};
+
+use crate::hex_search::SearchMode;
+
+pub use crate::data_inspector::Endianness;
+pub use crate::hex_file::*;
+
+actions!(
+    hex_editor,
+    [
+        /// Moves the cursor to the previous byte.
+        MoveLeft,
+        /// Moves the cursor to the next byte.
+        MoveRight,
+        /// Moves the cursor up by one row.
+        MoveUp,
+        /// Moves the cursor down by one row.
+        MoveDown,
+        /// Moves the cursor up by one page.
+        PageUp,
+        /// Moves the cursor down by one page.
+        PageDown,
+        /// Moves the cursor to the first byte of its row.
+        MoveToRowStart,
+        /// Moves the cursor to the last byte of its row.
+        MoveToRowEnd,
+        /// Moves the cursor to the first byte of the file.
+        MoveToStart,
+        /// Moves the cursor to the last byte of the file.
+        MoveToEnd,
+        /// Switches typing between the hex and text columns.
+        SwitchColumn,
+        /// Cycles how many bytes are grouped into each word.
+        CycleWordSize,
+        /// Toggles between reading words as little and big endian.
+        ToggleEndianness,
+        /// Undoes the last edit.
+        Undo,
+        /// Redoes the last undone edit.
+        Redo,
+        /// Shows the search bar and focuses its query.
+        Search,
+        /// Toggles between searching for text and for hex bytes.
+        ToggleSearchMode,
+        /// Selects the next match of the search query.
+        SelectNextMatch,
+        /// Selects the previous match of the search query.
+        SelectPreviousMatch,
+        /// Shows a bar for jumping to an offset.
+        GoToOffset,
+        /// Confirms the query in the search or go to offset bar.
+        Confirm,
+        /// Hides the search or go to offset bar.
+        Dismiss
+    ]
+);
+
+const BYTES_PER_ROW: usize = 16;
+
+/// How many bytes are grouped together in the hex column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WordSize {
+    #[default]
+    One,
+    Two,
+    Four,
+    Eight,
+}
+
+impl WordSize {
+    pub fn byte_count(self) -> usize {
+        match self {
+            WordSize::One => 1,
+            WordSize::Two => 2,
+            WordSize::Four => 4,
+            WordSize::Eight => 8,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            WordSize::One => WordSize::Two,
+            WordSize::Two => WordSize::Four,
+            WordSize::Four => WordSize::Eight,
+            WordSize::Eight => WordSize::One,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            WordSize::One => "1 Byte",
+            WordSize::Two => "2 Bytes",
+            WordSize::Four => "4 Bytes",
+            WordSize::Eight => "8 Bytes",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Hex,
+    Text,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Bar {
+    Search,
+    GoToOffset,
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Next,
+    Previous,
+}
+
+/// An editor for binary files, showing each row of bytes as offset, hex and text columns.
+pub struct HexEditor {
+    hex_file: Entity<HexFile>,
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    cursor: usize,
+    /// Whether the next hex digit typed replaces the low nibble of the byte under the cursor.
+    low_nibble: bool,
+    column: Column,
+    /// Whether the next edit continues the previous one, so that both are undone together.
+    typing: bool,
+    word_size: WordSize,
+    endianness: Endianness,
+    top_row: usize,
+    /// How far past `top_row` the view has been scrolled, less than one row.
+    scroll_remainder: Pixels,
+    /// How many rows fit in the view, measured during the last layout.
+    visible_row_count: usize,
+    bar: Option<Bar>,
+    search_mode: SearchMode,
+    query_editor: Entity<Editor>,
+    active_match: Option<Range<usize>>,
+    bar_message: Option<SharedString>,
+}
+
+impl HexEditor {
+    pub fn new(
+        hex_file: Entity<HexFile>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let query_editor = cx.new(|cx| Editor::single_line(window, cx));
+        cx.subscribe(&hex_file, Self::on_hex_file_event).detach();
+        cx.subscribe(&query_editor, |this, _, event: &EditorEvent, cx| {
+            if let EditorEvent::BufferEdited = event {
+                this.active_match = None;
+                this.bar_message = None;
+                cx.notify();
+            }
+        })
+        .detach();
+
+        Self {
+            hex_file,
+            project,
+            focus_handle: cx.focus_handle(),
+            cursor: 0,
+            low_nibble: false,
+            column: Column::Hex,
+            typing: false,
+            word_size: WordSize::default(),
+            endianness: Endianness::default(),
+            top_row: 0,
+            scroll_remainder: px(0.),
+            visible_row_count: 0,
+            bar: None,
+            search_mode: SearchMode::default(),
+            query_editor,
+            active_match: None,
+            bar_message: None,
+        }
+    }
+
+    fn on_hex_file_event(
+        &mut self,
+        _: Entity<HexFile>,
+        event: &HexFileEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            HexFileEvent::Edited | HexFileEvent::Saved => {}
+            HexFileEvent::Reloaded => {
+                self.active_match = None;
+                self.move_cursor(self.cursor, cx);
+            }
+        }
+        cx.emit(ItemEvent::UpdateTab);
+        cx.notify();
+    }
+
+    fn row_height(cx: &App) -> Pixels {
+        let settings = ThemeSettings::get_global(cx);
+        settings.buffer_font_size(cx) * settings.line_height()
+    }
+
+    fn max_top_row(&self, cx: &App) -> usize {
+        self.hex_file
+            .read(cx)
+            .len()
+            .div_ceil(BYTES_PER_ROW)
+            .saturating_sub(self.visible_row_count.max(1))
+    }
+
+    fn move_cursor(&mut self, offset: usize, cx: &mut Context<Self>) {
+        let len = self.hex_file.read(cx).len();
+        self.cursor = offset.min(len.saturating_sub(1));
+        self.low_nibble = false;
+        self.typing = false;
+        self.autoscroll();
+        cx.notify();
+    }
+
+    fn move_cursor_by(&mut self, delta: isize, cx: &mut Context<Self>) {
+        self.move_cursor(self.cursor.saturating_add_signed(delta), cx);
+    }
+
+    /// Scrolls the least amount needed to make the cursor's row visible.
+    fn autoscroll(&mut self) {
+        let row = self.cursor / BYTES_PER_ROW;
+        if row < self.top_row || self.visible_row_count == 0 {
+            self.top_row = row;
+        } else if row >= self.top_row + self.visible_row_count {
+            self.top_row = row + 1 - self.visible_row_count;
+        } else {
+            return;
+        }
+        self.scroll_remainder = px(0.);
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_by(-1, cx);
+    }
+
+    fn move_right(&mut self, _: &MoveRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_by(1, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        if self.cursor >= BYTES_PER_ROW {
+            self.move_cursor_by(-(BYTES_PER_ROW as isize), cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_by(BYTES_PER_ROW as isize, cx);
+    }
+
+    fn page_up(&mut self, _: &PageUp, _: &mut Window, cx: &mut Context<Self>) {
+        let rows = self
+            .visible_row_count
+            .max(1)
+            .min(self.cursor / BYTES_PER_ROW);
+        self.move_cursor_by(-((rows * BYTES_PER_ROW) as isize), cx);
+    }
+
+    fn page_down(&mut self, _: &PageDown, _: &mut Window, cx: &mut Context<Self>) {
+        let rows = self.visible_row_count.max(1);
+        self.move_cursor_by((rows * BYTES_PER_ROW) as isize, cx);
+    }
+
+    fn move_to_row_start(&mut self, _: &MoveToRowStart, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(self.cursor - self.cursor % BYTES_PER_ROW, cx);
+    }
+
+    fn move_to_row_end(&mut self, _: &MoveToRowEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(
+            self.cursor - self.cursor % BYTES_PER_ROW + BYTES_PER_ROW - 1,
+            cx,
+        );
+    }
+
+    fn move_to_start(&mut self, _: &MoveToStart, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(0, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(usize::MAX, cx);
+    }
+
+    fn switch_column(&mut self, _: &SwitchColumn, _: &mut Window, cx: &mut Context<Self>) {
+        self.column = match self.column {
+            Column::Hex => Column::Text,
+            Column::Text => Column::Hex,
+        };
+        self.move_cursor(self.cursor, cx);
+    }
+
+    fn cycle_word_size(&mut self, _: &CycleWordSize, _: &mut Window, cx: &mut Context<Self>) {
+        self.word_size = self.word_size.next();
+        cx.notify();
+    }
+
+    fn toggle_endianness(&mut self, _: &ToggleEndianness, _: &mut Window, cx: &mut Context<Self>) {
+        self.endianness = self.endianness.toggle();
+        cx.notify();
+    }
+
+    fn undo(&mut self, _: &Undo, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(offset) = self.hex_file.update(cx, |hex_file, cx| hex_file.undo(cx)) {
+            self.move_cursor(offset, cx);
+        }
+    }
+
+    fn redo(&mut self, _: &Redo, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(offset) = self.hex_file.update(cx, |hex_file, cx| hex_file.redo(cx)) {
+            self.move_cursor(offset, cx);
+        }
+    }
+
+    fn handle_key_down(
+        &mut self,
+        event: &KeyDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.focus_handle.is_focused(window) {
+            return;
+        }
+        let keystroke = &event.keystroke;
+        if keystroke.modifiers.control || keystroke.modifiers.platform {
+            return;
+        }
+        let Some(key_char) = keystroke.key_char.as_deref() else {
+            return;
+        };
+        let mut chars = key_char.chars();
+        if let (Some(character), None) = (chars.next(), chars.next())
+            && self.type_character(character, cx)
+        {
+            cx.stop_propagation();
+        }
+    }
+
+    /// Overwrites the byte under the cursor with a typed character, returning whether the
+    /// character could be typed into the current column.
+    fn type_character(&mut self, character: char, cx: &mut Context<Self>) -> bool {
+        let cursor = self.cursor;
+        let Some(&byte) = self.hex_file.read(cx).bytes().get(cursor) else {
+            return false;
+        };
+        let new_byte = match self.column {
+            Column::Hex => {
+                let Some(digit) = character.to_digit(16) else {
+                    return false;
+                };
+                if self.low_nibble {
+                    (byte & 0xF0) | digit as u8
+                } else {
+                    ((digit as u8) << 4) | (byte & 0x0F)
+                }
+            }
+            Column::Text => {
+                if !character.is_ascii() || character.is_ascii_control() {
+                    return false;
+                }
+                character as u8
+            }
+        };
+
+        let merge = self.typing;
+        self.hex_file.update(cx, |hex_file, cx| {
+            hex_file.overwrite(cursor, &[new_byte], merge, cx)
+        });
+        if self.column == Column::Hex && !self.low_nibble {
+            self.low_nibble = true;
+        } else {
+            self.move_cursor_by(1, cx);
+        }
+        self.typing = true;
+        cx.notify();
+        true
+    }
+
+    fn handle_scroll_wheel(
+        &mut self,
+        event: &ScrollWheelEvent,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let row_height = Self::row_height(cx);
+        let offset = self.scroll_remainder - event.delta.pixel_delta(row_height).y;
+        let rows = (offset / row_height).floor();
+        let target_row = self.top_row as isize + rows as isize;
+        self.top_row = target_row.clamp(0, self.max_top_row(cx) as isize) as usize;
+        self.scroll_remainder = if self.top_row as isize == target_row {
+            offset - row_height * rows
+        } else {
+            px(0.)
+        };
+        cx.notify();
+    }
+
+    fn deploy_bar(&mut self, bar: Bar, window: &mut Window, cx: &mut Context<Self>) {
+        self.bar = Some(bar);
+        self.bar_message = None;
+        let placeholder = match bar {
+            Bar::Search => "Search…",
+            Bar::GoToOffset => "Offset, like 1024 or 0x400…",
+        };
+        self.query_editor.update(cx, |editor, cx| {
+            editor.set_placeholder_text(placeholder, window, cx);
+            editor.select_all(&editor::actions::SelectAll, window, cx);
+        });
+        window.focus(&self.query_editor.focus_handle(cx), cx);
+        cx.notify();
+    }
+
+    fn search(&mut self, _: &Search, window: &mut Window, cx: &mut Context<Self>) {
+        self.deploy_bar(Bar::Search, window, cx);
+    }
+
+    fn go_to_offset(&mut self, _: &GoToOffset, window: &mut Window, cx: &mut Context<Self>) {
+        self.deploy_bar(Bar::GoToOffset, window, cx);
+    }
+
+    fn dismiss(&mut self, _: &Dismiss, window: &mut Window, cx: &mut Context<Self>) {
+        self.bar = None;
+        window.focus(&self.focus_handle, cx);
+        cx.notify();
+    }
+
+    fn toggle_search_mode(&mut self, _: &ToggleSearchMode, _: &mut Window, cx: &mut Context<Self>) {
+        self.search_mode = self.search_mode.toggle();
+        self.active_match = None;
+        self.bar_message = None;
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        match self.bar {
+            Some(Bar::Search) => self.find_match(Direction::Next, cx),
+            Some(Bar::GoToOffset) => self.jump_to_typed_offset(window, cx),
+            None => {}
+        }
+    }
+
+    fn select_next_match(&mut self, _: &SelectNextMatch, _: &mut Window, cx: &mut Context<Self>) {
+        self.find_match(Direction::Next, cx);
+    }
+
+    fn select_previous_match(
+        &mut self,
+        _: &SelectPreviousMatch,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.find_match(Direction::Previous, cx);
+    }
+
+    fn find_match(&mut self, direction: Direction, cx: &mut Context<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        let needle = match self.search_mode.needle(&query) {
+            Ok(needle) if needle.is_empty() => return,
+            Ok(needle) => needle,
+            Err(error) => {
+                self.bar_message = Some(error.to_string().into());
+                cx.notify();
+                return;
+            }
+        };
+
+        let bytes = self.hex_file.read(cx).bytes();
+        let found = match direction {
+            Direction::Next => {
+                let from = self
+                    .active_match
+                    .as_ref()
+                    .map_or(self.cursor, |active_match| active_match.start + 1);
+                hex_search::find_next(bytes, &needle, from)
+            }
+            Direction::Previous => {
+                let before = self
+                    .active_match
+                    .as_ref()
+                    .map_or(self.cursor, |active_match| active_match.start);
+                hex_search::find_previous(bytes, &needle, before)
+            }
+        };
+        match found {
+            Some(offset) => {
+                self.active_match = Some(offset..offset + needle.len());
+                self.bar_message = None;
+                self.move_cursor(offset, cx);
+            }
+            None => {
+                self.active_match = None;
+                self.bar_message = Some("No matches".into());
+                cx.notify();
+            }
+        }
+    }
+
+    fn jump_to_typed_offset(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        let len = self.hex_file.read(cx).len();
+        match hex_search::parse_offset(&query) {
+            Some(offset) if offset < len => {
+                self.bar = None;
+                self.move_cursor(offset, cx);
+                window.focus(&self.focus_handle, cx);
+            }
+            Some(_) => {
+                self.bar_message = Some(format!("The file is only {len} bytes long").into());
+            }
+            None => self.bar_message = Some("Invalid offset".into()),
+        }
+        cx.notify();
+    }
+
+    fn render_byte(
+        &self,
+        offset: usize,
+        column: Column,
+        text: String,
+        is_focused: bool,
+        cx: &Context<Self>,
+    ) -> Div {
+        let colors = cx.theme().colors();
+        let is_cursor = offset == self.cursor;
+        let is_match = self
+            .active_match
+            .as_ref()
+            .is_some_and(|active_match| active_match.contains(&offset));
+        let is_typing_column = is_focused && column == self.column;
+        let cursor_background = if is_typing_column {
+            colors.element_selected
+        } else {
+            colors.element_hover
+        };
+
+        let cell = h_flex()
+            .when(is_match, |cell| cell.bg(colors.search_match_background))
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, _, window, cx| {
+                    this.column = column;
+                    this.move_cursor(offset, cx);
+                    window.focus(&this.focus_handle, cx);
+                }),
+            );
+        if is_cursor && is_typing_column && column == Column::Hex {
+            // Highlight the nibble that the next typed digit replaces.
+            let (high, low) = text.split_at(1);
+            cell.child(
+                div()
+                    .when(!self.low_nibble, |digit| digit.bg(cursor_background))
+                    .child(high.to_string()),
+            )
+            .child(
+                div()
+                    .when(self.low_nibble, |digit| digit.bg(cursor_background))
+                    .child(low.to_string()),
+            )
+        } else {
+            cell.when(is_cursor, |cell| cell.bg(cursor_background))
+                .child(text)
+        }
+    }
+
+    fn render_row(
+        &self,
+        row: usize,
+        offset_width: usize,
+        is_focused: bool,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let row_start = row * BYTES_PER_ROW;
+        let row_bytes = {
+            let bytes = self.hex_file.read(cx).bytes();
+            bytes[row_start..(row_start + BYTES_PER_ROW).min(bytes.len())].to_vec()
+        };
+        let word_len = self.word_size.byte_count();
+
+        let hex_groups = (0..BYTES_PER_ROW)
+            .step_by(word_len)
+            .map(|group_start| {
+                // Little endian words are shown with their most significant byte first, so that
+                // they read as numbers.
+                let mut slots = (group_start..group_start + word_len).collect::<Vec<_>>();
+                if self.endianness == Endianness::Little {
+                    slots.reverse();
+                }
+                h_flex().children(slots.into_iter().map(|slot| {
+                    match row_bytes.get(slot) {
+                        Some(byte) => self
+                            .render_byte(
+                                row_start + slot,
+                                Column::Hex,
+                                format!("{byte:02X}"),
+                                is_focused,
+                                cx,
+                            )
+                            .into_any_element(),
+                        None => div().child("  ").into_any_element(),
+                    }
+                }))
+            })
+            .collect::<Vec<_>>();
+        let text = (0..BYTES_PER_ROW)
+            .map(|slot| match row_bytes.get(slot) {
+                Some(&byte) => {
+                    let character = if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    };
+                    self.render_byte(
+                        row_start + slot,
+                        Column::Text,
+                        character.to_string(),
+                        is_focused,
+                        cx,
+                    )
+                    .into_any_element()
+                }
+                None => div().child(" ").into_any_element(),
+            })
+            .collect::<Vec<_>>();
+
+        h_flex()
+            .gap_4()
+            .px_2()
+            .whitespace_nowrap()
+            .child(
+                div()
+                    .text_color(cx.theme().colors().editor_line_number)
+                    .child(format!("{row_start:0offset_width$X}")),
+            )
+            .child(h_flex().gap_2().children(hex_groups))
+            .child(h_flex().children(text))
+    }
+
+    fn render_bar(&self, bar: Bar, cx: &mut Context<Self>) -> impl IntoElement {
+        let icon = match bar {
+            Bar::Search => IconName::MagnifyingGlass,
+            Bar::GoToOffset => IconName::ArrowRight,
+        };
+        h_flex()
+            .key_context("HexEditorBar")
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::dismiss))
+            .on_action(cx.listener(Self::toggle_search_mode))
+            .gap_2()
+            .px_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Icon::new(icon).color(Color::Muted))
+            .child(div().flex_1().child(self.query_editor.clone()))
+            .when_some(self.bar_message.clone(), |this, message| {
+                this.child(
+                    Label::new(message)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .when(bar == Bar::Search, |this| {
+                this.child(
+                    Button::new("search-mode", self.search_mode.label())
+                        .label_size(LabelSize::Small)
+                        .tooltip(Tooltip::for_action_title(
+                            "Toggle Hex Search",
+                            &ToggleSearchMode,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.toggle_search_mode(&ToggleSearchMode, window, cx)
+                        })),
+                )
+                .child(
+                    IconButton::new("select-previous-match", IconName::ChevronUp)
+                        .tooltip(Tooltip::for_action_title(
+                            "Select Previous Match",
+                            &SelectPreviousMatch,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.select_previous_match(&SelectPreviousMatch, window, cx)
+                        })),
+                )
+                .child(
+                    IconButton::new("select-next-match", IconName::ChevronDown)
+                        .tooltip(Tooltip::for_action_title(
+                            "Select Next Match",
+                            &SelectNextMatch,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.select_next_match(&SelectNextMatch, window, cx)
+                        })),
+                )
+            })
+    }
+
+    fn render_inspector(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let bytes = self.hex_file.read(cx).bytes();
+        let values =
+            data_inspector::inspect(&bytes[self.cursor.min(bytes.len())..], self.endianness);
+
+        v_flex()
+            .flex_none()
+            .w(px(280.))
+            .p_2()
+            .gap_1()
+            .border_l_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new("Data Inspector")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .children(values.into_iter().map(|(label, value)| {
+                h_flex()
+                    .justify_between()
+                    .gap_2()
+                    .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+                    .child(
+                        Label::new(value)
+                            .size(LabelSize::Small)
+                            .buffer_font(cx)
+                            .truncate(),
+                    )
+            }))
+    }
+
+    fn render_status_bar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let len = self.hex_file.read(cx).len();
+        let position = if len == 0 {
+            "Empty file".to_string()
+        } else {
+            format!("Offset {:#X} ({}) of {len} bytes", self.cursor, self.cursor)
+        };
+
+        h_flex()
+            .gap_2()
+            .px_2()
+            .py_0p5()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Label::new(position).size(LabelSize::Small))
+            .child(div().flex_1())
+            .child(
+                Button::new("word-size", self.word_size.label())
+                    .label_size(LabelSize::Small)
+                    .tooltip(Tooltip::for_action_title("Cycle Word Size", &CycleWordSize))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.cycle_word_size(&CycleWordSize, window, cx)
+                    })),
+            )
+            .child(
+                Button::new("endianness", self.endianness.label())
+                    .label_size(LabelSize::Small)
+                    .tooltip(Tooltip::for_action_title(
+                        "Toggle Endianness",
+                        &ToggleEndianness,
+                    ))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.toggle_endianness(&ToggleEndianness, window, cx)
+                    })),
+            )
+            .child(
+                IconButton::new("go-to-offset", IconName::ArrowRight)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::for_action_title("Go to Offset", &GoToOffset))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.go_to_offset(&GoToOffset, window, cx)
+                    })),
+            )
+            .child(
+                IconButton::new("search", IconName::MagnifyingGlass)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::for_action_title("Search", &Search))
+                    .on_click(cx.listener(|this, _, window, cx| this.search(&Search, window, cx))),
+            )
+    }
+}
+
+impl EventEmitter<ItemEvent> for HexEditor {}
+
+impl Focusable for HexEditor {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for HexEditor {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let font = settings.buffer_font.clone();
+        let font_size = settings.buffer_font_size(cx);
+        let row_height = Self::row_height(cx);
+        let entity = cx.entity();
+
+        let len = self.hex_file.read(cx).len();
+        let offset_width = if len > u32::MAX as usize { 16 } else { 8 };
+        let is_focused = self.focus_handle.is_focused(window);
+        let visible_rows = self.top_row
+            ..(self.top_row + self.visible_row_count + 1).min(len.div_ceil(BYTES_PER_ROW));
+        let rows = visible_rows
+            .map(|row| self.render_row(row, offset_width, is_focused, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::select_next_match))
+            .on_action(cx.listener(Self::select_previous_match))
+            .when_some(self.bar, |this, bar| this.child(self.render_bar(bar, cx)))
+            .child(
+                h_flex()
+                    .flex_1()
+                    .items_start()
+                    .overflow_hidden()
+                    .child(
+                        div()
+                            .id("hex-editor-rows")
+                            .track_focus(&self.focus_handle)
+                            .key_context("HexEditor")
+                            .on_action(cx.listener(Self::move_left))
+                            .on_action(cx.listener(Self::move_right))
+                            .on_action(cx.listener(Self::move_up))
+                            .on_action(cx.listener(Self::move_down))
+                            .on_action(cx.listener(Self::page_up))
+                            .on_action(cx.listener(Self::page_down))
+                            .on_action(cx.listener(Self::move_to_row_start))
+                            .on_action(cx.listener(Self::move_to_row_end))
+                            .on_action(cx.listener(Self::move_to_start))
+                            .on_action(cx.listener(Self::move_to_end))
+                            .on_action(cx.listener(Self::switch_column))
+                            .on_action(cx.listener(Self::cycle_word_size))
+                            .on_action(cx.listener(Self::toggle_endianness))
+                            .on_action(cx.listener(Self::undo))
+                            .on_action(cx.listener(Self::redo))
+                            .on_action(cx.listener(Self::search))
+                            .on_action(cx.listener(Self::go_to_offset))
+                            .on_key_down(cx.listener(Self::handle_key_down))
+                            .on_scroll_wheel(cx.listener(Self::handle_scroll_wheel))
+                            .relative()
+                            .flex_1()
+                            .h_full()
+                            .overflow_hidden()
+                            .font(font)
+                            .text_size(font_size)
+                            .line_height(row_height)
+                            .child(
+                                canvas(
+                                    move |bounds, _, cx| {
+                                        entity.update(cx, |this, cx| {
+                                            let visible_row_count =
+                                                (bounds.size.height / row_height).ceil() as usize;
+                                            if visible_row_count != this.visible_row_count {
+                                                this.visible_row_count = visible_row_count;
+                                                cx.notify();
+                                            }
+                                        })
+                                    },
+                                    |_, _, _, _| {},
+                                )
+                                .absolute()
+                                .size_full(),
+                            )
+                            .child(
+                                v_flex()
+                                    .absolute()
+                                    .top(-self.scroll_remainder)
+                                    .left_0()
+                                    .right_0()
+                                    .children(rows),
+                            ),
+                    )
+                    .child(self.render_inspector(cx)),
+            )
+            .child(self.render_status_bar(cx))
+    }
+}
+
+impl Item for HexEditor {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.hex_file.entity_id(), self.hex_file.read(cx))
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.hex_file.read(cx).abs_path();
+        Some(abs_path.compact().to_string_lossy().into_owned().into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &Window, cx: &App) -> AnyElement {
+        let project_path = self.hex_file.read(cx).project_path(cx);
+        let label_color = match project_path {
+            Some(project_path) if ItemSettings::get_global(cx).git_status => {
+                let project = self.project.read(cx);
+                let git_status = project
+                    .project_path_git_status(&project_path, cx)
+                    .map(|status| status.summary())
+                    .unwrap_or_default();
+                project
+                    .entry_for_path(&project_path, cx)
+                    .map(|entry| {
+                        entry_git_aware_label_color(git_status, entry.is_ignored, params.selected)
+                    })
+                    .unwrap_or_else(|| params.text_color())
+            }
+            _ => params.text_color(),
+        };
+
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .single_line()
+            .color(label_color)
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.hex_file.read(cx).file_name().to_string().into()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.hex_file.read(cx).abs_path();
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.hex_file.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, _: &App) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        _: SaveOptions,
+        project: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        let fs = project.read(cx).fs().clone();
+        self.hex_file
+            .update(cx, |hex_file, cx| hex_file.save(fs, cx))
+    }
+
+    fn reload(
+        &mut self,
+        project: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        let fs = project.read(cx).fs().clone();
+        self.hex_file
+            .update(cx, |hex_file, cx| hex_file.reload(fs, cx))
+    }
+
+    fn can_split(&self) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Entity<Self>>>
+    where
+        Self: Sized,
+    {
+        let hex_file = self.hex_file.clone();
+        let project = self.project.clone();
+        let cursor = self.cursor;
+        Task::ready(Some(cx.new(|cx| {
+            let mut editor = Self::new(hex_file, project, window, cx);
+            editor.cursor = cursor;
+            editor.autoscroll();
+            editor
+        })))
+    }
+
+    fn buffer_kind(&self, _: &App) -> workspace::item::ItemBufferKind {
+        workspace::item::ItemBufferKind::Singleton
+    }
+}
+
+impl ProjectItem for HexEditor {
+    type Item = HexFile;
+
+    fn for_project_item(
+        project: Entity<Project>,
+        _: Option<&Pane>,
+        item: Entity<Self::Item>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, project, window, cx)
+    }
+}
+
+pub fn init(cx: &mut App) {
+    workspace::register_project_item::<HexEditor>(cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use std::any::TypeId;
+    use util::rel_path::rel_path;
+    use workspace::{AppState, Workspace};
+
+    #[gpui::test]
+    async fn test_open_text_and_binary_files(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            editor::init(cx);
+            init(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "notes.txt": "plain text\n" }))
+            .await;
+        fs.insert_file("/root/program", vec![0, 1, 2, 3, 0, 0, 0, 0])
+            .await;
+
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+
+        // Text files open straight in the editor, which refuses binary ones.
+        let text = workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_path((worktree_id, rel_path("notes.txt")), None, true, window, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(text.to_any_view().entity_type(), TypeId::of::<Editor>());
+
+        let binary = workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_path((worktree_id, rel_path("program")), None, true, window, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            binary.to_any_view().entity_type(),
+            TypeId::of::<HexEditor>()
+        );
+    }
+}
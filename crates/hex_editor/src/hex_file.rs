@@ -0,0 +1,370 @@
+use std::{
+    io::Read as _,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use fs::Fs;
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Task};
+use project::{Project, ProjectEntryId, ProjectPath, image_store::is_image_file};
+use worktree::{FILE_ANALYSIS_BYTES, is_binary_file_prefix};
+
+/// The largest file that's loaded into memory to be edited.
+const MAX_FILE_LEN: u64 = 256 * 1024 * 1024;
+
+/// An overwrite of a range of bytes, which can be undone.
+struct Edit {
+    offset: usize,
+    old: Vec<u8>,
+    new: Vec<u8>,
+}
+
+impl Edit {
+    fn end(&self) -> usize {
+        self.offset + self.new.len()
+    }
+}
+
+pub enum HexFileEvent {
+    Edited,
+    Saved,
+    Reloaded,
+}
+
+/// A binary file loaded into memory, which can be edited by overwriting its bytes.
+pub struct HexFile {
+    project_path: ProjectPath,
+    entry_id: Option<ProjectEntryId>,
+    abs_path: PathBuf,
+    bytes: Vec<u8>,
+    undo_stack: Vec<Edit>,
+    redo_stack: Vec<Edit>,
+    /// How many edits were on the undo stack when the file was last saved, or `None` if the saved
+    /// contents can no longer be reached by undoing or redoing.
+    saved_undo_len: Option<usize>,
+}
+
+impl HexFile {
+    fn new(
+        project_path: ProjectPath,
+        entry_id: Option<ProjectEntryId>,
+        abs_path: PathBuf,
+        bytes: Vec<u8>,
+    ) -> Self {
+        Self {
+            project_path,
+            entry_id,
+            abs_path,
+            bytes,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            saved_undo_len: Some(0),
+        }
+    }
+
+    pub fn abs_path(&self) -> &Path {
+        &self.abs_path
+    }
+
+    pub fn file_name(&self) -> &str {
+        self.project_path.path.file_name().unwrap_or_default()
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.is_at_saved_edit()
+    }
+
+    /// Overwrites the bytes starting at `offset`, which must all be within the file. When `merge`
+    /// is true and the overwritten bytes continue the last edit, both are undone together.
+    pub fn overwrite(&mut self, offset: usize, new: &[u8], merge: bool, cx: &mut Context<Self>) {
+        let end = offset + new.len();
+        if end > self.bytes.len() || self.bytes[offset..end] == *new {
+            return;
+        }
+
+        // Merging into the last edit would change contents that were saved or redone.
+        let merge = merge && self.redo_stack.is_empty() && !self.is_at_saved_edit();
+        let old = self.bytes[offset..end].to_vec();
+        self.bytes[offset..end].copy_from_slice(new);
+        self.redo_stack.clear();
+        if self
+            .saved_undo_len
+            .is_some_and(|saved_undo_len| saved_undo_len > self.undo_stack.len())
+        {
+            self.saved_undo_len = None;
+        }
+
+        match self.undo_stack.last_mut() {
+            Some(last) if merge && offset == last.end() => {
+                last.old.extend(old);
+                last.new.extend_from_slice(new);
+            }
+            Some(last) if merge && offset >= last.offset && end <= last.end() => {
+                last.new[offset - last.offset..end - last.offset].copy_from_slice(new);
+            }
+            _ => self.undo_stack.push(Edit {
+                offset,
+                old,
+                new: new.to_vec(),
+            }),
+        }
+        cx.emit(HexFileEvent::Edited);
+    }
+
+    /// Undoes the last edit, returning the offset it started at.
+    pub fn undo(&mut self, cx: &mut Context<Self>) -> Option<usize> {
+        let edit = self.undo_stack.pop()?;
+        self.bytes[edit.offset..edit.end()].copy_from_slice(&edit.old);
+        let offset = edit.offset;
+        self.redo_stack.push(edit);
+        cx.emit(HexFileEvent::Edited);
+        Some(offset)
+    }
+
+    /// Redoes the last undone edit, returning the offset it started at.
+    pub fn redo(&mut self, cx: &mut Context<Self>) -> Option<usize> {
+        let edit = self.redo_stack.pop()?;
+        self.bytes[edit.offset..edit.end()].copy_from_slice(&edit.new);
+        let offset = edit.offset;
+        self.undo_stack.push(edit);
+        cx.emit(HexFileEvent::Edited);
+        Some(offset)
+    }
+
+    pub fn save(&mut self, fs: Arc<dyn Fs>, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let abs_path = self.abs_path.clone();
+        let bytes = self.bytes.clone();
+        cx.spawn(async move |this, cx| {
+            cx.background_spawn({
+                let bytes = bytes.clone();
+                async move {
+                    fs.write(&abs_path, &bytes)
+                        .await
+                        .with_context(|| format!("saving {abs_path:?}"))
+                }
+            })
+            .await?;
+            this.update(cx, |this, cx| {
+                // Edits made while saving might have changed what was saved, which then can't
+                // be reached again.
+                this.saved_undo_len = (this.bytes == bytes).then_some(this.undo_stack.len());
+                cx.emit(HexFileEvent::Saved);
+            })
+        })
+    }
+
+    pub fn reload(&mut self, fs: Arc<dyn Fs>, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let abs_path = self.abs_path.clone();
+        cx.spawn(async move |this, cx| {
+            let bytes = cx
+                .background_spawn(async move { load_bytes(fs.as_ref(), &abs_path).await })
+                .await?;
+            this.update(cx, |this, cx| {
+                this.bytes = bytes;
+                this.undo_stack.clear();
+                this.redo_stack.clear();
+                this.saved_undo_len = Some(0);
+                cx.emit(HexFileEvent::Reloaded);
+            })
+        })
+    }
+
+    fn is_at_saved_edit(&self) -> bool {
+        self.saved_undo_len == Some(self.undo_stack.len())
+    }
+}
+
+impl EventEmitter<HexFileEvent> for HexFile {}
+
+/// Reads the start of the file the same way the worktree does when loading it into a buffer, to
+/// tell whether it's binary.
+async fn is_binary_file(fs: &dyn Fs, abs_path: &Path) -> Result<bool> {
+    let file = fs
+        .open_sync(abs_path)
+        .await
+        .with_context(|| format!("opening {abs_path:?}"))?;
+    let mut prefix = Vec::with_capacity(FILE_ANALYSIS_BYTES);
+    file.take(FILE_ANALYSIS_BYTES as u64)
+        .read_to_end(&mut prefix)
+        .with_context(|| format!("reading {abs_path:?}"))?;
+    Ok(is_binary_file_prefix(&prefix))
+}
+
+/// Loads the whole file, unless it has grown too large to keep in memory.
+async fn load_bytes(fs: &dyn Fs, abs_path: &Path) -> Result<Vec<u8>> {
+    let len = fs
+        .metadata(abs_path)
+        .await?
+        .with_context(|| format!("{abs_path:?} doesn't exist"))?
+        .len;
+    anyhow::ensure!(
+        len <= MAX_FILE_LEN,
+        "{abs_path:?} is too large to edit ({len} bytes)"
+    );
+    fs.load_bytes(abs_path)
+        .await
+        .with_context(|| format!("loading {abs_path:?}"))
+}
+
+impl project::ProjectItem for HexFile {
+    /// Text files are left to the editor, so only files it fails to load open here.
+    fn try_open(
+        _: &Entity<Project>,
+        _: &ProjectPath,
+        _: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        None
+    }
+
+    fn try_open_refused(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        if !project.read(cx).is_local() || is_image_file(project, path, cx) {
+            return None;
+        }
+        let entry = project.read(cx).entry_for_path(path, cx)?;
+        if !entry.is_file() || entry.size == 0 || entry.size > MAX_FILE_LEN {
+            return None;
+        }
+        let entry_id = entry.id;
+        let abs_path = project.read(cx).absolute_path(path, cx)?;
+        let fs = project.read(cx).fs().clone();
+        let project_path = path.clone();
+
+        Some(cx.spawn(async move |cx| {
+            let bytes = cx
+                .background_spawn({
+                    let abs_path = abs_path.clone();
+                    async move {
+                        // Files refused for other reasons aren't byte data to edit.
+                        anyhow::ensure!(
+                            is_binary_file(fs.as_ref(), &abs_path).await?,
+                            "{abs_path:?} isn't a binary file"
+                        );
+                        load_bytes(fs.as_ref(), &abs_path).await
+                    }
+                })
+                .await?;
+            Ok(cx.new(|_| HexFile::new(project_path, Some(entry_id), abs_path, bytes)))
+        }))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+
+    fn is_dirty(&self) -> bool {
+        HexFile::is_dirty(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use settings::WorktreeId;
+    use util::rel_path::rel_path;
+
+    fn hex_file(bytes: &[u8], cx: &mut TestAppContext) -> Entity<HexFile> {
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_usize(0),
+            path: rel_path("file.bin").into_arc(),
+        };
+        cx.new(|_| {
+            HexFile::new(
+                project_path,
+                None,
+                PathBuf::from("/file.bin"),
+                bytes.to_vec(),
+            )
+        })
+    }
+
+    #[gpui::test]
+    fn test_undo_merges_consecutive_edits(cx: &mut TestAppContext) {
+        let file = hex_file(&[0, 1, 2, 3], cx);
+        file.update(cx, |file, cx| {
+            file.overwrite(0, &[0xA0], false, cx);
+            file.overwrite(0, &[0xAB], true, cx);
+            file.overwrite(1, &[0xCD], true, cx);
+            file.overwrite(3, &[0xEF], false, cx);
+            assert_eq!(file.bytes(), [0xAB, 0xCD, 2, 0xEF]);
+            assert!(file.is_dirty());
+
+            assert_eq!(file.undo(cx), Some(3));
+            assert_eq!(file.bytes(), [0xAB, 0xCD, 2, 3]);
+            assert_eq!(file.undo(cx), Some(0));
+            assert_eq!(file.bytes(), [0, 1, 2, 3]);
+            assert!(!file.is_dirty());
+            assert_eq!(file.undo(cx), None);
+
+            assert_eq!(file.redo(cx), Some(0));
+            assert_eq!(file.bytes(), [0xAB, 0xCD, 2, 3]);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_save(cx: &mut TestAppContext) {
+        let fs = fs::FakeFs::new(cx.executor());
+        fs.insert_file("/blocker", Vec::new()).await;
+        let file = hex_file(&[0, 0], cx);
+
+        // A failed save leaves the file dirty.
+        file.update(cx, |file, cx| {
+            file.abs_path = PathBuf::from("/blocker/file.bin");
+            file.overwrite(0, &[1], false, cx);
+        });
+        let save = file.update(cx, |file, cx| file.save(fs.clone(), cx));
+        assert!(save.await.is_err());
+        file.read_with(cx, |file, _| assert!(file.is_dirty()));
+
+        file.update(cx, |file, _| file.abs_path = PathBuf::from("/file.bin"));
+        let save = file.update(cx, |file, cx| file.save(fs.clone(), cx));
+        save.await.unwrap();
+        file.read_with(cx, |file, _| assert!(!file.is_dirty()));
+        assert_eq!(fs.load_bytes("/file.bin".as_ref()).await.unwrap(), [1, 0]);
+    }
+
+    #[gpui::test]
+    fn test_dirty_after_undoing_past_save(cx: &mut TestAppContext) {
+        let file = hex_file(&[0, 0], cx);
+        file.update(cx, |file, cx| {
+            file.overwrite(0, &[1], false, cx);
+            file.saved_undo_len = Some(file.undo_stack.len());
+
+            // Saved contents aren't changed by merging later edits into them.
+            file.overwrite(1, &[2], true, cx);
+            assert!(file.is_dirty());
+            file.undo(cx);
+            assert_eq!(file.bytes(), [1, 0]);
+            assert!(!file.is_dirty());
+
+            // Once the saved edit is undone and replaced, it can't be reached again.
+            file.undo(cx);
+            file.overwrite(1, &[3], false, cx);
+            assert!(file.is_dirty());
+            file.undo(cx);
+            assert_eq!(file.bytes(), [0, 0]);
+            assert!(file.is_dirty());
+        });
+    }
+}
@@ -0,0 +1,128 @@
+use anyhow::{Result, anyhow};
+use memchr::memmem;
+
+/// How a search query is turned into the bytes to look for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum SearchMode {
+    /// The query's UTF-8 bytes.
+    #[default]
+    Text,
+    /// Pairs of hex digits, optionally separated by whitespace, like `DE AD BE EF`.
+    Hex,
+}
+
+impl SearchMode {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            SearchMode::Text => "Text",
+            SearchMode::Hex => "Hex",
+        }
+    }
+
+    pub(crate) fn toggle(self) -> Self {
+        match self {
+            SearchMode::Text => SearchMode::Hex,
+            SearchMode::Hex => SearchMode::Text,
+        }
+    }
+
+    pub(crate) fn needle(self, query: &str) -> Result<Vec<u8>> {
+        match self {
+            SearchMode::Text => Ok(query.as_bytes().to_vec()),
+            SearchMode::Hex => parse_hex_bytes(query),
+        }
+    }
+}
+
+fn parse_hex_bytes(query: &str) -> Result<Vec<u8>> {
+    let digits = query
+        .split_whitespace()
+        .map(|word| {
+            word.strip_prefix("0x")
+                .or_else(|| word.strip_prefix("0X"))
+                .unwrap_or(word)
+        })
+        .collect::<String>();
+    if !digits.len().is_multiple_of(2) {
+        return Err(anyhow!("Hex patterns need two digits per byte"));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|ix| {
+            digits
+                .get(ix..ix + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| anyhow!("Invalid hex digits"))
+        })
+        .collect()
+}
+
+/// Parses an offset typed by the user, which is hex when it starts with `0x` and decimal otherwise.
+pub(crate) fn parse_offset(query: &str) -> Option<usize> {
+    let query = query.trim().replace('_', "");
+    match query
+        .strip_prefix("0x")
+        .or_else(|| query.strip_prefix("0X"))
+    {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => query.parse().ok(),
+    }
+}
+
+/// Finds the first occurrence of `needle` starting at or after `from`, wrapping around to the
+/// start of `haystack`.
+pub(crate) fn find_next(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    let from = from.min(haystack.len());
+    memmem::find(&haystack[from..], needle)
+        .map(|ix| from + ix)
+        .or_else(|| memmem::find(haystack, needle))
+}
+
+/// Finds the last occurrence of `needle` starting before `before`, wrapping around to the end of
+/// `haystack`.
+pub(crate) fn find_previous(haystack: &[u8], needle: &[u8], before: usize) -> Option<usize> {
+    let end = (before + needle.len())
+        .saturating_sub(1)
+        .min(haystack.len());
+    memmem::rfind(&haystack[..end], needle).or_else(|| memmem::rfind(haystack, needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_needle() {
+        assert_eq!(
+            SearchMode::Hex.needle("DE AD be ef").unwrap(),
+            [0xDE, 0xAD, 0xBE, 0xEF]
+        );
+        assert_eq!(SearchMode::Hex.needle("0x7f454c46").unwrap(), b"\x7fELF");
+        assert!(SearchMode::Hex.needle("ABC").is_err());
+        assert!(SearchMode::Hex.needle("zz").is_err());
+        assert!(SearchMode::Hex.needle("é1").is_err());
+        assert_eq!(SearchMode::Text.needle("ELF").unwrap(), b"ELF");
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("1024"), Some(1024));
+        assert_eq!(parse_offset(" 0x1F "), Some(31));
+        assert_eq!(parse_offset("0X00ff_ff"), Some(0xFFFF));
+        assert_eq!(parse_offset("1F"), None);
+        assert_eq!(parse_offset(""), None);
+    }
+
+    #[test]
+    fn test_find_wraps_around() {
+        let haystack = b"abcabcabc";
+        assert_eq!(find_next(haystack, b"bc", 0), Some(1));
+        assert_eq!(find_next(haystack, b"bc", 2), Some(4));
+        assert_eq!(find_next(haystack, b"bc", 8), Some(1));
+        assert_eq!(find_previous(haystack, b"bc", 7), Some(4));
+        assert_eq!(find_previous(haystack, b"bc", 4), Some(1));
+        assert_eq!(find_previous(haystack, b"bc", 1), Some(7));
+        assert_eq!(find_next(haystack, b"xyz", 0), None);
+        assert_eq!(find_previous(haystack, b"xyz", 9), None);
+    }
+}
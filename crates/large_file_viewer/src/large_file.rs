@@ -0,0 +1,335 @@
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    fs::File,
+    io,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Task};
+use parking_lot::Mutex;
+use project::{Project, ProjectEntryId, ProjectPath, image_store::is_image_file};
+use settings::Settings as _;
+
+use util::ResultExt as _;
+
+use crate::{
+    LargeFileViewerSettings,
+    line_index::{LineIndex, ReadAt},
+};
+
+/// How many bytes are indexed before the index is published to views.
+const INDEX_CHUNK_LEN: usize = 16 * 1024 * 1024;
+
+/// How often the file is checked for appended data.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The size of the pages the file is read and cached in.
+const PAGE_LEN: usize = 64 * 1024;
+
+/// How many pages are kept in memory, which covers what's visible and what was recently searched.
+const CACHED_PAGE_COUNT: usize = 256;
+
+/// The contents of a file, read on demand and cached a page at a time.
+///
+/// Nothing is mapped, so a file that's truncated or replaced while open just reads short. Pages
+/// that are already cached keep showing the bytes they were read with until they're evicted,
+/// and data appended to the file isn't read until it's opened again.
+pub(crate) struct FileContents {
+    file: File,
+    len: usize,
+    /// The most recently used pages, by page index, in the order they were used.
+    pages: Mutex<VecDeque<(usize, Arc<[u8]>)>>,
+}
+
+impl FileContents {
+    fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("opening {path:?}"))?;
+        let len = file.metadata()?.len() as usize;
+        Ok(Self {
+            file,
+            len,
+            pages: Mutex::new(VecDeque::with_capacity(CACHED_PAGE_COUNT)),
+        })
+    }
+
+    /// Reads `len` bytes at `offset` past the page cache, stopping early at the end of the file.
+    pub(crate) fn read_uncached(&self, offset: usize, len: usize) -> Result<Vec<u8>> {
+        let mut bytes = vec![0; len];
+        let mut read = 0;
+        while read < len {
+            match read_at(&self.file, &mut bytes[read..], (offset + read) as u64) {
+                Ok(0) => break,
+                Ok(count) => read += count,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        bytes.truncate(read);
+        Ok(bytes)
+    }
+
+    fn page(&self, page_ix: usize) -> Arc<[u8]> {
+        let mut pages = self.pages.lock();
+        if let Some(position) = pages.iter().position(|(ix, _)| *ix == page_ix) {
+            let page = pages.remove(position).unwrap();
+            pages.push_back(page.clone());
+            return page.1;
+        }
+        drop(pages);
+
+        let page: Arc<[u8]> = self
+            .read_uncached(page_ix * PAGE_LEN, PAGE_LEN)
+            .log_err()
+            .unwrap_or_default()
+            .into();
+        let mut pages = self.pages.lock();
+        if pages.len() == CACHED_PAGE_COUNT {
+            pages.pop_front();
+        }
+        pages.push_back((page_ix, page.clone()));
+        page
+    }
+}
+
+impl ReadAt for FileContents {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn read_at(&self, range: Range<usize>) -> Cow<'_, [u8]> {
+        let range = range.start.min(self.len)..range.end.min(self.len);
+        let mut bytes = Vec::with_capacity(range.len());
+        let mut offset = range.start;
+        while offset < range.end {
+            let page = self.page(offset / PAGE_LEN);
+            let page_start = offset / PAGE_LEN * PAGE_LEN;
+            let start = offset - page_start;
+            let end = (range.end - page_start).min(page.len());
+            if start >= end {
+                break;
+            }
+            bytes.extend_from_slice(&page[start..end]);
+            offset = page_start + end;
+        }
+        Cow::Owned(bytes)
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+pub enum LargeFileEvent {
+    /// More of the file was indexed, or the file changed on disk.
+    Updated,
+}
+
+/// A file too large to load into a buffer, which is read on demand and indexed in the background
+/// instead.
+pub struct LargeFile {
+    project_path: ProjectPath,
+    entry_id: Option<ProjectEntryId>,
+    abs_path: PathBuf,
+    contents: Arc<FileContents>,
+    index: Arc<Mutex<LineIndex>>,
+    _index_task: Task<()>,
+    _poll_task: Task<()>,
+}
+
+impl LargeFile {
+    fn new(
+        project_path: ProjectPath,
+        entry_id: Option<ProjectEntryId>,
+        abs_path: PathBuf,
+        contents: FileContents,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut this = Self {
+            project_path,
+            entry_id,
+            abs_path,
+            contents: Arc::new(contents),
+            index: Arc::new(Mutex::new(LineIndex::new())),
+            _index_task: Task::ready(()),
+            _poll_task: Task::ready(()),
+        };
+        this.index_contents(cx);
+        this._poll_task = cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(POLL_INTERVAL).await;
+                let Ok(abs_path) = this.read_with(cx, |this, _| this.abs_path.clone()) else {
+                    break;
+                };
+                let Ok(metadata) = smol::fs::metadata(&abs_path).await else {
+                    continue;
+                };
+                let Ok(len) = this.read_with(cx, |this, _| this.len()) else {
+                    break;
+                };
+                if metadata.len() as usize == len {
+                    continue;
+                }
+                let contents = cx
+                    .background_spawn(async move { FileContents::open(&abs_path) })
+                    .await;
+                match contents {
+                    Ok(contents) => {
+                        if this
+                            .update(cx, |this, cx| this.set_contents(contents, cx))
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                    Err(error) => log::warn!("failed to reopen large file: {error:#}"),
+                }
+            }
+        });
+        this
+    }
+
+    pub fn abs_path(&self) -> &Path {
+        &self.abs_path
+    }
+
+    pub fn file_name(&self) -> &str {
+        self.project_path.path.file_name().unwrap_or_default()
+    }
+
+    pub(crate) fn contents(&self) -> Arc<FileContents> {
+        self.contents.clone()
+    }
+
+    /// The size of the file when it was last opened.
+    pub fn len(&self) -> usize {
+        self.contents.len()
+    }
+
+    /// How many bytes, from the start of the file, have been indexed.
+    pub fn indexed_len(&self) -> usize {
+        self.index.lock().indexed_len()
+    }
+
+    /// Whether the whole file has been indexed.
+    pub fn is_indexed(&self) -> bool {
+        self.indexed_len() == self.len()
+    }
+
+    /// The number of lines indexed so far.
+    pub fn line_count(&self) -> usize {
+        self.index.lock().line_count()
+    }
+
+    /// The byte ranges of the given lines, without their line endings.
+    pub(crate) fn line_ranges(&self, lines: Range<usize>) -> Vec<Range<usize>> {
+        self.index.lock().line_ranges(&*self.contents, lines)
+    }
+
+    /// The line containing the byte at `offset`, if it has been indexed.
+    pub(crate) fn line_for_offset(&self, offset: usize) -> Option<usize> {
+        let index = self.index.lock();
+        (offset <= index.indexed_len()).then(|| index.line_for_offset(&*self.contents, offset))
+    }
+
+    fn set_contents(&mut self, contents: FileContents, cx: &mut Context<Self>) {
+        // Appended data extends the existing index, but a file that shrank was truncated or
+        // replaced, so it's indexed from scratch.
+        if contents.len() < self.index.lock().indexed_len() {
+            self.index = Arc::new(Mutex::new(LineIndex::new()));
+        }
+        self.contents = Arc::new(contents);
+        self.index_contents(cx);
+        cx.emit(LargeFileEvent::Updated);
+    }
+
+    fn index_contents(&mut self, cx: &mut Context<Self>) {
+        let contents = self.contents.clone();
+        let index = self.index.clone();
+        self._index_task = cx.spawn(async move |this, cx| {
+            loop {
+                let is_indexed = cx
+                    .background_spawn({
+                        let contents = contents.clone();
+                        let index = index.clone();
+                        async move {
+                            // The chunk is read and scanned without holding the lock, so
+                            // views can read the index in the meantime.
+                            let position = index.lock().position();
+                            let chunk_len = INDEX_CHUNK_LEN.min(contents.len() - position.offset());
+                            let chunk = contents
+                                .read_uncached(position.offset(), chunk_len)
+                                .log_err()
+                                .unwrap_or_default();
+                            let scanned = position.scan(&chunk);
+                            let mut index = index.lock();
+                            index.append(scanned);
+                            chunk.len() < chunk_len || index.indexed_len() >= contents.len()
+                        }
+                    })
+                    .await;
+                if this
+                    .update(cx, |_, cx| cx.emit(LargeFileEvent::Updated))
+                    .is_err()
+                    || is_indexed
+                {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+impl EventEmitter<LargeFileEvent> for LargeFile {}
+
+impl project::ProjectItem for LargeFile {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        let threshold = LargeFileViewerSettings::get_global(cx).threshold()?;
+        if !project.read(cx).is_local() || is_image_file(project, path, cx) {
+            return None;
+        }
+        let entry = project.read(cx).entry_for_path(path, cx)?;
+        if !entry.is_file() || entry.size < threshold {
+            return None;
+        }
+        let entry_id = entry.id;
+        let abs_path = project.read(cx).absolute_path(path, cx)?;
+        let project_path = path.clone();
+
+        Some(cx.spawn(async move |cx| {
+            let contents = cx
+                .background_spawn({
+                    let abs_path = abs_path.clone();
+                    async move { FileContents::open(&abs_path) }
+                })
+                .await?;
+            Ok(cx.new(|cx| LargeFile::new(project_path, Some(entry_id), abs_path, contents, cx)))
+        }))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+
+    fn is_dirty(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,723 @@
+mod large_file;
+mod large_file_viewer_settings;
+mod line_index;
+mod search;
+
+use std::ops::Range;
+
+use editor::{Editor, EditorEvent, items::entry_git_aware_label_color};
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, App, Context, Entity, EventEmitter, FocusHandle, Focusable, HighlightStyle,
+    InteractiveElement, IntoElement, ParentElement, Pixels, Render, ScrollWheelEvent, Styled,
+    StyledText, Task, Window, actions, canvas, div, px,
+};
+use project::{Project, ProjectItem as _};
+use regex::bytes::{Regex, RegexBuilder};
+use settings::Settings;
+use theme::ThemeSettings;
+use ui::{Tooltip, prelude::*};
+use util::paths::PathExt;
+use workspace::{
+    ItemSettings, Pane, WorkspaceId,
+    item::{Item, ProjectItem, TabContentParams},
+};
+
+pub use crate::large_file::*;
+pub use crate::large_file_viewer_settings::*;
+
+actions!(
+    large_file_viewer,
+    [
+        /// Scrolls up by one line.
+        LineUp,
+        /// Scrolls down by one line.
+        LineDown,
+        /// Scrolls up by one page.
+        PageUp,
+        /// Scrolls down by one page.
+        PageDown,
+        /// Scrolls to the start of the file.
+        GoToStart,
+        /// Scrolls to the end of the file.
+        GoToEnd,
+        /// Toggles following data appended to the file, like `tail -f`.
+        ToggleFollow,
+        /// Shows the search bar and focuses its query.
+        Search,
+        /// Selects the next match of the search query.
+        SelectNextMatch,
+        /// Selects the previous match of the search query.
+        SelectPreviousMatch,
+        /// Hides the search bar.
+        DismissSearch
+    ]
+);
+
+/// How many bytes of a line are rendered before it's cut off.
+const MAX_RENDERED_LINE_LEN: usize = 2048;
+
+const TAB: &str = "    ";
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Next,
+    Previous,
+}
+
+/// A read-only view of a [`LargeFile`] that only lays out the lines that are visible.
+pub struct LargeFileView {
+    large_file: Entity<LargeFile>,
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    top_line: usize,
+    /// How far past `top_line` the view has been scrolled, less than one line.
+    scroll_remainder: Pixels,
+    /// How many lines fit in the view, measured during the last layout.
+    visible_line_count: usize,
+    follow: bool,
+    query_editor: Entity<Editor>,
+    search_visible: bool,
+    active_match: Option<Range<usize>>,
+    search_message: Option<SharedString>,
+    search_task: Option<Task<()>>,
+}
+
+impl LargeFileView {
+    pub fn new(
+        large_file: Entity<LargeFile>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let query_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Search with a regex…", window, cx);
+            editor
+        });
+        cx.subscribe(&large_file, Self::on_large_file_event)
+            .detach();
+        cx.subscribe(&query_editor, |this, _, event: &EditorEvent, cx| {
+            if let EditorEvent::BufferEdited = event {
+                this.active_match = None;
+                this.search_message = None;
+                this.search_task = None;
+                cx.notify();
+            }
+        })
+        .detach();
+
+        Self {
+            large_file,
+            project,
+            focus_handle: cx.focus_handle(),
+            top_line: 0,
+            scroll_remainder: px(0.),
+            visible_line_count: 0,
+            follow: false,
+            query_editor,
+            search_visible: false,
+            active_match: None,
+            search_message: None,
+            search_task: None,
+        }
+    }
+
+    fn on_large_file_event(
+        &mut self,
+        _: Entity<LargeFile>,
+        event: &LargeFileEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            LargeFileEvent::Updated => {
+                if self.follow {
+                    self.top_line = self.max_top_line(cx);
+                    self.scroll_remainder = px(0.);
+                } else {
+                    self.top_line = self.top_line.min(self.max_top_line(cx));
+                }
+                cx.notify();
+            }
+        }
+    }
+
+    fn line_height(cx: &App) -> Pixels {
+        let settings = ThemeSettings::get_global(cx);
+        settings.buffer_font_size(cx) * settings.line_height()
+    }
+
+    fn max_top_line(&self, cx: &App) -> usize {
+        self.large_file
+            .read(cx)
+            .line_count()
+            .saturating_sub(self.visible_line_count.max(1))
+    }
+
+    fn scroll_to(&mut self, top_line: usize, cx: &mut Context<Self>) {
+        self.top_line = top_line.min(self.max_top_line(cx));
+        self.scroll_remainder = px(0.);
+        cx.notify();
+    }
+
+    fn scroll_by(&mut self, lines: isize, cx: &mut Context<Self>) {
+        self.follow = false;
+        self.scroll_to(self.top_line.saturating_add_signed(lines), cx);
+    }
+
+    fn line_up(&mut self, _: &LineUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_by(-1, cx);
+    }
+
+    fn line_down(&mut self, _: &LineDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_by(1, cx);
+    }
+
+    fn page_up(&mut self, _: &PageUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_by(-(self.visible_line_count.max(1) as isize), cx);
+    }
+
+    fn page_down(&mut self, _: &PageDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_by(self.visible_line_count.max(1) as isize, cx);
+    }
+
+    fn go_to_start(&mut self, _: &GoToStart, _: &mut Window, cx: &mut Context<Self>) {
+        self.follow = false;
+        self.scroll_to(0, cx);
+    }
+
+    fn go_to_end(&mut self, _: &GoToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_to(usize::MAX, cx);
+    }
+
+    fn toggle_follow(&mut self, _: &ToggleFollow, _: &mut Window, cx: &mut Context<Self>) {
+        self.follow = !self.follow;
+        if self.follow {
+            self.scroll_to(usize::MAX, cx);
+        }
+        cx.notify();
+    }
+
+    fn handle_scroll_wheel(
+        &mut self,
+        event: &ScrollWheelEvent,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let line_height = Self::line_height(cx);
+        let delta = event.delta.pixel_delta(line_height).y;
+        if delta > px(0.) {
+            self.follow = false;
+        }
+
+        let offset = self.scroll_remainder - delta;
+        let lines = (offset / line_height).floor();
+        let target_line = self.top_line as isize + lines as isize;
+        self.top_line = target_line.clamp(0, self.max_top_line(cx) as isize) as usize;
+        self.scroll_remainder = if self.top_line as isize == target_line {
+            offset - line_height * lines
+        } else {
+            px(0.)
+        };
+        cx.notify();
+    }
+
+    fn search(&mut self, _: &Search, window: &mut Window, cx: &mut Context<Self>) {
+        self.search_visible = true;
+        self.query_editor.update(cx, |editor, cx| {
+            editor.select_all(&editor::actions::SelectAll, window, cx);
+        });
+        window.focus(&self.query_editor.focus_handle(cx), cx);
+        cx.notify();
+    }
+
+    fn dismiss_search(&mut self, _: &DismissSearch, window: &mut Window, cx: &mut Context<Self>) {
+        self.search_visible = false;
+        self.search_task = None;
+        window.focus(&self.focus_handle, cx);
+        cx.notify();
+    }
+
+    fn select_next_match(&mut self, _: &SelectNextMatch, _: &mut Window, cx: &mut Context<Self>) {
+        self.find_match(Direction::Next, cx);
+    }
+
+    fn select_previous_match(
+        &mut self,
+        _: &SelectPreviousMatch,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.find_match(Direction::Previous, cx);
+    }
+
+    fn find_match(&mut self, direction: Direction, cx: &mut Context<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        if query.is_empty() {
+            return;
+        }
+        let regex = match build_regex(&query) {
+            Ok(regex) => regex,
+            Err(error) => {
+                self.search_message = Some(error.to_string().into());
+                cx.notify();
+                return;
+            }
+        };
+
+        // Only the indexed part of the file is searched, so that every match can be scrolled to.
+        let large_file = self.large_file.read(cx);
+        let contents = large_file.contents();
+        let searchable_len = large_file.indexed_len();
+        let from = match (&self.active_match, direction) {
+            (Some(active_match), Direction::Next) => active_match.start + 1,
+            (Some(active_match), Direction::Previous) => active_match.start,
+            (None, _) => large_file
+                .line_ranges(self.top_line..self.top_line + 1)
+                .first()
+                .map_or(0, |range| range.start),
+        }
+        .min(searchable_len);
+
+        self.search_message = Some("Searching…".into());
+        cx.notify();
+        self.search_task = Some(cx.spawn(async move |this, cx| {
+            let found = cx
+                .background_spawn(async move {
+                    let text = &*contents;
+                    match direction {
+                        Direction::Next => {
+                            search::find_next(text, searchable_len, &regex, from).await
+                        }
+                        Direction::Previous => {
+                            search::find_previous(text, searchable_len, &regex, from).await
+                        }
+                    }
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                match found {
+                    Some(found) => this.select_match(found, cx),
+                    None => {
+                        this.active_match = None;
+                        this.search_message = Some("No matches".into());
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn select_match(&mut self, found: Range<usize>, cx: &mut Context<Self>) {
+        let Some(line) = self.large_file.read(cx).line_for_offset(found.start) else {
+            return;
+        };
+        self.follow = false;
+        self.active_match = Some(found);
+        self.search_message = None;
+        // Leave some of the lines before the match visible for context.
+        self.scroll_to(line.saturating_sub(self.visible_line_count / 3), cx);
+    }
+
+    fn render_lines(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let large_file = self.large_file.read(cx);
+        let line_count = large_file.line_count();
+        let first_line = self.top_line;
+        let line_ranges =
+            large_file.line_ranges(first_line..first_line + self.visible_line_count + 1);
+        let contents = large_file.contents();
+
+        let colors = cx.theme().colors();
+        let match_highlight = HighlightStyle {
+            background_color: Some(colors.search_active_match_background),
+            ..Default::default()
+        };
+        let gutter_width = line_count.max(1).ilog10() as usize + 1;
+
+        v_flex()
+            .absolute()
+            .top(-self.scroll_remainder)
+            .left_0()
+            .right_0()
+            .children(line_ranges.into_iter().enumerate().map(|(ix, range)| {
+                let line_number = format!("{:>gutter_width$}", first_line + ix + 1);
+                let rendered_len = range.len().min(MAX_RENDERED_LINE_LEN);
+                let bytes = contents.read_at(range.start..range.start + rendered_len);
+                let (line, highlight) =
+                    display_line(&bytes, range, self.active_match.as_ref(), match_highlight);
+                h_flex()
+                    .gap_4()
+                    .px_2()
+                    .whitespace_nowrap()
+                    .child(
+                        div()
+                            .text_color(colors.editor_line_number)
+                            .child(line_number),
+                    )
+                    .child(StyledText::new(line).with_highlights(highlight))
+            }))
+    }
+
+    fn render_search_bar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .key_context("LargeFileViewerSearch")
+            .on_action(cx.listener(Self::select_next_match))
+            .on_action(cx.listener(Self::select_previous_match))
+            .on_action(cx.listener(Self::dismiss_search))
+            .gap_2()
+            .px_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Icon::new(IconName::MagnifyingGlass).color(Color::Muted))
+            .child(div().flex_1().child(self.query_editor.clone()))
+            .when_some(self.search_message.clone(), |this, message| {
+                this.child(
+                    Label::new(message)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .child(
+                IconButton::new("select-previous-match", IconName::ChevronUp)
+                    .tooltip(Tooltip::for_action_title(
+                        "Select Previous Match",
+                        &SelectPreviousMatch,
+                    ))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.select_previous_match(&SelectPreviousMatch, window, cx)
+                    })),
+            )
+            .child(
+                IconButton::new("select-next-match", IconName::ChevronDown)
+                    .tooltip(Tooltip::for_action_title(
+                        "Select Next Match",
+                        &SelectNextMatch,
+                    ))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.select_next_match(&SelectNextMatch, window, cx)
+                    })),
+            )
+    }
+
+    fn render_status_bar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let large_file = self.large_file.read(cx);
+        let line_count = large_file.line_count();
+        let position = if line_count == 0 {
+            "Empty file".to_string()
+        } else {
+            format!(
+                "Line {} of {}",
+                (self.top_line + 1).min(line_count),
+                line_count
+            )
+        };
+        let indexing = (!large_file.is_indexed()).then(|| {
+            let percent = large_file.indexed_len() as f64 / large_file.len() as f64 * 100.;
+            format!("Indexing… {percent:.0}%")
+        });
+
+        h_flex()
+            .gap_2()
+            .px_2()
+            .py_0p5()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Label::new(position).size(LabelSize::Small))
+            .when_some(indexing, |this, indexing| {
+                this.child(
+                    Label::new(indexing)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .child(div().flex_1())
+            .child(
+                IconButton::new("search", IconName::MagnifyingGlass)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::for_action_title("Search", &Search))
+                    .on_click(cx.listener(|this, _, window, cx| this.search(&Search, window, cx))),
+            )
+            .child(
+                IconButton::new("follow", IconName::ArrowDown)
+                    .icon_size(IconSize::Small)
+                    .toggle_state(self.follow)
+                    .tooltip(Tooltip::for_action_title(
+                        "Follow Appended Data",
+                        &ToggleFollow,
+                    ))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.toggle_follow(&ToggleFollow, window, cx)
+                    })),
+            )
+    }
+}
+
+fn build_regex(query: &str) -> Result<Regex, regex::Error> {
+    // Like other searches, a query without uppercase letters is case insensitive.
+    RegexBuilder::new(query)
+        .case_insensitive(!query.chars().any(char::is_uppercase))
+        .multi_line(true)
+        .build()
+}
+
+/// Converts the bytes of a line to text that can be rendered, along with where the active match
+/// is highlighted in that text. `bytes` are those at the start of the line, which spans `range`
+/// in the file.
+fn display_line(
+    bytes: &[u8],
+    range: Range<usize>,
+    active_match: Option<&Range<usize>>,
+    highlight: HighlightStyle,
+) -> (String, Vec<(Range<usize>, HighlightStyle)>) {
+    let end = range.end.min(range.start + MAX_RENDERED_LINE_LEN);
+    let truncated = end < range.end;
+    let range = range.start..end;
+
+    // Each part of the line is decoded separately, so that replacement characters and expanded
+    // tabs don't shift the highlighted range.
+    let highlighted = active_match
+        .map(|active_match| active_match.start.max(range.start)..active_match.end.min(range.end))
+        .filter(|highlighted| !highlighted.is_empty())
+        .unwrap_or(range.end..range.end);
+    let mut line = String::new();
+    let mut highlights = Vec::new();
+    for (ix, part) in [
+        range.start..highlighted.start,
+        highlighted.clone(),
+        highlighted.end..range.end,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let start = line.len();
+        let part =
+            (part.start - range.start).min(bytes.len())..(part.end - range.start).min(bytes.len());
+        line.push_str(&String::from_utf8_lossy(&bytes[part]).replace('\t', TAB));
+        if ix == 1 && line.len() > start {
+            highlights.push((start..line.len(), highlight));
+        }
+    }
+    if truncated {
+        line.push('…');
+    }
+    (line, highlights)
+}
+
+impl EventEmitter<()> for LargeFileView {}
+
+impl Focusable for LargeFileView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for LargeFileView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let font = settings.buffer_font.clone();
+        let font_size = settings.buffer_font_size(cx);
+        let line_height = Self::line_height(cx);
+        let entity = cx.entity();
+
+        v_flex()
+            .track_focus(&self.focus_handle)
+            .key_context("LargeFileViewer")
+            .on_action(cx.listener(Self::line_up))
+            .on_action(cx.listener(Self::line_down))
+            .on_action(cx.listener(Self::page_up))
+            .on_action(cx.listener(Self::page_down))
+            .on_action(cx.listener(Self::go_to_start))
+            .on_action(cx.listener(Self::go_to_end))
+            .on_action(cx.listener(Self::toggle_follow))
+            .on_action(cx.listener(Self::search))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .when(self.search_visible, |this| {
+                this.child(self.render_search_bar(cx))
+            })
+            .child(
+                div()
+                    .id("large-file-lines")
+                    .relative()
+                    .flex_1()
+                    .overflow_hidden()
+                    .font(font)
+                    .text_size(font_size)
+                    .line_height(line_height)
+                    .on_scroll_wheel(cx.listener(Self::handle_scroll_wheel))
+                    .child(
+                        canvas(
+                            move |bounds, _, cx| {
+                                entity.update(cx, |this, cx| {
+                                    let visible_line_count =
+                                        (bounds.size.height / line_height).ceil() as usize;
+                                    if visible_line_count != this.visible_line_count {
+                                        this.visible_line_count = visible_line_count;
+                                        if this.follow {
+                                            this.top_line = this.max_top_line(cx);
+                                        }
+                                        cx.notify();
+                                    }
+                                })
+                            },
+                            |_, _, _, _| {},
+                        )
+                        .absolute()
+                        .size_full(),
+                    )
+                    .child(self.render_lines(cx)),
+            )
+            .child(self.render_status_bar(cx))
+    }
+}
+
+impl Item for LargeFileView {
+    type Event = ();
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.large_file.entity_id(), self.large_file.read(cx))
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.large_file.read(cx).abs_path();
+        Some(abs_path.compact().to_string_lossy().into_owned().into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &Window, cx: &App) -> AnyElement {
+        let project_path = self.large_file.read(cx).project_path(cx);
+        let label_color = match project_path {
+            Some(project_path) if ItemSettings::get_global(cx).git_status => {
+                let project = self.project.read(cx);
+                let git_status = project
+                    .project_path_git_status(&project_path, cx)
+                    .map(|status| status.summary())
+                    .unwrap_or_default();
+                project
+                    .entry_for_path(&project_path, cx)
+                    .map(|entry| {
+                        entry_git_aware_label_color(git_status, entry.is_ignored, params.selected)
+                    })
+                    .unwrap_or_else(|| params.text_color())
+            }
+            _ => params.text_color(),
+        };
+
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .single_line()
+            .color(label_color)
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.large_file.read(cx).file_name().to_string().into()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.large_file.read(cx).abs_path();
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn can_split(&self) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Entity<Self>>>
+    where
+        Self: Sized,
+    {
+        let large_file = self.large_file.clone();
+        let project = self.project.clone();
+        let top_line = self.top_line;
+        Task::ready(Some(cx.new(|cx| {
+            let mut view = Self::new(large_file, project, window, cx);
+            view.top_line = top_line;
+            view
+        })))
+    }
+
+    fn buffer_kind(&self, _: &App) -> workspace::item::ItemBufferKind {
+        workspace::item::ItemBufferKind::Singleton
+    }
+}
+
+impl ProjectItem for LargeFileView {
+    type Item = LargeFile;
+
+    fn for_project_item(
+        project: Entity<Project>,
+        _: Option<&Pane>,
+        item: Entity<Self::Item>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, project, window, cx)
+    }
+}
+
+pub fn init(cx: &mut App) {
+    workspace::register_project_item::<LargeFileView>(cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display(text: &str, active_match: Option<Range<usize>>) -> (String, Vec<Range<usize>>) {
+        let (line, highlights) = display_line(
+            text.as_bytes(),
+            0..text.len(),
+            active_match.as_ref(),
+            HighlightStyle::default(),
+        );
+        (
+            line,
+            highlights.into_iter().map(|(range, _)| range).collect(),
+        )
+    }
+
+    #[test]
+    fn test_display_line_highlights_match_after_tabs() {
+        assert_eq!(
+            display("\tkey\tvalue", Some(5..10)),
+            ("    key    value".to_string(), vec![11..16])
+        );
+        assert_eq!(
+            display("no match", None),
+            ("no match".to_string(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_display_line_truncates_long_lines() {
+        let text = "a".repeat(MAX_RENDERED_LINE_LEN + 10);
+        let (line, highlights) = display(&text, Some(MAX_RENDERED_LINE_LEN + 2..text.len()));
+        assert_eq!(line.len(), MAX_RENDERED_LINE_LEN + '…'.len_utf8());
+        assert!(highlights.is_empty());
+    }
+
+    #[test]
+    fn test_build_regex_uses_smart_case() {
+        assert!(build_regex("error").unwrap().is_match(b"ERROR: failed"));
+        assert!(!build_regex("Error").unwrap().is_match(b"ERROR: failed"));
+        assert!(build_regex("(").is_err());
+    }
+}
@@ -0,0 +1,31 @@
+use settings::{RegisterSetting, Settings};
+
+/// The settings for the large file viewer.
+#[derive(Clone, Debug, Default, RegisterSetting)]
+pub struct LargeFileViewerSettings {
+    /// Files at least this many megabytes large open in the large file viewer instead of an
+    /// editor. Zero disables the large file viewer.
+    ///
+    /// Default: 100
+    pub threshold_mb: u64,
+}
+
+impl LargeFileViewerSettings {
+    /// The size in bytes above which files open in the large file viewer, if it's enabled.
+    pub fn threshold(&self) -> Option<u64> {
+        (self.threshold_mb > 0).then(|| self.threshold_mb * 1024 * 1024)
+    }
+}
+
+impl Settings for LargeFileViewerSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        Self {
+            threshold_mb: content
+                .large_file_viewer
+                .clone()
+                .unwrap()
+                .threshold_mb
+                .unwrap(),
+        }
+    }
+}
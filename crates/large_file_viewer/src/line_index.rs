@@ -0,0 +1,315 @@
+use std::{borrow::Cow, ops::Range};
+
+/// How many lines apart the indexed line starts are.
+///
+/// Only every `CHECKPOINT_INTERVAL`th line start is remembered, so that indexing a file with
+/// hundreds of millions of lines stays cheap. Finding any other line scans forward from the
+/// checkpoint before it.
+const CHECKPOINT_INTERVAL: usize = 1024;
+
+/// How many bytes are read at a time while looking for the end of a line.
+const SCAN_LEN: usize = 64 * 1024;
+
+/// Text that's read on demand, such as a file that's too large to keep in memory.
+pub(crate) trait ReadAt {
+    /// The length of the text in bytes.
+    fn len(&self) -> usize;
+
+    /// The bytes in `range`, which are fewer than asked for if the text ends before it.
+    fn read_at(&self, range: Range<usize>) -> Cow<'_, [u8]>;
+}
+
+impl ReadAt for [u8] {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn read_at(&self, range: Range<usize>) -> Cow<'_, [u8]> {
+        let len = <[u8]>::len(self);
+        Cow::Borrowed(&self[range.start.min(len)..range.end.min(len)])
+    }
+}
+
+/// A sparse index of where lines start in a file, built incrementally as it's scanned.
+pub(crate) struct LineIndex {
+    /// The offset at which every `CHECKPOINT_INTERVAL`th line starts, beginning with line 0.
+    checkpoints: Vec<usize>,
+    /// How many newlines were found in the indexed bytes.
+    newline_count: usize,
+    /// The offset at which the last indexed line starts.
+    last_line_start: usize,
+    /// How many bytes, from the start of the file, have been scanned.
+    indexed_len: usize,
+}
+
+/// Where the index stopped scanning, which is all that's needed to scan the bytes after it.
+#[derive(Clone, Copy)]
+pub(crate) struct ScanPosition {
+    offset: usize,
+    newline_count: usize,
+}
+
+/// The lines found in a chunk of the file, to be added to the index.
+pub(crate) struct ScannedChunk {
+    start: usize,
+    end: usize,
+    newline_count: usize,
+    last_line_start: Option<usize>,
+    checkpoints: Vec<usize>,
+}
+
+impl ScanPosition {
+    pub(crate) fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Scans `chunk`, the bytes following this position, for line starts.
+    pub(crate) fn scan(self, chunk: &[u8]) -> ScannedChunk {
+        let mut scanned = ScannedChunk {
+            start: self.offset,
+            end: self.offset + chunk.len(),
+            newline_count: self.newline_count,
+            last_line_start: None,
+            checkpoints: Vec::new(),
+        };
+        for newline in memchr::memchr_iter(b'\n', chunk) {
+            let line_start = self.offset + newline + 1;
+            scanned.newline_count += 1;
+            scanned.last_line_start = Some(line_start);
+            if scanned.newline_count.is_multiple_of(CHECKPOINT_INTERVAL) {
+                scanned.checkpoints.push(line_start);
+            }
+        }
+        scanned
+    }
+}
+
+impl LineIndex {
+    pub(crate) fn new() -> Self {
+        Self {
+            checkpoints: vec![0],
+            newline_count: 0,
+            last_line_start: 0,
+            indexed_len: 0,
+        }
+    }
+
+    pub(crate) fn indexed_len(&self) -> usize {
+        self.indexed_len
+    }
+
+    pub(crate) fn position(&self) -> ScanPosition {
+        ScanPosition {
+            offset: self.indexed_len,
+            newline_count: self.newline_count,
+        }
+    }
+
+    /// Adds a chunk scanned from this index's position. A chunk scanned from another position
+    /// is ignored.
+    pub(crate) fn append(&mut self, chunk: ScannedChunk) {
+        if chunk.start != self.indexed_len {
+            return;
+        }
+        self.checkpoints.extend(chunk.checkpoints);
+        self.newline_count = chunk.newline_count;
+        if let Some(last_line_start) = chunk.last_line_start {
+            self.last_line_start = last_line_start;
+        }
+        self.indexed_len = chunk.end;
+    }
+
+    /// The number of lines in the indexed text. A trailing newline doesn't start another line.
+    pub(crate) fn line_count(&self) -> usize {
+        if self.indexed_len > self.last_line_start {
+            self.newline_count + 1
+        } else {
+            self.newline_count
+        }
+    }
+
+    /// The byte ranges of the given lines, without their line endings.
+    pub(crate) fn line_ranges(
+        &self,
+        text: &(impl ReadAt + ?Sized),
+        lines: Range<usize>,
+    ) -> Vec<Range<usize>> {
+        let lines = lines.start..lines.end.min(self.line_count());
+        if lines.is_empty() {
+            return Vec::new();
+        }
+
+        let end = self.indexed_len;
+        let mut start = self.checkpoints[lines.start / CHECKPOINT_INTERVAL];
+        for _ in 0..lines.start % CHECKPOINT_INTERVAL {
+            start = line_end_after(text, start, end);
+        }
+
+        let mut ranges = Vec::with_capacity(lines.len());
+        for _ in lines {
+            let next_start = line_end_after(text, start, end);
+            let line_ending = text.read_at(next_start.saturating_sub(2).max(start)..next_start);
+            let mut line_end = next_start;
+            if line_ending.ends_with(b"\n") {
+                line_end -= 1;
+                if line_ending[..line_ending.len() - 1].ends_with(b"\r") {
+                    line_end -= 1;
+                }
+            } else if line_ending.ends_with(b"\r") {
+                line_end -= 1;
+            }
+            ranges.push(start..line_end);
+            start = next_start;
+        }
+        ranges
+    }
+
+    /// The line containing the byte at `offset`, which must have been indexed.
+    pub(crate) fn line_for_offset(&self, text: &(impl ReadAt + ?Sized), offset: usize) -> usize {
+        let offset = offset.min(self.indexed_len);
+        let checkpoint = self
+            .checkpoints
+            .partition_point(|line_start| *line_start <= offset)
+            .saturating_sub(1);
+        let mut line = checkpoint * CHECKPOINT_INTERVAL;
+        let mut start = self.checkpoints[checkpoint];
+        while start < offset {
+            let end = (start + SCAN_LEN).min(offset);
+            line += memchr::memchr_iter(b'\n', &text.read_at(start..end)).count();
+            start = end;
+        }
+        line
+    }
+}
+
+/// The offset just past the end of the line containing `offset`, or `end` if the line doesn't
+/// end before it.
+pub(crate) fn line_end_after(text: &(impl ReadAt + ?Sized), offset: usize, end: usize) -> usize {
+    let mut start = offset;
+    while start < end {
+        let scan_end = (start + SCAN_LEN).min(end);
+        let bytes = text.read_at(start..scan_end);
+        if let Some(newline) = memchr::memchr(b'\n', &bytes) {
+            return start + newline + 1;
+        }
+        if bytes.len() < scan_end - start {
+            break;
+        }
+        start = scan_end;
+    }
+    end
+}
+
+/// The offset at which the line containing `offset` starts, or `start` if the line doesn't start
+/// after it.
+pub(crate) fn line_start_before(
+    text: &(impl ReadAt + ?Sized),
+    offset: usize,
+    start: usize,
+) -> usize {
+    let mut end = offset;
+    while end > start {
+        let scan_start = end.saturating_sub(SCAN_LEN).max(start);
+        if let Some(newline) = memchr::memrchr(b'\n', &text.read_at(scan_start..end)) {
+            return scan_start + newline + 1;
+        }
+        end = scan_start;
+    }
+    start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indexed(text: &[u8]) -> LineIndex {
+        let mut index = LineIndex::new();
+        index.append(index.position().scan(text));
+        index
+    }
+
+    fn lines<'a>(index: &LineIndex, text: &'a [u8], lines: Range<usize>) -> Vec<&'a str> {
+        index
+            .line_ranges(text, lines)
+            .into_iter()
+            .map(|range| std::str::from_utf8(&text[range]).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_line_count() {
+        assert_eq!(indexed(b"").line_count(), 0);
+        assert_eq!(indexed(b"one").line_count(), 1);
+        assert_eq!(indexed(b"one\n").line_count(), 1);
+        assert_eq!(indexed(b"one\ntwo").line_count(), 2);
+        assert_eq!(indexed(b"one\n\n").line_count(), 2);
+    }
+
+    #[test]
+    fn test_line_ranges() {
+        let text = b"one\r\ntwo\n\nfour";
+        let index = indexed(text);
+        assert_eq!(lines(&index, text, 0..10), ["one", "two", "", "four"]);
+        assert_eq!(lines(&index, text, 1..3), ["two", ""]);
+        assert_eq!(lines(&index, text, 4..5), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_lines_past_checkpoints() {
+        let text = (0..5000)
+            .map(|line| format!("line {line}\n"))
+            .collect::<String>();
+        let index = indexed(text.as_bytes());
+
+        assert_eq!(index.line_count(), 5000);
+        assert_eq!(
+            lines(&index, text.as_bytes(), 1023..1026),
+            ["line 1023", "line 1024", "line 1025"]
+        );
+        assert_eq!(lines(&index, text.as_bytes(), 4999..5000), ["line 4999"]);
+
+        let offset = text.find("line 3000").unwrap() + 3;
+        assert_eq!(index.line_for_offset(text.as_bytes(), offset), 3000);
+        assert_eq!(index.line_for_offset(text.as_bytes(), 0), 0);
+    }
+
+    #[test]
+    fn test_line_boundaries() {
+        let text = b"one\ntwo\nthree";
+        assert_eq!(line_start_before(&text[..], 6, 0), 4);
+        assert_eq!(line_start_before(&text[..], 2, 0), 0);
+        assert_eq!(line_start_before(&text[..], 6, 5), 5);
+        assert_eq!(line_end_after(&text[..], 5, text.len()), 8);
+        assert_eq!(line_end_after(&text[..], 9, text.len()), text.len());
+
+        let line = "x".repeat(3 * SCAN_LEN);
+        let text = format!("{line}\n{line}");
+        assert_eq!(
+            line_start_before(text.as_bytes(), text.len(), 0),
+            line.len() + 1
+        );
+        assert_eq!(
+            line_end_after(text.as_bytes(), 0, text.len()),
+            line.len() + 1
+        );
+    }
+
+    #[test]
+    fn test_incremental_indexing() {
+        let text = b"first\nsecond line\nthird";
+        let mut index = LineIndex::new();
+
+        let position = index.position();
+        index.append(position.scan(&text[..9]));
+        assert_eq!(index.line_count(), 2);
+        assert_eq!(lines(&index, text, 0..2), ["first", "sec"]);
+
+        // A chunk scanned from a stale position is dropped.
+        index.append(position.scan(&text[..9]));
+        assert_eq!(index.indexed_len(), 9);
+
+        index.append(index.position().scan(&text[9..]));
+        assert_eq!(index.line_count(), 3);
+        assert_eq!(lines(&index, text, 0..3), ["first", "second line", "third"]);
+    }
+}
@@ -0,0 +1,232 @@
+use regex::bytes::Regex;
+use smol::future::yield_now;
+use std::ops::Range;
+
+use crate::line_index::{ReadAt, line_end_after, line_start_before};
+
+/// How many bytes are searched at a time before yielding, so that a search can be cancelled by
+/// dropping its task. Chunks are extended to the end of a line, so patterns that span several
+/// lines can miss matches crossing a chunk boundary.
+const CHUNK_LEN: usize = 4 * 1024 * 1024;
+
+/// How far a chunk is extended to reach the edge of a line. Longer lines, such as those of binary
+/// dumps or minified logs, are split across chunks so that they're never read in one go.
+const MAX_LINE_EXTENSION: usize = CHUNK_LEN;
+
+/// Finds the first match starting at or after `from` in the first `len` bytes of `text`,
+/// wrapping around to the start.
+pub(crate) async fn find_next(
+    text: &(impl ReadAt + ?Sized),
+    len: usize,
+    regex: &Regex,
+    from: usize,
+) -> Option<Range<usize>> {
+    if let Some(found) = find_forward(text, len, regex, from, len).await {
+        return Some(found);
+    }
+    find_forward(text, len, regex, 0, from).await
+}
+
+/// Finds the last match starting before `from` in the first `len` bytes of `text`, wrapping
+/// around to the end.
+pub(crate) async fn find_previous(
+    text: &(impl ReadAt + ?Sized),
+    len: usize,
+    regex: &Regex,
+    from: usize,
+) -> Option<Range<usize>> {
+    if let Some(found) = find_backward(text, len, regex, 0, from).await {
+        return Some(found);
+    }
+    find_backward(text, len, regex, from, len).await
+}
+
+/// Finds the first match that starts within `start..end`.
+async fn find_forward(
+    text: &(impl ReadAt + ?Sized),
+    len: usize,
+    regex: &Regex,
+    start: usize,
+    end: usize,
+) -> Option<Range<usize>> {
+    // Search from the start of the line, so that anchors see the text before `start`.
+    let mut chunk_start = line_start_before(text, start, start.saturating_sub(MAX_LINE_EXTENSION));
+    let mut search_from = start;
+    while search_from < end {
+        let chunk_end = (search_from + CHUNK_LEN).min(len);
+        let chunk_end = line_end_after(text, chunk_end, (chunk_end + MAX_LINE_EXTENSION).min(len));
+        let chunk = text.read_at(chunk_start..chunk_end);
+        if let Some(found) = regex.find_at(&chunk, search_from - chunk_start) {
+            let found = chunk_start + found.start()..chunk_start + found.end();
+            return (found.start < end).then_some(found);
+        }
+        chunk_start = chunk_end;
+        search_from = chunk_end;
+        yield_now().await;
+    }
+    None
+}
+
+/// Finds the last match that starts within `start..end`.
+async fn find_backward(
+    text: &(impl ReadAt + ?Sized),
+    len: usize,
+    regex: &Regex,
+    start: usize,
+    end: usize,
+) -> Option<Range<usize>> {
+    let first_line_start = line_start_before(text, start, start.saturating_sub(MAX_LINE_EXTENSION));
+    let mut chunk_end = line_end_after(text, end, (end + MAX_LINE_EXTENSION).min(len));
+    loop {
+        let chunk_start = chunk_end.saturating_sub(CHUNK_LEN).max(first_line_start);
+        let chunk_start = line_start_before(
+            text,
+            chunk_start,
+            chunk_start
+                .saturating_sub(MAX_LINE_EXTENSION)
+                .max(first_line_start),
+        );
+        let chunk = text.read_at(chunk_start..chunk_end);
+        let found = regex
+            .find_iter(&chunk)
+            .map(|found| chunk_start + found.start()..chunk_start + found.end())
+            .filter(|found| found.start >= start && found.start < end)
+            .last();
+        if found.is_some() || chunk_start <= start {
+            return found;
+        }
+        chunk_end = chunk_start;
+        yield_now().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::bytes::RegexBuilder;
+    use std::borrow::Cow;
+
+    fn regex(pattern: &str) -> Regex {
+        RegexBuilder::new(pattern).multi_line(true).build().unwrap()
+    }
+
+    #[test]
+    fn test_find_next_wraps_around() {
+        let text = b"error: one\ninfo: two\nerror: three\n";
+        let missing = regex("missing");
+        let regex = regex("error");
+        smol::block_on(async {
+            assert_eq!(
+                find_next(&text[..], text.len(), &regex, 0).await,
+                Some(0..5)
+            );
+            assert_eq!(
+                find_next(&text[..], text.len(), &regex, 1).await,
+                Some(21..26)
+            );
+            assert_eq!(
+                find_next(&text[..], text.len(), &regex, 22).await,
+                Some(0..5)
+            );
+            assert_eq!(find_next(&text[..], text.len(), &missing, 0).await, None);
+        });
+    }
+
+    #[test]
+    fn test_find_previous_wraps_around() {
+        let text = b"error: one\ninfo: two\nerror: three\n";
+        let regex = regex("error");
+        smol::block_on(async {
+            assert_eq!(
+                find_previous(&text[..], text.len(), &regex, 21).await,
+                Some(0..5)
+            );
+            assert_eq!(
+                find_previous(&text[..], text.len(), &regex, 22).await,
+                Some(21..26)
+            );
+            assert_eq!(
+                find_previous(&text[..], text.len(), &regex, 0).await,
+                Some(21..26)
+            );
+        });
+    }
+
+    #[test]
+    fn test_anchors_match_line_starts() {
+        let text = b"a info\ninfo b\n";
+        let regex = regex("^info");
+        smol::block_on(async {
+            assert_eq!(
+                find_next(&text[..], text.len(), &regex, 2).await,
+                Some(7..11)
+            );
+            assert_eq!(
+                find_previous(&text[..], text.len(), &regex, text.len()).await,
+                Some(7..11)
+            );
+        });
+    }
+
+    #[test]
+    fn test_matches_across_chunks() {
+        let line = "x".repeat(1023) + "\n";
+        let mut text = line.repeat(2 * CHUNK_LEN / line.len());
+        let needle_offset = text.len();
+        text.push_str("needle\n");
+        text.push_str(&line.repeat(CHUNK_LEN / line.len()));
+
+        let regex = regex("needle");
+        let expected = needle_offset..needle_offset + 6;
+        smol::block_on(async {
+            assert_eq!(
+                find_next(text.as_bytes(), text.len(), &regex, 0).await,
+                Some(expected.clone())
+            );
+            assert_eq!(
+                find_previous(text.as_bytes(), text.len(), &regex, text.len()).await,
+                Some(expected)
+            );
+        });
+    }
+
+    /// Text that fails the test if more than a few chunks are read at once.
+    struct ChunkedReads<'a>(&'a [u8]);
+
+    impl ReadAt for ChunkedReads<'_> {
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn read_at(&self, range: Range<usize>) -> Cow<'_, [u8]> {
+            assert!(
+                range.len() <= CHUNK_LEN + 2 * MAX_LINE_EXTENSION,
+                "read {range:?} at once"
+            );
+            self.0.read_at(range)
+        }
+    }
+
+    #[test]
+    fn test_long_lines_are_read_in_chunks() {
+        let mut text = "x".repeat(5 * CHUNK_LEN);
+        let needle_offset = 2 * CHUNK_LEN + 100;
+        text.replace_range(needle_offset..needle_offset + 6, "needle");
+        let text = ChunkedReads(text.as_bytes());
+
+        let missing = regex("missing");
+        let regex = regex("needle");
+        let expected = needle_offset..needle_offset + 6;
+        smol::block_on(async {
+            assert_eq!(
+                find_next(&text, text.len(), &regex, 0).await,
+                Some(expected.clone())
+            );
+            assert_eq!(
+                find_previous(&text, text.len(), &regex, text.len()).await,
+                Some(expected)
+            );
+            assert_eq!(find_next(&text, text.len(), &missing, 0).await, None);
+        });
+    }
+}
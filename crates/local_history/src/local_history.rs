@@ -0,0 +1,288 @@
+mod local_history_settings;
+mod local_history_view;
+mod persistence;
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use collections::HashSet;
+use gpui::{App, AppContext as _, Context, Entity, EntityId, EventEmitter, Global, Task, actions};
+use language::{Buffer, BufferEvent};
+use project::{Project, ProjectPath, buffer_store::BufferStoreEvent};
+use settings::Settings as _;
+use time::OffsetDateTime;
+use workspace::Workspace;
+
+pub use crate::local_history_settings::*;
+pub use crate::local_history_view::LocalHistoryView;
+use crate::persistence::LOCAL_HISTORY_DB;
+pub use crate::persistence::{SnapshotEntry, SnapshotSource};
+
+actions!(
+    local_history,
+    [
+        /// Shows the versions of the active file recorded in its local history.
+        ShowHistory,
+        /// Replaces the file's contents with the selected version.
+        RestoreVersion
+    ]
+);
+
+pub enum LocalHistoryEvent {
+    /// A version of the file at the given location was recorded.
+    SnapshotRecorded(Arc<str>),
+}
+
+/// Records versions of files into a local database as they're saved, so they can be
+/// restored without version control.
+pub struct LocalHistory {
+    /// Buffers that have a periodic snapshot scheduled.
+    pending_snapshots: HashSet<EntityId>,
+}
+
+struct GlobalLocalHistory(Entity<LocalHistory>);
+
+impl Global for GlobalLocalHistory {}
+
+impl EventEmitter<LocalHistoryEvent> for LocalHistory {}
+
+impl LocalHistory {
+    pub fn global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalLocalHistory>()
+            .map(|local_history| local_history.0.clone())
+    }
+
+    /// Records a version of the file at `location`, pruning the versions that fall outside of
+    /// the retention limits.
+    pub fn record_snapshot(
+        &mut self,
+        location: Arc<str>,
+        content: String,
+        source: SnapshotSource,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let settings = LocalHistorySettings::get_global(cx);
+        let max_snapshots = settings.max_snapshots_per_file;
+        let max_age = settings.max_age();
+        let created_at = OffsetDateTime::now_utc();
+        cx.spawn(async move |this, cx| {
+            LOCAL_HISTORY_DB
+                .record_snapshot(
+                    location.to_string(),
+                    content,
+                    source,
+                    created_at,
+                    max_snapshots,
+                    max_age,
+                )
+                .await?;
+            this.update(cx, |_, cx| {
+                cx.emit(LocalHistoryEvent::SnapshotRecorded(location))
+            })
+        })
+    }
+}
+
+/// The key that a buffer's versions are recorded under. Files in remote projects are
+/// qualified with the host they live on.
+pub fn history_location(project: &Project, buffer: &Buffer, cx: &App) -> Option<Arc<str>> {
+    let file = buffer.file()?;
+    let abs_path = project.absolute_path(&ProjectPath::from_file(file.as_ref(), cx), cx)?;
+    let abs_path = abs_path.to_string_lossy();
+    Some(match project.remote_connection_options(cx) {
+        Some(connection_options) => {
+            format!("{}:{abs_path}", connection_options.display_name()).into()
+        }
+        None => abs_path.into(),
+    })
+}
+
+fn observe_buffer(buffer: &Entity<Buffer>, cx: &mut Context<Project>) {
+    cx.subscribe(buffer, |project, buffer, event, cx| match event {
+        BufferEvent::Saved => record_buffer(project, &buffer, SnapshotSource::Save, cx),
+        BufferEvent::Edited => schedule_periodic_snapshot(&buffer, cx),
+        _ => {}
+    })
+    .detach();
+}
+
+fn record_buffer(project: &Project, buffer: &Entity<Buffer>, source: SnapshotSource, cx: &mut App) {
+    let settings = LocalHistorySettings::get_global(cx);
+    if !settings.enabled {
+        return;
+    }
+    let max_file_size = settings.max_file_size();
+
+    let buffer = buffer.read(cx);
+    if buffer.len() > max_file_size {
+        return;
+    }
+    let Some(location) = history_location(project, buffer, cx) else {
+        return;
+    };
+    let content = buffer.text();
+
+    if let Some(local_history) = LocalHistory::global(cx) {
+        local_history
+            .update(cx, |local_history, cx| {
+                local_history.record_snapshot(location, content, source, cx)
+            })
+            .detach_and_log_err(cx);
+    }
+}
+
+/// Records a version of the buffer once the snapshot interval has passed, if it still has
+/// unsaved edits by then.
+fn schedule_periodic_snapshot(buffer: &Entity<Buffer>, cx: &mut Context<Project>) {
+    let Some(interval) = LocalHistorySettings::get_global(cx).snapshot_interval() else {
+        return;
+    };
+    let Some(local_history) = LocalHistory::global(cx) else {
+        return;
+    };
+    let buffer_id = buffer.entity_id();
+    let newly_scheduled = local_history.update(cx, |local_history, _| {
+        local_history.pending_snapshots.insert(buffer_id)
+    });
+    if !newly_scheduled {
+        return;
+    }
+
+    let buffer = buffer.downgrade();
+    cx.spawn(async move |project, cx| {
+        cx.background_executor().timer(interval).await;
+        local_history.update(cx, |local_history, _| {
+            local_history.pending_snapshots.remove(&buffer_id)
+        });
+        project
+            .update(cx, |project, cx| {
+                if let Some(buffer) = buffer.upgrade()
+                    && buffer.read(cx).is_dirty()
+                {
+                    record_buffer(project, &buffer, SnapshotSource::Periodic, cx);
+                }
+            })
+            .ok();
+    })
+    .detach();
+}
+
+pub fn init(cx: &mut App) {
+    let local_history = cx.new(|_| LocalHistory {
+        pending_snapshots: HashSet::default(),
+    });
+    cx.set_global(GlobalLocalHistory(local_history));
+
+    // Versions are otherwise only pruned as their file is saved again, so apply the retention
+    // limits to files that haven't been saved since they were lowered.
+    let settings = LocalHistorySettings::get_global(cx);
+    let max_snapshots = settings.max_snapshots_per_file;
+    let max_age = settings.max_age();
+    cx.background_spawn(async move {
+        LOCAL_HISTORY_DB
+            .prune_snapshots(OffsetDateTime::now_utc(), max_snapshots, max_age)
+            .await
+    })
+    .detach_and_log_err(cx);
+
+    cx.observe_new(|project: &mut Project, _, cx| {
+        // Collaborators' edits are recorded on the host's machine.
+        if project.is_via_collab() {
+            return;
+        }
+        for buffer in project.opened_buffers(cx) {
+            observe_buffer(&buffer, cx);
+        }
+        let buffer_store = project.buffer_store().clone();
+        cx.subscribe(&buffer_store, |_, _, event: &BufferStoreEvent, cx| {
+            if let BufferStoreEvent::BufferAdded(buffer) = event {
+                observe_buffer(buffer, cx);
+            }
+        })
+        .detach();
+    })
+    .detach();
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(LocalHistoryView::deploy);
+    })
+    .detach();
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use std::path::Path;
+    use util::path;
+    use workspace::AppState;
+
+    /// Opens one of the files of a test project. Every test opens a different file, as the
+    /// tests share their database.
+    pub(crate) async fn init_test(
+        file_name: &str,
+        cx: &mut TestAppContext,
+    ) -> (Entity<Project>, Entity<Buffer>) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            editor::init(cx);
+            crate::init(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({ "saved.txt": "one\n", "restored.txt": "one\n" }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(Path::new(path!("/dir")).join(file_name), cx)
+            })
+            .await
+            .unwrap();
+        (project, buffer)
+    }
+
+    pub(crate) fn contents(location: &str) -> Vec<(SnapshotSource, String)> {
+        LOCAL_HISTORY_DB
+            .snapshots(location)
+            .unwrap()
+            .into_iter()
+            .map(|entry| {
+                let content = LOCAL_HISTORY_DB.snapshot_content(entry.id).unwrap();
+                (entry.source, content.unwrap())
+            })
+            .collect()
+    }
+
+    #[gpui::test]
+    async fn test_saving_records_snapshot(cx: &mut TestAppContext) {
+        let (project, buffer) = init_test("saved.txt", cx).await;
+        let location = project
+            .read_with(cx, |project, cx| {
+                history_location(project, buffer.read(cx), cx)
+            })
+            .unwrap();
+        assert_eq!(&*location, path!("/dir/saved.txt"));
+
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..3, "two")], None, cx));
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            contents(&location),
+            [(SnapshotSource::Save, "two\n".to_string())]
+        );
+
+        // Edits aren't recorded until they're saved.
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..3, "six")], None, cx));
+        cx.run_until_parked();
+        assert_eq!(contents(&location).len(), 1);
+    }
+}
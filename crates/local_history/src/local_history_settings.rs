@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use settings::{RegisterSetting, Settings};
+
+/// The settings for local file history.
+#[derive(Clone, Debug, Default, RegisterSetting)]
+pub struct LocalHistorySettings {
+    /// Whether to record a version of files when they're saved.
+    ///
+    /// Default: true
+    pub enabled: bool,
+    /// How many versions to keep for each file.
+    ///
+    /// Default: 50
+    pub max_snapshots_per_file: u32,
+    /// How many days to keep versions for. Zero keeps them until they're pushed out by
+    /// newer versions.
+    ///
+    /// Default: 30
+    pub max_age_days: u32,
+    /// How often to record a version of files with unsaved edits, in seconds. Zero only
+    /// records versions on save.
+    ///
+    /// Default: 0
+    pub snapshot_interval_seconds: u64,
+    /// Files larger than this many kilobytes aren't recorded.
+    ///
+    /// Default: 1024
+    pub max_file_size_kb: u64,
+}
+
+impl LocalHistorySettings {
+    /// How long versions are kept for, if there's a limit.
+    pub fn max_age(&self) -> Option<time::Duration> {
+        (self.max_age_days > 0).then(|| time::Duration::days(self.max_age_days as i64))
+    }
+
+    /// How often versions of files with unsaved edits are recorded, if they are.
+    pub fn snapshot_interval(&self) -> Option<Duration> {
+        (self.snapshot_interval_seconds > 0)
+            .then(|| Duration::from_secs(self.snapshot_interval_seconds))
+    }
+
+    /// The size in bytes above which files aren't recorded.
+    pub fn max_file_size(&self) -> usize {
+        (self.max_file_size_kb as usize).saturating_mul(1024)
+    }
+}
+
+impl Settings for LocalHistorySettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let local_history = content.local_history.clone().unwrap();
+        Self {
+            enabled: local_history.enabled.unwrap(),
+            max_snapshots_per_file: local_history.max_snapshots_per_file.unwrap(),
+            max_age_days: local_history.max_age_days.unwrap(),
+            snapshot_interval_seconds: local_history.snapshot_interval_seconds.unwrap(),
+            max_file_size_kb: local_history.max_file_size_kb.unwrap(),
+        }
+    }
+}
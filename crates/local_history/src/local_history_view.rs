@@ -0,0 +1,581 @@
+use std::{any::TypeId, pin::pin, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use buffer_diff::BufferDiff;
+use editor::{Editor, MultiBuffer};
+use futures::{FutureExt, select_biased};
+use gpui::{
+    AnyElement, AnyEntity, App, AppContext as _, Context, Entity, EventEmitter, FocusHandle,
+    Focusable, IntoElement, Render, ScrollStrategy, Subscription, Task, UniformListScrollHandle,
+    Window, uniform_list,
+};
+use language::{Buffer, BufferEvent};
+use project::Project;
+use time::{OffsetDateTime, UtcOffset};
+use ui::{ListItem, WithScrollbar, prelude::*};
+use util::ResultExt as _;
+use workspace::{Item, Toast, Workspace, item::ItemEvent, notifications::NotificationId};
+
+use crate::{
+    LocalHistory, LocalHistoryEvent, RestoreVersion, ShowHistory, SnapshotEntry, SnapshotSource,
+    history_location, persistence::LOCAL_HISTORY_DB,
+};
+
+const RECALCULATE_DIFF_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Lists the recorded versions of a file, showing how the selected version differs from the
+/// file's current contents.
+pub struct LocalHistoryView {
+    buffer: Entity<Buffer>,
+    location: Arc<str>,
+    entries: Vec<SnapshotEntry>,
+    selected_entry: Option<usize>,
+    /// The contents of the selected version, once they've been loaded.
+    selected_content: Option<Arc<str>>,
+    editor: Entity<Editor>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    local_offset: UtcOffset,
+    buffer_changes_tx: watch::Sender<()>,
+    load_entries_task: Task<()>,
+    load_content_task: Task<()>,
+    _recalculate_diff_task: Task<Result<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl LocalHistoryView {
+    pub(crate) fn deploy(
+        workspace: &mut Workspace,
+        _: &ShowHistory,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let buffer = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton());
+        let project = workspace.project().clone();
+        let Some((buffer, location)) = buffer.and_then(|buffer| {
+            let location = history_location(project.read(cx), buffer.read(cx), cx)?;
+            Some((buffer, location))
+        }) else {
+            struct NoLocalHistory;
+
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<NoLocalHistory>(),
+                    "Local history is only recorded for files",
+                ),
+                cx,
+            );
+            return;
+        };
+
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items_of_type::<Self>()
+            .find(|view| view.read(cx).location == location);
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+        } else {
+            let view = cx.new(|cx| Self::new(buffer, location, project, window, cx));
+            workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+        }
+    }
+
+    pub fn new(
+        buffer: Entity<Buffer>,
+        location: Arc<str>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let buffer_snapshot = buffer.read(cx).text_snapshot();
+        let diff = cx.new(|cx| BufferDiff::new(&buffer_snapshot, cx));
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, Some(project), window, cx);
+            editor.start_temporary_diff_override();
+            editor.disable_diagnostics(cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor.set_render_diff_hunk_controls(
+                Arc::new(|_, _, _, _, _, _, _, _| gpui::Empty.into_any_element()),
+                cx,
+            );
+            editor
+        });
+
+        let (buffer_changes_tx, mut buffer_changes_rx) = watch::channel(());
+        let mut subscriptions = vec![cx.subscribe(&buffer, |this, _, event, _| match event {
+            BufferEvent::Edited | BufferEvent::LanguageChanged(_) | BufferEvent::Reparsed => {
+                this.buffer_changes_tx.send(()).ok();
+            }
+            _ => {}
+        })];
+        if let Some(local_history) = LocalHistory::global(cx) {
+            subscriptions.push(cx.subscribe(
+                &local_history,
+                |this, _, event: &LocalHistoryEvent, cx| match event {
+                    LocalHistoryEvent::SnapshotRecorded(location) => {
+                        if *location == this.location {
+                            this.load_entries(cx);
+                        }
+                    }
+                },
+            ));
+        }
+
+        let recalculate_diff_task = cx.spawn(async move |this, cx| {
+            while buffer_changes_rx.recv().await.is_ok() {
+                loop {
+                    let mut timer = cx
+                        .background_executor()
+                        .timer(RECALCULATE_DIFF_DEBOUNCE)
+                        .fuse();
+                    let mut recv = pin!(buffer_changes_rx.recv().fuse());
+                    select_biased! {
+                        _ = timer => break,
+                        _ = recv => continue,
+                    }
+                }
+
+                let (base_text, snapshot) = this.update(cx, |this, cx| {
+                    (
+                        this.selected_content.clone(),
+                        this.buffer.read(cx).snapshot(),
+                    )
+                })?;
+                // Until a version is loaded, the buffer is shown without a diff.
+                let Some(base_text) = base_text else {
+                    continue;
+                };
+                diff.update(cx, |diff, cx| {
+                    diff.set_base_text(
+                        Some(base_text),
+                        snapshot.language().cloned(),
+                        snapshot.text.clone(),
+                        cx,
+                    )
+                })
+                .await
+                .ok();
+            }
+            Ok(())
+        });
+
+        let mut this = Self {
+            buffer,
+            location,
+            entries: Vec::new(),
+            selected_entry: None,
+            selected_content: None,
+            editor,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            local_offset: UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC),
+            buffer_changes_tx,
+            load_entries_task: Task::ready(()),
+            load_content_task: Task::ready(()),
+            _recalculate_diff_task: recalculate_diff_task,
+            _subscriptions: subscriptions,
+        };
+        this.load_entries(cx);
+        this
+    }
+
+    fn load_entries(&mut self, cx: &mut Context<Self>) {
+        let location = self.location.clone();
+        let entries = cx.background_spawn(async move { LOCAL_HISTORY_DB.snapshots(&location) });
+        self.load_entries_task = cx.spawn(async move |this, cx| {
+            let Some(entries) = entries.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                let selected_id = this
+                    .selected_entry
+                    .and_then(|ix| this.entries.get(ix))
+                    .map(|entry| entry.id);
+                this.entries = entries;
+                let ix = selected_id
+                    .and_then(|id| this.entries.iter().position(|entry| entry.id == id))
+                    .or_else(|| (!this.entries.is_empty()).then_some(0));
+                this.select_ix(ix, cx);
+            })
+            .ok();
+        });
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let entry_count = self.entries.len();
+        let ix = match self.selected_entry {
+            _ if entry_count == 0 => None,
+            None => Some(0),
+            Some(ix) => Some((ix + 1).min(entry_count - 1)),
+        };
+        self.select_ix(ix, cx);
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let entry_count = self.entries.len();
+        let ix = match self.selected_entry {
+            _ if entry_count == 0 => None,
+            None => Some(entry_count - 1),
+            Some(ix) => Some(ix.saturating_sub(1)),
+        };
+        self.select_ix(ix, cx);
+    }
+
+    fn select_first(&mut self, _: &menu::SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        let ix = (!self.entries.is_empty()).then_some(0);
+        self.select_ix(ix, cx);
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        let ix = self.entries.len().checked_sub(1);
+        self.select_ix(ix, cx);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        window.focus(&self.editor.focus_handle(cx), cx);
+    }
+
+    fn select_ix(&mut self, ix: Option<usize>, cx: &mut Context<Self>) {
+        let previous_id = self
+            .selected_entry
+            .and_then(|ix| self.entries.get(ix))
+            .map(|entry| entry.id);
+        self.selected_entry = ix;
+        if let Some(ix) = ix {
+            self.scroll_handle.scroll_to_item(ix, ScrollStrategy::Top);
+        }
+        cx.notify();
+
+        let Some(entry) = ix.and_then(|ix| self.entries.get(ix)) else {
+            self.selected_content = None;
+            return;
+        };
+        if Some(entry.id) == previous_id && self.selected_content.is_some() {
+            return;
+        }
+        let id = entry.id;
+        self.selected_content = None;
+        let content = cx.background_spawn(async move { LOCAL_HISTORY_DB.snapshot_content(id) });
+        self.load_content_task = cx.spawn(async move |this, cx| {
+            let Some(Some(content)) = content.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.selected_content = Some(content.into());
+                this.buffer_changes_tx.send(()).ok();
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn restore_version(&mut self, _: &RestoreVersion, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(content) = self.selected_content.clone() else {
+            return;
+        };
+        let current_content = self.buffer.read(cx).text();
+        if current_content == *content {
+            return;
+        }
+
+        // Keep what's being replaced, so restoring can itself be undone after the buffer is
+        // saved or closed.
+        if let Some(local_history) = LocalHistory::global(cx) {
+            local_history
+                .update(cx, |local_history, cx| {
+                    local_history.record_snapshot(
+                        self.location.clone(),
+                        current_content,
+                        SnapshotSource::Restore,
+                        cx,
+                    )
+                })
+                .detach_and_log_err(cx);
+        }
+
+        // Only replace the lines that differ, so that anchors elsewhere in the buffer, such as
+        // the cursors of its editors, stay where they were.
+        let diff = self.buffer.read(cx).diff(content, cx);
+        let buffer = self.buffer.clone();
+        cx.spawn(async move |_, cx| {
+            let diff = diff.await;
+            buffer.update(cx, |buffer, cx| buffer.apply_diff(diff, cx));
+        })
+        .detach();
+    }
+
+    fn render_entry(&self, ix: usize, entry: &SnapshotEntry, cx: &Context<Self>) -> AnyElement {
+        let now = OffsetDateTime::now_utc();
+        let relative_timestamp = time_format::format_localized_timestamp(
+            entry.created_at,
+            now,
+            self.local_offset,
+            time_format::TimestampFormat::Relative,
+        );
+        let absolute_timestamp = time_format::format_localized_timestamp(
+            entry.created_at,
+            now,
+            self.local_offset,
+            time_format::TimestampFormat::EnhancedAbsolute,
+        );
+
+        ListItem::new(("version", ix))
+            .toggle_state(Some(ix) == self.selected_entry)
+            .child(
+                v_flex()
+                    .w_full()
+                    .py_1()
+                    .px_1()
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .justify_between()
+                            .gap_2()
+                            .child(Label::new(relative_timestamp).truncate())
+                            .child(
+                                Label::new(entry.source.label())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .child(
+                        Label::new(format!("{absolute_timestamp} · {}", format_size(entry.len)))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .truncate(),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, _, cx| {
+                this.select_ix(Some(ix), cx);
+            }))
+            .into_any_element()
+    }
+
+    fn file_name(&self, cx: &App) -> SharedString {
+        self.buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string().into())
+            .unwrap_or_else(|| "untitled".into())
+    }
+}
+
+fn format_size(len: usize) -> String {
+    if len < 1024 {
+        format!("{len} B")
+    } else {
+        format!("{:.1} KB", len as f64 / 1024.)
+    }
+}
+
+impl EventEmitter<ItemEvent> for LocalHistoryView {}
+
+impl Focusable for LocalHistoryView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for LocalHistoryView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let entry_count = self.entries.len();
+        let border_color = cx.theme().colors().border_variant;
+        let background = cx.theme().colors().editor_background;
+
+        let timeline = v_flex()
+            .id("local-history-timeline")
+            .key_context("LocalHistoryView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::restore_version))
+            .w(rems(20.))
+            .h_full()
+            .flex_none()
+            .border_r_1()
+            .border_color(border_color)
+            .child(
+                h_flex()
+                    .h(rems_from_px(41.))
+                    .pl_3()
+                    .pr_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(border_color)
+                    .child(
+                        Label::new(format!("{entry_count} versions"))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Button::new("restore-version", "Restore")
+                            .label_size(LabelSize::Small)
+                            .icon(IconName::RotateCcw)
+                            .icon_size(IconSize::Small)
+                            .icon_color(Color::Muted)
+                            .icon_position(IconPosition::Start)
+                            .disabled(self.selected_content.is_none())
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.restore_version(&RestoreVersion, window, cx);
+                            })),
+                    ),
+            )
+            .map(|this| {
+                if entry_count == 0 {
+                    this.child(v_flex().flex_1().p_3().child(
+                        Label::new("No versions have been recorded yet.").color(Color::Muted),
+                    ))
+                } else {
+                    let view = cx.weak_entity();
+                    this.child(
+                        v_flex()
+                            .flex_1()
+                            .size_full()
+                            .child(
+                                uniform_list(
+                                    "local-history-list",
+                                    entry_count,
+                                    move |range, _, cx| {
+                                        let Some(view) = view.upgrade() else {
+                                            return Vec::new();
+                                        };
+                                        view.update(cx, |this, cx| {
+                                            range
+                                                .filter_map(|ix| {
+                                                    let entry = this.entries.get(ix)?;
+                                                    Some(this.render_entry(ix, entry, cx))
+                                                })
+                                                .collect()
+                                        })
+                                    },
+                                )
+                                .flex_1()
+                                .size_full()
+                                .track_scroll(&self.scroll_handle),
+                            )
+                            .vertical_scrollbar_for(&self.scroll_handle, window, cx),
+                    )
+                }
+            });
+
+        h_flex()
+            .size_full()
+            .bg(background)
+            .child(timeline)
+            .child(div().flex_1().h_full().child(self.editor.clone()))
+    }
+}
+
+impl Item for LocalHistoryView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        format!("Local History: {}", self.file_name(cx)).into()
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(format!("Local history for {}", self.location).into())
+    }
+
+    fn tab_icon(&self, _: &Window, _: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Local History Opened")
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        _: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.focus(&self.focus_handle, cx);
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyEntity> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.clone().into())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{contents, init_test};
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_restore_version(cx: &mut TestAppContext) {
+        let (project, buffer) = init_test("restored.txt", cx).await;
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..3, "two")], None, cx));
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(buffer.len()..buffer.len(), "three\n")], None, cx)
+        });
+
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let view = workspace.update_in(cx, |workspace, window, cx| {
+            let location = history_location(project.read(cx), buffer.read(cx), cx).unwrap();
+            cx.new(|cx| LocalHistoryView::new(buffer.clone(), location, project, window, cx))
+        });
+        cx.run_until_parked();
+        view.read_with(cx, |view, _| {
+            assert_eq!(view.selected_entry, Some(0));
+            assert_eq!(view.selected_content.as_deref(), Some("two\n"));
+        });
+
+        view.update_in(cx, |view, window, cx| {
+            view.restore_version(&RestoreVersion, window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(buffer.read_with(cx, |buffer, _| buffer.text()), "two\n");
+
+        // The replaced contents are recorded, so that restoring can be undone.
+        let location = view.read_with(cx, |view, _| view.location.clone());
+        assert_eq!(
+            contents(&location),
+            [
+                (SnapshotSource::Restore, "two\nthree\n".to_string()),
+                (SnapshotSource::Save, "two\n".to_string()),
+            ]
+        );
+        view.read_with(cx, |view, _| assert_eq!(view.entries.len(), 2));
+    }
+}
@@ -0,0 +1,301 @@
+use anyhow::Result;
+use db::{
+    query,
+    sqlez::{
+        bindable::Column, domain::Domain, statement::Statement,
+        thread_safe_connection::ThreadSafeConnection,
+    },
+    sqlez_macros::sql,
+};
+use time::OffsetDateTime;
+
+/// What caused a snapshot to be recorded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotSource {
+    /// The file was saved.
+    Save,
+    /// The buffer had unsaved edits when the snapshot interval elapsed.
+    Periodic,
+    /// The buffer's contents right before an older version was restored over them.
+    Restore,
+}
+
+impl SnapshotSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SnapshotSource::Save => "save",
+            SnapshotSource::Periodic => "periodic",
+            SnapshotSource::Restore => "restore",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SnapshotSource::Save => "Saved",
+            SnapshotSource::Periodic => "Autosnapshot",
+            SnapshotSource::Restore => "Before restore",
+        }
+    }
+
+    fn from_str(source: &str) -> Result<Self> {
+        match source {
+            "save" => Ok(SnapshotSource::Save),
+            "periodic" => Ok(SnapshotSource::Periodic),
+            "restore" => Ok(SnapshotSource::Restore),
+            _ => anyhow::bail!("unknown local history snapshot source {source:?}"),
+        }
+    }
+}
+
+/// A recorded version of a file, without its contents.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotEntry {
+    pub id: i64,
+    pub source: SnapshotSource,
+    pub created_at: OffsetDateTime,
+    pub len: usize,
+}
+
+impl Column for SnapshotEntry {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (id, next_index): (i64, i32) = Column::column(statement, start_index)?;
+        let (source, next_index): (String, i32) = Column::column(statement, next_index)?;
+        let (created_at, next_index): (i64, i32) = Column::column(statement, next_index)?;
+        let (len, next_index): (i64, i32) = Column::column(statement, next_index)?;
+
+        let entry = Self {
+            id,
+            source: SnapshotSource::from_str(&source)?,
+            created_at: OffsetDateTime::from_unix_timestamp(created_at)?,
+            len: len as usize,
+        };
+        Ok((entry, next_index))
+    }
+}
+
+pub struct LocalHistoryDb(ThreadSafeConnection);
+
+impl Domain for LocalHistoryDb {
+    const NAME: &str = stringify!(LocalHistoryDb);
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE IF NOT EXISTS local_history_snapshots(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            location TEXT NOT NULL,
+            content TEXT NOT NULL,
+            source TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        ) STRICT;
+        CREATE INDEX IF NOT EXISTS local_history_snapshots_by_location
+            ON local_history_snapshots(location, created_at);
+    )];
+}
+
+db::static_connection!(LOCAL_HISTORY_DB, LocalHistoryDb, []);
+
+impl LocalHistoryDb {
+    /// Records a version of the file at `location`, unless it's identical to the latest one,
+    /// then drops the versions that fall outside of the retention limits.
+    pub async fn record_snapshot(
+        &self,
+        location: String,
+        content: String,
+        source: SnapshotSource,
+        created_at: OffsetDateTime,
+        max_snapshots: u32,
+        max_age: Option<time::Duration>,
+    ) -> Result<()> {
+        self.record_snapshot_internal(
+            location,
+            content,
+            source.as_str().to_string(),
+            created_at.unix_timestamp(),
+            age_cutoff(created_at, max_age),
+            max_snapshots as i64,
+        )
+        .await
+    }
+
+    /// Drops the versions of every file that fall outside of the retention limits, including
+    /// those of files that haven't been saved since the limits were lowered.
+    pub async fn prune_snapshots(
+        &self,
+        now: OffsetDateTime,
+        max_snapshots: u32,
+        max_age: Option<time::Duration>,
+    ) -> Result<()> {
+        self.prune_snapshots_internal(age_cutoff(now, max_age), max_snapshots as i64)
+            .await
+    }
+
+    query! {
+        async fn record_snapshot_internal(
+            location: String,
+            content: String,
+            source: String,
+            created_at: i64,
+            cutoff: i64,
+            max_snapshots: i64
+        ) -> Result<()> {
+            INSERT INTO local_history_snapshots (location, content, source, created_at)
+            SELECT ?1, ?2, ?3, ?4
+            WHERE ?2 IS NOT (
+                SELECT content FROM local_history_snapshots
+                WHERE location = ?1
+                ORDER BY created_at DESC, id DESC
+                LIMIT 1
+            );
+            DELETE FROM local_history_snapshots
+            WHERE location = ?1 AND created_at < ?5;
+            DELETE FROM local_history_snapshots
+            WHERE location = ?1 AND id NOT IN (
+                SELECT id FROM local_history_snapshots
+                WHERE location = ?1
+                ORDER BY created_at DESC, id DESC
+                LIMIT ?6
+            );
+        }
+    }
+
+    query! {
+        async fn prune_snapshots_internal(cutoff: i64, max_snapshots: i64) -> Result<()> {
+            DELETE FROM local_history_snapshots
+            WHERE created_at < ?1;
+            DELETE FROM local_history_snapshots
+            WHERE id IN (
+                SELECT id FROM (
+                    SELECT id, ROW_NUMBER() OVER (
+                        PARTITION BY location
+                        ORDER BY created_at DESC, id DESC
+                    ) AS position
+                    FROM local_history_snapshots
+                )
+                WHERE position > ?2
+            );
+        }
+    }
+
+    query! {
+        pub fn snapshots(location: &str) -> Result<Vec<SnapshotEntry>> {
+            SELECT id, source, created_at, LENGTH(CAST(content AS BLOB))
+            FROM local_history_snapshots
+            WHERE location = ?
+            ORDER BY created_at DESC, id DESC
+        }
+    }
+
+    query! {
+        pub fn snapshot_content(id: i64) -> Result<Option<String>> {
+            SELECT content
+            FROM local_history_snapshots
+            WHERE id = ?
+        }
+    }
+}
+
+/// The timestamp before which versions are older than `max_age`.
+fn age_cutoff(now: OffsetDateTime, max_age: Option<time::Duration>) -> i64 {
+    max_age.map_or(i64::MIN, |max_age| {
+        now.unix_timestamp().saturating_sub(max_age.whole_seconds())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_record_snapshots() {
+        let db = LocalHistoryDb::open_test_db("test_record_snapshots").await;
+        let location = "/project/src/main.rs";
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let record = |content: &str, source, seconds_later| {
+            db.record_snapshot(
+                location.to_string(),
+                content.to_string(),
+                source,
+                now + time::Duration::seconds(seconds_later),
+                3,
+                Some(time::Duration::days(1)),
+            )
+        };
+
+        record("one", SnapshotSource::Save, 0).await.unwrap();
+        // Saving the same contents again doesn't record a new version.
+        record("one", SnapshotSource::Save, 10).await.unwrap();
+        record("two", SnapshotSource::Periodic, 20).await.unwrap();
+
+        let snapshots = db.snapshots(location).unwrap();
+        assert_eq!(
+            snapshots
+                .iter()
+                .map(|entry| (entry.source, entry.len))
+                .collect::<Vec<_>>(),
+            [(SnapshotSource::Periodic, 3), (SnapshotSource::Save, 3)]
+        );
+        assert_eq!(
+            db.snapshot_content(snapshots[0].id).unwrap().as_deref(),
+            Some("two")
+        );
+        assert!(db.snapshots("/project/src/lib.rs").unwrap().is_empty());
+
+        // Only the latest versions are kept.
+        record("three", SnapshotSource::Save, 30).await.unwrap();
+        record("four", SnapshotSource::Restore, 40).await.unwrap();
+        let contents = db
+            .snapshots(location)
+            .unwrap()
+            .into_iter()
+            .map(|entry| db.snapshot_content(entry.id).unwrap().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(contents, ["four", "three", "two"]);
+
+        // Versions older than the maximum age are dropped.
+        record("five", SnapshotSource::Save, 60 * 60 * 24 + 35)
+            .await
+            .unwrap();
+        let contents = db
+            .snapshots(location)
+            .unwrap()
+            .into_iter()
+            .map(|entry| db.snapshot_content(entry.id).unwrap().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(contents, ["five", "four"]);
+    }
+
+    #[gpui::test]
+    async fn test_prune_snapshots() {
+        let db = LocalHistoryDb::open_test_db("test_prune_snapshots").await;
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        for (location, content, days_ago) in [
+            ("/project/a.rs", "a1", 40),
+            ("/project/a.rs", "a2", 3),
+            ("/project/a.rs", "a3", 2),
+            ("/project/a.rs", "a4", 1),
+            ("/project/b.rs", "b1", 50),
+        ] {
+            db.record_snapshot(
+                location.to_string(),
+                content.to_string(),
+                SnapshotSource::Save,
+                now - time::Duration::days(days_ago),
+                50,
+                None,
+            )
+            .await
+            .unwrap();
+        }
+
+        db.prune_snapshots(now, 2, Some(time::Duration::days(30)))
+            .await
+            .unwrap();
+        let contents = |location| {
+            db.snapshots(location)
+                .unwrap()
+                .into_iter()
+                .map(|entry| db.snapshot_content(entry.id).unwrap().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(contents("/project/a.rs"), ["a4", "a3"]);
+        assert!(contents("/project/b.rs").is_empty());
+    }
+}
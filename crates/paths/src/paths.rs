@@ -260,6 +260,12 @@ pub fn keymap_file() -> &'static PathBuf {
     KEYMAP_FILE.get_or_init(|| config_dir().join("keymap.json"))
 }
 
+/// Returns the path to the `vimrc` file, read when vim mode is enabled.
+pub fn vimrc_file() -> &'static PathBuf {
+    static VIMRC_FILE: OnceLock<PathBuf> = OnceLock::new();
+    VIMRC_FILE.get_or_init(|| config_dir().join("vimrc"))
+}
+
 /// Returns the path to the `keymap_backup.json` file.
 pub fn keymap_backup_file() -> &'static PathBuf {
     static KEYMAP_FILE: OnceLock<PathBuf> = OnceLock::new();
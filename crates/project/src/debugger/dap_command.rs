@@ -1792,6 +1792,95 @@ impl LocalDapCommand for SetDataBreakpointsCommand {
     }
 }
 
+/// Which instructions to disassemble, relative to the instruction at a memory reference.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum DisassembleSpan {
+    /// Instructions on both sides of the reference, including it.
+    Around,
+    /// Instructions leading up to the reference, excluding it.
+    Before,
+    /// Instructions following the reference, excluding it.
+    After,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub(crate) struct DisassembleCommand {
+    pub memory_reference: String,
+    pub span: DisassembleSpan,
+}
+
+impl LocalDapCommand for DisassembleCommand {
+    type Response = Vec<dap::DisassembledInstruction>;
+    type DapRequest = dap::requests::Disassemble;
+
+    fn is_supported(capabilities: &Capabilities) -> bool {
+        capabilities
+            .supports_disassemble_request
+            .unwrap_or_default()
+    }
+
+    fn to_dap(&self) -> <Self::DapRequest as dap::requests::Request>::Arguments {
+        let (instruction_offset, instruction_count) = match self.span {
+            DisassembleSpan::Around => (Some(-32), 64),
+            DisassembleSpan::Before => (Some(-64), 64),
+            DisassembleSpan::After => (Some(1), 64),
+        };
+        dap::DisassembleArguments {
+            memory_reference: self.memory_reference.clone(),
+            offset: None,
+            instruction_offset,
+            instruction_count,
+            resolve_symbols: Some(true),
+        }
+    }
+
+    fn response_from_dap(
+        &self,
+        message: <Self::DapRequest as dap::requests::Request>::Response,
+    ) -> Result<Self::Response> {
+        Ok(message.instructions)
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub(crate) struct SetInstructionBreakpointsCommand {
+    /// The addresses of the instructions to break on.
+    pub instruction_references: Vec<String>,
+}
+
+impl LocalDapCommand for SetInstructionBreakpointsCommand {
+    type Response = Vec<dap::Breakpoint>;
+    type DapRequest = dap::requests::SetInstructionBreakpoints;
+
+    fn is_supported(capabilities: &Capabilities) -> bool {
+        capabilities
+            .supports_instruction_breakpoints
+            .unwrap_or_default()
+    }
+
+    fn to_dap(&self) -> <Self::DapRequest as dap::requests::Request>::Arguments {
+        dap::SetInstructionBreakpointsArguments {
+            breakpoints: self
+                .instruction_references
+                .iter()
+                .map(|instruction_reference| dap::InstructionBreakpoint {
+                    instruction_reference: instruction_reference.clone(),
+                    offset: None,
+                    condition: None,
+                    hit_condition: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn response_from_dap(
+        &self,
+        message: <Self::DapRequest as dap::requests::Request>::Response,
+    ) -> Result<Self::Response> {
+        Ok(message.breakpoints)
+    }
+}
+
 #[derive(Clone, Debug, Hash, PartialEq)]
 pub(super) enum SetExceptionBreakpoints {
     Plain {
@@ -2,12 +2,13 @@ use super::breakpoint_store::{
     BreakpointStore, BreakpointStoreEvent, BreakpointUpdatedReason, SourceBreakpoint,
 };
 use super::dap_command::{
-    self, Attach, ConfigurationDone, ContinueCommand, DataBreakpointInfoCommand, DisconnectCommand,
-    EvaluateCommand, Initialize, Launch, LoadedSourcesCommand, LocalDapCommand, LocationsCommand,
-    ModulesCommand, NextCommand, PauseCommand, RestartCommand, RestartStackFrameCommand,
-    ScopesCommand, SetDataBreakpointsCommand, SetExceptionBreakpoints, SetVariableValueCommand,
-    StackTraceCommand, StepBackCommand, StepCommand, StepInCommand, StepOutCommand,
-    TerminateCommand, TerminateThreadsCommand, ThreadsCommand, VariablesCommand,
+    self, Attach, ConfigurationDone, ContinueCommand, DataBreakpointInfoCommand,
+    DisassembleCommand, DisassembleSpan, DisconnectCommand, EvaluateCommand, Initialize, Launch,
+    LoadedSourcesCommand, LocalDapCommand, LocationsCommand, ModulesCommand, NextCommand,
+    PauseCommand, RestartCommand, RestartStackFrameCommand, ScopesCommand,
+    SetDataBreakpointsCommand, SetExceptionBreakpoints, SetInstructionBreakpointsCommand,
+    SetVariableValueCommand, StackTraceCommand, StepBackCommand, StepCommand, StepInCommand,
+    StepOutCommand, TerminateCommand, TerminateThreadsCommand, ThreadsCommand, VariablesCommand,
 };
 use super::dap_store::DapStore;
 use crate::debugger::breakpoint_store::BreakpointSessionState;
@@ -47,7 +48,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use smol::net::{TcpListener, TcpStream};
 use std::any::TypeId;
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::net::Ipv4Addr;
 use std::ops::RangeInclusive;
 use std::path::PathBuf;
@@ -447,6 +448,8 @@ impl RunningMode {
         let supports_exception_filters = capabilities
             .supports_exception_filter_options
             .unwrap_or_default();
+        let supports_instruction_breakpoints =
+            SetInstructionBreakpointsCommand::is_supported(capabilities);
         let this = self.clone();
         let worktree = self.worktree().clone();
         let mut filters = capabilities
@@ -495,6 +498,23 @@ impl RunningMode {
                     }
                 })?;
 
+                // These are only set when restarting a session, since they're toggled on
+                // instructions the adapter disassembled.
+                let instruction_references = session.read_with(cx, |session, _| {
+                    session
+                        .instruction_breakpoints
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })?;
+                if supports_instruction_breakpoints && !instruction_references.is_empty() {
+                    this.request(SetInstructionBreakpointsCommand {
+                        instruction_references,
+                    })
+                    .await
+                    .log_err();
+                }
+
                 if should_send_exception_breakpoints {
                     _ = session.update(cx, |this, _| {
                         filters.retain(|filter| {
@@ -709,6 +729,8 @@ pub struct Session {
     ignore_breakpoints: bool,
     exception_breakpoints: BTreeMap<String, (ExceptionBreakpointsFilter, IsEnabled)>,
     data_breakpoints: BTreeMap<String, DataBreakpointState>,
+    /// The addresses of the instructions to break on.
+    instruction_breakpoints: BTreeSet<String>,
     background_tasks: Vec<Task<()>>,
     restart_task: Option<Task<()>>,
     task_context: SharedTaskContext,
@@ -883,6 +905,7 @@ impl Session {
                 ignore_breakpoints: false,
                 breakpoint_store,
                 data_breakpoints: Default::default(),
+                instruction_breakpoints: Default::default(),
                 exception_breakpoints: Default::default(),
                 label,
                 adapter,
@@ -1899,6 +1922,9 @@ impl Session {
     }
 
     pub fn write_memory(&mut self, address: u64, data: &[u8], cx: &mut Context<Self>) {
+        if self.is_post_mortem() {
+            return;
+        }
         let data = base64::engine::general_purpose::STANDARD.encode(data);
         self.request(
             WriteMemoryArguments {
@@ -2086,6 +2112,60 @@ impl Session {
         self.send_data_breakpoints(cx);
     }
 
+    pub fn has_instruction_breakpoint(&self, instruction_reference: &str) -> bool {
+        self.instruction_breakpoints.contains(instruction_reference)
+    }
+
+    pub fn instruction_breakpoints(&self) -> impl Iterator<Item = &String> {
+        self.instruction_breakpoints.iter()
+    }
+
+    /// Carries instruction breakpoints over from a session being restarted. They're sent to
+    /// the adapter during the configuration sequence, so this has to be called before `boot`.
+    pub fn set_instruction_breakpoints(&mut self, instruction_references: Vec<String>) {
+        self.instruction_breakpoints = instruction_references.into_iter().collect();
+    }
+
+    pub fn toggle_instruction_breakpoint(
+        &mut self,
+        instruction_reference: String,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.instruction_breakpoints.remove(&instruction_reference) {
+            self.instruction_breakpoints.insert(instruction_reference);
+        }
+        self.send_instruction_breakpoints(cx);
+        cx.notify();
+    }
+
+    fn send_instruction_breakpoints(&mut self, cx: &mut Context<Self>) {
+        if !SetInstructionBreakpointsCommand::is_supported(&self.capabilities) {
+            return;
+        }
+        if let Some(mode) = self.as_running() {
+            let command = SetInstructionBreakpointsCommand {
+                instruction_references: self.instruction_breakpoints.iter().cloned().collect(),
+            };
+            mode.request(command).detach_and_log_err(cx);
+        }
+    }
+
+    pub fn disassemble(
+        &mut self,
+        memory_reference: String,
+        span: DisassembleSpan,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Vec<dap::DisassembledInstruction>>> {
+        self.request(
+            DisassembleCommand {
+                memory_reference,
+                span,
+            },
+            |_, response, _| response.log_err(),
+            cx,
+        )
+    }
+
     pub fn breakpoints_enabled(&self) -> bool {
         self.ignore_breakpoints
     }
@@ -2194,7 +2274,13 @@ impl Session {
                             RestartCommand {
                                 raw: args.unwrap_or(Value::Null),
                             },
-                            Self::fallback_to_manual_restart,
+                            |session, result, cx| {
+                                session.fallback_to_manual_restart(result, cx)?;
+                                // Some adapters drop instruction breakpoints when the debuggee
+                                // restarts.
+                                session.send_instruction_breakpoints(cx);
+                                Some(())
+                            },
                             cx,
                         )
                         .detach();
@@ -2275,6 +2361,9 @@ impl Session {
     }
 
     pub fn continue_thread(&mut self, thread_id: ThreadId, cx: &mut Context<Self>) {
+        if self.is_post_mortem() {
+            return;
+        }
         self.select_historic_snapshot(None, cx);
 
         let supports_single_thread_execution_requests =
@@ -2312,6 +2401,9 @@ impl Session {
         granularity: SteppingGranularity,
         cx: &mut Context<Self>,
     ) {
+        if self.is_post_mortem() {
+            return;
+        }
         self.select_historic_snapshot(None, cx);
 
         let supports_single_thread_execution_requests =
@@ -2344,6 +2436,9 @@ impl Session {
         granularity: SteppingGranularity,
         cx: &mut Context<Self>,
     ) {
+        if self.is_post_mortem() {
+            return;
+        }
         self.select_historic_snapshot(None, cx);
 
         let supports_single_thread_execution_requests =
@@ -2376,6 +2471,9 @@ impl Session {
         granularity: SteppingGranularity,
         cx: &mut Context<Self>,
     ) {
+        if self.is_post_mortem() {
+            return;
+        }
         self.select_historic_snapshot(None, cx);
 
         let supports_single_thread_execution_requests =
@@ -2408,6 +2506,9 @@ impl Session {
         granularity: SteppingGranularity,
         cx: &mut Context<Self>,
     ) {
+        if self.is_post_mortem() {
+            return;
+        }
         self.select_historic_snapshot(None, cx);
 
         let supports_single_thread_execution_requests =
@@ -2685,7 +2786,7 @@ impl Session {
         value: String,
         cx: &mut Context<Self>,
     ) {
-        if self.capabilities.supports_set_variable.unwrap_or_default() {
+        if self.capabilities.supports_set_variable.unwrap_or_default() && !self.is_post_mortem() {
             self.request(
                 SetVariableValueCommand {
                     name,
@@ -2801,6 +2902,20 @@ impl Session {
         local_mode.binary.request_args.request == StartDebuggingRequestArgumentsRequest::Attach
     }
 
+    /// Whether the session is looking at a core dump, which can be inspected but not resumed
+    /// or changed.
+    pub fn is_post_mortem(&self) -> bool {
+        let SessionState::Running(local_mode) = &self.state else {
+            return false;
+        };
+        local_mode
+            .binary
+            .request_args
+            .configuration
+            .get("coreFile")
+            .is_some()
+    }
+
     pub fn disconnect_client(&mut self, cx: &mut Context<Self>) {
         let command = DisconnectCommand {
             restart: Some(false),
@@ -26,19 +26,23 @@ use futures::{
 };
 use git::{
     BuildPermalinkParams, GitHostingProviderRegistry, Oid, RunHook,
+    bisect::{BisectProgress, BisectTerm},
     blame::Blame,
     parse_git_remote_url,
+    rebase::{RebaseControl, RebaseProgress, RebaseTodoEntry},
     repository::{
         Branch, CommitDetails, CommitDiff, CommitFile, CommitOptions, DiffType, FetchOptions,
         GitRepository, GitRepositoryCheckpoint, GraphCommitData, InitialGraphCommitData, LogOrder,
         LogSource, PushOptions, Remote, RemoteCommandOutput, RepoPath, ResetMode,
         UpstreamTrackingStatus, Worktree as GitWorktree,
     },
+    signing::CommitSignature,
     stash::{GitStash, StashEntry},
     status::{
         DiffTreeType, FileStatus, GitSummary, StatusCode, TrackedStatus, TreeDiff, TreeDiffStatus,
         UnmergedStatus, UnmergedStatusCode,
     },
+    submodule::{Submodule, SubmoduleCommand},
 };
 use gpui::{
     App, AppContext, AsyncApp, Context, Entity, EventEmitter, SharedString, Subscription, Task,
@@ -62,7 +66,7 @@ use std::{
     cmp::Ordering,
     collections::{BTreeSet, HashSet, VecDeque},
     future::Future,
-    mem,
+    iter, mem,
     ops::Range,
     path::{Path, PathBuf},
     str::FromStr,
@@ -275,6 +279,12 @@ pub struct RepositorySnapshot {
     pub remote_origin_url: Option<String>,
     pub remote_upstream_url: Option<String>,
     pub stash_entries: GitStash,
+    /// The rebase in progress, if any. Not synced to collab guests yet.
+    pub rebase: Option<RebaseProgress>,
+    /// The bisect in progress, if any.
+    pub bisect: Option<BisectProgress>,
+    /// The submodules declared in `.gitmodules`. Not synced to collab guests yet.
+    pub submodules: Vec<Submodule>,
 }
 
 type JobId = u64;
@@ -395,6 +405,7 @@ pub enum RepositoryEvent {
     StatusesChanged,
     MergeHeadsChanged,
     BranchChanged,
+    RebaseChanged,
     StashEntriesChanged,
     PendingOpsChanged { pending_ops: SumTree<PendingOps> },
     GitGraphCountUpdated((LogOrder, LogSource), usize),
@@ -507,6 +518,15 @@ impl GitStore {
         client.add_entity_request_handler(Self::handle_create_remote);
         client.add_entity_request_handler(Self::handle_remove_remote);
         client.add_entity_request_handler(Self::handle_delete_branch);
+        client.add_entity_request_handler(Self::handle_cherry_pick);
+        client.add_entity_request_handler(Self::handle_cherry_pick_range);
+        client.add_entity_request_handler(Self::handle_bisect_start);
+        client.add_entity_request_handler(Self::handle_bisect_mark);
+        client.add_entity_request_handler(Self::handle_bisect_run);
+        client.add_entity_request_handler(Self::handle_bisect_reset);
+        client.add_entity_request_handler(Self::handle_revert);
+        client.add_entity_request_handler(Self::handle_create_tag);
+        client.add_entity_request_handler(Self::handle_delete_tag);
         client.add_entity_request_handler(Self::handle_git_init);
         client.add_entity_request_handler(Self::handle_push);
         client.add_entity_request_handler(Self::handle_pull);
@@ -1742,6 +1762,25 @@ impl GitStore {
             .max_by_key(|(repo, _)| repo.read(cx).work_directory_abs_path.clone())
     }
 
+    /// Finds the submodule at `abs_path` along with the repository that declares it.
+    /// Initialized submodules are repositories of their own, so this looks past the
+    /// innermost repository.
+    pub fn submodule_for_abs_path(
+        &self,
+        abs_path: &Path,
+        cx: &App,
+    ) -> Option<(Entity<Repository>, Submodule)> {
+        self.repositories.values().find_map(|repo| {
+            let snapshot = repo.read(cx);
+            if snapshot.submodules.is_empty() {
+                return None;
+            }
+            let repo_path = snapshot.abs_path_to_repo_path(abs_path)?;
+            let submodule = snapshot.submodule_for_path(&repo_path)?.clone();
+            Some((repo.clone(), submodule))
+        })
+    }
+
     pub fn git_init(
         &self,
         path: Arc<Path>,
@@ -2342,10 +2381,11 @@ impl GitStore {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
         let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
         let branch_name = envelope.payload.branch_name;
+        let base_branch = envelope.payload.base_branch;
 
         repository_handle
             .update(&mut cx, |repository_handle, _| {
-                repository_handle.create_branch(branch_name, None)
+                repository_handle.create_branch(branch_name, base_branch)
             })
             .await??;
 
@@ -2426,6 +2466,182 @@ impl GitStore {
         Ok(proto::Ack {})
     }
 
+    async fn handle_cherry_pick(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitCherryPick>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let commits = envelope.payload.commits;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.cherry_pick(commits)
+            })
+            .await??;
+
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_cherry_pick_range(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitCherryPickRange>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let proto::GitCherryPickRange { first, last, .. } = envelope.payload;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.cherry_pick_range(first, last)
+            })
+            .await??;
+
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_bisect_start(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitBisectStart>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitBisectStepResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let proto::GitBisectStart { good, bad, .. } = envelope.payload;
+
+        let first_bad = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.bisect_start(good, bad)
+            })
+            .await??;
+
+        Ok(proto::GitBisectStepResponse {
+            first_bad: first_bad.map(|sha| sha.to_string()),
+        })
+    }
+
+    async fn handle_bisect_mark(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitBisectMark>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitBisectStepResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let term = match envelope.payload.term() {
+            proto::git_bisect_mark::BisectTerm::Good => BisectTerm::Good,
+            proto::git_bisect_mark::BisectTerm::Bad => BisectTerm::Bad,
+            proto::git_bisect_mark::BisectTerm::Skip => BisectTerm::Skip,
+        };
+
+        let first_bad = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.bisect_mark(term)
+            })
+            .await??;
+
+        Ok(proto::GitBisectStepResponse {
+            first_bad: first_bad.map(|sha| sha.to_string()),
+        })
+    }
+
+    async fn handle_bisect_run(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitBisectRun>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitBisectStepResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let command = envelope.payload.command;
+
+        let first_bad = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.bisect_run(command)
+            })
+            .await??;
+
+        Ok(proto::GitBisectStepResponse {
+            first_bad: first_bad.map(|sha| sha.to_string()),
+        })
+    }
+
+    async fn handle_bisect_reset(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitBisectReset>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.bisect_reset()
+            })
+            .await??;
+
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_revert(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRevert>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let commit = envelope.payload.commit;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.revert(commit)
+            })
+            .await??;
+
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_create_tag(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitCreateTag>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let proto::GitCreateTag {
+            name,
+            commit,
+            message,
+            ..
+        } = envelope.payload;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.create_tag(name, commit, message)
+            })
+            .await??;
+
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_delete_tag(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitDeleteTag>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let name = envelope.payload.name;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.delete_tag(name)
+            })
+            .await??;
+
+        Ok(proto::Ack {})
+    }
+
     async fn handle_remove_remote(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::GitRemoveRemote>,
@@ -2476,7 +2692,10 @@ impl GitStore {
 
         let commit_diff = repository_handle
             .update(&mut cx, |repository_handle, _| {
-                repository_handle.load_commit_diff(envelope.payload.commit)
+                match envelope.payload.base {
+                    Some(base) => repository_handle.compare_commits(base, envelope.payload.commit),
+                    None => repository_handle.load_commit_diff(envelope.payload.commit),
+                }
             })
             .await??;
         Ok(proto::LoadCommitDiffResponse {
@@ -3433,6 +3652,9 @@ impl RepositorySnapshot {
             remote_origin_url: None,
             remote_upstream_url: None,
             stash_entries: Default::default(),
+            rebase: None,
+            bisect: None,
+            submodules: Vec::new(),
             path_style,
         }
     }
@@ -3468,6 +3690,7 @@ impl RepositorySnapshot {
                 .collect(),
             remote_upstream_url: self.remote_upstream_url.clone(),
             remote_origin_url: self.remote_origin_url.clone(),
+            bisect: self.bisect.as_ref().map(bisect_progress_to_proto),
         }
     }
 
@@ -3539,6 +3762,7 @@ impl RepositorySnapshot {
                 .collect(),
             remote_upstream_url: self.remote_upstream_url.clone(),
             remote_origin_url: self.remote_origin_url.clone(),
+            bisect: self.bisect.as_ref().map(bisect_progress_to_proto),
         }
     }
 
@@ -3556,11 +3780,17 @@ impl RepositorySnapshot {
             .cloned()
     }
 
+    pub fn submodule_for_path(&self, path: &RepoPath) -> Option<&Submodule> {
+        self.submodules
+            .iter()
+            .find(|submodule| &submodule.path == path)
+    }
+
     pub fn abs_path_to_repo_path(&self, abs_path: &Path) -> Option<RepoPath> {
         Self::abs_path_to_repo_path_inner(&self.work_directory_abs_path, abs_path, self.path_style)
     }
 
-    fn repo_path_to_abs_path(&self, repo_path: &RepoPath) -> PathBuf {
+    pub fn repo_path_to_abs_path(&self, repo_path: &RepoPath) -> PathBuf {
         self.path_style
             .join(&self.work_directory_abs_path, repo_path.as_std_path())
             .unwrap()
@@ -3621,6 +3851,32 @@ pub fn proto_to_stash(entry: &proto::StashEntry) -> Result<StashEntry> {
     })
 }
 
+fn bisect_progress_to_proto(progress: &BisectProgress) -> proto::GitBisectProgress {
+    proto::GitBisectProgress {
+        bad: progress.bad.map(|oid| oid.to_string()),
+        good: progress.good.iter().map(ToString::to_string).collect(),
+        skipped: progress.skipped.iter().map(ToString::to_string).collect(),
+        remaining: progress.remaining.iter().map(ToString::to_string).collect(),
+    }
+}
+
+fn proto_to_bisect_progress(progress: &proto::GitBisectProgress) -> BisectProgress {
+    let oids = |shas: &[String]| -> Vec<Oid> {
+        shas.iter()
+            .filter_map(|sha| Oid::from_str(sha).log_err())
+            .collect()
+    };
+    BisectProgress {
+        bad: progress
+            .bad
+            .as_deref()
+            .and_then(|sha| Oid::from_str(sha).log_err()),
+        good: oids(&progress.good),
+        skipped: oids(&progress.skipped),
+        remaining: oids(&progress.remaining).into_iter().collect(),
+    }
+}
+
 impl MergeDetails {
     async fn load(
         backend: &Arc<dyn GitRepository>,
@@ -4270,12 +4526,30 @@ impl Repository {
     }
 
     pub fn load_commit_diff(&mut self, commit: String) -> oneshot::Receiver<Result<CommitDiff>> {
+        self.load_diff(None, commit)
+    }
+
+    /// Loads the changes between two commits, as if `head` were a commit on top of `base`.
+    pub fn compare_commits(
+        &mut self,
+        base: String,
+        head: String,
+    ) -> oneshot::Receiver<Result<CommitDiff>> {
+        self.load_diff(Some(base), head)
+    }
+
+    fn load_diff(
+        &mut self,
+        base: Option<String>,
+        commit: String,
+    ) -> oneshot::Receiver<Result<CommitDiff>> {
         let id = self.id;
         self.send_job(None, move |git_repo, cx| async move {
             match git_repo {
-                RepositoryState::Local(LocalRepositoryState { backend, .. }) => {
-                    backend.load_commit(commit, cx).await
-                }
+                RepositoryState::Local(LocalRepositoryState { backend, .. }) => match base {
+                    Some(base) => backend.compare_commits(base, commit, cx).await,
+                    None => backend.load_commit(commit, cx).await,
+                },
                 RepositoryState::Remote(RemoteRepositoryState {
                     client, project_id, ..
                 }) => {
@@ -4284,6 +4558,7 @@ impl Repository {
                             project_id: project_id.0,
                             repository_id: id.to_proto(),
                             commit,
+                            base,
                         })
                         .await?;
                     Ok(CommitDiff {
@@ -5696,6 +5971,7 @@ impl Repository {
                             project_id: project_id.0,
                             repository_id: id.to_proto(),
                             branch_name,
+                            base_branch,
                         })
                         .await?;
 
@@ -5753,6 +6029,149 @@ impl Repository {
         )
     }
 
+    pub fn cherry_pick(&mut self, commits: Vec<String>) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git cherry-pick {}", commits.join(" ")).into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState {
+                        backend,
+                        environment,
+                        ..
+                    }) => backend.cherry_pick(commits, environment).await,
+                    RepositoryState::Remote(RemoteRepositoryState { project_id, client }) => {
+                        client
+                            .request(proto::GitCherryPick {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                commits,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn cherry_pick_range(
+        &mut self,
+        first: String,
+        last: String,
+    ) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git cherry-pick {first}..{last}").into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState {
+                        backend,
+                        environment,
+                        ..
+                    }) => backend.cherry_pick_range(first, last, environment).await,
+                    RepositoryState::Remote(RemoteRepositoryState { project_id, client }) => {
+                        client
+                            .request(proto::GitCherryPickRange {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                first,
+                                last,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn revert(&mut self, commit: String) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git revert {commit}").into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState {
+                        backend,
+                        environment,
+                        ..
+                    }) => backend.revert(commit, environment).await,
+                    RepositoryState::Remote(RemoteRepositoryState { project_id, client }) => {
+                        client
+                            .request(proto::GitRevert {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                commit,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn create_tag(
+        &mut self,
+        name: String,
+        commit: String,
+        message: Option<String>,
+    ) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git tag {name} {commit}").into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState {
+                        backend,
+                        environment,
+                        ..
+                    }) => backend.create_tag(name, commit, message, environment).await,
+                    RepositoryState::Remote(RemoteRepositoryState { project_id, client }) => {
+                        client
+                            .request(proto::GitCreateTag {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                name,
+                                commit,
+                                message,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn delete_tag(&mut self, name: String) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git tag --delete {name}").into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(state) => state.backend.delete_tag(name).await,
+                    RepositoryState::Remote(RemoteRepositoryState { project_id, client }) => {
+                        client
+                            .request(proto::GitDeleteTag {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                name,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
     pub fn rename_branch(
         &mut self,
         branch: String,
@@ -5806,6 +6225,231 @@ impl Repository {
         })
     }
 
+    pub fn rebase_todo(&mut self, base: String) -> oneshot::Receiver<Result<Vec<RebaseTodoEntry>>> {
+        self.send_job(None, move |repo, _cx| async move {
+            match repo {
+                RepositoryState::Local(LocalRepositoryState { backend, .. }) => {
+                    backend.rebase_todo(base).await
+                }
+                RepositoryState::Remote(..) => {
+                    anyhow::bail!("Interactive rebase is not supported for collab yet")
+                }
+            }
+        })
+    }
+
+    pub fn rebase_interactive(
+        &mut self,
+        base: String,
+        entries: Vec<RebaseTodoEntry>,
+    ) -> oneshot::Receiver<Result<()>> {
+        self.send_job(
+            Some(format!("git rebase --interactive {base}").into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState {
+                        backend,
+                        environment,
+                        ..
+                    }) => backend.rebase_interactive(base, entries, environment).await,
+                    RepositoryState::Remote(..) => {
+                        anyhow::bail!("Interactive rebase is not supported for collab yet")
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn rebase_control(&mut self, control: RebaseControl) -> oneshot::Receiver<Result<()>> {
+        self.send_job(
+            Some(format!("git rebase {}", control.flag()).into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState {
+                        backend,
+                        environment,
+                        ..
+                    }) => backend.rebase_control(control, environment).await,
+                    RepositoryState::Remote(..) => {
+                        anyhow::bail!("Interactive rebase is not supported for collab yet")
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn bisect_start(
+        &mut self,
+        good: String,
+        bad: String,
+    ) -> oneshot::Receiver<Result<Option<SharedString>>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git bisect start {bad} {good}").into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState {
+                        backend,
+                        environment,
+                        ..
+                    }) => backend.bisect_start(good, bad, environment).await,
+                    RepositoryState::Remote(RemoteRepositoryState { project_id, client }) => {
+                        let response = client
+                            .request(proto::GitBisectStart {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                good,
+                                bad,
+                            })
+                            .await?;
+                        Ok(response.first_bad.map(SharedString::from))
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn bisect_mark(
+        &mut self,
+        term: BisectTerm,
+    ) -> oneshot::Receiver<Result<Option<SharedString>>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git bisect {}", term.keyword()).into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState {
+                        backend,
+                        environment,
+                        ..
+                    }) => backend.bisect_mark(term, environment).await,
+                    RepositoryState::Remote(RemoteRepositoryState { project_id, client }) => {
+                        let term = match term {
+                            BisectTerm::Good => proto::git_bisect_mark::BisectTerm::Good,
+                            BisectTerm::Bad => proto::git_bisect_mark::BisectTerm::Bad,
+                            BisectTerm::Skip => proto::git_bisect_mark::BisectTerm::Skip,
+                        };
+                        let response = client
+                            .request(proto::GitBisectMark {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                term: term.into(),
+                            })
+                            .await?;
+                        Ok(response.first_bad.map(SharedString::from))
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn bisect_run(
+        &mut self,
+        command: String,
+    ) -> oneshot::Receiver<Result<Option<SharedString>>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git bisect run {command}").into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState {
+                        backend,
+                        environment,
+                        ..
+                    }) => backend.bisect_run(command, environment).await,
+                    RepositoryState::Remote(RemoteRepositoryState { project_id, client }) => {
+                        let response = client
+                            .request(proto::GitBisectRun {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                command,
+                            })
+                            .await?;
+                        Ok(response.first_bad.map(SharedString::from))
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn bisect_reset(&mut self) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some("git bisect reset".into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState {
+                        backend,
+                        environment,
+                        ..
+                    }) => backend.bisect_reset(environment).await,
+                    RepositoryState::Remote(RemoteRepositoryState { project_id, client }) => {
+                        client
+                            .request(proto::GitBisectReset {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                            })
+                            .await?;
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn submodule_command(
+        &mut self,
+        command: SubmoduleCommand,
+    ) -> oneshot::Receiver<Result<()>> {
+        self.send_job(
+            Some(format!("git {}", command.args().join(" ")).into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState {
+                        backend,
+                        environment,
+                        ..
+                    }) => backend.submodule_command(command, environment).await,
+                    RepositoryState::Remote(..) => {
+                        anyhow::bail!("Submodules are not supported for collab yet")
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn verify_commit(
+        &mut self,
+        sha: String,
+    ) -> oneshot::Receiver<Result<Option<CommitSignature>>> {
+        self.send_job(None, move |repo, _cx| async move {
+            match repo {
+                RepositoryState::Local(LocalRepositoryState { backend, .. }) => {
+                    backend.verify_commit(sha).await
+                }
+                RepositoryState::Remote(..) => {
+                    anyhow::bail!("Verifying signatures is not supported for collab yet")
+                }
+            }
+        })
+    }
+
+    pub fn verify_commits(
+        &mut self,
+        shas: Vec<Oid>,
+    ) -> oneshot::Receiver<Result<HashMap<Oid, CommitSignature>>> {
+        self.send_job(None, move |repo, _cx| async move {
+            match repo {
+                RepositoryState::Local(LocalRepositoryState { backend, .. }) => {
+                    backend.verify_commits(shas).await
+                }
+                RepositoryState::Remote(..) => {
+                    anyhow::bail!("Verifying signatures is not supported for collab yet")
+                }
+            }
+        })
+    }
+
     pub fn checkpoint(&mut self) -> oneshot::Receiver<Result<GitRepositoryCheckpoint>> {
         self.send_job(None, |repo, _cx| async move {
             match repo {
@@ -5868,6 +6512,11 @@ impl Repository {
         self.snapshot.stash_entries = new_stash_entries;
         self.snapshot.remote_upstream_url = update.remote_upstream_url;
         self.snapshot.remote_origin_url = update.remote_origin_url;
+        let new_bisect = update.bisect.as_ref().map(proto_to_bisect_progress);
+        if self.snapshot.bisect != new_bisect {
+            cx.emit(RepositoryEvent::BranchChanged)
+        }
+        self.snapshot.bisect = new_bisect;
 
         let edits = update
             .removed_statuses
@@ -6621,6 +7270,42 @@ async fn compute_snapshot(
     let remote_origin_url = backend.remote_url("origin").await;
     let remote_upstream_url = backend.remote_url("upstream").await;
 
+    let rebase = backend.rebase_progress().await;
+    if rebase != prev_snapshot.rebase {
+        events.push(RepositoryEvent::RebaseChanged);
+    }
+
+    let bisect = backend.bisect_progress().await;
+    if bisect != prev_snapshot.bisect {
+        events.push(RepositoryEvent::BranchChanged);
+    }
+
+    // Listing submodules spawns several git processes, so only do it when something that could
+    // affect them changed: the checked-out commit, `.gitmodules`, or a known submodule's status.
+    let gitmodules_path: Arc<RelPath> = RelPath::unix(".gitmodules").unwrap().into();
+    let submodules_may_have_changed = prev_snapshot.scan_id == 0
+        || branch != prev_snapshot.branch
+        || head_commit != prev_snapshot.head_commit
+        || iter::once(gitmodules_path)
+            .chain(
+                prev_snapshot
+                    .submodules
+                    .iter()
+                    .map(|submodule| submodule.path.as_ref().clone()),
+            )
+            .any(|path| {
+                let key = PathKey(path);
+                statuses_by_path.get(&key, ()) != prev_snapshot.statuses_by_path.get(&key, ())
+            });
+    let submodules = if submodules_may_have_changed {
+        backend.submodules().await.log_err().unwrap_or_default()
+    } else {
+        prev_snapshot.submodules.clone()
+    };
+    if submodules != prev_snapshot.submodules {
+        events.push(RepositoryEvent::StatusesChanged);
+    }
+
     let snapshot = RepositorySnapshot {
         id,
         statuses_by_path,
@@ -6633,6 +7318,9 @@ async fn compute_snapshot(
         remote_origin_url,
         remote_upstream_url,
         stash_entries,
+        rebase,
+        bisect,
+        submodules,
     };
 
     Ok((snapshot, events))
@@ -186,6 +186,18 @@ pub trait ProjectItem: 'static {
     ) -> Option<Task<Result<Entity<Self>>>>
     where
         Self: Sized;
+    /// Starts opening the file at `path` as this kind of item after the kind that claimed it
+    /// failed to load it, e.g. because it's binary. Only consulted once loading has failed.
+    fn try_open_refused(
+        _project: &Entity<Project>,
+        _path: &ProjectPath,
+        _cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>>
+    where
+        Self: Sized,
+    {
+        None
+    }
     fn entry_id(&self, cx: &App) -> Option<ProjectEntryId>;
     fn project_path(&self, cx: &App) -> Option<ProjectPath>;
     fn is_dirty(&self) -> bool;
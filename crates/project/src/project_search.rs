@@ -327,6 +327,7 @@ impl Search {
                 };
 
                 let should_find_all_matches = !tx.is_closed();
+                let wait_for_parsing = query.is_structural();
 
                 let _executor = executor.clone();
                 let worker_pool = executor.spawn(async move {
@@ -365,6 +366,7 @@ impl Search {
                             grab_buffer_snapshot_rx,
                             find_all_matches_tx,
                             sorted_matches_tx,
+                            wait_for_parsing,
                             cx.clone(),
                         )
                         .boxed_local(),
@@ -533,10 +535,17 @@ impl Search {
             oneshot::Sender<(Entity<Buffer>, Vec<Range<language::Anchor>>)>,
         )>,
         results: Sender<oneshot::Receiver<(Entity<Buffer>, Vec<Range<language::Anchor>>)>>,
+        wait_for_parsing: bool,
         mut cx: AsyncApp,
     ) {
         _ = maybe!(async move {
             while let Ok(buffer) = rx.recv().await {
+                // Structural queries match against syntax trees, which buffers that were
+                // just opened for the search might not have yet.
+                if wait_for_parsing {
+                    let parsing_idle = buffer.read_with(&mut cx, |this, _| this.parsing_idle());
+                    parsing_idle.await;
+                }
                 let snapshot = buffer.read_with(&mut cx, |this, _| this.snapshot());
                 let (tx, rx) = oneshot::channel();
                 find_all_matches_tx.send((buffer, snapshot, tx)).await?;
@@ -18,6 +18,10 @@ use util::{
     rel_path::RelPath,
 };
 
+mod structural;
+
+pub use structural::StructuralPattern;
+
 #[derive(Debug)]
 pub enum SearchResult {
     Buffer {
@@ -77,6 +81,12 @@ pub enum SearchQuery {
         one_match_per_line: bool,
         inner: SearchInputs,
     },
+    Structural {
+        pattern: Arc<StructuralPattern>,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 static WORD_MATCH_TEST: LazyLock<Regex> = LazyLock::new(|| {
@@ -206,6 +216,36 @@ impl SearchQuery {
         })
     }
 
+    /// Create a structural query
+    ///
+    /// The query is either a code pattern with metavariables, such as `foo($A, $B)`, or a tree-sitter query,
+    /// and it is matched against the syntax trees of buffers. See [`SearchQuery::text`] for the meaning of
+    /// `match_full_paths`.
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        match_full_paths: bool,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(query.as_str())?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            match_full_paths,
+            buffers,
+        };
+        Ok(Self::Structural {
+            pattern: Arc::new(pattern),
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     /// Extracts case sensitivity settings from pattern items in the provided
     /// query and returns the same query, with the pattern items removed.
     ///
@@ -270,7 +310,16 @@ impl SearchQuery {
             message.files_to_exclude
         };
 
-        if message.regex {
+        if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                PathMatcher::new(files_to_include, path_style)?,
+                PathMatcher::new(files_to_exclude, path_style)?,
+                message.match_full_paths,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -305,6 +354,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -324,6 +377,7 @@ impl SearchQuery {
             files_to_include: files_to_include.clone().map(ToOwned::to_owned).collect(),
             files_to_exclude: files_to_exclude.clone().map(ToOwned::to_owned).collect(),
             match_full_paths: self.match_full_paths(),
+            structural: self.is_structural(),
             // Populate legacy fields for backwards compatibility
             files_to_include_legacy: files_to_include.join(","),
             files_to_exclude_legacy: files_to_exclude.join(","),
@@ -387,20 +441,44 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structural { pattern, .. } => {
+                // Files can't be parsed before they are opened, so only skip the
+                // ones that lack a word every match would contain.
+                let Some(literal) = pattern.literal() else {
+                    return Ok(true);
+                };
+                let mut text = String::new();
+                let mut bytes_read = 0;
+                while reader.read_line(&mut text)? > 0 {
+                    if text.contains(literal) {
+                        return Ok(true);
+                    }
+                    bytes_read += text.len();
+                    if bytes_read >= YIELD_THRESHOLD {
+                        bytes_read = 0;
+                        smol::future::yield_now().await;
+                    }
+                    text.clear();
+                }
+                Ok(false)
+            }
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
+    ///
+    /// Structural queries need the syntax tree around a match, see [`SearchQuery::structural_replacement`].
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
             SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
+            SearchQuery::Structural { .. } => None,
             SearchQuery::Regex {
                 regex, replacement, ..
             } => {
@@ -424,6 +502,23 @@ impl SearchQuery {
         }
     }
 
+    /// Replaces the structural match at `range` in `buffer` if replacement is set, substituting the text
+    /// captured by the metavariables the replacement refers to.
+    pub fn structural_replacement(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        match self {
+            SearchQuery::Structural {
+                pattern,
+                replacement,
+                ..
+            } => pattern.replacement(buffer, range, replacement.as_deref()?),
+            SearchQuery::Text { .. } | SearchQuery::Regex { .. } => None,
+        }
+    }
+
     pub async fn search(
         &self,
         buffer: &BufferSnapshot,
@@ -519,6 +614,13 @@ impl SearchQuery {
                     }
                 }
             }
+
+            Self::Structural { pattern, .. } => {
+                let range = range_offset..range_offset + rope.len();
+                for range in pattern.search(buffer, range) {
+                    matches.push(range.start - range_offset..range.end - range_offset);
+                }
+            }
         }
 
         matches
@@ -536,6 +638,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -543,6 +646,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
         }
     }
 
@@ -554,6 +658,9 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -561,6 +668,10 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -604,20 +715,22 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
         }
     }
 
     /// Whether this search should replace only one match per line, instead of
     /// all matches.
-    /// Returns `None` for text searches, as only regex searches support this
+    /// Returns `None` for text and structural searches, as only regex searches support this
     /// option.
     pub fn one_match_per_line(&self) -> Option<bool> {
         match self {
             Self::Regex {
                 one_match_per_line, ..
             } => Some(*one_match_per_line),
-            Self::Text { .. } => None,
+            Self::Text { .. } | Self::Structural { .. } => None,
         }
     }
 }
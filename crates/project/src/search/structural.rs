@@ -0,0 +1,515 @@
+//! Structural search matches patterns against the syntax trees of buffers
+//! rather than against their text.
+//!
+//! A pattern is either a snippet of code containing metavariables, such as
+//! `foo($A, $B)`, or a raw tree-sitter query, such as
+//! `(call_expression function: (identifier) @name) @match`.
+//!
+//! Code patterns are parsed with the grammar of each buffer's language and
+//! compared node by node, ignoring whitespace and comments. `$NAME` matches any
+//! single node, `$$$NAME` matches any number of sibling nodes, and `$_` and
+//! `$$$` match without binding a name. A metavariable that occurs more than
+//! once has to match the same text every time.
+//!
+//! Queries match the node captured as `@match`, or the nodes spanned by all of
+//! their captures if there is no such capture.
+//!
+//! Replacements refer to metavariables and captures by name, so replacing
+//! `foo($A, $B)` with `foo($B, $A)` swaps the arguments of every matched call
+//! while keeping their text.
+
+use collections::HashMap;
+use language::{BufferSnapshot, Language, LanguageName, with_parser, with_query_cursor};
+use parking_lot::Mutex;
+use std::{cmp::Reverse, fmt, ops::Range, sync::Arc};
+use tree_sitter::{Node, Query, QueryErrorKind, QueryMatch, StreamingIterator as _, Tree};
+
+/// Metavariables are replaced with identifiers carrying these prefixes before
+/// a code pattern is parsed, as `$` is not valid in most languages.
+const METAVARIABLE_PREFIX: &str = "__zed_var_";
+const MULTI_METAVARIABLE_PREFIX: &str = "__zed_vars_";
+
+/// A structural search pattern, compiled lazily for every language it is
+/// matched against.
+pub struct StructuralPattern {
+    source: Arc<str>,
+    compiled: Mutex<HashMap<LanguageName, Option<Arc<CompiledPattern>>>>,
+}
+
+impl fmt::Debug for StructuralPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("StructuralPattern")
+            .field(&self.source)
+            .finish()
+    }
+}
+
+impl StructuralPattern {
+    pub fn new(source: impl Into<Arc<str>>) -> anyhow::Result<Self> {
+        let source = source.into();
+        let substituted = substitute_metavariables(&source);
+        let trimmed = substituted.trim();
+        anyhow::ensure!(!trimmed.is_empty(), "Structural pattern is empty");
+        anyhow::ensure!(
+            metavariable_name(trimmed).is_none(),
+            "Structural pattern must contain code besides a metavariable"
+        );
+        Ok(Self {
+            source,
+            compiled: Mutex::default(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the longest word that every match of a code pattern contains,
+    /// which is used to skip files without having to parse them.
+    pub fn literal(&self) -> Option<&str> {
+        if looks_like_query(&self.source) {
+            return None;
+        }
+        let mut longest: Option<&str> = None;
+        let mut word_start = None;
+        for (ix, c) in self.source.char_indices().chain([(self.source.len(), ' ')]) {
+            if c.is_alphanumeric() || c == '_' {
+                word_start.get_or_insert(ix);
+                continue;
+            }
+            let Some(start) = word_start.take() else {
+                continue;
+            };
+            let is_metavariable = self.source[..start].ends_with('$');
+            if !is_metavariable && longest.is_none_or(|longest| longest.len() < ix - start) {
+                longest = Some(&self.source[start..ix]);
+            }
+        }
+        longest
+    }
+
+    /// Returns the non-overlapping ranges in `range` that match this pattern.
+    pub fn search(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<Range<usize>> {
+        let text = BufferText(buffer);
+        let mut matches = Vec::new();
+        for layer in buffer.syntax_layers() {
+            if let Some(compiled) = self.compile(layer.language) {
+                compiled.find_matches(layer.node(), text, &range, &mut matches);
+            }
+        }
+
+        matches.sort_by_key(|range| (range.start, Reverse(range.end)));
+        let mut last_end = 0;
+        matches.retain(|range| {
+            if range.start < last_end {
+                return false;
+            }
+            last_end = range.end;
+            true
+        });
+        matches
+    }
+
+    /// Renders `template` for the match at `range`, substituting the text of
+    /// the nodes bound to the metavariables or captures it refers to.
+    pub fn replacement(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+        template: &str,
+    ) -> Option<String> {
+        let text = BufferText(buffer);
+        buffer.syntax_layers().find_map(|layer| {
+            let compiled = self.compile(layer.language)?;
+            let bindings = compiled.bindings(layer.node(), text, &range)?;
+            Some(render_replacement(template, &bindings))
+        })
+    }
+
+    fn compile(&self, language: &Arc<Language>) -> Option<Arc<CompiledPattern>> {
+        self.compiled
+            .lock()
+            .entry(language.name())
+            .or_insert_with(|| CompiledPattern::new(&self.source, language).map(Arc::new))
+            .clone()
+    }
+}
+
+enum CompiledPattern {
+    Query {
+        query: Query,
+        match_capture: Option<u32>,
+    },
+    Code {
+        source: String,
+        tree: Tree,
+        root_range: Range<usize>,
+    },
+}
+
+impl CompiledPattern {
+    fn new(source: &str, language: &Language) -> Option<Self> {
+        let grammar = language.grammar()?;
+        if looks_like_query(source) {
+            match Query::new(&grammar.ts_language, source) {
+                Ok(query) => {
+                    let match_capture = query.capture_index_for_name("match");
+                    return (!query.capture_names().is_empty()).then_some(Self::Query {
+                        query,
+                        match_capture,
+                    });
+                }
+                // A syntactically valid query that doesn't fit this grammar was
+                // meant for another language.
+                Err(error) if !matches!(error.kind, QueryErrorKind::Syntax) => return None,
+                Err(_) => {}
+            }
+        }
+
+        let source = substitute_metavariables(source);
+        let start = source.len() - source.trim_start().len();
+        let end = source.trim_end().len();
+        // Expressions are usually not valid at the top level of a file, so retry
+        // with a statement terminator when the pattern doesn't parse on its own.
+        [source.clone(), format!("{source};")]
+            .into_iter()
+            .find_map(|source| {
+                let tree = with_parser(|parser| {
+                    parser.set_language(&grammar.ts_language).ok()?;
+                    parser.parse(&source, None)
+                })?;
+                let is_valid = tree
+                    .root_node()
+                    .named_descendant_for_byte_range(start, end)
+                    .is_some_and(|root| {
+                        root.byte_range() == (start..end)
+                            && !root.has_error()
+                            && metavariable_name(&source[start..end]).is_none()
+                    });
+                is_valid.then_some(Self::Code {
+                    source,
+                    tree,
+                    root_range: start..end,
+                })
+            })
+    }
+
+    fn find_matches(
+        &self,
+        root: Node,
+        text: BufferText,
+        range: &Range<usize>,
+        matches: &mut Vec<Range<usize>>,
+    ) {
+        match self {
+            Self::Query {
+                query,
+                match_capture,
+            } => with_query_cursor(|cursor| {
+                cursor.set_byte_range(range.clone());
+                let mut query_matches = cursor.matches(query, root, text);
+                while let Some(query_match) = query_matches.next() {
+                    if let Some(match_range) = query_match_range(query_match, *match_capture)
+                        && match_range.start >= range.start
+                        && match_range.end <= range.end
+                    {
+                        matches.push(match_range);
+                    }
+                }
+            }),
+            Self::Code { source, .. } => {
+                let pattern_root = self.pattern_root();
+                let mut cursor = root.walk();
+                loop {
+                    let node = cursor.node();
+                    let mut descend =
+                        node.end_byte() > range.start && node.start_byte() < range.end;
+                    if descend
+                        && node.kind_id() == pattern_root.kind_id()
+                        && node.start_byte() >= range.start
+                        && node.end_byte() <= range.end
+                        && Matcher::new(source, text).match_node(pattern_root, node)
+                    {
+                        matches.push(node.byte_range());
+                        descend = false;
+                    }
+                    if descend && cursor.goto_first_child() {
+                        continue;
+                    }
+                    while !cursor.goto_next_sibling() {
+                        if !cursor.goto_parent() {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn bindings(
+        &self,
+        root: Node,
+        text: BufferText,
+        range: &Range<usize>,
+    ) -> Option<HashMap<String, String>> {
+        match self {
+            Self::Query {
+                query,
+                match_capture,
+            } => with_query_cursor(|cursor| {
+                cursor.set_byte_range(range.clone());
+                let mut query_matches = cursor.matches(query, root, text);
+                while let Some(query_match) = query_matches.next() {
+                    if query_match_range(query_match, *match_capture).as_ref() != Some(range) {
+                        continue;
+                    }
+                    let mut bindings = HashMap::default();
+                    for capture in query_match.captures {
+                        let name = query.capture_names()[capture.index as usize];
+                        bindings
+                            .entry(name.to_string())
+                            .or_insert_with(|| text.string(capture.node.byte_range()));
+                    }
+                    return Some(bindings);
+                }
+                None
+            }),
+            Self::Code { source, .. } => {
+                let pattern_root = self.pattern_root();
+                let mut node = root.descendant_for_byte_range(range.start, range.end)?;
+                while node.byte_range() == *range {
+                    if node.kind_id() == pattern_root.kind_id() {
+                        let mut matcher = Matcher::new(source, text);
+                        if matcher.match_node(pattern_root, node) {
+                            return Some(
+                                matcher
+                                    .bindings
+                                    .into_iter()
+                                    .map(|(name, range)| (name.to_string(), text.string(range)))
+                                    .collect(),
+                            );
+                        }
+                    }
+                    node = node.parent()?;
+                }
+                None
+            }
+        }
+    }
+
+    fn pattern_root(&self) -> Node<'_> {
+        match self {
+            Self::Code {
+                tree, root_range, ..
+            } => tree
+                .root_node()
+                .named_descendant_for_byte_range(root_range.start, root_range.end)
+                .expect("pattern root was validated when compiling"),
+            Self::Query { .. } => unreachable!("queries have no pattern tree"),
+        }
+    }
+}
+
+/// Compares the nodes of a code pattern with the nodes of a buffer, recording
+/// the ranges of the text bound to each metavariable.
+struct Matcher<'a> {
+    pattern: &'a str,
+    text: BufferText<'a>,
+    bindings: HashMap<&'a str, Range<usize>>,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(pattern: &'a str, text: BufferText<'a>) -> Self {
+        Self {
+            pattern,
+            text,
+            bindings: HashMap::default(),
+        }
+    }
+
+    fn match_node(&mut self, pattern: Node, node: Node) -> bool {
+        let pattern_text = &self.pattern[pattern.byte_range()];
+        if let Some(name) = metavariable_name(pattern_text) {
+            return self.bind(name, node.byte_range());
+        }
+        if pattern.kind_id() != node.kind_id() {
+            return false;
+        }
+
+        let pattern_children = significant_children(pattern);
+        let children = significant_children(node);
+        if pattern_children.is_empty() && children.is_empty() {
+            return self.text.eq_str(node.byte_range(), pattern_text);
+        }
+        self.match_children(&pattern_children, &children)
+    }
+
+    fn match_children(&mut self, patterns: &[Node], nodes: &[Node]) -> bool {
+        let Some((pattern, remaining_patterns)) = patterns.split_first() else {
+            return nodes.is_empty();
+        };
+
+        let pattern_text = &self.pattern[pattern.byte_range()];
+        if let Some(name) = pattern_text.strip_prefix(MULTI_METAVARIABLE_PREFIX)
+            && metavariable_name(pattern_text).is_some()
+        {
+            for count in 0..=nodes.len() {
+                let bindings = self.bindings.clone();
+                let range = match &nodes[..count] {
+                    [] => 0..0,
+                    [first, .., last] => first.start_byte()..last.end_byte(),
+                    [only] => only.byte_range(),
+                };
+                if self.bind(name, range)
+                    && self.match_children(remaining_patterns, &nodes[count..])
+                {
+                    return true;
+                }
+                self.bindings = bindings;
+            }
+            return false;
+        }
+
+        let Some((node, remaining_nodes)) = nodes.split_first() else {
+            return false;
+        };
+        let bindings = self.bindings.clone();
+        if self.match_node(*pattern, *node)
+            && self.match_children(remaining_patterns, remaining_nodes)
+        {
+            return true;
+        }
+        self.bindings = bindings;
+        false
+    }
+
+    fn bind(&mut self, name: &'a str, range: Range<usize>) -> bool {
+        if name.is_empty() || name == "_" {
+            return true;
+        }
+        match self.bindings.get(name) {
+            Some(bound) => self.text.eq_ranges(bound.clone(), range),
+            None => {
+                self.bindings.insert(name, range);
+                true
+            }
+        }
+    }
+}
+
+/// Reads the text of nodes from a buffer's rope, so that matching doesn't copy
+/// the whole buffer.
+#[derive(Clone, Copy)]
+struct BufferText<'a>(&'a BufferSnapshot);
+
+impl BufferText<'_> {
+    fn string(&self, range: Range<usize>) -> String {
+        self.0.text_for_range(range).collect()
+    }
+
+    fn eq_str(&self, range: Range<usize>, text: &str) -> bool {
+        range.len() == text.len() && self.0.bytes_in_range(range).flatten().eq(text.as_bytes())
+    }
+
+    fn eq_ranges(&self, a: Range<usize>, b: Range<usize>) -> bool {
+        a.len() == b.len()
+            && self
+                .0
+                .bytes_in_range(a)
+                .flatten()
+                .eq(self.0.bytes_in_range(b).flatten())
+    }
+}
+
+impl<'a> tree_sitter::TextProvider<&'a [u8]> for BufferText<'a> {
+    type I = text::Bytes<'a>;
+
+    fn text(&mut self, node: Node) -> Self::I {
+        self.0.bytes_in_range(node.byte_range())
+    }
+}
+
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra() && !child.is_missing())
+        .collect()
+}
+
+fn query_match_range(query_match: &QueryMatch, match_capture: Option<u32>) -> Option<Range<usize>> {
+    let mut captures = query_match
+        .captures
+        .iter()
+        .filter(|capture| match_capture.is_none_or(|ix| capture.index == ix));
+    let first = captures.next()?.node.byte_range();
+    Some(captures.fold(first, |range, capture| {
+        range.start.min(capture.node.start_byte())..range.end.max(capture.node.end_byte())
+    }))
+}
+
+fn looks_like_query(source: &str) -> bool {
+    source.trim_start().starts_with(['(', '['])
+}
+
+/// Returns the name of the metavariable that `text` consists of, if any.
+fn metavariable_name(text: &str) -> Option<&str> {
+    let name = text
+        .strip_prefix(MULTI_METAVARIABLE_PREFIX)
+        .or_else(|| text.strip_prefix(METAVARIABLE_PREFIX))?;
+    name.chars()
+        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        .then_some(name)
+}
+
+/// Replaces `$NAME` and `$$$NAME` in a code pattern with identifiers that
+/// the language's grammar can parse.
+fn substitute_metavariables(source: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(dollar) = rest.find('$') {
+        result.push_str(&rest[..dollar]);
+        let (prefix, after) = match rest[dollar..].strip_prefix("$$$") {
+            Some(after) => (MULTI_METAVARIABLE_PREFIX, after),
+            None => (METAVARIABLE_PREFIX, &rest[dollar + 1..]),
+        };
+        let name_len = after
+            .find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+            .unwrap_or(after.len());
+        if name_len > 0 || prefix == MULTI_METAVARIABLE_PREFIX {
+            result.push_str(prefix);
+            result.push_str(&after[..name_len]);
+            rest = &after[name_len..];
+        } else {
+            result.push('$');
+            rest = &rest[dollar + 1..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Substitutes `$NAME` and `$$$NAME` in a replacement with the text bound to
+/// `NAME`, leaving references to unknown names as they are.
+fn render_replacement(template: &str, bindings: &HashMap<String, String>) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(dollar) = rest.find('$') {
+        result.push_str(&rest[..dollar]);
+        let after = rest[dollar..].trim_start_matches('$');
+        let dollars = &rest[dollar..rest.len() - after.len()];
+        let name_len = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        let name = &after[..name_len];
+        match bindings.get(name) {
+            Some(text) if dollars.len() == 1 || dollars.len() == 3 => result.push_str(text),
+            _ => {
+                result.push_str(dollars);
+                result.push_str(name);
+            }
+        }
+        rest = &after[name_len..];
+    }
+    result.push_str(rest);
+    result
+}
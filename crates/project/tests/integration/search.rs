@@ -154,3 +154,149 @@ async fn test_multiline_regex(cx: &mut gpui::TestAppContext) {
     let results = search_query.search(&snapshot, None).await;
     assert_eq!(results, vec![0..6, 12..18]);
 }
+
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    use language::{Buffer, rust_lang};
+
+    let text = "fn main() {\n    foo(1, bar(2));\n    foo(a, /* b */ b);\n    baz(foo);\n}\n";
+    let snapshot = cx
+        .update(|app| Buffer::build_snapshot(Rope::from(text), Some(rust_lang()), None, app))
+        .await;
+    let structural_query = |query: &str, replacement: &str| {
+        SearchQuery::structural(
+            query,
+            false,
+            Default::default(),
+            Default::default(),
+            false,
+            None,
+        )
+        .expect("Should be able to create a structural SearchQuery")
+        .with_replacement(replacement.to_string())
+    };
+
+    for (query, replacement, expected) in [
+        (
+            "foo($A, $B)",
+            "foo($B, $A)",
+            vec![
+                ("foo(1, bar(2))", "foo(bar(2), 1)"),
+                ("foo(a, /* b */ b)", "foo(b, a)"),
+            ],
+        ),
+        (
+            "foo($$$ARGS)",
+            "qux($$$ARGS)",
+            vec![
+                ("foo(1, bar(2))", "qux(1, bar(2))"),
+                ("foo(a, /* b */ b)", "qux(a, /* b */ b)"),
+            ],
+        ),
+        (
+            r#"(call_expression function: (identifier) @name (#eq? @name "baz")) @match"#,
+            "$name()",
+            vec![("baz(foo)", "baz()")],
+        ),
+    ] {
+        let search_query = structural_query(query, replacement);
+        let matches = search_query
+            .search(&snapshot, None)
+            .await
+            .into_iter()
+            .map(|range| {
+                (
+                    snapshot.text_for_range(range.clone()).collect::<String>(),
+                    search_query
+                        .structural_replacement(&snapshot, range)
+                        .expect("Every match should have a replacement"),
+                )
+            })
+            .collect::<Vec<_>>();
+        let expected = expected
+            .into_iter()
+            .map(|(text, replacement)| (text.to_string(), replacement.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(matches, expected, "Unexpected matches for {query}");
+    }
+
+    assert!(
+        SearchQuery::structural(
+            "$A",
+            false,
+            Default::default(),
+            Default::default(),
+            false,
+            None
+        )
+        .is_err(),
+        "A pattern consisting of a single metavariable should be rejected"
+    );
+}
+
+#[gpui::test]
+async fn test_structural_search_metavariables(cx: &mut gpui::TestAppContext) {
+    use language::{Buffer, rust_lang};
+    use project::search::StructuralPattern;
+
+    let text = "fn main() {\n    foo(x, y, b);\n    bar(\"$x\", 1);\n    let _ = x == x;\n    let _ = x == y;\n}\n";
+    let snapshot = cx
+        .update(|app| Buffer::build_snapshot(Rope::from(text), Some(rust_lang()), None, app))
+        .await;
+
+    for (query, replacement, expected) in [
+        // A metavariable that occurs twice has to match the same text both times.
+        ("$A == $A", "$A.eq(&$A)", vec![("x == x", "x.eq(&x)")]),
+        // `$$$ARGS` takes as many arguments as it needs for the rest of the pattern to match.
+        (
+            "foo($$$ARGS, b)",
+            "foo(b, $$$ARGS)",
+            vec![("foo(x, y, b)", "foo(b, x, y)")],
+        ),
+        // Dollar signs that don't start a known metavariable are kept as they are.
+        (
+            r#"bar("$x", $A)"#,
+            "$A $B $$A $x",
+            vec![(r#"bar("$x", 1)"#, "1 $B $$A $x")],
+        ),
+    ] {
+        let search_query = SearchQuery::structural(
+            query,
+            false,
+            Default::default(),
+            Default::default(),
+            false,
+            None,
+        )
+        .expect("Should be able to create a structural SearchQuery")
+        .with_replacement(replacement.to_string());
+        let matches = search_query
+            .search(&snapshot, None)
+            .await
+            .into_iter()
+            .map(|range| {
+                (
+                    snapshot.text_for_range(range.clone()).collect::<String>(),
+                    search_query
+                        .structural_replacement(&snapshot, range)
+                        .expect("Every match should have a replacement"),
+                )
+            })
+            .collect::<Vec<_>>();
+        let expected = expected
+            .into_iter()
+            .map(|(text, replacement)| (text.to_string(), replacement.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(matches, expected, "Unexpected matches for {query}");
+    }
+
+    let literal = |pattern: &str| {
+        StructuralPattern::new(pattern)
+            .unwrap()
+            .literal()
+            .map(str::to_string)
+    };
+    assert_eq!(literal("foo($A, bar_baz)").as_deref(), Some("bar_baz"));
+    assert_eq!(literal("$$$ARGS.unwrap()").as_deref(), Some("unwrap"));
+    assert_eq!(literal("(identifier) @name"), None);
+}
@@ -144,6 +144,8 @@ pub struct ProjectPanel {
     pending_serialization: Task<Option<()>>,
     diagnostics: HashMap<(WorktreeId, Arc<RelPath>), DiagnosticSeverity>,
     diagnostic_summary_update: Task<()>,
+    /// Submodule tooltips keyed by the submodule's absolute path, rebuilt when repositories change.
+    submodule_statuses: HashMap<PathBuf, SharedString>,
     // We keep track of the mouse down state on entries so we don't flash the UI
     // in case a user clicks to open a file.
     mouse_down: bool,
@@ -627,6 +629,7 @@ impl ProjectPanel {
                     GitStoreEvent::RepositoryUpdated(_, RepositoryEvent::StatusesChanged, _)
                     | GitStoreEvent::RepositoryAdded
                     | GitStoreEvent::RepositoryRemoved(_) => {
+                        this.update_submodule_statuses(cx);
                         this.update_visible_entries(None, false, false, window, cx);
                         cx.notify();
                     }
@@ -836,6 +839,7 @@ impl ProjectPanel {
                 pending_serialization: Task::ready(None),
                 diagnostics: Default::default(),
                 diagnostic_summary_update: Task::ready(()),
+                submodule_statuses: HashMap::default(),
                 scroll_handle,
                 mouse_down: false,
                 hover_expand_task: None,
@@ -854,6 +858,7 @@ impl ProjectPanel {
                 },
                 update_visible_entries_task: Default::default(),
             };
+            this.update_submodule_statuses(cx);
             this.update_visible_entries(None, false, false, window, cx);
 
             this
@@ -3755,6 +3760,31 @@ impl ProjectPanel {
         }
     }
 
+    fn update_submodule_statuses(&mut self, cx: &App) {
+        let git_store = self.project.read(cx).git_store().read(cx);
+        self.submodule_statuses = git_store
+            .repositories()
+            .values()
+            .flat_map(|repo| {
+                let snapshot = repo.read(cx);
+                snapshot
+                    .submodules
+                    .iter()
+                    .map(|submodule| {
+                        let status = format!(
+                            "Submodule: {}",
+                            submodule.describe().as_deref().unwrap_or("up to date")
+                        );
+                        (
+                            snapshot.repo_path_to_abs_path(&submodule.path),
+                            SharedString::from(status),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+
     fn update_visible_entries(
         &mut self,
         new_selected_entry: Option<(WorktreeId, ProjectEntryId)>,
@@ -5035,6 +5065,16 @@ impl ProjectPanel {
 
         let depth = details.depth;
         let worktree_id = details.worktree_id;
+        let submodule_status =
+            if settings.git_status && kind.is_dir() && !self.submodule_statuses.is_empty() {
+                maybe!({
+                    let worktree = self.project.read(cx).worktree_for_id(worktree_id, cx)?;
+                    let abs_path = worktree.read(cx).absolutize(&path);
+                    self.submodule_statuses.get(&abs_path).cloned()
+                })
+            } else {
+                None
+            };
         let dragged_selection = DraggedSelection {
             active_selection: SelectedEntry {
                 worktree_id: selection.worktree_id,
@@ -5476,6 +5516,20 @@ impl ProjectPanel {
                                 .into_any_element(),
                         )
                     })
+                    .when_some(submodule_status, |this, status| {
+                        this.end_slot::<AnyElement>(
+                            div()
+                                .id("submodule_icon")
+                                .pr_3()
+                                .tooltip(Tooltip::text(status))
+                                .child(
+                                    Icon::new(IconName::GitBranch)
+                                        .size(IconSize::Indicator)
+                                        .color(filename_text_color),
+                                )
+                                .into_any_element(),
+                        )
+                    })
                     .child(if let Some(icon) = &icon {
                         if let Some((_, decoration_color)) =
                             entry_diagnostic_aware_icon_decoration_and_color(diagnostic_severity)
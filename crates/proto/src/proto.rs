@@ -316,6 +316,16 @@ messages!(
     (GitCreateBranch, Background),
     (GitChangeBranch, Background),
     (GitRenameBranch, Background),
+    (GitCherryPick, Background),
+    (GitCherryPickRange, Background),
+    (GitBisectStart, Background),
+    (GitBisectMark, Background),
+    (GitBisectRun, Background),
+    (GitBisectReset, Background),
+    (GitBisectStepResponse, Background),
+    (GitRevert, Background),
+    (GitCreateTag, Background),
+    (GitDeleteTag, Background),
     (TrustWorktrees, Background),
     (RestrictWorktrees, Background),
     (CheckForPushedCommits, Background),
@@ -359,6 +369,11 @@ messages!(
     (GetSharedAgentThreadResponse, Foreground),
     (FindSearchCandidatesChunk, Background),
     (FindSearchCandidatesCancelled, Background),
+    (UpdateSharedTerminal, Foreground),
+    (UnshareTerminal, Foreground),
+    (GetSharedTerminals, Foreground),
+    (GetSharedTerminalsResponse, Foreground),
+    (SharedTerminalInput, Foreground),
 );
 
 request_messages!(
@@ -532,6 +547,15 @@ request_messages!(
     (GitCreateBranch, Ack),
     (GitChangeBranch, Ack),
     (GitRenameBranch, Ack),
+    (GitCherryPick, Ack),
+    (GitCherryPickRange, Ack),
+    (GitBisectStart, GitBisectStepResponse),
+    (GitBisectMark, GitBisectStepResponse),
+    (GitBisectRun, GitBisectStepResponse),
+    (GitBisectReset, Ack),
+    (GitRevert, Ack),
+    (GitCreateTag, Ack),
+    (GitDeleteTag, Ack),
     (CheckForPushedCommits, CheckForPushedCommitsResponse),
     (GitDiff, GitDiffResponse),
     (GitInit, Ack),
@@ -555,6 +579,8 @@ request_messages!(
     (TrustWorktrees, Ack),
     (RestrictWorktrees, Ack),
     (FindSearchCandidatesChunk, Ack),
+    (GetSharedTerminals, GetSharedTerminalsResponse),
+    (SharedTerminalInput, Ack),
 );
 
 lsp_messages!(
@@ -712,6 +738,15 @@ entity_messages!(
     GitChangeBranch,
     GitRenameBranch,
     GitCreateBranch,
+    GitCherryPick,
+    GitCherryPickRange,
+    GitBisectStart,
+    GitBisectMark,
+    GitBisectRun,
+    GitBisectReset,
+    GitRevert,
+    GitCreateTag,
+    GitDeleteTag,
     GitCreateRemote,
     GitRemoveRemote,
     CheckForPushedCommits,
@@ -740,7 +775,11 @@ entity_messages!(
     RestrictWorktrees,
     FindSearchCandidatesChunk,
     FindSearchCandidatesCancelled,
-    DownloadFileByPath
+    DownloadFileByPath,
+    UpdateSharedTerminal,
+    UnshareTerminal,
+    GetSharedTerminals,
+    SharedTerminalInput
 );
 
 entity_messages!(
@@ -0,0 +1,665 @@
+//! Runs a command on every session in a group and shows how each device's output differs
+//! from the first one's, e.g. to spot configuration drift across routers.
+
+use std::{
+    any::{Any, TypeId},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use buffer_diff::BufferDiff;
+use editor::{Editor, EditorEvent, ExcerptRange, MultiBuffer};
+use futures::{FutureExt as _, future::join_all};
+use gpui::{
+    AnyElement, App, AppContext as _, AsyncApp, Context, DismissEvent, Entity, EventEmitter,
+    FocusHandle, Focusable, IntoElement, ParentElement, Render, Styled, Task, WeakEntity, Window,
+};
+use itertools::Itertools as _;
+use language::{Buffer, Capability, DiskState, Point};
+use project::{Project, WorktreeId};
+use regex::Regex;
+use terminal::{
+    SessionConfig, SessionNode, SessionStoreEntity,
+    remote_command::{
+        DEFAULT_PROMPT_REGEX, connect_to_session, wait_for_completion, wait_until_at_prompt,
+    },
+};
+use ui::{
+    Button, ButtonStyle, Color, Icon, IconName, Label, LabelSize, h_flex, prelude::*, v_flex,
+};
+use util::{paths::PathStyle, rel_path::RelPath};
+use uuid::Uuid;
+use workspace::{
+    DetachAndPromptErr as _, Item, ItemNavHistory, ModalView, Toast, Workspace,
+    item::{ItemEvent, TabContentParams},
+    notifications::NotificationId,
+    searchable::SearchableItemHandle,
+};
+
+/// How long to wait for the first prompt after connecting, and for each command to finish.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Asks for the command to run on every session in a group, and for the lines to leave out
+/// of the comparison.
+pub struct CompareOutputModal {
+    group_id: Uuid,
+    workspace: WeakEntity<Workspace>,
+    commands_editor: Entity<Editor>,
+    ignore_editor: Entity<Editor>,
+    error: Option<SharedString>,
+}
+
+impl CompareOutputModal {
+    pub fn new(
+        group_id: Uuid,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let commands_editor = cx.new(|cx| {
+            let mut editor = Editor::auto_height(1, 4, window, cx);
+            editor.set_placeholder_text("show running-config", window, cx);
+            editor
+        });
+        let ignore_editor = cx.new(|cx| {
+            let mut editor = Editor::auto_height(1, 4, window, cx);
+            editor.set_placeholder_text("^! Last configuration change", window, cx);
+            editor
+        });
+        window.focus(&commands_editor.focus_handle(cx), cx);
+
+        Self {
+            group_id,
+            workspace,
+            commands_editor,
+            ignore_editor,
+            error: None,
+        }
+    }
+
+    fn group(&self, cx: &App) -> Option<(String, Vec<SessionConfig>)> {
+        match SessionStoreEntity::global(cx)
+            .read(cx)
+            .store()
+            .find_node(self.group_id)?
+        {
+            SessionNode::Group(group) => Some((
+                group.name.clone(),
+                group.sessions().into_iter().cloned().collect(),
+            )),
+            SessionNode::Session(_) => None,
+        }
+    }
+
+    fn compare(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let commands = non_empty_lines(&self.commands_editor.read(cx).text(cx));
+        if commands.is_empty() {
+            return;
+        }
+        let ignore_patterns = match non_empty_lines(&self.ignore_editor.read(cx).text(cx))
+            .iter()
+            .map(|pattern| Regex::new(pattern))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(patterns) => patterns,
+            Err(error) => {
+                self.error = Some(error.to_string().into());
+                cx.notify();
+                return;
+            }
+        };
+        let Some((group_name, sessions)) = self.group(cx) else {
+            cx.emit(DismissEvent);
+            return;
+        };
+        if sessions.len() < 2 {
+            self.error = Some(format!("\"{group_name}\" needs at least two sessions").into());
+            cx.notify();
+            return;
+        }
+
+        if let Some(workspace) = self.workspace.upgrade() {
+            workspace.update(cx, |workspace, cx| {
+                compare_output(
+                    workspace,
+                    group_name,
+                    sessions,
+                    commands,
+                    ignore_patterns,
+                    window,
+                    cx,
+                );
+            });
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn cancel(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+}
+
+impl ModalView for CompareOutputModal {}
+
+impl EventEmitter<DismissEvent> for CompareOutputModal {}
+
+impl Focusable for CompareOutputModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.commands_editor.focus_handle(cx)
+    }
+}
+
+impl Render for CompareOutputModal {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        let border_color = theme.colors().border;
+        let border_variant_color = theme.colors().border_variant;
+        let (group_name, session_count) = self
+            .group(cx)
+            .map(|(name, sessions)| (name, sessions.len()))
+            .unwrap_or_default();
+
+        let field = |label: &'static str, editor: Entity<Editor>| {
+            v_flex()
+                .w_full()
+                .gap_1()
+                .child(Label::new(label).size(LabelSize::Small))
+                .child(
+                    div()
+                        .w_full()
+                        .border_1()
+                        .border_color(border_color)
+                        .rounded_sm()
+                        .px_1()
+                        .py_px()
+                        .child(editor),
+                )
+        };
+
+        v_flex()
+            .key_context("CompareOutputModal")
+            .on_action(cx.listener(|this, _: &menu::Cancel, window, cx| {
+                this.cancel(window, cx);
+            }))
+            .elevation_3(cx)
+            .w_96()
+            .overflow_hidden()
+            .child(
+                h_flex()
+                    .w_full()
+                    .p_2()
+                    .border_b_1()
+                    .border_color(border_variant_color)
+                    .child(Label::new(format!(
+                        "Compare Command Output Across \"{group_name}\""
+                    ))),
+            )
+            .child(
+                v_flex()
+                    .w_full()
+                    .p_2()
+                    .gap_2()
+                    .child(field("Commands", self.commands_editor.clone()))
+                    .child(field("Ignore Lines Matching", self.ignore_editor.clone()))
+                    .child(
+                        Label::new(format!(
+                            "Runs each command in turn on the {session_count} sessions in the \
+                             group and compares the output of the last one with the first \
+                             session's. Put one command and one regex per line, e.g. turn off \
+                             paging before showing the configuration."
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    )
+                    .when_some(self.error.clone(), |this, error| {
+                        this.child(Label::new(error).size(LabelSize::Small).color(Color::Error))
+                    }),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .p_2()
+                    .gap_2()
+                    .justify_end()
+                    .border_t_1()
+                    .border_color(border_variant_color)
+                    .child(
+                        Button::new("cancel", "Cancel")
+                            .style(ButtonStyle::Subtle)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.cancel(window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("compare", "Compare")
+                            .style(ButtonStyle::Filled)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.compare(window, cx);
+                            })),
+                    ),
+            )
+    }
+}
+
+/// Connects to every session, runs `commands` on each and opens an [`OutputComparison`] of
+/// the output of the last command. Sessions that fail are reported once the rest are shown.
+fn compare_output(
+    workspace: &mut Workspace,
+    group_name: String,
+    sessions: Vec<SessionConfig>,
+    commands: Vec<String>,
+    ignore_patterns: Vec<Regex>,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    struct RunningToast;
+
+    let command = commands.last().cloned().unwrap_or_default();
+    let window_id = window.window_handle().window_id().as_u64();
+    let captures = sessions
+        .iter()
+        .map(|session| {
+            let connection = connect_to_session(session, window_id, cx);
+            let capture = capture_output(connection, commands.clone(), cx.to_async());
+            let name = session.name.clone();
+            let command = command.clone();
+            async move {
+                let output = capture
+                    .await
+                    .with_context(|| format!("running `{command}` on \"{name}\""));
+                (name, output)
+            }
+        })
+        .collect::<Vec<_>>();
+    let project = workspace.project().clone();
+    let toast_id = NotificationId::unique::<RunningToast>();
+    workspace.show_toast(
+        Toast::new(
+            toast_id.clone(),
+            format!("Running `{command}` on {} sessions…", sessions.len()),
+        ),
+        cx,
+    );
+
+    cx.spawn_in(window, async move |workspace, cx| {
+        let mut outputs = Vec::new();
+        let mut errors = Vec::new();
+        for (name, output) in join_all(captures).await {
+            match output {
+                Ok(output) => outputs.push((name, filter_output(&output, &ignore_patterns))),
+                Err(error) => errors.push(error),
+            }
+        }
+        workspace.update(cx, |workspace, cx| workspace.dismiss_toast(&toast_id, cx))?;
+
+        if outputs.len() >= 2 {
+            let title = format!("`{command}` across {group_name}");
+            let tooltip = format!("{title}, compared with {}", outputs[0].0);
+            let multibuffer = build_multibuffer(outputs, cx).await;
+            workspace.update_in(cx, |workspace, window, cx| {
+                let comparison = cx.new(|cx| {
+                    OutputComparison::new(
+                        multibuffer,
+                        project,
+                        title.into(),
+                        tooltip.into(),
+                        window,
+                        cx,
+                    )
+                });
+                workspace.add_item_to_active_pane(Box::new(comparison), None, true, window, cx);
+            })?;
+        } else if errors.is_empty() {
+            return Err(anyhow!("only one session returned any output"));
+        }
+
+        if !errors.is_empty() {
+            return Err(anyhow!(
+                "{}",
+                errors.iter().map(|error| format!("{error:#}")).join("\n")
+            ));
+        }
+        Ok(())
+    })
+    .detach_and_prompt_err("Failed to compare command output", window, cx, |_, _, _| {
+        None
+    });
+}
+
+/// Types `commands` into the terminal one at a time and returns the output of the last.
+async fn capture_output(
+    connection: Task<Result<terminal::TerminalBuilder>>,
+    commands: Vec<String>,
+    mut cx: AsyncApp,
+) -> Result<String> {
+    let prompt = Regex::new(DEFAULT_PROMPT_REGEX)?;
+    let builder = connection.await?;
+    let terminal = cx.new(|cx| builder.subscribe(cx));
+
+    let at_prompt = wait_until_at_prompt(terminal.clone(), prompt.clone(), cx.clone());
+    futures::select! {
+        _ = at_prompt.fuse() => {},
+        _ = cx.background_executor().timer(COMMAND_TIMEOUT).fuse() => {
+            anyhow::bail!(
+                "no prompt appeared within {} seconds",
+                COMMAND_TIMEOUT.as_secs()
+            );
+        }
+    }
+
+    let mut output = String::new();
+    for command in commands {
+        let sent_line = terminal.update(&mut cx, |terminal, _| {
+            let line = terminal.cursor_line();
+            terminal.input(format!("{command}\r").into_bytes());
+            line
+        });
+        let finished = wait_for_completion(terminal.clone(), sent_line, prompt.clone(), cx.clone());
+        let completion = futures::select! {
+            completion = finished.fuse() => completion,
+            _ = cx.background_executor().timer(COMMAND_TIMEOUT).fuse() => {
+                anyhow::bail!(
+                    "`{command}` didn't finish within {} seconds",
+                    COMMAND_TIMEOUT.as_secs()
+                );
+            }
+        };
+        output = terminal.read_with(&cx, |terminal, _| {
+            // Output cut off at the top would be compared as if it were complete.
+            anyhow::ensure!(
+                terminal.topmost_line() <= sent_line + 1,
+                "the output of `{command}` exceeded the scrollback"
+            );
+            Ok(terminal.text_in_lines(sent_line + 1..=completion.last_output_line))
+        })?;
+    }
+    Ok(output)
+}
+
+fn non_empty_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// Drops the lines matching any of `ignore_patterns`, and trailing whitespace, which
+/// terminals pad lines with.
+fn filter_output(output: &str, ignore_patterns: &[Regex]) -> String {
+    let mut filtered = String::new();
+    for line in output.lines().map(str::trim_end) {
+        if ignore_patterns.iter().any(|pattern| pattern.is_match(line)) {
+            continue;
+        }
+        filtered.push_str(line);
+        filtered.push('\n');
+    }
+    filtered
+}
+
+/// The output of one command on several devices, with the differences from the first
+/// device's output shown as diff hunks.
+pub struct OutputComparison {
+    editor: Entity<Editor>,
+    title: SharedString,
+    tooltip: SharedString,
+}
+
+impl OutputComparison {
+    fn new(
+        multibuffer: Entity<MultiBuffer>,
+        project: Entity<Project>,
+        title: SharedString,
+        tooltip: SharedString,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, Some(project), window, cx);
+            editor.start_temporary_diff_override();
+            editor.disable_diagnostics(cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor.set_render_diff_hunk_controls(
+                Arc::new(|_, _, _, _, _, _, _, _| gpui::Empty.into_any_element()),
+                cx,
+            );
+            editor.set_read_only(true);
+            editor
+        });
+        Self {
+            editor,
+            title,
+            tooltip,
+        }
+    }
+}
+
+/// One read-only excerpt per device, each after the first diffed against the first.
+async fn build_multibuffer(
+    outputs: Vec<(String, String)>,
+    cx: &mut AsyncApp,
+) -> Entity<MultiBuffer> {
+    let reference = outputs
+        .first()
+        .map(|(_, text)| Arc::<str>::from(text.as_str()));
+    let mut buffers = Vec::new();
+    for (ix, (name, text)) in outputs.into_iter().enumerate() {
+        let file = Arc::new(DeviceOutput::new(ix, name));
+        let buffer = cx.new(|cx| {
+            let mut buffer = Buffer::local(text, cx);
+            buffer.file_updated(file, cx);
+            buffer.set_capability(Capability::ReadOnly, cx);
+            buffer
+        });
+        let diff = match reference.clone().filter(|_| ix > 0) {
+            Some(base_text) => Some(build_diff(&buffer, base_text, cx).await),
+            None => None,
+        };
+        buffers.push((buffer, diff));
+    }
+
+    cx.new(|cx| {
+        let mut multibuffer = MultiBuffer::new(Capability::ReadOnly);
+        for (buffer, diff) in buffers {
+            let max_point = buffer.read(cx).max_point();
+            multibuffer.push_excerpts(buffer, [ExcerptRange::new(Point::zero()..max_point)], cx);
+            if let Some(diff) = diff {
+                multibuffer.add_diff(diff, cx);
+            }
+        }
+        multibuffer
+    })
+}
+
+async fn build_diff(
+    buffer: &Entity<Buffer>,
+    base_text: Arc<str>,
+    cx: &mut AsyncApp,
+) -> Entity<BufferDiff> {
+    let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+    let diff = cx.new(|cx| BufferDiff::new(&snapshot.text, cx));
+    let update = diff
+        .update(cx, |diff, cx| {
+            diff.update_diff(snapshot.text.clone(), Some(base_text), Some(true), None, cx)
+        })
+        .await;
+    diff.update(cx, |diff, cx| diff.set_snapshot(update, &snapshot.text, cx))
+        .await;
+    diff
+}
+
+/// Stands in for a file so each excerpt's header shows the device the output came from.
+struct DeviceOutput {
+    path: Arc<RelPath>,
+    name: String,
+}
+
+impl DeviceOutput {
+    fn new(ix: usize, name: String) -> Self {
+        let path = RelPath::unix(&name.replace('/', "-"))
+            .map(RelPath::into_arc)
+            .unwrap_or_else(|_| RelPath::unix(&format!("session-{ix}")).unwrap().into_arc());
+        Self { path, name }
+    }
+}
+
+impl language::File for DeviceOutput {
+    fn as_local(&self) -> Option<&dyn language::LocalFile> {
+        None
+    }
+
+    fn disk_state(&self) -> DiskState {
+        DiskState::Historic { was_deleted: false }
+    }
+
+    fn path_style(&self, _: &App) -> PathStyle {
+        PathStyle::local()
+    }
+
+    fn path(&self) -> &Arc<RelPath> {
+        &self.path
+    }
+
+    fn full_path(&self, _: &App) -> PathBuf {
+        self.path.as_std_path().to_path_buf()
+    }
+
+    fn file_name<'a>(&'a self, _: &'a App) -> &'a str {
+        &self.name
+    }
+
+    fn worktree_id(&self, _: &App) -> WorktreeId {
+        WorktreeId::from_usize(0)
+    }
+
+    fn to_proto(&self, _cx: &App) -> language::proto::File {
+        unimplemented!()
+    }
+
+    fn is_private(&self) -> bool {
+        false
+    }
+
+    fn can_open(&self) -> bool {
+        false
+    }
+}
+
+impl EventEmitter<EditorEvent> for OutputComparison {}
+
+impl Focusable for OutputComparison {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Render for OutputComparison {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        self.editor.clone()
+    }
+}
+
+impl Item for OutputComparison {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Diff).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, _: &App) -> SharedString {
+        self.title.clone()
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(self.tooltip.clone())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<gpui::AnyEntity> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.clone().into())
+        } else {
+            None
+        }
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>, _: &App) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn navigate(
+        &mut self,
+        data: Arc<dyn Any + Send>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            editor.added_to_workspace(workspace, window, cx)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_output() {
+        let output = "hostname r1   \r\n! Last configuration change at 12:00\ninterface Gi0/1\n";
+        let ignore = [Regex::new("^! Last configuration change").unwrap()];
+        assert_eq!(
+            filter_output(output, &ignore),
+            "hostname r1\ninterface Gi0/1\n"
+        );
+        assert_eq!(non_empty_lines(" a \n\n b"), vec!["a", "b"]);
+    }
+}
@@ -317,6 +317,7 @@ fn parse_ssh_host_string(input: &str) -> (String, u16, Option<String>) {
 
 pub fn connect_ssh<T: 'static>(
     ssh_config: terminal::SshSessionConfig,
+    session_id: Option<uuid::Uuid>,
     workspace: Entity<Workspace>,
     pane: Entity<Pane>,
     window: &mut Window,
@@ -337,8 +338,9 @@ pub fn connect_ssh<T: 'static>(
     let window_id = window.window_handle().window_id().as_u64();
     let weak_workspace = workspace.downgrade();
 
-    let terminal_task = TerminalBuilder::new_with_ssh(
+    let terminal_task = TerminalBuilder::new_with_ssh_and_session_id(
         config,
+        session_id,
         cursor_shape,
         alternate_scroll,
         max_scroll_history_lines,
@@ -381,6 +383,7 @@ pub fn connect_ssh<T: 'static>(
 
 pub fn connect_telnet<T: 'static>(
     telnet_config: terminal::TelnetSessionConfig,
+    session_id: Option<uuid::Uuid>,
     workspace: Entity<Workspace>,
     pane: Entity<Pane>,
     window: &mut Window,
@@ -401,8 +404,9 @@ pub fn connect_telnet<T: 'static>(
     let window_id = window.window_handle().window_id().as_u64();
     let weak_workspace = workspace.downgrade();
 
-    let terminal_task = TerminalBuilder::new_with_telnet(
+    let terminal_task = TerminalBuilder::new_with_telnet_and_session_id(
         config,
+        session_id,
         cursor_shape,
         alternate_scroll,
         max_scroll_history_lines,
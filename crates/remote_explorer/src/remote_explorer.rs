@@ -1,3 +1,4 @@
+mod output_comparison;
 mod quick_add;
 mod session_edit_modal;
 
@@ -11,18 +12,21 @@ use gpui::{
     ListSizingBehavior, MouseDownEvent, ParentElement, Point, Render, Styled, Subscription, Task,
     UniformListScrollHandle, WeakEntity, Window, anchored, deferred, px, uniform_list,
 };
-use terminal::{ProtocolConfig, SessionNode, SessionStoreEntity, SessionStoreEvent};
+use terminal::{ProtocolConfig, SessionLayout, SessionNode, SessionStoreEntity, SessionStoreEvent};
+use terminal_view::terminal_panel::TerminalPanel;
 use ui::{
     prelude::*, Color, ContextMenu, Disclosure, Icon, IconName, IconSize, Label, LabelSize,
     ListItem, ListItemSpacing, h_flex, v_flex,
 };
 use uuid::Uuid;
 use workspace::{
-    Pane, Workspace,
+    DetachAndPromptErr as _, InvalidInput, Pane, TextPromptModal, Toast, Workspace,
     dock::{DockPosition, Panel, PanelEvent},
+    notifications::NotificationId,
 };
-use zed_actions::remote_explorer::ToggleFocus;
+use zed_actions::remote_explorer::{SaveTerminalLayout, ToggleFocus};
 
+pub use output_comparison::{CompareOutputModal, OutputComparison};
 pub use quick_add::*;
 pub use session_edit_modal::SessionEditModal;
 
@@ -35,10 +39,60 @@ pub fn init(cx: &mut App) {
         workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
             workspace.toggle_panel_focus::<RemoteExplorer>(window, cx);
         });
+        workspace.register_action(|workspace, _: &SaveTerminalLayout, window, cx| {
+            save_terminal_layout(workspace, window, cx);
+        });
     })
     .detach();
 }
 
+fn save_terminal_layout(
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    struct NoLayoutToast;
+
+    let root = workspace
+        .panel::<TerminalPanel>(cx)
+        .and_then(|terminal_panel| terminal_panel.read(cx).capture_layout(cx));
+    let Some(root) = root else {
+        workspace.show_toast(
+            Toast::new(
+                NotificationId::unique::<NoLayoutToast>(),
+                "Open saved sessions in the terminal panel to save them as a layout",
+            )
+            .autohide(),
+            cx,
+        );
+        return;
+    };
+    let session_count = root.session_ids().len();
+    let session_store = SessionStoreEntity::global(cx);
+    workspace.toggle_modal(window, cx, |window, cx| {
+        TextPromptModal::new(
+            "Save Terminal Layout",
+            &["Layout Name"],
+            move |texts, _, cx| {
+                let name = &texts[0];
+                if name.is_empty() {
+                    return Err(InvalidInput::new(0, "Enter a layout name"));
+                }
+                let layout = SessionLayout::new(name.clone(), root.clone());
+                session_store.update(cx, |store, cx| store.save_layout(layout, cx));
+                Ok(())
+            },
+            window,
+            cx,
+        )
+        .note(format!(
+            "Saves how the {session_count} open saved session{} are tiled. \
+             An existing layout with the same name is replaced.",
+            if session_count == 1 { "" } else { "s" }
+        ))
+    });
+}
+
 /// A flattened tree entry for uniform list rendering.
 #[derive(Clone, Debug)]
 pub struct FlattenedEntry {
@@ -101,6 +155,7 @@ pub struct RemoteExplorer {
     width: Option<Pixels>,
     quick_add_expanded: bool,
     quick_add_area: QuickAddArea,
+    layouts_expanded: bool,
     selected_entry_id: Option<Uuid>,
     context_menu: Option<(Entity<ContextMenu>, Point<Pixels>, Subscription)>,
     drag_target: Option<DragTarget>,
@@ -128,7 +183,8 @@ impl RemoteExplorer {
                 SessionStoreEvent::Changed
                 | SessionStoreEvent::SessionAdded(_)
                 | SessionStoreEvent::SessionRemoved(_)
-                | SessionStoreEvent::CredentialPresetChanged => {
+                | SessionStoreEvent::CredentialPresetChanged
+                | SessionStoreEvent::LayoutsChanged => {
                     this.update_visible_entries(cx);
                 }
             });
@@ -164,6 +220,7 @@ impl RemoteExplorer {
             width: None,
             quick_add_expanded: true,
             quick_add_area,
+            layouts_expanded: true,
             selected_entry_id: None,
             context_menu: None,
             drag_target: None,
@@ -237,14 +294,28 @@ impl RemoteExplorer {
                 let workspace = self.workspace.clone();
                 let pane = self.get_terminal_pane(cx);
                 if let (Some(workspace), Some(pane)) = (workspace.upgrade(), pane) {
-                    connect_ssh(ssh_config.clone(), workspace, pane, window, cx);
+                    connect_ssh(
+                        ssh_config.clone(),
+                        Some(session.id),
+                        workspace,
+                        pane,
+                        window,
+                        cx,
+                    );
                 }
             }
             ProtocolConfig::Telnet(telnet_config) => {
                 let workspace = self.workspace.clone();
                 let pane = self.get_terminal_pane(cx);
                 if let (Some(workspace), Some(pane)) = (workspace.upgrade(), pane) {
-                    connect_telnet(telnet_config.clone(), workspace, pane, window, cx);
+                    connect_telnet(
+                        telnet_config.clone(),
+                        Some(session.id),
+                        workspace,
+                        pane,
+                        window,
+                        cx,
+                    );
                 }
             }
         }
@@ -262,13 +333,26 @@ impl RemoteExplorer {
             return;
         };
 
-        let SessionNode::Session(_session) = node else {
-            return;
-        };
-
         let workspace = self.workspace.clone();
         let session_store_entity = self.session_store.clone();
 
+        if let SessionNode::Group(_) = node {
+            let context_menu = ContextMenu::build(window, cx, move |menu, _window, _cx| {
+                menu.entry("Compare Command Output…", None, move |window, cx| {
+                    if let Some(workspace_entity) = workspace.upgrade() {
+                        let workspace = workspace.clone();
+                        workspace_entity.update(cx, |ws, cx| {
+                            ws.toggle_modal(window, cx, |window, cx| {
+                                CompareOutputModal::new(entry_id, workspace, window, cx)
+                            });
+                        });
+                    }
+                })
+            });
+            self.show_context_menu(context_menu, position, window, cx);
+            return;
+        }
+
         let context_menu = ContextMenu::build(window, cx, move |menu, _window, _cx| {
             let workspace_for_edit = workspace.clone();
 
@@ -287,7 +371,65 @@ impl RemoteExplorer {
                 });
             })
         });
+        self.show_context_menu(context_menu, position, window, cx);
+    }
+
+    fn open_layout(&mut self, id: Uuid, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(layout) = self.session_store.read(cx).store().find_layout(id).cloned() else {
+            return;
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+
+        workspace.update(cx, |workspace, cx| {
+            let Some(terminal_panel) = workspace.focus_panel::<TerminalPanel>(window, cx) else {
+                return;
+            };
+            terminal_panel
+                .update(cx, |terminal_panel, cx| {
+                    terminal_panel.open_layout(&layout, window, cx)
+                })
+                .detach_and_prompt_err(
+                    &format!("Failed to open all of \"{}\"", layout.name),
+                    window,
+                    cx,
+                    |_, _, _| None,
+                );
+        });
+    }
+
+    fn deploy_layout_context_menu(
+        &mut self,
+        position: Point<Pixels>,
+        layout_id: Uuid,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let this = cx.entity().downgrade();
+        let session_store = self.session_store.clone();
+
+        let context_menu = ContextMenu::build(window, cx, move |menu, _window, _cx| {
+            menu.entry("Open Layout", None, move |window, cx| {
+                this.update(cx, |this, cx| this.open_layout(layout_id, window, cx))
+                    .ok();
+            })
+            .entry("Delete Layout", None, move |_window, cx| {
+                session_store.update(cx, |store, cx| {
+                    store.remove_layout(layout_id, cx);
+                });
+            })
+        });
+        self.show_context_menu(context_menu, position, window, cx);
+    }
 
+    fn show_context_menu(
+        &mut self,
+        context_menu: Entity<ContextMenu>,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
         window.focus(&context_menu.focus_handle(cx), cx);
         let subscription = cx.subscribe(&context_menu, |this, _, _: &DismissEvent, cx| {
             this.context_menu.take();
@@ -312,10 +454,10 @@ impl RemoteExplorer {
         {
             match result {
                 ConnectionResult::Ssh(ssh_config, workspace, pane) => {
-                    connect_ssh(ssh_config, workspace, pane, window, cx);
+                    connect_ssh(ssh_config, None, workspace, pane, window, cx);
                 }
                 ConnectionResult::Telnet(telnet_config, workspace, pane) => {
-                    connect_telnet(telnet_config, workspace, pane, window, cx);
+                    connect_telnet(telnet_config, None, workspace, pane, window, cx);
                 }
             }
         }
@@ -328,7 +470,7 @@ impl RemoteExplorer {
             .quick_add_area
             .handle_telnet_connect(workspace, pane, window, cx)
         {
-            connect_telnet(telnet_config, workspace, pane, window, cx);
+            connect_telnet(telnet_config, None, workspace, pane, window, cx);
         }
     }
 
@@ -339,7 +481,7 @@ impl RemoteExplorer {
             .quick_add_area
             .handle_ssh_connect(workspace, pane, window, cx)
         {
-            connect_ssh(ssh_config, workspace, pane, window, cx);
+            connect_ssh(ssh_config, None, workspace, pane, window, cx);
         }
     }
 
@@ -631,6 +773,103 @@ impl RemoteExplorer {
             )
     }
 
+    fn render_layouts_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        let expanded = self.layouts_expanded;
+
+        h_flex()
+            .id("layouts-header")
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .cursor_pointer()
+            .hover(|style| style.bg(theme.colors().ghost_element_hover))
+            .on_click(cx.listener(|this, _: &ClickEvent, _window, cx| {
+                this.layouts_expanded = !this.layouts_expanded;
+                cx.notify();
+            }))
+            .child(Disclosure::new("layouts-disclosure", expanded))
+            .child(
+                Label::new("Layouts")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(div().flex_1())
+            .child(
+                ui::IconButton::new("save-terminal-layout", IconName::Plus)
+                    .icon_size(IconSize::Small)
+                    .tooltip(ui::Tooltip::text("Save Terminal Layout"))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        cx.stop_propagation();
+                        if let Some(workspace) = this.workspace.upgrade() {
+                            workspace.update(cx, |workspace, cx| {
+                                save_terminal_layout(workspace, window, cx);
+                            });
+                        }
+                    })),
+            )
+    }
+
+    fn render_layouts(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let layouts = self
+            .session_store
+            .read(cx)
+            .layouts()
+            .iter()
+            .map(|layout| {
+                (
+                    layout.id,
+                    layout.name.clone(),
+                    layout.root.session_ids().len(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .w_full()
+            .pb_1()
+            .when(layouts.is_empty(), |this| {
+                this.child(
+                    div().px_2().child(
+                        Label::new(
+                            "Save the terminal panel's tiling of saved sessions to reopen it later",
+                        )
+                        .size(LabelSize::XSmall)
+                        .color(Color::Muted),
+                    ),
+                )
+            })
+            .children(layouts.into_iter().map(|(id, name, session_count)| {
+                ListItem::new(SharedString::from(format!("layout-{id}")))
+                    .indent_level(1)
+                    .indent_step_size(px(12.))
+                    .spacing(ListItemSpacing::Dense)
+                    .start_slot(
+                        Icon::new(IconName::Screen)
+                            .color(Color::Muted)
+                            .size(IconSize::Small),
+                    )
+                    .child(Label::new(name))
+                    .end_slot(
+                        Label::new(session_count.to_string())
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    )
+                    .on_click(cx.listener(move |this, event: &ClickEvent, window, cx| {
+                        if event.click_count() == 2 {
+                            this.open_layout(id, window, cx);
+                        }
+                    }))
+                    .on_secondary_mouse_down(cx.listener(
+                        move |this, event: &MouseDownEvent, window, cx| {
+                            cx.stop_propagation();
+                            this.deploy_layout_context_menu(event.position, id, window, cx);
+                        },
+                    ))
+            }))
+    }
+
     fn handle_drag_move(
         &mut self,
         target_id: Uuid,
@@ -822,6 +1061,13 @@ impl RemoteExplorer {
                     this.toggle_expanded(id, window, cx);
                 }))
             })
+            .on_secondary_mouse_down(cx.listener(
+                move |this, event: &MouseDownEvent, window, cx| {
+                    cx.stop_propagation();
+                    this.select_entry(id, cx);
+                    this.deploy_context_menu(event.position, id, window, cx);
+                },
+            ))
             .when(!is_group, |this| {
                 this.on_click(cx.listener(move |this, event: &ClickEvent, window, cx| {
                     if event.click_count() == 2 {
@@ -830,13 +1076,6 @@ impl RemoteExplorer {
                         this.select_entry(id, cx);
                     }
                 }))
-                .on_secondary_mouse_down(cx.listener(
-                    move |this, event: &MouseDownEvent, window, cx| {
-                        cx.stop_propagation();
-                        this.select_entry(id, cx);
-                        this.deploy_context_menu(event.position, id, window, cx);
-                    },
-                ))
             })
             .start_slot(
                 Icon::new(icon)
@@ -949,6 +1188,16 @@ impl Render for RemoteExplorer {
                         this.child(self.render_quick_add_content(window, cx))
                     }),
             )
+            .child(
+                v_flex()
+                    .w_full()
+                    .border_b_1()
+                    .border_color(border_variant)
+                    .child(self.render_layouts_header(cx))
+                    .when(self.layouts_expanded, |this| {
+                        this.child(self.render_layouts(cx))
+                    }),
+            )
             .child(
                 v_flex()
                     .flex_1()
@@ -7,7 +7,9 @@ use terminal::{
     AuthMethod, ProtocolConfig, SessionConfig, SessionNode, SessionStoreEntity,
     SshSessionConfig, TelnetSessionConfig,
 };
-use ui::{prelude::*, Button, ButtonStyle, Color, Label, LabelSize, h_flex, v_flex};
+use ui::{
+    Button, ButtonStyle, Checkbox, Color, Label, LabelSize, ToggleState, h_flex, prelude::*, v_flex,
+};
 use uuid::Uuid;
 use workspace::ModalView;
 
@@ -20,6 +22,7 @@ pub struct SessionEditModal {
     username_editor: Entity<Editor>,
     password_editor: Entity<Editor>,
     protocol: ProtocolType,
+    agent_forwarding: bool,
     focus_handle: FocusHandle,
 }
 
@@ -34,12 +37,26 @@ impl SessionEditModal {
         let session_store = SessionStoreEntity::global(cx);
         let focus_handle = cx.focus_handle();
 
-        let (name, host, port, username, password, protocol) = {
+        let ((name, host, port, username, password, protocol), agent_forwarding) = {
             let store = session_store.read(cx);
             if let Some(SessionNode::Session(session)) = store.store().find_node(session_id) {
-                extract_session_data(session)
+                let agent_forwarding = match &session.protocol {
+                    ProtocolConfig::Ssh(ssh) => ssh.agent_forwarding,
+                    ProtocolConfig::Telnet(_) => false,
+                };
+                (extract_session_data(session), agent_forwarding)
             } else {
-                (String::new(), String::new(), 22, String::new(), String::new(), ProtocolType::Ssh)
+                (
+                    (
+                        String::new(),
+                        String::new(),
+                        22,
+                        String::new(),
+                        String::new(),
+                        ProtocolType::Ssh,
+                    ),
+                    false,
+                )
             }
         };
 
@@ -87,6 +104,7 @@ impl SessionEditModal {
             username_editor,
             password_editor,
             protocol,
+            agent_forwarding,
             focus_handle,
         }
     }
@@ -104,6 +122,7 @@ impl SessionEditModal {
         let password = self.password_editor.read(cx).text(cx);
 
         let protocol = self.protocol;
+        let agent_forwarding = self.agent_forwarding;
         self.session_store.update(cx, |store, cx| {
             store.update_session(
                 self.session_id,
@@ -127,6 +146,7 @@ impl SessionEditModal {
                                 env: std::collections::HashMap::new(),
                                 keepalive_interval_secs: Some(30),
                                 initial_command: None,
+                                agent_forwarding,
                             });
                         }
                         ProtocolType::Telnet => {
@@ -339,7 +359,18 @@ impl Render for SessionEditModal {
                                             .child(self.password_editor.clone()),
                                     ),
                             ),
-                    ),
+                    )
+                    .when(self.protocol == ProtocolType::Ssh, |this| {
+                        this.child(
+                            Checkbox::new("agent-forwarding", self.agent_forwarding.into())
+                                .label("Forward SSH agent")
+                                .label_size(LabelSize::Small)
+                                .on_click(cx.listener(|this, state: &ToggleState, _, cx| {
+                                    this.agent_forwarding = state.selected();
+                                    cx.notify();
+                                })),
+                        )
+                    }),
             )
             .child(
                 h_flex()
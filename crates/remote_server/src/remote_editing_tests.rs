@@ -1917,6 +1917,101 @@ async fn test_remote_git_branches(cx: &mut TestAppContext, server_cx: &mut TestA
     assert_eq!(server_branch.name(), "totally-new-branch");
 }
 
+#[gpui::test]
+async fn test_remote_git_tags_and_cherry_picks(
+    cx: &mut TestAppContext,
+    server_cx: &mut TestAppContext,
+) {
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        path!("/code"),
+        json!({
+            "project1": {
+                ".git": {},
+                "README.md": "# project 1",
+            },
+        }),
+    )
+    .await;
+    let dot_git = Path::new(path!("/code/project1/.git"));
+
+    let (project, _headless_project) = init_test(&fs, cx, server_cx).await;
+    project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(path!("/code/project1"), true, cx)
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    let repository = project.update(cx, |project, cx| project.active_repository(cx).unwrap());
+
+    repository
+        .update(cx, |repository, _| {
+            repository.create_tag("v1".into(), "HEAD".into(), Some("First".into()))
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    let tag_sha = fs
+        .with_git_state(dot_git, false, |state| {
+            state.refs.get("refs/tags/v1").cloned()
+        })
+        .unwrap();
+    assert_eq!(tag_sha.as_deref(), Some("abc"));
+
+    let error = repository
+        .update(cx, |repository, _| {
+            repository.create_tag("v1".into(), "HEAD".into(), None)
+        })
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert!(error.to_string().contains("already exists"), "{error}");
+
+    repository
+        .update(cx, |repository, _| repository.delete_tag("v1".into()))
+        .await
+        .unwrap()
+        .unwrap();
+    let tag_sha = fs
+        .with_git_state(dot_git, false, |state| {
+            state.refs.get("refs/tags/v1").cloned()
+        })
+        .unwrap();
+    assert_eq!(tag_sha, None);
+
+    repository
+        .update(cx, |repository, _| {
+            repository.cherry_pick(vec!["HEAD".into()])
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    repository
+        .update(cx, |repository, _| repository.revert("HEAD".into()))
+        .await
+        .unwrap()
+        .unwrap();
+    let error = repository
+        .update(cx, |repository, _| {
+            repository.cherry_pick_range("HEAD".into(), "missing".into())
+        })
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert!(error.to_string().contains("missing"), "{error}");
+
+    let diff = repository
+        .update(cx, |repository, _| {
+            repository.compare_commits("HEAD".into(), "HEAD".into())
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(diff.files.is_empty());
+}
+
 #[gpui::test]
 async fn test_remote_agent_fs_tool_calls(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
     let fs = FakeFs::new(server_cx.executor());
@@ -195,7 +195,9 @@ impl VsCodeSettings {
             image_viewer: None,
             journal: None,
             language_models: None,
+            large_file_viewer: None,
             line_indicator_format: None,
+            local_history: None,
             log: None,
             message_editor: None,
             node: self.node_binary_settings(),
@@ -142,6 +142,12 @@ pub struct SettingsContent {
     /// The settings for the image viewer.
     pub image_viewer: Option<ImageViewerSettingsContent>,
 
+    /// The settings for the large file viewer.
+    pub large_file_viewer: Option<LargeFileViewerSettingsContent>,
+
+    /// The settings for local file history.
+    pub local_history: Option<LocalHistorySettingsContent>,
+
     pub repl: Option<ReplSettingsContent>,
 
     /// Whether or not to enable Helix mode.
@@ -747,6 +753,14 @@ pub struct VimSettingsContent {
     pub custom_digraphs: Option<HashMap<String, Arc<str>>>,
     pub highlight_on_yank_duration: Option<u64>,
     pub cursor_shape: Option<CursorShapeSettings>,
+    /// The shell command run by `:make`. Arguments given to `:make` are appended to it.
+    ///
+    /// Default: "make"
+    pub make_command: Option<String>,
+    /// The regex used to read locations from the output of `:make` into the quickfix
+    /// list. It uses the named groups `file`, `line`, `column` and `message`; only
+    /// `file` and `line` are required.
+    pub error_format: Option<String>,
 }
 
 #[derive(
@@ -989,6 +1003,46 @@ pub struct ImageViewerSettingsContent {
     pub unit: Option<ImageFileSizeUnit>,
 }
 
+/// The settings for the large file viewer.
+#[with_fallible_options]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, Default, PartialEq)]
+pub struct LargeFileViewerSettingsContent {
+    /// Files at least this many megabytes large open read-only in the large file viewer,
+    /// instead of being loaded into an editor. Set to 0 to always open files in an editor.
+    ///
+    /// Default: 100
+    pub threshold_mb: Option<u64>,
+}
+
+/// The settings for local file history.
+#[with_fallible_options]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, Default, PartialEq)]
+pub struct LocalHistorySettingsContent {
+    /// Whether to record a version of files in a local database whenever they're saved,
+    /// so earlier versions can be restored outside of version control.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// How many versions to keep for each file. Older versions are dropped first.
+    ///
+    /// Default: 50
+    pub max_snapshots_per_file: Option<u32>,
+    /// How many days to keep versions for. Set to 0 to keep versions until they're
+    /// pushed out by newer ones.
+    ///
+    /// Default: 30
+    pub max_age_days: Option<u32>,
+    /// How often to record a version of files with unsaved edits, in seconds.
+    /// Set to 0 to only record versions when files are saved.
+    ///
+    /// Default: 0
+    pub snapshot_interval_seconds: Option<u64>,
+    /// Files larger than this many kilobytes aren't recorded.
+    ///
+    /// Default: 1024
+    pub max_file_size_kb: Option<u64>,
+}
+
 #[with_fallible_options]
 #[derive(
     Clone,
@@ -2424,7 +2424,7 @@ fn editor_page() -> SettingsPage {
         ]
     }
 
-    fn vim_settings_section() -> [SettingsPageItem; 12] {
+    fn vim_settings_section() -> [SettingsPageItem; 14] {
         [
             SettingsPageItem::SectionHeader("Vim"),
             SettingsPageItem::SettingItem(SettingItem {
@@ -2655,6 +2655,32 @@ fn editor_page() -> SettingsPage {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Make Command",
+                description: "The shell command run by :make.",
+                field: Box::new(SettingField {
+                    json_path: Some("vim.make_command"),
+                    pick: |settings_content| settings_content.vim.as_ref()?.make_command.as_ref(),
+                    write: |settings_content, value| {
+                        settings_content.vim.get_or_insert_default().make_command = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Error Format",
+                description: "The regex used to read file locations from the output of :make into the quickfix list.",
+                field: Box::new(SettingField {
+                    json_path: Some("vim.error_format"),
+                    pick: |settings_content| settings_content.vim.as_ref()?.error_format.as_ref(),
+                    write: |settings_content, value| {
+                        settings_content.vim.get_or_insert_default().error_format = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
         ]
     }
 
@@ -8396,7 +8422,7 @@ fn language_settings_data() -> Box<[SettingsPageItem]> {
         ]
     }
 
-    fn global_only_miscellaneous_sub_section() -> [SettingsPageItem; 3] {
+    fn global_only_miscellaneous_sub_section() -> [SettingsPageItem; 6] {
         [
             SettingsPageItem::SettingItem(SettingItem {
                 title: "Image Viewer",
@@ -8416,6 +8442,69 @@ fn language_settings_data() -> Box<[SettingsPageItem]> {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Large File Viewer Threshold",
+                description: "Files at least this many megabytes large open read-only in the large file viewer. 0 always opens an editor.",
+                field: Box::new(SettingField {
+                    json_path: Some("large_file_viewer.threshold_mb"),
+                    pick: |settings_content| {
+                        settings_content
+                            .large_file_viewer
+                            .as_ref()
+                            .and_then(|large_file_viewer| large_file_viewer.threshold_mb.as_ref())
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .large_file_viewer
+                            .get_or_insert_default()
+                            .threshold_mb = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Local History",
+                description: "Whether to record a version of files whenever they're saved, so earlier versions can be restored.",
+                field: Box::new(SettingField {
+                    json_path: Some("local_history.enabled"),
+                    pick: |settings_content| {
+                        settings_content
+                            .local_history
+                            .as_ref()
+                            .and_then(|local_history| local_history.enabled.as_ref())
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .local_history
+                            .get_or_insert_default()
+                            .enabled = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Local History Versions",
+                description: "How many versions of each file to keep in the local history.",
+                field: Box::new(SettingField {
+                    json_path: Some("local_history.max_snapshots_per_file"),
+                    pick: |settings_content| {
+                        settings_content
+                            .local_history
+                            .as_ref()
+                            .and_then(|local_history| local_history.max_snapshots_per_file.as_ref())
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .local_history
+                            .get_or_insert_default()
+                            .max_snapshots_per_file = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
             SettingsPageItem::SettingItem(SettingItem {
                 title: "Auto Replace Emoji Shortcode",
                 description: "Whether to automatically replace emoji shortcodes with emoji characters.",
@@ -26,6 +26,12 @@ const TOOLS: &[ToolInfo] = &[
         description: "Commands executed in the terminal",
         regex_explanation: "Patterns are matched against each command in the input. Commands chained with &&, ||, ;, or pipes are split and checked individually.",
     },
+    ToolInfo {
+        id: "remote_terminal",
+        name: "Remote Terminal",
+        description: "Commands sent to saved SSH and Telnet sessions",
+        regex_explanation: "Patterns are matched against the session name and command, as \"<session>: <command>\", so rules can apply to particular sessions.",
+    },
     ToolInfo {
         id: "edit_file",
         name: "Edit File",
@@ -298,6 +304,7 @@ fn get_tool_render_fn(
 ) -> fn(&SettingsWindow, &ScrollHandle, &mut Window, &mut Context<SettingsWindow>) -> AnyElement {
     match tool_id {
         "terminal" => render_terminal_tool_config,
+        "remote_terminal" => render_remote_terminal_tool_config,
         "edit_file" => render_edit_file_tool_config,
         "delete_path" => render_delete_path_tool_config,
         "copy_path" => render_copy_path_tool_config,
@@ -1374,6 +1381,7 @@ macro_rules! tool_config_page_fn {
 }
 
 tool_config_page_fn!(render_terminal_tool_config, "terminal");
+tool_config_page_fn!(render_remote_terminal_tool_config, "remote_terminal");
 tool_config_page_fn!(render_edit_file_tool_config, "edit_file");
 tool_config_page_fn!(render_delete_path_tool_config, "delete_path");
 tool_config_page_fn!(render_copy_path_tool_config, "copy_path");
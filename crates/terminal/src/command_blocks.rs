@@ -0,0 +1,889 @@
+use std::{
+    io::{self, Read},
+    ops::RangeInclusive,
+    sync::Arc,
+};
+
+use alacritty_terminal::{
+    Term,
+    event::{EventListener, OnResize, WindowSize},
+    grid::Dimensions,
+    index::{Column, Line},
+    term::TermMode,
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+};
+use collections::VecDeque;
+use parking_lot::Mutex;
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+const OSC_START: u8 = b']';
+const STRING_TERMINATOR: u8 = b'\\';
+
+/// OSC 133 parameters are short (`133;D;127`), anything longer is some other OSC.
+const MAX_OSC_PARAMS_LEN: usize = 64;
+
+/// Upper bound on the number of command blocks kept per terminal.
+const MAX_COMMAND_BLOCKS: usize = 1000;
+
+/// A semantic prompt mark, as emitted by shells via `OSC 133 ; <kind> ST`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptMarkKind {
+    /// `A`: the shell is about to print the prompt.
+    PromptStart,
+    /// `B`: the prompt was printed, user input starts here.
+    CommandStart,
+    /// `C`: the command was submitted, its output starts here.
+    OutputStart,
+    /// `D[;exit_code]`: the command finished.
+    CommandFinished { exit_code: Option<i32> },
+}
+
+impl PromptMarkKind {
+    fn parse(params: &[u8]) -> Option<Self> {
+        let params = std::str::from_utf8(params).ok()?;
+        let mut parts = params.split(';');
+        if parts.next()? != "133" {
+            return None;
+        }
+        match parts.next()? {
+            "A" => Some(Self::PromptStart),
+            "B" => Some(Self::CommandStart),
+            "C" => Some(Self::OutputStart),
+            "D" => Some(Self::CommandFinished {
+                exit_code: parts.next().and_then(|code| code.trim().parse().ok()),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParserState {
+    Ground,
+    Escape,
+    OscParams,
+    OscIgnore,
+    OscEscape { ignoring: bool },
+}
+
+/// Incremental scanner that finds OSC 133 marks in a raw terminal byte stream.
+///
+/// The bytes are not consumed: the same stream is still fed to the emulator, this
+/// only reports where the marks ended so callers can resolve their grid positions.
+pub struct SemanticPromptParser {
+    state: ParserState,
+    params: Vec<u8>,
+}
+
+impl Default for SemanticPromptParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SemanticPromptParser {
+    pub fn new() -> Self {
+        Self {
+            state: ParserState::Ground,
+            params: Vec::new(),
+        }
+    }
+
+    /// Scans `bytes`, returning every complete mark together with the offset
+    /// just past its terminator. Sequences split across calls are handled.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<(usize, PromptMarkKind)> {
+        let mut marks = Vec::new();
+
+        for (index, &byte) in bytes.iter().enumerate() {
+            match self.state {
+                ParserState::Ground => {
+                    if byte == ESC {
+                        self.state = ParserState::Escape;
+                    }
+                }
+                ParserState::Escape => {
+                    if byte == OSC_START {
+                        self.params.clear();
+                        self.state = ParserState::OscParams;
+                    } else if byte != ESC {
+                        self.state = ParserState::Ground;
+                    }
+                }
+                ParserState::OscParams | ParserState::OscIgnore => {
+                    let ignoring = self.state == ParserState::OscIgnore;
+                    match byte {
+                        BEL => {
+                            if !ignoring && let Some(kind) = PromptMarkKind::parse(&self.params) {
+                                marks.push((index + 1, kind));
+                            }
+                            self.state = ParserState::Ground;
+                        }
+                        ESC => self.state = ParserState::OscEscape { ignoring },
+                        _ if ignoring => {}
+                        _ => {
+                            // Bail out early on anything that can't be an OSC 133 sequence,
+                            // so large payloads (images, clipboard) are not buffered.
+                            let is_prefix = b"133;"
+                                .get(self.params.len())
+                                .is_none_or(|expected| *expected == byte);
+                            if is_prefix && self.params.len() < MAX_OSC_PARAMS_LEN {
+                                self.params.push(byte);
+                            } else {
+                                self.state = ParserState::OscIgnore;
+                            }
+                        }
+                    }
+                }
+                ParserState::OscEscape { ignoring } => {
+                    if byte == STRING_TERMINATOR {
+                        if !ignoring && let Some(kind) = PromptMarkKind::parse(&self.params) {
+                            marks.push((index + 1, kind));
+                        }
+                        self.state = ParserState::Ground;
+                    } else if byte == OSC_START {
+                        // An unterminated OSC followed by a new one.
+                        self.params.clear();
+                        self.state = ParserState::OscParams;
+                    } else {
+                        self.state = ParserState::Ground;
+                    }
+                }
+            }
+        }
+
+        marks
+    }
+}
+
+/// How a command block ended, used to colour its gutter marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandStatus {
+    /// The prompt is shown or the command is still producing output.
+    Running,
+    Succeeded,
+    Failed(i32),
+    /// The command finished without reporting an exit code.
+    Unknown,
+}
+
+/// One prompt, the command typed into it and the command's output.
+///
+/// Lines are absolute (see [`ScrollTracker`]), so they stay stable while the viewport
+/// scrolls and while output pushes old lines out of a full scrollback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandBlock {
+    pub prompt_line: i64,
+    pub command_start: Option<(i64, usize)>,
+    pub output_start: Option<i64>,
+    pub output_end: Option<i64>,
+    pub finished: bool,
+    pub exit_code: Option<i32>,
+    /// Whether the output is folded down to its first line.
+    pub folded: bool,
+}
+
+impl CommandBlock {
+    fn new(prompt_line: i64) -> Self {
+        Self {
+            prompt_line,
+            command_start: None,
+            output_start: None,
+            output_end: None,
+            finished: false,
+            exit_code: None,
+            folded: false,
+        }
+    }
+
+    pub fn status(&self) -> CommandStatus {
+        match (self.finished, self.exit_code) {
+            (false, _) => CommandStatus::Running,
+            (true, Some(0)) => CommandStatus::Succeeded,
+            (true, Some(code)) => CommandStatus::Failed(code),
+            (true, None) => CommandStatus::Unknown,
+        }
+    }
+
+    /// The last absolute line that belongs to this block.
+    pub fn last_line(&self, next_prompt_line: Option<i64>, bottom_line: i64) -> i64 {
+        self.output_end
+            .or_else(|| next_prompt_line.map(|line| line - 1))
+            .unwrap_or(bottom_line)
+            .max(self.prompt_line)
+    }
+
+    /// The absolute line range holding the command's output, if it produced any.
+    pub fn output_lines(
+        &self,
+        next_prompt_line: Option<i64>,
+        bottom_line: i64,
+    ) -> Option<RangeInclusive<i64>> {
+        let start = self.output_start?;
+        let end = self.last_line(next_prompt_line, bottom_line);
+        (start <= end).then_some(start..=end)
+    }
+
+    /// The lines hidden when the block is folded: all of the output but its first line.
+    /// Output still being written can't be folded.
+    fn foldable_lines(&self, next_prompt_line: Option<i64>) -> Option<RangeInclusive<i64>> {
+        if !self.finished && next_prompt_line.is_none() {
+            return None;
+        }
+        let lines = self.output_lines(next_prompt_line, self.prompt_line)?;
+        (lines.start() < lines.end()).then(|| lines.start() + 1..=*lines.end())
+    }
+}
+
+/// The command blocks seen in a terminal, oldest first.
+#[derive(Debug, Default)]
+pub struct CommandBlocks {
+    blocks: VecDeque<CommandBlock>,
+}
+
+impl CommandBlocks {
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&CommandBlock> {
+        self.blocks.get(index)
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &CommandBlock> + '_ {
+        self.blocks.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+    }
+
+    /// Records a mark observed with the cursor at the given absolute line and column.
+    pub fn record(&mut self, kind: PromptMarkKind, line: i64, column: usize) {
+        match kind {
+            PromptMarkKind::PromptStart => {
+                if let Some(last) = self.blocks.back_mut()
+                    && !last.finished
+                {
+                    // The previous command never reported `D`, e.g. it was interrupted.
+                    last.finished = last.output_start.is_some();
+                    if last.output_end.is_none() && last.output_start.is_some() {
+                        last.output_end = Some(line - 1);
+                    }
+                }
+                if self
+                    .blocks
+                    .back()
+                    .is_some_and(|last| last.prompt_line == line && last.output_start.is_none())
+                {
+                    // Redrawn prompt (e.g. on resize), keep a single block for it.
+                    return;
+                }
+                self.blocks.push_back(CommandBlock::new(line));
+                if self.blocks.len() > MAX_COMMAND_BLOCKS {
+                    self.blocks.pop_front();
+                }
+            }
+            PromptMarkKind::CommandStart => {
+                if let Some(last) = self.blocks.back_mut() {
+                    last.command_start = Some((line, column));
+                }
+            }
+            PromptMarkKind::OutputStart => {
+                if let Some(last) = self.blocks.back_mut() {
+                    // `C` is usually emitted after the newline of the submitted command,
+                    // but some shells emit it before moving the cursor.
+                    let output_start = if column == 0 { line } else { line + 1 };
+                    last.output_start = Some(output_start);
+                }
+            }
+            PromptMarkKind::CommandFinished { exit_code } => {
+                if let Some(last) = self.blocks.back_mut()
+                    && !last.finished
+                {
+                    last.finished = true;
+                    last.exit_code = exit_code;
+                    if let Some(output_start) = last.output_start {
+                        let output_end = if column == 0 { line - 1 } else { line };
+                        last.output_end = Some(output_end.max(output_start - 1));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drops the blocks that ended above `first_line`, the oldest line still in the
+    /// scrollback. The latest block is kept while its command may still be running.
+    pub fn prune(&mut self, first_line: i64) {
+        while let Some(block) = self.blocks.front() {
+            if block.last_line(self.next_prompt_line(0), i64::MAX) >= first_line {
+                break;
+            }
+            self.blocks.pop_front();
+        }
+    }
+
+    /// Folds or unfolds the output of the block at `index`. Returns false when it has
+    /// nothing to fold.
+    pub fn toggle_fold(&mut self, index: usize) -> bool {
+        let next_prompt_line = self.next_prompt_line(index);
+        let Some(block) = self.blocks.get_mut(index) else {
+            return false;
+        };
+        if !block.folded && block.foldable_lines(next_prompt_line).is_none() {
+            return false;
+        }
+        block.folded = !block.folded;
+        true
+    }
+
+    /// The absolute lines hidden by folded blocks, top to bottom.
+    pub fn hidden_lines(&self) -> Vec<RangeInclusive<i64>> {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| block.folded)
+            .filter_map(|(index, block)| block.foldable_lines(self.next_prompt_line(index)))
+            .collect()
+    }
+
+    /// Index of the block that contains the given absolute line.
+    pub fn index_at_line(&self, line: i64) -> Option<usize> {
+        let index = self
+            .blocks
+            .iter()
+            .rposition(|block| block.prompt_line <= line)?;
+        Some(index)
+    }
+
+    /// The prompt line of the closest block starting strictly above `line`.
+    pub fn previous_prompt(&self, line: i64) -> Option<i64> {
+        self.blocks
+            .iter()
+            .rev()
+            .map(|block| block.prompt_line)
+            .find(|prompt_line| *prompt_line < line)
+    }
+
+    /// The prompt line of the closest block starting strictly below `line`.
+    pub fn next_prompt(&self, line: i64) -> Option<i64> {
+        self.blocks
+            .iter()
+            .map(|block| block.prompt_line)
+            .find(|prompt_line| *prompt_line > line)
+    }
+
+    /// The prompt line of the block following the block at `index`.
+    pub fn next_prompt_line(&self, index: usize) -> Option<i64> {
+        self.blocks.get(index + 1).map(|block| block.prompt_line)
+    }
+}
+
+/// Counts the lines scrolled off the top of the primary screen since the terminal
+/// started. Absolute lines are `scrolled_lines + grid line`, which keep pointing at the
+/// same text after the scrollback is full and its size stops growing.
+///
+/// Alacritty doesn't report scrolling, but it scrolls by rotating the grid's rows
+/// without moving their cells, so the row that was at the bottom of the screen can be
+/// found again by address. Scrolling by the whole grid or more between two updates
+/// rotates that row back into place and goes unnoticed.
+#[derive(Debug, Default)]
+pub struct ScrollTracker {
+    scrolled_lines: i64,
+    /// The address of the cells of the bottom screen row at the last update.
+    anchor: Option<usize>,
+}
+
+impl ScrollTracker {
+    pub fn scrolled_lines(&self) -> i64 {
+        self.scrolled_lines
+    }
+
+    /// The absolute line of a grid line, as of the last update.
+    pub fn absolute_line(&self, line: Line) -> i64 {
+        self.scrolled_lines + line.0 as i64
+    }
+
+    /// The absolute line of the oldest line in a scrollback of the given size.
+    pub fn first_line(&self, history_size: usize) -> i64 {
+        self.scrolled_lines - history_size as i64
+    }
+
+    /// Counts the lines scrolled since the last update. The alternate screen has no
+    /// scrollback and is skipped.
+    pub fn update<T: EventListener>(&mut self, term: &Term<T>) {
+        if let Some(scrolled_lines) = self.current(term) {
+            self.scrolled_lines = scrolled_lines;
+            self.anchor = Some(row_address(term, term.grid().bottommost_line()));
+        }
+    }
+
+    /// The lines scrolled off the screen by now, without updating the tracker.
+    pub fn scrolled_lines_in<T: EventListener>(&self, term: &Term<T>) -> i64 {
+        self.current(term).unwrap_or(self.scrolled_lines)
+    }
+
+    /// Starts over from the reflowed grid after a resize, counting the lines the
+    /// resize moved into or out of the scrollback.
+    pub fn resized<T: EventListener>(&mut self, term: &Term<T>, previous_history_size: usize) {
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            self.anchor = None;
+            return;
+        }
+        self.scrolled_lines += term.history_size() as i64 - previous_history_size as i64;
+        self.anchor = Some(row_address(term, term.grid().bottommost_line()));
+    }
+
+    fn current<T: EventListener>(&self, term: &Term<T>) -> Option<i64> {
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return None;
+        }
+        let Some(anchor) = self.anchor else {
+            return Some(self.scrolled_lines);
+        };
+        let grid = term.grid();
+        let bottom = grid.bottommost_line();
+        let scrolled = (0..grid.total_lines())
+            .find(|offset| row_address(term, Line(bottom.0 - *offset as i32)) == anchor)
+            // The rows were replaced, e.g. by a reset, so the whole screen is new.
+            .unwrap_or(grid.screen_lines());
+        Some(self.scrolled_lines + scrolled as i64)
+    }
+}
+
+fn row_address<T: EventListener>(term: &Term<T>, line: Line) -> usize {
+    &term.grid()[line][Column(0)] as *const _ as usize
+}
+
+/// A mark read from a local PTY, positioned relative to the cursor once the
+/// emulator has parsed the surrounding output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingPromptMark {
+    pub kind: PromptMarkKind,
+    /// Line feeds read after the mark, i.e. how far above the cursor it is.
+    pub line_feeds_after: u64,
+    /// Characters printed since the last line start when the mark was seen.
+    pub column: usize,
+}
+
+/// Marks found in a local PTY's output before the emulator parsed it.
+///
+/// The tee runs on alacritty's IO thread and cannot see the grid, so each mark is
+/// stored with the number of line feeds read so far. When the terminal drains the
+/// marks after the chunk was parsed, the mark's line is the cursor line minus the
+/// line feeds that followed it.
+#[derive(Default)]
+pub struct PendingPromptMarks {
+    marks: Vec<(PromptMarkKind, u64, usize)>,
+    position: OutputPosition,
+}
+
+impl PendingPromptMarks {
+    fn scan(&mut self, bytes: &[u8], marks: Vec<(usize, PromptMarkKind)>) {
+        let mut scanned = 0;
+        for (offset, kind) in marks {
+            self.position.advance(&bytes[scanned..offset]);
+            scanned = offset;
+            self.marks
+                .push((kind, self.position.line_feeds, self.position.column));
+        }
+        self.position.advance(&bytes[scanned..]);
+    }
+
+    pub fn drain(&mut self) -> Vec<PendingPromptMark> {
+        let line_feeds = self.position.line_feeds;
+        self.marks
+            .drain(..)
+            .map(|(kind, seen, column)| PendingPromptMark {
+                kind,
+                line_feeds_after: line_feeds - seen,
+                column,
+            })
+            .collect()
+    }
+}
+
+/// Follows the cursor through raw output without a grid: the line feeds passed so far
+/// and the column on the current line. Escape sequences don't take up columns, wrapped
+/// lines and wide characters are not accounted for.
+#[derive(Debug, Default)]
+pub(crate) struct OutputPosition {
+    pub line_feeds: u64,
+    pub column: usize,
+    state: OutputState,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum OutputState {
+    #[default]
+    Ground,
+    Escape,
+    Csi,
+    /// OSC, DCS, APC, PM and SOS sequences, which run until BEL or ST.
+    String,
+    StringEscape,
+}
+
+impl OutputState {
+    fn after_escape(byte: u8) -> Self {
+        match byte {
+            b'[' => Self::Csi,
+            b']' | b'P' | b'_' | b'^' | b'X' => Self::String,
+            // Intermediate bytes, e.g. the `(` of a charset designation.
+            ESC | 0x20..=0x2f => Self::Escape,
+            _ => Self::Ground,
+        }
+    }
+}
+
+impl OutputPosition {
+    pub fn advance(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state = match self.state {
+                OutputState::Ground if byte == ESC => OutputState::Escape,
+                OutputState::Ground => {
+                    self.print(byte);
+                    OutputState::Ground
+                }
+                OutputState::Escape => OutputState::after_escape(byte),
+                OutputState::Csi => match byte {
+                    ESC => OutputState::Escape,
+                    0x40..=0x7e => OutputState::Ground,
+                    _ => OutputState::Csi,
+                },
+                OutputState::String => match byte {
+                    BEL => OutputState::Ground,
+                    ESC => OutputState::StringEscape,
+                    _ => OutputState::String,
+                },
+                OutputState::StringEscape => match byte {
+                    STRING_TERMINATOR => OutputState::Ground,
+                    _ => OutputState::after_escape(byte),
+                },
+            };
+        }
+    }
+
+    fn print(&mut self, byte: u8) {
+        match byte {
+            b'\n' => {
+                self.line_feeds += 1;
+                self.column = 0;
+            }
+            b'\r' => self.column = 0,
+            b'\t' => self.column = (self.column / 8 + 1) * 8,
+            0x08 => self.column = self.column.saturating_sub(1),
+            // Other control characters and UTF-8 continuation bytes don't take up a column.
+            0x00..=0x1f | 0x7f..=0xbf => {}
+            _ => self.column += 1,
+        }
+    }
+}
+
+/// Wraps a local PTY so the bytes alacritty reads from it are scanned for OSC 133 marks.
+pub struct SemanticPromptPty<P: EventedPty> {
+    reader: SemanticPromptReader<P>,
+}
+
+pub struct SemanticPromptReader<P: EventedPty> {
+    pty: P,
+    parser: SemanticPromptParser,
+    pending: Arc<Mutex<PendingPromptMarks>>,
+}
+
+impl<P: EventedPty> SemanticPromptPty<P> {
+    pub fn new(pty: P, pending: Arc<Mutex<PendingPromptMarks>>) -> Self {
+        Self {
+            reader: SemanticPromptReader {
+                pty,
+                parser: SemanticPromptParser::new(),
+                pending,
+            },
+        }
+    }
+}
+
+impl<P: EventedPty> Read for SemanticPromptReader<P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.pty.reader().read(buf)?;
+        let bytes = &buf[..read];
+        let marks = self.parser.feed(bytes);
+        self.pending.lock().scan(bytes, marks);
+        Ok(read)
+    }
+}
+
+impl<P: EventedPty> EventedReadWrite for SemanticPromptPty<P> {
+    type Reader = SemanticPromptReader<P>;
+    type Writer = P::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<polling::Poller>,
+        interest: polling::Event,
+        mode: polling::PollMode,
+    ) -> io::Result<()> {
+        unsafe { self.reader.pty.register(poll, interest, mode) }
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<polling::Poller>,
+        interest: polling::Event,
+        mode: polling::PollMode,
+    ) -> io::Result<()> {
+        self.reader.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<polling::Poller>) -> io::Result<()> {
+        self.reader.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        &mut self.reader
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.reader.pty.writer()
+    }
+}
+
+impl<P: EventedPty> EventedPty for SemanticPromptPty<P> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.reader.pty.next_child_event()
+    }
+}
+
+impl<P: EventedPty + OnResize> OnResize for SemanticPromptPty<P> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.reader.pty.on_resize(window_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alacritty_terminal::{
+        event::VoidListener,
+        term::{Config, test::TermSize},
+        vte::ansi::{Processor, StdSyncHandler},
+    };
+
+    #[test]
+    fn test_parses_marks_with_both_terminators() {
+        let mut parser = SemanticPromptParser::new();
+        let input = b"\x1b]133;A\x07$ \x1b]133;B\x1b\\ls\r\n\x1b]133;C\x07out\r\n\x1b]133;D;2\x07";
+        let marks = parser.feed(input);
+        let kinds: Vec<_> = marks.iter().map(|(_, kind)| *kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                PromptMarkKind::PromptStart,
+                PromptMarkKind::CommandStart,
+                PromptMarkKind::OutputStart,
+                PromptMarkKind::CommandFinished { exit_code: Some(2) },
+            ]
+        );
+        assert_eq!(marks[0].0, 8);
+        assert_eq!(marks.last().unwrap().0, input.len());
+    }
+
+    #[test]
+    fn test_marks_split_across_chunks() {
+        let mut parser = SemanticPromptParser::new();
+        assert!(parser.feed(b"text\x1b]13").is_empty());
+        assert!(parser.feed(b"3;D").is_empty());
+        let marks = parser.feed(b"\x1b\\more");
+        assert_eq!(
+            marks,
+            vec![(2, PromptMarkKind::CommandFinished { exit_code: None })]
+        );
+    }
+
+    #[test]
+    fn test_ignores_other_osc_sequences() {
+        let mut parser = SemanticPromptParser::new();
+        let mut input = b"\x1b]0;title\x07\x1b]8;;http://a\x1b\\".to_vec();
+        input.extend(b"\x1b]1337;File=");
+        input.extend(std::iter::repeat_n(b'A', 4096));
+        input.extend(b"\x07\x1b]133;A\x07");
+        let marks = parser.feed(&input);
+        assert_eq!(marks, vec![(input.len(), PromptMarkKind::PromptStart)]);
+    }
+
+    #[test]
+    fn test_command_blocks_lifecycle() {
+        let mut blocks = CommandBlocks::default();
+        blocks.record(PromptMarkKind::PromptStart, 0, 0);
+        blocks.record(PromptMarkKind::CommandStart, 0, 2);
+        blocks.record(PromptMarkKind::OutputStart, 1, 0);
+        blocks.record(PromptMarkKind::CommandFinished { exit_code: Some(0) }, 4, 0);
+        blocks.record(PromptMarkKind::PromptStart, 4, 0);
+        blocks.record(PromptMarkKind::OutputStart, 5, 0);
+        blocks.record(PromptMarkKind::CommandFinished { exit_code: Some(1) }, 6, 3);
+
+        assert_eq!(blocks.len(), 2);
+        let first = blocks.get(0).unwrap();
+        assert_eq!(first.status(), CommandStatus::Succeeded);
+        assert_eq!(first.output_lines(Some(4), 10), Some(1..=3));
+        let second = blocks.get(1).unwrap();
+        assert_eq!(second.status(), CommandStatus::Failed(1));
+        assert_eq!(second.output_lines(None, 10), Some(5..=6));
+
+        assert_eq!(blocks.previous_prompt(4), Some(0));
+        assert_eq!(blocks.next_prompt(0), Some(4));
+        assert_eq!(blocks.next_prompt(4), None);
+        assert_eq!(blocks.index_at_line(3), Some(0));
+        assert_eq!(blocks.index_at_line(8), Some(1));
+    }
+
+    #[test]
+    fn test_folding_hides_all_but_the_first_output_line() {
+        let mut blocks = CommandBlocks::default();
+        blocks.record(PromptMarkKind::PromptStart, 0, 0);
+        blocks.record(PromptMarkKind::OutputStart, 1, 0);
+        blocks.record(PromptMarkKind::CommandFinished { exit_code: Some(0) }, 5, 0);
+        blocks.record(PromptMarkKind::PromptStart, 5, 0);
+        blocks.record(PromptMarkKind::OutputStart, 6, 0);
+        blocks.record(PromptMarkKind::CommandFinished { exit_code: Some(0) }, 7, 0);
+        blocks.record(PromptMarkKind::PromptStart, 7, 0);
+        blocks.record(PromptMarkKind::OutputStart, 8, 0);
+
+        // A single line of output, and output still being written, can't be folded.
+        assert!(!blocks.toggle_fold(1));
+        assert!(!blocks.toggle_fold(2));
+        assert!(blocks.toggle_fold(0));
+        assert_eq!(blocks.hidden_lines(), vec![2..=4]);
+
+        assert!(blocks.toggle_fold(0));
+        assert!(blocks.hidden_lines().is_empty());
+    }
+
+    #[test]
+    fn test_interrupted_command_is_closed_by_next_prompt() {
+        let mut blocks = CommandBlocks::default();
+        blocks.record(PromptMarkKind::PromptStart, 0, 0);
+        blocks.record(PromptMarkKind::OutputStart, 1, 0);
+        blocks.record(PromptMarkKind::PromptStart, 7, 0);
+
+        let first = blocks.get(0).unwrap();
+        assert_eq!(first.status(), CommandStatus::Unknown);
+        assert_eq!(first.output_end, Some(6));
+        assert_eq!(blocks.get(1).unwrap().status(), CommandStatus::Running);
+    }
+
+    #[test]
+    fn test_pending_marks_count_following_line_feeds() {
+        let mut pending = PendingPromptMarks::default();
+        let mut parser = SemanticPromptParser::new();
+        let bytes = b"\x1b]133;A\x07line one\nline two\n$ \x1b]133;B\x07";
+        pending.scan(bytes, parser.feed(bytes));
+        assert_eq!(
+            pending.drain(),
+            vec![
+                PendingPromptMark {
+                    kind: PromptMarkKind::PromptStart,
+                    line_feeds_after: 2,
+                    column: 0,
+                },
+                PendingPromptMark {
+                    kind: PromptMarkKind::CommandStart,
+                    line_feeds_after: 0,
+                    column: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pending_marks_skip_escape_sequences() {
+        let mut pending = PendingPromptMarks::default();
+        let mut parser = SemanticPromptParser::new();
+        let bytes =
+            "\x1b]0;title\x07\x1b[1;32muser\x1b[0m \x1b(Bcafé\tx\x1bP$qm\x1b\\$ \x1b]133;B\x07";
+        pending.scan(bytes.as_bytes(), parser.feed(bytes.as_bytes()));
+        assert_eq!(
+            pending.drain(),
+            vec![PendingPromptMark {
+                kind: PromptMarkKind::CommandStart,
+                line_feeds_after: 0,
+                column: 19,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_blocks_keep_their_lines_once_scrollback_is_full() {
+        let config = Config {
+            scrolling_history: 10,
+            ..Config::default()
+        };
+        let mut term = Term::new(config, &TermSize::new(20, 5), VoidListener);
+        let mut processor = Processor::<StdSyncHandler>::new();
+        let mut tracker = ScrollTracker::default();
+        let mut blocks = CommandBlocks::default();
+
+        let mut output =
+            |term: &mut Term<VoidListener>, tracker: &mut ScrollTracker, text: &str| {
+                processor.advance(term, text.as_bytes());
+                tracker.update(term);
+            };
+        let record = |blocks: &mut CommandBlocks,
+                      term: &Term<VoidListener>,
+                      tracker: &ScrollTracker,
+                      kind| {
+            let cursor = term.grid().cursor.point;
+            blocks.record(kind, tracker.absolute_line(cursor.line), cursor.column.0);
+        };
+
+        for command in 0..4 {
+            record(&mut blocks, &term, &tracker, PromptMarkKind::PromptStart);
+            output(&mut term, &mut tracker, &format!("$ cmd{command}\r\n"));
+            record(&mut blocks, &term, &tracker, PromptMarkKind::OutputStart);
+            for line in 0..8 {
+                output(&mut term, &mut tracker, &format!("out{command}-{line}\r\n"));
+            }
+            record(
+                &mut blocks,
+                &term,
+                &tracker,
+                PromptMarkKind::CommandFinished { exit_code: Some(0) },
+            );
+        }
+
+        // Each command took 9 lines, and the scrollback stopped growing after 10.
+        assert_eq!(term.history_size(), 10);
+        assert_eq!(tracker.scrolled_lines(), 32);
+        assert_eq!(
+            blocks
+                .iter()
+                .map(|block| block.prompt_line)
+                .collect::<Vec<_>>(),
+            vec![0, 9, 18, 27]
+        );
+
+        let line_text = |line: i64| {
+            let line = Line((line - tracker.scrolled_lines()) as i32);
+            (0..term.columns())
+                .map(|column| term.grid()[line][Column(column)].c)
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        };
+        assert_eq!(line_text(27), "$ cmd3");
+        assert_eq!(line_text(28), "out3-0");
+
+        blocks.prune(tracker.first_line(term.history_size()));
+        assert_eq!(
+            blocks
+                .iter()
+                .map(|block| block.prompt_line)
+                .collect::<Vec<_>>(),
+            vec![18, 27]
+        );
+    }
+}
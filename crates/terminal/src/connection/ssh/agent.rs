@@ -0,0 +1,34 @@
+//! Agent forwarding: `auth-agent@openssh.com` channels opened by the remote host are
+//! proxied to the local SSH agent, so keys never leave this machine.
+
+use anyhow::{Context as _, Result};
+use russh::Channel;
+use russh::client::Msg;
+
+/// Relays one forwarded agent connection until either side closes it.
+pub(super) async fn proxy_to_local_agent(channel: Channel<Msg>) -> Result<()> {
+    let mut agent = connect_to_local_agent().await?;
+    let mut stream = channel.into_stream();
+    tokio::io::copy_bidirectional(&mut stream, &mut agent)
+        .await
+        .context("forwarding the SSH agent")?;
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn connect_to_local_agent() -> Result<tokio::net::UnixStream> {
+    let socket = std::env::var_os("SSH_AUTH_SOCK").context("SSH_AUTH_SOCK is not set")?;
+    tokio::net::UnixStream::connect(&socket)
+        .await
+        .with_context(|| format!("connecting to the SSH agent at {}", socket.display()))
+}
+
+#[cfg(windows)]
+async fn connect_to_local_agent() -> Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    const OPENSSH_AGENT_PIPE: &str = r"\\.\pipe\openssh-ssh-agent";
+
+    let pipe = std::env::var("SSH_AUTH_SOCK").unwrap_or_else(|_| OPENSSH_AGENT_PIPE.to_string());
+    tokio::net::windows::named_pipe::ClientOptions::new()
+        .open(&pipe)
+        .with_context(|| format!("connecting to the SSH agent at {pipe}"))
+}
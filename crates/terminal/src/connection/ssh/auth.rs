@@ -1,19 +1,23 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context as _, Result};
 use russh::client::{AuthResult, Handle};
-use russh::keys::{PrivateKey, PrivateKeyWithHashAlg};
+use russh::keys::{Certificate, PrivateKey, PrivateKeyWithHashAlg};
 
 /// SSH authentication configuration.
 #[derive(Clone, Debug)]
 pub enum SshAuthConfig {
     /// Use password authentication.
     Password(String),
-    /// Use private key file for authentication.
+    /// Use private key file for authentication. An OpenSSH certificate for the
+    /// key is offered first: `certificate` if given, otherwise `<key>-cert.pub`
+    /// next to the key.
     PrivateKey {
         path: PathBuf,
         passphrase: Option<String>,
+        certificate: Option<PathBuf>,
     },
     /// Try authentication methods in order: keys -> password prompt.
     Auto,
@@ -23,6 +27,7 @@ pub enum SshAuthConfig {
 #[derive(Debug)]
 pub enum SshAuthMethod {
     PrivateKey(PathBuf),
+    Certificate { key: PathBuf, certificate: PathBuf },
     Password,
     None,
 }
@@ -38,9 +43,19 @@ pub async fn authenticate<H: russh::client::Handler>(
             authenticate_with_password(session, username, password).await?;
             Ok(SshAuthMethod::Password)
         }
-        SshAuthConfig::PrivateKey { path, passphrase } => {
-            authenticate_with_key(session, username, path, passphrase.as_deref()).await?;
-            Ok(SshAuthMethod::PrivateKey(path.clone()))
+        SshAuthConfig::PrivateKey {
+            path,
+            passphrase,
+            certificate,
+        } => {
+            authenticate_with_key(
+                session,
+                username,
+                path,
+                passphrase.as_deref(),
+                certificate.as_deref(),
+            )
+            .await
         }
         SshAuthConfig::Auto => authenticate_auto(session, username).await,
     }
@@ -64,18 +79,94 @@ async fn authenticate_with_key<H: russh::client::Handler>(
     username: &str,
     key_path: &PathBuf,
     passphrase: Option<&str>,
-) -> Result<()> {
-    let key_pair: PrivateKey = russh::keys::load_secret_key(key_path, passphrase)
-        .context("failed to load private key")?;
+    certificate_path: Option<&Path>,
+) -> Result<SshAuthMethod> {
+    let key_pair: PrivateKey =
+        russh::keys::load_secret_key(key_path, passphrase).context("failed to load private key")?;
+    let key_pair = Arc::new(key_pair);
+
+    if let Some((certificate_path, certificate)) = load_certificate(key_path, certificate_path) {
+        match session
+            .authenticate_openssh_cert(username, key_pair.clone(), certificate)
+            .await
+        {
+            Ok(AuthResult::Success) => {
+                return Ok(SshAuthMethod::Certificate {
+                    key: key_path.clone(),
+                    certificate: certificate_path,
+                });
+            }
+            Ok(AuthResult::Failure { .. }) => log::info!(
+                "certificate {} was rejected, trying the plain key",
+                certificate_path.display()
+            ),
+            Err(error) => log::warn!(
+                "certificate authentication with {} failed: {error}",
+                certificate_path.display()
+            ),
+        }
+    }
 
-    let key_with_hash = PrivateKeyWithHashAlg::new(Arc::new(key_pair), None);
+    let key_with_hash = PrivateKeyWithHashAlg::new(key_pair, None);
 
     let result = session
         .authenticate_publickey(username, key_with_hash)
         .await
         .context("public key authentication failed")?;
 
-    check_auth_result(result, "public key")
+    check_auth_result(result, "public key")?;
+    Ok(SshAuthMethod::PrivateKey(key_path.clone()))
+}
+
+/// Where the OpenSSH certificate for a private key is: the configured file, or
+/// `id_ed25519-cert.pub` for `id_ed25519`.
+fn certificate_path(key_path: &Path, configured: Option<&Path>) -> Option<PathBuf> {
+    if let Some(configured) = configured {
+        return Some(configured.to_path_buf());
+    }
+    let mut file_name = key_path.file_name()?.to_os_string();
+    file_name.push("-cert.pub");
+    Some(key_path.with_file_name(file_name))
+}
+
+/// The OpenSSH certificate for a private key, if there is one that hasn't expired.
+fn load_certificate(key_path: &Path, configured: Option<&Path>) -> Option<(PathBuf, Certificate)> {
+    let certificate_path = certificate_path(key_path, configured)?;
+    if !certificate_path.exists() {
+        if configured.is_some() {
+            log::warn!(
+                "certificate {} doesn't exist, skipping it",
+                certificate_path.display()
+            );
+        }
+        return None;
+    }
+
+    let certificate = match russh::keys::load_openssh_certificate(&certificate_path) {
+        Ok(certificate) => certificate,
+        Err(error) => {
+            log::warn!(
+                "failed to load certificate {}: {error}",
+                certificate_path.display()
+            );
+            return None;
+        }
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    if !certificate_is_current(certificate.valid_after(), certificate.valid_before(), now) {
+        log::warn!(
+            "certificate {} is not valid now, skipping it",
+            certificate_path.display()
+        );
+        return None;
+    }
+    Some((certificate_path, certificate))
+}
+
+fn certificate_is_current(valid_after: u64, valid_before: u64, now: u64) -> bool {
+    (valid_after..valid_before).contains(&now)
 }
 
 fn check_auth_result(result: AuthResult, method_name: &str) -> Result<()> {
@@ -107,8 +198,8 @@ async fn authenticate_auto<H: russh::client::Handler>(
     username: &str,
 ) -> Result<SshAuthMethod> {
     for key_path in find_default_ssh_keys() {
-        if let Ok(()) = authenticate_with_key(session, username, &key_path, None).await {
-            return Ok(SshAuthMethod::PrivateKey(key_path));
+        if let Ok(method) = authenticate_with_key(session, username, &key_path, None, None).await {
+            return Ok(method);
         }
     }
 
@@ -128,3 +219,39 @@ fn find_default_ssh_keys() -> Vec<PathBuf> {
     }
     keys
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_certificate_validity_window() {
+        assert!(certificate_is_current(100, 200, 100));
+        assert!(certificate_is_current(100, 200, 199));
+        assert!(!certificate_is_current(100, 200, 200));
+        assert!(!certificate_is_current(100, 200, 99));
+        // OpenSSH uses `u64::MAX` for certificates that never expire.
+        assert!(certificate_is_current(0, u64::MAX, 1_700_000_000));
+    }
+
+    #[test]
+    fn test_certificate_path() {
+        let key_path = Path::new("/home/user/.ssh/id_ed25519");
+        assert_eq!(
+            certificate_path(key_path, None),
+            Some(PathBuf::from("/home/user/.ssh/id_ed25519-cert.pub"))
+        );
+        assert_eq!(
+            certificate_path(key_path, Some(Path::new("/etc/ssh/user-cert.pub"))),
+            Some(PathBuf::from("/etc/ssh/user-cert.pub"))
+        );
+    }
+
+    #[test]
+    fn test_missing_certificate_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = dir.path().join("id_ed25519");
+        assert!(load_certificate(&key_path, None).is_none());
+        assert!(load_certificate(&key_path, Some(&dir.path().join("user-cert.pub"))).is_none());
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock, Weak};
 
 use anyhow::Result;
 use parking_lot::RwLock;
@@ -8,8 +8,11 @@ use super::{SshConfig, SshHostKey};
 
 /// Manages SSH sessions, allowing connection reuse across terminal, SFTP, and tunnels.
 /// Uses a hub-and-spoke model where sessions are pooled by host.
+///
+/// The pool doesn't keep sessions alive: a host is disconnected from once the last
+/// terminal or tunnel using its session goes away.
 pub struct SshSessionManager {
-    sessions: RwLock<collections::HashMap<SshHostKey, Arc<SshSession>>>,
+    sessions: RwLock<collections::HashMap<SshHostKey, Weak<SshSession>>>,
 }
 
 impl SshSessionManager {
@@ -19,19 +22,26 @@ impl SshSessionManager {
         }
     }
 
+    /// The pool shared by terminals and tunnels to remote debug servers, so that they use
+    /// one connection per host.
+    pub fn global() -> &'static Self {
+        static GLOBAL: LazyLock<SshSessionManager> = LazyLock::new(SshSessionManager::new);
+        &GLOBAL
+    }
+
     /// Get an existing session or create a new one for the given configuration.
     /// If an existing connected session exists for this host, it will be reused.
     pub async fn get_or_create_session(&self, config: &SshConfig) -> Result<Arc<SshSession>> {
         let key = SshHostKey::from(config);
 
-        if let Some(session) = self.sessions.read().get(&key) {
-            if session.is_connected() {
-                return Ok(session.clone());
-            }
+        if let Some(session) = self.get_session(&key) {
+            return Ok(session);
         }
 
         let session = SshSession::connect(config).await?;
-        self.sessions.write().insert(key, session.clone());
+        let mut sessions = self.sessions.write();
+        sessions.retain(|_, session| session.strong_count() > 0);
+        sessions.insert(key, Arc::downgrade(&session));
         Ok(session)
     }
 
@@ -45,20 +55,27 @@ impl SshSessionManager {
         self.sessions
             .read()
             .get(host_key)
+            .and_then(Weak::upgrade)
             .filter(|s| s.is_connected())
-            .cloned()
     }
 
     /// Close and remove all sessions.
     pub async fn close_all(&self) {
         let sessions: Vec<_> = self.sessions.write().drain().collect();
-        for (_, session) in sessions {
+        for session in sessions
+            .into_iter()
+            .filter_map(|(_, session)| session.upgrade())
+        {
             session.close().await;
         }
     }
 
     /// Get the number of active sessions.
     pub fn session_count(&self) -> usize {
-        self.sessions.read().len()
+        self.sessions
+            .read()
+            .values()
+            .filter(|session| session.strong_count() > 0)
+            .count()
     }
 }
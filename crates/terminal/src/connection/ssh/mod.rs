@@ -1,12 +1,15 @@
+mod agent;
 mod auth;
 mod manager;
 mod session;
 mod terminal;
+mod tunnel;
 
 pub use auth::{SshAuthConfig, SshAuthMethod};
 pub use manager::SshSessionManager;
 pub use session::SshSession;
 pub use terminal::SshTerminalConnection;
+pub use tunnel::start_forwarded_server;
 
 use std::hash::Hash;
 
@@ -20,6 +23,9 @@ pub struct SshConfig {
     pub env: collections::HashMap<String, String>,
     pub keepalive_interval: Option<std::time::Duration>,
     pub initial_command: Option<String>,
+    /// Whether to forward the local SSH agent, so the remote host can use its keys
+    /// to connect onward.
+    pub agent_forwarding: bool,
 }
 
 impl SshConfig {
@@ -32,6 +38,7 @@ impl SshConfig {
             env: collections::HashMap::default(),
             keepalive_interval: Some(std::time::Duration::from_secs(30)),
             initial_command: None,
+            agent_forwarding: false,
         }
     }
 
@@ -59,6 +66,11 @@ impl SshConfig {
         self.initial_command = Some(command.into());
         self
     }
+
+    pub fn with_agent_forwarding(mut self, agent_forwarding: bool) -> Self {
+        self.agent_forwarding = agent_forwarding;
+        self
+    }
 }
 
 /// Identifies a unique SSH host for session reuse.
@@ -1,25 +1,39 @@
+use std::path::Path;
 use std::sync::Arc;
 
 use alacritty_terminal::event::WindowSize;
-use anyhow::{Context as _, Result};
+use anyhow::{Context as _, Result, bail};
+use collections::HashSet;
 use gpui::Task;
-use parking_lot::RwLock;
-use russh::client::{Config, Handle};
-use russh::ChannelId;
+use parking_lot::{Mutex, RwLock};
+use russh::client::{Config, Handle, Msg, Session};
+use russh::{Channel, ChannelId};
+use russh_sftp::client::SftpSession;
+use smol::io::AsyncWriteExt as _;
+use tokio::io::AsyncReadExt as _;
 use tokio::sync::RwLock as TokioRwLock;
 
-use super::auth::{authenticate, SshAuthMethod};
+use super::agent;
+use super::auth::{SshAuthMethod, authenticate};
 use super::{SshConfig, SshHostKey};
 use crate::connection::ConnectionState;
 
+/// The open channels that requested agent forwarding.
+///
+/// The server doesn't say which channel an agent channel it opens is for, so agent
+/// channels are accepted while any of these channels is open, and refused otherwise.
+type AgentForwardingChannels = Arc<Mutex<HashSet<ChannelId>>>;
+
 struct SshClientHandler {
     host_key_verified: bool,
+    agent_forwarding: AgentForwardingChannels,
 }
 
 impl SshClientHandler {
-    fn new() -> Self {
+    fn new(agent_forwarding: AgentForwardingChannels) -> Self {
         Self {
             host_key_verified: false,
+            agent_forwarding,
         }
     }
 }
@@ -34,6 +48,36 @@ impl russh::client::Handler for SshClientHandler {
         self.host_key_verified = true;
         async { Ok(true) }
     }
+
+    fn server_channel_open_agent_forward(
+        &mut self,
+        channel: Channel<Msg>,
+        _session: &mut Session,
+    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
+        let enabled = !self.agent_forwarding.lock().is_empty();
+        async move {
+            if !enabled {
+                log::warn!("refusing an agent channel the SSH server opened without being asked");
+                channel.close().await.ok();
+                return Ok(());
+            }
+            tokio::spawn(async move {
+                if let Err(error) = agent::proxy_to_local_agent(channel).await {
+                    log::warn!("SSH agent forwarding failed: {error:#}");
+                }
+            });
+            Ok(())
+        }
+    }
+
+    fn channel_close(
+        &mut self,
+        channel: ChannelId,
+        _session: &mut Session,
+    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
+        self.agent_forwarding.lock().remove(&channel);
+        async { Ok(()) }
+    }
 }
 
 /// An established SSH session to a remote host.
@@ -45,6 +89,7 @@ pub struct SshSession {
     #[allow(dead_code)]
     keepalive_task: Option<Task<()>>,
     auth_method: SshAuthMethod,
+    agent_forwarding: AgentForwardingChannels,
 }
 
 impl SshSession {
@@ -56,7 +101,8 @@ impl SshSession {
         });
 
         let addr = format!("{}:{}", config.host, config.port);
-        let handler = SshClientHandler::new();
+        let agent_forwarding = AgentForwardingChannels::default();
+        let handler = SshClientHandler::new(agent_forwarding.clone());
 
         let mut handle = russh::client::connect(ssh_config, &addr, handler)
             .await
@@ -81,6 +127,7 @@ impl SshSession {
             state: RwLock::new(ConnectionState::Connected),
             keepalive_task: None,
             auth_method,
+            agent_forwarding,
         });
 
         Ok(session)
@@ -102,11 +149,13 @@ impl SshSession {
         &self.auth_method
     }
 
-    /// Open a new terminal channel with a PTY.
+    /// Open a new terminal channel with a PTY, optionally forwarding the local SSH
+    /// agent to it.
     pub async fn open_terminal_channel(
         &self,
         initial_size: WindowSize,
         env: &collections::HashMap<String, String>,
+        agent_forwarding: bool,
     ) -> Result<SshChannel> {
         let handle_guard = self.handle.read().await;
         let handle = handle_guard
@@ -126,6 +175,14 @@ impl SshSession {
             }
         }
 
+        if agent_forwarding {
+            self.agent_forwarding.lock().insert(channel_id);
+            if let Err(error) = channel.agent_forward(true).await {
+                self.agent_forwarding.lock().remove(&channel_id);
+                log::warn!("Failed to request SSH agent forwarding: {}", error);
+            }
+        }
+
         channel
             .request_pty(
                 true,
@@ -150,6 +207,113 @@ impl SshSession {
         })
     }
 
+    /// Run `command` on the remote host without a PTY. Its output arrives as data on
+    /// the returned channel, followed by its exit status.
+    pub async fn exec(&self, command: &str) -> Result<SshChannel> {
+        let handle_guard = self.handle.read().await;
+        let handle = handle_guard
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("SSH session is closed"))?;
+
+        let channel = handle
+            .channel_open_session()
+            .await
+            .context("failed to open SSH channel")?;
+        let channel_id = channel.id();
+        channel
+            .exec(true, command)
+            .await
+            .with_context(|| format!("failed to run {command:?}"))?;
+
+        Ok(SshChannel {
+            channel,
+            channel_id,
+        })
+    }
+
+    /// Copy the file at `remote_path` on the remote host to `local_path` over SFTP.
+    pub async fn download(&self, remote_path: &str, local_path: &Path) -> Result<()> {
+        let channel = {
+            let handle_guard = self.handle.read().await;
+            let handle = handle_guard
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("SSH session is closed"))?;
+            handle
+                .channel_open_session()
+                .await
+                .context("failed to open SSH channel")?
+        };
+        channel
+            .request_subsystem(true, "sftp")
+            .await
+            .context("failed to start SFTP on the SSH host")?;
+        let sftp = SftpSession::new(channel.into_stream())
+            .await
+            .context("failed to start SFTP on the SSH host")?;
+
+        let mut remote = sftp
+            .open(remote_path)
+            .await
+            .with_context(|| format!("failed to open {remote_path}"))?;
+        let mut file = smol::fs::File::create(local_path)
+            .await
+            .with_context(|| format!("failed to create {local_path:?}"))?;
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let read = remote
+                .read(&mut buffer)
+                .await
+                .with_context(|| format!("failed to read {remote_path}"))?;
+            if read == 0 {
+                break;
+            }
+            file.write_all(&buffer[..read]).await?;
+        }
+        file.flush().await?;
+        sftp.close().await.ok();
+        Ok(())
+    }
+
+    /// Asks the remote host for a TCP port that's free on its loopback interface.
+    ///
+    /// The server binds port 0 for a remote forward and reports the port it got. The forward
+    /// is cancelled right away, leaving the port for a server started on the host.
+    pub async fn free_remote_port(&self) -> Result<u16> {
+        let handle_guard = self.handle.read().await;
+        let handle = handle_guard
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("SSH session is closed"))?;
+
+        let port = handle
+            .tcpip_forward("127.0.0.1", 0)
+            .await
+            .context("failed to ask the SSH host for a free port")?;
+        handle
+            .cancel_tcpip_forward("127.0.0.1", port)
+            .await
+            .context("failed to release the SSH host's free port")?;
+        u16::try_from(port).context("the SSH host reported an invalid port")
+    }
+
+    /// Open a channel to `host:port` as seen from the remote host.
+    pub async fn open_direct_tcpip(&self, host: &str, port: u16) -> Result<SshChannel> {
+        let handle_guard = self.handle.read().await;
+        let handle = handle_guard
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("SSH session is closed"))?;
+
+        let channel = handle
+            .channel_open_direct_tcpip(host, port as u32, "127.0.0.1", 0)
+            .await
+            .with_context(|| format!("failed to forward a connection to {host}:{port}"))?;
+        let channel_id = channel.id();
+
+        Ok(SshChannel {
+            channel,
+            channel_id,
+        })
+    }
+
     pub async fn close(&self) {
         *self.state.write() = ConnectionState::Disconnected;
         if let Some(handle) = self.handle.write().await.take() {
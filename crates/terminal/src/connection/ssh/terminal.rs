@@ -44,7 +44,7 @@ impl SshTerminalConnection {
         let state = Arc::new(RwLock::new(ConnectionState::Connecting));
 
         let channel = session
-            .open_terminal_channel(initial_size, &config.env)
+            .open_terminal_channel(initial_size, &config.env, config.agent_forwarding)
             .await?;
 
         let (command_tx, command_rx) = unbounded();
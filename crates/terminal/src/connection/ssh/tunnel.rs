@@ -0,0 +1,114 @@
+//! Tunnels to servers started on the remote host, such as `gdbserver` for remote debugging.
+
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context as _, Result, bail};
+use futures::future::{self, Either};
+use russh::{Channel, ChannelMsg, client::Msg};
+use tokio::net::TcpListener;
+
+use super::session::SshSession;
+
+/// How long to wait for whatever uses the tunnel to connect to it.
+const ACCEPT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long to keep retrying the connection to the server while it starts up.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Runs `command` on the remote host, which should start a server listening on
+/// `127.0.0.1:remote_port` there, and forwards a local port to it over the session.
+///
+/// Returns the local port right away. The forward accepts a single connection and passes
+/// it on once the server accepts connections. It's torn down together with the server
+/// when either side goes away, disconnecting from the host if nothing else uses the
+/// session. Must be called on a Tokio runtime.
+pub async fn start_forwarded_server(
+    session: Arc<SshSession>,
+    command: String,
+    remote_port: u16,
+) -> Result<u16> {
+    let mut server = session.exec(&command).await?;
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .context("failed to bind a local port for the SSH tunnel")?;
+    let local_port = listener.local_addr()?.port();
+
+    tokio::spawn(async move {
+        {
+            let forward = pin!(forward_connection(listener, &session, remote_port));
+            let server_output = pin!(read_output(&mut server.channel));
+            match future::select(forward, server_output).await {
+                Either::Left((Err(error), _)) => log::error!("SSH tunnel failed: {error:#}"),
+                Either::Left((Ok(()), _)) => {}
+                Either::Right(((Some(0), _), _)) => log::info!("{command:?} exited"),
+                Either::Right(((exit_status, output), _)) => log::error!(
+                    "{command:?} exited with status {exit_status:?}: {}",
+                    String::from_utf8_lossy(&output).trim()
+                ),
+            }
+        }
+
+        // Hang up on the server in case it's still running.
+        server.channel.close().await.ok();
+        if Arc::strong_count(&session) == 1 {
+            session.close().await;
+        }
+    });
+
+    Ok(local_port)
+}
+
+/// Accepts a single connection and forwards it to the server, retrying while the server
+/// starts listening.
+async fn forward_connection(
+    listener: TcpListener,
+    session: &SshSession,
+    remote_port: u16,
+) -> Result<()> {
+    let accept = pin!(listener.accept());
+    let (mut stream, _) = match future::select(accept, smol::Timer::after(ACCEPT_TIMEOUT)).await {
+        Either::Left((accepted, _)) => accepted?,
+        Either::Right(_) => bail!("nothing connected to the SSH tunnel"),
+    };
+
+    let started_at = Instant::now();
+    let channel = loop {
+        match session.open_direct_tcpip("127.0.0.1", remote_port).await {
+            Ok(channel) => break channel,
+            Err(error) if started_at.elapsed() < CONNECT_TIMEOUT => {
+                log::debug!("waiting for the remote server to listen: {error:#}");
+                smol::Timer::after(CONNECT_RETRY_INTERVAL).await;
+            }
+            Err(error) => return Err(error),
+        }
+    };
+
+    let mut remote = channel.channel.into_stream();
+    tokio::io::copy_bidirectional(&mut stream, &mut remote)
+        .await
+        .context("failed to forward to the remote server")?;
+    Ok(())
+}
+
+/// Collects the server's output until it exits, returning its exit status and output.
+async fn read_output(channel: &mut Channel<Msg>) -> (Option<u32>, Vec<u8>) {
+    let mut exit_status = None;
+    let mut output = Vec::new();
+    while let Some(message) = channel.wait().await {
+        match message {
+            ChannelMsg::Data { data } | ChannelMsg::ExtendedData { data, .. } => {
+                log::debug!("{}", String::from_utf8_lossy(&data).trim_end());
+                output.extend_from_slice(&data);
+            }
+            ChannelMsg::ExitStatus {
+                exit_status: status,
+            } => exit_status = Some(status),
+            _ => {}
+        }
+    }
+    (exit_status, output)
+}
@@ -0,0 +1,51 @@
+//! Checksums used by the XMODEM family of protocols.
+
+/// Continues a CRC-16/XMODEM computation (polynomial 0x1021, no reflection).
+pub fn crc16_update(mut crc: u16, bytes: &[u8]) -> u16 {
+    for &byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+pub fn crc16(bytes: &[u8]) -> u16 {
+    crc16_update(0, bytes)
+}
+
+/// Continues an IEEE 802.3 CRC-32 computation. Start from `!0` and invert the result.
+pub fn crc32_update(mut crc: u32, bytes: &[u8]) -> u32 {
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !crc32_update(!0, bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_values() {
+        assert_eq!(crc16(b"123456789"), 0x31C3);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc16_update(crc16(b"1234"), b"56789"), crc16(b"123456789"));
+    }
+}
@@ -0,0 +1,370 @@
+//! File transfers over a terminal's byte stream.
+//!
+//! ZMODEM sessions are started by the remote side running `sz` or `rz`; the terminal
+//! spots the session's opening header in the output of any connection and takes the
+//! stream over until the transfer ends. XMODEM and YMODEM sends are started by the
+//! user, typically to feed firmware to a bootloader over a serial or Telnet console.
+//!
+//! The protocol engines don't do any IO of their own towards the terminal: they consume
+//! bytes read from the connection and produce bytes to write to it. [`run_transfer`]
+//! drives an engine on a background task.
+
+mod crc;
+mod tap;
+pub mod xmodem;
+pub mod zmodem;
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use futures::{
+    FutureExt as _, StreamExt as _,
+    channel::mpsc::{UnboundedReceiver, UnboundedSender},
+};
+use gpui::BackgroundExecutor;
+
+pub use tap::{TransferPty, TransferTap};
+pub use xmodem::{XmodemSender, XmodemVariant};
+pub use zmodem::{ZmodemReceiver, ZmodemSender};
+
+/// How long to wait for the remote side before an engine retries or gives up.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Minimum time between progress updates sent to the terminal.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Eight CANs to abort the remote side, followed by backspaces to erase them from a
+/// shell's input line if the remote side had already stopped listening.
+pub const CANCEL_SEQUENCE: &[u8] = &[
+    0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferProtocol {
+    Zmodem,
+    Xmodem,
+    Xmodem1k,
+    Ymodem,
+}
+
+impl TransferProtocol {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TransferProtocol::Zmodem => "ZMODEM",
+            TransferProtocol::Xmodem => "XMODEM",
+            TransferProtocol::Xmodem1k => "XMODEM-1K",
+            TransferProtocol::Ymodem => "YMODEM",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferDirection {
+    /// Files go from this machine to the remote side.
+    Send,
+    /// Files come from the remote side to this machine.
+    Receive,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferProgress {
+    pub protocol: TransferProtocol,
+    pub direction: TransferDirection,
+    /// The file currently being transferred.
+    pub file_name: Option<String>,
+    /// Bytes of the current file transferred so far.
+    pub bytes_transferred: u64,
+    /// Size of the current file, if the sender announced it.
+    pub total_bytes: Option<u64>,
+    pub files_completed: usize,
+}
+
+impl TransferProgress {
+    pub fn new(protocol: TransferProtocol, direction: TransferDirection) -> Self {
+        Self {
+            protocol,
+            direction,
+            file_name: None,
+            bytes_transferred: 0,
+            total_bytes: None,
+            files_completed: 0,
+        }
+    }
+
+    /// Progress through the current file, between 0 and 1.
+    pub fn fraction(&self) -> Option<f32> {
+        let total = self.total_bytes.filter(|total| *total > 0)?;
+        Some((self.bytes_transferred as f64 / total as f64).min(1.) as f32)
+    }
+
+    fn start_file(&mut self, file_name: String, total_bytes: Option<u64>) {
+        self.file_name = Some(file_name);
+        self.bytes_transferred = 0;
+        self.total_bytes = total_bytes;
+    }
+}
+
+/// What the remote side asked for when it opened a ZMODEM session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZmodemRequest {
+    /// The remote side ran `sz` and sent ZRQINIT; we receive.
+    Receive,
+    /// The remote side ran `rz` and sent ZRINIT; we send files the user picks.
+    Send,
+}
+
+/// A ZMODEM session start found in terminal output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZmodemDetection {
+    pub request: ZmodemRequest,
+    /// Offset in the chunk where the header starts. Bytes before it belong to the emulator.
+    /// The header may have begun in an earlier chunk, in which case this is 0.
+    pub start: usize,
+    /// Offset just past the header's frame type.
+    pub end: usize,
+}
+
+impl ZmodemDetection {
+    /// The bytes the transfer engine should start from: the complete header prefix
+    /// followed by whatever came after it in `chunk`.
+    pub fn transfer_input(&self, chunk: &[u8]) -> Vec<u8> {
+        let frame_type = match self.request {
+            ZmodemRequest::Receive => b'0',
+            ZmodemRequest::Send => b'1',
+        };
+        let mut input = b"**\x18B0".to_vec();
+        input.push(frame_type);
+        input.extend_from_slice(&chunk[self.end..]);
+        input
+    }
+}
+
+/// Scans terminal output for the hex ZRQINIT (`**\x18B00`) or ZRINIT (`**\x18B01`)
+/// header that `sz` and `rz` send when they start.
+#[derive(Default)]
+pub struct ZmodemDetector {
+    matched: usize,
+}
+
+impl ZmodemDetector {
+    pub fn reset(&mut self) {
+        self.matched = 0;
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) -> Option<ZmodemDetection> {
+        // Where the header's first asterisk is, if it's in this chunk.
+        let mut start = 0;
+        for (ix, &byte) in chunk.iter().enumerate() {
+            self.matched = match (self.matched, byte) {
+                (0, b'*') => {
+                    start = ix;
+                    1
+                }
+                (1, b'*') => 2,
+                // Senders may pad with more than two asterisks.
+                (2, b'*') => 2,
+                (2, 0x18) => 3,
+                (3, b'B') => 4,
+                (4, b'0') => 5,
+                (5, b'0' | b'1') => {
+                    self.matched = 0;
+                    let end = ix + 1;
+                    return Some(ZmodemDetection {
+                        request: if byte == b'0' {
+                            ZmodemRequest::Receive
+                        } else {
+                            ZmodemRequest::Send
+                        },
+                        start,
+                        end,
+                    });
+                }
+                (_, b'*') => {
+                    start = ix;
+                    1
+                }
+                _ => 0,
+            };
+        }
+        None
+    }
+}
+
+/// Status an engine reports after handling input or a timeout.
+#[derive(Debug, PartialEq, Eq)]
+pub enum EngineStatus {
+    Running,
+    /// The session is over. `remainder` is output that followed it, which belongs to
+    /// the emulator again.
+    Finished {
+        remainder: Vec<u8>,
+    },
+}
+
+/// A sans-IO implementation of one side of a transfer protocol.
+pub trait TransferEngine: Send {
+    fn progress(&self) -> TransferProgress;
+
+    /// Handles bytes read from the connection, appending any reply to `output`.
+    fn receive(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<EngineStatus>;
+
+    /// Appends more outgoing data if the protocol allows sending without waiting for
+    /// the remote side. Returns whether it should be called again right away.
+    fn poll_send(&mut self, _output: &mut Vec<u8>) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Called when the remote side has been quiet for [`RESPONSE_TIMEOUT`].
+    fn timeout(&mut self, output: &mut Vec<u8>) -> Result<EngineStatus>;
+
+    /// Appends whatever aborts the session on the remote side.
+    fn cancel(&mut self, output: &mut Vec<u8>) {
+        output.extend_from_slice(CANCEL_SEQUENCE);
+    }
+}
+
+pub enum TransferInput {
+    Data(Vec<u8>),
+    Cancel,
+}
+
+pub enum TransferUpdate {
+    /// Bytes to write to the connection.
+    Output(Vec<u8>),
+    Progress(TransferProgress),
+    Finished {
+        result: Result<TransferProgress, String>,
+        /// Output that followed the end of the session.
+        remainder: Vec<u8>,
+        /// The input channel, so output queued after the engine stopped reading can be
+        /// handed back to the emulator.
+        input: UnboundedReceiver<TransferInput>,
+    },
+}
+
+enum Step {
+    Input(Option<TransferInput>),
+    Timeout,
+    Continue,
+}
+
+/// Runs `engine` until the session finishes, fails or is cancelled.
+pub async fn run_transfer(
+    mut engine: Box<dyn TransferEngine>,
+    mut input: UnboundedReceiver<TransferInput>,
+    updates: UnboundedSender<TransferUpdate>,
+    executor: BackgroundExecutor,
+) {
+    let mut reported_progress = engine.progress();
+    let mut reported_at = Instant::now();
+    loop {
+        let mut output = Vec::new();
+        let status = match engine.poll_send(&mut output) {
+            Ok(more) => {
+                let step = if more {
+                    match input.try_next() {
+                        Ok(next) => Step::Input(next),
+                        Err(_) => Step::Continue,
+                    }
+                } else {
+                    send_output(&updates, &mut output);
+                    let mut timer = executor.timer(RESPONSE_TIMEOUT).fuse();
+                    futures::select_biased! {
+                        next = input.next() => Step::Input(next),
+                        _ = timer => Step::Timeout,
+                    }
+                };
+                match step {
+                    Step::Input(Some(TransferInput::Data(data))) => {
+                        engine.receive(&data, &mut output)
+                    }
+                    Step::Input(Some(TransferInput::Cancel) | None) => Err(anyhow!("cancelled")),
+                    Step::Timeout => engine.timeout(&mut output),
+                    Step::Continue => Ok(EngineStatus::Running),
+                }
+            }
+            Err(error) => Err(error),
+        };
+
+        let progress = engine.progress();
+        let finished = !matches!(status, Ok(EngineStatus::Running));
+        if progress != reported_progress && (finished || reported_at.elapsed() >= PROGRESS_INTERVAL)
+        {
+            updates
+                .unbounded_send(TransferUpdate::Progress(progress.clone()))
+                .ok();
+            reported_progress = progress.clone();
+            reported_at = Instant::now();
+        }
+
+        match status {
+            Ok(EngineStatus::Running) => {
+                send_output(&updates, &mut output);
+                smol::future::yield_now().await;
+            }
+            Ok(EngineStatus::Finished { remainder }) => {
+                send_output(&updates, &mut output);
+                updates
+                    .unbounded_send(TransferUpdate::Finished {
+                        result: Ok(progress),
+                        remainder,
+                        input,
+                    })
+                    .ok();
+                return;
+            }
+            Err(error) => {
+                engine.cancel(&mut output);
+                send_output(&updates, &mut output);
+                updates
+                    .unbounded_send(TransferUpdate::Finished {
+                        result: Err(error.to_string()),
+                        remainder: Vec::new(),
+                        input,
+                    })
+                    .ok();
+                return;
+            }
+        }
+    }
+}
+
+fn send_output(updates: &UnboundedSender<TransferUpdate>, output: &mut Vec<u8>) {
+    if !output.is_empty() {
+        updates
+            .unbounded_send(TransferUpdate::Output(std::mem::take(output)))
+            .ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_zmodem_headers_across_chunks() {
+        let mut detector = ZmodemDetector::default();
+        assert_eq!(detector.feed(b"rz\r**"), None);
+        let chunk = b"\x18B00000000000000\r\x8a\x11";
+        let detection = detector.feed(chunk).unwrap();
+        assert_eq!(detection.request, ZmodemRequest::Receive);
+        assert_eq!(detection.start, 0);
+        assert_eq!(detection.end, 4);
+        assert_eq!(
+            detection.transfer_input(chunk),
+            b"**\x18B00000000000000\r\x8a\x11".to_vec()
+        );
+
+        let chunk = b"$ rz\r\n***\x18B0100000023be50\r\x8a\x11";
+        let detection = detector.feed(chunk).unwrap();
+        assert_eq!(detection.request, ZmodemRequest::Send);
+        assert_eq!(&chunk[..detection.start], b"$ rz\r\n");
+
+        let chunk = b"$ sz file\r\n**\x18B00000000000000\r\x8a\x11";
+        let detection = detector.feed(chunk).unwrap();
+        assert_eq!(detection.request, ZmodemRequest::Receive);
+        assert_eq!(&chunk[..detection.start], b"$ sz file\r\n");
+        assert_eq!(&chunk[detection.start..detection.end], b"**\x18B00");
+
+        assert_eq!(detector.feed(b"**\x18A00 plain output"), None);
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    io::{self, Read},
+    sync::Arc,
+};
+
+use alacritty_terminal::{
+    event::{Event as AlacTermEvent, OnResize, WindowSize},
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+};
+use futures::channel::mpsc::UnboundedSender;
+use parking_lot::Mutex;
+
+use super::{ZmodemDetector, ZmodemRequest};
+
+/// Lets a file transfer take a local PTY's output away from alacritty.
+///
+/// Alacritty's IO thread reads and parses PTY output on its own, so detection has to
+/// happen in the reader: once a ZMODEM header shows up, or the user starts a send,
+/// output is collected here and the terminal is woken up to route it to the transfer.
+pub struct TransferTap {
+    detector: ZmodemDetector,
+    detected: Option<ZmodemRequest>,
+    /// Output read while a transfer owns the stream, not yet taken by the terminal.
+    diverted: Option<Vec<u8>>,
+    events: UnboundedSender<AlacTermEvent>,
+}
+
+impl TransferTap {
+    pub fn new(events: UnboundedSender<AlacTermEvent>) -> Self {
+        Self {
+            detector: ZmodemDetector::default(),
+            detected: None,
+            diverted: None,
+            events,
+        }
+    }
+
+    /// Returns how many leading bytes of a fresh read the emulator should see.
+    fn filter(&mut self, bytes: &[u8]) -> usize {
+        if let Some(diverted) = &mut self.diverted {
+            diverted.extend_from_slice(bytes);
+            self.events.unbounded_send(AlacTermEvent::Wakeup).ok();
+            return 0;
+        }
+        let Some(detection) = self.detector.feed(bytes) else {
+            return bytes.len();
+        };
+        self.detected = Some(detection.request);
+        self.diverted = Some(detection.transfer_input(bytes));
+        self.events.unbounded_send(AlacTermEvent::Wakeup).ok();
+        detection.start
+    }
+
+    /// Starts collecting output for a transfer the user started.
+    pub fn divert(&mut self) {
+        self.diverted.get_or_insert_default();
+    }
+
+    /// The ZMODEM session the remote side started since the last call, if any.
+    pub fn take_detected(&mut self) -> Option<ZmodemRequest> {
+        self.detected.take()
+    }
+
+    pub fn take_diverted(&mut self) -> Vec<u8> {
+        self.diverted
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Hands the stream back to the emulator, returning output that hasn't been taken yet.
+    pub fn resume(&mut self) -> Vec<u8> {
+        self.detector.reset();
+        self.detected = None;
+        self.diverted.take().unwrap_or_default()
+    }
+}
+
+/// Wraps a local PTY so its output passes through a [`TransferTap`].
+pub struct TransferPty<P: EventedPty> {
+    reader: TransferReader<P>,
+}
+
+pub struct TransferReader<P: EventedPty> {
+    pty: P,
+    tap: Arc<Mutex<TransferTap>>,
+}
+
+impl<P: EventedPty> TransferPty<P> {
+    pub fn new(pty: P, tap: Arc<Mutex<TransferTap>>) -> Self {
+        Self {
+            reader: TransferReader { pty, tap },
+        }
+    }
+}
+
+impl<P: EventedPty> Read for TransferReader<P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Keep reading while everything goes to a transfer, so alacritty only stops
+        // once the PTY is drained.
+        loop {
+            let read = self.pty.reader().read(buf)?;
+            if read == 0 {
+                return Ok(0);
+            }
+            let passed = self.tap.lock().filter(&buf[..read]);
+            if passed > 0 {
+                return Ok(passed);
+            }
+        }
+    }
+}
+
+impl<P: EventedPty> EventedReadWrite for TransferPty<P> {
+    type Reader = TransferReader<P>;
+    type Writer = P::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<polling::Poller>,
+        interest: polling::Event,
+        mode: polling::PollMode,
+    ) -> io::Result<()> {
+        unsafe { self.reader.pty.register(poll, interest, mode) }
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<polling::Poller>,
+        interest: polling::Event,
+        mode: polling::PollMode,
+    ) -> io::Result<()> {
+        self.reader.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<polling::Poller>) -> io::Result<()> {
+        self.reader.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        &mut self.reader
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.reader.pty.writer()
+    }
+}
+
+impl<P: EventedPty> EventedPty for TransferPty<P> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.reader.pty.next_child_event()
+    }
+}
+
+impl<P: EventedPty + OnResize> OnResize for TransferPty<P> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.reader.pty.on_resize(window_size);
+    }
+}
@@ -0,0 +1,360 @@
+//! XMODEM, XMODEM-1K and YMODEM batch senders, for bootloaders that only accept those.
+
+use std::{fs::File, io::Read as _, path::Path, time::UNIX_EPOCH};
+
+use anyhow::{Context as _, Result, bail};
+
+use super::{
+    EngineStatus, TransferDirection, TransferEngine, TransferProgress, TransferProtocol, crc,
+};
+
+const SOH: u8 = 0x01;
+const STX: u8 = 0x02;
+const EOT: u8 = 0x04;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+const CAN: u8 = 0x18;
+/// Sent by receivers instead of NAK to ask for CRC-16 instead of the 8-bit checksum.
+const CRC_REQUEST: u8 = b'C';
+/// Pads the last block of a file.
+const SUB: u8 = 0x1a;
+
+const MAX_RETRIES: usize = 10;
+/// Receivers usually need a moment to start; wait this many timeouts for the first request.
+const MAX_START_WAITS: usize = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XmodemVariant {
+    /// 128-byte blocks.
+    Xmodem,
+    /// 1024-byte blocks.
+    Xmodem1k,
+    /// 1024-byte blocks, preceded by a block with the file's name and size.
+    Ymodem,
+}
+
+impl XmodemVariant {
+    pub fn protocol(&self) -> TransferProtocol {
+        match self {
+            XmodemVariant::Xmodem => TransferProtocol::Xmodem,
+            XmodemVariant::Xmodem1k => TransferProtocol::Xmodem1k,
+            XmodemVariant::Ymodem => TransferProtocol::Ymodem,
+        }
+    }
+
+    fn block_size(&self) -> usize {
+        match self {
+            XmodemVariant::Xmodem => 128,
+            XmodemVariant::Xmodem1k | XmodemVariant::Ymodem => 1024,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SenderState {
+    AwaitingStart,
+    /// YMODEM: block 0 with the file name is out.
+    AwaitingHeaderAck,
+    /// YMODEM: block 0 was acknowledged; the receiver asks again before the data.
+    AwaitingDataStart,
+    AwaitingBlockAck,
+    AwaitingEotAck,
+    /// YMODEM: the file is done; the receiver asks for the next file's block 0.
+    AwaitingBatchEnd,
+    /// YMODEM: the empty block 0 that ends the batch is out.
+    AwaitingBatchEndAck,
+}
+
+/// Sends one file to an XMODEM or YMODEM receiver.
+pub struct XmodemSender {
+    variant: XmodemVariant,
+    file: File,
+    file_name: String,
+    size: u64,
+    modified: u64,
+    state: SenderState,
+    use_crc: bool,
+    block_number: u8,
+    /// The last packet sent, kept for retransmission.
+    packet: Vec<u8>,
+    /// File bytes in the last data block.
+    block_len: usize,
+    retries: usize,
+    cancel_run: usize,
+    progress: TransferProgress,
+}
+
+impl XmodemSender {
+    pub fn new(path: &Path, variant: XmodemVariant) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+        let metadata = file.metadata()?;
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "upload".to_string());
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |modified| modified.as_secs());
+
+        let mut progress = TransferProgress::new(variant.protocol(), TransferDirection::Send);
+        progress.start_file(file_name.clone(), Some(metadata.len()));
+        Ok(Self {
+            variant,
+            file,
+            file_name,
+            size: metadata.len(),
+            modified,
+            state: SenderState::AwaitingStart,
+            use_crc: false,
+            block_number: 1,
+            packet: Vec::new(),
+            block_len: 0,
+            retries: 0,
+            cancel_run: 0,
+            progress,
+        })
+    }
+
+    fn build_packet(&self, number: u8, data: &[u8], block_size: usize, pad: u8) -> Vec<u8> {
+        let mut packet = Vec::with_capacity(block_size + 5);
+        packet.push(if block_size == 1024 { STX } else { SOH });
+        packet.push(number);
+        packet.push(!number);
+        packet.extend_from_slice(data);
+        packet.resize(3 + block_size, pad);
+        let block = &packet[3..];
+        if self.use_crc {
+            let crc = crc::crc16(block);
+            packet.extend_from_slice(&crc.to_be_bytes());
+        } else {
+            let checksum = block.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+            packet.push(checksum);
+        }
+        packet
+    }
+
+    /// Block 0 of a YMODEM batch: the file's name, size and modification time, or
+    /// nothing at all to end the batch.
+    fn send_batch_header(&mut self, end_of_batch: bool, output: &mut Vec<u8>) {
+        let mut info = Vec::new();
+        if !end_of_batch {
+            info.extend_from_slice(self.file_name.as_bytes());
+            info.push(0);
+            info.extend_from_slice(format!("{} {:o}", self.size, self.modified).as_bytes());
+        }
+        let block_size = if info.len() > 128 { 1024 } else { 128 };
+        info.truncate(block_size);
+        self.packet = self.build_packet(0, &info, block_size, 0);
+        output.extend_from_slice(&self.packet);
+    }
+
+    fn send_next_block(&mut self, output: &mut Vec<u8>) -> Result<()> {
+        let block_size = self.variant.block_size();
+        let mut data = vec![0; block_size];
+        let mut read = 0;
+        while read < block_size {
+            match self.file.read(&mut data[read..])? {
+                0 => break,
+                n => read += n,
+            }
+        }
+        data.truncate(read);
+
+        if data.is_empty() {
+            output.push(EOT);
+            self.packet = vec![EOT];
+            self.state = SenderState::AwaitingEotAck;
+            return Ok(());
+        }
+
+        // Short tails go out in a 128-byte block rather than padding a whole 1K block.
+        let block_size = if data.len() <= 128 { 128 } else { block_size };
+        self.block_len = data.len();
+        self.packet = self.build_packet(self.block_number, &data, block_size, SUB);
+        output.extend_from_slice(&self.packet);
+        self.state = SenderState::AwaitingBlockAck;
+        Ok(())
+    }
+
+    fn resend(&mut self, output: &mut Vec<u8>) -> Result<()> {
+        self.retries += 1;
+        if self.retries > MAX_RETRIES {
+            bail!("too many errors, giving up");
+        }
+        output.extend_from_slice(&self.packet);
+        Ok(())
+    }
+
+    /// Handles one byte from the receiver. Returns whether the transfer is over.
+    fn handle_byte(&mut self, byte: u8, output: &mut Vec<u8>) -> Result<bool> {
+        if byte == CAN {
+            self.cancel_run += 1;
+            if self.cancel_run >= 2 {
+                bail!("cancelled by the receiver");
+            }
+            return Ok(false);
+        }
+        self.cancel_run = 0;
+
+        match (self.state, byte) {
+            (SenderState::AwaitingStart, CRC_REQUEST | NAK) => {
+                self.use_crc = byte == CRC_REQUEST;
+                self.retries = 0;
+                if self.variant == XmodemVariant::Ymodem {
+                    self.send_batch_header(false, output);
+                    self.state = SenderState::AwaitingHeaderAck;
+                } else {
+                    self.send_next_block(output)?;
+                }
+            }
+            (SenderState::AwaitingHeaderAck, ACK) => {
+                self.retries = 0;
+                self.state = SenderState::AwaitingDataStart;
+            }
+            (SenderState::AwaitingDataStart, CRC_REQUEST | NAK) => self.send_next_block(output)?,
+            (SenderState::AwaitingBlockAck, ACK) => {
+                self.retries = 0;
+                self.block_number = self.block_number.wrapping_add(1);
+                self.progress.bytes_transferred += self.block_len as u64;
+                self.send_next_block(output)?;
+            }
+            (SenderState::AwaitingEotAck, ACK) => {
+                self.progress.files_completed = 1;
+                if self.variant != XmodemVariant::Ymodem {
+                    return Ok(true);
+                }
+                self.state = SenderState::AwaitingBatchEnd;
+            }
+            (SenderState::AwaitingBatchEnd, CRC_REQUEST | NAK) => {
+                self.send_batch_header(true, output);
+                self.state = SenderState::AwaitingBatchEndAck;
+            }
+            (SenderState::AwaitingBatchEndAck, ACK) => return Ok(true),
+            (
+                SenderState::AwaitingHeaderAck
+                | SenderState::AwaitingBlockAck
+                | SenderState::AwaitingEotAck
+                | SenderState::AwaitingBatchEndAck,
+                NAK,
+            )
+            | (SenderState::AwaitingHeaderAck, CRC_REQUEST) => self.resend(output)?,
+            // Anything else is line noise or bootloader chatter.
+            _ => {}
+        }
+        Ok(false)
+    }
+}
+
+impl TransferEngine for XmodemSender {
+    fn progress(&self) -> TransferProgress {
+        self.progress.clone()
+    }
+
+    fn receive(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<EngineStatus> {
+        for (ix, &byte) in input.iter().enumerate() {
+            if self.handle_byte(byte, output)? {
+                return Ok(EngineStatus::Finished {
+                    remainder: input[ix + 1..].to_vec(),
+                });
+            }
+        }
+        Ok(EngineStatus::Running)
+    }
+
+    fn timeout(&mut self, output: &mut Vec<u8>) -> Result<EngineStatus> {
+        match self.state {
+            SenderState::AwaitingStart | SenderState::AwaitingDataStart => {
+                self.retries += 1;
+                if self.retries > MAX_START_WAITS {
+                    bail!("the receiver never asked for data");
+                }
+            }
+            SenderState::AwaitingBatchEnd => {
+                // The file is through; some receivers don't bother ending the batch.
+                return Ok(EngineStatus::Finished {
+                    remainder: Vec::new(),
+                });
+            }
+            _ => self.resend(output)?,
+        }
+        Ok(EngineStatus::Running)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ymodem_send() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("boot.img");
+        std::fs::write(&path, vec![0xa5; 1100]).unwrap();
+        let mut sender = XmodemSender::new(&path, XmodemVariant::Ymodem).unwrap();
+
+        let mut output = Vec::new();
+        sender.receive(b"boot> C", &mut output).unwrap();
+        assert_eq!(output.len(), 3 + 128 + 2);
+        assert_eq!(&output[..3], &[SOH, 0, 0xff]);
+        assert!(output[3..].starts_with(b"boot.img\x001100 "));
+
+        output.clear();
+        sender.receive(&[ACK, b'C'], &mut output).unwrap();
+        assert_eq!(&output[..3], &[STX, 1, 0xfe]);
+        assert_eq!(output.len(), 3 + 1024 + 2);
+        let crc = crc::crc16(&output[3..1027]);
+        assert_eq!(&output[1027..], &crc.to_be_bytes());
+
+        // A NAK gets the same block again.
+        let first = std::mem::take(&mut output);
+        sender.receive(&[NAK], &mut output).unwrap();
+        assert_eq!(output, first);
+
+        output.clear();
+        sender.receive(&[ACK], &mut output).unwrap();
+        assert_eq!(&output[..3], &[SOH, 2, 0xfd]);
+        assert_eq!(output.len(), 3 + 128 + 2);
+        assert_eq!(output[3 + 75], 0xa5);
+        assert_eq!(output[3 + 76], SUB);
+
+        output.clear();
+        sender.receive(&[ACK], &mut output).unwrap();
+        assert_eq!(output, vec![EOT]);
+        assert_eq!(sender.progress().bytes_transferred, 1100);
+
+        output.clear();
+        sender.receive(&[ACK, b'C'], &mut output).unwrap();
+        assert_eq!(&output[..3], &[SOH, 0, 0xff]);
+        assert!(output[3..131].iter().all(|byte| *byte == 0));
+
+        assert_eq!(
+            sender
+                .receive(&[ACK, b'\r', b'\n'], &mut Vec::new())
+                .unwrap(),
+            EngineStatus::Finished {
+                remainder: b"\r\n".to_vec()
+            }
+        );
+    }
+
+    #[test]
+    fn test_xmodem_checksum_mode_and_cancel() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.bin");
+        std::fs::write(&path, b"hello").unwrap();
+        let mut sender = XmodemSender::new(&path, XmodemVariant::Xmodem).unwrap();
+
+        let mut output = Vec::new();
+        sender.receive(&[NAK], &mut output).unwrap();
+        assert_eq!(&output[..3], &[SOH, 1, 0xfe]);
+        assert_eq!(output.len(), 3 + 128 + 1);
+        let checksum = output[3..131]
+            .iter()
+            .fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        assert_eq!(output[131], checksum);
+
+        assert!(sender.receive(&[CAN, CAN], &mut Vec::new()).is_err());
+    }
+}
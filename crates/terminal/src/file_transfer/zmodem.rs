@@ -0,0 +1,1208 @@
+//! ZMODEM, as spoken by lrzsz's `sz` and `rz`.
+//!
+//! Supports hex and binary (CRC-16 and CRC-32) headers, ZDLE escaping, streaming with
+//! ZCRCQ acknowledgements, and resuming from ZRPOS after a damaged subpacket. Crash
+//! recovery, ZCOMMAND and compression are not supported, and files are limited to 4 GiB
+//! since positions are 32 bits on the wire.
+
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{self, Read as _, Seek as _, SeekFrom, Write as _},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+
+use super::{
+    EngineStatus, TransferDirection, TransferEngine, TransferProgress, TransferProtocol, crc,
+};
+
+const ZPAD: u8 = b'*';
+const ZDLE: u8 = 0x18;
+const ZBIN: u8 = b'A';
+const ZHEX: u8 = b'B';
+const ZBIN32: u8 = b'C';
+
+/// Subpacket ends: CRC next, frame ends, header follows.
+const ZCRCE: u8 = b'h';
+/// Subpacket ends: CRC next, frame continues nonstop.
+const ZCRCG: u8 = b'i';
+/// Subpacket ends: CRC next, frame continues, ZACK expected.
+const ZCRCQ: u8 = b'j';
+/// Subpacket ends: CRC next, ZACK expected, end of frame.
+const ZCRCW: u8 = b'k';
+const ZRUB0: u8 = b'l';
+const ZRUB1: u8 = b'm';
+
+const XON: u8 = 0x11;
+const XOFF: u8 = 0x13;
+
+// ZRINIT capabilities, in ZF0.
+const CANFDX: u8 = 0x01;
+const CANOVIO: u8 = 0x02;
+const CANFC32: u8 = 0x20;
+const ESCCTL: u8 = 0x40;
+
+/// ZFILE conversion option, in ZF0: binary transfer.
+const ZCBIN: u8 = 1;
+
+/// Consecutive CANs that abort a session.
+const CANCEL_RUN: usize = 5;
+const SUBPACKET_SIZE: usize = 1024;
+/// Longest subpacket accepted from a sender; lrzsz never sends more than 8 KiB.
+const MAX_SUBPACKET_SIZE: usize = 8 * 1024;
+/// Unacknowledged bytes allowed in flight before waiting for a ZACK.
+const WINDOW_SIZE: u64 = 16 * SUBPACKET_SIZE as u64;
+/// Ask the receiver for a ZACK after this many subpackets.
+const SUBPACKETS_PER_ACK: usize = 4;
+const MAX_RETRIES: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameType {
+    Rqinit,
+    Rinit,
+    Sinit,
+    Ack,
+    File,
+    Skip,
+    Nak,
+    Abort,
+    Fin,
+    Rpos,
+    Data,
+    Eof,
+    Ferr,
+    Crc,
+    Challenge,
+    Compl,
+    Can,
+    Freecnt,
+    Command,
+    Stderr,
+}
+
+impl FrameType {
+    const ALL: [FrameType; 20] = [
+        FrameType::Rqinit,
+        FrameType::Rinit,
+        FrameType::Sinit,
+        FrameType::Ack,
+        FrameType::File,
+        FrameType::Skip,
+        FrameType::Nak,
+        FrameType::Abort,
+        FrameType::Fin,
+        FrameType::Rpos,
+        FrameType::Data,
+        FrameType::Eof,
+        FrameType::Ferr,
+        FrameType::Crc,
+        FrameType::Challenge,
+        FrameType::Compl,
+        FrameType::Can,
+        FrameType::Freecnt,
+        FrameType::Command,
+        FrameType::Stderr,
+    ];
+
+    fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.get(value as usize).copied()
+    }
+
+    fn to_u8(self) -> u8 {
+        Self::ALL
+            .iter()
+            .position(|frame_type| *frame_type == self)
+            .unwrap_or(0) as u8
+    }
+
+    /// Whether data subpackets follow a header of this type.
+    fn has_data(self) -> bool {
+        matches!(
+            self,
+            FrameType::Sinit | FrameType::File | FrameType::Data | FrameType::Command
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub frame_type: FrameType,
+    /// ZP0..ZP3 for positions, or ZF3..ZF0 for flags.
+    pub data: [u8; 4],
+}
+
+impl Header {
+    pub fn new(frame_type: FrameType) -> Self {
+        Self {
+            frame_type,
+            data: [0; 4],
+        }
+    }
+
+    pub fn with_position(frame_type: FrameType, position: u64) -> Self {
+        Self {
+            frame_type,
+            data: (position as u32).to_le_bytes(),
+        }
+    }
+
+    pub fn with_flags(frame_type: FrameType, zf0: u8) -> Self {
+        Self {
+            frame_type,
+            data: [0, 0, 0, zf0],
+        }
+    }
+
+    pub fn position(&self) -> u64 {
+        u32::from_le_bytes(self.data) as u64
+    }
+
+    pub fn zf0(&self) -> u8 {
+        self.data[3]
+    }
+
+    fn bytes(&self) -> [u8; 5] {
+        let [a, b, c, d] = self.data;
+        [self.frame_type.to_u8(), a, b, c, d]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeaderEncoding {
+    Hex,
+    Bin16,
+    Bin32,
+}
+
+pub fn write_hex_header(header: &Header, output: &mut Vec<u8>) {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let bytes = header.bytes();
+    let crc = crc::crc16(&bytes);
+    output.extend_from_slice(&[ZPAD, ZPAD, ZDLE, ZHEX]);
+    for byte in bytes.iter().chain(&crc.to_be_bytes()) {
+        output.push(DIGITS[(byte >> 4) as usize]);
+        output.push(DIGITS[(byte & 0xf) as usize]);
+    }
+    output.extend_from_slice(b"\r\x8a");
+    if !matches!(header.frame_type, FrameType::Ack | FrameType::Fin) {
+        output.push(XON);
+    }
+}
+
+/// ZDLE-escapes outgoing binary data.
+#[derive(Default)]
+pub struct Escaper {
+    /// Escape all control characters, when the receiver asks with ESCCTL.
+    pub escape_control: bool,
+    last: u8,
+}
+
+impl Escaper {
+    pub fn push(&mut self, byte: u8, output: &mut Vec<u8>) {
+        let escape = match byte {
+            ZDLE | 0x10 | 0x90 | XON | 0x91 | XOFF | 0x93 => true,
+            // Telnet-style "@\r" sequences can be swallowed by network gear.
+            b'\r' | 0x8d => self.last & 0x7f == b'@',
+            _ => self.escape_control && byte & 0x60 == 0,
+        };
+        if escape {
+            output.push(ZDLE);
+            self.last = byte ^ 0x40;
+        } else {
+            self.last = byte;
+        }
+        output.push(self.last);
+    }
+
+    pub fn extend(&mut self, bytes: &[u8], output: &mut Vec<u8>) {
+        for &byte in bytes {
+            self.push(byte, output);
+        }
+    }
+}
+
+pub fn write_binary_header(
+    header: &Header,
+    crc32: bool,
+    escaper: &mut Escaper,
+    output: &mut Vec<u8>,
+) {
+    let bytes = header.bytes();
+    output.extend_from_slice(&[ZPAD, ZDLE, if crc32 { ZBIN32 } else { ZBIN }]);
+    escaper.extend(&bytes, output);
+    if crc32 {
+        escaper.extend(&crc::crc32(&bytes).to_le_bytes(), output);
+    } else {
+        escaper.extend(&crc::crc16(&bytes).to_be_bytes(), output);
+    }
+}
+
+pub fn write_subpacket(
+    data: &[u8],
+    end: u8,
+    crc32: bool,
+    escaper: &mut Escaper,
+    output: &mut Vec<u8>,
+) {
+    escaper.extend(data, output);
+    output.extend_from_slice(&[ZDLE, end]);
+    if crc32 {
+        let crc = !crc::crc32_update(crc::crc32_update(!0, data), &[end]);
+        escaper.extend(&crc.to_le_bytes(), output);
+    } else {
+        let crc = crc::crc16_update(crc::crc16(data), &[end]);
+        escaper.extend(&crc.to_be_bytes(), output);
+    }
+    if end == ZCRCW {
+        output.push(XON);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Packet {
+    Header(Header, HeaderEncoding),
+    Data {
+        data: Vec<u8>,
+        end: u8,
+    },
+    /// A header or subpacket failed its CRC check or was malformed.
+    Corrupt,
+    /// The remote side sent the cancel sequence.
+    Cancelled,
+}
+
+enum DecodeState {
+    /// Looking for ZPAD, ZDLE and the header format byte; holds how many were seen.
+    Seek(u8),
+    Hex(Vec<u8>),
+    Binary {
+        crc32: bool,
+        bytes: Vec<u8>,
+        escaped: bool,
+    },
+    Subpacket {
+        crc32: bool,
+        data: Vec<u8>,
+        end: Option<u8>,
+        crc: Vec<u8>,
+        escaped: bool,
+    },
+}
+
+impl DecodeState {
+    fn subpacket(crc32: bool) -> Self {
+        DecodeState::Subpacket {
+            crc32,
+            data: Vec::new(),
+            end: None,
+            crc: Vec::new(),
+            escaped: false,
+        }
+    }
+}
+
+enum Unescaped {
+    Byte(u8),
+    FrameEnd(u8),
+    Skip,
+    Invalid,
+}
+
+fn unescape(byte: u8, escaped: &mut bool) -> Unescaped {
+    if *escaped {
+        *escaped = false;
+        match byte {
+            ZCRCE..=ZCRCW => Unescaped::FrameEnd(byte),
+            ZRUB0 => Unescaped::Byte(0x7f),
+            ZRUB1 => Unescaped::Byte(0xff),
+            ZDLE => {
+                *escaped = true;
+                Unescaped::Skip
+            }
+            _ if byte & 0x60 == 0x40 => Unescaped::Byte(byte ^ 0x40),
+            _ => Unescaped::Invalid,
+        }
+    } else {
+        match byte {
+            ZDLE => {
+                *escaped = true;
+                Unescaped::Skip
+            }
+            // Flow control characters are never part of the data; senders escape them.
+            XON | XOFF | 0x91 | 0x93 => Unescaped::Skip,
+            _ => Unescaped::Byte(byte),
+        }
+    }
+}
+
+/// Decodes headers and data subpackets one byte at a time.
+pub struct Decoder {
+    state: DecodeState,
+    cancel_run: usize,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self {
+            state: DecodeState::Seek(0),
+            cancel_run: 0,
+        }
+    }
+}
+
+impl Decoder {
+    pub fn push(&mut self, byte: u8) -> Option<Packet> {
+        if byte == ZDLE {
+            self.cancel_run += 1;
+            if self.cancel_run >= CANCEL_RUN {
+                self.cancel_run = 0;
+                self.state = DecodeState::Seek(0);
+                return Some(Packet::Cancelled);
+            }
+        } else {
+            self.cancel_run = 0;
+        }
+
+        let state = std::mem::replace(&mut self.state, DecodeState::Seek(0));
+        let (state, packet) = Self::step(state, byte);
+        self.state = state;
+        packet
+    }
+
+    fn step(state: DecodeState, byte: u8) -> (DecodeState, Option<Packet>) {
+        match state {
+            DecodeState::Seek(seen) => {
+                let state = match (seen, byte) {
+                    (0 | 1, ZPAD) => DecodeState::Seek(1),
+                    (1, ZDLE) => DecodeState::Seek(2),
+                    (2, ZHEX) => DecodeState::Hex(Vec::with_capacity(14)),
+                    (2, ZBIN | ZBIN32) => DecodeState::Binary {
+                        crc32: byte == ZBIN32,
+                        bytes: Vec::with_capacity(9),
+                        escaped: false,
+                    },
+                    (_, ZPAD) => DecodeState::Seek(1),
+                    _ => DecodeState::Seek(0),
+                };
+                (state, None)
+            }
+            DecodeState::Hex(mut digits) => {
+                if !byte.is_ascii_hexdigit() {
+                    return (DecodeState::Seek(0), Some(Packet::Corrupt));
+                }
+                digits.push(byte);
+                if digits.len() < 14 {
+                    return (DecodeState::Hex(digits), None);
+                }
+                let bytes = digits
+                    .chunks(2)
+                    .map(|pair| (hex_value(pair[0]) << 4) | hex_value(pair[1]))
+                    .collect::<Vec<_>>();
+                let crc = u16::from_be_bytes([bytes[5], bytes[6]]);
+                if crc::crc16(&bytes[..5]) != crc {
+                    return (DecodeState::Seek(0), Some(Packet::Corrupt));
+                }
+                Self::header(&bytes[..5], HeaderEncoding::Hex)
+            }
+            DecodeState::Binary {
+                crc32,
+                mut bytes,
+                mut escaped,
+            } => match unescape(byte, &mut escaped) {
+                Unescaped::Skip => (
+                    DecodeState::Binary {
+                        crc32,
+                        bytes,
+                        escaped,
+                    },
+                    None,
+                ),
+                Unescaped::FrameEnd(_) | Unescaped::Invalid => {
+                    (DecodeState::Seek(0), Some(Packet::Corrupt))
+                }
+                Unescaped::Byte(byte) => {
+                    bytes.push(byte);
+                    let crc_len = if crc32 { 4 } else { 2 };
+                    if bytes.len() < 5 + crc_len {
+                        return (
+                            DecodeState::Binary {
+                                crc32,
+                                bytes,
+                                escaped,
+                            },
+                            None,
+                        );
+                    }
+                    let (header, crc) = bytes.split_at(5);
+                    let valid = if crc32 {
+                        crc::crc32(header).to_le_bytes() == crc
+                    } else {
+                        crc::crc16(header).to_be_bytes() == crc
+                    };
+                    if !valid {
+                        return (DecodeState::Seek(0), Some(Packet::Corrupt));
+                    }
+                    let encoding = if crc32 {
+                        HeaderEncoding::Bin32
+                    } else {
+                        HeaderEncoding::Bin16
+                    };
+                    Self::header(header, encoding)
+                }
+            },
+            DecodeState::Subpacket {
+                crc32,
+                mut data,
+                mut end,
+                mut crc,
+                mut escaped,
+            } => {
+                match (unescape(byte, &mut escaped), end) {
+                    (Unescaped::Skip, _) => {}
+                    (Unescaped::FrameEnd(frame_end), None) => end = Some(frame_end),
+                    (Unescaped::Byte(byte), None) => {
+                        if data.len() >= MAX_SUBPACKET_SIZE {
+                            return (DecodeState::Seek(0), Some(Packet::Corrupt));
+                        }
+                        data.push(byte);
+                    }
+                    (Unescaped::Byte(byte), Some(frame_end)) => {
+                        crc.push(byte);
+                        let crc_len = if crc32 { 4 } else { 2 };
+                        if crc.len() == crc_len {
+                            let valid = if crc32 {
+                                let expected =
+                                    !crc::crc32_update(crc::crc32_update(!0, &data), &[frame_end]);
+                                expected.to_le_bytes()[..] == crc[..]
+                            } else {
+                                let expected = crc::crc16_update(crc::crc16(&data), &[frame_end]);
+                                expected.to_be_bytes()[..] == crc[..]
+                            };
+                            if !valid {
+                                return (DecodeState::Seek(0), Some(Packet::Corrupt));
+                            }
+                            let state = if matches!(frame_end, ZCRCG | ZCRCQ) {
+                                DecodeState::subpacket(crc32)
+                            } else {
+                                DecodeState::Seek(0)
+                            };
+                            return (
+                                state,
+                                Some(Packet::Data {
+                                    data,
+                                    end: frame_end,
+                                }),
+                            );
+                        }
+                    }
+                    (Unescaped::FrameEnd(_), Some(_)) | (Unescaped::Invalid, _) => {
+                        return (DecodeState::Seek(0), Some(Packet::Corrupt));
+                    }
+                }
+                (
+                    DecodeState::Subpacket {
+                        crc32,
+                        data,
+                        end,
+                        crc,
+                        escaped,
+                    },
+                    None,
+                )
+            }
+        }
+    }
+
+    fn header(bytes: &[u8], encoding: HeaderEncoding) -> (DecodeState, Option<Packet>) {
+        let Some(frame_type) = FrameType::from_u8(bytes[0]) else {
+            return (DecodeState::Seek(0), Some(Packet::Corrupt));
+        };
+        let header = Header {
+            frame_type,
+            data: [bytes[1], bytes[2], bytes[3], bytes[4]],
+        };
+        let state = if frame_type.has_data() {
+            DecodeState::subpacket(encoding == HeaderEncoding::Bin32)
+        } else {
+            DecodeState::Seek(0)
+        };
+        (state, Some(Packet::Header(header, encoding)))
+    }
+}
+
+fn hex_value(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        _ => digit.to_ascii_lowercase() - b'a' + 10,
+    }
+}
+
+fn write_rinit(output: &mut Vec<u8>) {
+    write_hex_header(
+        &Header::with_flags(FrameType::Rinit, CANFDX | CANOVIO | CANFC32),
+        output,
+    );
+}
+
+enum ReceiverState {
+    AwaitingFile,
+    AwaitingSinitData,
+    AwaitingFileInfo,
+    /// Receiving file data; `in_sync` is false after an error until the sender resumes
+    /// from the position we asked for.
+    Receiving {
+        in_sync: bool,
+    },
+    /// Our ZFIN is sent; consuming the "OO" that ends the session.
+    Finishing {
+        overs: usize,
+    },
+}
+
+struct IncomingFile {
+    file: File,
+    position: u64,
+}
+
+/// Receives files from a remote `sz` into a local directory.
+pub struct ZmodemReceiver {
+    decoder: Decoder,
+    directory: PathBuf,
+    state: ReceiverState,
+    file: Option<IncomingFile>,
+    progress: TransferProgress,
+    retries: usize,
+}
+
+impl ZmodemReceiver {
+    pub fn new(directory: PathBuf) -> Self {
+        Self {
+            decoder: Decoder::default(),
+            directory,
+            state: ReceiverState::AwaitingFile,
+            file: None,
+            progress: TransferProgress::new(TransferProtocol::Zmodem, TransferDirection::Receive),
+            retries: 0,
+        }
+    }
+
+    fn handle_packet(&mut self, packet: Packet, output: &mut Vec<u8>) -> Result<()> {
+        match packet {
+            Packet::Cancelled => bail!("cancelled by the sender"),
+            Packet::Corrupt => match (&mut self.state, &self.file) {
+                (ReceiverState::Receiving { in_sync }, Some(file)) => {
+                    *in_sync = false;
+                    write_hex_header(
+                        &Header::with_position(FrameType::Rpos, file.position),
+                        output,
+                    );
+                }
+                _ => write_hex_header(&Header::new(FrameType::Nak), output),
+            },
+            Packet::Header(header, _) => match header.frame_type {
+                FrameType::Rqinit => {
+                    if matches!(self.state, ReceiverState::AwaitingFile) {
+                        write_rinit(output);
+                    }
+                }
+                FrameType::Sinit => self.state = ReceiverState::AwaitingSinitData,
+                FrameType::File => self.state = ReceiverState::AwaitingFileInfo,
+                FrameType::Data => {
+                    if let (ReceiverState::Receiving { in_sync }, Some(file)) =
+                        (&mut self.state, &self.file)
+                    {
+                        *in_sync = header.position() == file.position;
+                        if !*in_sync {
+                            write_hex_header(
+                                &Header::with_position(FrameType::Rpos, file.position),
+                                output,
+                            );
+                        }
+                    }
+                }
+                FrameType::Eof => {
+                    if self
+                        .file
+                        .as_ref()
+                        .is_some_and(|file| file.position == header.position())
+                        && let Some(mut file) = self.file.take()
+                    {
+                        file.file.flush()?;
+                        self.progress.files_completed += 1;
+                        self.state = ReceiverState::AwaitingFile;
+                        write_rinit(output);
+                    }
+                }
+                FrameType::Fin | FrameType::Abort => {
+                    write_hex_header(&Header::new(FrameType::Fin), output);
+                    self.state = ReceiverState::Finishing { overs: 0 };
+                }
+                FrameType::Ferr | FrameType::Can => bail!("the sender aborted the transfer"),
+                _ => {}
+            },
+            Packet::Data { data, end } => match self.state {
+                ReceiverState::AwaitingSinitData => {
+                    write_hex_header(&Header::new(FrameType::Ack), output);
+                    self.state = ReceiverState::AwaitingFile;
+                }
+                ReceiverState::AwaitingFileInfo => {
+                    self.open_file(&data)?;
+                    write_hex_header(&Header::with_position(FrameType::Rpos, 0), output);
+                    self.state = ReceiverState::Receiving { in_sync: false };
+                }
+                ReceiverState::Receiving { in_sync: true } => {
+                    let Some(file) = &mut self.file else {
+                        return Ok(());
+                    };
+                    file.file.write_all(&data)?;
+                    file.position += data.len() as u64;
+                    self.progress.bytes_transferred = file.position;
+                    self.retries = 0;
+                    if matches!(end, ZCRCQ | ZCRCW) {
+                        write_hex_header(
+                            &Header::with_position(FrameType::Ack, file.position),
+                            output,
+                        );
+                    }
+                }
+                _ => {}
+            },
+        }
+        Ok(())
+    }
+
+    /// Creates the file announced by a ZFILE subpacket: a NUL-terminated name followed
+    /// by the size and other optional fields, separated by spaces.
+    fn open_file(&mut self, info: &[u8]) -> Result<()> {
+        let mut fields = info.splitn(2, |byte| *byte == 0);
+        let name = String::from_utf8_lossy(fields.next().unwrap_or_default()).into_owned();
+        let size = fields.next().and_then(|rest| {
+            let rest = String::from_utf8_lossy(rest);
+            rest.split(|c: char| c == ' ' || c == '\0')
+                .next()
+                .and_then(|size| size.parse::<u64>().ok())
+        });
+
+        // Never trust the sender's path, only its file name.
+        let name = Path::new(&name)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "download".to_string());
+
+        std::fs::create_dir_all(&self.directory)
+            .with_context(|| format!("creating download directory {}", self.directory.display()))?;
+        let (path, file) = create_unique_file(&self.directory, &name)?;
+        log::info!("Receiving {} via ZMODEM", path.display());
+
+        self.file = Some(IncomingFile { file, position: 0 });
+        self.progress.start_file(
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or(name),
+            size,
+        );
+        Ok(())
+    }
+}
+
+/// Creates `name` in `directory`, adding a numeric suffix if it's taken.
+fn create_unique_file(directory: &Path, name: &str) -> Result<(PathBuf, File)> {
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+        _ => (name, None),
+    };
+    for attempt in 0..1000 {
+        let candidate = match (attempt, extension) {
+            (0, _) => name.to_string(),
+            (_, Some(extension)) => format!("{stem} ({attempt}).{extension}"),
+            (_, None) => format!("{stem} ({attempt})"),
+        };
+        let path = directory.join(candidate);
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => {
+                return Err(error).with_context(|| format!("creating {}", path.display()));
+            }
+        }
+    }
+    Err(anyhow!(
+        "no free file name for {name} in {}",
+        directory.display()
+    ))
+}
+
+impl TransferEngine for ZmodemReceiver {
+    fn progress(&self) -> TransferProgress {
+        self.progress.clone()
+    }
+
+    fn receive(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<EngineStatus> {
+        for (ix, &byte) in input.iter().enumerate() {
+            if let ReceiverState::Finishing { overs } = &mut self.state {
+                if byte == b'O' {
+                    *overs += 1;
+                    if *overs == 2 {
+                        return Ok(EngineStatus::Finished {
+                            remainder: input[ix + 1..].to_vec(),
+                        });
+                    }
+                    continue;
+                }
+                return Ok(EngineStatus::Finished {
+                    remainder: input[ix..].to_vec(),
+                });
+            }
+            if let Some(packet) = self.decoder.push(byte) {
+                self.handle_packet(packet, output)?;
+            }
+        }
+        Ok(EngineStatus::Running)
+    }
+
+    fn timeout(&mut self, output: &mut Vec<u8>) -> Result<EngineStatus> {
+        if let ReceiverState::Finishing { .. } = self.state {
+            return Ok(EngineStatus::Finished {
+                remainder: Vec::new(),
+            });
+        }
+        self.retries += 1;
+        if self.retries > MAX_RETRIES {
+            bail!("timed out waiting for the sender");
+        }
+        match (&mut self.state, &self.file) {
+            (ReceiverState::Receiving { in_sync }, Some(file)) => {
+                *in_sync = false;
+                write_hex_header(
+                    &Header::with_position(FrameType::Rpos, file.position),
+                    output,
+                );
+            }
+            _ => write_rinit(output),
+        }
+        Ok(EngineStatus::Running)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SenderState {
+    AwaitingReceiver,
+    AwaitingPosition,
+    Streaming,
+    AwaitingEofAck,
+    AwaitingFinAck,
+}
+
+struct OutgoingFile {
+    file: File,
+    size: u64,
+    /// The ZFILE subpacket, kept for retransmission.
+    info: Vec<u8>,
+    /// Offset of the next byte to send.
+    position: u64,
+    /// Offset the receiver has acknowledged.
+    acked: u64,
+    subpackets_since_ack: usize,
+}
+
+/// Sends files to a remote `rz`.
+pub struct ZmodemSender {
+    decoder: Decoder,
+    files: VecDeque<PathBuf>,
+    current: Option<OutgoingFile>,
+    state: SenderState,
+    escaper: Escaper,
+    crc32: bool,
+    progress: TransferProgress,
+    retries: usize,
+}
+
+impl ZmodemSender {
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        Self {
+            decoder: Decoder::default(),
+            files: files.into(),
+            current: None,
+            state: SenderState::AwaitingReceiver,
+            escaper: Escaper::default(),
+            crc32: false,
+            progress: TransferProgress::new(TransferProtocol::Zmodem, TransferDirection::Send),
+            retries: 0,
+        }
+    }
+
+    /// Returns whether the session is over.
+    fn handle_packet(&mut self, packet: Packet, output: &mut Vec<u8>) -> Result<bool> {
+        let header = match packet {
+            Packet::Cancelled => bail!("cancelled by the receiver"),
+            // The receiver repeats itself when it doesn't hear from us.
+            Packet::Corrupt | Packet::Data { .. } => return Ok(false),
+            Packet::Header(header, _) => header,
+        };
+        match (header.frame_type, self.state) {
+            (FrameType::Rinit, SenderState::AwaitingReceiver | SenderState::AwaitingEofAck) => {
+                self.crc32 = header.zf0() & CANFC32 != 0;
+                self.escaper.escape_control = header.zf0() & ESCCTL != 0;
+                if self.state == SenderState::AwaitingEofAck {
+                    self.current = None;
+                    self.progress.files_completed += 1;
+                }
+                self.retries = 0;
+                self.start_next_file(output)?;
+            }
+            (FrameType::Rinit | FrameType::Nak, SenderState::AwaitingPosition) => {
+                self.retry()?;
+                self.write_file_header(output);
+            }
+            (
+                FrameType::Rpos,
+                SenderState::AwaitingPosition
+                | SenderState::Streaming
+                | SenderState::AwaitingEofAck,
+            ) => {
+                if self.state != SenderState::AwaitingPosition {
+                    self.retry()?;
+                }
+                self.resume_from(header.position(), output)?;
+            }
+            (FrameType::Ack, SenderState::Streaming) => {
+                if let Some(file) = &mut self.current {
+                    file.acked = file.acked.max(header.position());
+                }
+                self.retries = 0;
+            }
+            (FrameType::Skip, _) => {
+                self.current = None;
+                self.start_next_file(output)?;
+            }
+            (FrameType::Fin, SenderState::AwaitingFinAck) => {
+                output.extend_from_slice(b"OO");
+                return Ok(true);
+            }
+            (FrameType::Nak, SenderState::AwaitingFinAck) => {
+                write_hex_header(&Header::new(FrameType::Fin), output);
+            }
+            (FrameType::Abort | FrameType::Ferr | FrameType::Can, _) => {
+                bail!("the receiver aborted the transfer")
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn retry(&mut self) -> Result<()> {
+        self.retries += 1;
+        if self.retries > MAX_RETRIES {
+            bail!("too many errors, giving up");
+        }
+        Ok(())
+    }
+
+    fn start_next_file(&mut self, output: &mut Vec<u8>) -> Result<()> {
+        let Some(path) = self.files.pop_front() else {
+            write_hex_header(&Header::new(FrameType::Fin), output);
+            self.state = SenderState::AwaitingFinAck;
+            return Ok(());
+        };
+
+        let file = File::open(&path).with_context(|| format!("opening {}", path.display()))?;
+        let metadata = file.metadata()?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "upload".to_string());
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |modified| modified.as_secs());
+        let mut info = name.clone().into_bytes();
+        info.push(0);
+        info.extend_from_slice(format!("{} {:o} 100644", metadata.len(), modified).as_bytes());
+        info.push(0);
+
+        self.progress.start_file(name, Some(metadata.len()));
+        self.current = Some(OutgoingFile {
+            file,
+            size: metadata.len(),
+            info,
+            position: 0,
+            acked: 0,
+            subpackets_since_ack: 0,
+        });
+        self.write_file_header(output);
+        Ok(())
+    }
+
+    fn write_file_header(&mut self, output: &mut Vec<u8>) {
+        let Some(file) = &self.current else {
+            return;
+        };
+        write_binary_header(
+            &Header::with_flags(FrameType::File, ZCBIN),
+            self.crc32,
+            &mut self.escaper,
+            output,
+        );
+        write_subpacket(&file.info, ZCRCW, self.crc32, &mut self.escaper, output);
+        self.state = SenderState::AwaitingPosition;
+    }
+
+    fn resume_from(&mut self, position: u64, output: &mut Vec<u8>) -> Result<()> {
+        let Some(file) = &mut self.current else {
+            return Ok(());
+        };
+        file.file.seek(SeekFrom::Start(position))?;
+        file.position = position;
+        file.acked = position;
+        file.subpackets_since_ack = 0;
+        self.progress.bytes_transferred = position;
+        write_binary_header(
+            &Header::with_position(FrameType::Data, position),
+            self.crc32,
+            &mut self.escaper,
+            output,
+        );
+        self.state = SenderState::Streaming;
+        Ok(())
+    }
+}
+
+impl TransferEngine for ZmodemSender {
+    fn progress(&self) -> TransferProgress {
+        self.progress.clone()
+    }
+
+    fn receive(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<EngineStatus> {
+        for (ix, &byte) in input.iter().enumerate() {
+            if let Some(packet) = self.decoder.push(byte)
+                && self.handle_packet(packet, output)?
+            {
+                return Ok(EngineStatus::Finished {
+                    remainder: input[ix + 1..].to_vec(),
+                });
+            }
+        }
+        Ok(EngineStatus::Running)
+    }
+
+    fn poll_send(&mut self, output: &mut Vec<u8>) -> Result<bool> {
+        if self.state != SenderState::Streaming {
+            return Ok(false);
+        }
+        let Some(file) = &mut self.current else {
+            return Ok(false);
+        };
+        if file.position - file.acked >= WINDOW_SIZE {
+            return Ok(false);
+        }
+
+        let mut chunk = vec![0; SUBPACKET_SIZE];
+        let mut read = 0;
+        while read < chunk.len() {
+            match file.file.read(&mut chunk[read..])? {
+                0 => break,
+                n => read += n,
+            }
+        }
+        chunk.truncate(read);
+        file.position += read as u64;
+        self.progress.bytes_transferred = file.position;
+
+        if read < SUBPACKET_SIZE || file.position >= file.size {
+            let position = file.position;
+            write_subpacket(&chunk, ZCRCE, self.crc32, &mut self.escaper, output);
+            write_binary_header(
+                &Header::with_position(FrameType::Eof, position),
+                self.crc32,
+                &mut self.escaper,
+                output,
+            );
+            self.state = SenderState::AwaitingEofAck;
+            return Ok(false);
+        }
+
+        file.subpackets_since_ack += 1;
+        let end = if file.subpackets_since_ack >= SUBPACKETS_PER_ACK {
+            file.subpackets_since_ack = 0;
+            ZCRCQ
+        } else {
+            ZCRCG
+        };
+        write_subpacket(&chunk, end, self.crc32, &mut self.escaper, output);
+        Ok(true)
+    }
+
+    fn timeout(&mut self, output: &mut Vec<u8>) -> Result<EngineStatus> {
+        if self.state == SenderState::AwaitingFinAck && self.retries >= MAX_RETRIES {
+            // Every file went through; the receiver just didn't say goodbye.
+            return Ok(EngineStatus::Finished {
+                remainder: Vec::new(),
+            });
+        }
+        self.retry()?;
+        match self.state {
+            SenderState::AwaitingReceiver => {
+                write_hex_header(&Header::new(FrameType::Rqinit), output)
+            }
+            SenderState::AwaitingPosition => self.write_file_header(output),
+            SenderState::Streaming => {
+                // Rewind to what the receiver has confirmed; it asks for a ZRPOS if it
+                // actually got further.
+                let acked = self.current.as_ref().map_or(0, |file| file.acked);
+                self.resume_from(acked, output)?;
+            }
+            SenderState::AwaitingEofAck => {
+                let position = self.current.as_ref().map_or(0, |file| file.position);
+                write_binary_header(
+                    &Header::with_position(FrameType::Eof, position),
+                    self.crc32,
+                    &mut self.escaper,
+                    output,
+                );
+            }
+            SenderState::AwaitingFinAck => write_hex_header(&Header::new(FrameType::Fin), output),
+        }
+        Ok(EngineStatus::Running)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Vec<Packet> {
+        let mut decoder = Decoder::default();
+        bytes
+            .iter()
+            .filter_map(|byte| decoder.push(*byte))
+            .collect()
+    }
+
+    #[test]
+    fn test_hex_header_round_trip() {
+        let mut output = Vec::new();
+        write_rinit(&mut output);
+        assert_eq!(output, b"**\x18B0100000023be50\r\x8a\x11");
+        assert_eq!(
+            decode(&output),
+            vec![Packet::Header(
+                Header::with_flags(FrameType::Rinit, CANFDX | CANOVIO | CANFC32),
+                HeaderEncoding::Hex
+            )]
+        );
+    }
+
+    #[test]
+    fn test_binary_frames_round_trip() {
+        let data = (0..=255).collect::<Vec<u8>>();
+        for crc32 in [false, true] {
+            let mut escaper = Escaper::default();
+            let mut output = Vec::new();
+            write_binary_header(
+                &Header::with_position(FrameType::Data, 0x1234),
+                crc32,
+                &mut escaper,
+                &mut output,
+            );
+            write_subpacket(&data, ZCRCG, crc32, &mut escaper, &mut output);
+            write_subpacket(b"tail", ZCRCE, crc32, &mut escaper, &mut output);
+            assert!(!output.contains(&XON) && !output.contains(&XOFF));
+
+            let encoding = if crc32 {
+                HeaderEncoding::Bin32
+            } else {
+                HeaderEncoding::Bin16
+            };
+            assert_eq!(
+                decode(&output),
+                vec![
+                    Packet::Header(Header::with_position(FrameType::Data, 0x1234), encoding),
+                    Packet::Data {
+                        data: data.clone(),
+                        end: ZCRCG
+                    },
+                    Packet::Data {
+                        data: b"tail".to_vec(),
+                        end: ZCRCE
+                    },
+                ]
+            );
+
+            let mut corrupted = output.clone();
+            let tail = corrupted
+                .windows(4)
+                .position(|window| window == b"tail")
+                .unwrap();
+            corrupted[tail] = b'T';
+            assert_eq!(decode(&corrupted).last(), Some(&Packet::Corrupt));
+        }
+    }
+
+    #[test]
+    fn test_cancel_sequence() {
+        assert_eq!(
+            decode(super::super::CANCEL_SEQUENCE),
+            vec![Packet::Cancelled]
+        );
+    }
+
+    #[test]
+    fn test_send_and_receive_files() {
+        let source = tempfile::tempdir().unwrap();
+        let source = source.path();
+        let destination = tempfile::tempdir().unwrap();
+        let destination = destination.path().to_path_buf();
+        let contents = (0..5000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        std::fs::write(source.join("firmware.bin"), &contents).unwrap();
+        std::fs::write(source.join("empty.txt"), b"").unwrap();
+        std::fs::write(destination.join("empty.txt"), b"existing").unwrap();
+
+        let mut sender =
+            ZmodemSender::new(vec![source.join("firmware.bin"), source.join("empty.txt")]);
+        let mut receiver = ZmodemReceiver::new(destination.clone());
+
+        // The receiver opens the session like `rz` does.
+        let mut to_sender = Vec::new();
+        write_rinit(&mut to_sender);
+        let mut sender_done = false;
+        let mut receiver_done = false;
+        for _ in 0..1000 {
+            let mut to_receiver = Vec::new();
+            if !sender_done {
+                sender_done |= matches!(
+                    sender.receive(&std::mem::take(&mut to_sender), &mut to_receiver),
+                    Ok(EngineStatus::Finished { .. })
+                );
+                while sender.poll_send(&mut to_receiver).unwrap() {}
+            }
+            if !receiver_done {
+                receiver_done |= matches!(
+                    receiver.receive(&to_receiver, &mut to_sender),
+                    Ok(EngineStatus::Finished { .. })
+                );
+            }
+            if sender_done && receiver_done {
+                break;
+            }
+        }
+
+        assert!(sender_done && receiver_done);
+        assert_eq!(
+            std::fs::read(destination.join("firmware.bin")).unwrap(),
+            contents
+        );
+        assert_eq!(
+            std::fs::read(destination.join("empty (1).txt")).unwrap(),
+            b""
+        );
+        assert_eq!(receiver.progress().files_completed, 2);
+        assert_eq!(sender.progress().files_completed, 2);
+    }
+}
@@ -0,0 +1,103 @@
+//! Parsing of iTerm2 inline images: `OSC 1337 ; File=[arguments]:[base64 data] ST`.
+
+use anyhow::{Context as _, Result};
+use base64::Engine as _;
+
+use super::BASE64;
+
+/// A width or height argument.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Dimension {
+    Auto,
+    Cells(u32),
+    Pixels(u32),
+    /// Percent of the terminal's width or height.
+    Percent(u32),
+}
+
+impl Dimension {
+    fn parse(value: &str) -> Self {
+        let parsed = if let Some(pixels) = value.strip_suffix("px") {
+            pixels.parse().map(Dimension::Pixels)
+        } else if let Some(percent) = value.strip_suffix('%') {
+            percent.parse().map(Dimension::Percent)
+        } else {
+            value.parse().map(Dimension::Cells)
+        };
+        parsed.unwrap_or(Dimension::Auto)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct InlineFile {
+    /// Whether the file is to be shown rather than downloaded.
+    pub inline: bool,
+    pub width: Dimension,
+    pub height: Dimension,
+    pub preserve_aspect_ratio: bool,
+    pub data: Vec<u8>,
+}
+
+/// Parses what follows `File=`.
+pub(super) fn parse(arguments: &[u8]) -> Result<InlineFile> {
+    let separator = arguments
+        .iter()
+        .position(|byte| *byte == b':')
+        .context("missing file contents")?;
+    let (keys, data) = (&arguments[..separator], &arguments[separator + 1..]);
+
+    let mut file = InlineFile {
+        inline: false,
+        width: Dimension::Auto,
+        height: Dimension::Auto,
+        preserve_aspect_ratio: true,
+        data: Vec::new(),
+    };
+    for pair in std::str::from_utf8(keys)?.split(';') {
+        let Some((key, value)) = pair.split_once('=') else {
+            continue;
+        };
+        match key {
+            "inline" => file.inline = value == "1",
+            "width" => file.width = Dimension::parse(value),
+            "height" => file.height = Dimension::parse(value),
+            "preserveAspectRatio" => file.preserve_aspect_ratio = value != "0",
+            _ => {}
+        }
+    }
+
+    let data: Vec<u8> = data
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    file.data = BASE64
+        .decode(data)
+        .context("invalid base64 file contents")?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_arguments() {
+        let file =
+            parse(b"name=aW1hZ2UucG5n;size=3;width=50%;height=10px;inline=1:AQID\r\n").unwrap();
+        assert!(file.inline);
+        assert!(file.preserve_aspect_ratio);
+        assert_eq!(file.width, Dimension::Percent(50));
+        assert_eq!(file.height, Dimension::Pixels(10));
+        assert_eq!(file.data, vec![1, 2, 3]);
+
+        let file = parse(b"width=4;height=auto;preserveAspectRatio=0:AQI").unwrap();
+        assert!(!file.inline);
+        assert!(!file.preserve_aspect_ratio);
+        assert_eq!(file.width, Dimension::Cells(4));
+        assert_eq!(file.height, Dimension::Auto);
+        assert_eq!(file.data, vec![1, 2]);
+
+        assert!(parse(b"inline=1").is_err());
+    }
+}
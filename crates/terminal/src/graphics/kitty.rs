@@ -0,0 +1,396 @@
+//! The Kitty graphics protocol: `APC G [control data] ; [base64 payload] ST`.
+//!
+//! Images are transmitted directly in the escape sequences, possibly in chunks, as
+//! RGB, RGBA or PNG data. Transmitting through files or shared memory and compressed
+//! data are not supported; programs such as `kitty +kitten icat` query for support
+//! and fall back to direct transmission.
+
+use std::sync::Arc;
+
+use base64::Engine as _;
+use collections::{HashMap, VecDeque};
+
+use super::{BASE64, ImageDeletion, KittyPlacementId, MAX_SEQUENCE_LEN, TerminalImage};
+
+/// Memory budget for the images a terminal keeps for later display.
+const MAX_STORED_IMAGE_BYTES: usize = 128 * 1024 * 1024;
+
+/// A parsed control data section, e.g. `a=T,f=100,i=1`.
+#[derive(Clone, Debug)]
+struct Command {
+    action: u8,
+    quiet: u32,
+    format: u32,
+    medium: u8,
+    compression: Option<u8>,
+    width: u32,
+    height: u32,
+    image_id: u32,
+    placement_id: u32,
+    more: bool,
+    columns: u32,
+    rows: u32,
+    move_cursor: bool,
+    delete: u8,
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Self {
+            action: b't',
+            quiet: 0,
+            format: 32,
+            medium: b'd',
+            compression: None,
+            width: 0,
+            height: 0,
+            image_id: 0,
+            placement_id: 0,
+            more: false,
+            columns: 0,
+            rows: 0,
+            move_cursor: true,
+            delete: b'a',
+        }
+    }
+}
+
+impl Command {
+    fn parse(control: &[u8]) -> Result<Self, String> {
+        let mut command = Self::default();
+        for pair in control.split(|byte| *byte == b',') {
+            let [key, b'=', value @ ..] = pair else {
+                if pair.is_empty() {
+                    continue;
+                }
+                return Err("EINVAL:malformed control data".into());
+            };
+            match key {
+                b'a' => command.action = character(value)?,
+                b'q' => command.quiet = number(value)?,
+                b'f' => command.format = number(value)?,
+                b't' => command.medium = character(value)?,
+                b'o' => command.compression = Some(character(value)?),
+                b's' => command.width = number(value)?,
+                b'v' => command.height = number(value)?,
+                b'i' => command.image_id = number(value)?,
+                b'p' => command.placement_id = number(value)?,
+                b'm' => command.more = number(value)? == 1,
+                b'c' => command.columns = number(value)?,
+                b'r' => command.rows = number(value)?,
+                b'C' => command.move_cursor = number(value)? == 0,
+                b'd' => command.delete = character(value)?,
+                _ => {}
+            }
+        }
+        Ok(command)
+    }
+
+    fn id(&self) -> Option<KittyPlacementId> {
+        (self.image_id != 0).then_some(KittyPlacementId {
+            image_id: self.image_id,
+            placement_id: self.placement_id,
+        })
+    }
+}
+
+fn character(value: &[u8]) -> Result<u8, String> {
+    match value {
+        [character] => Ok(*character),
+        _ => Err("EINVAL:malformed control data".into()),
+    }
+}
+
+fn number(value: &[u8]) -> Result<u32, String> {
+    std::str::from_utf8(value)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| "EINVAL:malformed control data".into())
+}
+
+pub(super) enum KittyAction {
+    Display {
+        image: Arc<TerminalImage>,
+        id: Option<KittyPlacementId>,
+        columns: Option<u32>,
+        rows: Option<u32>,
+        move_cursor: bool,
+    },
+    Delete(ImageDeletion),
+    Reply(Vec<u8>),
+}
+
+/// An image sent in several chunks, `m=1` on all but the last.
+struct Transmission {
+    command: Command,
+    data: Vec<u8>,
+}
+
+/// Kitty graphics state of one terminal: transmitted images and a chunked
+/// transmission in progress.
+#[derive(Default)]
+pub(super) struct KittyGraphics {
+    images: HashMap<u32, Arc<TerminalImage>>,
+    /// Stored image ids, oldest first.
+    order: VecDeque<u32>,
+    stored_bytes: usize,
+    transmission: Option<Transmission>,
+}
+
+impl KittyGraphics {
+    pub fn abort_transmission(&mut self) {
+        self.transmission = None;
+    }
+
+    /// Handles one `APC G` sequence, given what follows the `G`.
+    pub fn handle(&mut self, sequence: &[u8]) -> Vec<KittyAction> {
+        let (control, payload) = match sequence.iter().position(|byte| *byte == b';') {
+            Some(separator) => (&sequence[..separator], &sequence[separator + 1..]),
+            None => (sequence, &[][..]),
+        };
+        let mut actions = Vec::new();
+
+        if let Some(mut transmission) = self.transmission.take() {
+            // Chunks after the first only carry `m`, the rest comes from the first.
+            let more = Command::parse(control).is_ok_and(|command| command.more);
+            if transmission.data.len() + payload.len() > MAX_SEQUENCE_LEN {
+                reply(
+                    &transmission.command,
+                    Err("EFBIG:image is too large".into()),
+                    &mut actions,
+                );
+                return actions;
+            }
+            transmission.data.extend_from_slice(payload);
+            if more {
+                self.transmission = Some(transmission);
+            } else {
+                self.finish_transmission(transmission.command, &transmission.data, &mut actions);
+            }
+            return actions;
+        }
+
+        let command = match Command::parse(control) {
+            Ok(command) => command,
+            Err(error) => {
+                log::debug!("invalid kitty graphics command: {error}");
+                return actions;
+            }
+        };
+        match command.action {
+            b't' | b'T' | b'q' if command.more => {
+                self.transmission = Some(Transmission {
+                    command,
+                    data: payload.to_vec(),
+                });
+            }
+            b't' | b'T' | b'q' => self.finish_transmission(command, payload, &mut actions),
+            b'p' => match self.images.get(&command.image_id) {
+                Some(image) => {
+                    actions.push(display(&command, image.clone()));
+                    reply(&command, Ok(()), &mut actions);
+                }
+                None => reply(
+                    &command,
+                    Err("ENOENT:no image with that id".into()),
+                    &mut actions,
+                ),
+            },
+            b'd' => {
+                let deletion = match command.delete.to_ascii_lowercase() {
+                    b'a' => ImageDeletion::All,
+                    b'i' => ImageDeletion::Image {
+                        image_id: command.image_id,
+                        placement_id: (command.placement_id != 0).then_some(command.placement_id),
+                    },
+                    _ => return actions,
+                };
+                // Upper case also frees the image data.
+                if command.delete.is_ascii_uppercase() {
+                    match deletion {
+                        ImageDeletion::All => self.remove_images(|_| true),
+                        ImageDeletion::Image { image_id, .. } => {
+                            self.remove_images(|id| id == image_id)
+                        }
+                    }
+                }
+                actions.push(KittyAction::Delete(deletion));
+            }
+            _ => {}
+        }
+        actions
+    }
+
+    fn finish_transmission(
+        &mut self,
+        command: Command,
+        data: &[u8],
+        actions: &mut Vec<KittyAction>,
+    ) {
+        let image = match decode(&command, data) {
+            Ok(image) => Arc::new(image),
+            Err(error) => {
+                reply(&command, Err(error), actions);
+                return;
+            }
+        };
+        if command.action != b'q' {
+            if command.image_id != 0 {
+                self.store(command.image_id, image.clone());
+            }
+            if command.action == b'T' {
+                actions.push(display(&command, image));
+            }
+        }
+        reply(&command, Ok(()), actions);
+    }
+
+    fn store(&mut self, image_id: u32, image: Arc<TerminalImage>) {
+        self.remove_images(|id| id == image_id);
+        self.stored_bytes += image.byte_len();
+        self.images.insert(image_id, image);
+        self.order.push_back(image_id);
+        while self.stored_bytes > MAX_STORED_IMAGE_BYTES
+            && let Some(oldest) = self.order.pop_front()
+        {
+            if let Some(image) = self.images.remove(&oldest) {
+                self.stored_bytes -= image.byte_len();
+            }
+        }
+    }
+
+    fn remove_images(&mut self, mut predicate: impl FnMut(u32) -> bool) {
+        self.order.retain(|id| !predicate(*id));
+        let images = &mut self.images;
+        let mut freed = 0;
+        images.retain(|id, image| {
+            let keep = self.order.contains(id);
+            if !keep {
+                freed += image.byte_len();
+            }
+            keep
+        });
+        self.stored_bytes -= freed;
+    }
+}
+
+fn decode(command: &Command, data: &[u8]) -> Result<TerminalImage, String> {
+    if command.medium != b'd' {
+        return Err("EINVAL:only direct transmission is supported".into());
+    }
+    if command.compression.is_some() {
+        return Err("EINVAL:compressed data is not supported".into());
+    }
+    let bytes = BASE64
+        .decode(data)
+        .map_err(|_| "EINVAL:invalid base64 payload".to_string())?;
+    let image = match command.format {
+        24 => TerminalImage::from_rgb(command.width, command.height, bytes),
+        32 => TerminalImage::from_rgba(command.width, command.height, bytes),
+        100 => TerminalImage::decode(&bytes),
+        format => return Err(format!("EINVAL:unsupported format {format}")),
+    };
+    image.map_err(|error| format!("EINVAL:{error:#}"))
+}
+
+fn display(command: &Command, image: Arc<TerminalImage>) -> KittyAction {
+    KittyAction::Display {
+        image,
+        id: command.id(),
+        columns: (command.columns != 0).then_some(command.columns),
+        rows: (command.rows != 0).then_some(command.rows),
+        move_cursor: command.move_cursor,
+    }
+}
+
+/// Responds to a command that named its image, unless asked to stay quiet.
+fn reply(command: &Command, result: Result<(), String>, actions: &mut Vec<KittyAction>) {
+    if command.image_id == 0 {
+        return;
+    }
+    let message = match result {
+        Ok(()) if command.quiet == 0 => "OK".to_string(),
+        Err(error) if command.quiet < 2 => error,
+        _ => return,
+    };
+    let mut response = format!("\x1b_Gi={}", command.image_id);
+    if command.placement_id != 0 {
+        response.push_str(&format!(",p={}", command.placement_id));
+    }
+    response.push_str(&format!(";{message}\x1b\\"));
+    actions.push(KittyAction::Reply(response.into_bytes()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replies(actions: &[KittyAction]) -> Vec<String> {
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                KittyAction::Reply(reply) => Some(String::from_utf8(reply.clone()).unwrap()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chunked_transmission_and_placement() {
+        let mut kitty = KittyGraphics::default();
+        // A 2x1 RGB image, sent in two chunks.
+        assert!(kitty.handle(b"a=t,f=24,s=2,v=1,i=7,m=1;/wAA").is_empty());
+        let actions = kitty.handle(b"m=0;AP8A");
+        assert_eq!(replies(&actions), vec!["\x1b_Gi=7;OK\x1b\\"]);
+        assert_eq!(kitty.images[&7].width, 2);
+
+        let actions = kitty.handle(b"a=p,i=7,p=3,c=4,C=1");
+        let KittyAction::Display {
+            id,
+            columns,
+            rows,
+            move_cursor,
+            ..
+        } = &actions[0]
+        else {
+            panic!("expected the image to be displayed");
+        };
+        assert_eq!(
+            *id,
+            Some(KittyPlacementId {
+                image_id: 7,
+                placement_id: 3
+            })
+        );
+        assert_eq!((*columns, *rows, *move_cursor), (Some(4), None, false));
+        assert_eq!(replies(&actions), vec!["\x1b_Gi=7,p=3;OK\x1b\\"]);
+
+        let actions = kitty.handle(b"a=d,d=I,i=7");
+        assert!(matches!(
+            actions[..],
+            [KittyAction::Delete(ImageDeletion::Image {
+                image_id: 7,
+                placement_id: None
+            })]
+        ));
+        assert!(kitty.images.is_empty());
+        assert_eq!(kitty.stored_bytes, 0);
+
+        let actions = kitty.handle(b"a=p,i=7,q=1");
+        assert_eq!(
+            replies(&actions),
+            vec!["\x1b_Gi=7;ENOENT:no image with that id\x1b\\"]
+        );
+    }
+
+    #[test]
+    fn test_rejects_unsupported_transmission() {
+        let mut kitty = KittyGraphics::default();
+        let actions = kitty.handle(b"a=q,t=f,i=31;L3RtcC9pbWFnZS5wbmc=");
+        assert_eq!(
+            replies(&actions),
+            vec!["\x1b_Gi=31;EINVAL:only direct transmission is supported\x1b\\"]
+        );
+        assert!(kitty.handle(b"a=T,f=32,s=1,v=1,q=2;AAAA").is_empty());
+    }
+}
@@ -0,0 +1,889 @@
+//! Inline images drawn by programs running in a terminal.
+//!
+//! Alacritty ignores the escape sequences that carry images, so they are taken out of
+//! the output before it reaches the emulator: Sixel (`DCS q`), iTerm2 inline images
+//! (`OSC 1337 ; File=`) and the Kitty graphics protocol (`APC G`). Each image is
+//! anchored to the cell the cursor was on, and the emulator is fed plain cursor
+//! movement in place of the sequence, so following output lands below or beside the
+//! image like it would in a terminal that draws images itself.
+
+mod iterm;
+mod kitty;
+mod sixel;
+mod tap;
+
+use std::{io::Cursor, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use collections::VecDeque;
+use gpui::RenderImage;
+
+use iterm::Dimension;
+use kitty::{KittyAction, KittyGraphics};
+pub use tap::{GraphicsPty, GraphicsTap, PendingGraphics};
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+const STRING_TERMINATOR: u8 = b'\\';
+
+/// Largest image sequence that is buffered; bigger ones are dropped.
+const MAX_SEQUENCE_LEN: usize = 64 * 1024 * 1024;
+/// Largest width or height of a decoded image.
+const MAX_IMAGE_DIMENSION: u32 = 8192;
+/// Largest decoded image, in pixels.
+const MAX_IMAGE_PIXELS: u64 = 4096 * 4096;
+/// Memory budget for the decoded images placed in one terminal. The oldest
+/// placements are dropped to stay below it.
+const MAX_PLACED_IMAGE_BYTES: usize = 256 * 1024 * 1024;
+/// Upper bound on the number of images placed in one terminal.
+const MAX_PLACEMENTS: usize = 1000;
+
+/// Base64 as sent by image tools, some of which leave out the padding.
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// A decoded image, ready to be painted.
+pub struct TerminalImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Arc<RenderImage>,
+}
+
+impl std::fmt::Debug for TerminalImage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TerminalImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TerminalImage {
+    /// Builds an image from RGBA pixels, row by row.
+    fn from_rgba(width: u32, height: u32, mut pixels: Vec<u8>) -> Result<Self> {
+        check_dimensions(width, height)?;
+        anyhow::ensure!(
+            pixels.len() as u64 == width as u64 * height as u64 * 4,
+            "expected {width}x{height} RGBA pixels, got {} bytes",
+            pixels.len()
+        );
+        // GPUI expects BGRA.
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        let buffer =
+            image::RgbaImage::from_raw(width, height, pixels).context("invalid image buffer")?;
+        Ok(Self {
+            width,
+            height,
+            data: Arc::new(RenderImage::new(vec![image::Frame::new(buffer)])),
+        })
+    }
+
+    /// Builds an image from RGB pixels, row by row.
+    fn from_rgb(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self> {
+        check_dimensions(width, height)?;
+        anyhow::ensure!(
+            pixels.len() as u64 == width as u64 * height as u64 * 3,
+            "expected {width}x{height} RGB pixels, got {} bytes",
+            pixels.len()
+        );
+        let pixels = pixels
+            .chunks_exact(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], u8::MAX])
+            .collect();
+        Self::from_rgba(width, height, pixels)
+    }
+
+    /// Decodes an image file, such as a PNG, JPEG or GIF.
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut reader = image::ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+        let mut limits = image::Limits::default();
+        limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+        limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+        limits.max_alloc = Some(MAX_IMAGE_PIXELS * 8);
+        reader.limits(limits);
+        let image = reader.decode()?.into_rgba8();
+        let (width, height) = image.dimensions();
+        Self::from_rgba(width, height, image.into_raw())
+    }
+
+    /// Memory taken by the decoded pixels.
+    pub fn byte_len(&self) -> usize {
+        self.width as usize * self.height as usize * 4
+    }
+}
+
+fn check_dimensions(width: u32, height: u32) -> Result<()> {
+    anyhow::ensure!(width > 0 && height > 0, "empty image");
+    anyhow::ensure!(
+        width <= MAX_IMAGE_DIMENSION
+            && height <= MAX_IMAGE_DIMENSION
+            && width as u64 * height as u64 <= MAX_IMAGE_PIXELS,
+        "{width}x{height} image is too large"
+    );
+    Ok(())
+}
+
+/// Identifies a Kitty image placement, so it can be replaced or deleted later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KittyPlacementId {
+    pub image_id: u32,
+    /// 0 when the program didn't name the placement.
+    pub placement_id: u32,
+}
+
+/// An image to show with its top left corner at the cursor.
+#[derive(Clone, Debug)]
+pub struct ImageDisplay {
+    pub image: Arc<TerminalImage>,
+    pub columns: usize,
+    pub rows: usize,
+    /// Whether the image fills its cells even if that distorts it.
+    pub stretch: bool,
+    pub kitty_id: Option<KittyPlacementId>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageDeletion {
+    /// Every Kitty placement.
+    All,
+    /// The placements of one Kitty image, or just one of them.
+    Image {
+        image_id: u32,
+        placement_id: Option<u32>,
+    },
+}
+
+/// Terminal output with the image sequences taken out, in order.
+#[derive(Debug)]
+pub enum GraphicsEvent {
+    /// Bytes for the emulator.
+    Text(Vec<u8>),
+    Image(ImageDisplay),
+    Delete(ImageDeletion),
+    /// A response to write back to the program.
+    Reply(Vec<u8>),
+}
+
+/// Size of the terminal's cells and grid, used to lay images out on the grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellMetrics {
+    pub cell_width: f32,
+    pub cell_height: f32,
+    pub columns: usize,
+    pub lines: usize,
+}
+
+impl Default for CellMetrics {
+    fn default() -> Self {
+        Self {
+            cell_width: 8.,
+            cell_height: 16.,
+            columns: 80,
+            lines: 24,
+        }
+    }
+}
+
+/// The size a program asked an image to be shown at, in pixels.
+#[derive(Clone, Copy, Debug, Default)]
+struct RequestedSize {
+    width: Option<f32>,
+    height: Option<f32>,
+    preserve_aspect_ratio: bool,
+}
+
+impl CellMetrics {
+    /// The cells an image covers, and whether it should be stretched to fill them.
+    fn layout(&self, image: &TerminalImage, requested: RequestedSize) -> (usize, usize, bool) {
+        let (image_width, image_height) = (image.width as f32, image.height as f32);
+        let (mut width, mut height) = match (requested.width, requested.height) {
+            (None, None) => (image_width, image_height),
+            (Some(width), None) if requested.preserve_aspect_ratio => {
+                (width, width * image_height / image_width)
+            }
+            (None, Some(height)) if requested.preserve_aspect_ratio => {
+                (height * image_width / image_height, height)
+            }
+            (Some(width), Some(height)) if requested.preserve_aspect_ratio => {
+                let scale = (width / image_width).min(height / image_height);
+                (image_width * scale, image_height * scale)
+            }
+            (width, height) => (width.unwrap_or(image_width), height.unwrap_or(image_height)),
+        };
+        let stretch = !requested.preserve_aspect_ratio
+            && (requested.width.is_some() || requested.height.is_some());
+
+        // Images wider than the terminal are scaled down to fit, as other terminals do.
+        let max_width = self.columns as f32 * self.cell_width;
+        if width > max_width && max_width > 0. {
+            height *= max_width / width;
+            width = max_width;
+        }
+
+        let columns = (width / self.cell_width).ceil().max(1.) as usize;
+        let rows = (height / self.cell_height).ceil().max(1.) as usize;
+        (columns, rows, stretch)
+    }
+}
+
+/// Where the cursor goes once an image is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CursorMovement {
+    /// To the line below the image, as with Sixel.
+    BelowImage,
+    /// Right of the image's last row, as with iTerm2 and Kitty images.
+    AfterImage,
+    Stay,
+}
+
+impl CursorMovement {
+    /// The bytes that make the emulator move the cursor past the image.
+    fn sequence(self, columns: usize, rows: usize) -> Vec<u8> {
+        match self {
+            CursorMovement::BelowImage => vec![b'\n'; rows],
+            CursorMovement::AfterImage => {
+                let mut sequence = vec![b'\n'; rows - 1];
+                sequence.extend(format!("\x1b[{columns}C").into_bytes());
+                sequence
+            }
+            CursorMovement::Stay => Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StringKind {
+    Dcs,
+    Osc,
+    Apc,
+}
+
+impl StringKind {
+    fn introducer(self) -> u8 {
+        match self {
+            StringKind::Dcs => b'P',
+            StringKind::Osc => b']',
+            StringKind::Apc => b'_',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ImageProtocol {
+    Sixel,
+    Iterm,
+    Kitty,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ParserState {
+    Ground,
+    Escape,
+    /// The start of a control string, held back until it's known whether it carries
+    /// an image.
+    Introducer(StringKind),
+    Payload(ImageProtocol),
+    PayloadEscape(ImageProtocol),
+}
+
+/// The OSC prefix of iTerm2 inline images. Other `OSC 1337` commands go to the emulator.
+const ITERM_FILE_PREFIX: &[u8] = b"1337;File=";
+/// Sixel parameters are a few short numbers (`P1;P2;P3`).
+const MAX_SIXEL_PARAMS_LEN: usize = 16;
+
+/// Incremental scanner that takes image sequences out of a raw terminal byte stream
+/// and decodes them.
+pub struct GraphicsParser {
+    state: ParserState,
+    introducer: Vec<u8>,
+    payload: Vec<u8>,
+    /// Set when the current sequence outgrew [`MAX_SEQUENCE_LEN`].
+    overflowed: bool,
+    metrics: CellMetrics,
+    kitty: KittyGraphics,
+}
+
+impl Default for GraphicsParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GraphicsParser {
+    pub fn new() -> Self {
+        Self {
+            state: ParserState::Ground,
+            introducer: Vec::new(),
+            payload: Vec::new(),
+            overflowed: false,
+            metrics: CellMetrics::default(),
+            kitty: KittyGraphics::default(),
+        }
+    }
+
+    pub fn set_metrics(&mut self, metrics: CellMetrics) {
+        self.metrics = metrics;
+    }
+
+    /// Scans `bytes`, returning the output for the emulator and the images in between.
+    /// Sequences split across calls are handled: their start is held back until the
+    /// rest arrives.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<GraphicsEvent> {
+        let mut events = Vec::new();
+        let mut text = Vec::with_capacity(bytes.len());
+        let mut index = 0;
+
+        while index < bytes.len() {
+            let byte = bytes[index];
+            match self.state {
+                ParserState::Ground => {
+                    let rest = &bytes[index..];
+                    match rest.iter().position(|byte| *byte == ESC) {
+                        Some(offset) => {
+                            text.extend_from_slice(&rest[..offset]);
+                            index += offset;
+                            self.state = ParserState::Escape;
+                        }
+                        None => {
+                            text.extend_from_slice(rest);
+                            break;
+                        }
+                    }
+                }
+                ParserState::Escape => {
+                    let kind = match byte {
+                        b'P' => StringKind::Dcs,
+                        b']' => StringKind::Osc,
+                        b'_' => StringKind::Apc,
+                        _ => {
+                            text.push(ESC);
+                            self.state = ParserState::Ground;
+                            continue;
+                        }
+                    };
+                    self.introducer.clear();
+                    self.state = ParserState::Introducer(kind);
+                }
+                ParserState::Introducer(kind) => match self.classify(kind, byte) {
+                    Some(Some(protocol)) => {
+                        self.payload.clear();
+                        self.overflowed = false;
+                        self.state = ParserState::Payload(protocol);
+                    }
+                    Some(None) => self.introducer.push(byte),
+                    None => {
+                        // Not an image, let the emulator have the sequence.
+                        text.push(ESC);
+                        text.push(kind.introducer());
+                        text.append(&mut self.introducer);
+                        self.state = ParserState::Ground;
+                        continue;
+                    }
+                },
+                ParserState::Payload(protocol) => {
+                    let rest = &bytes[index..];
+                    let end = rest.iter().position(|byte| {
+                        *byte == ESC || (*byte == BEL && protocol == ImageProtocol::Iterm)
+                    });
+                    let data = &rest[..end.unwrap_or(rest.len())];
+                    if self.payload.len() + data.len() > MAX_SEQUENCE_LEN {
+                        self.overflowed = true;
+                        self.payload.clear();
+                    } else if !self.overflowed {
+                        self.payload.extend_from_slice(data);
+                    }
+                    let Some(end) = end else {
+                        break;
+                    };
+                    index += end;
+                    if rest[end] == BEL {
+                        self.complete(protocol, &mut text, &mut events);
+                        self.state = ParserState::Ground;
+                    } else {
+                        self.state = ParserState::PayloadEscape(protocol);
+                    }
+                }
+                ParserState::PayloadEscape(protocol) => {
+                    if byte == STRING_TERMINATOR {
+                        self.complete(protocol, &mut text, &mut events);
+                        self.state = ParserState::Ground;
+                    } else {
+                        // The sequence was cut short by a new escape sequence.
+                        self.payload.clear();
+                        self.state = ParserState::Escape;
+                        continue;
+                    }
+                }
+            }
+            index += 1;
+        }
+
+        if !text.is_empty() {
+            events.push(GraphicsEvent::Text(text));
+        }
+        events
+    }
+
+    /// Decides whether a control string carries an image, given its next byte:
+    /// `None` if it doesn't, `Some(None)` if that isn't known yet.
+    fn classify(&self, kind: StringKind, byte: u8) -> Option<Option<ImageProtocol>> {
+        match kind {
+            StringKind::Dcs => match byte {
+                b'q' => Some(Some(ImageProtocol::Sixel)),
+                b'0'..=b'9' | b';' if self.introducer.len() < MAX_SIXEL_PARAMS_LEN => Some(None),
+                _ => None,
+            },
+            StringKind::Osc => {
+                let matched = self.introducer.len();
+                if ITERM_FILE_PREFIX.get(matched) != Some(&byte) {
+                    None
+                } else if matched + 1 == ITERM_FILE_PREFIX.len() {
+                    Some(Some(ImageProtocol::Iterm))
+                } else {
+                    Some(None)
+                }
+            }
+            StringKind::Apc => (byte == b'G').then_some(Some(ImageProtocol::Kitty)),
+        }
+    }
+
+    fn complete(
+        &mut self,
+        protocol: ImageProtocol,
+        text: &mut Vec<u8>,
+        events: &mut Vec<GraphicsEvent>,
+    ) {
+        let payload = std::mem::take(&mut self.payload);
+        if std::mem::take(&mut self.overflowed) {
+            log::warn!("dropped {protocol:?} image larger than {MAX_SEQUENCE_LEN} bytes");
+            if protocol == ImageProtocol::Kitty {
+                self.kitty.abort_transmission();
+            }
+            return;
+        }
+
+        if !text.is_empty() {
+            events.push(GraphicsEvent::Text(std::mem::take(text)));
+        }
+        match protocol {
+            ImageProtocol::Sixel => match sixel::decode(&payload) {
+                Ok(image) => self.display(
+                    Arc::new(image),
+                    RequestedSize::default(),
+                    CursorMovement::BelowImage,
+                    None,
+                    events,
+                ),
+                Err(error) => log::debug!("failed to decode sixel image: {error:#}"),
+            },
+            ImageProtocol::Iterm => {
+                let file = match iterm::parse(&payload) {
+                    Ok(file) if file.inline => file,
+                    // Files sent for download rather than display are not supported.
+                    Ok(_) => return,
+                    Err(error) => {
+                        log::debug!("invalid iTerm2 inline image: {error:#}");
+                        return;
+                    }
+                };
+                match TerminalImage::decode(&file.data) {
+                    Ok(image) => {
+                        let requested = RequestedSize {
+                            width: self.resolve_dimension(file.width, true),
+                            height: self.resolve_dimension(file.height, false),
+                            preserve_aspect_ratio: file.preserve_aspect_ratio,
+                        };
+                        self.display(
+                            Arc::new(image),
+                            requested,
+                            CursorMovement::AfterImage,
+                            None,
+                            events,
+                        );
+                    }
+                    Err(error) => log::debug!("failed to decode iTerm2 inline image: {error:#}"),
+                }
+            }
+            ImageProtocol::Kitty => {
+                for action in self.kitty.handle(&payload) {
+                    match action {
+                        KittyAction::Display {
+                            image,
+                            id,
+                            columns,
+                            rows,
+                            move_cursor,
+                        } => {
+                            let requested = RequestedSize {
+                                width: columns
+                                    .map(|columns| columns as f32 * self.metrics.cell_width),
+                                height: rows.map(|rows| rows as f32 * self.metrics.cell_height),
+                                preserve_aspect_ratio: columns.is_none() || rows.is_none(),
+                            };
+                            let movement = if move_cursor {
+                                CursorMovement::AfterImage
+                            } else {
+                                CursorMovement::Stay
+                            };
+                            self.display(image, requested, movement, id, events);
+                        }
+                        KittyAction::Delete(deletion) => {
+                            events.push(GraphicsEvent::Delete(deletion))
+                        }
+                        KittyAction::Reply(reply) => events.push(GraphicsEvent::Reply(reply)),
+                    }
+                }
+            }
+        }
+    }
+
+    fn resolve_dimension(&self, dimension: Dimension, horizontal: bool) -> Option<f32> {
+        let (cell, cells) = if horizontal {
+            (self.metrics.cell_width, self.metrics.columns)
+        } else {
+            (self.metrics.cell_height, self.metrics.lines)
+        };
+        match dimension {
+            Dimension::Auto => None,
+            Dimension::Cells(count) => Some(count as f32 * cell),
+            Dimension::Pixels(pixels) => Some(pixels as f32),
+            Dimension::Percent(percent) => Some(percent as f32 / 100. * cells as f32 * cell),
+        }
+    }
+
+    fn display(
+        &self,
+        image: Arc<TerminalImage>,
+        requested: RequestedSize,
+        movement: CursorMovement,
+        kitty_id: Option<KittyPlacementId>,
+        events: &mut Vec<GraphicsEvent>,
+    ) {
+        let (columns, rows, stretch) = self.metrics.layout(&image, requested);
+        events.push(GraphicsEvent::Image(ImageDisplay {
+            image,
+            columns,
+            rows,
+            stretch,
+            kitty_id,
+        }));
+        let movement = movement.sequence(columns, rows);
+        if !movement.is_empty() {
+            events.push(GraphicsEvent::Text(movement));
+        }
+    }
+}
+
+/// An image shown in the terminal.
+///
+/// Lines are absolute, like those of [`crate::command_blocks::CommandBlock`], so the
+/// image scrolls with the text around it.
+#[derive(Clone, Debug)]
+pub struct ImagePlacement {
+    pub image: Arc<TerminalImage>,
+    /// The line of the image's top row.
+    pub line: i64,
+    pub column: usize,
+    pub columns: usize,
+    pub rows: usize,
+    pub stretch: bool,
+    /// Images drawn on the alternate screen go away with it.
+    pub alt_screen: bool,
+    kitty_id: Option<KittyPlacementId>,
+}
+
+/// The images shown in a terminal, oldest first.
+#[derive(Default)]
+pub struct TerminalImages {
+    placements: VecDeque<ImagePlacement>,
+    bytes_used: usize,
+    /// Images no longer shown, whose textures the renderer can release.
+    evicted: Vec<Arc<RenderImage>>,
+}
+
+impl TerminalImages {
+    pub fn is_empty(&self) -> bool {
+        self.placements.is_empty()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &ImagePlacement> + '_ {
+        self.placements.iter()
+    }
+
+    /// Shows an image with its top left corner at the given line and column. Lines of the
+    /// primary screen are absolute, as tracked by [`crate::command_blocks::ScrollTracker`],
+    /// while lines of the alternate screen are those of its grid.
+    pub fn place(&mut self, display: ImageDisplay, line: i64, column: usize, alt_screen: bool) {
+        // A new image in the same spot replaces the old one, so programs that redraw
+        // images don't pile them up.
+        let mut index = 0;
+        while index < self.placements.len() {
+            let placement = &self.placements[index];
+            let replaced = match (display.kitty_id, placement.kitty_id) {
+                (Some(new), Some(old)) if new.placement_id != 0 => new == old,
+                _ => false,
+            } || (placement.line == line
+                && placement.column == column
+                && placement.alt_screen == alt_screen);
+            if replaced {
+                self.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+
+        if !self.is_placed(&display.image) {
+            self.bytes_used += display.image.byte_len();
+        }
+        self.placements.push_back(ImagePlacement {
+            image: display.image,
+            line,
+            column,
+            columns: display.columns,
+            rows: display.rows,
+            stretch: display.stretch,
+            alt_screen,
+            kitty_id: display.kitty_id,
+        });
+        while self.placements.len() > 1
+            && (self.bytes_used > MAX_PLACED_IMAGE_BYTES || self.placements.len() > MAX_PLACEMENTS)
+        {
+            self.remove(0);
+        }
+    }
+
+    pub fn delete(&mut self, deletion: ImageDeletion) {
+        self.remove_where(|placement| {
+            placement.kitty_id.is_some_and(|id| match deletion {
+                ImageDeletion::All => true,
+                ImageDeletion::Image {
+                    image_id,
+                    placement_id,
+                } => {
+                    id.image_id == image_id
+                        && placement_id.is_none_or(|placement_id| id.placement_id == placement_id)
+                }
+            })
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.remove_where(|_| true);
+    }
+
+    /// Drops the images of an alternate screen that is no longer shown.
+    pub fn clear_alt_screen(&mut self) {
+        self.remove_where(|placement| placement.alt_screen);
+    }
+
+    /// Drops the images of the primary screen that scrolled out of the history, whose
+    /// first line is `first_line`.
+    pub fn prune(&mut self, first_line: i64) {
+        self.remove_where(|placement| {
+            !placement.alt_screen && placement.line + (placement.rows as i64) <= first_line
+        });
+    }
+
+    /// Images no longer shown since the last call.
+    pub fn take_evicted(&mut self) -> Vec<Arc<RenderImage>> {
+        std::mem::take(&mut self.evicted)
+    }
+
+    fn is_placed(&self, image: &Arc<TerminalImage>) -> bool {
+        self.placements
+            .iter()
+            .any(|placement| Arc::ptr_eq(&placement.image, image))
+    }
+
+    fn remove_where(&mut self, mut predicate: impl FnMut(&ImagePlacement) -> bool) {
+        let mut index = 0;
+        while index < self.placements.len() {
+            if predicate(&self.placements[index]) {
+                self.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    fn remove(&mut self, index: usize) {
+        let Some(placement) = self.placements.remove(index) else {
+            return;
+        };
+        if !self.is_placed(&placement.image) {
+            self.bytes_used -= placement.image.byte_len();
+            self.evicted.push(placement.image.data.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(events: &[GraphicsEvent]) -> Vec<u8> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                GraphicsEvent::Text(text) => Some(text.as_slice()),
+                _ => None,
+            })
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn test_takes_images_out_of_output() {
+        let mut parser = GraphicsParser::new();
+        parser.set_metrics(CellMetrics {
+            cell_width: 10.,
+            cell_height: 20.,
+            columns: 80,
+            lines: 24,
+        });
+
+        // A 12x30 red sixel image, split in the middle of the introducer and the data.
+        let mut events = parser.feed(b"before\x1bP0;1");
+        assert_eq!(text(&events), b"before");
+        events.extend(parser.feed(b";0q\"1;1;12;30#1;2;100;0;0#1!12~-!12~-!12~-!12"));
+        events.extend(parser.feed(b"~-!12~\x1b\\after"));
+
+        let images: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                GraphicsEvent::Image(display) => Some(display),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(images.len(), 1);
+        assert_eq!((images[0].image.width, images[0].image.height), (12, 30));
+        assert_eq!((images[0].columns, images[0].rows), (2, 2));
+        assert_eq!(text(&events), b"before\n\nafter");
+    }
+
+    #[test]
+    fn test_passes_other_sequences_through() {
+        let mut parser = GraphicsParser::new();
+        let input =
+            b"\x1b[31mred\x1b]0;title\x07\x1b]1337;SetMark\x07\x1bP$qm\x1b\\\x1b_Xapc\x1b\\";
+        let events = parser.feed(input);
+        assert_eq!(text(&events), input.to_vec());
+    }
+
+    #[test]
+    fn test_images_follow_scrollback() {
+        let image = Arc::new(TerminalImage::from_rgb(2, 2, vec![0; 12]).unwrap());
+        let display = |kitty_id| ImageDisplay {
+            image: image.clone(),
+            columns: 1,
+            rows: 1,
+            stretch: false,
+            kitty_id,
+        };
+
+        let mut images = TerminalImages::default();
+        images.place(display(None), 4, 0, false);
+        images.place(
+            display(Some(KittyPlacementId {
+                image_id: 1,
+                placement_id: 0,
+            })),
+            12,
+            0,
+            false,
+        );
+        images.place(display(None), 12, 0, false);
+        assert_eq!(images.iter().count(), 2);
+        assert!(images.take_evicted().is_empty());
+
+        images.prune(5);
+        assert_eq!(
+            images
+                .iter()
+                .map(|placement| placement.line)
+                .collect::<Vec<_>>(),
+            vec![12]
+        );
+
+        images.delete(ImageDeletion::All);
+        assert_eq!(images.iter().count(), 1);
+        images.clear();
+        assert!(images.is_empty());
+        assert_eq!(images.take_evicted().len(), 1);
+    }
+
+    #[test]
+    fn test_images_keep_their_lines_once_scrollback_is_full() {
+        use crate::command_blocks::ScrollTracker;
+        use alacritty_terminal::{
+            Term,
+            event::VoidListener,
+            grid::Dimensions as _,
+            index::{Column, Line},
+            term::{Config, test::TermSize},
+            vte::ansi::{Processor, StdSyncHandler},
+        };
+
+        let config = Config {
+            scrolling_history: 10,
+            ..Config::default()
+        };
+        let mut term = Term::new(config, &TermSize::new(20, 5), VoidListener);
+        let mut processor = Processor::<StdSyncHandler>::new();
+        let mut tracker = ScrollTracker::default();
+        let mut output =
+            |term: &mut Term<VoidListener>, tracker: &mut ScrollTracker, text: &str| {
+                processor.advance(term, text.as_bytes());
+                tracker.update(term);
+            };
+        let line_text = |term: &Term<VoidListener>, line: Line| {
+            (0..3)
+                .map(|column| term.grid()[line][Column(column)].c)
+                .collect::<String>()
+        };
+
+        let mut images = TerminalImages::default();
+        let image = Arc::new(TerminalImage::from_rgb(2, 2, vec![0; 12]).unwrap());
+        output(&mut term, &mut tracker, "a0\r\na1\r\nimg");
+        let line = tracker.absolute_line(term.grid().cursor.point.line);
+        images.place(
+            ImageDisplay {
+                image,
+                columns: 1,
+                rows: 1,
+                stretch: false,
+                kitty_id: None,
+            },
+            line,
+            0,
+            false,
+        );
+
+        output(&mut term, &mut tracker, "\r\n");
+        for index in 0..12 {
+            output(&mut term, &mut tracker, &format!("b{index}\r\n"));
+        }
+
+        // The scrollback stopped growing, but the image still points at its line.
+        assert_eq!(term.history_size(), 10);
+        assert_eq!(tracker.scrolled_lines(), 11);
+        images.prune(tracker.first_line(term.history_size()));
+        let placement = images.iter().next().unwrap();
+        let grid_line = Line((placement.line - tracker.scrolled_lines()) as i32);
+        assert_eq!(line_text(&term, grid_line), "img");
+
+        // Once its line is dropped from the scrollback, so is the image.
+        for index in 0..5 {
+            output(&mut term, &mut tracker, &format!("c{index}\r\n"));
+        }
+        images.prune(tracker.first_line(term.history_size()));
+        assert!(images.is_empty());
+        assert_eq!(images.take_evicted().len(), 1);
+    }
+}
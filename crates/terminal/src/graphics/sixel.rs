@@ -0,0 +1,223 @@
+//! Decoding of Sixel image data, the part of a `DCS q` sequence after the `q`.
+//!
+//! Pixels the image doesn't set are left transparent, whatever background mode the
+//! sequence asked for, so images look right on any theme.
+
+use anyhow::{Result, bail};
+
+use super::{MAX_IMAGE_DIMENSION, MAX_IMAGE_PIXELS, TerminalImage};
+
+/// Number of color registers. Programs rarely use more than 256.
+const PALETTE_SIZE: usize = 1024;
+
+/// The VT340's default colors, which images without their own palette rely on.
+const DEFAULT_PALETTE: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [51, 51, 204],
+    [204, 33, 33],
+    [51, 204, 51],
+    [204, 51, 204],
+    [51, 204, 204],
+    [204, 204, 51],
+    [135, 135, 135],
+    [66, 66, 66],
+    [84, 84, 153],
+    [153, 66, 66],
+    [66, 153, 66],
+    [153, 66, 153],
+    [66, 153, 153],
+    [153, 153, 66],
+    [204, 204, 204],
+];
+
+/// A pixel as RGBA bytes packed into a `u32`, 0 for a pixel that was never set.
+fn pack([r, g, b]: [u8; 3]) -> u32 {
+    u32::from_le_bytes([r, g, b, u8::MAX])
+}
+
+pub(super) fn decode(data: &[u8]) -> Result<TerminalImage> {
+    let mut palette = vec![pack([0, 0, 0]); PALETTE_SIZE];
+    for (register, color) in DEFAULT_PALETTE.into_iter().enumerate() {
+        palette[register] = pack(color);
+    }
+    let mut color = palette[0];
+    let mut rows: Vec<Vec<u32>> = Vec::new();
+    let (mut x, mut band, mut width) = (0, 0, 0);
+    let (mut raster_width, mut raster_height) = (0, 0);
+
+    let mut index = 0;
+    while index < data.len() {
+        let byte = data[index];
+        index += 1;
+        let (sixel, count) = match byte {
+            b'"' => {
+                let params = read_params(data, &mut index);
+                raster_width = params.get(2).copied().unwrap_or(0);
+                raster_height = params.get(3).copied().unwrap_or(0);
+                continue;
+            }
+            b'#' => {
+                let params = read_params(data, &mut index);
+                let register = params.first().copied().unwrap_or(0) % PALETTE_SIZE;
+                if let [_, space, a, b, c] = params[..]
+                    && let Some(defined) = define_color(space, a, b, c)
+                {
+                    palette[register] = defined;
+                }
+                color = palette[register];
+                continue;
+            }
+            b'!' => {
+                let count = read_params(data, &mut index)
+                    .first()
+                    .copied()
+                    .unwrap_or(1)
+                    .max(1);
+                match data.get(index) {
+                    Some(&sixel @ 0x3f..=0x7e) => {
+                        index += 1;
+                        (sixel, count)
+                    }
+                    _ => continue,
+                }
+            }
+            b'$' => {
+                x = 0;
+                continue;
+            }
+            b'-' => {
+                x = 0;
+                band += 1;
+                continue;
+            }
+            0x3f..=0x7e => (byte, 1),
+            _ => continue,
+        };
+
+        if x + count > MAX_IMAGE_DIMENSION as usize {
+            bail!("sixel image is wider than {MAX_IMAGE_DIMENSION} pixels");
+        }
+        let bits = sixel - 0x3f;
+        for bit in 0..6 {
+            if bits & (1 << bit) == 0 {
+                continue;
+            }
+            let y = band * 6 + bit;
+            if y >= MAX_IMAGE_DIMENSION as usize {
+                bail!("sixel image is taller than {MAX_IMAGE_DIMENSION} pixels");
+            }
+            if rows.len() <= y {
+                rows.resize_with(y + 1, Vec::new);
+            }
+            let row = &mut rows[y];
+            if row.len() < x + count {
+                row.resize(x + count, 0);
+            }
+            row[x..x + count].fill(color);
+        }
+        x += count;
+        width = width.max(x);
+    }
+
+    let width = width.max(raster_width.min(MAX_IMAGE_DIMENSION as usize));
+    let height = rows
+        .len()
+        .max(raster_height.min(MAX_IMAGE_DIMENSION as usize));
+    if width as u64 * height as u64 > MAX_IMAGE_PIXELS {
+        bail!("{width}x{height} sixel image is too large");
+    }
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        let row = rows.get(y).map(Vec::as_slice).unwrap_or_default();
+        for x in 0..width {
+            pixels.extend(row.get(x).copied().unwrap_or(0).to_le_bytes());
+        }
+    }
+    TerminalImage::from_rgba(width as u32, height as u32, pixels)
+}
+
+/// Reads `;` separated numbers starting at `index`, leaving it on the first other byte.
+fn read_params(data: &[u8], index: &mut usize) -> Vec<usize> {
+    let mut params = Vec::new();
+    let mut current = 0usize;
+    let mut any = false;
+    while let Some(&byte) = data.get(*index) {
+        match byte {
+            b'0'..=b'9' => {
+                current = current
+                    .saturating_mul(10)
+                    .saturating_add((byte - b'0') as usize);
+                any = true;
+            }
+            b';' => {
+                params.push(current);
+                current = 0;
+                any = true;
+            }
+            _ => break,
+        }
+        *index += 1;
+    }
+    if any {
+        params.push(current);
+    }
+    params
+}
+
+/// A color register definition: `2` is RGB in percent, `1` is DEC's HLS.
+fn define_color(space: usize, a: usize, b: usize, c: usize) -> Option<u32> {
+    let percent = |value: usize| (value.min(100) * 255 / 100) as u8;
+    match space {
+        1 => Some(pack(hls_to_rgb(a % 360, b.min(100), c.min(100)))),
+        2 => Some(pack([percent(a), percent(b), percent(c)])),
+        _ => None,
+    }
+}
+
+/// Converts DEC HLS, where hue 0 is blue rather than red, to RGB.
+fn hls_to_rgb(hue: usize, lightness: usize, saturation: usize) -> [u8; 3] {
+    let hue = ((hue + 240) % 360) as f32;
+    let lightness = lightness as f32 / 100.;
+    let saturation = saturation as f32 / 100.;
+
+    let chroma = (1. - (2. * lightness - 1.).abs()) * saturation;
+    let sector = hue / 60.;
+    let x = chroma * (1. - (sector % 2. - 1.).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x),
+    };
+    let offset = lightness - chroma / 2.;
+    let channel = |value: f32| ((value + offset) * 255.).round().clamp(0., 255.) as u8;
+    [channel(r), channel(g), channel(b)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(image: &TerminalImage, x: u32, y: u32) -> [u8; 4] {
+        let bytes = image.data.as_bytes(0).unwrap();
+        let offset = ((y * image.width + x) * 4) as usize;
+        // Stored as BGRA.
+        let [b, g, r, a] = bytes[offset..offset + 4].try_into().unwrap();
+        [r, g, b, a]
+    }
+
+    #[test]
+    fn test_decodes_colors_repeats_and_bands() {
+        // Blue columns with a transparent gap, above a pixel of HLS red.
+        let image = decode(b"#1;2;0;0;100#1!2~$!3?~-#2;1;120;50;100@").unwrap();
+        assert_eq!((image.width, image.height), (4, 7));
+        assert_eq!(pixel(&image, 0, 0), [0, 0, 255, 255]);
+        assert_eq!(pixel(&image, 1, 5), [0, 0, 255, 255]);
+        assert_eq!(pixel(&image, 2, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(&image, 3, 3), [0, 0, 255, 255]);
+        assert_eq!(pixel(&image, 0, 6), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 1, 6), [0, 0, 0, 0]);
+    }
+}
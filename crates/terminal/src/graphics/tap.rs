@@ -0,0 +1,234 @@
+use std::{
+    io::{self, Read},
+    sync::Arc,
+};
+
+use alacritty_terminal::{
+    event::{Event as AlacTermEvent, OnResize, WindowSize},
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+};
+use collections::VecDeque;
+use futures::channel::mpsc::UnboundedSender;
+use parking_lot::Mutex;
+
+use super::{CellMetrics, GraphicsEvent, GraphicsParser};
+use crate::command_blocks::OutputPosition;
+
+/// An image or deletion read from a local PTY, positioned relative to the cursor
+/// once the emulator has parsed the surrounding output.
+#[derive(Debug)]
+pub struct PendingGraphics {
+    /// An [`GraphicsEvent::Image`] or [`GraphicsEvent::Delete`].
+    pub event: GraphicsEvent,
+    /// Line feeds passed to the emulator after the image, i.e. how far above the
+    /// cursor it is.
+    pub line_feeds_after: u64,
+    /// The column the cursor was at when the image was seen.
+    pub column: usize,
+}
+
+/// Takes image sequences out of a local PTY's output before alacritty parses it.
+///
+/// Like [`crate::command_blocks::PendingPromptMarks`], images are stored with the
+/// number of line feeds passed to the emulator so far, and resolved to grid positions
+/// by the terminal once the output has been parsed.
+pub struct GraphicsTap {
+    parser: GraphicsParser,
+    /// Parsed output not yet handed to the emulator, because it didn't fit the read.
+    queue: VecDeque<GraphicsEvent>,
+    pending: Vec<(GraphicsEvent, u64, usize)>,
+    position: OutputPosition,
+    events: UnboundedSender<AlacTermEvent>,
+}
+
+impl GraphicsTap {
+    pub fn new(events: UnboundedSender<AlacTermEvent>) -> Self {
+        Self {
+            parser: GraphicsParser::new(),
+            queue: VecDeque::new(),
+            pending: Vec::new(),
+            position: OutputPosition::default(),
+            events,
+        }
+    }
+
+    pub fn set_metrics(&mut self, metrics: CellMetrics) {
+        self.parser.set_metrics(metrics);
+    }
+
+    pub fn drain(&mut self) -> Vec<PendingGraphics> {
+        let line_feeds = self.position.line_feeds;
+        self.pending
+            .drain(..)
+            .map(|(event, seen, column)| PendingGraphics {
+                event,
+                line_feeds_after: line_feeds - seen,
+                column,
+            })
+            .collect()
+    }
+
+    /// Moves queued output into `buf`, returning how many bytes were written.
+    fn fill(&mut self, buf: &mut [u8]) -> usize {
+        let mut written = 0;
+        while written < buf.len()
+            && let Some(event) = self.queue.pop_front()
+        {
+            match event {
+                GraphicsEvent::Text(mut text) => {
+                    let len = text.len().min(buf.len() - written);
+                    buf[written..written + len].copy_from_slice(&text[..len]);
+                    self.position.advance(&text[..len]);
+                    written += len;
+                    if len < text.len() {
+                        text.drain(..len);
+                        self.queue.push_front(GraphicsEvent::Text(text));
+                    }
+                }
+                GraphicsEvent::Reply(reply) => {
+                    self.events
+                        .unbounded_send(AlacTermEvent::PtyWrite(
+                            String::from_utf8_lossy(&reply).into_owned(),
+                        ))
+                        .ok();
+                }
+                event @ (GraphicsEvent::Image(_) | GraphicsEvent::Delete(_)) => {
+                    self.pending
+                        .push((event, self.position.line_feeds, self.position.column));
+                    self.events.unbounded_send(AlacTermEvent::Wakeup).ok();
+                }
+            }
+        }
+        written
+    }
+}
+
+/// Wraps a local PTY so its output passes through a [`GraphicsTap`].
+pub struct GraphicsPty<P: EventedPty> {
+    reader: GraphicsReader<P>,
+}
+
+pub struct GraphicsReader<P: EventedPty> {
+    pty: P,
+    tap: Arc<Mutex<GraphicsTap>>,
+}
+
+impl<P: EventedPty> GraphicsPty<P> {
+    pub fn new(pty: P, tap: Arc<Mutex<GraphicsTap>>) -> Self {
+        Self {
+            reader: GraphicsReader { pty, tap },
+        }
+    }
+}
+
+impl<P: EventedPty> Read for GraphicsReader<P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        // Keep reading while the output only carries image data, so alacritty only
+        // stops once the PTY is drained.
+        loop {
+            {
+                let mut tap = self.tap.lock();
+                let written = tap.fill(buf);
+                if written > 0 {
+                    return Ok(written);
+                }
+            }
+            let read = self.pty.reader().read(buf)?;
+            if read == 0 {
+                return Ok(0);
+            }
+            let mut tap = self.tap.lock();
+            let events = tap.parser.feed(&buf[..read]);
+            tap.queue.extend(events);
+        }
+    }
+}
+
+impl<P: EventedPty> EventedReadWrite for GraphicsPty<P> {
+    type Reader = GraphicsReader<P>;
+    type Writer = P::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<polling::Poller>,
+        interest: polling::Event,
+        mode: polling::PollMode,
+    ) -> io::Result<()> {
+        unsafe { self.reader.pty.register(poll, interest, mode) }
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<polling::Poller>,
+        interest: polling::Event,
+        mode: polling::PollMode,
+    ) -> io::Result<()> {
+        self.reader.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<polling::Poller>) -> io::Result<()> {
+        self.reader.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        &mut self.reader
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.reader.pty.writer()
+    }
+}
+
+impl<P: EventedPty> EventedPty for GraphicsPty<P> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.reader.pty.next_child_event()
+    }
+}
+
+impl<P: EventedPty + OnResize> OnResize for GraphicsPty<P> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.reader.pty.on_resize(window_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions_images_by_line_feeds_passed_on() {
+        let (events_tx, _events_rx) = futures::channel::mpsc::unbounded();
+        let mut tap = GraphicsTap::new(events_tx);
+        tap.set_metrics(CellMetrics {
+            cell_width: 10.,
+            cell_height: 10.,
+            columns: 80,
+            lines: 24,
+        });
+        let events = tap
+            .parser
+            .feed(b"$ cat\r\n\x1b[1mab\x1bPq#0;2;100;100;100!15~-!15~\x1b\\$ ");
+        tap.queue.extend(events);
+
+        // A small buffer splits the output around the image over several reads.
+        let mut output = Vec::new();
+        let mut buf = [0; 4];
+        loop {
+            let written = tap.fill(&mut buf);
+            if written == 0 {
+                break;
+            }
+            output.extend_from_slice(&buf[..written]);
+        }
+        assert_eq!(output, b"$ cat\r\n\x1b[1mab\n\n$ ");
+
+        let pending = tap.drain();
+        assert_eq!(pending.len(), 1);
+        assert!(matches!(pending[0].event, GraphicsEvent::Image(_)));
+        assert_eq!(pending[0].line_feeds_after, 2);
+        assert_eq!(pending[0].column, 2);
+    }
+}
@@ -0,0 +1,394 @@
+//! Recording of terminal sessions in the asciicast v2 format, as used by asciinema.
+//!
+//! A recording is a JSON header line followed by one `[time, code, data]` line per
+//! event, with the time in seconds since the recording started.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write as _},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+};
+use anyhow::{Context as _, Result, bail};
+use collections::HashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// The file extension of asciicast recordings.
+pub const CAST_EXTENSION: &str = "cast";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AsciicastHeader {
+    pub version: u32,
+    pub width: usize,
+    pub height: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    /// Longest pause, in seconds, players should keep between events.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_time_limit: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<HashMap<String, String>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CastEventKind {
+    /// `o`: data written to the terminal.
+    Output,
+    /// `i`: data typed by the user.
+    Input,
+    /// `r`: the terminal was resized to `{columns}x{lines}`.
+    Resize,
+    /// `m`: a marker, with an optional label.
+    Marker,
+}
+
+impl CastEventKind {
+    fn code(self) -> &'static str {
+        match self {
+            Self::Output => "o",
+            Self::Input => "i",
+            Self::Resize => "r",
+            Self::Marker => "m",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "o" => Some(Self::Output),
+            "i" => Some(Self::Input),
+            "r" => Some(Self::Resize),
+            "m" => Some(Self::Marker),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CastEvent {
+    /// Seconds since the recording started.
+    pub time: f64,
+    pub kind: CastEventKind,
+    pub data: String,
+}
+
+impl CastEvent {
+    /// The size a resize event sets, as `(columns, lines)`.
+    pub fn size(&self) -> Option<(usize, usize)> {
+        if self.kind != CastEventKind::Resize {
+            return None;
+        }
+        let (columns, lines) = self.data.split_once('x')?;
+        Some((columns.parse().ok()?, lines.parse().ok()?))
+    }
+}
+
+/// A parsed recording.
+#[derive(Clone, Debug)]
+pub struct Asciicast {
+    pub header: AsciicastHeader,
+    /// Events in order of time.
+    pub events: Vec<CastEvent>,
+}
+
+impl Asciicast {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        let header: AsciicastHeader = serde_json::from_str(lines.next().context("empty file")?)
+            .context("invalid asciicast header")?;
+        if header.version != 2 {
+            bail!("unsupported asciicast version {}", header.version);
+        }
+
+        let mut events = Vec::new();
+        for (index, line) in lines.enumerate() {
+            let (time, code, data): (f64, String, String) = serde_json::from_str(line)
+                .with_context(|| format!("invalid event on line {}", index + 2))?;
+            // Later versions of the format may add event types, which players skip.
+            if let Some(kind) = CastEventKind::from_code(&code) {
+                events.push(CastEvent { time, kind, data });
+            }
+        }
+        events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(Self { header, events })
+    }
+
+    /// Length of the recording in seconds.
+    pub fn duration(&self) -> f64 {
+        self.events.last().map_or(0., |event| event.time)
+    }
+}
+
+/// Writes a terminal session to an asciicast file as it happens.
+pub struct Recorder {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    started_at: Instant,
+    record_input: bool,
+    /// Trailing bytes of an incomplete UTF-8 character, completed by the next chunk.
+    partial_output: Vec<u8>,
+    partial_input: Vec<u8>,
+    error: Option<io::Error>,
+}
+
+impl Recorder {
+    pub fn create(
+        path: &Path,
+        width: usize,
+        height: usize,
+        title: Option<String>,
+        record_input: bool,
+    ) -> Result<Self> {
+        let file =
+            File::create(path).with_context(|| format!("creating recording {}", path.display()))?;
+        let header = AsciicastHeader {
+            version: 2,
+            width,
+            height,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|duration| duration.as_secs()),
+            idle_time_limit: None,
+            title,
+            env: Some(HashMap::from_iter([(
+                "TERM".to_string(),
+                "xterm-256color".to_string(),
+            )])),
+        };
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, &header)?;
+        writer.write_all(b"\n")?;
+        Ok(Self {
+            writer,
+            path: path.to_path_buf(),
+            started_at: Instant::now(),
+            record_input,
+            partial_output: Vec::new(),
+            partial_input: Vec::new(),
+            error: None,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn output(&mut self, bytes: &[u8]) {
+        let data = decode_utf8(&mut self.partial_output, bytes);
+        self.write_event(CastEventKind::Output, data);
+    }
+
+    pub fn input(&mut self, bytes: &[u8]) {
+        if self.record_input {
+            let data = decode_utf8(&mut self.partial_input, bytes);
+            self.write_event(CastEventKind::Input, data);
+        }
+    }
+
+    pub fn resize(&mut self, columns: usize, lines: usize) {
+        self.write_event(CastEventKind::Resize, format!("{columns}x{lines}"));
+    }
+
+    /// Flushes the recording, returning where it was written.
+    pub fn finish(mut self) -> Result<PathBuf> {
+        if let Some(error) = self.error.take() {
+            return Err(error).context("writing recording");
+        }
+        self.writer.flush().context("writing recording")?;
+        Ok(self.path)
+    }
+
+    fn write_event(&mut self, kind: CastEventKind, data: String) {
+        if data.is_empty() || self.error.is_some() {
+            return;
+        }
+        let time = self.started_at.elapsed().as_secs_f64();
+        let result = serde_json::to_writer(&mut self.writer, &(time, kind.code(), data))
+            .map_err(io::Error::from)
+            .and_then(|()| self.writer.write_all(b"\n"));
+        if let Err(error) = result {
+            log::error!(
+                "failed to write to recording {}: {error}",
+                self.path.display()
+            );
+            self.error = Some(error);
+        }
+    }
+}
+
+/// Appends `bytes` to `partial` and returns the complete characters, leaving an
+/// incomplete trailing character in `partial`. Invalid bytes are replaced.
+fn decode_utf8(partial: &mut Vec<u8>, bytes: &[u8]) -> String {
+    partial.extend_from_slice(bytes);
+    let mut text = String::new();
+    let mut rest = &partial[..];
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(valid) => {
+                text.push_str(valid);
+                rest = &[];
+                break;
+            }
+            Err(error) => {
+                let (valid, after) = rest.split_at(error.valid_up_to());
+                text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                match error.error_len() {
+                    Some(len) => {
+                        text.push(char::REPLACEMENT_CHARACTER);
+                        rest = &after[len..];
+                    }
+                    None => {
+                        rest = after;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    *partial = rest.to_vec();
+    text
+}
+
+/// Wraps a local PTY so its output is also written to the terminal's recording, if any.
+pub struct RecordingPty<P: EventedPty> {
+    reader: RecordingReader<P>,
+}
+
+pub struct RecordingReader<P: EventedPty> {
+    pty: P,
+    recorder: Arc<Mutex<Option<Recorder>>>,
+}
+
+impl<P: EventedPty> RecordingPty<P> {
+    pub fn new(pty: P, recorder: Arc<Mutex<Option<Recorder>>>) -> Self {
+        Self {
+            reader: RecordingReader { pty, recorder },
+        }
+    }
+}
+
+impl<P: EventedPty> io::Read for RecordingReader<P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.pty.reader().read(buf)?;
+        if read > 0
+            && let Some(recorder) = self.recorder.lock().as_mut()
+        {
+            recorder.output(&buf[..read]);
+        }
+        Ok(read)
+    }
+}
+
+impl<P: EventedPty> EventedReadWrite for RecordingPty<P> {
+    type Reader = RecordingReader<P>;
+    type Writer = P::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<polling::Poller>,
+        interest: polling::Event,
+        mode: polling::PollMode,
+    ) -> io::Result<()> {
+        unsafe { self.reader.pty.register(poll, interest, mode) }
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<polling::Poller>,
+        interest: polling::Event,
+        mode: polling::PollMode,
+    ) -> io::Result<()> {
+        self.reader.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<polling::Poller>) -> io::Result<()> {
+        self.reader.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        &mut self.reader
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.reader.pty.writer()
+    }
+}
+
+impl<P: EventedPty> EventedPty for RecordingPty<P> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.reader.pty.next_child_event()
+    }
+}
+
+impl<P: EventedPty + OnResize> OnResize for RecordingPty<P> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.reader.pty.on_resize(window_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_and_parses_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.cast");
+        let mut recorder =
+            Recorder::create(&path, 80, 24, Some("router".to_string()), false).unwrap();
+        recorder.output(b"$ ls\r\n");
+        recorder.input(b"ignored");
+        recorder.resize(100, 30);
+        // A character split over two reads is written once complete.
+        recorder.output(b"caf\xc3");
+        recorder.output(b"\xa9 \xff\x1b[0m");
+        assert_eq!(recorder.finish().unwrap(), path);
+
+        let cast = Asciicast::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!((cast.header.width, cast.header.height), (80, 24));
+        assert_eq!(cast.header.title.as_deref(), Some("router"));
+        let events: Vec<_> = cast
+            .events
+            .iter()
+            .map(|event| (event.kind, event.data.as_str()))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (CastEventKind::Output, "$ ls\r\n"),
+                (CastEventKind::Resize, "100x30"),
+                (CastEventKind::Output, "caf"),
+                (CastEventKind::Output, "é \u{fffd}\x1b[0m"),
+            ]
+        );
+        assert_eq!(cast.events[1].size(), Some((100, 30)));
+    }
+
+    #[test]
+    fn test_parses_asciinema_recordings() {
+        let cast = Asciicast::parse(concat!(
+            r#"{"version": 2, "width": 40, "height": 10, "env": {"SHELL": "/bin/zsh"}}"#,
+            "\n",
+            r#"[0.5, "o", "hello"]"#,
+            "\n",
+            r#"[1.25, "x", "unknown"]"#,
+            "\n",
+            r#"[2.0, "m", "done"]"#,
+            "\n"
+        ))
+        .unwrap();
+        assert_eq!(cast.events.len(), 2);
+        assert_eq!(cast.duration(), 2.0);
+
+        assert!(Asciicast::parse(r#"{"version": 1, "width": 40, "height": 10}"#).is_err());
+    }
+}
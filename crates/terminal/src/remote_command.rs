@@ -0,0 +1,202 @@
+//! Running commands at the prompt of a remote device and telling when they finish, for
+//! code that drives SSH and Telnet terminals without a user typing into them.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use gpui::{App, AsyncApp, Entity, Task};
+use regex::Regex;
+use settings::Settings as _;
+use util::paths::PathStyle;
+
+use crate::{
+    ProtocolConfig, SessionConfig, SshConfig, TelnetConfig, Terminal, TerminalBuilder,
+    terminal_settings::TerminalSettings,
+};
+
+/// Matches prompts such as `$ `, `root@host:~# `, `Router>` and `switch(config)#`.
+pub const DEFAULT_PROMPT_REGEX: &str = r"[$#>%]\s*$";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Connects a new terminal to a saved session.
+pub fn connect_to_session(
+    session: &SessionConfig,
+    window_id: u64,
+    cx: &App,
+) -> Task<Result<TerminalBuilder>> {
+    let settings = TerminalSettings::get_global(cx);
+    match &session.protocol {
+        ProtocolConfig::Ssh(config) => TerminalBuilder::new_with_ssh_and_session_id(
+            SshConfig::from(config),
+            Some(session.id),
+            settings.cursor_shape,
+            settings.alternate_scroll,
+            settings.max_scroll_history_lines,
+            window_id,
+            cx,
+            PathStyle::local(),
+        ),
+        ProtocolConfig::Telnet(config) => TerminalBuilder::new_with_telnet_and_session_id(
+            TelnetConfig::from(config),
+            Some(session.id),
+            settings.cursor_shape,
+            settings.alternate_scroll,
+            settings.max_scroll_history_lines,
+            window_id,
+            cx,
+            PathStyle::local(),
+        ),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CommandCompletion {
+    pub last_output_line: i64,
+    pub exit_code: Option<i32>,
+}
+
+/// Whether the command typed on `sent_line` has finished, i.e. the prompt is back.
+///
+/// Terminals whose shell marks prompts with OSC 133 are judged by those marks alone,
+/// since command output could look like a prompt.
+pub fn command_completion(
+    terminal: &Terminal,
+    sent_line: i64,
+    prompt: &Regex,
+) -> Option<CommandCompletion> {
+    let blocks = terminal.command_blocks();
+    if !blocks.is_empty() {
+        let next_prompt = blocks
+            .iter()
+            .position(|block| block.prompt_line > sent_line)?;
+        let exit_code = next_prompt
+            .checked_sub(1)
+            .and_then(|index| blocks.get(index))
+            .and_then(|block| block.exit_code);
+        return Some(CommandCompletion {
+            last_output_line: blocks.get(next_prompt)?.prompt_line - 1,
+            exit_code,
+        });
+    }
+
+    let cursor_line = terminal.cursor_line();
+    (cursor_line > sent_line && is_at_prompt(terminal, prompt)).then_some(CommandCompletion {
+        last_output_line: cursor_line - 1,
+        exit_code: None,
+    })
+}
+
+pub fn is_at_prompt(terminal: &Terminal, prompt: &Regex) -> bool {
+    if let Some(block) = terminal.command_blocks().iter().last() {
+        return block.output_start.is_none();
+    }
+    let cursor_line = terminal.cursor_line();
+    let line = terminal.text_in_lines(cursor_line..=cursor_line);
+    let line = line.trim_end();
+    !line.is_empty() && prompt.is_match(line)
+}
+
+/// Resolves once `terminal` shows a prompt, e.g. after the login banner of a new connection.
+pub async fn wait_until_at_prompt(terminal: Entity<Terminal>, prompt: Regex, cx: AsyncApp) {
+    poll(cx, |cx| {
+        terminal
+            .read_with(cx, |terminal, _| is_at_prompt(terminal, &prompt))
+            .then_some(())
+    })
+    .await
+}
+
+/// Resolves once the command typed on `sent_line` has finished.
+pub async fn wait_for_completion(
+    terminal: Entity<Terminal>,
+    sent_line: i64,
+    prompt: Regex,
+    cx: AsyncApp,
+) -> CommandCompletion {
+    poll(cx, |cx| {
+        terminal.read_with(cx, |terminal, _| {
+            command_completion(terminal, sent_line, &prompt)
+        })
+    })
+    .await
+}
+
+/// Calls `check` every [`POLL_INTERVAL`] until it returns a value.
+async fn poll<T>(mut cx: AsyncApp, mut check: impl FnMut(&mut AsyncApp) -> Option<T>) -> T {
+    loop {
+        if let Some(value) = check(&mut cx) {
+            return value;
+        }
+        cx.background_executor().timer(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal_settings::CursorShape;
+    use alacritty_terminal::term::AlternateScroll;
+    use gpui::{AppContext as _, TestAppContext};
+
+    fn display_terminal(cx: &mut TestAppContext) -> Entity<Terminal> {
+        let builder = TerminalBuilder::new_display_only(
+            CursorShape::default(),
+            AlternateScroll::On,
+            None,
+            0,
+            &cx.background_executor,
+            PathStyle::local(),
+        )
+        .unwrap();
+        cx.new(|cx| builder.subscribe(cx))
+    }
+
+    #[gpui::test]
+    fn test_completion_by_prompt(cx: &mut TestAppContext) {
+        let terminal = display_terminal(cx);
+        let prompt = Regex::new(DEFAULT_PROMPT_REGEX).unwrap();
+        terminal.update(cx, |terminal, cx| {
+            terminal.write_raw_output(b"\r\nRouter#", cx);
+            assert!(is_at_prompt(terminal, &prompt));
+            let sent_line = terminal.cursor_line();
+            terminal.write_raw_output(b"show clock", cx);
+            assert!(!is_at_prompt(terminal, &prompt));
+            assert_eq!(command_completion(terminal, sent_line, &prompt), None);
+
+            terminal.write_raw_output(b"\r\n*12:00:00.000 UTC Mon Oct 19 2026\r\n", cx);
+            assert_eq!(command_completion(terminal, sent_line, &prompt), None);
+
+            terminal.write_raw_output(b"Router#", cx);
+            let finished = command_completion(terminal, sent_line, &prompt).unwrap();
+            assert_eq!(
+                terminal
+                    .text_in_lines(sent_line + 1..=finished.last_output_line)
+                    .trim(),
+                "*12:00:00.000 UTC Mon Oct 19 2026"
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_completion_by_shell_integration(cx: &mut TestAppContext) {
+        let terminal = display_terminal(cx);
+        // A line ending in `>` isn't taken for the prompt once the shell reports prompts.
+        let prompt = Regex::new(DEFAULT_PROMPT_REGEX).unwrap();
+        terminal.update(cx, |terminal, cx| {
+            terminal.write_raw_output(b"\x1b]133;A\x07$ \x1b]133;B\x07false", cx);
+            let sent_line = terminal.cursor_line();
+            terminal.write_raw_output(b"\r\n\x1b]133;C\x07<html>", cx);
+            assert_eq!(command_completion(terminal, sent_line, &prompt), None);
+
+            terminal.write_raw_output(b"\r\n\x1b]133;D;1\x07\x1b]133;A\x07$ ", cx);
+            assert_eq!(
+                command_completion(terminal, sent_line, &prompt),
+                Some(CommandCompletion {
+                    last_output_line: sent_line + 1,
+                    exit_code: Some(1),
+                })
+            );
+        });
+    }
+}
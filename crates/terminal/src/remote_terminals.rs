@@ -0,0 +1,41 @@
+//! The SSH and Telnet terminals shown in terminal views, so that code without access
+//! to the workspace (e.g. agent tools) can find and drive them.
+
+use gpui::{App, Entity, Global, WeakEntity};
+
+use crate::Terminal;
+
+#[derive(Default)]
+pub struct OpenRemoteTerminals {
+    terminals: Vec<WeakEntity<Terminal>>,
+}
+
+impl Global for OpenRemoteTerminals {}
+
+impl OpenRemoteTerminals {
+    /// Remembers `terminal` if it's connected to a remote host. Dropped terminals are
+    /// forgotten on their own.
+    pub fn register(terminal: &Entity<Terminal>, cx: &mut App) {
+        if terminal.read(cx).connection_info().is_none() {
+            return;
+        }
+        let this = cx.default_global::<Self>();
+        this.terminals.retain(|existing| existing.is_upgradable());
+        let terminal = terminal.downgrade();
+        if !this.terminals.contains(&terminal) {
+            this.terminals.push(terminal);
+        }
+    }
+
+    /// The open remote terminals that are still alive, oldest first.
+    pub fn terminals(cx: &App) -> Vec<Entity<Terminal>> {
+        cx.try_global::<Self>()
+            .map(|this| {
+                this.terminals
+                    .iter()
+                    .filter_map(WeakEntity::upgrade)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
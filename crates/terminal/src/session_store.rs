@@ -85,6 +85,22 @@ impl SessionGroup {
             children: Vec::new(),
         }
     }
+
+    /// The sessions in this group and its subgroups, in tree order.
+    pub fn sessions(&self) -> Vec<&SessionConfig> {
+        fn traverse<'a>(nodes: &'a [SessionNode], sessions: &mut Vec<&'a SessionConfig>) {
+            for node in nodes {
+                match node {
+                    SessionNode::Session(session) => sessions.push(session),
+                    SessionNode::Group(group) => traverse(&group.children, sessions),
+                }
+            }
+        }
+
+        let mut sessions = Vec::new();
+        traverse(&self.children, &mut sessions);
+        sessions
+    }
 }
 
 /// Configuration for a saved session.
@@ -136,6 +152,9 @@ pub struct SshSessionConfig {
     pub env: HashMap<String, String>,
     pub keepalive_interval_secs: Option<u64>,
     pub initial_command: Option<String>,
+    /// Forward the local SSH agent, for hopping onward from the remote host.
+    #[serde(default)]
+    pub agent_forwarding: bool,
 }
 
 impl SshSessionConfig {
@@ -148,6 +167,7 @@ impl SshSessionConfig {
             env: HashMap::new(),
             keepalive_interval_secs: Some(30),
             initial_command: None,
+            agent_forwarding: false,
         }
     }
 
@@ -168,7 +188,13 @@ impl SshSessionConfig {
 pub enum AuthMethod {
     Interactive,
     Password { password: String },
-    PrivateKey { path: PathBuf, passphrase: Option<String> },
+    PrivateKey {
+        path: PathBuf,
+        passphrase: Option<String>,
+        /// The OpenSSH certificate for the key, when it isn't `<key>-cert.pub`.
+        #[serde(default)]
+        certificate_file: Option<PathBuf>,
+    },
     Agent,
 }
 
@@ -220,7 +246,7 @@ impl From<&SshSessionConfig> for SshConfig {
         if let Some(cmd) = &config.initial_command {
             ssh_config = ssh_config.with_initial_command(cmd);
         }
-        ssh_config
+        ssh_config.with_agent_forwarding(config.agent_forwarding)
     }
 }
 
@@ -229,9 +255,14 @@ impl From<&AuthMethod> for SshAuthConfig {
         match method {
             AuthMethod::Interactive => SshAuthConfig::Auto,
             AuthMethod::Password { password } => SshAuthConfig::Password(password.clone()),
-            AuthMethod::PrivateKey { path, passphrase } => SshAuthConfig::PrivateKey {
+            AuthMethod::PrivateKey {
+                path,
+                passphrase,
+                certificate_file,
+            } => SshAuthConfig::PrivateKey {
                 path: path.clone(),
                 passphrase: passphrase.clone(),
+                certificate: certificate_file.clone(),
             },
             AuthMethod::Agent => SshAuthConfig::Auto,
         }
@@ -248,6 +279,7 @@ impl From<&SshConfig> for SshSessionConfig {
             env: config.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
             keepalive_interval_secs: config.keepalive_interval.map(|d| d.as_secs()),
             initial_command: config.initial_command.clone(),
+            agent_forwarding: config.agent_forwarding,
         }
     }
 }
@@ -257,9 +289,14 @@ impl From<&SshAuthConfig> for AuthMethod {
         match config {
             SshAuthConfig::Auto => AuthMethod::Interactive,
             SshAuthConfig::Password(password) => AuthMethod::Password { password: password.clone() },
-            SshAuthConfig::PrivateKey { path, passphrase } => AuthMethod::PrivateKey {
+            SshAuthConfig::PrivateKey {
+                path,
+                passphrase,
+                certificate,
+            } => AuthMethod::PrivateKey {
                 path: path.clone(),
                 passphrase: passphrase.clone(),
+                certificate_file: certificate.clone(),
             },
         }
     }
@@ -281,6 +318,71 @@ impl From<&TelnetSessionConfig> for TelnetConfig {
     }
 }
 
+/// A named arrangement of saved sessions in the terminal panel, e.g. four routers tiled
+/// in a 2x2 grid.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionLayout {
+    pub id: Uuid,
+    pub name: String,
+    pub root: LayoutNode,
+}
+
+impl SessionLayout {
+    pub fn new(name: impl Into<String>, root: LayoutNode) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            root,
+        }
+    }
+}
+
+/// A pane of tabs, or panes split along an axis.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LayoutNode {
+    Pane {
+        tabs: Vec<LayoutTab>,
+        #[serde(default)]
+        active_tab: usize,
+    },
+    Split {
+        axis: LayoutAxis,
+        /// The relative size of each child, or `None` to size them equally.
+        #[serde(default)]
+        flexes: Option<Vec<f32>>,
+        children: Vec<LayoutNode>,
+    },
+}
+
+impl LayoutNode {
+    /// The saved sessions opened by the layout, in order.
+    pub fn session_ids(&self) -> Vec<Uuid> {
+        match self {
+            LayoutNode::Pane { tabs, .. } => tabs.iter().map(|tab| tab.session_id).collect(),
+            LayoutNode::Split { children, .. } => {
+                children.iter().flat_map(LayoutNode::session_ids).collect()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutAxis {
+    Horizontal,
+    Vertical,
+}
+
+/// A tab connected to a saved session.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayoutTab {
+    pub session_id: Uuid,
+    /// The title the tab was renamed to, if any.
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
 /// The session store containing all saved sessions.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct SessionStore {
@@ -288,6 +390,8 @@ pub struct SessionStore {
     pub root: Vec<SessionNode>,
     #[serde(default)]
     pub credential_presets: Vec<CredentialPreset>,
+    #[serde(default)]
+    pub layouts: Vec<SessionLayout>,
 }
 
 impl SessionStore {
@@ -298,6 +402,7 @@ impl SessionStore {
             version: Self::CURRENT_VERSION,
             root: Vec::new(),
             credential_presets: Vec::new(),
+            layouts: Vec::new(),
         }
     }
 
@@ -566,6 +671,62 @@ impl SessionStore {
         traverse(&self.root, &mut credentials, &mut seen);
         credentials
     }
+
+    /// Finds the sessions named `name_or_tag`, or failing that the sessions tagged with
+    /// it, ignoring case.
+    pub fn find_sessions(&self, name_or_tag: &str) -> Vec<&SessionConfig> {
+        fn traverse<'a>(nodes: &'a [SessionNode], sessions: &mut Vec<&'a SessionConfig>) {
+            for node in nodes {
+                match node {
+                    SessionNode::Session(session) => sessions.push(session),
+                    SessionNode::Group(group) => traverse(&group.children, sessions),
+                }
+            }
+        }
+
+        let mut sessions = Vec::new();
+        traverse(&self.root, &mut sessions);
+
+        let by_name: Vec<_> = sessions
+            .iter()
+            .copied()
+            .filter(|session| session.name.eq_ignore_ascii_case(name_or_tag))
+            .collect();
+        if !by_name.is_empty() {
+            return by_name;
+        }
+        sessions
+            .into_iter()
+            .filter(|session| {
+                session
+                    .tags
+                    .iter()
+                    .any(|tag| tag.eq_ignore_ascii_case(name_or_tag))
+            })
+            .collect()
+    }
+
+    /// Adds `layout`, replacing any layout with the same name.
+    pub fn save_layout(&mut self, layout: SessionLayout) {
+        match self
+            .layouts
+            .iter_mut()
+            .find(|existing| existing.name == layout.name)
+        {
+            Some(existing) => existing.root = layout.root,
+            None => self.layouts.push(layout),
+        }
+    }
+
+    pub fn remove_layout(&mut self, id: Uuid) -> bool {
+        let len = self.layouts.len();
+        self.layouts.retain(|layout| layout.id != id);
+        self.layouts.len() != len
+    }
+
+    pub fn find_layout(&self, id: Uuid) -> Option<&SessionLayout> {
+        self.layouts.iter().find(|layout| layout.id == id)
+    }
 }
 
 /// Events emitted by the session store for UI subscription.
@@ -575,6 +736,7 @@ pub enum SessionStoreEvent {
     SessionAdded(Uuid),
     SessionRemoved(Uuid),
     CredentialPresetChanged,
+    LayoutsChanged,
 }
 
 /// Global marker for cx.global access.
@@ -606,6 +768,17 @@ impl SessionStoreEntity {
         cx.set_global(GlobalSessionStore(entity));
     }
 
+    /// Installs `store` as the global session store, without reading or writing the
+    /// sessions file.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn init_test(store: SessionStore, cx: &mut App) {
+        let entity = cx.new(|_| Self {
+            store,
+            save_task: None,
+        });
+        cx.set_global(GlobalSessionStore(entity));
+    }
+
     /// Get global instance.
     pub fn global(cx: &App) -> Entity<Self> {
         cx.global::<GlobalSessionStore>().0.clone()
@@ -745,6 +918,28 @@ impl SessionStoreEntity {
         }
     }
 
+    /// Saved layouts.
+    pub fn layouts(&self) -> &[SessionLayout] {
+        &self.store.layouts
+    }
+
+    /// Save a layout, replacing any layout with the same name, and trigger save.
+    pub fn save_layout(&mut self, layout: SessionLayout, cx: &mut Context<Self>) {
+        self.store.save_layout(layout);
+        self.schedule_save(cx);
+        cx.emit(SessionStoreEvent::LayoutsChanged);
+        cx.notify();
+    }
+
+    /// Remove a layout by ID and trigger save.
+    pub fn remove_layout(&mut self, id: Uuid, cx: &mut Context<Self>) {
+        if self.store.remove_layout(id) {
+            self.schedule_save(cx);
+            cx.emit(SessionStoreEvent::LayoutsChanged);
+            cx.notify();
+        }
+    }
+
     fn schedule_save(&mut self, cx: &mut Context<Self>) {
         let store = self.store.clone();
         self.save_task = Some(cx.spawn(async move |_, _| {
@@ -855,6 +1050,7 @@ mod tests {
             env: [("TERM".into(), "xterm".into())].into_iter().collect(),
             keepalive_interval_secs: Some(60),
             initial_command: Some("htop".into()),
+            agent_forwarding: true,
         };
 
         let ssh_config: SshConfig = (&session_config).into();
@@ -865,6 +1061,7 @@ mod tests {
         assert!(matches!(ssh_config.auth, SshAuthConfig::Password(_)));
         assert_eq!(ssh_config.keepalive_interval, Some(Duration::from_secs(60)));
         assert_eq!(ssh_config.initial_command, Some("htop".into()));
+        assert!(ssh_config.agent_forwarding);
     }
 
     #[test]
@@ -875,6 +1072,12 @@ mod tests {
             AuthMethod::PrivateKey {
                 path: PathBuf::from("/home/user/.ssh/id_rsa"),
                 passphrase: Some("phrase".into()),
+                certificate_file: None,
+            },
+            AuthMethod::PrivateKey {
+                path: PathBuf::from("/home/user/.ssh/id_rsa"),
+                passphrase: None,
+                certificate_file: Some(PathBuf::from("/etc/ssh/user-cert.pub")),
             },
             AuthMethod::Agent,
         ];
@@ -890,11 +1093,20 @@ mod tests {
                     assert_eq!(p1, p2);
                 }
                 (
-                    AuthMethod::PrivateKey { path: p1, passphrase: pp1 },
-                    AuthMethod::PrivateKey { path: p2, passphrase: pp2 },
+                    AuthMethod::PrivateKey {
+                        path: p1,
+                        passphrase: pp1,
+                        certificate_file: c1,
+                    },
+                    AuthMethod::PrivateKey {
+                        path: p2,
+                        passphrase: pp2,
+                        certificate_file: c2,
+                    },
                 ) => {
                     assert_eq!(p1, p2);
                     assert_eq!(pp1, pp2);
+                    assert_eq!(c1, c2);
                 }
                 _ => panic!("Conversion mismatch"),
             }
@@ -1166,4 +1378,76 @@ mod tests {
         assert!(credentials.contains(&("root".to_string(), "pass2".to_string())));
         assert!(credentials.contains(&("user".to_string(), "pass3".to_string())));
     }
+
+    #[test]
+    fn test_find_sessions_by_name_then_tag() {
+        let mut store = SessionStore::new();
+        let mut core = SessionConfig::new_ssh("core-router", SshSessionConfig::new("10.0.0.1", 22));
+        core.tags = vec!["routers".into()];
+        let mut edge = SessionConfig::new_telnet("Edge", TelnetSessionConfig::new("10.0.0.2", 23));
+        edge.tags = vec!["Routers".into(), "core-router".into()];
+        store.add_node(SessionNode::Session(core), None);
+        let group = SessionGroup::new("Lab");
+        let group_id = group.id;
+        store.add_node(SessionNode::Group(group), None);
+        store.add_node(SessionNode::Session(edge), Some(group_id));
+
+        let names = |query: &str| {
+            store
+                .find_sessions(query)
+                .into_iter()
+                .map(|session| session.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        // A name wins over a tag of the same text.
+        assert_eq!(names("Core-Router"), vec!["core-router"]);
+        assert_eq!(names("edge"), vec!["Edge"]);
+        assert_eq!(names("routers"), vec!["core-router", "Edge"]);
+        assert!(names("switches").is_empty());
+    }
+
+    #[test]
+    fn test_save_layout_replaces_same_name() {
+        let mut store = SessionStore::new();
+        let first = SessionConfig::new_ssh("r1", SshSessionConfig::new("10.0.0.1", 22));
+        let second = SessionConfig::new_telnet("r2", TelnetSessionConfig::new("10.0.0.2", 23));
+        let pane = |session_id| LayoutNode::Pane {
+            tabs: vec![LayoutTab {
+                session_id,
+                title: None,
+            }],
+            active_tab: 0,
+        };
+
+        store.save_layout(SessionLayout::new(
+            "core routers",
+            LayoutNode::Split {
+                axis: LayoutAxis::Horizontal,
+                flexes: Some(vec![0.5, 1.5]),
+                children: vec![pane(first.id), pane(second.id)],
+            },
+        ));
+        let id = store.layouts[0].id;
+        assert_eq!(
+            store.find_layout(id).unwrap().root.session_ids(),
+            vec![first.id, second.id]
+        );
+
+        store.save_layout(SessionLayout::new("core routers", pane(second.id)));
+        assert_eq!(store.layouts.len(), 1);
+        assert_eq!(store.layouts[0].id, id);
+        assert_eq!(store.layouts[0].root.session_ids(), vec![second.id]);
+
+        let json = serde_json::to_string(&store).expect("serialize");
+        let restored: SessionStore = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(restored.layouts[0].root, store.layouts[0].root);
+
+        // Stores written before layouts existed still load.
+        let restored: SessionStore =
+            serde_json::from_str(r#"{"version": 1, "root": []}"#).expect("deserialize");
+        assert!(restored.layouts.is_empty());
+
+        assert!(store.remove_layout(id));
+        assert!(store.find_layout(id).is_none());
+    }
 }
@@ -0,0 +1,210 @@
+//! Copies of a terminal's screen, used to show a terminal to collaborators in a call, who
+//! can't read from its PTY.
+
+use alacritty_terminal::{
+    Term,
+    grid::Dimensions as _,
+    index::{Column, Direction as AlacDirection, Line, Point as AlacPoint},
+    selection::{Selection, SelectionRange, SelectionType},
+    term::{TermMode, cell::Cell},
+    vte::ansi::{Handler as _, NamedPrivateMode, PrivateMode},
+};
+
+/// The screen of a terminal scrolled to the bottom, with its cursor and selection.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SharedScreen {
+    pub columns: usize,
+    /// The cells of each line, top first. Blank cells at the end of a line are left out.
+    pub rows: Vec<Vec<Cell>>,
+    /// Where the cursor is, if it's shown.
+    pub cursor: Option<AlacPoint>,
+    /// The part of the selection that's on the screen.
+    pub selection: Option<SelectionRange>,
+}
+
+impl SharedScreen {
+    pub(crate) fn capture<T>(term: &Term<T>) -> Self {
+        let columns = term.columns();
+        let blank = Cell::default();
+        let rows = (0..term.screen_lines())
+            .map(|line| {
+                let mut cells = term.grid()[Line(line as i32)][..Column(columns)].to_vec();
+                while cells.last() == Some(&blank) {
+                    cells.pop();
+                }
+                cells
+            })
+            .collect();
+        let cursor = term
+            .mode()
+            .contains(TermMode::SHOW_CURSOR)
+            .then(|| term.grid().cursor.point);
+        let selection = term
+            .selection
+            .as_ref()
+            .and_then(|selection| selection.to_range(term))
+            .filter(|range| range.end.line >= Line(0))
+            .map(|mut range| {
+                if range.start.line < Line(0) {
+                    range.start = AlacPoint::new(Line(0), Column(0));
+                }
+                range
+            });
+        Self {
+            columns,
+            rows,
+            cursor,
+            selection,
+        }
+    }
+
+    pub fn lines(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The lines whose contents differ from `previous`, which is all of them if the size of the
+    /// screen changed.
+    pub fn changed_lines(&self, previous: &SharedScreen) -> Vec<usize> {
+        if self.columns != previous.columns || self.lines() != previous.lines() {
+            return (0..self.lines()).collect();
+        }
+        (0..self.lines())
+            .filter(|&line| self.rows[line] != previous.rows[line])
+            .collect()
+    }
+
+    /// Draws the screen into `term`, whose size may differ from the screen's. When `term` is
+    /// shorter, the bottom of the screen is kept, since that's where the cursor usually is.
+    pub(crate) fn draw<T>(&self, term: &mut Term<T>, update_selection: bool) {
+        let lines = term.screen_lines();
+        let columns = term.columns();
+        let skipped_lines = self.lines().saturating_sub(lines) as i32;
+        let blank = Cell::default();
+        for line in 0..lines {
+            let row = self.rows.get(line + skipped_lines as usize);
+            for column in 0..columns {
+                term.grid_mut()[Line(line as i32)][Column(column)] = row
+                    .and_then(|row| row.get(column))
+                    .unwrap_or(&blank)
+                    .clone();
+            }
+        }
+
+        let cursor = self.cursor.and_then(|cursor| {
+            let line = cursor.line.0 - skipped_lines;
+            (line >= 0 && (line as usize) < lines).then(|| {
+                AlacPoint::new(
+                    Line(line),
+                    Column(cursor.column.0.min(columns.saturating_sub(1))),
+                )
+            })
+        });
+        let show_cursor = PrivateMode::Named(NamedPrivateMode::ShowCursor);
+        match cursor {
+            Some(cursor) => {
+                term.grid_mut().cursor.point = cursor;
+                term.set_private_mode(show_cursor);
+            }
+            None => term.unset_private_mode(show_cursor),
+        }
+
+        if update_selection {
+            term.selection = self.selection.as_ref().and_then(|range| {
+                let start_line = (range.start.line.0 - skipped_lines).max(0);
+                let end_line = (range.end.line.0 - skipped_lines).min(lines as i32 - 1);
+                if start_line > end_line {
+                    return None;
+                }
+                let selection_type = if range.is_block {
+                    SelectionType::Block
+                } else {
+                    SelectionType::Simple
+                };
+                let mut selection = Selection::new(
+                    selection_type,
+                    AlacPoint::new(Line(start_line), range.start.column),
+                    AlacDirection::Left,
+                );
+                selection.update(
+                    AlacPoint::new(Line(end_line), range.end.column),
+                    AlacDirection::Right,
+                );
+                Some(selection)
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alacritty_terminal::{
+        event::VoidListener,
+        term::{Config, test::TermSize},
+    };
+
+    fn term_with_lines(lines: &[&str], columns: usize, screen_lines: usize) -> Term<VoidListener> {
+        let mut term = Term::new(
+            Config::default(),
+            &TermSize::new(columns, screen_lines),
+            VoidListener,
+        );
+        for (ix, line) in lines.iter().enumerate() {
+            if ix > 0 {
+                term.carriage_return();
+                term.linefeed();
+            }
+            for character in line.chars() {
+                term.input(character);
+            }
+        }
+        term
+    }
+
+    fn screen_text<T>(term: &Term<T>) -> Vec<String> {
+        (0..term.screen_lines())
+            .map(|line| {
+                term.grid()[Line(line as i32)][..Column(term.columns())]
+                    .iter()
+                    .map(|cell| cell.c)
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_capture_and_changed_lines() {
+        let term = term_with_lines(&["one", "two"], 10, 4);
+        let screen = SharedScreen::capture(&term);
+        assert_eq!(screen.columns, 10);
+        assert_eq!(screen.lines(), 4);
+        assert_eq!(screen.rows[0].len(), 3);
+        assert!(screen.rows[2].is_empty());
+        assert_eq!(screen.cursor, Some(AlacPoint::new(Line(1), Column(3))));
+
+        let changed = SharedScreen::capture(&term_with_lines(&["one", "2", "three"], 10, 4));
+        assert_eq!(changed.changed_lines(&screen), [1, 2]);
+        let resized = SharedScreen::capture(&term_with_lines(&["one", "two"], 12, 4));
+        assert_eq!(resized.changed_lines(&screen), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_draw_into_smaller_term_keeps_bottom() {
+        let host = term_with_lines(&["a", "b", "c", "d"], 10, 4);
+        let screen = SharedScreen::capture(&host);
+
+        let mut guest = term_with_lines(&["stale output"], 3, 2);
+        screen.draw(&mut guest, true);
+        assert_eq!(screen_text(&guest), ["c", "d"]);
+        assert_eq!(
+            guest.grid().cursor.point,
+            AlacPoint::new(Line(1), Column(1))
+        );
+
+        let mut guest = term_with_lines(&[], 10, 6);
+        screen.draw(&mut guest, true);
+        assert_eq!(screen_text(&guest), ["a", "b", "c", "d", "", ""]);
+    }
+}
@@ -1,12 +1,20 @@
+pub mod command_blocks;
 pub mod connection;
+pub mod file_transfer;
+pub mod graphics;
 pub mod mappings;
+pub mod recording;
+pub mod remote_command;
+pub mod remote_terminals;
 pub mod session_store;
+pub mod shared_screen;
 
 pub use alacritty_terminal;
 
 pub use session_store::{
-    AuthMethod, GlobalSessionStore, ProtocolConfig, SessionConfig, SessionGroup, SessionNode,
-    SessionStore, SessionStoreEntity, SessionStoreEvent, SshSessionConfig, TelnetSessionConfig,
+    AuthMethod, GlobalSessionStore, LayoutAxis, LayoutNode, LayoutTab, ProtocolConfig,
+    SessionConfig, SessionGroup, SessionLayout, SessionNode, SessionStore, SessionStoreEntity,
+    SessionStoreEvent, SshSessionConfig, TelnetSessionConfig,
 };
 
 pub use crate::connection::ssh::{SshAuthConfig, SshConfig};
@@ -50,10 +58,26 @@ use mappings::mouse::{
 };
 
 use collections::{HashMap, VecDeque};
+use command_blocks::{
+    CommandBlocks, CommandStatus, PendingPromptMarks, ScrollTracker, SemanticPromptParser,
+    SemanticPromptPty,
+};
+use file_transfer::{
+    TransferDirection, TransferEngine, TransferInput, TransferProgress, TransferProtocol,
+    TransferPty, TransferTap, TransferUpdate, XmodemSender, XmodemVariant, ZmodemDetector,
+    ZmodemReceiver, ZmodemRequest, ZmodemSender,
+};
 use futures::StreamExt;
+use graphics::{
+    CellMetrics, GraphicsEvent, GraphicsParser, GraphicsPty, GraphicsTap, ImagePlacement,
+    TerminalImages,
+};
+use parking_lot::Mutex;
 use pty_info::{ProcessIdGetter, PtyProcessInfo};
+use recording::{Recorder, RecordingPty};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shared_screen::SharedScreen;
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, SpawnInTerminal};
 use terminal_hyperlinks::RegexSearches;
@@ -69,7 +93,7 @@ use std::{
     cmp::{self, min},
     fmt::Display,
     ops::{Deref, RangeInclusive},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::ExitStatus,
     sync::Arc,
     time::{Duration, Instant},
@@ -79,7 +103,7 @@ use thiserror::Error;
 use gpui::{
     App, AppContext as _, BackgroundExecutor, Bounds, ClipboardItem, Context, EventEmitter, Hsla,
     Keystroke, Modifiers, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, Point,
-    Rgba, ScrollWheelEvent, Size, Task, TouchPhase, Window, actions, black, px,
+    RenderImage, Rgba, ScrollWheelEvent, Size, Task, TouchPhase, Window, actions, black, px,
 };
 
 use crate::mappings::{colors::to_alac_rgb, keys::to_esc_str};
@@ -113,6 +137,16 @@ actions!(
         ScrollToTop,
         /// Scrolls to the bottom of the terminal buffer.
         ScrollToBottom,
+        /// Scrolls to the previous shell prompt.
+        ScrollToPreviousPrompt,
+        /// Scrolls to the next shell prompt.
+        ScrollToNextPrompt,
+        /// Selects the output of the current command block.
+        SelectCommandOutput,
+        /// Copies the output of the current command block to the clipboard.
+        CopyCommandOutput,
+        /// Folds or unfolds the output of the current command block.
+        ToggleCommandOutputFold,
         /// Toggles vi mode in the terminal.
         ToggleViMode,
         /// Selects all text in the terminal.
@@ -150,6 +184,14 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    /// The remote side ran `rz` and is waiting for the user to pick files to send.
+    FileTransferRequested,
+    /// The remote side ran `sz` and is waiting for the user to agree to receive its files.
+    FileReceiveRequested,
+    FileTransferFinished {
+        summary: String,
+        succeeded: bool,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -424,6 +466,19 @@ impl TerminalBuilder {
                 window_id,
             },
             child_exited: None,
+            command_blocks: CommandBlocks::default(),
+            scroll_tracker: ScrollTracker::default(),
+            fixed_size: None,
+            prompt_parser: SemanticPromptParser::new(),
+            pending_prompt_marks: None,
+            file_transfer: None,
+            zmodem_detector: ZmodemDetector::default(),
+            transfer_tap: None,
+            images: TerminalImages::default(),
+            graphics_parser: GraphicsParser::new(),
+            graphics_tap: None,
+            recorder: Arc::default(),
+            shared_screen: None,
             event_loop_task: Task::ready(Ok(())),
             background_executor: background_executor.clone(),
             path_style,
@@ -435,6 +490,27 @@ impl TerminalBuilder {
         })
     }
 
+    /// Creates a terminal showing the screen of one shared by the host of a project, whose
+    /// input is sent to `input` to be forwarded to the host.
+    pub fn new_shared_by_host(
+        input: UnboundedSender<Vec<u8>>,
+        cursor_shape: CursorShape,
+        window_id: u64,
+        background_executor: &BackgroundExecutor,
+        path_style: PathStyle,
+    ) -> Result<TerminalBuilder> {
+        let mut builder = Self::new_display_only(
+            cursor_shape,
+            AlternateScroll::Off,
+            None,
+            window_id,
+            background_executor,
+            path_style,
+        )?;
+        builder.terminal.terminal_type = TerminalType::SharedByHost { input };
+        Ok(builder)
+    }
+
     pub fn new(
         working_directory: Option<PathBuf>,
         task: Option<TaskState>,
@@ -598,6 +674,18 @@ impl TerminalBuilder {
 
             let pty_info = PtyProcessInfo::new(&pty);
 
+            // Let file transfers take the shell's output away from alacritty, record what's
+            // left, take inline images out of it, and scan the rest for OSC 133 prompt marks
+            // before alacritty parses it.
+            let transfer_tap = Arc::new(Mutex::new(TransferTap::new(events_tx.clone())));
+            let pty = TransferPty::new(pty, transfer_tap.clone());
+            let recorder = Arc::new(Mutex::new(None));
+            let pty = RecordingPty::new(pty, recorder.clone());
+            let graphics_tap = Arc::new(Mutex::new(GraphicsTap::new(events_tx.clone())));
+            let pty = GraphicsPty::new(pty, graphics_tap.clone());
+            let pending_prompt_marks = Arc::new(Mutex::new(PendingPromptMarks::default()));
+            let pty = SemanticPromptPty::new(pty, pending_prompt_marks.clone());
+
             //And connect them together
             let event_loop = EventLoop::new(
                 term.clone(),
@@ -661,6 +749,19 @@ impl TerminalBuilder {
                     window_id,
                 },
                 child_exited: None,
+                command_blocks: CommandBlocks::default(),
+                scroll_tracker: ScrollTracker::default(),
+                fixed_size: None,
+                prompt_parser: SemanticPromptParser::new(),
+                pending_prompt_marks: Some(pending_prompt_marks),
+                file_transfer: None,
+                zmodem_detector: ZmodemDetector::default(),
+                transfer_tap: Some(transfer_tap),
+                images: TerminalImages::default(),
+                graphics_parser: GraphicsParser::new(),
+                graphics_tap: Some(graphics_tap),
+                recorder,
+                shared_screen: None,
                 event_loop_task: Task::ready(Ok(())),
                 background_executor,
                 path_style,
@@ -756,6 +857,13 @@ impl TerminalBuilder {
                 connection::ssh::SshAuthConfig::PrivateKey { passphrase, .. } => passphrase.clone(),
                 _ => None,
             },
+            certificate_path: match &ssh_config.auth {
+                connection::ssh::SshAuthConfig::PrivateKey { certificate, .. } => {
+                    certificate.clone()
+                }
+                _ => None,
+            },
+            agent_forwarding: ssh_config.agent_forwarding,
             session_id,
         };
 
@@ -788,8 +896,7 @@ impl TerminalBuilder {
                 .spawn({
                     let tokio_handle = tokio_handle.clone();
                     async move {
-                        let session_manager = connection::ssh::SshSessionManager::new();
-                        let session = session_manager
+                        let session = connection::ssh::SshSessionManager::global()
                             .get_or_create_session(&ssh_config)
                             .await
                             .context("failed to establish SSH session")?;
@@ -848,6 +955,19 @@ impl TerminalBuilder {
                     window_id,
                 },
                 child_exited: None,
+                command_blocks: CommandBlocks::default(),
+                scroll_tracker: ScrollTracker::default(),
+                fixed_size: None,
+                prompt_parser: SemanticPromptParser::new(),
+                pending_prompt_marks: None,
+                file_transfer: None,
+                zmodem_detector: ZmodemDetector::default(),
+                transfer_tap: None,
+                images: TerminalImages::default(),
+                graphics_parser: GraphicsParser::new(),
+                graphics_tap: None,
+                recorder: Arc::default(),
+                shared_screen: None,
                 event_loop_task: Task::ready(Ok(())),
                 background_executor,
                 path_style,
@@ -999,6 +1119,19 @@ impl TerminalBuilder {
                     window_id,
                 },
                 child_exited: None,
+                command_blocks: CommandBlocks::default(),
+                scroll_tracker: ScrollTracker::default(),
+                fixed_size: None,
+                prompt_parser: SemanticPromptParser::new(),
+                pending_prompt_marks: None,
+                file_transfer: None,
+                zmodem_detector: ZmodemDetector::default(),
+                transfer_tap: None,
+                images: TerminalImages::default(),
+                graphics_parser: GraphicsParser::new(),
+                graphics_tap: None,
+                recorder: Arc::default(),
+                shared_screen: None,
                 event_loop_task: Task::ready(Ok(())),
                 background_executor,
                 path_style,
@@ -1164,6 +1297,19 @@ impl TerminalBuilder {
                 window_id,
             },
             child_exited: None,
+            command_blocks: CommandBlocks::default(),
+            scroll_tracker: ScrollTracker::default(),
+            fixed_size: None,
+            prompt_parser: SemanticPromptParser::new(),
+            pending_prompt_marks: None,
+            file_transfer: None,
+            zmodem_detector: ZmodemDetector::default(),
+            transfer_tap: None,
+            images: TerminalImages::default(),
+            graphics_parser: GraphicsParser::new(),
+            graphics_tap: None,
+            recorder: Arc::default(),
+            shared_screen: None,
             event_loop_task: Task::ready(Ok(())),
             background_executor: background_executor.clone(),
             path_style,
@@ -1226,6 +1372,12 @@ pub struct TerminalContent {
     pub last_hovered_word: Option<HoveredWord>,
     pub scrolled_to_top: bool,
     pub scrolled_to_bottom: bool,
+    pub history_size: usize,
+    /// Lines scrolled off the top of the primary screen, see [`ScrollTracker`].
+    pub scrolled_lines: i64,
+    /// The grid line shown on each row of the viewport when folded command output is
+    /// hidden from it. Empty when row `r` shows line `r - display_offset`.
+    pub row_lines: Vec<Line>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -1252,7 +1404,103 @@ impl Default for TerminalContent {
             last_hovered_word: None,
             scrolled_to_top: false,
             scrolled_to_bottom: false,
+            history_size: 0,
+            scrolled_lines: 0,
+            row_lines: Vec::new(),
+        }
+    }
+}
+
+impl TerminalContent {
+    /// The grid line shown on a row of the viewport.
+    pub fn line_for_row(&self, row: i32) -> Line {
+        if self.row_lines.is_empty() {
+            return Line(row - self.display_offset as i32);
+        }
+        let clamped_row = row.clamp(0, self.row_lines.len() as i32 - 1);
+        Line(self.row_lines[clamped_row as usize].0 + row - clamped_row)
+    }
+
+    /// The row of the viewport a grid line is shown on, if it's shown.
+    pub fn row_for_line(&self, line: Line) -> Option<i32> {
+        if self.row_lines.is_empty() {
+            let row = line.0 + self.display_offset as i32;
+            return (0..self.terminal_bounds.num_lines() as i32)
+                .contains(&row)
+                .then_some(row);
+        }
+        self.row_lines
+            .binary_search(&line)
+            .ok()
+            .map(|row| row as i32)
+    }
+
+    /// The rows of the viewport showing any of the given absolute lines (see
+    /// [`command_blocks::CommandBlock`]).
+    pub fn rows_for_lines(&self, lines: RangeInclusive<i64>) -> Option<RangeInclusive<i32>> {
+        let start = *lines.start() - self.scrolled_lines;
+        let end = *lines.end() - self.scrolled_lines;
+        if self.row_lines.is_empty() {
+            let display_offset = self.display_offset as i64;
+            let last_row = self.terminal_bounds.num_lines() as i64 - 1;
+            let start_row = (start + display_offset).max(0);
+            let end_row = (end + display_offset).min(last_row);
+            return (start_row <= end_row).then(|| start_row as i32..=end_row as i32);
+        }
+        let start_row = self
+            .row_lines
+            .partition_point(|line| (line.0 as i64) < start);
+        let end_row = self
+            .row_lines
+            .partition_point(|line| (line.0 as i64) <= end);
+        (start_row < end_row).then(|| start_row as i32..=end_row as i32 - 1)
+    }
+
+    /// The grid point shown where a point computed from a mouse position is, which
+    /// differs when folded command output is hidden from the viewport.
+    pub fn unfold_point(&self, point: AlacPoint) -> AlacPoint {
+        if self.row_lines.is_empty() {
+            return point;
         }
+        let row = point.line.0 + self.display_offset as i32;
+        AlacPoint::new(self.line_for_row(row), point.column)
+    }
+
+    /// Where a range of grid points is shown, as points of the viewport relative to the
+    /// display offset like the grid points of an unfolded viewport. Parts of the range
+    /// hidden by folded command output are left out.
+    pub fn fold_range(
+        &self,
+        range: &RangeInclusive<AlacPoint>,
+    ) -> Option<RangeInclusive<AlacPoint>> {
+        if self.row_lines.is_empty() {
+            return Some(range.clone());
+        }
+        let start_row = self
+            .row_lines
+            .partition_point(|line| *line < range.start().line);
+        let end_row = self
+            .row_lines
+            .partition_point(|line| *line <= range.end().line);
+        if start_row >= end_row {
+            return None;
+        }
+        let end_row = end_row - 1;
+        let start_column = if self.row_lines[start_row] == range.start().line {
+            range.start().column
+        } else {
+            Column(0)
+        };
+        let end_column = if self.row_lines[end_row] == range.end().line {
+            range.end().column
+        } else {
+            self.terminal_bounds.last_column()
+        };
+        let display_offset = self.display_offset as i32;
+        Some(
+            AlacPoint::new(Line(start_row as i32 - display_offset), start_column)
+                ..=AlacPoint::new(Line(end_row as i32 - display_offset), end_column),
+        )
     }
 }
 
@@ -1268,6 +1516,10 @@ enum TerminalType {
     },
     Disconnected,
     DisplayOnly,
+    /// Shows the screen of a terminal shared by the host of the project.
+    SharedByHost {
+        input: UnboundedSender<Vec<u8>>,
+    },
 }
 
 /// Information about a remote connection that can be used for reconnection.
@@ -1280,6 +1532,8 @@ pub enum ConnectionInfo {
         password: Option<String>,
         private_key_path: Option<PathBuf>,
         passphrase: Option<String>,
+        certificate_path: Option<PathBuf>,
+        agent_forwarding: bool,
         session_id: Option<uuid::Uuid>,
     },
     Telnet {
@@ -1291,6 +1545,21 @@ pub enum ConnectionInfo {
     },
 }
 
+impl ConnectionInfo {
+    pub fn host(&self) -> &str {
+        match self {
+            Self::Ssh { host, .. } | Self::Telnet { host, .. } => host,
+        }
+    }
+
+    /// The saved session the connection was opened from, if any.
+    pub fn session_id(&self) -> Option<uuid::Uuid> {
+        match self {
+            Self::Ssh { session_id, .. } | Self::Telnet { session_id, .. } => *session_id,
+        }
+    }
+}
+
 pub struct Terminal {
     terminal_type: TerminalType,
     connection_info: Option<ConnectionInfo>,
@@ -1322,11 +1591,43 @@ pub struct Terminal {
     template: CopyTemplate,
     activation_script: Vec<String>,
     child_exited: Option<ExitStatus>,
+    command_blocks: CommandBlocks,
+    scroll_tracker: ScrollTracker,
+    /// The columns and lines a display-only terminal is held at regardless of the space it's
+    /// shown in, e.g. those of a recording being played back.
+    fixed_size: Option<(usize, usize)>,
+    prompt_parser: SemanticPromptParser,
+    pending_prompt_marks: Option<Arc<Mutex<PendingPromptMarks>>>,
+    file_transfer: Option<FileTransferState>,
+    zmodem_detector: ZmodemDetector,
+    transfer_tap: Option<Arc<Mutex<TransferTap>>>,
+    images: TerminalImages,
+    graphics_parser: GraphicsParser,
+    graphics_tap: Option<Arc<Mutex<GraphicsTap>>>,
+    /// The recording in progress, shared with the local PTY's reader.
+    recorder: Arc<Mutex<Option<Recorder>>>,
+    /// The screen last received from the host, for terminals shared by the host of a project.
+    shared_screen: Option<SharedScreen>,
     event_loop_task: Task<Result<(), anyhow::Error>>,
     background_executor: BackgroundExecutor,
     path_style: PathStyle,
 }
 
+enum FileTransferState {
+    /// The remote side is waiting while the user picks files to send or agrees to receive
+    /// them; its output is held here.
+    AwaitingUser {
+        progress: TransferProgress,
+        buffered: Vec<u8>,
+    },
+    Running {
+        progress: TransferProgress,
+        input_tx: UnboundedSender<TransferInput>,
+        _engine_task: Task<()>,
+        _update_task: Task<Result<()>>,
+    },
+}
+
 struct CopyTemplate {
     shell: Shell,
     env: HashMap<String, String>,
@@ -1373,6 +1674,26 @@ impl TaskStatus {
 
 const FIND_HYPERLINK_THROTTLE_PX: Pixels = px(5.0);
 
+fn transfer_summary(progress: &TransferProgress) -> String {
+    let protocol = progress.protocol.label();
+    let files = match progress.files_completed {
+        1 => "1 file".to_string(),
+        count => format!("{count} files"),
+    };
+    match progress.direction {
+        TransferDirection::Send => format!("Sent {files} with {protocol}"),
+        TransferDirection::Receive => format!(
+            "Received {files} with {protocol} into {}",
+            file_transfer_directory().display()
+        ),
+    }
+}
+
+/// Where files received over ZMODEM are saved.
+pub fn file_transfer_directory() -> PathBuf {
+    dirs::download_dir().unwrap_or_else(|| paths::home_dir().clone())
+}
+
 impl Terminal {
     fn process_event(&mut self, event: AlacTermEvent, cx: &mut Context<Self>) {
         match event {
@@ -1428,11 +1749,16 @@ impl Terminal {
                 //NOOP, Handled in render
             }
             AlacTermEvent::Wakeup => {
-                if let TerminalType::Connected { connection } = &self.terminal_type {
-                    if let Some(data) = connection.read() {
-                        self.process_ssh_input(&data);
-                    }
+                let data = match &self.terminal_type {
+                    TerminalType::Connected { connection } => connection.read(),
+                    _ => None,
+                };
+                if let Some(data) = data {
+                    self.process_connection_output(data, cx);
                 }
+                self.poll_transfer_tap(cx);
+                self.resolve_pending_prompt_marks();
+                self.resolve_pending_graphics();
 
                 cx.emit(Event::Wakeup);
 
@@ -1461,6 +1787,50 @@ impl Terminal {
         self.selection_phase == SelectionPhase::Selecting
     }
 
+    fn resize(
+        &mut self,
+        mut new_bounds: TerminalBounds,
+        term: &mut Term<ZedListener>,
+        cx: &mut Context<Self>,
+    ) {
+        trace!("Resizing: new_bounds={new_bounds:?}");
+        new_bounds.bounds.size.height = cmp::max(new_bounds.line_height, new_bounds.height());
+        new_bounds.bounds.size.width = cmp::max(new_bounds.cell_width, new_bounds.width());
+
+        self.last_content.terminal_bounds = new_bounds;
+
+        let metrics = CellMetrics {
+            cell_width: new_bounds.cell_width.into(),
+            cell_height: new_bounds.line_height.into(),
+            columns: new_bounds.num_columns(),
+            lines: new_bounds.num_lines(),
+        };
+        self.graphics_parser.set_metrics(metrics);
+        if let Some(graphics_tap) = &self.graphics_tap {
+            graphics_tap.lock().set_metrics(metrics);
+        }
+
+        if let TerminalType::Connected { connection } = &self.terminal_type {
+            connection.resize(new_bounds.into()).ok();
+        }
+        if let Some(recorder) = self.recorder.lock().as_mut() {
+            recorder.resize(new_bounds.num_columns(), new_bounds.num_lines());
+        }
+
+        let history_size = term.history_size();
+        term.resize(new_bounds);
+        self.scroll_tracker.resized(term, history_size);
+        if let Some(shared_screen) = &self.shared_screen {
+            shared_screen.draw(term, true);
+        }
+        // If there are matches we need to emit a wake up event to
+        // invalidate the matches and recalculate their locations
+        // in the new terminal layout
+        if !self.matches.is_empty() {
+            cx.emit(Event::Wakeup);
+        }
+    }
+
     fn process_terminal_event(
         &mut self,
         event: &InternalEvent,
@@ -1469,28 +1839,11 @@ impl Terminal {
         cx: &mut Context<Self>,
     ) {
         match event {
-            &InternalEvent::Resize(mut new_bounds) => {
-                trace!("Resizing: new_bounds={new_bounds:?}");
-                new_bounds.bounds.size.height =
-                    cmp::max(new_bounds.line_height, new_bounds.height());
-                new_bounds.bounds.size.width = cmp::max(new_bounds.cell_width, new_bounds.width());
-
-                self.last_content.terminal_bounds = new_bounds;
-
-                if let TerminalType::Connected { connection } = &self.terminal_type {
-                    connection.resize(new_bounds.into()).ok();
-                }
-
-                term.resize(new_bounds);
-                // If there are matches we need to emit a wake up event to
-                // invalidate the matches and recalculate their locations
-                // in the new terminal layout
-                if !self.matches.is_empty() {
-                    cx.emit(Event::Wakeup);
-                }
-            }
+            &InternalEvent::Resize(new_bounds) => self.resize(new_bounds, term, cx),
             InternalEvent::Clear => {
                 trace!("Clearing");
+                self.command_blocks.clear();
+                self.images.clear();
                 // Clear back buffer
                 term.clear_screen(ClearMode::Saved);
 
@@ -1524,7 +1877,7 @@ impl Terminal {
             }
             InternalEvent::Scroll(scroll) => {
                 trace!("Scrolling: scroll={scroll:?}");
-                term.scroll_display(*scroll);
+                term.scroll_display(self.skip_folded_lines(*scroll, term));
                 self.refresh_hovered_word(window);
 
                 if self.vi_mode_enabled {
@@ -1586,6 +1939,7 @@ impl Terminal {
                         self.last_content.terminal_bounds,
                         term.grid().display_offset(),
                     );
+                    let point = self.last_content.unfold_point(point);
 
                     selection.update(point, side);
                     term.selection = Some(selection);
@@ -1634,12 +1988,14 @@ impl Terminal {
             InternalEvent::FindHyperlink(position, open) => {
                 trace!("Finding hyperlink at position: position={position:?}, open={open:?}");
 
-                let point = grid_point(
-                    *position,
-                    self.last_content.terminal_bounds,
-                    term.grid().display_offset(),
-                )
-                .grid_clamp(term, Boundary::Grid);
+                let point = self
+                    .last_content
+                    .unfold_point(grid_point(
+                        *position,
+                        self.last_content.terminal_bounds,
+                        term.grid().display_offset(),
+                    ))
+                    .grid_clamp(term, Boundary::Grid);
 
                 match terminal_hyperlinks::find_from_grid_point(
                     term,
@@ -1763,28 +2119,626 @@ impl Terminal {
             prev_byte = byte;
         }
 
-        let mut processor = alacritty_terminal::vte::ansi::Processor::<
-            alacritty_terminal::vte::ansi::StdSyncHandler,
-        >::new();
-        {
-            let mut term = self.term.lock();
-            processor.advance(&mut *term, &converted);
-        }
+        self.advance_with_prompt_marks(&converted);
         cx.emit(Event::Wakeup);
     }
 
-    fn process_ssh_input(&self, bytes: &[u8]) {
+    fn process_ssh_input(&mut self, bytes: &[u8]) {
+        if let Some(recorder) = self.recorder.lock().as_mut() {
+            recorder.output(bytes);
+        }
+        for event in self.graphics_parser.feed(bytes) {
+            match event {
+                GraphicsEvent::Text(text) => self.advance_with_prompt_marks(&text),
+                GraphicsEvent::Image(display) => {
+                    let term = self.term.lock();
+                    let alt_screen = term.mode().contains(TermMode::ALT_SCREEN);
+                    let cursor = term.grid().cursor.point;
+                    let line = if alt_screen {
+                        cursor.line.0 as i64
+                    } else {
+                        self.scroll_tracker.update(&*term);
+                        self.scroll_tracker.absolute_line(cursor.line)
+                    };
+                    drop(term);
+                    self.images
+                        .place(display, line, cursor.column.0, alt_screen);
+                }
+                GraphicsEvent::Delete(deletion) => self.images.delete(deletion),
+                GraphicsEvent::Reply(reply) => self.write_to_pty(reply),
+            }
+        }
+    }
+
+    fn advance_with_prompt_marks(&mut self, bytes: &[u8]) {
         let mut processor = alacritty_terminal::vte::ansi::Processor::<
             alacritty_terminal::vte::ansi::StdSyncHandler,
         >::new();
+        let marks = self.prompt_parser.feed(bytes);
         let mut term = self.term.lock();
-        processor.advance(&mut *term, bytes);
+
+        // Feed the emulator up to each prompt mark, so the mark lands where the cursor is.
+        let mut processed = 0;
+        for (offset, kind) in marks {
+            processor.advance(&mut *term, &bytes[processed..offset]);
+            processed = offset;
+
+            self.scroll_tracker.update(&*term);
+            let cursor = term.grid().cursor.point;
+            self.command_blocks.record(
+                kind,
+                self.scroll_tracker.absolute_line(cursor.line),
+                cursor.column.0,
+            );
+        }
+        processor.advance(&mut *term, &bytes[processed..]);
+        self.scroll_tracker.update(&*term);
+    }
+
+    /// Positions the prompt marks the local PTY tee found in output alacritty has since parsed.
+    fn resolve_pending_prompt_marks(&mut self) {
+        let Some(pending_prompt_marks) = &self.pending_prompt_marks else {
+            return;
+        };
+        let marks = pending_prompt_marks.lock().drain();
+        if marks.is_empty() {
+            return;
+        }
+
+        let term = self.term.lock();
+        self.scroll_tracker.update(&*term);
+        let cursor_line = self
+            .scroll_tracker
+            .absolute_line(term.grid().cursor.point.line);
+        let first_line = self.scroll_tracker.first_line(term.history_size());
+        drop(term);
+        for mark in marks {
+            let line = cursor_line - mark.line_feeds_after as i64;
+            self.command_blocks
+                .record(mark.kind, line.max(first_line), mark.column);
+        }
+    }
+
+    /// Places the images the local PTY tap took out of output alacritty has since parsed.
+    fn resolve_pending_graphics(&mut self) {
+        let Some(graphics_tap) = &self.graphics_tap else {
+            return;
+        };
+        let pending = graphics_tap.lock().drain();
+        if pending.is_empty() {
+            return;
+        }
+
+        let term = self.term.lock();
+        let alt_screen = term.mode().contains(TermMode::ALT_SCREEN);
+        let cursor = term.grid().cursor.point;
+        let (cursor_line, first_line) = if alt_screen {
+            (cursor.line.0 as i64, 0)
+        } else {
+            self.scroll_tracker.update(&*term);
+            (
+                self.scroll_tracker.absolute_line(cursor.line),
+                self.scroll_tracker.first_line(term.history_size()),
+            )
+        };
+        drop(term);
+        for graphics in pending {
+            match graphics.event {
+                GraphicsEvent::Image(display) => {
+                    let line = cursor_line - graphics.line_feeds_after as i64;
+                    self.images
+                        .place(display, line.max(first_line), graphics.column, alt_screen);
+                }
+                GraphicsEvent::Delete(deletion) => self.images.delete(deletion),
+                GraphicsEvent::Text(_) | GraphicsEvent::Reply(_) => {}
+            }
+        }
+    }
+
+    /// Routes output read from a remote connection: to a running file transfer, or to
+    /// the emulator, starting a ZMODEM transfer if the remote side opened one.
+    fn process_connection_output(&mut self, data: Vec<u8>, cx: &mut Context<Self>) {
+        if self.file_transfer.is_some() {
+            self.forward_to_file_transfer(data);
+            return;
+        }
+        match self.zmodem_detector.feed(&data) {
+            Some(detection) => {
+                self.process_ssh_input(&data[..detection.start]);
+                self.begin_zmodem(detection.request, detection.transfer_input(&data), cx);
+            }
+            None => self.process_ssh_input(&data),
+        }
+    }
+
+    /// Picks up local PTY output the transfer tap kept away from alacritty.
+    fn poll_transfer_tap(&mut self, cx: &mut Context<Self>) {
+        let Some(transfer_tap) = &self.transfer_tap else {
+            return;
+        };
+        let (detected, diverted) = {
+            let mut tap = transfer_tap.lock();
+            (tap.take_detected(), tap.take_diverted())
+        };
+        if let Some(request) = detected {
+            self.begin_zmodem(request, diverted, cx);
+        } else if !diverted.is_empty() {
+            self.forward_to_file_transfer(diverted);
+        }
+    }
+
+    fn forward_to_file_transfer(&mut self, data: Vec<u8>) {
+        match &mut self.file_transfer {
+            Some(FileTransferState::AwaitingUser { buffered, .. }) => buffered.extend(data),
+            Some(FileTransferState::Running { input_tx, .. }) => {
+                input_tx.unbounded_send(TransferInput::Data(data)).ok();
+            }
+            None => self.process_ssh_input(&data),
+        }
+    }
+
+    /// Holds the transfer the remote side opened until the user answers for it, since the
+    /// header could come from any output, such as a file being printed.
+    fn begin_zmodem(&mut self, request: ZmodemRequest, input: Vec<u8>, cx: &mut Context<Self>) {
+        let (direction, event) = match request {
+            ZmodemRequest::Receive => (TransferDirection::Receive, Event::FileReceiveRequested),
+            ZmodemRequest::Send => (TransferDirection::Send, Event::FileTransferRequested),
+        };
+        self.file_transfer = Some(FileTransferState::AwaitingUser {
+            progress: TransferProgress::new(TransferProtocol::Zmodem, direction),
+            buffered: input,
+        });
+        cx.emit(event);
+        cx.notify();
+    }
+
+    /// Takes the output held while waiting for the user, if the transfer waiting is in
+    /// `direction`.
+    fn take_awaited_transfer(&mut self, direction: TransferDirection) -> Option<Vec<u8>> {
+        match &mut self.file_transfer {
+            Some(FileTransferState::AwaitingUser { progress, buffered })
+                if progress.direction == direction =>
+            {
+                Some(std::mem::take(buffered))
+            }
+            _ => None,
+        }
+    }
+
+    fn start_file_transfer(
+        &mut self,
+        engine: Box<dyn TransferEngine>,
+        input: Vec<u8>,
+        cx: &mut Context<Self>,
+    ) {
+        let progress = engine.progress();
+        let (input_tx, input_rx) = unbounded();
+        let (updates_tx, mut updates_rx) = unbounded();
+        if !input.is_empty() {
+            input_tx.unbounded_send(TransferInput::Data(input)).ok();
+        }
+        let engine_task = self.background_executor.spawn(file_transfer::run_transfer(
+            engine,
+            input_rx,
+            updates_tx,
+            self.background_executor.clone(),
+        ));
+        let update_task = cx.spawn(async move |terminal, cx| {
+            while let Some(update) = updates_rx.next().await {
+                terminal.update(cx, |terminal, cx| {
+                    terminal.handle_transfer_update(update, cx)
+                })?;
+            }
+            anyhow::Ok(())
+        });
+        self.file_transfer = Some(FileTransferState::Running {
+            progress,
+            input_tx,
+            _engine_task: engine_task,
+            _update_task: update_task,
+        });
+        cx.notify();
+    }
+
+    fn handle_transfer_update(&mut self, update: TransferUpdate, cx: &mut Context<Self>) {
+        match update {
+            TransferUpdate::Output(bytes) => self.write_to_pty(bytes),
+            TransferUpdate::Progress(new_progress) => {
+                if let Some(FileTransferState::Running { progress, .. }) = &mut self.file_transfer {
+                    *progress = new_progress;
+                }
+                cx.notify();
+            }
+            TransferUpdate::Finished {
+                result,
+                remainder,
+                mut input,
+            } => {
+                self.file_transfer = None;
+                self.zmodem_detector.reset();
+
+                // Whatever the remote side printed after the session is the emulator's again.
+                let mut output = remainder;
+                while let Ok(Some(pending)) = input.try_next() {
+                    if let TransferInput::Data(data) = pending {
+                        output.extend(data);
+                    }
+                }
+                if let Some(transfer_tap) = &self.transfer_tap {
+                    output.extend(transfer_tap.lock().resume());
+                }
+                if !output.is_empty() {
+                    self.process_connection_output(output, cx);
+                }
+
+                let (summary, succeeded) = match result {
+                    Ok(progress) => (transfer_summary(&progress), true),
+                    Err(error) => (format!("File transfer failed: {error}"), false),
+                };
+                cx.emit(Event::FileTransferFinished { summary, succeeded });
+                cx.emit(Event::Wakeup);
+            }
+        }
+    }
+
+    /// Progress of the running file transfer, if any.
+    pub fn file_transfer(&self) -> Option<&TransferProgress> {
+        match self.file_transfer.as_ref()? {
+            FileTransferState::AwaitingUser { progress, .. }
+            | FileTransferState::Running { progress, .. } => Some(progress),
+        }
+    }
+
+    /// Answers a [`Event::FileTransferRequested`] with the files to send; no files
+    /// declines the transfer.
+    pub fn send_files(&mut self, paths: Vec<PathBuf>, cx: &mut Context<Self>) {
+        if paths.is_empty() {
+            self.cancel_file_transfer(cx);
+            return;
+        }
+        let Some(buffered) = self.take_awaited_transfer(TransferDirection::Send) else {
+            return;
+        };
+        self.start_file_transfer(Box::new(ZmodemSender::new(paths)), buffered, cx);
+    }
+
+    /// Answers a [`Event::FileReceiveRequested`] by receiving the files into
+    /// [`file_transfer_directory`]. [`Self::cancel_file_transfer`] declines them instead.
+    pub fn receive_files(&mut self, cx: &mut Context<Self>) {
+        let Some(buffered) = self.take_awaited_transfer(TransferDirection::Receive) else {
+            return;
+        };
+        let receiver = ZmodemReceiver::new(file_transfer_directory());
+        self.start_file_transfer(Box::new(receiver), buffered, cx);
+    }
+
+    /// Sends a file to an XMODEM or YMODEM receiver, such as a bootloader waiting for an image.
+    pub fn start_xmodem_send(
+        &mut self,
+        path: PathBuf,
+        variant: XmodemVariant,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        if self.file_transfer.is_some() {
+            bail!("a file transfer is already running");
+        }
+        if !self.is_connected() {
+            bail!("the terminal is not connected");
+        }
+        let engine = XmodemSender::new(&path, variant)?;
+        if let Some(transfer_tap) = &self.transfer_tap {
+            transfer_tap.lock().divert();
+        }
+        self.start_file_transfer(Box::new(engine), Vec::new(), cx);
+        Ok(())
+    }
+
+    pub fn cancel_file_transfer(&mut self, cx: &mut Context<Self>) {
+        match &self.file_transfer {
+            Some(FileTransferState::AwaitingUser { .. }) => {
+                self.file_transfer = None;
+                self.zmodem_detector.reset();
+                self.write_to_pty(file_transfer::CANCEL_SEQUENCE);
+                // Drop the receiver's retries rather than printing them.
+                if let Some(transfer_tap) = &self.transfer_tap {
+                    transfer_tap.lock().resume();
+                }
+                cx.emit(Event::FileTransferFinished {
+                    summary: "File transfer cancelled".to_string(),
+                    succeeded: false,
+                });
+                cx.notify();
+            }
+            Some(FileTransferState::Running { input_tx, .. }) => {
+                input_tx.unbounded_send(TransferInput::Cancel).ok();
+            }
+            None => {}
+        }
     }
 
     pub fn total_lines(&self) -> usize {
         self.term.lock_unfair().total_lines()
     }
 
+    pub fn command_blocks(&self) -> &CommandBlocks {
+        &self.command_blocks
+    }
+
+    /// Absolute line (see [`command_blocks::CommandBlock`]) shown at the top of the viewport.
+    fn viewport_top_line(&self) -> i64 {
+        self.last_content.scrolled_lines + self.last_content.line_for_row(0).0 as i64
+    }
+
+    /// Scrolls so the prompt above the top of the viewport becomes the top line.
+    /// Returns false when there is no such prompt.
+    pub fn scroll_to_previous_prompt(&mut self) -> bool {
+        match self
+            .command_blocks
+            .previous_prompt(self.viewport_top_line())
+        {
+            Some(line) => {
+                self.scroll_to_absolute_line(line);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Scrolls so the prompt below the top of the viewport becomes the top line,
+    /// or to the bottom when there are no more prompts.
+    pub fn scroll_to_next_prompt(&mut self) -> bool {
+        match self.command_blocks.next_prompt(self.viewport_top_line()) {
+            Some(line) => {
+                self.scroll_to_absolute_line(line);
+                true
+            }
+            None if self.last_content.display_offset > 0 => {
+                self.scroll_to_bottom();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn scroll_to_absolute_line(&mut self, line: i64) {
+        let history_size = self.last_content.history_size as i64;
+        let display_offset = (self.last_content.scrolled_lines - line).clamp(0, history_size);
+        let top = self.viewport_top_line();
+        let target_top = self.last_content.scrolled_lines - display_offset;
+        // Scrolling moves by rows of the viewport, which skip folded output.
+        let (above, below) = (top.min(target_top), top.max(target_top));
+        let hidden_lines = self
+            .command_blocks
+            .hidden_lines()
+            .into_iter()
+            .map(|lines| (*lines.end() + 1).min(below) - (*lines.start()).max(above))
+            .filter(|count| *count > 0)
+            .sum::<i64>();
+        let delta = (top - target_top).signum() * (below - above - hidden_lines);
+        if delta != 0 {
+            self.events
+                .push_back(InternalEvent::Scroll(AlacScroll::Delta(delta as i32)));
+        }
+    }
+
+    /// The command block that block actions apply to: the one at the top of the
+    /// viewport when scrolled back, otherwise the latest one that produced output.
+    pub fn focused_command_block(&self) -> Option<usize> {
+        if self.last_content.display_offset == 0 {
+            self.command_blocks
+                .iter()
+                .rposition(|block| block.output_start.is_some())
+        } else {
+            self.command_blocks.index_at_line(self.viewport_top_line())
+        }
+    }
+
+    /// The grid range holding the output of the command block at `index`.
+    fn command_output_range(&self, index: usize) -> Option<RangeInclusive<AlacPoint>> {
+        let block = self.command_blocks.get(index)?;
+        let scrolled_lines = self.last_content.scrolled_lines;
+        let cursor_line = scrolled_lines + self.last_content.cursor.point.line.0 as i64;
+        let lines = block.output_lines(self.command_blocks.next_prompt_line(index), cursor_line)?;
+        let start = Line((*lines.start() - scrolled_lines) as i32);
+        let end = Line((*lines.end() - scrolled_lines) as i32);
+        let last_column = self.last_content.terminal_bounds.last_column();
+        Some(AlacPoint::new(start, Column(0))..=AlacPoint::new(end, last_column))
+    }
+
+    pub fn select_command_output(&mut self, index: usize) -> bool {
+        let Some(range) = self.command_output_range(index) else {
+            return false;
+        };
+        self.set_selection(Some((make_selection(&range), *range.end())));
+        true
+    }
+
+    pub fn command_output_text(&self, index: usize) -> Option<String> {
+        let range = self.command_output_range(index)?;
+        let term = self.term.lock_unfair();
+        let text = term.bounds_to_string(*range.start(), *range.end());
+        Some(text.trim_end().to_string())
+    }
+
+    /// The absolute line (see [`command_blocks::CommandBlock`]) the cursor is on.
+    pub fn cursor_line(&self) -> i64 {
+        let term = self.term.lock_unfair();
+        self.scroll_tracker.scrolled_lines_in(&term) + term.grid().cursor.point.line.0 as i64
+    }
+
+    /// The first absolute line still in the scrollback; earlier lines have been dropped.
+    pub fn topmost_line(&self) -> i64 {
+        let term = self.term.lock_unfair();
+        self.scroll_tracker.scrolled_lines_in(&term) + term.topmost_line().0 as i64
+    }
+
+    /// The text of the given absolute lines that are still in the scrollback.
+    pub fn text_in_lines(&self, lines: RangeInclusive<i64>) -> String {
+        let term = self.term.lock_unfair();
+        let scrolled_lines = self.scroll_tracker.scrolled_lines_in(&term);
+        let start = (*lines.start() - scrolled_lines).max(term.topmost_line().0 as i64);
+        let end = (*lines.end() - scrolled_lines).min(term.bottommost_line().0 as i64);
+        if start > end {
+            return String::new();
+        }
+        term.bounds_to_string(
+            AlacPoint::new(Line(start as i32), Column(0)),
+            AlacPoint::new(Line(end as i32), term.last_column()),
+        )
+    }
+
+    /// The last `count` lines up to the cursor. Read from the grid, so the text carries
+    /// no escape sequences.
+    pub fn last_lines_text(&self, count: u32) -> String {
+        let cursor_line = self.cursor_line();
+        let start = cursor_line - count.max(1) as i64 + 1;
+        self.text_in_lines(start..=cursor_line)
+            .trim_end()
+            .to_string()
+    }
+
+    /// The prompt, command and output of the latest command block that produced output.
+    pub fn last_command_text(&self) -> Option<String> {
+        let index = self
+            .command_blocks
+            .iter()
+            .rposition(|block| block.output_start.is_some())?;
+        let block = self.command_blocks.get(index)?;
+        let last_line = block.last_line(
+            self.command_blocks.next_prompt_line(index),
+            self.cursor_line(),
+        );
+        Some(
+            self.text_in_lines(block.prompt_line..=last_line)
+                .trim_end()
+                .to_string(),
+        )
+    }
+
+    /// Command blocks intersecting the viewport, as viewport line ranges.
+    pub fn visible_command_blocks(&self) -> Vec<(RangeInclusive<i32>, CommandStatus)> {
+        let cursor_line =
+            self.last_content.scrolled_lines + self.last_content.cursor.point.line.0 as i64;
+
+        self.command_blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, block)| {
+                let next_prompt = self.command_blocks.next_prompt_line(index);
+                let last_line = block.last_line(next_prompt, cursor_line);
+                let rows = self
+                    .last_content
+                    .rows_for_lines(block.prompt_line..=last_line)?;
+                Some((rows, block.status()))
+            })
+            .collect()
+    }
+
+    /// Folded command outputs intersecting the viewport: the row of the output line left
+    /// shown, and how many lines are hidden below it.
+    pub fn visible_folds(&self) -> Vec<(i32, usize)> {
+        if self.last_content.mode.contains(TermMode::ALT_SCREEN) {
+            return Vec::new();
+        }
+        self.command_blocks
+            .hidden_lines()
+            .into_iter()
+            .filter_map(|lines| {
+                let shown_line = *lines.start() - 1 - self.last_content.scrolled_lines;
+                let row = self.last_content.row_for_line(Line(shown_line as i32))?;
+                Some((row, (lines.end() - lines.start() + 1) as usize))
+            })
+            .collect()
+    }
+
+    /// Folds or unfolds the output of the command block at `index`.
+    pub fn toggle_command_output_fold(&mut self, index: usize) -> bool {
+        self.command_blocks.toggle_fold(index)
+    }
+
+    /// Images intersecting the viewport, with the viewport line of their top row.
+    pub fn visible_images(&self) -> Vec<(i32, ImagePlacement)> {
+        if self.images.is_empty() {
+            return Vec::new();
+        }
+        let alt_screen = self.last_content.mode.contains(TermMode::ALT_SCREEN);
+        let top = if alt_screen {
+            0
+        } else {
+            self.viewport_top_line()
+        };
+        let bottom = top + self.last_content.terminal_bounds.num_lines() as i64;
+
+        let folded = !alt_screen && !self.last_content.row_lines.is_empty();
+
+        self.images
+            .iter()
+            .filter(|placement| placement.alt_screen == alt_screen)
+            .filter_map(|placement| {
+                if folded {
+                    // Rows aren't contiguous, so only images whose top row is shown are.
+                    let line = placement.line - self.last_content.scrolled_lines;
+                    let row = self.last_content.row_for_line(Line(line as i32))?;
+                    return Some((row, placement.clone()));
+                }
+                (placement.line < bottom && placement.line + placement.rows as i64 > top)
+                    .then(|| ((placement.line - top) as i32, placement.clone()))
+            })
+            .collect()
+    }
+
+    /// Images no longer shown, whose textures can be released.
+    pub fn take_evicted_images(&mut self) -> Vec<Arc<RenderImage>> {
+        self.images.take_evicted()
+    }
+
+    /// Starts writing the session to an asciicast file at `path`, optionally with
+    /// what the user types.
+    pub fn start_recording(&mut self, path: &Path, record_input: bool) -> Result<()> {
+        if !self.is_connected() {
+            bail!("only connected terminals can be recorded");
+        }
+        let mut recorder = self.recorder.lock();
+        if recorder.is_some() {
+            bail!("the terminal is already being recorded");
+        }
+        let bounds = self.last_content.terminal_bounds;
+        *recorder = Some(Recorder::create(
+            path,
+            bounds.num_columns(),
+            bounds.num_lines(),
+            Some(self.title(false)),
+            record_input,
+        )?);
+        Ok(())
+    }
+
+    /// Stops the recording in progress, returning the file it was written to.
+    pub fn stop_recording(&mut self) -> Result<Option<PathBuf>> {
+        let recorder = self.recorder.lock().take();
+        recorder.map(Recorder::finish).transpose()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.lock().is_some()
+    }
+
+    /// Feeds recorded output to a display-only terminal, as it was read from the PTY.
+    pub fn write_raw_output(&mut self, bytes: &[u8], cx: &mut Context<Self>) {
+        self.process_ssh_input(bytes);
+        cx.emit(Event::Wakeup);
+    }
+
+    /// Resets the screen and drops the scrollback, e.g. before replaying a recording
+    /// from the start.
+    pub fn reset_display(&mut self, cx: &mut Context<Self>) {
+        self.command_blocks.clear();
+        self.images.clear();
+        // RIS: a full reset, which also clears the history.
+        self.advance_with_prompt_marks(b"\x1bc");
+        cx.emit(Event::Wakeup);
+    }
+
     pub fn viewport_lines(&self) -> usize {
         self.term.lock_unfair().screen_lines()
     }
@@ -1892,24 +2846,54 @@ impl Terminal {
 
     ///Resize the terminal and the PTY.
     pub fn set_size(&mut self, new_bounds: TerminalBounds) {
+        let new_bounds = self.fit_fixed_size(new_bounds);
         if self.last_content.terminal_bounds != new_bounds {
             self.events.push_back(InternalEvent::Resize(new_bounds))
         }
     }
 
+    /// Holds the grid at `columns` by `lines` however much space the terminal is given, or
+    /// lets it follow that space again. Resizes right away, so output written afterwards is
+    /// laid out at the new size.
+    pub fn set_fixed_size(&mut self, size: Option<(usize, usize)>, cx: &mut Context<Self>) {
+        self.fixed_size = size;
+        let new_bounds = self.fit_fixed_size(self.last_content.terminal_bounds);
+        if self.last_content.terminal_bounds != new_bounds {
+            let term = self.term.clone();
+            let mut term = term.lock();
+            self.resize(new_bounds, &mut term, cx);
+        }
+    }
+
+    fn fit_fixed_size(&self, mut bounds: TerminalBounds) -> TerminalBounds {
+        if let Some((columns, lines)) = self.fixed_size {
+            bounds.bounds.size = Size {
+                width: bounds.cell_width * columns as f32,
+                height: bounds.line_height * lines as f32,
+            };
+        }
+        bounds
+    }
+
     /// Write the Input payload to the connection, if applicable.
     /// (This is a no-op for display-only terminals.)
     fn write_to_pty(&self, input: impl Into<Cow<'static, [u8]>>) {
-        if let TerminalType::Connected { connection } = &self.terminal_type {
-            let input = input.into();
-            if log::log_enabled!(log::Level::Debug) {
-                if let Ok(str) = str::from_utf8(&input) {
-                    log::debug!("Writing to connection: {:?}", str);
-                } else {
-                    log::debug!("Writing to connection: {:?}", input);
+        match &self.terminal_type {
+            TerminalType::Connected { connection } => {
+                let input = input.into();
+                if log::log_enabled!(log::Level::Debug) {
+                    if let Ok(str) = str::from_utf8(&input) {
+                        log::debug!("Writing to connection: {:?}", str);
+                    } else {
+                        log::debug!("Writing to connection: {:?}", input);
+                    }
                 }
+                connection.write(input).ok();
             }
-            connection.write(input).ok();
+            TerminalType::SharedByHost { input: input_tx } => {
+                input_tx.unbounded_send(input.into().into_owned()).ok();
+            }
+            TerminalType::Disconnected | TerminalType::DisplayOnly => {}
         }
     }
 
@@ -1924,6 +2908,10 @@ impl Terminal {
             .push_back(InternalEvent::Scroll(AlacScroll::Bottom));
         self.events.push_back(InternalEvent::SetSelection(None));
 
+        let input = input.into();
+        if let Some(recorder) = self.recorder.lock().as_mut() {
+            recorder.input(&input);
+        }
         self.write_to_pty(input);
     }
 
@@ -2070,12 +3058,132 @@ impl Terminal {
     pub fn sync(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let term = self.term.clone();
         let mut terminal = term.lock_unfair();
+        self.scroll_tracker.update(&terminal);
         //Note that the ordering of events matters for event processing
         while let Some(e) = self.events.pop_front() {
             self.process_terminal_event(&e, &mut terminal, window, cx)
         }
 
+        if !terminal.mode().contains(TermMode::ALT_SCREEN) {
+            self.scroll_tracker.update(&terminal);
+            let first_line = self.scroll_tracker.first_line(terminal.history_size());
+            self.command_blocks.prune(first_line);
+            self.images.clear_alt_screen();
+            self.images.prune(first_line);
+        }
         self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content.scrolled_lines = self.scroll_tracker.scrolled_lines();
+        self.layout_folds(&terminal);
+    }
+
+    /// Lays out the viewport around folded command output: hidden lines are skipped, and
+    /// the rows they free up show more of the scrollback.
+    fn layout_folds(&mut self, term: &Term<ZedListener>) {
+        let hidden = self.hidden_grid_lines(term);
+        if hidden.is_empty() {
+            return;
+        }
+        let hidden_range = |line: i32| hidden.iter().find(|range| range.contains(&line));
+
+        let screen_lines = term.screen_lines();
+        let top = -(term.grid().display_offset() as i32);
+        let mut rows = VecDeque::with_capacity(screen_lines);
+        let mut line = top;
+        while rows.len() < screen_lines && line <= term.bottommost_line().0 {
+            match hidden_range(line) {
+                Some(range) => line = range.end() + 1,
+                None => {
+                    rows.push_back(Line(line));
+                    line += 1;
+                }
+            }
+        }
+        // Near the bottom, the rest of the screen shows the lines above.
+        let mut line = top - 1;
+        while rows.len() < screen_lines && line >= term.topmost_line().0 {
+            match hidden_range(line) {
+                Some(range) => line = range.start() - 1,
+                None => {
+                    rows.push_front(Line(line));
+                    line -= 1;
+                }
+            }
+        }
+        let unfolded = rows.len() == screen_lines
+            && rows.front() == Some(&Line(top))
+            && rows.back() == Some(&Line(top + screen_lines as i32 - 1));
+        if unfolded {
+            return;
+        }
+
+        let grid = term.grid();
+        let columns = term.columns();
+        self.last_content.cells = rows
+            .iter()
+            .flat_map(|&line| {
+                (0..columns).map(move |column| IndexedCell {
+                    point: AlacPoint::new(line, Column(column)),
+                    cell: grid[line][Column(column)].clone(),
+                })
+            })
+            .collect();
+        self.last_content.row_lines = rows.into_iter().collect();
+    }
+
+    /// The grid lines hidden by folded command output, top to bottom.
+    fn hidden_grid_lines(&self, term: &Term<ZedListener>) -> Vec<RangeInclusive<i32>> {
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return Vec::new();
+        }
+        let scrolled_lines = self.scroll_tracker.scrolled_lines();
+        let topmost_line = term.topmost_line().0 as i64;
+        let bottommost_line = term.bottommost_line().0 as i64;
+        self.command_blocks
+            .hidden_lines()
+            .into_iter()
+            .filter_map(|lines| {
+                let start = (*lines.start() - scrolled_lines).max(topmost_line);
+                let end = (*lines.end() - scrolled_lines).min(bottommost_line);
+                (start <= end).then(|| start as i32..=end as i32)
+            })
+            .collect()
+    }
+
+    /// Scrolls by rows of the viewport rather than grid lines while folded command output
+    /// is hidden, so scrolling doesn't stall over the hidden lines.
+    fn skip_folded_lines(&self, scroll: AlacScroll, term: &Term<ZedListener>) -> AlacScroll {
+        let rows = match scroll {
+            AlacScroll::Delta(rows) => rows,
+            AlacScroll::PageUp => term.screen_lines() as i32,
+            AlacScroll::PageDown => -(term.screen_lines() as i32),
+            AlacScroll::Top | AlacScroll::Bottom => return scroll,
+        };
+        let hidden = self.hidden_grid_lines(term);
+        if hidden.is_empty() {
+            return scroll;
+        }
+
+        let display_offset = term.grid().display_offset() as i32;
+        let mut top = self
+            .last_content
+            .row_lines
+            .first()
+            .map_or(-display_offset, |line| line.0);
+        for _ in 0..rows.abs() {
+            if (rows > 0 && top <= term.topmost_line().0) || (rows < 0 && top >= 0) {
+                break;
+            }
+            top -= rows.signum();
+            if let Some(range) = hidden.iter().find(|range| range.contains(&top)) {
+                top = if rows > 0 {
+                    range.start() - 1
+                } else {
+                    range.end() + 1
+                };
+            }
+        }
+        let new_display_offset = (-top).clamp(0, term.history_size() as i32);
+        AlacScroll::Delta(new_display_offset - display_offset)
     }
 
     fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
@@ -2108,6 +3216,9 @@ impl Terminal {
             last_hovered_word: last_content.last_hovered_word.clone(),
             scrolled_to_top: content.display_offset == term.history_size(),
             scrolled_to_bottom: content.display_offset == 0,
+            history_size: term.history_size(),
+            scrolled_lines: last_content.scrolled_lines,
+            row_lines: Vec::new(),
         }
     }
 
@@ -2215,6 +3326,7 @@ impl Terminal {
                 self.last_content.terminal_bounds,
                 self.last_content.display_offset,
             );
+            let point = self.last_content.unfold_point(point);
 
             if self.mouse_changed(point, side)
                 && let Some(bytes) =
@@ -2266,6 +3378,7 @@ impl Terminal {
             self.last_content.terminal_bounds,
             self.last_content.display_offset,
         );
+        let point = self.last_content.unfold_point(point);
         let selection = Selection::new(SelectionType::Semantic, point, side);
         self.events
             .push_back(InternalEvent::SetSelection(Some((selection, point))));
@@ -2280,11 +3393,11 @@ impl Terminal {
         let position = e.position - self.last_content.terminal_bounds.bounds.origin;
         if !self.mouse_mode(e.modifiers.shift) {
             if let Some((.., hyperlink_range)) = &self.mouse_down_hyperlink {
-                let point = grid_point(
+                let point = self.last_content.unfold_point(grid_point(
                     position,
                     self.last_content.terminal_bounds,
                     self.last_content.display_offset,
-                );
+                ));
 
                 if !hyperlink_range.contains(&point) {
                     self.mouse_down_hyperlink = None;
@@ -2333,11 +3446,11 @@ impl Terminal {
 
     pub fn mouse_down(&mut self, e: &MouseDownEvent, _cx: &mut Context<Self>) {
         let position = e.position - self.last_content.terminal_bounds.bounds.origin;
-        let point = grid_point(
+        let point = self.last_content.unfold_point(grid_point(
             position,
             self.last_content.terminal_bounds,
             self.last_content.display_offset,
-        );
+        ));
 
         if e.button == MouseButton::Left
             && e.modifiers.secondary()
@@ -2371,6 +3484,7 @@ impl Terminal {
                         self.last_content.terminal_bounds,
                         self.last_content.display_offset,
                     );
+                    let point = self.last_content.unfold_point(point);
 
                     let selection_type = match e.click_count {
                         0 => return, //This is a release
@@ -2411,11 +3525,11 @@ impl Terminal {
 
         let position = e.position - self.last_content.terminal_bounds.bounds.origin;
         if self.mouse_mode(e.modifiers.shift) {
-            let point = grid_point(
+            let point = self.last_content.unfold_point(grid_point(
                 position,
                 self.last_content.terminal_bounds,
                 self.last_content.display_offset,
-            );
+            ));
 
             if let Some(bytes) =
                 mouse_button_report(point, e.button, e.modifiers, false, self.last_content.mode)
@@ -2428,11 +3542,11 @@ impl Terminal {
             }
 
             if let Some(mouse_down_hyperlink) = self.mouse_down_hyperlink.take() {
-                let point = grid_point(
+                let point = self.last_content.unfold_point(grid_point(
                     position,
                     self.last_content.terminal_bounds,
                     self.last_content.display_offset,
-                );
+                ));
 
                 if let Some(mouse_up_hyperlink) = {
                     let term_lock = self.term.lock();
@@ -2479,11 +3593,11 @@ impl Terminal {
             && scroll_lines != 0
         {
             if mouse_mode {
-                let point = grid_point(
+                let point = self.last_content.unfold_point(grid_point(
                     e.position - self.last_content.terminal_bounds.bounds.origin,
                     self.last_content.terminal_bounds,
                     self.last_content.display_offset,
-                );
+                ));
 
                 if let Some(scrolls) = scroll_report(point, scroll_lines, e, self.last_content.mode)
                 {
@@ -2746,6 +3860,39 @@ impl Terminal {
         matches!(self.terminal_type, TerminalType::Disconnected)
     }
 
+    pub fn is_connected(&self) -> bool {
+        matches!(self.terminal_type, TerminalType::Connected { .. })
+    }
+
+    /// Whether this terminal shows one shared by the host of the project.
+    pub fn is_shared_by_host(&self) -> bool {
+        matches!(self.terminal_type, TerminalType::SharedByHost { .. })
+    }
+
+    /// Copies the screen, to show it to collaborators.
+    pub fn shared_screen(&self) -> SharedScreen {
+        SharedScreen::capture(&*self.term.lock_unfair())
+    }
+
+    /// Shows a screen received from the host, keeping the local selection unless the host's
+    /// selection changed.
+    pub fn show_shared_screen(&mut self, screen: SharedScreen, cx: &mut Context<Self>) {
+        let selection_changed = self
+            .shared_screen
+            .as_ref()
+            .is_none_or(|shown| shown.selection != screen.selection);
+        screen.draw(&mut *self.term.lock(), selection_changed);
+        self.shared_screen = Some(screen);
+        cx.emit(Event::Wakeup);
+    }
+
+    pub fn set_title_override(&mut self, title: Option<String>, cx: &mut Context<Self>) {
+        if self.title_override != title {
+            self.title_override = title;
+            cx.emit(Event::TitleChanged);
+        }
+    }
+
     pub fn clone_builder(&self, cx: &App, cwd: Option<PathBuf>) -> Task<Result<TerminalBuilder>> {
         let working_directory = self.working_directory().or_else(|| cwd);
         TerminalBuilder::new(
@@ -3500,6 +4647,162 @@ mod tests {
         assert!(line2_col0, "Second line should start at column 0");
     }
 
+    #[gpui::test]
+    async fn test_zmodem_receive_waits_for_the_user(cx: &mut TestAppContext) {
+        let terminal = cx.new(|cx| {
+            TerminalBuilder::new_display_only(
+                CursorShape::default(),
+                AlternateScroll::On,
+                None,
+                0,
+                cx.background_executor(),
+                PathStyle::local(),
+            )
+            .unwrap()
+            .subscribe(cx)
+        });
+
+        terminal.update(cx, |terminal, cx| {
+            terminal.process_connection_output(
+                b"$ sz file\r\n**\x18B00000000000000\r\x8a\x11".to_vec(),
+                cx,
+            );
+            assert!(matches!(
+                terminal.file_transfer,
+                Some(FileTransferState::AwaitingUser { .. })
+            ));
+            assert_eq!(
+                terminal.file_transfer().unwrap().direction,
+                TransferDirection::Receive
+            );
+
+            // Picking files to send doesn't answer an offer to receive.
+            terminal.send_files(vec![PathBuf::from("/file")], cx);
+            assert!(matches!(
+                terminal.file_transfer,
+                Some(FileTransferState::AwaitingUser { .. })
+            ));
+
+            terminal.cancel_file_transfer(cx);
+            assert!(terminal.file_transfer().is_none());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_topmost_line_follows_dropped_scrollback(cx: &mut TestAppContext) {
+        let terminal = cx.new(|cx| {
+            TerminalBuilder::new_display_only(
+                CursorShape::default(),
+                AlternateScroll::On,
+                Some(10),
+                0,
+                cx.background_executor(),
+                PathStyle::local(),
+            )
+            .unwrap()
+            .subscribe(cx)
+        });
+
+        terminal.update(cx, |terminal, cx| {
+            assert_eq!(terminal.topmost_line(), 0);
+            let output = (0..100).map(|line| format!("line {line}\n")).join("");
+            terminal.write_output(output.as_bytes(), cx);
+
+            let topmost_line = terminal.topmost_line();
+            assert!(topmost_line > 0);
+            assert_eq!(
+                terminal.text_in_lines(topmost_line..=topmost_line).trim(),
+                format!("line {topmost_line}")
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_fixed_size_ignores_the_space_given(cx: &mut TestAppContext) {
+        let terminal = cx.new(|cx| {
+            TerminalBuilder::new_display_only(
+                CursorShape::default(),
+                AlternateScroll::On,
+                None,
+                0,
+                cx.background_executor(),
+                PathStyle::local(),
+            )
+            .unwrap()
+            .subscribe(cx)
+        });
+
+        terminal.update(cx, |terminal, cx| {
+            terminal.set_fixed_size(Some((10, 4)), cx);
+            {
+                let term = terminal.term.lock();
+                assert_eq!((term.columns(), term.screen_lines()), (10, 4));
+            }
+
+            // The view handing the terminal its default size leaves the grid alone.
+            terminal.set_size(TerminalBounds::default());
+            assert!(terminal.events.is_empty());
+
+            // Output written after the resize wraps at the fixed width.
+            terminal.write_output(b"0123456789abc", cx);
+            let content = Terminal::make_content(&terminal.term.lock(), &terminal.last_content);
+            let cell = content.cells.iter().find(|cell| cell.c == 'a').unwrap();
+            assert_eq!(cell.point, AlacPoint::new(Line(1), Column(0)));
+
+            terminal.set_fixed_size(None, cx);
+            terminal.set_size(TerminalBounds::default());
+            assert_eq!(terminal.events.len(), 1);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_write_output_tracks_command_blocks(cx: &mut TestAppContext) {
+        let terminal = cx.new(|cx| {
+            TerminalBuilder::new_display_only(
+                CursorShape::default(),
+                AlternateScroll::On,
+                None,
+                0,
+                cx.background_executor(),
+                PathStyle::local(),
+            )
+            .unwrap()
+            .subscribe(cx)
+        });
+
+        terminal.update(cx, |terminal, cx| {
+            terminal.write_output(
+                b"\x1b]133;A\x07$ \x1b]133;B\x07ls\n\x1b]133;C\x07a.txt\nb.txt\n\x1b]133;D;0\x07\
+                  \x1b]133;A\x07$ \x1b]133;B\x07false\n\x1b]133;C\x07\x1b]133;D;1\x07\x1b]133;A\x07$ ",
+                cx,
+            );
+            let term = terminal.term.clone();
+            terminal.last_content = Terminal::make_content(&term.lock_unfair(), &terminal.last_content);
+        });
+
+        terminal.update(cx, |terminal, _cx| {
+            let blocks = terminal.command_blocks();
+            assert_eq!(blocks.len(), 3);
+            assert_eq!(blocks.get(0).unwrap().status(), CommandStatus::Succeeded);
+            assert_eq!(blocks.get(1).unwrap().status(), CommandStatus::Failed(1));
+            assert_eq!(blocks.get(2).unwrap().status(), CommandStatus::Running);
+            assert_eq!(blocks.get(1).unwrap().prompt_line, 3);
+
+            assert_eq!(terminal.focused_command_block(), Some(1));
+            assert_eq!(
+                terminal.command_output_text(0).as_deref(),
+                Some("a.txt\nb.txt")
+            );
+            assert_eq!(terminal.command_output_text(1), None);
+            assert!(
+                terminal
+                    .last_command_text()
+                    .is_some_and(|text| text.starts_with("$ false"))
+            );
+            assert_eq!(terminal.last_lines_text(1), "$");
+        });
+    }
+
     #[gpui::test]
     async fn test_write_output_preserves_existing_crlf(cx: &mut TestAppContext) {
         let terminal = cx.new(|cx| {
@@ -0,0 +1,543 @@
+//! Playback of asciicast recordings, such as those made with [`ToggleRecording`],
+//! in a read-only terminal.
+//!
+//! [`ToggleRecording`]: crate::ToggleRecording
+
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use gpui::{
+    AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, MouseButton, MouseDownEvent,
+    Render, Task, WeakEntity, canvas,
+};
+use project::{Project, ProjectEntryId, ProjectPath};
+use terminal::{
+    Terminal, TerminalBuilder,
+    recording::{Asciicast, CAST_EXTENSION, CastEvent, CastEventKind},
+    terminal_settings::{AlternateScroll, CursorShape},
+};
+use ui::{IconButtonShape, ProgressBar, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{
+    Pane, Workspace,
+    item::{Item, ItemBufferKind, ProjectItem, TabContentParams},
+};
+
+use crate::TerminalView;
+
+/// How often the position is advanced while playing.
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+const SPEEDS: [f64; 4] = [0.5, 1., 2., 4.];
+
+/// A `.cast` file in the project.
+pub struct CastFile {
+    abs_path: PathBuf,
+    project_path: ProjectPath,
+    id: ProjectEntryId,
+    cast: Arc<Asciicast>,
+}
+
+impl project::ProjectItem for CastFile {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<anyhow::Result<Entity<Self>>>> {
+        if path.path.extension() != Some(CAST_EXTENSION) {
+            return None;
+        }
+        let path = path.clone();
+        let project = project.clone();
+        let fs = project.read(cx).fs().clone();
+        Some(cx.spawn(async move |cx| {
+            let abs_path = project
+                .read_with(cx, |project, cx| project.absolute_path(&path, cx))
+                .with_context(|| format!("finding the absolute path of {path:?}"))?;
+            let text = fs.load(&abs_path).await?;
+            let cast = Asciicast::parse(&text)
+                .with_context(|| format!("reading recording {}", abs_path.display()))?;
+            let id = project
+                .update(cx, |project, cx| {
+                    project.entry_for_path(&path, cx).map(|entry| entry.id)
+                })
+                .context("Entry not found")?;
+            Ok(cx.new(|_| CastFile {
+                abs_path,
+                project_path: path,
+                id,
+                cast: Arc::new(cast),
+            }))
+        }))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        Some(self.id)
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+
+    fn is_dirty(&self) -> bool {
+        false
+    }
+}
+
+/// Replays a recording in a display-only terminal, with play/pause, seeking and
+/// speed controls.
+pub struct CastPlayer {
+    cast_file: Entity<CastFile>,
+    cast: Arc<Asciicast>,
+    project: WeakEntity<Project>,
+    terminal: Option<Entity<Terminal>>,
+    terminal_view: Option<Entity<TerminalView>>,
+    focus_handle: FocusHandle,
+    /// Index of the next event to play.
+    next_event: usize,
+    /// Recording time reached, in seconds.
+    position: f64,
+    speed: f64,
+    playing: bool,
+    _playback: Task<()>,
+}
+
+impl CastPlayer {
+    pub fn new(
+        cast_file: Entity<CastFile>,
+        project: WeakEntity<Project>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let cast = cast_file.read(cx).cast.clone();
+        let path_style = project
+            .read_with(cx, |project, cx| project.path_style(cx))
+            .unwrap_or(util::paths::PathStyle::local());
+        let terminal = TerminalBuilder::new_display_only(
+            CursorShape::default(),
+            AlternateScroll::On,
+            None,
+            window.window_handle().window_id().as_u64(),
+            cx.background_executor(),
+            path_style,
+        )
+        .log_err()
+        .map(|builder| {
+            cx.new(|cx| {
+                let mut terminal = builder.subscribe(cx);
+                terminal.set_fixed_size(Some((cast.header.width, cast.header.height)), cx);
+                terminal
+            })
+        });
+        let terminal_view = terminal.clone().map(|terminal| {
+            cx.new(|cx| TerminalView::new(terminal, workspace, None, project.clone(), window, cx))
+        });
+
+        let mut this = Self {
+            cast_file,
+            cast,
+            project,
+            terminal,
+            terminal_view,
+            focus_handle: cx.focus_handle(),
+            next_event: 0,
+            position: 0.,
+            speed: 1.,
+            playing: false,
+            _playback: Task::ready(()),
+        };
+        this.play(window, cx);
+        this
+    }
+
+    fn duration(&self) -> f64 {
+        self.cast.duration()
+    }
+
+    fn toggle_playback(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.playing {
+            self.pause(cx);
+        } else {
+            self.play(window, cx);
+        }
+    }
+
+    fn play(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.next_event >= self.cast.events.len() {
+            self.seek(0., cx);
+        }
+        self.playing = true;
+        self._playback = cx.spawn_in(window, async move |this, cx| {
+            let mut last_frame = Instant::now();
+            loop {
+                cx.background_executor().timer(FRAME_INTERVAL).await;
+                let now = Instant::now();
+                let elapsed = now - last_frame;
+                last_frame = now;
+                let Ok(true) = this.update(cx, |this, cx| this.advance(elapsed, cx)) else {
+                    break;
+                };
+            }
+        });
+        cx.notify();
+    }
+
+    fn pause(&mut self, cx: &mut Context<Self>) {
+        self.playing = false;
+        self._playback = Task::ready(());
+        cx.notify();
+    }
+
+    /// Moves playback on by `elapsed` wall time, returning whether there is more to play.
+    fn advance(&mut self, elapsed: Duration, cx: &mut Context<Self>) -> bool {
+        let mut position = self.position + elapsed.as_secs_f64() * self.speed;
+        // Skip over long pauses, as asciinema does.
+        if let Some(limit) = self.cast.header.idle_time_limit
+            && let Some(next) = self.cast.events.get(self.next_event)
+        {
+            position = position.max(next.time - limit);
+        }
+        self.play_until(position, cx);
+        if self.next_event >= self.cast.events.len() {
+            self.playing = false;
+        }
+        cx.notify();
+        self.playing
+    }
+
+    /// Moves to `position`, replaying from the start when going backwards.
+    fn seek(&mut self, position: f64, cx: &mut Context<Self>) {
+        let position = position.clamp(0., self.duration());
+        if position < self.position {
+            if let Some(terminal) = &self.terminal {
+                let size = (self.cast.header.width, self.cast.header.height);
+                terminal.update(cx, |terminal, cx| {
+                    terminal.reset_display(cx);
+                    terminal.set_fixed_size(Some(size), cx);
+                });
+            }
+            self.next_event = 0;
+            self.position = 0.;
+        }
+        self.play_until(position, cx);
+        cx.notify();
+    }
+
+    fn play_until(&mut self, position: f64, cx: &mut Context<Self>) {
+        let steps = playback_steps(&self.cast.events, &mut self.next_event, position);
+        self.position = position.min(self.duration());
+        if let Some(terminal) = &self.terminal
+            && !steps.is_empty()
+        {
+            terminal.update(cx, |terminal, cx| {
+                for step in steps {
+                    match step {
+                        PlaybackStep::Output(output) => terminal.write_raw_output(&output, cx),
+                        PlaybackStep::Resize(size) => terminal.set_fixed_size(Some(size), cx),
+                    }
+                }
+            });
+        }
+    }
+
+    fn render_controls(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let duration = self.duration();
+        let player = cx.entity().downgrade();
+        let seek_bar = div()
+            .relative()
+            .flex_1()
+            .h_2()
+            .flex()
+            .items_center()
+            .cursor_pointer()
+            .child(ProgressBar::new(
+                "cast-position",
+                self.position as f32,
+                duration.max(f64::EPSILON) as f32,
+                cx,
+            ))
+            .child(
+                canvas(
+                    |_, _, _| {},
+                    move |bounds, _, window, _| {
+                        window.on_mouse_event(move |event: &MouseDownEvent, phase, _, cx| {
+                            if phase.bubble()
+                                && event.button == MouseButton::Left
+                                && bounds.contains(&event.position)
+                            {
+                                let fraction =
+                                    (event.position.x - bounds.left()) / bounds.size.width;
+                                player
+                                    .update(cx, |player, cx| {
+                                        player.seek(fraction as f64 * duration, cx)
+                                    })
+                                    .ok();
+                            }
+                        });
+                    },
+                )
+                .absolute()
+                .size_full(),
+            );
+
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .border_t_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                IconButton::new(
+                    "toggle-playback",
+                    if self.playing {
+                        IconName::DebugPause
+                    } else {
+                        IconName::PlayFilled
+                    },
+                )
+                .shape(IconButtonShape::Square)
+                .tooltip(Tooltip::text(if self.playing { "Pause" } else { "Play" }))
+                .on_click(cx.listener(|this, _, window, cx| this.toggle_playback(window, cx))),
+            )
+            .child(
+                Label::new(format!(
+                    "{} / {}",
+                    format_time(self.position),
+                    format_time(duration)
+                ))
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .child(seek_bar)
+            .children(SPEEDS.into_iter().map(|speed| {
+                Button::new(
+                    SharedString::from(format!("speed-{speed}")),
+                    format!("{speed}×"),
+                )
+                .label_size(LabelSize::Small)
+                .toggle_state(self.speed == speed)
+                .on_click(cx.listener(move |this, _, _, cx| {
+                    this.speed = speed;
+                    cx.notify();
+                }))
+            }))
+    }
+}
+
+/// A change playing a recording makes to the terminal.
+#[derive(Debug, PartialEq)]
+enum PlaybackStep {
+    Output(Vec<u8>),
+    /// The recorded terminal was resized to `(columns, lines)`.
+    Resize((usize, usize)),
+}
+
+/// The changes made by the events from `next_event` up to `position`, in order, with
+/// consecutive output merged. Advances `next_event` past them.
+fn playback_steps(
+    events: &[CastEvent],
+    next_event: &mut usize,
+    position: f64,
+) -> Vec<PlaybackStep> {
+    let mut steps = Vec::new();
+    while let Some(event) = events.get(*next_event)
+        && event.time <= position
+    {
+        match event.kind {
+            CastEventKind::Output => {
+                if let Some(PlaybackStep::Output(output)) = steps.last_mut() {
+                    output.extend_from_slice(event.data.as_bytes());
+                } else {
+                    steps.push(PlaybackStep::Output(event.data.as_bytes().to_vec()));
+                }
+            }
+            CastEventKind::Resize => {
+                if let Some(size) = event.size() {
+                    steps.push(PlaybackStep::Resize(size));
+                }
+            }
+            CastEventKind::Input | CastEventKind::Marker => {}
+        }
+        *next_event += 1;
+    }
+    steps
+}
+
+/// Formats seconds as `m:ss`.
+fn format_time(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+impl EventEmitter<()> for CastPlayer {}
+
+impl Focusable for CastPlayer {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        match &self.terminal_view {
+            Some(terminal_view) => terminal_view.focus_handle(cx),
+            None => self.focus_handle.clone(),
+        }
+    }
+}
+
+impl Render for CastPlayer {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(terminal_view) = self.terminal_view.clone() else {
+            return v_flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .track_focus(&self.focus_handle)
+                .child(Label::new("Failed to create a terminal for playback").color(Color::Error))
+                .into_any_element();
+        };
+        v_flex()
+            .size_full()
+            .child(div().flex_1().min_h_0().child(terminal_view))
+            .child(self.render_controls(cx))
+            .into_any_element()
+    }
+}
+
+impl Item for CastPlayer {
+    type Event = ();
+
+    fn buffer_kind(&self, _: &App) -> ItemBufferKind {
+        ItemBufferKind::Singleton
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.cast_file.entity_id(), self.cast_file.read(cx))
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        Some(
+            self.cast_file
+                .read(cx)
+                .abs_path
+                .to_string_lossy()
+                .into_owned()
+                .into(),
+        )
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.cast_file
+            .read(cx)
+            .project_path
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string()
+            .into()
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .single_line()
+            .color(params.text_color())
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_icon(&self, _: &Window, _: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::PlayOutlined))
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn can_split(&self) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<workspace::WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Entity<Self>>> {
+        let workspace = self
+            .terminal_view
+            .as_ref()
+            .map(|terminal_view| terminal_view.read(cx).workspace.clone())
+            .unwrap_or_else(WeakEntity::new_invalid);
+        let (cast_file, project) = (self.cast_file.clone(), self.project.clone());
+        Task::ready(Some(
+            cx.new(|cx| Self::new(cast_file, project, workspace, window, cx)),
+        ))
+    }
+}
+
+impl ProjectItem for CastPlayer {
+    type Item = CastFile;
+
+    fn for_project_item(
+        project: Entity<Project>,
+        _: Option<&Pane>,
+        item: Entity<Self::Item>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let workspace = window
+            .root::<Workspace>()
+            .flatten()
+            .map(|workspace| workspace.downgrade())
+            .unwrap_or_else(WeakEntity::new_invalid);
+        Self::new(item, project.downgrade(), workspace, window, cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0.4), "0:00");
+        assert_eq!(format_time(75.9), "1:15");
+        assert_eq!(format_time(3600.), "60:00");
+    }
+
+    #[test]
+    fn test_playback_steps_keep_resizes_between_output() {
+        let cast = Asciicast::parse(
+            r#"{"version": 2, "width": 40, "height": 10}
+[0.1, "o", "one "]
+[0.2, "i", "x"]
+[0.3, "o", "two"]
+[0.5, "r", "100x30"]
+[0.6, "o", "three"]
+[0.7, "r", "bad"]
+[0.8, "m", ""]
+[1.5, "o", "four"]"#,
+        )
+        .unwrap();
+
+        let mut next_event = 0;
+        assert_eq!(
+            playback_steps(&cast.events, &mut next_event, 1.),
+            [
+                PlaybackStep::Output(b"one two".to_vec()),
+                PlaybackStep::Resize((100, 30)),
+                PlaybackStep::Output(b"three".to_vec()),
+            ]
+        );
+        assert_eq!(next_event, 7);
+        assert_eq!(
+            playback_steps(&cast.events, &mut next_event, 2.),
+            [PlaybackStep::Output(b"four".to_vec())]
+        );
+        assert!(playback_steps(&cast.events, &mut next_event, 3.).is_empty());
+    }
+}
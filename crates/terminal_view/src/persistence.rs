@@ -65,7 +65,8 @@ fn serialize_pane(pane: &Entity<Pane>, active: bool, cx: &mut App) -> Serialized
         .items()
         .filter_map(|item| {
             let terminal_view = item.act_as::<TerminalView>(cx)?;
-            if terminal_view.read(cx).terminal().read(cx).task().is_some() {
+            let terminal = terminal_view.read(cx).terminal().read(cx);
+            if terminal.task().is_some() || terminal.is_shared_by_host() {
                 None
             } else {
                 let id = item.item_id().as_u64();
@@ -449,6 +450,10 @@ pub enum SerializableConnectionInfo {
         password: Option<String>,
         private_key_path: Option<PathBuf>,
         passphrase: Option<String>,
+        #[serde(default)]
+        certificate_path: Option<PathBuf>,
+        #[serde(default)]
+        agent_forwarding: bool,
         session_id: Option<Uuid>,
     },
     Telnet {
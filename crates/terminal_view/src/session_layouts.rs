@@ -0,0 +1,415 @@
+//! Named layouts of saved sessions in the terminal panel, saved in the session store and
+//! listed in the remote explorer.
+
+use std::vec;
+
+use anyhow::{Context as _, Result, anyhow};
+use futures::future::join_all;
+use gpui::{App, AppContext as _, Axis, Context, Entity, Focusable as _, Task, Window};
+use itertools::Itertools as _;
+use terminal::{
+    LayoutAxis, LayoutNode, LayoutTab, SessionLayout, SessionNode, SessionStoreEntity, Terminal,
+    remote_command::connect_to_session,
+};
+use workspace::{Member, Pane, PaneAxis, PaneGroup, move_item};
+
+use crate::{
+    TerminalView,
+    terminal_panel::{TerminalPanel, new_terminal_pane},
+};
+
+impl TerminalPanel {
+    /// The panel's panes and the saved sessions open in them, or `None` if no saved sessions
+    /// are open. Tabs that aren't connected to a saved session are left out.
+    pub fn capture_layout(&self, cx: &App) -> Option<LayoutNode> {
+        capture_member(&self.center.root, cx)
+    }
+
+    /// Connects to every session in `layout` and tiles them in the same arrangement. The
+    /// panel's current terminals are kept as tabs in the layout's first pane.
+    ///
+    /// Sessions that were deleted since the layout was saved are skipped. If some of the
+    /// sessions can't be connected to, the others are still opened.
+    pub fn open_layout(
+        &mut self,
+        layout: &SessionLayout,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let session_store = SessionStoreEntity::global(cx);
+        let sessions = layout
+            .root
+            .session_ids()
+            .into_iter()
+            .map(
+                |session_id| match session_store.read(cx).store().find_node(session_id) {
+                    Some(SessionNode::Session(session)) => Some(session.clone()),
+                    _ => None,
+                },
+            )
+            .collect::<Vec<_>>();
+        let window_id = window.window_handle().window_id().as_u64();
+        let connections = sessions
+            .iter()
+            .map(|session| {
+                let session = session.clone();
+                let task = session
+                    .as_ref()
+                    .map(|session| connect_to_session(session, window_id, cx));
+                async move {
+                    let session = session?;
+                    let result = task?.await;
+                    Some(result.with_context(|| format!("connecting to \"{}\"", session.name)))
+                }
+            })
+            .collect::<Vec<_>>();
+        let root = layout.root.clone();
+        let name = layout.name.clone();
+
+        cx.spawn_in(window, async move |terminal_panel, cx| {
+            let mut errors = Vec::new();
+            let builders = join_all(connections)
+                .await
+                .into_iter()
+                .map(|connection| match connection? {
+                    Ok(builder) => Some(builder),
+                    Err(error) => {
+                        errors.push(error);
+                        None
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let opened = terminal_panel.update_in(cx, |terminal_panel, window, cx| {
+                let terminals = builders
+                    .into_iter()
+                    .map(|builder| builder.map(|builder| cx.new(|cx| builder.subscribe(cx))))
+                    .collect::<Vec<_>>();
+                terminal_panel.tile_terminals(&root, terminals, window, cx)
+            })?;
+
+            if !errors.is_empty() {
+                return Err(anyhow!(
+                    "{}",
+                    errors.iter().map(|error| format!("{error:#}")).join("\n")
+                ));
+            }
+            if !opened {
+                return Err(anyhow!(
+                    "none of the sessions in \"{name}\" are saved anymore"
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    /// Replaces the panel's panes with `root`, showing each of `terminals` in the tab of the
+    /// session at the same position. Tabs whose terminal is `None` are left out.
+    ///
+    /// Returns whether any of the terminals were shown.
+    fn tile_terminals(
+        &mut self,
+        root: &LayoutNode,
+        terminals: Vec<Option<Entity<Terminal>>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(root) = self.build_layout_member(root, &mut terminals.into_iter(), window, cx)
+        else {
+            return false;
+        };
+        self.replace_center(PaneGroup::with_root(root), window, cx);
+        true
+    }
+
+    fn build_layout_member(
+        &mut self,
+        node: &LayoutNode,
+        terminals: &mut vec::IntoIter<Option<Entity<Terminal>>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Member> {
+        match node {
+            LayoutNode::Pane { tabs, active_tab } => {
+                let tab_terminals = tabs
+                    .iter()
+                    .map(|_| terminals.next().flatten())
+                    .collect::<Vec<_>>();
+                let workspace = self.workspace.upgrade()?;
+                let database_id = workspace.read(cx).database_id();
+                let project = workspace.read(cx).project().clone();
+                let mut views = Vec::new();
+                let mut active_view = 0;
+                for (ix, (tab, terminal)) in tabs.iter().zip(tab_terminals).enumerate() {
+                    let Some(terminal) = terminal else {
+                        continue;
+                    };
+                    if ix == *active_tab {
+                        active_view = views.len();
+                    }
+                    views.push(cx.new(|cx| {
+                        let mut view = TerminalView::new(
+                            terminal,
+                            self.workspace.clone(),
+                            database_id,
+                            project.downgrade(),
+                            window,
+                            cx,
+                        );
+                        view.custom_title = tab.title.clone();
+                        view
+                    }));
+                }
+                if views.is_empty() {
+                    return None;
+                }
+
+                let zoomed = self.active_pane.read(cx).is_zoomed();
+                let pane = new_terminal_pane(self.workspace.clone(), project, zoomed, window, cx);
+                pane.update(cx, |pane, cx| {
+                    for view in views {
+                        pane.add_item(Box::new(view), false, false, None, window, cx);
+                    }
+                    pane.activate_item(active_view, false, false, window, cx);
+                });
+                Some(Member::Pane(pane))
+            }
+            LayoutNode::Split {
+                axis,
+                flexes,
+                children,
+            } => {
+                let mut members = Vec::new();
+                let mut member_flexes = Vec::new();
+                for (ix, child) in children.iter().enumerate() {
+                    if let Some(member) = self.build_layout_member(child, terminals, window, cx) {
+                        members.push(member);
+                        member_flexes.push(
+                            flexes
+                                .as_ref()
+                                .and_then(|flexes| flexes.get(ix).copied())
+                                .unwrap_or(1.),
+                        );
+                    }
+                }
+                match members.len() {
+                    0 => None,
+                    1 => members.pop(),
+                    _ => Some(Member::Axis(PaneAxis::load(
+                        match axis {
+                            LayoutAxis::Horizontal => Axis::Horizontal,
+                            LayoutAxis::Vertical => Axis::Vertical,
+                        },
+                        members,
+                        Some(normalize_flexes(member_flexes)),
+                    ))),
+                }
+            }
+        }
+    }
+
+    fn replace_center(&mut self, center: PaneGroup, window: &mut Window, cx: &mut Context<Self>) {
+        let old_panes = self.center.panes().into_iter().cloned().collect::<Vec<_>>();
+        self.center = center;
+        self.active_pane = self.center.first_pane();
+        for old_pane in old_panes {
+            let item_ids = old_pane
+                .read(cx)
+                .items()
+                .map(|item| item.item_id())
+                .collect::<Vec<_>>();
+            for item_id in item_ids {
+                let index = self.active_pane.read(cx).items_len();
+                move_item(
+                    &old_pane,
+                    &self.active_pane,
+                    item_id,
+                    index,
+                    false,
+                    window,
+                    cx,
+                );
+            }
+        }
+        for pane in self.center.panes() {
+            self.apply_tab_bar_buttons(pane, cx);
+        }
+        self.serialize(cx);
+        window.focus(&self.active_pane.focus_handle(cx), cx);
+        cx.notify();
+    }
+}
+
+fn capture_member(member: &Member, cx: &App) -> Option<LayoutNode> {
+    match member {
+        Member::Axis(PaneAxis {
+            axis,
+            members,
+            flexes,
+            bounding_boxes: _,
+        }) => {
+            let flexes = flexes.lock().clone();
+            let (mut children, flexes): (Vec<_>, Vec<_>) = members
+                .iter()
+                .zip(flexes)
+                .filter_map(|(member, flex)| Some((capture_member(member, cx)?, flex)))
+                .unzip();
+            if children.len() <= 1 {
+                return children.pop();
+            }
+            Some(LayoutNode::Split {
+                axis: match axis {
+                    Axis::Horizontal => LayoutAxis::Horizontal,
+                    Axis::Vertical => LayoutAxis::Vertical,
+                },
+                flexes: Some(normalize_flexes(flexes)),
+                children,
+            })
+        }
+        Member::Pane(pane) => capture_pane(pane, cx),
+    }
+}
+
+fn capture_pane(pane: &Entity<Pane>, cx: &App) -> Option<LayoutNode> {
+    let pane = pane.read(cx);
+    let active_item_id = pane.active_item().map(|item| item.item_id());
+    let mut tabs = Vec::new();
+    let mut active_tab = 0;
+    for item in pane.items() {
+        let Some(terminal_view) = item.downcast::<TerminalView>() else {
+            continue;
+        };
+        let terminal_view = terminal_view.read(cx);
+        let Some(session_id) = terminal_view
+            .terminal()
+            .read(cx)
+            .connection_info()
+            .and_then(|info| info.session_id())
+        else {
+            continue;
+        };
+        if Some(item.item_id()) == active_item_id {
+            active_tab = tabs.len();
+        }
+        tabs.push(LayoutTab {
+            session_id,
+            title: terminal_view.custom_title().map(ToOwned::to_owned),
+        });
+    }
+    (!tabs.is_empty()).then_some(LayoutNode::Pane { tabs, active_tab })
+}
+
+/// Scales `flexes` so they add up to their count, as `PaneAxis` expects.
+fn normalize_flexes(flexes: Vec<f32>) -> Vec<f32> {
+    let total = flexes.iter().sum::<f32>();
+    if total <= 0. {
+        return vec![1.; flexes.len()];
+    }
+    let count = flexes.len() as f32;
+    flexes
+        .into_iter()
+        .map(|flex| flex * count / total)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal_panel::tests::init_test;
+    use gpui::TestAppContext;
+    use project::{FakeFs, Project};
+    use terminal::{
+        ConnectionInfo, TerminalBuilder,
+        terminal_settings::{AlternateScroll, CursorShape},
+    };
+    use util::paths::PathStyle;
+    use uuid::Uuid;
+    use workspace::Workspace;
+
+    #[gpui::test]
+    async fn test_captured_layout_matches_opened_one(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let workspace = cx.add_window(|window, cx| Workspace::test_new(project, window, cx));
+
+        let session_ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let layout = LayoutNode::Split {
+            axis: LayoutAxis::Horizontal,
+            flexes: Some(vec![0.5, 1.5]),
+            children: vec![
+                LayoutNode::Pane {
+                    tabs: vec![
+                        LayoutTab {
+                            session_id: session_ids[0],
+                            title: Some("core-1".into()),
+                        },
+                        LayoutTab {
+                            session_id: session_ids[1],
+                            title: None,
+                        },
+                    ],
+                    active_tab: 1,
+                },
+                LayoutNode::Pane {
+                    tabs: vec![LayoutTab {
+                        session_id: session_ids[2],
+                        title: None,
+                    }],
+                    active_tab: 0,
+                },
+            ],
+        };
+
+        let terminal_panel = workspace
+            .update(cx, |workspace, window, cx| {
+                let terminal_panel = cx.new(|cx| TerminalPanel::new(workspace, window, cx));
+                let terminals = session_ids
+                    .iter()
+                    .map(|&session_id| {
+                        let connection_info = ConnectionInfo::Ssh {
+                            host: "router".into(),
+                            port: 22,
+                            username: None,
+                            password: None,
+                            private_key_path: None,
+                            passphrase: None,
+                            certificate_path: None,
+                            agent_forwarding: false,
+                            session_id: Some(session_id),
+                        };
+                        Some(cx.new(|cx| {
+                            TerminalBuilder::new_disconnected_ssh(
+                                connection_info,
+                                CursorShape::default(),
+                                AlternateScroll::On,
+                                None,
+                                0,
+                                cx.background_executor(),
+                                PathStyle::local(),
+                            )
+                            .unwrap()
+                            .subscribe(cx)
+                        }))
+                    })
+                    .collect();
+                terminal_panel.update(cx, |terminal_panel, cx| {
+                    assert!(terminal_panel.tile_terminals(&layout, terminals, window, cx));
+                });
+                terminal_panel
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        terminal_panel.read_with(cx, |terminal_panel, cx| {
+            assert_eq!(terminal_panel.capture_layout(cx), Some(layout));
+        });
+    }
+
+    #[test]
+    fn test_normalize_flexes() {
+        assert_eq!(normalize_flexes(vec![0.5, 0.5]), vec![1., 1.]);
+        assert_eq!(normalize_flexes(vec![1., 3.]), vec![0.5, 1.5]);
+        assert_eq!(normalize_flexes(vec![0., 0.]), vec![1., 1.]);
+    }
+}
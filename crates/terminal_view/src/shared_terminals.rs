@@ -0,0 +1,727 @@
+//! Sharing terminals with collaborators in a call.
+//!
+//! The host of a shared project streams the screen of each terminal it shares, sending a
+//! snapshot first and then only the lines that changed. Guests show the screen in their terminal
+//! panel and, when the host allows it, send what they type back to the host's terminal.
+
+use std::{future::Future, sync::Arc, time::Duration};
+
+use anyhow::{Context as _, Result};
+use client::{AnyProtoClient, Client, TypedEnvelope, proto};
+use collections::HashMap;
+use futures::{StreamExt as _, channel::mpsc};
+use gpui::{
+    App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity,
+};
+use project::Project;
+use settings::Settings as _;
+use terminal::{
+    Event, Terminal, TerminalBuilder,
+    alacritty_terminal::{
+        index::{Column, Line, Point as AlacPoint},
+        selection::SelectionRange,
+        term::cell::{Cell, Flags},
+        vte::ansi::{Color, NamedColor, Rgb},
+    },
+    shared_screen::SharedScreen,
+    terminal_settings::TerminalSettings,
+};
+use util::ResultExt as _;
+
+/// How long changes to a shared terminal are batched before they're sent to guests.
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+pub fn init(client: &Arc<Client>) {
+    let client = AnyProtoClient::from(client.clone());
+    client.add_entity_message_handler(SharedTerminals::handle_update_shared_terminal);
+    client.add_entity_message_handler(SharedTerminals::handle_unshare_terminal);
+    client.add_entity_request_handler(SharedTerminals::handle_get_shared_terminals);
+    client.add_entity_request_handler(SharedTerminals::handle_shared_terminal_input);
+}
+
+pub enum SharedTerminalsEvent {
+    /// The host started sharing a terminal, which should be shown in the terminal panel.
+    TerminalShared(Entity<Terminal>),
+    /// The host stopped sharing a terminal.
+    TerminalUnshared(Entity<Terminal>),
+}
+
+/// The terminals shared in a project: those this client shares as the host, or those the host
+/// shares when this client is a guest.
+pub struct SharedTerminals {
+    project: WeakEntity<Project>,
+    client: Arc<Client>,
+    client_subscription: Option<client::Subscription>,
+    hosted: HashMap<u64, HostedTerminal>,
+    guest: HashMap<u64, GuestTerminal>,
+    _project_subscription: Subscription,
+}
+
+struct HostedTerminal {
+    terminal: WeakEntity<Terminal>,
+    writable: bool,
+    /// The screen and title as last sent to guests.
+    sent_screen: SharedScreen,
+    sent_title: String,
+    pending_update: Option<Task<()>>,
+    _subscriptions: [Subscription; 2],
+}
+
+struct GuestTerminal {
+    terminal: Entity<Terminal>,
+    screen: SharedScreen,
+    writable: bool,
+    _forward_input: Task<()>,
+}
+
+impl EventEmitter<SharedTerminalsEvent> for SharedTerminals {}
+
+impl SharedTerminals {
+    pub fn new(project: Entity<Project>, cx: &mut Context<Self>) -> Self {
+        let mut this = Self {
+            project: project.downgrade(),
+            client: project.read(cx).client(),
+            client_subscription: None,
+            hosted: HashMap::default(),
+            guest: HashMap::default(),
+            _project_subscription: cx.subscribe(&project, Self::handle_project_event),
+        };
+        this.subscribe_to_project(cx);
+        this
+    }
+
+    /// Whether terminals can be shared, which needs the project to be shared by this client.
+    pub fn can_share(&self, cx: &App) -> bool {
+        self.host_project_id(cx).is_some()
+    }
+
+    pub fn is_shared(&self, terminal: &Entity<Terminal>) -> bool {
+        self.hosted.contains_key(&terminal_id(terminal))
+    }
+
+    /// Whether guests may type into a terminal shared by this client.
+    pub fn is_writable(&self, terminal: &Entity<Terminal>) -> bool {
+        self.hosted
+            .get(&terminal_id(terminal))
+            .is_some_and(|hosted| hosted.writable)
+    }
+
+    pub fn toggle_sharing(
+        &mut self,
+        terminal: &Entity<Terminal>,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let id = terminal_id(terminal);
+        if self.hosted.contains_key(&id) {
+            self.unshare(id, cx);
+            return Ok(());
+        }
+        anyhow::ensure!(
+            self.can_share(cx),
+            "Share the project in a call to share its terminals"
+        );
+        anyhow::ensure!(
+            !terminal.read(cx).is_shared_by_host(),
+            "Only the host can share this terminal"
+        );
+
+        let subscriptions = [
+            cx.subscribe(terminal, move |this, _, event, cx| match event {
+                Event::CloseTerminal => this.unshare(id, cx),
+                Event::Wakeup | Event::SelectionsChanged | Event::TitleChanged => {
+                    this.schedule_update(id, cx)
+                }
+                _ => {}
+            }),
+            cx.observe_release(terminal, move |this, _, cx| this.unshare(id, cx)),
+        ];
+        self.hosted.insert(
+            id,
+            HostedTerminal {
+                terminal: terminal.downgrade(),
+                writable: false,
+                sent_screen: SharedScreen::default(),
+                sent_title: String::new(),
+                pending_update: None,
+                _subscriptions: subscriptions,
+            },
+        );
+        self.send_update(id, true, cx);
+        Ok(())
+    }
+
+    /// Lets guests type into a shared terminal, or makes it read-only for them again.
+    pub fn toggle_writable(&mut self, terminal: &Entity<Terminal>, cx: &mut Context<Self>) {
+        let id = terminal_id(terminal);
+        if let Some(hosted) = self.hosted.get_mut(&id) {
+            hosted.writable = !hosted.writable;
+            self.send_update(id, true, cx);
+        }
+    }
+
+    fn unshare(&mut self, id: u64, cx: &mut Context<Self>) {
+        if self.hosted.remove(&id).is_none() {
+            return;
+        }
+        if let Some(project_id) = self.host_project_id(cx) {
+            self.client
+                .send(proto::UnshareTerminal {
+                    project_id,
+                    terminal_id: id,
+                })
+                .log_err();
+        }
+    }
+
+    fn host_project_id(&self, cx: &App) -> Option<u64> {
+        let project = self.project.upgrade()?.read(cx);
+        if project.is_via_collab() {
+            return None;
+        }
+        project.remote_id()
+    }
+
+    fn subscribe_to_project(&mut self, cx: &mut Context<Self>) {
+        // The previous subscription has to be dropped before subscribing again.
+        self.client_subscription = None;
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let Some(remote_id) = project.read(cx).remote_id() else {
+            return;
+        };
+        self.client_subscription = self
+            .client
+            .subscribe_to_entity(remote_id)
+            .log_err()
+            .map(|subscription| subscription.set_entity(&cx.entity(), &cx.to_async()));
+        if project.read(cx).is_via_collab() {
+            self.fetch_shared_terminals(cx);
+        }
+    }
+
+    fn handle_project_event(
+        &mut self,
+        _: Entity<Project>,
+        event: &project::Event,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            project::Event::RemoteIdChanged(remote_id) => {
+                if remote_id.is_none() {
+                    self.hosted.clear();
+                }
+                self.subscribe_to_project(cx);
+            }
+            project::Event::Reshared => {
+                let ids = self.hosted.keys().copied().collect::<Vec<_>>();
+                for id in ids {
+                    self.send_update(id, true, cx);
+                }
+            }
+            project::Event::HostReshared | project::Event::Rejoined => {
+                self.fetch_shared_terminals(cx);
+            }
+            project::Event::DisconnectedFromHost => {
+                for (_, guest) in self.guest.drain() {
+                    cx.emit(SharedTerminalsEvent::TerminalUnshared(guest.terminal));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn schedule_update(&mut self, id: u64, cx: &mut Context<Self>) {
+        let Some(hosted) = self.hosted.get_mut(&id) else {
+            return;
+        };
+        if hosted.pending_update.is_some() {
+            return;
+        }
+        hosted.pending_update = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+            this.update(cx, |this, cx| {
+                if let Some(hosted) = this.hosted.get_mut(&id) {
+                    hosted.pending_update = None;
+                }
+                this.send_update(id, false, cx);
+            })
+            .ok();
+        }));
+    }
+
+    /// Sends guests the lines of a shared terminal that changed since the last update, or all of
+    /// them when `snapshot` is set.
+    fn send_update(&mut self, id: u64, snapshot: bool, cx: &mut Context<Self>) {
+        let Some(project_id) = self.host_project_id(cx) else {
+            return;
+        };
+        let Some(hosted) = self.hosted.get_mut(&id) else {
+            return;
+        };
+        let Some(terminal) = hosted.terminal.upgrade() else {
+            return;
+        };
+        let terminal = terminal.read(cx);
+        let screen = terminal.shared_screen();
+        let title = terminal.title(false);
+        let changed_lines = if snapshot {
+            (0..screen.lines()).collect()
+        } else {
+            screen.changed_lines(&hosted.sent_screen)
+        };
+        if changed_lines.is_empty()
+            && screen.cursor == hosted.sent_screen.cursor
+            && screen.selection == hosted.sent_screen.selection
+            && title == hosted.sent_title
+        {
+            return;
+        }
+
+        let message = update_to_proto(
+            project_id,
+            id,
+            &screen,
+            &changed_lines,
+            title.clone(),
+            hosted.writable,
+            snapshot,
+        );
+        hosted.sent_screen = screen;
+        hosted.sent_title = title;
+        self.client.send(message).log_err();
+    }
+
+    fn fetch_shared_terminals(&mut self, cx: &mut Context<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        if !project.read(cx).is_via_collab() {
+            return;
+        }
+        let Some(project_id) = project.read(cx).remote_id() else {
+            return;
+        };
+        let request = self
+            .client
+            .request(proto::GetSharedTerminals { project_id });
+        cx.spawn(async move |this, cx| {
+            let response = request.await?;
+            this.update(cx, |this, cx| {
+                let shared_ids = response
+                    .terminals
+                    .iter()
+                    .map(|update| update.terminal_id)
+                    .collect::<Vec<_>>();
+                let unshared_ids = this
+                    .guest
+                    .keys()
+                    .copied()
+                    .filter(|id| !shared_ids.contains(id))
+                    .collect::<Vec<_>>();
+                for id in unshared_ids {
+                    this.remove_guest_terminal(id, cx);
+                }
+                for update in response.terminals {
+                    this.apply_update(update, cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn apply_update(&mut self, update: proto::UpdateSharedTerminal, cx: &mut Context<Self>) {
+        let id = update.terminal_id;
+        if !self.guest.contains_key(&id) {
+            // Changed lines can't be shown without the rest of the screen.
+            if !update.snapshot {
+                return;
+            }
+            let Some(guest) = self.open_guest_terminal(id, cx).log_err() else {
+                return;
+            };
+            let terminal = guest.terminal.clone();
+            self.guest.insert(id, guest);
+            cx.emit(SharedTerminalsEvent::TerminalShared(terminal));
+        }
+        let Some(guest) = self.guest.get_mut(&id) else {
+            return;
+        };
+
+        guest.writable = update.writable;
+        apply_update_to_screen(&mut guest.screen, &update);
+        let screen = guest.screen.clone();
+        let title = if update.writable {
+            update.title
+        } else {
+            format!("{} (read-only)", update.title)
+        };
+        guest.terminal.update(cx, |terminal, cx| {
+            terminal.set_title_override(Some(title), cx);
+            terminal.show_shared_screen(screen, cx);
+        });
+    }
+
+    fn open_guest_terminal(&self, id: u64, cx: &mut Context<Self>) -> Result<GuestTerminal> {
+        let path_style = self
+            .project
+            .upgrade()
+            .context("project was dropped")?
+            .read(cx)
+            .path_style(cx);
+        let (input_tx, mut input_rx) = mpsc::unbounded();
+        let builder = TerminalBuilder::new_shared_by_host(
+            input_tx,
+            TerminalSettings::get_global(cx).cursor_shape,
+            0,
+            cx.background_executor(),
+            path_style,
+        )?;
+        let terminal = cx.new(|cx| builder.subscribe(cx));
+        let forward_input = cx.spawn(async move |this, cx| {
+            while let Some(input) = input_rx.next().await {
+                let Ok(request) = this.update(cx, |this, cx| this.forward_input(id, input, cx))
+                else {
+                    break;
+                };
+                if let Some(request) = request {
+                    request.await.log_err();
+                }
+            }
+        });
+        Ok(GuestTerminal {
+            terminal,
+            screen: SharedScreen::default(),
+            writable: false,
+            _forward_input: forward_input,
+        })
+    }
+
+    /// Sends what was typed into a guest's terminal to the host, unless the terminal is
+    /// read-only.
+    fn forward_input(
+        &self,
+        id: u64,
+        input: Vec<u8>,
+        cx: &App,
+    ) -> Option<impl Future<Output = Result<proto::Ack>> + use<>> {
+        if !self.guest.get(&id)?.writable {
+            return None;
+        }
+        let project_id = self.project.upgrade()?.read(cx).remote_id()?;
+        Some(self.client.request(proto::SharedTerminalInput {
+            project_id,
+            terminal_id: id,
+            input,
+        }))
+    }
+
+    fn remove_guest_terminal(&mut self, id: u64, cx: &mut Context<Self>) {
+        if let Some(guest) = self.guest.remove(&id) {
+            cx.emit(SharedTerminalsEvent::TerminalUnshared(guest.terminal));
+        }
+    }
+
+    async fn handle_update_shared_terminal(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::UpdateSharedTerminal>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| this.apply_update(envelope.payload, cx));
+        Ok(())
+    }
+
+    async fn handle_unshare_terminal(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::UnshareTerminal>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            this.remove_guest_terminal(envelope.payload.terminal_id, cx)
+        });
+        Ok(())
+    }
+
+    async fn handle_get_shared_terminals(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetSharedTerminals>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetSharedTerminalsResponse> {
+        let project_id = envelope.payload.project_id;
+        let terminals = this.update(&mut cx, |this, cx| {
+            this.hosted
+                .iter()
+                .filter_map(|(&id, hosted)| {
+                    let terminal = hosted.terminal.upgrade()?.read(cx);
+                    let screen = terminal.shared_screen();
+                    Some(update_to_proto(
+                        project_id,
+                        id,
+                        &screen,
+                        &(0..screen.lines()).collect::<Vec<_>>(),
+                        terminal.title(false),
+                        hosted.writable,
+                        true,
+                    ))
+                })
+                .collect()
+        });
+        Ok(proto::GetSharedTerminalsResponse { terminals })
+    }
+
+    async fn handle_shared_terminal_input(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::SharedTerminalInput>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |this, cx| {
+            let hosted = this
+                .hosted
+                .get(&envelope.payload.terminal_id)
+                .context("terminal is not shared")?;
+            anyhow::ensure!(hosted.writable, "terminal is read-only");
+            let terminal = hosted.terminal.upgrade().context("terminal was closed")?;
+            terminal.update(cx, |terminal, _| terminal.input(envelope.payload.input));
+            anyhow::Ok(())
+        })?;
+        Ok(proto::Ack {})
+    }
+}
+
+fn terminal_id(terminal: &Entity<Terminal>) -> u64 {
+    terminal.entity_id().as_u64()
+}
+
+fn update_to_proto(
+    project_id: u64,
+    terminal_id: u64,
+    screen: &SharedScreen,
+    changed_lines: &[usize],
+    title: String,
+    writable: bool,
+    snapshot: bool,
+) -> proto::UpdateSharedTerminal {
+    proto::UpdateSharedTerminal {
+        project_id,
+        terminal_id,
+        title,
+        writable,
+        columns: screen.columns as u32,
+        lines: screen.lines() as u32,
+        snapshot,
+        changed_lines: changed_lines
+            .iter()
+            .map(|&line| proto::SharedTerminalLine {
+                line: line as u32,
+                cells: screen.rows[line].iter().map(cell_to_proto).collect(),
+            })
+            .collect(),
+        cursor: screen.cursor.map(point_to_proto),
+        selection: screen
+            .selection
+            .as_ref()
+            .map(|selection| proto::SharedTerminalSelection {
+                start: Some(point_to_proto(selection.start)),
+                end: Some(point_to_proto(selection.end)),
+                block: selection.is_block,
+            }),
+    }
+}
+
+fn apply_update_to_screen(screen: &mut SharedScreen, update: &proto::UpdateSharedTerminal) {
+    screen.columns = update.columns as usize;
+    screen.rows.resize_with(update.lines as usize, Vec::new);
+    if update.snapshot {
+        screen.rows.iter_mut().for_each(Vec::clear);
+    }
+    for line in &update.changed_lines {
+        if let Some(row) = screen.rows.get_mut(line.line as usize) {
+            *row = line.cells.iter().map(cell_from_proto).collect();
+        }
+    }
+    screen.cursor = update.cursor.as_ref().map(point_from_proto);
+    screen.selection = update.selection.as_ref().and_then(|selection| {
+        Some(SelectionRange {
+            start: point_from_proto(selection.start.as_ref()?),
+            end: point_from_proto(selection.end.as_ref()?),
+            is_block: selection.block,
+        })
+    });
+}
+
+fn point_to_proto(point: AlacPoint) -> proto::SharedTerminalPoint {
+    proto::SharedTerminalPoint {
+        line: point.line.0.max(0) as u32,
+        column: point.column.0 as u32,
+    }
+}
+
+fn point_from_proto(point: &proto::SharedTerminalPoint) -> AlacPoint {
+    AlacPoint::new(Line(point.line as i32), Column(point.column as usize))
+}
+
+fn cell_to_proto(cell: &Cell) -> proto::SharedTerminalCell {
+    let mut text = cell.c.to_string();
+    text.extend(cell.zerowidth().into_iter().flatten());
+    proto::SharedTerminalCell {
+        text,
+        foreground: Some(color_to_proto(cell.fg)),
+        background: Some(color_to_proto(cell.bg)),
+        flags: cell.flags.bits() as u32,
+    }
+}
+
+fn cell_from_proto(cell: &proto::SharedTerminalCell) -> Cell {
+    let mut result = Cell::default();
+    let mut chars = cell.text.chars();
+    if let Some(character) = chars.next() {
+        result.c = character;
+    }
+    for character in chars {
+        result.push_zerowidth(character);
+    }
+    if let Some(color) = cell.foreground.as_ref().and_then(color_from_proto) {
+        result.fg = color;
+    }
+    if let Some(color) = cell.background.as_ref().and_then(color_from_proto) {
+        result.bg = color;
+    }
+    result.flags = Flags::from_bits_truncate(cell.flags as u16);
+    result
+}
+
+/// Every named color, to look colors up by their index.
+const NAMED_COLORS: [NamedColor; 29] = [
+    NamedColor::Black,
+    NamedColor::Red,
+    NamedColor::Green,
+    NamedColor::Yellow,
+    NamedColor::Blue,
+    NamedColor::Magenta,
+    NamedColor::Cyan,
+    NamedColor::White,
+    NamedColor::BrightBlack,
+    NamedColor::BrightRed,
+    NamedColor::BrightGreen,
+    NamedColor::BrightYellow,
+    NamedColor::BrightBlue,
+    NamedColor::BrightMagenta,
+    NamedColor::BrightCyan,
+    NamedColor::BrightWhite,
+    NamedColor::Foreground,
+    NamedColor::Background,
+    NamedColor::Cursor,
+    NamedColor::DimBlack,
+    NamedColor::DimRed,
+    NamedColor::DimGreen,
+    NamedColor::DimYellow,
+    NamedColor::DimBlue,
+    NamedColor::DimMagenta,
+    NamedColor::DimCyan,
+    NamedColor::DimWhite,
+    NamedColor::BrightForeground,
+    NamedColor::DimForeground,
+];
+
+fn color_to_proto(color: Color) -> proto::TerminalColor {
+    let color = match color {
+        Color::Named(named) => proto::terminal_color::Color::Named(named as u32),
+        Color::Indexed(index) => proto::terminal_color::Color::Indexed(index.into()),
+        Color::Spec(Rgb { r, g, b }) => proto::terminal_color::Color::Rgb(
+            (u32::from(r) << 16) | (u32::from(g) << 8) | u32::from(b),
+        ),
+    };
+    proto::TerminalColor { color: Some(color) }
+}
+
+fn color_from_proto(color: &proto::TerminalColor) -> Option<Color> {
+    Some(match *color.color.as_ref()? {
+        proto::terminal_color::Color::Named(index) => Color::Named(
+            NAMED_COLORS
+                .iter()
+                .copied()
+                .find(|named| *named as u32 == index)?,
+        ),
+        proto::terminal_color::Color::Indexed(index) => Color::Indexed(index.try_into().ok()?),
+        proto::terminal_color::Color::Rgb(rgb) => Color::Spec(Rgb {
+            r: (rgb >> 16) as u8,
+            g: (rgb >> 8) as u8,
+            b: rgb as u8,
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cells_round_trip_through_proto() {
+        let mut cell = Cell::default();
+        cell.c = 'e';
+        cell.push_zerowidth('\u{301}');
+        cell.fg = Color::Spec(Rgb {
+            r: 0x12,
+            g: 0x34,
+            b: 0x56,
+        });
+        cell.bg = Color::Named(NamedColor::DimForeground);
+        cell.flags = Flags::BOLD | Flags::UNDERLINE;
+        assert_eq!(cell_from_proto(&cell_to_proto(&cell)), cell);
+
+        let mut cell = Cell::default();
+        cell.fg = Color::Indexed(200);
+        assert_eq!(cell_from_proto(&cell_to_proto(&cell)), cell);
+    }
+
+    #[test]
+    fn test_updates_apply_changed_lines() {
+        let row = |text: &str| {
+            text.chars()
+                .map(|character| {
+                    let mut cell = Cell::default();
+                    cell.c = character;
+                    cell
+                })
+                .collect::<Vec<_>>()
+        };
+        let host_screen = SharedScreen {
+            columns: 10,
+            rows: vec![row("one"), row("two"), Vec::new()],
+            cursor: Some(AlacPoint::new(Line(1), Column(3))),
+            selection: None,
+        };
+        let mut guest_screen = SharedScreen::default();
+        apply_update_to_screen(
+            &mut guest_screen,
+            &update_to_proto(1, 2, &host_screen, &[0, 1, 2], "sh".into(), false, true),
+        );
+        assert_eq!(guest_screen, host_screen);
+
+        let changed_screen = SharedScreen {
+            rows: vec![row("one"), row("2"), row("three")],
+            cursor: Some(AlacPoint::new(Line(2), Column(5))),
+            selection: Some(SelectionRange {
+                start: AlacPoint::new(Line(0), Column(0)),
+                end: AlacPoint::new(Line(1), Column(0)),
+                is_block: false,
+            }),
+            ..host_screen.clone()
+        };
+        let changed_lines = changed_screen.changed_lines(&host_screen);
+        assert_eq!(changed_lines, [1, 2]);
+        apply_update_to_screen(
+            &mut guest_screen,
+            &update_to_proto(
+                1,
+                2,
+                &changed_screen,
+                &changed_lines,
+                "sh".into(),
+                false,
+                false,
+            ),
+        );
+        assert_eq!(guest_screen, changed_screen);
+    }
+}
@@ -1,12 +1,12 @@
 use editor::{CursorLayout, EditorSettings, HighlightedRange, HighlightedRangeLine};
 use gpui::{
-    AbsoluteLength, AnyElement, App, AvailableSpace, Bounds, ContentMask, Context, DispatchPhase,
-    Element, ElementId, Entity, FocusHandle, Font, FontFeatures, FontStyle, FontWeight,
-    GlobalElementId, HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement, Interactivity,
-    IntoElement, LayoutId, Length, ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels,
-    Point, StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun, TextStyle,
-    UTF16Selection, UnderlineStyle, WeakEntity, WhiteSpace, Window, div, fill, point, px, relative,
-    size,
+    AbsoluteLength, AnyElement, App, AvailableSpace, Bounds, ContentMask, Context, Corners,
+    DispatchPhase, Element, ElementId, Entity, FocusHandle, Font, FontFeatures, FontStyle,
+    FontWeight, GlobalElementId, HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement,
+    Interactivity, IntoElement, LayoutId, Length, ModifiersChangedEvent, MouseButton,
+    MouseMoveEvent, Pixels, Point, ShapedLine, StatefulInteractiveElement, StrikethroughStyle,
+    Styled, TextRun, TextStyle, UTF16Selection, UnderlineStyle, WeakEntity, WhiteSpace, Window,
+    div, fill, point, px, relative, size,
 };
 use itertools::Itertools;
 use language::CursorShape;
@@ -53,6 +53,10 @@ pub struct LayoutState {
     block_below_cursor_element: Option<AnyElement>,
     base_text_style: TextStyle,
     content_mode: ContentMode,
+    command_block_markers: Vec<(RangeInclusive<i32>, Hsla)>,
+    /// The rows of folded command output, with a label for the lines hidden below them.
+    fold_markers: Vec<(i32, ShapedLine)>,
+    images: Vec<(i32, ImagePlacement)>,
 }
 
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
@@ -1023,6 +1027,15 @@ impl Element for TerminalElement {
                     element
                 });
 
+                // Release the textures of images the terminal no longer shows.
+                for image in self
+                    .terminal
+                    .update(cx, |terminal, _| terminal.take_evicted_images())
+                {
+                    window.drop_image(image).log_err();
+                }
+
+                let last_content = &self.terminal.read(cx).last_content;
                 let TerminalContent {
                     cells,
                     mode,
@@ -1031,24 +1044,72 @@ impl Element for TerminalElement {
                     selection,
                     cursor,
                     ..
-                } = &self.terminal.read(cx).last_content;
+                } = last_content;
                 let mode = *mode;
                 let display_offset = *display_offset;
 
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
                 for search_match in search_matches {
-                    relative_highlighted_ranges.push((search_match, match_color))
+                    if let Some(range) = last_content.fold_range(&search_match) {
+                        relative_highlighted_ranges.push((range, match_color))
+                    }
                 }
-                if let Some(selection) = selection {
-                    relative_highlighted_ranges
-                        .push((selection.start..=selection.end, player_color.selection));
+                if let Some(selection) = selection
+                    && let Some(range) = last_content.fold_range(&(selection.start..=selection.end))
+                {
+                    relative_highlighted_ranges.push((range, player_color.selection));
                 }
 
                 // then have that representation be converted to the appropriate highlight data structure
 
                 let content_mode = self.terminal_view.read(cx).content_mode(window, cx);
 
+                let command_block_markers = if content_mode.is_scrollable() {
+                    let status_colors = theme.status();
+                    self.terminal
+                        .read(cx)
+                        .visible_command_blocks()
+                        .into_iter()
+                        .map(|(lines, status)| {
+                            let color = match status {
+                                CommandStatus::Running => status_colors.info,
+                                CommandStatus::Succeeded => status_colors.success,
+                                CommandStatus::Failed(_) => status_colors.error,
+                                CommandStatus::Unknown => status_colors.hint,
+                            };
+                            (lines, color)
+                        })
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+
+                let fold_markers = self
+                    .terminal
+                    .read(cx)
+                    .visible_folds()
+                    .into_iter()
+                    .map(|(row, hidden_lines)| {
+                        let label = format!("⋯ {hidden_lines} folded lines");
+                        let len = label.len();
+                        let label = window.text_system().shape_line(
+                            label.into(),
+                            text_style.font_size.to_pixels(window.rem_size()),
+                            &[TextRun {
+                                len,
+                                font: text_style.font(),
+                                color: theme.colors().text_muted,
+                                ..Default::default()
+                            }],
+                            None,
+                        );
+                        (row, label)
+                    })
+                    .collect();
+
+                let images = self.terminal.read(cx).visible_images();
+
                 // Calculate the intersection of the terminal's bounds with the current
                 // content mask (the visible viewport after all parent clipping).
                 // This allows us to only render cells that are actually visible, which is
@@ -1117,7 +1178,10 @@ impl Element for TerminalElement {
 
                 // Layout cursor. Rectangle is used for IME, so we should lay it out even
                 // if we don't end up showing it.
-                let cursor_point = DisplayCursor::from(cursor.point, display_offset);
+                let cursor_point = last_content
+                    .fold_range(&(cursor.point..=cursor.point))
+                    .map_or(cursor.point, |range| *range.start());
+                let cursor_point = DisplayCursor::from(cursor_point, display_offset);
                 let cursor_text = {
                     let str_trxt = cursor_char.to_string();
                     let len = str_trxt.len();
@@ -1177,7 +1241,12 @@ impl Element for TerminalElement {
                 let block_below_cursor_element = if let Some(block) = &self.block_below_cursor {
                     let terminal = self.terminal.read(cx);
                     if terminal.last_content.display_offset == 0 {
-                        let target_line = terminal.last_content.cursor.point.line.0 + 1;
+                        let cursor_line = terminal.last_content.cursor.point.line;
+                        let target_line = terminal
+                            .last_content
+                            .row_for_line(cursor_line)
+                            .unwrap_or(cursor_line.0)
+                            + 1;
                         let render = &block.render;
                         let mut block_cx = BlockContext {
                             window,
@@ -1222,6 +1291,9 @@ impl Element for TerminalElement {
                     block_below_cursor_element,
                     base_text_style: text_style,
                     content_mode,
+                    command_block_markers,
+                    fold_markers,
+                    images,
                 }
             },
         )
@@ -1302,6 +1374,27 @@ impl Element for TerminalElement {
                         rect.paint(origin, &layout.dimensions, window);
                     }
 
+                    for (line, placement) in &layout.images {
+                        paint_image(placement, *line, origin, &layout.dimensions, window);
+                    }
+
+                    // Command block markers live in the gutter, left of the grid.
+                    let marker_width = (layout.gutter / 3.).max(px(2.));
+                    for (lines, color) in &layout.command_block_markers {
+                        let line_height = layout.dimensions.line_height;
+                        let marker_bounds = Bounds::new(
+                            point(
+                                bounds.origin.x + (layout.gutter - marker_width) / 2.,
+                                origin.y + *lines.start() as f32 * line_height,
+                            ),
+                            size(
+                                marker_width,
+                                (lines.end() - lines.start() + 1) as f32 * line_height,
+                            ),
+                        );
+                        window.paint_quad(fill(marker_bounds, color.opacity(0.8)));
+                    }
+
                     for (relative_highlighted_range, color) in &layout.relative_highlighted_ranges {
                         if let Some((start_y, highlighted_range_lines)) =
                             to_highlighted_range_lines(relative_highlighted_range, layout, origin)
@@ -1330,6 +1423,33 @@ impl Element for TerminalElement {
                     }
                     let text_paint_time = text_paint_start.elapsed();
 
+                    // Fold labels sit at the right edge of the output line left shown.
+                    for (row, label) in &layout.fold_markers {
+                        let line_height = layout.dimensions.line_height;
+                        let label_origin = point(
+                            origin.x + layout.dimensions.width()
+                                - label.width
+                                - layout.dimensions.cell_width,
+                            origin.y + *row as f32 * line_height,
+                        );
+                        let label_bounds = Bounds::new(
+                            label_origin - point(layout.dimensions.cell_width / 2., px(0.)),
+                            size(label.width + layout.dimensions.cell_width, line_height),
+                        );
+                        window
+                            .paint_quad(fill(label_bounds, cx.theme().colors().element_background));
+                        label
+                            .paint(
+                                label_origin,
+                                line_height,
+                                gpui::TextAlign::Left,
+                                None,
+                                window,
+                                cx,
+                            )
+                            .log_err();
+                    }
+
                     if let Some(text_to_mark) = &marked_text_cloned
                         && !text_to_mark.is_empty()
                         && let Some(ime_bounds) = layout.ime_cursor_bounds
@@ -1552,6 +1672,50 @@ pub fn is_blank(cell: &IndexedCell) -> bool {
     true
 }
 
+/// Paints an image at a viewport line, scaled to the cells it covers.
+fn paint_image(
+    placement: &ImagePlacement,
+    line: i32,
+    origin: Point<Pixels>,
+    dimensions: &TerminalBounds,
+    window: &mut Window,
+) {
+    let cell_bounds = Bounds::new(
+        point(
+            origin.x + placement.column as f32 * dimensions.cell_width,
+            origin.y + line as f32 * dimensions.line_height,
+        ),
+        size(
+            placement.columns as f32 * dimensions.cell_width,
+            placement.rows as f32 * dimensions.line_height,
+        ),
+    );
+    let image = &placement.image;
+    let image_bounds = if placement.stretch {
+        cell_bounds
+    } else {
+        // The cells round the image's size up, so keep its aspect ratio within them.
+        let scale = (f32::from(cell_bounds.size.width) / image.width as f32)
+            .min(f32::from(cell_bounds.size.height) / image.height as f32);
+        Bounds::new(
+            cell_bounds.origin,
+            size(
+                px(image.width as f32 * scale),
+                px(image.height as f32 * scale),
+            ),
+        )
+    };
+    window
+        .paint_image(
+            image_bounds,
+            Corners::default(),
+            image.data.clone(),
+            0,
+            false,
+        )
+        .log_err();
+}
+
 fn to_highlighted_range_lines(
     range: &RangeInclusive<AlacPoint>,
     layout: &LayoutState,
@@ -2336,4 +2500,97 @@ mod tests {
         assert_eq!(negative_filtered.first().unwrap().point.line, Line(-7));
         assert_eq!(negative_filtered.last().unwrap().point.line, Line(-4));
     }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    fn test_terminal_renders_truecolor_backgrounds() {
+        use gpui::{AppContext as _, VisualTestAppContext};
+        use terminal::{
+            TerminalBuilder,
+            terminal_settings::{AlternateScroll, CursorShape},
+        };
+        use util::paths::PathStyle;
+
+        let mut cx = VisualTestAppContext::new();
+        cx.update(|cx| {
+            workspace::AppState::test(cx);
+        });
+
+        let terminal = cx.new(|cx| {
+            TerminalBuilder::new_display_only(
+                CursorShape::default(),
+                AlternateScroll::On,
+                None,
+                0,
+                cx.background_executor(),
+                PathStyle::local(),
+            )
+            .expect("Failed to create display-only terminal")
+            .subscribe(cx)
+        });
+        let window = cx
+            .open_offscreen_window(size(px(480.), px(160.)), |window, cx| {
+                cx.new(|cx| {
+                    TerminalView::new(
+                        terminal.clone(),
+                        WeakEntity::new_invalid(),
+                        None,
+                        WeakEntity::new_invalid(),
+                        window,
+                        cx,
+                    )
+                })
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        terminal.update(&mut cx, |terminal, cx| {
+            terminal.write_output(b"\x1b[48;2;255;0;0m          \x1b[0m\n", cx);
+        });
+        cx.run_until_parked();
+
+        let screenshot = cx.capture_screenshot(window.into()).unwrap();
+        let red_pixels = screenshot
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0 == [255, 0, 0, 255])
+            .map(|(x, y, _)| (x, y))
+            .collect::<Vec<_>>();
+        assert!(!red_pixels.is_empty(), "no cell was painted red");
+
+        let (min_x, max_x) = red_pixels
+            .iter()
+            .map(|(x, _)| *x)
+            .minmax()
+            .into_option()
+            .unwrap();
+        let (min_y, max_y) = red_pixels
+            .iter()
+            .map(|(_, y)| *y)
+            .minmax()
+            .into_option()
+            .unwrap();
+        // Ten cells on a single line should paint a strip in the top-left corner.
+        assert!(
+            max_x - min_x > 3 * (max_y - min_y),
+            "red area spans {min_x}..={max_x} x {min_y}..={max_y}"
+        );
+        assert!(min_y < screenshot.height() / 2);
+        assert_ne!(
+            screenshot
+                .get_pixel(screenshot.width() - 1, screenshot.height() - 1)
+                .0,
+            [255, 0, 0, 255]
+        );
+
+        // Compared loosely, so that glyph anti-aliasing changing with the font or its rasterizer
+        // doesn't fail the test. Run with `UPDATE_BASELINES=1` to write a new golden.
+        gpui::test::assert_or_update_baseline(
+            &screenshot,
+            &std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("test_data/truecolor_backgrounds.png"),
+            0.01,
+            16,
+        )
+        .unwrap();
+    }
 }
@@ -5,6 +5,7 @@ use crate::{
     persistence::{
         SerializedItems, SerializedTerminalPanel, deserialize_terminal_panel, serialize_pane_group,
     },
+    shared_terminals::{SharedTerminals, SharedTerminalsEvent},
     ssh_connect_modal::SshConnectModal,
 };
 use breadcrumbs::Breadcrumbs;
@@ -14,7 +15,7 @@ use futures::{channel::oneshot, future::join_all};
 use gpui::{
     Action, AnyView, App, AsyncApp, AsyncWindowContext, Context, Corner, Entity, EventEmitter,
     ExternalPaths, FocusHandle, Focusable, IntoElement, ParentElement, Pixels, Render, Styled,
-    Task, WeakEntity, Window, actions,
+    Subscription, Task, WeakEntity, Window, actions,
 };
 use itertools::Itertools;
 use project::{Fs, Project, ProjectEntryId};
@@ -31,8 +32,9 @@ use workspace::{
     ActivateNextPane, ActivatePane, ActivatePaneDown, ActivatePaneLeft, ActivatePaneRight,
     ActivatePaneUp, ActivatePreviousPane, ConnectSsh, DraggedSelection, DraggedTab, ItemId,
     MoveItemToPane, MoveItemToPaneInDirection, MovePaneDown, MovePaneLeft, MovePaneRight,
-    MovePaneUp, Pane, PaneGroup, SplitDirection, SplitDown, SplitLeft, SplitMode, SplitRight,
-    SplitUp, SwapPaneDown, SwapPaneLeft, SwapPaneRight, SwapPaneUp, ToggleZoom, Workspace,
+    MovePaneUp, Pane, PaneGroup, SaveIntent, SplitDirection, SplitDown, SplitLeft, SplitMode,
+    SplitRight, SplitUp, SwapPaneDown, SwapPaneLeft, SwapPaneRight, SwapPaneUp, ToggleZoom,
+    Workspace,
     dock::{DockPosition, Panel, PanelEvent, PanelHandle},
     item::SerializableItem,
     move_active_item, move_item, pane,
@@ -86,7 +88,7 @@ pub struct TerminalPanel {
     pub(crate) active_pane: Entity<Pane>,
     pub(crate) center: PaneGroup,
     fs: Arc<dyn Fs>,
-    workspace: WeakEntity<Workspace>,
+    pub(crate) workspace: WeakEntity<Workspace>,
     pub(crate) width: Option<Pixels>,
     pub(crate) height: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
@@ -95,6 +97,8 @@ pub struct TerminalPanel {
     assistant_enabled: bool,
     assistant_tab_bar_button: Option<AnyView>,
     active: bool,
+    shared_terminals: Entity<SharedTerminals>,
+    _shared_terminals_subscription: Subscription,
 }
 
 impl TerminalPanel {
@@ -102,6 +106,12 @@ impl TerminalPanel {
         let project = workspace.project();
         let pane = new_terminal_pane(workspace.weak_handle(), project.clone(), false, window, cx);
         let center = PaneGroup::new(pane.clone());
+        let shared_terminals = cx.new(|cx| SharedTerminals::new(project.clone(), cx));
+        let shared_terminals_subscription = cx.subscribe_in(
+            &shared_terminals,
+            window,
+            Self::handle_shared_terminals_event,
+        );
         let terminal_panel = Self {
             center,
             active_pane: pane,
@@ -115,11 +125,65 @@ impl TerminalPanel {
             assistant_enabled: false,
             assistant_tab_bar_button: None,
             active: false,
+            shared_terminals,
+            _shared_terminals_subscription: shared_terminals_subscription,
         };
         terminal_panel.apply_tab_bar_buttons(&terminal_panel.active_pane, cx);
         terminal_panel
     }
 
+    /// The terminals shared with, or by, collaborators in the project.
+    pub fn shared_terminals(&self) -> &Entity<SharedTerminals> {
+        &self.shared_terminals
+    }
+
+    fn handle_shared_terminals_event(
+        &mut self,
+        _: &Entity<SharedTerminals>,
+        event: &SharedTerminalsEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            SharedTerminalsEvent::TerminalShared(terminal) => {
+                let Some(workspace) = self.workspace.upgrade() else {
+                    return;
+                };
+                let project = workspace.read(cx).project().downgrade();
+                let terminal_view = Box::new(cx.new(|cx| {
+                    TerminalView::new(
+                        terminal.clone(),
+                        self.workspace.clone(),
+                        None,
+                        project,
+                        window,
+                        cx,
+                    )
+                }));
+                self.active_pane.update(cx, |pane, cx| {
+                    pane.add_item(terminal_view, true, false, None, window, cx);
+                });
+            }
+            SharedTerminalsEvent::TerminalUnshared(terminal) => {
+                for pane in self.center.panes() {
+                    pane.update(cx, |pane, cx| {
+                        let item_id = pane
+                            .items()
+                            .find(|item| {
+                                item.downcast::<TerminalView>()
+                                    .is_some_and(|view| view.read(cx).terminal() == terminal)
+                            })
+                            .map(|item| item.item_id());
+                        if let Some(item_id) = item_id {
+                            pane.close_item_by_id(item_id, SaveIntent::Skip, window, cx)
+                                .detach_and_log_err(cx);
+                        }
+                    });
+                }
+            }
+        }
+    }
+
     pub fn set_assistant_enabled(&mut self, enabled: bool, cx: &mut Context<Self>) {
         self.assistant_enabled = enabled;
         if enabled {
@@ -362,6 +426,10 @@ impl TerminalPanel {
             pane::Event::ActivateItem { .. } => self.serialize(cx),
             pane::Event::RemovedItem { .. } => self.serialize(cx),
             pane::Event::Remove { focus_on_pane } => {
+                // Panes replaced by opening a layout are no longer in the center.
+                if !self.center.panes().contains(&pane) {
+                    return;
+                }
                 let pane_count_before_removal = self.center.panes().len();
                 let _removal_result = self.center.remove(pane, cx);
                 if pane_count_before_removal == 1 {
@@ -942,7 +1010,7 @@ impl TerminalPanel {
         })
     }
 
-    fn serialize(&mut self, cx: &mut Context<Self>) {
+    pub(crate) fn serialize(&mut self, cx: &mut Context<Self>) {
         let height = self.height;
         let width = self.width;
         let Some(serialization_key) = self
@@ -1107,6 +1175,19 @@ impl TerminalPanel {
         self.center.panes()
     }
 
+    /// Returns the terminal views in all panes.
+    pub fn terminal_views(&self, cx: &App) -> Vec<Entity<crate::TerminalView>> {
+        self.center
+            .panes()
+            .iter()
+            .flat_map(|pane| {
+                pane.read(cx)
+                    .items()
+                    .filter_map(|item| item.downcast::<crate::TerminalView>())
+            })
+            .collect()
+    }
+
     /// Returns all non-empty terminal selections from all terminal views in all panes.
     pub fn terminal_selections(&self, cx: &App) -> Vec<String> {
         self.center
@@ -1856,7 +1937,7 @@ impl Render for InlineAssistTabBarButton {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use std::num::NonZero;
 
     use super::*;
@@ -1,4 +1,7 @@
+mod cast_player;
 mod persistence;
+mod session_layouts;
+pub mod shared_terminals;
 mod ssh_connect_modal;
 pub mod terminal_element;
 pub mod terminal_panel;
@@ -7,12 +10,13 @@ pub mod terminal_scrollbar;
 mod terminal_slash_command;
 
 use assistant_slash_command::SlashCommandRegistry;
+use cast_player::CastPlayer;
 use editor::{Editor, EditorSettings, actions::SelectAll, blink_manager::BlinkManager};
 use gpui::{
-    Action, AnyElement, App, ClipboardEntry, DismissEvent, Entity, EventEmitter, FocusHandle,
-    Focusable, KeyContext, KeyDownEvent, Keystroke, MouseButton, MouseDownEvent, Pixels, Point,
-    Render, ScrollWheelEvent, Styled, Subscription, Task, WeakEntity, actions, anchored, deferred,
-    div,
+    Action, AnyElement, App, ClipboardEntry, ClipboardItem, DismissEvent, Entity, EventEmitter,
+    FocusHandle, Focusable, KeyContext, KeyDownEvent, Keystroke, MouseButton, MouseDownEvent,
+    PathPromptOptions, Pixels, Point, PromptLevel, Render, ScrollWheelEvent, Styled, Subscription,
+    Task, WeakEntity, actions, anchored, deferred, div,
 };
 use menu;
 use persistence::TERMINAL_DB;
@@ -20,6 +24,7 @@ use project::{Project, search::SearchQuery};
 use schemars::JsonSchema;
 use serde::Deserialize;
 use settings::{Settings, SettingsStore, TerminalBlink, WorkingDirectory};
+use shared_terminals::SharedTerminals;
 use std::{
     cmp,
     ops::{Range, RangeInclusive},
@@ -30,13 +35,16 @@ use std::{
 };
 use task::TaskId;
 use terminal::{
-    Clear, Copy, Event, HoveredWord, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp,
-    ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskState,
-    TaskStatus, Terminal, TerminalBounds, ToggleViMode,
+    Clear, Copy, CopyCommandOutput, Event, HoveredWord, MaybeNavigationTarget, Paste,
+    ScrollLineDown, ScrollLineUp, ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToNextPrompt,
+    ScrollToPreviousPrompt, ScrollToTop, SelectCommandOutput, ShowCharacterPalette, TaskState,
+    TaskStatus, Terminal, TerminalBounds, ToggleCommandOutputFold, ToggleViMode,
     alacritty_terminal::{
         index::Point as AlacPoint,
         term::{TermMode, point_to_viewport, search::RegexSearch},
     },
+    file_transfer::{TransferDirection, TransferProgress, XmodemVariant},
+    remote_terminals::OpenRemoteTerminals,
     terminal_settings::{CursorShape, TerminalSettings},
 };
 use terminal_element::TerminalElement;
@@ -45,17 +53,18 @@ use terminal_path_like_target::{hover_path_like_target, open_path_like_target};
 use terminal_scrollbar::TerminalScrollHandle;
 use terminal_slash_command::TerminalSlashCommand;
 use ui::{
-    ContextMenu, Divider, ScrollAxes, Scrollbars, Tooltip, WithScrollbar,
+    ContextMenu, Divider, Indicator, ProgressBar, ScrollAxes, Scrollbars, Tooltip, WithScrollbar,
     prelude::*,
     scrollbars::{self, GlobalSetting, ScrollbarVisibility},
 };
-use util::ResultExt;
+use util::{ResultExt, size::format_file_size};
 use workspace::{
-    CloseActiveItem, NewCenterTerminal, NewTerminal, ToolbarItemLocation, Workspace, WorkspaceId,
-    delete_unloaded_items,
+    CloseActiveItem, NewCenterTerminal, NewTerminal, Toast, ToolbarItemLocation, Workspace,
+    WorkspaceId, delete_unloaded_items,
     item::{
         BreadcrumbText, Item, ItemEvent, SerializableItem, TabContentParams, TabTooltipContent,
     },
+    notifications::NotificationId,
     register_serializable_item,
     searchable::{
         Direction, SearchEvent, SearchOptions, SearchToken, SearchableItem, SearchableItemHandle,
@@ -90,9 +99,32 @@ actions!(
         RerunTask,
         /// Reconnects a disconnected SSH/Telnet terminal.
         ReconnectTerminal,
+        /// Sends a file to an XMODEM receiver, such as a bootloader.
+        SendFileWithXmodem,
+        /// Sends a file to an XMODEM-1K receiver.
+        SendFileWithXmodem1k,
+        /// Sends a file to a YMODEM receiver.
+        SendFileWithYmodem,
+        /// Cancels the running ZMODEM, XMODEM or YMODEM transfer.
+        CancelFileTransfer,
+        /// Shares the terminal with collaborators in the call, or stops sharing it.
+        ToggleShareTerminal,
+        /// Lets collaborators type into the shared terminal, or makes it read-only for them.
+        ToggleSharedTerminalInput,
     ]
 );
 
+/// Starts recording the terminal session to an asciicast file, or stops the
+/// recording in progress.
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema, PartialEq, Action)]
+#[action(namespace = terminal)]
+#[serde(deny_unknown_fields)]
+pub struct ToggleRecording {
+    /// Whether to also record what is typed, e.g. to show keystrokes on playback.
+    #[serde(default)]
+    pub include_input: bool,
+}
+
 /// Renames the terminal tab.
 #[derive(Clone, Debug, Default, Deserialize, JsonSchema, PartialEq, Action)]
 #[action(namespace = terminal)]
@@ -103,6 +135,7 @@ pub fn init(cx: &mut App) {
     terminal_panel::init(cx);
 
     register_serializable_item::<TerminalView>(cx);
+    workspace::register_project_item::<CastPlayer>(cx);
 
     cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
         workspace.register_action(TerminalView::deploy);
@@ -234,6 +267,7 @@ impl TerminalView {
         let workspace_handle = workspace.clone();
         let terminal_subscriptions =
             subscribe_for_terminal_events(&terminal, workspace, window, cx);
+        OpenRemoteTerminals::register(&terminal, cx);
 
         let focus_handle = cx.focus_handle();
         let focus_in = cx.on_focus_in(&focus_handle, window, |terminal_view, window, cx| {
@@ -509,6 +543,21 @@ impl TerminalView {
             .selection_text
             .as_ref()
             .is_some_and(|text| !text.is_empty());
+        let has_command_blocks = !self.terminal.read(cx).command_blocks().is_empty();
+        let is_connected = self.terminal.read(cx).is_connected();
+        let transferring_file = self.terminal.read(cx).file_transfer().is_some();
+        let recording = self.terminal.read(cx).is_recording();
+        let (can_share, shared, writable) = self
+            .shared_terminals(cx)
+            .map(|shared_terminals| {
+                let shared_terminals = shared_terminals.read(cx);
+                (
+                    shared_terminals.can_share(cx),
+                    shared_terminals.is_shared(&self.terminal),
+                    shared_terminals.is_writable(&self.terminal),
+                )
+            })
+            .unwrap_or_default();
         let context_menu = ContextMenu::build(window, cx, |menu, _, _| {
             menu.context(self.focus_handle.clone())
                 .action("New Terminal", Box::new(NewTerminal::default()))
@@ -517,6 +566,54 @@ impl TerminalView {
                 .action("Paste", Box::new(Paste))
                 .action("Select All", Box::new(SelectAll))
                 .action("Clear", Box::new(Clear))
+                .when(has_command_blocks, |menu| {
+                    menu.separator()
+                        .action("Previous Prompt", Box::new(ScrollToPreviousPrompt))
+                        .action("Next Prompt", Box::new(ScrollToNextPrompt))
+                        .action("Select Command Output", Box::new(SelectCommandOutput))
+                        .action("Copy Command Output", Box::new(CopyCommandOutput))
+                        .action("Fold Command Output", Box::new(ToggleCommandOutputFold))
+                })
+                .when(transferring_file, |menu| {
+                    menu.separator()
+                        .action("Cancel File Transfer", Box::new(CancelFileTransfer))
+                })
+                .when(is_connected && !transferring_file, |menu| {
+                    menu.separator()
+                        .action("Send File with XMODEM", Box::new(SendFileWithXmodem))
+                        .action("Send File with XMODEM-1K", Box::new(SendFileWithXmodem1k))
+                        .action("Send File with YMODEM", Box::new(SendFileWithYmodem))
+                })
+                .when(recording, |menu| {
+                    menu.separator()
+                        .action("Stop Recording", Box::new(ToggleRecording::default()))
+                })
+                .when(is_connected && !recording, |menu| {
+                    menu.separator()
+                        .action("Start Recording", Box::new(ToggleRecording::default()))
+                        .action(
+                            "Start Recording with Input",
+                            Box::new(ToggleRecording {
+                                include_input: true,
+                            }),
+                        )
+                })
+                .when(shared, |menu| {
+                    menu.separator()
+                        .action("Stop Sharing Terminal", Box::new(ToggleShareTerminal))
+                        .action(
+                            if writable {
+                                "Make Read-Only for Collaborators"
+                            } else {
+                                "Let Collaborators Type"
+                            },
+                            Box::new(ToggleSharedTerminalInput),
+                        )
+                })
+                .when(can_share && !shared, |menu| {
+                    menu.separator()
+                        .action("Share Terminal", Box::new(ToggleShareTerminal))
+                })
                 .when(assistant_enabled, |menu| {
                     menu.separator()
                         .action("Inline Assist", Box::new(InlineAssist::default()))
@@ -751,6 +848,345 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(
+        &mut self,
+        _: &ScrollToNextPrompt,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn select_command_output(
+        &mut self,
+        _: &SelectCommandOutput,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal.update(cx, |term, _| {
+            if let Some(index) = term.focused_command_block() {
+                term.select_command_output(index);
+            }
+        });
+        cx.notify();
+    }
+
+    fn copy_command_output(
+        &mut self,
+        _: &CopyCommandOutput,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let terminal = self.terminal.read(cx);
+        if let Some(text) = terminal
+            .focused_command_block()
+            .and_then(|index| terminal.command_output_text(index))
+        {
+            cx.write_to_clipboard(ClipboardItem::new_string(text));
+        }
+    }
+
+    fn toggle_command_output_fold(
+        &mut self,
+        _: &ToggleCommandOutputFold,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal.update(cx, |term, _| {
+            if let Some(index) = term.focused_command_block() {
+                term.toggle_command_output_fold(index);
+            }
+        });
+        cx.notify();
+    }
+
+    fn send_file_with_xmodem(
+        &mut self,
+        variant: XmodemVariant,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some(format!("Send with {}", variant.protocol().label()).into()),
+        });
+        let terminal = self.terminal.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            let Some(path) = paths.await??.and_then(|paths| paths.into_iter().next()) else {
+                return anyhow::Ok(());
+            };
+            let result = terminal.update(cx, |terminal, cx| {
+                terminal.start_xmodem_send(path, variant, cx)
+            })?;
+            if let Err(error) = result {
+                workspace.update(cx, |workspace, cx| {
+                    show_file_transfer_toast(workspace, format!("Can't send file: {error}"), cx)
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn cancel_file_transfer(
+        &mut self,
+        _: &CancelFileTransfer,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |terminal, cx| terminal.cancel_file_transfer(cx));
+    }
+
+    fn toggle_recording(
+        &mut self,
+        action: &ToggleRecording,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.terminal.read(cx).is_recording() {
+            let result = self
+                .terminal
+                .update(cx, |terminal, _| terminal.stop_recording());
+            let message = match result {
+                Ok(Some(path)) => format!("Recording saved to {}", path.display()),
+                Ok(None) => return,
+                Err(error) => format!("Failed to save recording: {error:#}"),
+            };
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    show_recording_toast(workspace, message, cx)
+                })
+                .ok();
+            cx.notify();
+            return;
+        }
+
+        let directory = self
+            .workspace
+            .upgrade()
+            .and_then(|workspace| first_project_directory(workspace.read(cx), cx))
+            .or_else(dirs::home_dir)
+            .unwrap_or_default();
+        let path = cx.prompt_for_new_path(&directory, Some("session.cast"));
+        let include_input = action.include_input;
+        let terminal = self.terminal.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let Some(path) = path.await?? else {
+                return anyhow::Ok(());
+            };
+            let result = terminal.update(cx, |terminal, _| {
+                terminal.start_recording(&path, include_input)
+            })?;
+            if let Err(error) = result {
+                workspace.update(cx, |workspace, cx| {
+                    show_recording_toast(workspace, format!("Can't record: {error:#}"), cx)
+                })?;
+            }
+            this.update(cx, |_, cx| cx.notify())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn shared_terminals(&self, cx: &App) -> Option<Entity<SharedTerminals>> {
+        let terminal_panel = self
+            .workspace
+            .upgrade()?
+            .read(cx)
+            .panel::<TerminalPanel>(cx)?;
+        Some(terminal_panel.read(cx).shared_terminals().clone())
+    }
+
+    fn toggle_share_terminal(
+        &mut self,
+        _: &ToggleShareTerminal,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(shared_terminals) = self.shared_terminals(cx) else {
+            return;
+        };
+        let result = shared_terminals.update(cx, |shared_terminals, cx| {
+            shared_terminals.toggle_sharing(&self.terminal, cx)
+        });
+        if let Err(error) = result {
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    show_sharing_toast(workspace, format!("{error:#}"), cx)
+                })
+                .ok();
+        }
+    }
+
+    fn toggle_shared_terminal_input(
+        &mut self,
+        _: &ToggleSharedTerminalInput,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(shared_terminals) = self.shared_terminals(cx) {
+            shared_terminals.update(cx, |shared_terminals, cx| {
+                shared_terminals.toggle_writable(&self.terminal, cx)
+            });
+        }
+    }
+
+    fn render_recording_indicator(&self, cx: &Context<Self>) -> AnyElement {
+        h_flex()
+            .id("recording-indicator")
+            .absolute()
+            .top_2()
+            .right_2()
+            .px_1p5()
+            .py_0p5()
+            .gap_1()
+            .rounded_sm()
+            .elevation_2(cx)
+            .cursor_pointer()
+            .child(Indicator::dot().color(Color::Error))
+            .child(
+                Label::new("REC")
+                    .size(LabelSize::XSmall)
+                    .color(Color::Error),
+            )
+            .tooltip(Tooltip::text("Stop Recording"))
+            .on_click(cx.listener(|this, _, window, cx| {
+                this.toggle_recording(&ToggleRecording::default(), window, cx);
+            }))
+            .into_any_element()
+    }
+
+    /// Asks which files to send after the remote side ran `rz`.
+    fn prompt_for_files_to_send(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: true,
+            prompt: Some("Send with ZMODEM".into()),
+        });
+        let terminal = self.terminal.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            let paths = paths.await?.log_err().flatten().unwrap_or_default();
+            terminal.update(cx, |terminal, cx| terminal.send_files(paths, cx))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Asks whether to accept the files the remote side offered by running `sz`.
+    fn prompt_to_receive_files(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let detail = format!(
+            "They will be saved to {}.",
+            terminal::file_transfer_directory().display()
+        );
+        let answer = window.prompt(
+            PromptLevel::Info,
+            "Receive files sent with ZMODEM?",
+            Some(&detail),
+            &["Receive", "Cancel"],
+            cx,
+        );
+        let terminal = self.terminal.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            let receive = answer.await.ok() == Some(0);
+            terminal.update(cx, |terminal, cx| {
+                if receive {
+                    terminal.receive_files(cx);
+                } else {
+                    terminal.cancel_file_transfer(cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_file_transfer(&self, progress: &TransferProgress, cx: &Context<Self>) -> AnyElement {
+        let verb = match progress.direction {
+            TransferDirection::Send => "Sending",
+            TransferDirection::Receive => "Receiving",
+        };
+        let title = match &progress.file_name {
+            Some(file_name) => format!("{verb} {file_name}"),
+            None if progress.direction == TransferDirection::Send => {
+                "Choose files to send".to_string()
+            }
+            None => format!("Waiting for {}", progress.protocol.label()),
+        };
+        let amount = match progress.total_bytes {
+            Some(total) => format!(
+                "{} of {}",
+                format_file_size(progress.bytes_transferred, false),
+                format_file_size(total, false)
+            ),
+            None => format_file_size(progress.bytes_transferred, false),
+        };
+
+        v_flex()
+            .id("file-transfer")
+            .absolute()
+            .bottom_2()
+            .right_2()
+            .w_72()
+            .p_2()
+            .gap_1()
+            .elevation_2(cx)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Icon::new(match progress.direction {
+                            TransferDirection::Send => IconName::ArrowUp,
+                            TransferDirection::Receive => IconName::ArrowDown,
+                        })
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                    )
+                    .child(div().flex_1().min_w_0().child(Label::new(title).truncate()))
+                    .child(
+                        Label::new(progress.protocol.label())
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    ),
+            )
+            .when_some(progress.fraction(), |this, fraction| {
+                this.child(ProgressBar::new("file-transfer-progress", fraction, 1., cx))
+            })
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(
+                        Label::new(amount)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Button::new("cancel-file-transfer", "Cancel")
+                            .label_size(LabelSize::Small)
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.terminal
+                                    .update(cx, |terminal, cx| terminal.cancel_file_transfer(cx));
+                            })),
+                    ),
+            )
+            .into_any_element()
+    }
+
     fn toggle_vi_mode(&mut self, _: &ToggleViMode, _: &mut Window, cx: &mut Context<Self>) {
         self.terminal.update(cx, |term, _| term.toggle_vi_mode());
         cx.notify();
@@ -944,6 +1380,7 @@ impl TerminalView {
     ) {
         self._terminal_subscriptions =
             subscribe_for_terminal_events(&terminal, self.workspace.clone(), window, cx);
+        OpenRemoteTerminals::register(&terminal, cx);
         self.terminal = terminal;
     }
 
@@ -961,12 +1398,15 @@ impl TerminalView {
                     password,
                     private_key_path,
                     passphrase,
+                    certificate_path,
+                    agent_forwarding,
                     session_id,
                 } => {
                     let auth = if let Some(ref key_path) = private_key_path {
                         terminal::SshAuthConfig::PrivateKey {
                             path: key_path.clone(),
                             passphrase: passphrase.clone(),
+                            certificate: certificate_path.clone(),
                         }
                     } else if let Some(ref pwd) = password {
                         terminal::SshAuthConfig::Password(pwd.clone())
@@ -974,7 +1414,9 @@ impl TerminalView {
                         terminal::SshAuthConfig::Auto
                     };
 
-                    let mut ssh_config = terminal::SshConfig::new(host, port).with_auth(auth);
+                    let mut ssh_config = terminal::SshConfig::new(host, port)
+                        .with_auth(auth)
+                        .with_agent_forwarding(agent_forwarding);
                     if let Some(user) = username {
                         ssh_config = ssh_config.with_username(user);
                     }
@@ -1067,6 +1509,37 @@ fn terminal_rerun_override(task: &TaskId) -> zed_actions::Rerun {
     }
 }
 
+fn show_file_transfer_toast(
+    workspace: &mut Workspace,
+    message: String,
+    cx: &mut Context<Workspace>,
+) {
+    struct FileTransferToast;
+
+    workspace.show_toast(
+        Toast::new(NotificationId::unique::<FileTransferToast>(), message).autohide(),
+        cx,
+    );
+}
+
+fn show_recording_toast(workspace: &mut Workspace, message: String, cx: &mut Context<Workspace>) {
+    struct RecordingToast;
+
+    workspace.show_toast(
+        Toast::new(NotificationId::unique::<RecordingToast>(), message).autohide(),
+        cx,
+    );
+}
+
+fn show_sharing_toast(workspace: &mut Workspace, message: String, cx: &mut Context<Workspace>) {
+    struct SharingToast;
+
+    workspace.show_toast(
+        Toast::new(NotificationId::unique::<SharingToast>(), message).autohide(),
+        cx,
+    );
+}
+
 fn subscribe_for_terminal_events(
     terminal: &Entity<Terminal>,
     workspace: WeakEntity<Workspace>,
@@ -1182,6 +1655,20 @@ fn subscribe_for_terminal_events(
                     window.invalidate_character_coordinates();
                     cx.emit(SearchEvent::ActiveMatchChanged)
                 }
+                Event::FileTransferRequested => {
+                    terminal_view.prompt_for_files_to_send(window, cx);
+                }
+                Event::FileReceiveRequested => {
+                    terminal_view.prompt_to_receive_files(window, cx);
+                }
+                Event::FileTransferFinished { summary, .. } => {
+                    let summary = summary.clone();
+                    workspace
+                        .update(cx, |workspace, cx| {
+                            show_file_transfer_toast(workspace, summary, cx)
+                        })
+                        .ok();
+                }
             }
         },
     );
@@ -1316,6 +1803,11 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_page_down))
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::select_command_output))
+            .on_action(cx.listener(TerminalView::copy_command_output))
+            .on_action(cx.listener(TerminalView::toggle_command_output_fold))
             .on_action(cx.listener(TerminalView::toggle_vi_mode))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
@@ -1324,6 +1816,19 @@ impl Render for TerminalView {
             .on_action(cx.listener(|this, _: &ReconnectTerminal, window, cx| {
                 this.reconnect_terminal(window, cx);
             }))
+            .on_action(cx.listener(|this, _: &SendFileWithXmodem, window, cx| {
+                this.send_file_with_xmodem(XmodemVariant::Xmodem, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &SendFileWithXmodem1k, window, cx| {
+                this.send_file_with_xmodem(XmodemVariant::Xmodem1k, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &SendFileWithYmodem, window, cx| {
+                this.send_file_with_xmodem(XmodemVariant::Ymodem, window, cx);
+            }))
+            .on_action(cx.listener(TerminalView::cancel_file_transfer))
+            .on_action(cx.listener(TerminalView::toggle_recording))
+            .on_action(cx.listener(TerminalView::toggle_share_terminal))
+            .on_action(cx.listener(TerminalView::toggle_shared_terminal_input))
             .on_key_down(cx.listener(Self::key_down))
             .on_mouse_down(
                 MouseButton::Right,
@@ -1428,6 +1933,13 @@ impl Render for TerminalView {
                         ),
                 )
             })
+            .when_some(
+                terminal_handle.read(cx).file_transfer().cloned(),
+                |el, progress| el.child(self.render_file_transfer(&progress, cx)),
+            )
+            .when(terminal_handle.read(cx).is_recording(), |el| {
+                el.child(self.render_recording_indicator(cx))
+            })
             .children(self.context_menu.as_ref().map(|(menu, position, _)| {
                 deferred(
                     anchored()
@@ -1777,7 +2289,7 @@ impl SerializableItem for TerminalView {
         cx: &mut Context<Self>,
     ) -> Option<Task<anyhow::Result<()>>> {
         let terminal = self.terminal().read(cx);
-        if terminal.task().is_some() {
+        if terminal.task().is_some() || terminal.is_shared_by_host() {
             return None;
         }
 
@@ -1800,6 +2312,8 @@ impl SerializableItem for TerminalView {
                     password,
                     private_key_path,
                     passphrase,
+                    certificate_path,
+                    agent_forwarding,
                     session_id,
                 } => SerializableConnectionInfo::Ssh {
                     host: host.clone(),
@@ -1808,6 +2322,8 @@ impl SerializableItem for TerminalView {
                     password: password.clone(),
                     private_key_path: private_key_path.clone(),
                     passphrase: passphrase.clone(),
+                    certificate_path: certificate_path.clone(),
+                    agent_forwarding: *agent_forwarding,
                     session_id: *session_id,
                 },
                 ConnectionInfo::Telnet {
@@ -1897,6 +2413,8 @@ impl SerializableItem for TerminalView {
                         password,
                         private_key_path,
                         passphrase,
+                        certificate_path,
+                        agent_forwarding,
                         session_id,
                     } => ConnectionInfo::Ssh {
                         host,
@@ -1905,6 +2423,8 @@ impl SerializableItem for TerminalView {
                         password,
                         private_key_path,
                         passphrase,
+                        certificate_path,
+                        agent_forwarding,
                         session_id,
                     },
                     SerializableConnectionInfo::Telnet {
@@ -40,14 +40,19 @@ use workspace::{SplitDirection, notifications::DetachAndPromptErr};
 use zed_actions::{OpenDocs, RevealTarget};
 
 use crate::{
-    ToggleMarksView, ToggleRegistersView, Vim, VimSettings,
+    ToggleMarksView, ToggleRegistersView, UseSystemClipboard, Vim, VimSettings,
     motion::{EndOfDocument, Motion, MotionKind, StartOfDocument},
     normal::{
         JoinLines,
         search::{FindCommand, ReplaceCommand, Replacement},
     },
     object::Object,
+    quickfix::{
+        QuickfixClose, QuickfixDiagnostics, QuickfixDo, QuickfixFirst, QuickfixGoTo, QuickfixLast,
+        QuickfixMake, QuickfixNext, QuickfixOpen, QuickfixPrevious, QuickfixSearch, QuickfixShow,
+    },
     state::{Mark, Mode},
+    vimrc::{self, VimSource},
     visual::VisualDeleteLine,
 };
 
@@ -82,13 +87,16 @@ pub struct WithCount {
     action: WrappedAction,
 }
 
-#[derive(Clone, Deserialize, JsonSchema, PartialEq)]
+#[derive(Clone, Debug, Deserialize, JsonSchema, PartialEq)]
 pub enum VimOption {
     Wrap(bool),
     Number(bool),
     RelativeNumber(bool),
     IgnoreCase(bool),
+    SmartCase(bool),
     GDefault(bool),
+    ScrollOff(u32),
+    Clipboard(bool),
 }
 
 impl VimOption {
@@ -104,7 +112,15 @@ impl VimOption {
             }
         }
 
-        Self::possibilities(prefix)
+        let mut possibilities = Self::possibilities(prefix).collect::<Vec<_>>();
+        if possibilities.is_empty()
+            && let Some(option) = Self::from(prefix)
+        {
+            possibilities.push(option);
+        }
+
+        possibilities
+            .into_iter()
             .map(|possible| {
                 let mut options = prefix_of_options.clone();
                 options.push(possible);
@@ -135,17 +151,28 @@ impl VimOption {
             (None, VimOption::IgnoreCase(false)),
             (Some("ic"), VimOption::IgnoreCase(true)),
             (Some("noic"), VimOption::IgnoreCase(false)),
+            (None, VimOption::SmartCase(true)),
+            (None, VimOption::SmartCase(false)),
+            (Some("scs"), VimOption::SmartCase(true)),
+            (Some("noscs"), VimOption::SmartCase(false)),
             (None, VimOption::GDefault(true)),
             (Some("gd"), VimOption::GDefault(true)),
             (None, VimOption::GDefault(false)),
             (Some("nogd"), VimOption::GDefault(false)),
+            (None, VimOption::Clipboard(true)),
+            (None, VimOption::Clipboard(false)),
         ]
         .into_iter()
-        .filter(move |(prefix, option)| prefix.unwrap_or(option.to_string()).starts_with(query))
+        .filter(move |(prefix, option)| {
+            prefix
+                .map(ToOwned::to_owned)
+                .unwrap_or_else(|| option.to_string())
+                .starts_with(query)
+        })
         .map(|(_, option)| option)
     }
 
-    fn from(option: &str) -> Option<Self> {
+    pub(crate) fn from(option: &str) -> Option<Self> {
         match option {
             "wrap" => Some(Self::Wrap(true)),
             "nowrap" => Some(Self::Wrap(false)),
@@ -170,12 +197,111 @@ impl VimOption {
             "nogdefault" => Some(Self::GDefault(false)),
             "nogd" => Some(Self::GDefault(false)),
 
-            _ => None,
+            "smartcase" => Some(Self::SmartCase(true)),
+            "scs" => Some(Self::SmartCase(true)),
+            "nosmartcase" => Some(Self::SmartCase(false)),
+            "noscs" => Some(Self::SmartCase(false)),
+
+            "clipboard=unnamed" | "clipboard=unnamedplus" | "cb=unnamed" | "cb=unnamedplus" => {
+                Some(Self::Clipboard(true))
+            }
+            "clipboard=" | "cb=" => Some(Self::Clipboard(false)),
+
+            _ => {
+                let (name, value) = option.split_once('=')?;
+                match name {
+                    "scrolloff" | "so" => value.parse().ok().map(Self::ScrollOff),
+                    _ => None,
+                }
+            }
         }
     }
 
-    fn to_string(&self) -> &'static str {
-        match self {
+    /// Applies the options that Zed keeps in global settings rather than on each editor.
+    /// Settings that already have the option's value are left alone, so this is safe to
+    /// call whenever settings change.
+    pub(crate) fn override_settings(options: &[VimOption], cx: &mut App) {
+        fn assign<T: PartialEq>(field: &mut T, value: T, changed: &mut bool) {
+            if *field != value {
+                *field = value;
+                *changed = true;
+            }
+        }
+
+        let mut editor_settings = EditorSettings::get_global(cx).clone();
+        let mut vim_settings = VimSettings::get_global(cx).clone();
+        let mut editor_changed = false;
+        let mut vim_changed = false;
+        for option in options {
+            match option {
+                VimOption::Wrap(_) => {}
+                VimOption::Number(enabled) => assign(
+                    &mut editor_settings.gutter.line_numbers,
+                    *enabled,
+                    &mut editor_changed,
+                ),
+                VimOption::RelativeNumber(enabled) => assign(
+                    &mut editor_settings.relative_line_numbers,
+                    if *enabled {
+                        settings::RelativeLineNumbers::Enabled
+                    } else {
+                        settings::RelativeLineNumbers::Disabled
+                    },
+                    &mut editor_changed,
+                ),
+                VimOption::IgnoreCase(enabled) => assign(
+                    &mut editor_settings.search.case_sensitive,
+                    !*enabled,
+                    &mut editor_changed,
+                ),
+                VimOption::SmartCase(enabled) => {
+                    assign(
+                        &mut editor_settings.use_smartcase_search,
+                        *enabled,
+                        &mut editor_changed,
+                    );
+                    assign(
+                        &mut vim_settings.use_smartcase_find,
+                        *enabled,
+                        &mut vim_changed,
+                    );
+                }
+                VimOption::GDefault(enabled) => {
+                    assign(&mut vim_settings.gdefault, *enabled, &mut vim_changed)
+                }
+                VimOption::ScrollOff(lines) => assign(
+                    &mut editor_settings.vertical_scroll_margin,
+                    *lines as f64,
+                    &mut editor_changed,
+                ),
+                VimOption::Clipboard(enabled) => assign(
+                    &mut vim_settings.use_system_clipboard,
+                    if *enabled {
+                        UseSystemClipboard::Always
+                    } else {
+                        UseSystemClipboard::Never
+                    },
+                    &mut vim_changed,
+                ),
+            }
+        }
+
+        if editor_changed || vim_changed {
+            SettingsStore::update(cx, |store, _| {
+                if editor_changed {
+                    store.override_global(editor_settings);
+                }
+                if vim_changed {
+                    store.override_global(vim_settings);
+                }
+            });
+        }
+    }
+}
+
+impl std::fmt::Display for VimOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
             VimOption::Wrap(true) => "wrap",
             VimOption::Wrap(false) => "nowrap",
             VimOption::Number(true) => "number",
@@ -184,9 +310,15 @@ impl VimOption {
             VimOption::RelativeNumber(false) => "norelativenumber",
             VimOption::IgnoreCase(true) => "ignorecase",
             VimOption::IgnoreCase(false) => "noignorecase",
+            VimOption::SmartCase(true) => "smartcase",
+            VimOption::SmartCase(false) => "nosmartcase",
             VimOption::GDefault(true) => "gdefault",
             VimOption::GDefault(false) => "nogdefault",
-        }
+            VimOption::ScrollOff(lines) => return write!(f, "scrolloff={lines}"),
+            VimOption::Clipboard(true) => "clipboard=unnamedplus",
+            VimOption::Clipboard(false) => "clipboard=",
+        };
+        f.write_str(name)
     }
 }
 
@@ -261,7 +393,7 @@ struct VimNorm {
 }
 
 #[derive(Debug)]
-struct WrappedAction(Box<dyn Action>);
+pub(crate) struct WrappedAction(pub(crate) Box<dyn Action>);
 
 impl PartialEq for WrappedAction {
     fn eq(&self, other: &Self) -> bool {
@@ -299,20 +431,12 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
                 VimOption::RelativeNumber(enabled) => {
                     editor.set_relative_line_number(Some(*enabled), cx);
                 }
-                VimOption::IgnoreCase(enabled) => {
-                    let mut settings = EditorSettings::get_global(cx).clone();
-                    settings.search.case_sensitive = !*enabled;
-                    SettingsStore::update(cx, |store, _| {
-                        store.override_global(settings);
-                    });
-                }
-                VimOption::GDefault(enabled) => {
-                    let mut settings = VimSettings::get_global(cx).clone();
-                    settings.gdefault = *enabled;
-
-                    SettingsStore::update(cx, |store, _| {
-                        store.override_global(settings);
-                    })
+                VimOption::IgnoreCase(_)
+                | VimOption::SmartCase(_)
+                | VimOption::GDefault(_)
+                | VimOption::ScrollOff(_)
+                | VimOption::Clipboard(_) => {
+                    VimOption::override_settings(std::slice::from_ref(option), cx);
                 }
             });
         }
@@ -1699,21 +1823,46 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
         .bang(workspace::CloseInactiveTabsAndPanes {
             save_intent: Some(SaveIntent::Skip),
         }),
-        VimCommand::str(("cl", "ist"), "diagnostics::Deploy"),
-        VimCommand::new(("cc", ""), editor::actions::Hover),
+        VimCommand::new(("cl", "ist"), QuickfixShow),
+        VimCommand::new(("cc", ""), QuickfixGoTo { index: None }).args(|_, args| {
+            let index = args.trim().parse().ok()?;
+            Some(QuickfixGoTo { index: Some(index) }.boxed_clone())
+        }),
         VimCommand::new(("ll", ""), editor::actions::Hover),
-        VimCommand::new(("cn", "ext"), editor::actions::GoToDiagnostic::default())
-            .range(wrap_count),
-        VimCommand::new(
-            ("cp", "revious"),
-            editor::actions::GoToPreviousDiagnostic::default(),
-        )
-        .range(wrap_count),
-        VimCommand::new(
-            ("cN", "ext"),
-            editor::actions::GoToPreviousDiagnostic::default(),
-        )
-        .range(wrap_count),
+        VimCommand::new(("cn", "ext"), QuickfixNext).range(wrap_count),
+        VimCommand::new(("cp", "revious"), QuickfixPrevious).range(wrap_count),
+        VimCommand::new(("cN", "ext"), QuickfixPrevious).range(wrap_count),
+        VimCommand::new(("cfir", "st"), QuickfixFirst),
+        VimCommand::new(("cr", "ewind"), QuickfixFirst),
+        VimCommand::new(("cla", "st"), QuickfixLast),
+        VimCommand::new(("cope", "n"), QuickfixOpen),
+        VimCommand::new(("cw", "indow"), QuickfixOpen),
+        VimCommand::new(("ccl", "ose"), QuickfixClose),
+        VimCommand::new(("vim", "grep"), ArgumentRequired)
+            .args(|_, args| Some(QuickfixSearch::parse(&args)?.boxed_clone())),
+        VimCommand::new(("gr", "ep"), ArgumentRequired)
+            .args(|_, args| Some(QuickfixSearch::parse(&args)?.boxed_clone())),
+        VimCommand::new(("cdia", "gnostics"), QuickfixDiagnostics),
+        VimCommand::new(("mak", "e"), QuickfixMake::default())
+            .args(|_, args| Some(QuickfixMake { args }.boxed_clone())),
+        VimCommand::new(("cdo", ""), ArgumentRequired).args(|_, command| {
+            Some(
+                QuickfixDo {
+                    command,
+                    per_file: false,
+                }
+                .boxed_clone(),
+            )
+        }),
+        VimCommand::new(("cfdo", ""), ArgumentRequired).args(|_, command| {
+            Some(
+                QuickfixDo {
+                    command,
+                    per_file: true,
+                }
+                .boxed_clone(),
+            )
+        }),
         VimCommand::new(
             ("lp", "revious"),
             editor::actions::GoToPreviousDiagnostic::default(),
@@ -1776,6 +1925,8 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
         VimCommand::new(("cpp", "link"), editor::actions::CopyPermalinkToLine).range(act_on_range),
         VimCommand::str(("opt", "ions"), "zed::OpenDefaultSettings"),
         VimCommand::str(("map", ""), "vim::OpenDefaultKeymap"),
+        VimCommand::new(("so", "urce"), VimSource::default())
+            .filename(|_, filename| Some(VimSource { filename }.boxed_clone())),
         VimCommand::new(("h", "elp"), OpenDocs),
     ]
 }
@@ -1855,6 +2006,8 @@ pub fn command_interceptor(
             }
             .boxed_clone(),
         )
+    } else if let Some(action) = vimrc::ex_command_action(query, &range_prefix, &workspace, cx) {
+        Some(action)
     } else if query.starts_with('/') || query.starts_with('?') {
         Some(
             FindCommand {
@@ -2061,6 +2214,36 @@ pub(crate) struct OnMatchingLines {
     invert: bool,
 }
 
+/// Reads a search pattern up to the closing `delimiter`, flipping escaped and unescaped
+/// parens so that vim-style groups become regex groups.
+pub(crate) fn parse_pattern(chars: &mut impl Iterator<Item = char>, delimiter: char) -> String {
+    let mut search = String::new();
+    let mut escaped = false;
+
+    for c in chars {
+        if escaped {
+            escaped = false;
+            // unescape escaped parens
+            if c != '(' && c != ')' && c != delimiter {
+                search.push('\\')
+            }
+            search.push(c)
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == delimiter {
+            break;
+        } else {
+            // escape unescaped parens
+            if c == '(' || c == ')' {
+                search.push('\\')
+            }
+            search.push(c)
+        }
+    }
+
+    search
+}
+
 impl OnMatchingLines {
     // convert a vim query into something more usable by zed.
     // we don't attempt to fully convert between the two regex syntaxes,
@@ -2096,30 +2279,7 @@ impl OnMatchingLines {
         let delimiter = query_chars.next().filter(|c| {
             !c.is_alphanumeric() && *c != '"' && *c != '|' && *c != '\'' && *c != '!'
         })?;
-
-        let mut search = String::new();
-        let mut escaped = false;
-
-        for c in query_chars.by_ref() {
-            if escaped {
-                escaped = false;
-                // unescape escaped parens
-                if c != '(' && c != ')' && c != delimiter {
-                    search.push('\\')
-                }
-                search.push(c)
-            } else if c == '\\' {
-                escaped = true;
-            } else if c == delimiter {
-                break;
-            } else {
-                // escape unescaped parens
-                if c == '(' || c == ')' {
-                    search.push('\\')
-                }
-                search.push(c)
-            }
-        }
+        let search = parse_pattern(&mut query_chars, delimiter);
 
         Some((query_chars.collect::<String>(), range, search, invert))
     }
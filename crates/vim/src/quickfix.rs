@@ -0,0 +1,985 @@
+//! The quickfix list: locations across the project gathered by `:vimgrep`, `:cdiagnostics` or
+//! `:make`. `:cnext` and friends step through it, `:copen` shows it in a pane, and `:cdo` and
+//! `:cfdo` run an ex command on its lines or files.
+//!
+//! While the list is empty, `:cnext`, `:cprevious`, `:cc` and `:clist` keep working with the
+//! diagnostics of the active editor.
+
+use anyhow::{anyhow, bail};
+use editor::{Editor, EditorSettings, MultiBuffer, SelectionEffects, scroll::Autoscroll};
+use futures::{FutureExt as _, StreamExt as _};
+use gpui::{
+    Action, AnyElement, App, AppContext as _, Context, Entity, EventEmitter, FocusHandle,
+    Focusable, ScrollStrategy, SharedString, Subscription, UniformListScrollHandle, WeakEntity,
+    Window, actions, uniform_list,
+};
+use itertools::Itertools as _;
+use language::{Bias, Buffer, Capability, DiagnosticSeverity, Point};
+use multi_buffer::PathKey;
+use project::{
+    SearchResults,
+    search::{SearchQuery, SearchResult},
+};
+use regex::Regex;
+use settings::Settings as _;
+use std::{ops::Range, process::Stdio, time::Instant};
+use ui::{ListItem, WithScrollbar, prelude::*};
+use util::{ResultExt as _, paths::PathMatcher};
+use workspace::{
+    Pane, SaveIntent, SplitDirection, Workspace,
+    item::{Item, ItemEvent},
+    notifications::{DetachAndPromptErr as _, NotifyResultExt as _},
+};
+
+use crate::{
+    VimSettings,
+    command::{command_interceptor, parse_pattern},
+    state::VimGlobals,
+};
+
+actions!(
+    vim,
+    [
+        /// Jumps to the next entry of the quickfix list, or to the next diagnostic
+        /// while the list is empty.
+        QuickfixNext,
+        /// Jumps to the previous entry of the quickfix list, or to the previous
+        /// diagnostic while the list is empty.
+        QuickfixPrevious,
+        /// Jumps to the first entry of the quickfix list.
+        QuickfixFirst,
+        /// Jumps to the last entry of the quickfix list.
+        QuickfixLast,
+        /// Opens the quickfix list in a pane below the active one.
+        QuickfixOpen,
+        /// Closes the quickfix list pane.
+        QuickfixClose,
+        /// Opens the quickfix list, or the project diagnostics while the list is empty.
+        QuickfixShow,
+        /// Fills the quickfix list with the errors and warnings of the project.
+        QuickfixDiagnostics,
+    ]
+);
+
+/// Jumps to the entry of the quickfix list with the given number, counting from 1,
+/// or to the current entry.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct QuickfixGoTo {
+    pub index: Option<usize>,
+}
+
+/// Fills the quickfix list with the matches of a project search.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct QuickfixSearch {
+    query: String,
+    files: Vec<String>,
+}
+
+/// Runs the make command and fills the quickfix list with the locations in its output.
+#[derive(Clone, Default, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct QuickfixMake {
+    pub args: String,
+}
+
+/// Runs an ex command on every line, or every file, of the quickfix list.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct QuickfixDo {
+    pub command: String,
+    pub per_file: bool,
+}
+
+impl QuickfixSearch {
+    /// Parses the arguments of `:vimgrep` and `:grep`: a pattern, either as the first word or
+    /// between delimiters as in `/foo/g`, followed by globs of the files to search.
+    pub(crate) fn parse(args: &str) -> Option<Self> {
+        let args = args.trim();
+        let mut chars = args.chars();
+        let delimiter = chars.next()?;
+        let (query, files) = if delimiter.is_alphanumeric() || delimiter == '_' || delimiter == '\\'
+        {
+            args.split_once(char::is_whitespace)
+                .map_or((args.to_string(), ""), |(query, files)| {
+                    (query.to_string(), files)
+                })
+        } else {
+            let query = parse_pattern(&mut chars, delimiter);
+            // The `g` and `j` flags don't apply: every match is listed, and the list opens
+            // at the first one.
+            (query, chars.as_str().trim_start_matches(['g', 'j']))
+        };
+        if query.is_empty() {
+            return None;
+        }
+        Some(Self {
+            query,
+            files: files.split_whitespace().map(ToString::to_string).collect(),
+        })
+    }
+}
+
+#[derive(Clone)]
+struct QuickfixEntry {
+    buffer: Entity<Buffer>,
+    position: text::Anchor,
+    path: SharedString,
+    text: SharedString,
+}
+
+impl QuickfixEntry {
+    /// An entry at `point`, described by `message` or else by the text of its line.
+    fn new(buffer: &Entity<Buffer>, point: Point, message: Option<String>, cx: &App) -> Self {
+        let snapshot = buffer.read(cx);
+        let point = snapshot.clip_point(point, Bias::Left);
+        let text = message.unwrap_or_else(|| {
+            snapshot
+                .text_for_range(
+                    Point::new(point.row, 0)..Point::new(point.row, snapshot.line_len(point.row)),
+                )
+                .collect::<String>()
+        });
+        let path = snapshot.file().map_or_else(
+            || "[No Name]".to_string(),
+            |file| file.path().display(file.path_style(cx)).into_owned(),
+        );
+        Self {
+            buffer: buffer.clone(),
+            position: snapshot.anchor_before(point),
+            path: path.into(),
+            text: text.trim().to_string().into(),
+        }
+    }
+
+    fn point(&self, cx: &App) -> Point {
+        self.buffer.read(cx).summary_for_anchor(&self.position)
+    }
+}
+
+/// The quickfix list of a workspace.
+#[derive(Default)]
+pub struct QuickfixList {
+    title: SharedString,
+    entries: Vec<QuickfixEntry>,
+    current: usize,
+}
+
+impl QuickfixList {
+    fn for_workspace(cx: &Context<Workspace>) -> Option<Entity<Self>> {
+        cx.try_global::<VimGlobals>()?
+            .quickfix
+            .get(&cx.entity_id())
+            .cloned()
+    }
+
+    fn set(&mut self, title: String, mut entries: Vec<QuickfixEntry>, cx: &mut Context<Self>) {
+        entries.sort_by_cached_key(|entry| (entry.path.clone(), entry.point(cx)));
+        self.title = title.into();
+        self.entries = entries;
+        self.current = 0;
+        cx.notify();
+    }
+}
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &QuickfixNext, window, cx| {
+            match current_index(cx) {
+                Some(current) => go_to(workspace, current + 1, window, cx),
+                None => window
+                    .dispatch_action(editor::actions::GoToDiagnostic::default().boxed_clone(), cx),
+            }
+        });
+        workspace.register_action(
+            |workspace, _: &QuickfixPrevious, window, cx| match current_index(cx) {
+                Some(current) => go_to(workspace, current.saturating_sub(1), window, cx),
+                None => window.dispatch_action(
+                    editor::actions::GoToPreviousDiagnostic::default().boxed_clone(),
+                    cx,
+                ),
+            },
+        );
+        workspace.register_action(|workspace, action: &QuickfixGoTo, window, cx| {
+            match (action.index, current_index(cx)) {
+                (Some(index), _) => go_to(workspace, index.saturating_sub(1), window, cx),
+                (None, Some(current)) => go_to(workspace, current, window, cx),
+                (None, None) => window.dispatch_action(editor::actions::Hover.boxed_clone(), cx),
+            }
+        });
+        workspace.register_action(|workspace, _: &QuickfixFirst, window, cx| {
+            go_to(workspace, 0, window, cx)
+        });
+        workspace.register_action(|workspace, _: &QuickfixLast, window, cx| {
+            go_to(workspace, usize::MAX, window, cx)
+        });
+        workspace
+            .register_action(|workspace, _: &QuickfixOpen, window, cx| open(workspace, window, cx));
+        workspace.register_action(|workspace, _: &QuickfixClose, window, cx| {
+            close(workspace, window, cx)
+        });
+        workspace.register_action(|workspace, _: &QuickfixShow, window, cx| {
+            if current_index(cx).is_some() {
+                open(workspace, window, cx);
+            } else if let Some(action) = cx.build_action("diagnostics::Deploy", None).log_err() {
+                window.dispatch_action(action, cx);
+            }
+        });
+        workspace.register_action(search);
+        workspace.register_action(|workspace, _: &QuickfixDiagnostics, window, cx| {
+            collect_diagnostics(workspace, window, cx)
+        });
+        workspace.register_action(make);
+        workspace.register_action(run_on_entries);
+    })
+    .detach();
+}
+
+/// The index of the current entry, or `None` while the quickfix list is empty.
+fn current_index(cx: &Context<Workspace>) -> Option<usize> {
+    let list = QuickfixList::for_workspace(cx)?;
+    let list = list.read(cx);
+    (!list.entries.is_empty()).then_some(list.current)
+}
+
+fn set_entries(
+    workspace: &mut Workspace,
+    title: String,
+    entries: Vec<QuickfixEntry>,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(list) = QuickfixList::for_workspace(cx) else {
+        return;
+    };
+    let is_empty = entries.is_empty();
+    list.update(cx, |list, cx| list.set(title, entries, cx));
+    if !is_empty {
+        go_to(workspace, 0, window, cx);
+    }
+}
+
+/// Makes the entry at `index`, clamped to the list, current and opens it in an editor.
+fn go_to(
+    workspace: &mut Workspace,
+    index: usize,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(list) = QuickfixList::for_workspace(cx) else {
+        return;
+    };
+    let Some(entry) = list.update(cx, |list, cx| {
+        let index = index.min(list.entries.len().checked_sub(1)?);
+        list.current = index;
+        cx.notify();
+        Some(list.entries[index].clone())
+    }) else {
+        return;
+    };
+
+    let pane = target_pane(workspace, cx);
+    let point = entry.point(cx);
+    let editor = workspace.open_project_item::<Editor>(
+        pane,
+        entry.buffer,
+        true,
+        true,
+        true,
+        true,
+        window,
+        cx,
+    );
+    editor.update(cx, |editor, cx| {
+        editor.change_selections(
+            SelectionEffects::scroll(Autoscroll::center()),
+            window,
+            cx,
+            |s| s.select_ranges([point..point]),
+        );
+    });
+}
+
+/// The pane entries open in: the active pane, unless that is the quickfix pane.
+fn target_pane(workspace: &Workspace, cx: &App) -> Entity<Pane> {
+    let has_quickfix_view = |pane: &Entity<Pane>| {
+        pane.read(cx)
+            .items_of_type::<QuickfixView>()
+            .next()
+            .is_some()
+    };
+    let active_pane = workspace.active_pane();
+    if !has_quickfix_view(active_pane) {
+        return active_pane.clone();
+    }
+    workspace
+        .panes()
+        .iter()
+        .find(|pane| !has_quickfix_view(pane))
+        .unwrap_or(active_pane)
+        .clone()
+}
+
+fn open(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+    let view = workspace.items_of_type::<QuickfixView>(cx).next();
+    if let Some(view) = view {
+        workspace.activate_item(&view, true, true, window, cx);
+        return;
+    }
+    let Some(list) = QuickfixList::for_workspace(cx) else {
+        return;
+    };
+    let workspace_handle = cx.entity().downgrade();
+    let view = cx.new(|cx| QuickfixView::new(list, workspace_handle, cx));
+    workspace.split_item(SplitDirection::Down, Box::new(view), window, cx);
+}
+
+fn close(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+    for pane in workspace.panes().to_vec() {
+        let views = pane
+            .read(cx)
+            .items_of_type::<QuickfixView>()
+            .map(|view| view.entity_id())
+            .collect::<Vec<_>>();
+        for view in views {
+            pane.update(cx, |pane, cx| {
+                pane.close_item_by_id(view, SaveIntent::Skip, window, cx)
+            })
+            .detach_and_log_err(cx);
+        }
+    }
+}
+
+fn search(
+    workspace: &mut Workspace,
+    action: &QuickfixSearch,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let project = workspace.project().clone();
+    let path_style = project.read(cx).path_style(cx);
+    let settings = EditorSettings::get_global(cx);
+    let case_sensitive = if settings.use_smartcase_search {
+        action.query.chars().any(char::is_uppercase)
+    } else {
+        settings.search.case_sensitive
+    };
+    let Some(query) = PathMatcher::new(&action.files, path_style)
+        .map_err(anyhow::Error::from)
+        .and_then(|files_to_include| {
+            SearchQuery::regex(
+                &action.query,
+                false,
+                case_sensitive,
+                false,
+                false,
+                files_to_include,
+                PathMatcher::default(),
+                false,
+                None,
+            )
+        })
+        .notify_err(workspace, cx)
+    else {
+        return;
+    };
+
+    let title = format!(":vimgrep /{}/", action.query);
+    let pattern = action.query.clone();
+    let results = project.update(cx, |project, cx| project.search(query, cx));
+    cx.spawn_in(window, async move |workspace, cx| {
+        let SearchResults { rx, _task_handle } = results;
+        futures::pin_mut!(rx);
+        let mut entries = Vec::new();
+        while let Some(SearchResult::Buffer { buffer, ranges }) = rx.next().await {
+            buffer.read_with(cx, |snapshot, cx| {
+                for range in ranges {
+                    let point = snapshot.summary_for_anchor(&range.start);
+                    entries.push(QuickfixEntry::new(&buffer, point, None, cx));
+                }
+            });
+        }
+        workspace.update_in(cx, |workspace, window, cx| {
+            if entries.is_empty() {
+                Err::<(), _>(anyhow!("No matches for {pattern}")).notify_err(workspace, cx);
+            }
+            set_entries(workspace, title, entries, window, cx);
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+fn collect_diagnostics(
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let project = workspace.project().clone();
+    let paths = project
+        .read(cx)
+        .diagnostic_summaries(false, cx)
+        .filter(|(_, _, summary)| summary.error_count + summary.warning_count > 0)
+        .map(|(path, _, _)| path)
+        .unique()
+        .collect::<Vec<_>>();
+    let buffers = paths
+        .into_iter()
+        .map(|path| project.update(cx, |project, cx| project.open_buffer(path, cx)))
+        .collect::<Vec<_>>();
+
+    cx.spawn_in(window, async move |workspace, cx| {
+        let mut entries = Vec::new();
+        for buffer in futures::future::join_all(buffers).await {
+            let Some(buffer) = buffer.log_err() else {
+                continue;
+            };
+            buffer.read_with(cx, |snapshot, cx| {
+                let snapshot = snapshot.snapshot();
+                for diagnostic in
+                    snapshot.diagnostics_in_range::<_, Point>(0..snapshot.len(), false)
+                {
+                    if diagnostic.diagnostic.is_primary
+                        && diagnostic.diagnostic.severity <= DiagnosticSeverity::WARNING
+                    {
+                        entries.push(QuickfixEntry::new(
+                            &buffer,
+                            diagnostic.range.start,
+                            Some(diagnostic.diagnostic.message.clone()),
+                            cx,
+                        ));
+                    }
+                }
+            });
+        }
+        workspace.update_in(cx, |workspace, window, cx| {
+            set_entries(workspace, ":cdiagnostics".to_string(), entries, window, cx);
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// A location read from the output of `:make`.
+#[derive(Debug, PartialEq)]
+struct OutputLocation {
+    path: String,
+    point: Point,
+    message: String,
+}
+
+/// Reads the locations in `output` with the `error_format` regex. A location without a message
+/// takes the last line that wasn't a location, as in the output of rustc.
+fn parse_output(error_format: &Regex, output: &str) -> Vec<OutputLocation> {
+    let mut locations = Vec::new();
+    let mut last_line = "";
+    for line in output.lines() {
+        let Some(captures) = error_format.captures(line) else {
+            if !line.trim().is_empty() {
+                last_line = line.trim();
+            }
+            continue;
+        };
+        let Some((path, row)) = captures.name("file").zip(
+            captures
+                .name("line")
+                .and_then(|row| row.as_str().parse::<u32>().ok()),
+        ) else {
+            continue;
+        };
+        let column = captures
+            .name("column")
+            .and_then(|column| column.as_str().parse::<u32>().ok())
+            .unwrap_or(1);
+        let message = captures
+            .name("message")
+            .map_or("", |message| message.as_str().trim());
+        locations.push(OutputLocation {
+            path: path.as_str().to_string(),
+            point: Point::new(row.saturating_sub(1), column.saturating_sub(1)),
+            message: if message.is_empty() {
+                last_line
+            } else {
+                message
+            }
+            .to_string(),
+        });
+    }
+    locations
+}
+
+fn make(
+    workspace: &mut Workspace,
+    action: &QuickfixMake,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let settings = VimSettings::get_global(cx);
+    let mut command = settings.make_command.clone();
+    let Some(error_format) = Regex::new(&settings.error_format)
+        .map_err(|error| anyhow!("Invalid vim.error_format: {error}"))
+        .notify_err(workspace, cx)
+    else {
+        return;
+    };
+    if !action.args.is_empty() {
+        command.push(' ');
+        command.push_str(&action.args);
+    }
+
+    let project = workspace.project().clone();
+    let process = project.update(cx, |project, cx| project.exec_in_shell(command.clone(), cx));
+    cx.spawn_in(window, async move |workspace, cx| {
+        let mut process = process.await?;
+        process.stdin(Stdio::null());
+        let output = cx
+            .background_spawn(async move { process.output().await })
+            .await?;
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+
+        let mut entries = Vec::new();
+        for location in parse_output(&error_format, &text) {
+            let Some(project_path) = project.read_with(cx, |project, cx| {
+                project.find_project_path(&location.path, cx)
+            }) else {
+                continue;
+            };
+            let Some(buffer) = project
+                .update(cx, |project, cx| project.open_buffer(project_path, cx))
+                .await
+                .log_err()
+            else {
+                continue;
+            };
+            entries.push(buffer.read_with(cx, |_, cx| {
+                QuickfixEntry::new(&buffer, location.point, Some(location.message), cx)
+            }));
+        }
+        workspace.update_in(cx, |workspace, window, cx| {
+            set_entries(workspace, format!(":make {command}"), entries, window, cx);
+        })
+    })
+    .detach_and_prompt_err("Failed to run make", window, cx, |_, _, _| None);
+}
+
+/// Runs the command of `:cdo` or `:cfdo` in a multibuffer of the lines, or files, of the quickfix
+/// list, so that it changes every one of them and can be undone in one step per buffer.
+fn run_on_entries(
+    workspace: &mut Workspace,
+    action: &QuickfixDo,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(list) = QuickfixList::for_workspace(cx) else {
+        return;
+    };
+    let mut ranges: Vec<(Entity<Buffer>, Vec<Range<Point>>)> = Vec::new();
+    for entry in &list.read(cx).entries {
+        let buffer = entry.buffer.read(cx);
+        let range = if action.per_file {
+            Point::zero()..buffer.max_point()
+        } else {
+            let row = entry.point(cx).row;
+            Point::new(row, 0)..Point::new(row, buffer.line_len(row))
+        };
+        match ranges
+            .iter_mut()
+            .find(|(buffer, _)| *buffer == entry.buffer)
+        {
+            Some((_, buffer_ranges)) => buffer_ranges.push(range),
+            None => ranges.push((entry.buffer.clone(), vec![range])),
+        }
+    }
+    if ranges.is_empty() {
+        return;
+    }
+
+    let command = action.command.trim().trim_start_matches(':');
+    let Some(command_action) = ex_command(&format!("%{command}"), cx)
+        .or_else(|| ex_command(command, cx))
+        .ok_or_else(|| anyhow!("Not an editor command: {command}"))
+        .notify_err(workspace, cx)
+    else {
+        return;
+    };
+
+    let title = format!(
+        ":{} {command}",
+        if action.per_file { "cfdo" } else { "cdo" }
+    );
+    let multibuffer = cx.new(|cx| {
+        let mut multibuffer = MultiBuffer::new(Capability::ReadWrite).with_title(title);
+        for (buffer, ranges) in ranges {
+            multibuffer.set_excerpts_for_path(
+                PathKey::for_buffer(&buffer, cx),
+                buffer,
+                ranges,
+                0,
+                cx,
+            );
+        }
+        multibuffer
+    });
+    let project = workspace.project().clone();
+    let editor = cx.new(|cx| Editor::for_multibuffer(multibuffer, Some(project), window, cx));
+    workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, window, cx);
+
+    cx.spawn_in(window, async move |_, cx| {
+        cx.update(|window, cx| {
+            // Actions are dispatched through the last drawn frame, which doesn't have the editor
+            // until the window is drawn again.
+            window.draw(cx).clear();
+            let focus_handle = editor.focus_handle(cx);
+            if !focus_handle.is_focused(window) {
+                bail!("the quickfix editor lost focus before the command ran");
+            }
+            editor.update(cx, |editor, cx| {
+                editor.start_transaction_at(Instant::now(), window, cx);
+            });
+            focus_handle.dispatch_action(command_action.as_ref(), window, cx);
+            editor.update(cx, |editor, cx| {
+                editor.end_transaction_at(Instant::now(), cx);
+            });
+            Ok(())
+        })?
+    })
+    .detach_and_log_err(cx);
+}
+
+fn ex_command(command: &str, cx: &mut App) -> Option<Box<dyn Action>> {
+    // The workspace is being updated, so it can't be lent to the interceptor.
+    command_interceptor(command, WeakEntity::new_invalid(), cx)
+        .now_or_never()?
+        .results
+        .into_iter()
+        .next()
+        .map(|result| result.action)
+}
+
+/// The quickfix list shown in a pane by `:copen`.
+pub(crate) struct QuickfixView {
+    list: Entity<QuickfixList>,
+    workspace: WeakEntity<Workspace>,
+    selected: usize,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    _subscription: Subscription,
+}
+
+impl QuickfixView {
+    fn new(
+        list: Entity<QuickfixList>,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let subscription = cx.observe(&list, |this, list, cx| {
+            this.select(list.read(cx).current, cx);
+        });
+        let selected = list.read(cx).current;
+        let scroll_handle = UniformListScrollHandle::new();
+        scroll_handle.scroll_to_item(selected, ScrollStrategy::Center);
+        Self {
+            list,
+            workspace,
+            selected,
+            focus_handle: cx.focus_handle(),
+            scroll_handle,
+            _subscription: subscription,
+        }
+    }
+
+    fn select(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected = ix;
+        self.scroll_handle
+            .scroll_to_item(ix, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let last = self.list.read(cx).entries.len().saturating_sub(1);
+        self.select((self.selected + 1).min(last), cx);
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.select(self.selected.saturating_sub(1), cx);
+    }
+
+    fn select_first(&mut self, _: &menu::SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        self.select(0, cx);
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        let last = self.list.read(cx).entries.len().saturating_sub(1);
+        self.select(last, cx);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        self.open_entry(self.selected, window, cx);
+    }
+
+    fn open_entry(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| go_to(workspace, ix, window, cx))
+            .log_err();
+    }
+
+    fn render_entry(&self, ix: usize, entry: &QuickfixEntry, cx: &mut Context<Self>) -> AnyElement {
+        let point = entry.point(cx);
+        ListItem::new(ix)
+            .toggle_state(ix == self.selected)
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.select(ix, cx);
+                this.open_entry(ix, window, cx);
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Label::new(format!(
+                            "{}:{}:{}",
+                            entry.path,
+                            point.row + 1,
+                            point.column + 1
+                        ))
+                        .color(Color::Muted)
+                        .buffer_font(cx),
+                    )
+                    .child(Label::new(entry.text.clone()).single_line().buffer_font(cx)),
+            )
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<ItemEvent> for QuickfixView {}
+
+impl Focusable for QuickfixView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for QuickfixView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let entry_count = self.list.read(cx).entries.len();
+        let view = cx.weak_entity();
+        v_flex()
+            .id("quickfix_view")
+            .key_context("QuickfixView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                v_flex()
+                    .flex_1()
+                    .size_full()
+                    .child(
+                        uniform_list("quickfix-list", entry_count, move |range, _, cx| {
+                            let Some(view) = view.upgrade() else {
+                                return Vec::new();
+                            };
+                            view.update(cx, |this, cx| {
+                                let entries = this.list.read(cx).entries[range.clone()].to_vec();
+                                range
+                                    .zip(&entries)
+                                    .map(|(ix, entry)| this.render_entry(ix, entry, cx))
+                                    .collect()
+                            })
+                        })
+                        .flex_1()
+                        .size_full()
+                        .track_scroll(&self.scroll_handle),
+                    )
+                    .vertical_scrollbar_for(&self.scroll_handle, window, cx),
+            )
+    }
+}
+
+impl Item for QuickfixView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Quickfix List".into()
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        Some(self.list.read(cx).title.clone())
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::ListTodo))
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{state::Mode, test::VimTestContext};
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use std::path::Path;
+    use util::path;
+
+    #[test]
+    fn test_parse_search() {
+        let search = QuickfixSearch::parse("/foo(bar) baz/gj src/**/*.rs *.md").unwrap();
+        assert_eq!(search.query, "foo\\(bar\\) baz");
+        assert_eq!(search.files, ["src/**/*.rs", "*.md"]);
+
+        let search = QuickfixSearch::parse("foo.*bar").unwrap();
+        assert_eq!(search.query, "foo.*bar");
+        assert!(search.files.is_empty());
+
+        assert!(QuickfixSearch::parse("//").is_none());
+    }
+
+    #[test]
+    fn test_parse_output() {
+        let error_format = Regex::new(
+            r"^(?:\s*--> )?(?P<file>[^:\s][^:]*):(?P<line>\d+):(?:(?P<column>\d+):?)?\s*(?P<message>.*)$",
+        )
+        .unwrap();
+        let output = indoc! {r#"
+            error[E0308]: mismatched types
+             --> src/main.rs:2:18
+              |
+            2 |     let x: u32 = "a";
+              |                  ^^^ expected `u32`, found `&str`
+            main.c:10:5: error: expected ';'
+            script.py:3: undefined name
+            error: could not compile `app`
+        "#};
+        assert_eq!(
+            parse_output(&error_format, output),
+            [
+                OutputLocation {
+                    path: "src/main.rs".into(),
+                    point: Point::new(1, 17),
+                    message: "error[E0308]: mismatched types".into(),
+                },
+                OutputLocation {
+                    path: "main.c".into(),
+                    point: Point::new(9, 4),
+                    message: "error: expected ';'".into(),
+                },
+                OutputLocation {
+                    path: "script.py".into(),
+                    point: Point::new(2, 0),
+                    message: "undefined name".into(),
+                },
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_vimgrep_and_cdo(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let fs = cx.workspace(|workspace, _, cx| workspace.project().read(cx).fs().clone());
+        fs.as_fake()
+            .insert_file(path!("/root/dir/one.rs"), "foo\nbar\nfoo foo\n".into())
+            .await;
+        fs.as_fake()
+            .insert_file(path!("/root/dir/two.rs"), "bar\nfoo\n".into())
+            .await;
+        cx.run_until_parked();
+
+        cx.simulate_keystrokes(": v i m g r e p space / f o o / enter");
+        cx.run_until_parked();
+        cx.workspace(|workspace, _, cx| {
+            let list = QuickfixList::for_workspace(cx).unwrap();
+            let entries = list
+                .read(cx)
+                .entries
+                .iter()
+                .map(|entry| (entry.path.to_string(), entry.point(cx)))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                entries,
+                [
+                    (path!("dir/one.rs").to_string(), Point::new(0, 0)),
+                    (path!("dir/one.rs").to_string(), Point::new(2, 0)),
+                    (path!("dir/one.rs").to_string(), Point::new(2, 4)),
+                    (path!("dir/two.rs").to_string(), Point::new(1, 0)),
+                ]
+            );
+            let path = workspace.active_item(cx).unwrap().project_path(cx).unwrap();
+            assert_eq!(path.path.as_std_path(), Path::new("dir/one.rs"));
+        });
+
+        cx.simulate_keystrokes(": c l a enter");
+        cx.run_until_parked();
+        cx.workspace(|workspace, _, cx| {
+            let path = workspace.active_item(cx).unwrap().project_path(cx).unwrap();
+            assert_eq!(path.path.as_std_path(), Path::new("dir/two.rs"));
+        });
+
+        cx.simulate_keystrokes(": c d o space s / f o o / b a z / g enter");
+        cx.run_until_parked();
+        cx.workspace(|workspace, _, cx| {
+            let texts = workspace
+                .project()
+                .read(cx)
+                .opened_buffers(cx)
+                .into_iter()
+                .map(|buffer| buffer.read(cx).text())
+                .collect::<Vec<_>>();
+            assert!(texts.contains(&"baz\nbar\nbaz baz\n".to_string()));
+            assert!(texts.contains(&"bar\nbaz\n".to_string()));
+        });
+
+        cx.simulate_keystrokes("u");
+        cx.run_until_parked();
+        cx.workspace(|workspace, _, cx| {
+            let texts = workspace
+                .project()
+                .read(cx)
+                .opened_buffers(cx)
+                .into_iter()
+                .map(|buffer| buffer.read(cx).text())
+                .collect::<Vec<_>>();
+            assert!(texts.contains(&"foo\nbar\nfoo foo\n".to_string()));
+            assert!(texts.contains(&"bar\nfoo\n".to_string()));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_empty_list_uses_diagnostics(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.set_state("ˇone\ntwo", Mode::Normal);
+        cx.simulate_keystrokes(": c n enter");
+        cx.run_until_parked();
+        cx.workspace(|workspace, _, cx| {
+            assert!(current_index(cx).is_none());
+            assert_eq!(workspace.items_of_type::<QuickfixView>(cx).count(), 0);
+        });
+        cx.assert_state("ˇone\ntwo", Mode::Normal);
+
+        cx.simulate_keystrokes(": c o p e n enter");
+        cx.run_until_parked();
+        cx.workspace(|workspace, _, cx| {
+            assert_eq!(workspace.items_of_type::<QuickfixView>(cx).count(), 1);
+        });
+        cx.simulate_keystrokes(": c c l o s e enter");
+        cx.run_until_parked();
+        cx.workspace(|workspace, _, cx| {
+            assert_eq!(workspace.items_of_type::<QuickfixView>(cx).count(), 0);
+        });
+    }
+}
@@ -1,6 +1,7 @@
 use crate::command::command_interceptor;
 use crate::motion::MotionKind;
 use crate::normal::repeat::Replayer;
+use crate::quickfix::QuickfixList;
 use crate::surrounds::SurroundsType;
 use crate::{ToggleMarksView, ToggleRegistersView, UseSystemClipboard, Vim, VimAddon, VimSettings};
 use crate::{motion::Motion, object::Object};
@@ -240,6 +241,10 @@ pub struct VimGlobals {
     pub focused_vim: Option<WeakEntity<Vim>>,
 
     pub marks: HashMap<EntityId, Entity<MarksState>>,
+    pub quickfix: HashMap<EntityId, Entity<QuickfixList>>,
+
+    /// Whether the keystrokes of a vimrc `noremap` mapping are being sent.
+    pub dispatching_noremap: bool,
 }
 
 pub struct MarksState {
@@ -760,9 +765,12 @@ impl VimGlobals {
     fn register_workspace(&mut self, workspace: &Workspace, cx: &mut Context<Workspace>) {
         let entity_id = cx.entity_id();
         self.marks.insert(entity_id, MarksState::new(workspace, cx));
+        self.quickfix
+            .insert(entity_id, cx.new(|_| QuickfixList::default()));
         cx.observe_release(&cx.entity(), move |_, _, cx| {
             Vim::update_globals(cx, |globals, _| {
                 globals.marks.remove(&entity_id);
+                globals.quickfix.remove(&entity_id);
             })
         })
         .detach();
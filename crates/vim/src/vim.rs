@@ -13,10 +13,12 @@ mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod quickfix;
 mod replace;
 mod rewrap;
 mod state;
 mod surrounds;
+mod vimrc;
 mod visual;
 
 use crate::normal::paste::Paste as VimPaste;
@@ -55,6 +57,7 @@ use theme::ThemeSettings;
 use ui::{IntoElement, SharedString, px};
 use vim_mode_setting::HelixModeSetting;
 use vim_mode_setting::VimModeSetting;
+pub use vimrc::{Vimrc, handle_vimrc_file_changes};
 use workspace::{self, Pane, Workspace};
 
 use crate::{
@@ -278,6 +281,8 @@ actions!(
 /// Initializes the `vim` crate.
 pub fn init(cx: &mut App) {
     VimGlobals::register(cx);
+    vimrc::init(cx);
+    quickfix::init(cx);
 
     cx.observe_new(Vim::register).detach();
 
@@ -643,6 +648,7 @@ impl Vim {
         });
 
         Vim::sync_vim_settings_to_editor(&state, editor, window, cx);
+        vimrc::sync_to_editor(editor, cx);
 
         editor.register_addon(VimAddon {
             entity: vim.clone(),
@@ -952,6 +958,7 @@ impl Vim {
             helix::register(editor, cx);
             motion::register(editor, cx);
             command::register(editor, cx);
+            vimrc::register(editor, cx);
             replace::register(editor, cx);
             indent::register(editor, cx);
             rewrap::register(editor, cx);
@@ -1425,6 +1432,9 @@ impl Vim {
         }
         context.set("vim_mode", mode);
         context.set("vim_operator", operator_id);
+        if cx.global::<VimGlobals>().dispatching_noremap {
+            context.add(vimrc::NOREMAP_CONTEXT);
+        }
     }
 
     fn focused(&mut self, preserve_selection: bool, window: &mut Window, cx: &mut Context<Self>) {
@@ -2103,6 +2113,8 @@ struct VimSettings {
     pub custom_digraphs: HashMap<String, Arc<str>>,
     pub highlight_on_yank_duration: u64,
     pub cursor_shape: CursorShapeSettings,
+    pub make_command: String,
+    pub error_format: String,
 }
 
 /// Cursor shape configuration for insert mode.
@@ -2189,6 +2201,8 @@ impl Settings for VimSettings {
             custom_digraphs: vim.custom_digraphs.unwrap(),
             highlight_on_yank_duration: vim.highlight_on_yank_duration.unwrap(),
             cursor_shape: vim.cursor_shape.unwrap().into(),
+            make_command: vim.make_command.unwrap(),
+            error_format: vim.error_format.unwrap(),
         }
     }
 }
@@ -0,0 +1,1005 @@
+//! Support for a vimrc-style file of mappings, options and user commands.
+//!
+//! The file at [`paths::vimrc_file`] is run when Zed starts and again whenever it changes,
+//! and other files can be run with `:source`. Each line is one of:
+//!
+//! - a mapping command such as `nnoremap <leader>w :w<CR>` or `iunmap jk`,
+//! - `set` with the options understood by `:set`,
+//! - `let mapleader = ","` or `let maplocalleader = ","`,
+//! - `command Name replacement`, which defines a user command.
+//!
+//! Mappings become key bindings that send their right-hand side as keystrokes. The keystrokes
+//! of `noremap` mappings are sent with the vimrc's mappings turned off, so they always run the
+//! default bindings. Those of `map` mappings can run other mappings, except that a mapping whose
+//! right-hand side starts with its own left-hand side falls through to the default binding
+//! rather than recursing.
+
+use anyhow::{Context as _, Result, bail, ensure};
+use collections::HashMap;
+use editor::Editor;
+use futures::{FutureExt as _, StreamExt as _, channel::mpsc::UnboundedReceiver};
+use gpui::{
+    Action, App, AppContext as _, Context, Global, KeyBinding, KeyBindingContextPredicate,
+    Keystroke, NoAction, Task, WeakEntity, Window,
+};
+use itertools::Itertools as _;
+use language::language_settings::SoftWrap;
+use settings::{KeybindSource, Settings as _, SettingsStore};
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use util::ResultExt as _;
+use vim_mode_setting::VimModeSetting;
+use workspace::{
+    SendKeystrokes, Workspace,
+    notifications::{
+        DetachAndPromptErr as _, NotificationId, dismiss_app_notification, show_app_notification,
+        simple_message_notification::MessageNotification,
+    },
+};
+
+use crate::{
+    Vim,
+    command::{VimOption, WrappedAction, command_interceptor},
+};
+
+/// How deeply user commands may expand into other user commands.
+const MAX_USER_COMMAND_DEPTH: usize = 10;
+
+/// The key context set on vim editors while the keystrokes of a `noremap` mapping are sent.
+pub(crate) const NOREMAP_CONTEXT: &str = "vim_noremap";
+
+/// Runs the current vimrc, or the given file.
+#[derive(Clone, Default, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct VimSource {
+    pub filename: String,
+}
+
+/// Runs one vimrc line typed on the command line, such as `:nnoremap Y y$`.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+struct RunVimrcLine {
+    line: String,
+}
+
+/// Sends the right-hand side of a `noremap` mapping as keystrokes, which the vimrc's mappings
+/// don't apply to.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+struct SendNoremapKeystrokes(String);
+
+/// Runs each action of a user command that expands to more than one action.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+struct RunUserCommand {
+    actions: Vec<WrappedAction>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MapMode {
+    Normal,
+    Visual,
+    OperatorPending,
+    Insert,
+}
+
+impl MapMode {
+    fn context(self) -> &'static str {
+        match self {
+            MapMode::Normal => "vim_mode == normal",
+            MapMode::Visual => "vim_mode == visual",
+            MapMode::OperatorPending => "vim_mode == operator",
+            MapMode::Insert => "vim_mode == insert",
+        }
+    }
+}
+
+const NORMAL_VISUAL_OPERATOR: &[MapMode] =
+    &[MapMode::Normal, MapMode::Visual, MapMode::OperatorPending];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MapCommand {
+    Map,
+    Noremap,
+    Unmap,
+}
+
+/// The mapping commands, as the shortest abbreviation vim accepts, the full name,
+/// and what they do.
+const MAP_COMMANDS: &[(&str, &str, &[MapMode], MapCommand)] = &[
+    ("map", "map", NORMAL_VISUAL_OPERATOR, MapCommand::Map),
+    ("no", "noremap", NORMAL_VISUAL_OPERATOR, MapCommand::Noremap),
+    ("unm", "unmap", NORMAL_VISUAL_OPERATOR, MapCommand::Unmap),
+    ("map!", "map!", &[MapMode::Insert], MapCommand::Map),
+    ("no!", "noremap!", &[MapMode::Insert], MapCommand::Noremap),
+    ("unm!", "unmap!", &[MapMode::Insert], MapCommand::Unmap),
+    ("nm", "nmap", &[MapMode::Normal], MapCommand::Map),
+    ("nn", "nnoremap", &[MapMode::Normal], MapCommand::Noremap),
+    ("nun", "nunmap", &[MapMode::Normal], MapCommand::Unmap),
+    ("vm", "vmap", &[MapMode::Visual], MapCommand::Map),
+    ("vn", "vnoremap", &[MapMode::Visual], MapCommand::Noremap),
+    ("vu", "vunmap", &[MapMode::Visual], MapCommand::Unmap),
+    ("xm", "xmap", &[MapMode::Visual], MapCommand::Map),
+    ("xn", "xnoremap", &[MapMode::Visual], MapCommand::Noremap),
+    ("xu", "xunmap", &[MapMode::Visual], MapCommand::Unmap),
+    ("om", "omap", &[MapMode::OperatorPending], MapCommand::Map),
+    (
+        "ono",
+        "onoremap",
+        &[MapMode::OperatorPending],
+        MapCommand::Noremap,
+    ),
+    (
+        "ou",
+        "ounmap",
+        &[MapMode::OperatorPending],
+        MapCommand::Unmap,
+    ),
+    ("im", "imap", &[MapMode::Insert], MapCommand::Map),
+    ("ino", "inoremap", &[MapMode::Insert], MapCommand::Noremap),
+    ("iu", "iunmap", &[MapMode::Insert], MapCommand::Unmap),
+];
+
+fn parse_map_command(command: &str) -> Option<(&'static [MapMode], MapCommand)> {
+    MAP_COMMANDS
+        .iter()
+        .find(|(abbreviation, name, _, _)| is_abbreviation(command, abbreviation, name))
+        .map(|(_, _, modes, map_command)| (*modes, *map_command))
+}
+
+/// Whether `command` is `name`, or an abbreviation of it at least as long as `abbreviation`.
+fn is_abbreviation(command: &str, abbreviation: &str, name: &str) -> bool {
+    let (command, abbreviation, name) = match (
+        command.strip_suffix('!'),
+        abbreviation.strip_suffix('!'),
+        name.strip_suffix('!'),
+    ) {
+        (Some(command), Some(abbreviation), Some(name)) => (command, abbreviation, name),
+        (None, None, None) => (command, abbreviation, name),
+        _ => return false,
+    };
+    command.starts_with(abbreviation) && name.starts_with(command)
+}
+
+/// Whether `command` is `:command` or `:command!`.
+fn is_command_command(command: &str) -> bool {
+    is_abbreviation(command.trim_end_matches('!'), "com", "command")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Mapping {
+    modes: Vec<MapMode>,
+    /// The left-hand side, as space-separated Zed keystrokes.
+    keys: String,
+    /// The right-hand side, as space-separated Zed keystrokes, or `None` for `<Nop>`.
+    keystrokes: Option<String>,
+    /// Whether the right-hand side runs the default bindings rather than other mappings.
+    noremap: bool,
+}
+
+/// The mappings, options and user commands defined by the user's vimrc.
+#[derive(Clone, Debug)]
+pub struct Vimrc {
+    leader: String,
+    local_leader: String,
+    mappings: Vec<Mapping>,
+    options: Vec<VimOption>,
+    commands: HashMap<String, String>,
+}
+
+impl Global for Vimrc {}
+
+impl Default for Vimrc {
+    fn default() -> Self {
+        Self {
+            leader: "\\".to_string(),
+            local_leader: "\\".to_string(),
+            mappings: Vec::new(),
+            options: Vec::new(),
+            commands: HashMap::default(),
+        }
+    }
+}
+
+impl Vimrc {
+    /// Runs each line of a vimrc, returning a message for each line that couldn't be run.
+    pub fn source(&mut self, text: &str) -> Vec<String> {
+        text.lines()
+            .enumerate()
+            .filter_map(|(ix, line)| {
+                self.run_line(line)
+                    .err()
+                    .map(|error| format!("line {}: {error}", ix + 1))
+            })
+            .collect()
+    }
+
+    fn run_line(&mut self, line: &str) -> Result<()> {
+        let line = line.trim().trim_start_matches(':').trim_start();
+        if line.is_empty() || line.starts_with('"') {
+            return Ok(());
+        }
+
+        let (command, args) = split_word(line);
+        if let Some((modes, map_command)) = parse_map_command(command) {
+            return match map_command {
+                MapCommand::Map => self.map(modes, args, false),
+                MapCommand::Noremap => self.map(modes, args, true),
+                MapCommand::Unmap => self.unmap(modes, args),
+            };
+        }
+        match command {
+            "se" | "set" => self.set(args),
+            "let" => self.let_variable(args),
+            _ if is_command_command(command) => self.define_command(args, command.ends_with('!')),
+            _ if is_abbreviation(command, "delc", "delcommand") => {
+                self.commands
+                    .remove(args)
+                    .with_context(|| format!("no such user command `{args}`"))?;
+                Ok(())
+            }
+            _ => bail!("unsupported command `{command}`"),
+        }
+    }
+
+    fn map(&mut self, modes: &[MapMode], args: &str, noremap: bool) -> Result<()> {
+        let mut args = args;
+        loop {
+            let (word, rest) = split_word(args);
+            match word.to_ascii_lowercase().as_str() {
+                "<silent>" | "<nowait>" | "<unique>" | "<special>" => args = rest,
+                "<buffer>" | "<expr>" | "<script>" => bail!("{word} mappings aren't supported"),
+                _ => break,
+            }
+        }
+
+        let (lhs, rhs) = split_word(args);
+        ensure!(!lhs.is_empty(), "listing mappings isn't supported");
+        ensure!(!rhs.is_empty(), "missing right-hand side for `{lhs}`");
+        let keys = self.keystrokes(lhs);
+        let keystrokes = if rhs.eq_ignore_ascii_case("<nop>") {
+            None
+        } else {
+            Some(self.keystrokes(rhs))
+        };
+
+        self.remove_mappings(modes, &keys);
+        self.mappings.push(Mapping {
+            modes: modes.to_vec(),
+            keys,
+            keystrokes,
+            noremap,
+        });
+        Ok(())
+    }
+
+    fn unmap(&mut self, modes: &[MapMode], args: &str) -> Result<()> {
+        let keys = self.keystrokes(args);
+        ensure!(
+            self.remove_mappings(modes, &keys),
+            "no such mapping `{args}`"
+        );
+        Ok(())
+    }
+
+    /// Removes the given modes from the mappings for `keys`, returning whether any were removed.
+    fn remove_mappings(&mut self, modes: &[MapMode], keys: &str) -> bool {
+        let mut removed = false;
+        self.mappings.retain_mut(|mapping| {
+            if mapping.keys == keys {
+                let count = mapping.modes.len();
+                mapping.modes.retain(|mode| !modes.contains(mode));
+                removed |= mapping.modes.len() != count;
+            }
+            !mapping.modes.is_empty()
+        });
+        removed
+    }
+
+    fn set(&mut self, args: &str) -> Result<()> {
+        let args = args.split_once(" \"").map_or(args, |(args, _comment)| args);
+        for word in args.split_whitespace() {
+            let option =
+                VimOption::from(word).with_context(|| format!("unsupported option `{word}`"))?;
+            self.options.retain(|existing| {
+                std::mem::discriminant(existing) != std::mem::discriminant(&option)
+            });
+            self.options.push(option);
+        }
+        Ok(())
+    }
+
+    fn let_variable(&mut self, args: &str) -> Result<()> {
+        let (name, value) = args
+            .split_once('=')
+            .context("expected `let {name} = {value}`")?;
+        let name = name.trim();
+        let value = parse_string(value.trim())?;
+        match name.strip_prefix("g:").unwrap_or(name) {
+            "mapleader" => self.leader = value,
+            "maplocalleader" => self.local_leader = value,
+            _ => bail!("unsupported variable `{name}`"),
+        }
+        Ok(())
+    }
+
+    fn define_command(&mut self, args: &str, replace: bool) -> Result<()> {
+        let mut args = args;
+        // Attributes like `-nargs=1` only matter to vim's argument checking.
+        while args.starts_with('-') {
+            args = split_word(args).1;
+        }
+
+        let (name, replacement) = split_word(args);
+        ensure!(!name.is_empty(), "listing user commands isn't supported");
+        ensure!(
+            name.starts_with(|c: char| c.is_ascii_uppercase())
+                && name.chars().all(|c| c.is_ascii_alphanumeric()),
+            "user command names must start with an uppercase letter, got `{name}`"
+        );
+        ensure!(!replacement.is_empty(), "missing replacement for `{name}`");
+        ensure!(
+            replace || !self.commands.contains_key(name),
+            "user command `{name}` already exists, use `command!` to replace it"
+        );
+        self.commands
+            .insert(name.to_string(), replacement.to_string());
+        Ok(())
+    }
+
+    /// The key bindings for the vimrc's mappings, to be bound after the vim keymap.
+    pub fn key_bindings(&self, cx: &App) -> Vec<KeyBinding> {
+        self.mappings
+            .iter()
+            .filter_map(|mapping| {
+                // None of the mappings apply while a `noremap` mapping's keystrokes are sent.
+                let context = format!(
+                    "({}) && !menu && !{NOREMAP_CONTEXT}",
+                    mapping.modes.iter().map(|mode| mode.context()).join(" || ")
+                );
+                let predicate = KeyBindingContextPredicate::parse(&context).log_err()?;
+                let action: Box<dyn Action> = match &mapping.keystrokes {
+                    Some(keystrokes) if mapping.noremap => {
+                        Box::new(SendNoremapKeystrokes(keystrokes.clone()))
+                    }
+                    Some(keystrokes) => Box::new(SendKeystrokes(keystrokes.clone())),
+                    None => Box::new(NoAction),
+                };
+                let binding = KeyBinding::load(
+                    &mapping.keys,
+                    action,
+                    Some(Rc::new(predicate)),
+                    false,
+                    None,
+                    cx.keyboard_mapper().as_ref(),
+                )
+                .log_err()?;
+                Some(binding.with_meta(KeybindSource::Vim.meta()))
+            })
+            .collect()
+    }
+
+    /// Converts vim key notation, like `<leader>f<C-w>`, to space-separated Zed keystrokes.
+    fn keystrokes(&self, notation: &str) -> String {
+        let mut keystrokes = Vec::new();
+        let mut rest = notation;
+        while let Some(c) = rest.chars().next() {
+            if c == '<'
+                && let Some(end) = rest.find('>')
+                && let Some(keys) = self.special_key(&rest[1..end])
+            {
+                keystrokes.extend(keys);
+                rest = &rest[end + 1..];
+            } else {
+                keystrokes.push(char_keystroke(c));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        keystrokes.join(" ")
+    }
+
+    fn special_key(&self, name: &str) -> Option<Vec<String>> {
+        match name.to_ascii_lowercase().as_str() {
+            "leader" => return Some(self.leader.chars().map(char_keystroke).collect()),
+            "localleader" => return Some(self.local_leader.chars().map(char_keystroke).collect()),
+            _ => {}
+        }
+        if let Some(key) = named_key(name) {
+            return Some(vec![key.to_string()]);
+        }
+
+        let (modifiers, key) = name.rsplit_once('-')?;
+        let mut keystroke = String::new();
+        for modifier in modifiers.split('-') {
+            keystroke.push_str(match modifier.to_ascii_lowercase().as_str() {
+                "c" => "ctrl-",
+                "s" => "shift-",
+                "a" | "m" => "alt-",
+                "d" => "cmd-",
+                _ => return None,
+            });
+        }
+        let mut chars = key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => keystroke.push(c.to_ascii_lowercase()),
+            _ => keystroke.push_str(named_key(key)?),
+        }
+        Some(vec![keystroke])
+    }
+}
+
+fn named_key(name: &str) -> Option<&'static str> {
+    let key = match name.to_ascii_lowercase().as_str() {
+        "lt" => "<",
+        "bar" => "|",
+        "bslash" => "\\",
+        "space" => "space",
+        "cr" | "enter" | "return" => "enter",
+        "esc" => "escape",
+        "tab" => "tab",
+        "bs" => "backspace",
+        "del" => "delete",
+        "insert" => "insert",
+        "up" => "up",
+        "down" => "down",
+        "left" => "left",
+        "right" => "right",
+        "home" => "home",
+        "end" => "end",
+        "pageup" => "pageup",
+        "pagedown" => "pagedown",
+        "f1" => "f1",
+        "f2" => "f2",
+        "f3" => "f3",
+        "f4" => "f4",
+        "f5" => "f5",
+        "f6" => "f6",
+        "f7" => "f7",
+        "f8" => "f8",
+        "f9" => "f9",
+        "f10" => "f10",
+        "f11" => "f11",
+        "f12" => "f12",
+        _ => return None,
+    };
+    Some(key)
+}
+
+fn char_keystroke(c: char) -> String {
+    match c {
+        ' ' => "space".to_string(),
+        'A'..='Z' => format!("shift-{}", c.to_ascii_lowercase()),
+        _ => c.to_string(),
+    }
+}
+
+/// Splits off the first whitespace-separated word.
+fn split_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    let (word, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    (word, rest.trim_start())
+}
+
+/// Parses a vim string literal: `'literal'`, or `"escaped"` with `\\`, `\"` and `\<Space>`.
+fn parse_string(literal: &str) -> Result<String> {
+    if let Some(contents) = literal
+        .strip_prefix('\'')
+        .and_then(|literal| literal.strip_suffix('\''))
+    {
+        return Ok(contents.replace("''", "'"));
+    }
+
+    let contents = literal
+        .strip_prefix('"')
+        .and_then(|literal| literal.strip_suffix('"'))
+        .with_context(|| format!("expected a quoted string, got `{literal}`"))?;
+    let mut value = String::new();
+    let mut rest = contents;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        if c != '\\' {
+            value.push(c);
+        } else if let Some(key) = rest.strip_prefix('<').and_then(|key| key.split_once('>')) {
+            value.push(match key.0.to_ascii_lowercase().as_str() {
+                "space" => ' ',
+                "tab" => '\t',
+                _ => bail!("unsupported key `<{}>` in string", key.0),
+            });
+            rest = key.1;
+        } else {
+            let c = rest
+                .chars()
+                .next()
+                .context("unterminated escape in string")?;
+            value.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    Ok(value)
+}
+
+/// Runs the user's vimrc, and again each time it changes.
+pub fn handle_vimrc_file_changes(
+    mut vimrc_file_rx: UnboundedReceiver<String>,
+    vimrc_watcher: Task<()>,
+    cx: &mut App,
+) {
+    cx.spawn(async move |cx| {
+        let _vimrc_watcher = vimrc_watcher;
+        while let Some(content) = vimrc_file_rx.next().await {
+            cx.update(|cx| {
+                let mut vimrc = Vimrc::default();
+                let errors = vimrc.source(&content);
+                set_vimrc(vimrc, paths::vimrc_file(), errors, cx);
+            });
+        }
+    })
+    .detach();
+}
+
+pub(crate) fn init(cx: &mut App) {
+    // Settings files replace the settings that the vimrc's options override.
+    cx.observe_global::<SettingsStore>(|cx| {
+        if VimModeSetting::get_global(cx).0
+            && let Some(vimrc) = cx.try_global::<Vimrc>()
+        {
+            let options = vimrc.options.clone();
+            VimOption::override_settings(&options, cx);
+        }
+    })
+    .detach();
+}
+
+fn set_vimrc(vimrc: Vimrc, path: &Path, errors: Vec<String>, cx: &mut App) {
+    let options = vimrc.options.clone();
+    cx.set_global(vimrc);
+    if VimModeSetting::get_global(cx).0 {
+        VimOption::override_settings(&options, cx);
+    }
+
+    struct VimrcErrorNotification;
+    let notification_id = NotificationId::unique::<VimrcErrorNotification>();
+    if errors.is_empty() {
+        dismiss_app_notification(&notification_id, cx);
+        return;
+    }
+    let message = format!(
+        "Some lines of {} couldn't be run:\n\n{}",
+        path.display(),
+        errors.join("\n")
+    );
+    show_app_notification(notification_id, cx, move |cx| {
+        let message = message.clone();
+        cx.new(|cx| MessageNotification::new(message, cx))
+    });
+}
+
+/// Applies the vimrc's options that Zed keeps on each editor.
+pub(crate) fn sync_to_editor(editor: &mut Editor, cx: &mut Context<Editor>) {
+    let wrap = cx.try_global::<Vimrc>().and_then(|vimrc| {
+        vimrc.options.iter().find_map(|option| match option {
+            VimOption::Wrap(wrap) => Some(*wrap),
+            _ => None,
+        })
+    });
+    if let Some(wrap) = wrap {
+        editor.set_soft_wrap_mode(
+            if wrap {
+                SoftWrap::EditorWidth
+            } else {
+                SoftWrap::None
+            },
+            cx,
+        );
+    }
+}
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
+    Vim::action(editor, cx, |vim, action: &VimSource, window, cx| {
+        let Some(workspace) = vim.workspace(window) else {
+            return;
+        };
+        let workspace = workspace.read(cx);
+        let fs = workspace.app_state().fs.clone();
+        let path = if action.filename.is_empty() {
+            paths::vimrc_file().clone()
+        } else {
+            resolve_path(&action.filename, workspace, cx)
+        };
+
+        cx.spawn(async move |_, cx| {
+            let content = fs
+                .load(&path)
+                .await
+                .with_context(|| format!("reading {}", path.display()))?;
+            cx.update(|cx| {
+                // Re-running the vimrc starts afresh, so that deleted lines stop applying.
+                let mut vimrc = if path == *paths::vimrc_file() {
+                    Vimrc::default()
+                } else {
+                    cx.try_global::<Vimrc>().cloned().unwrap_or_default()
+                };
+                let errors = vimrc.source(&content);
+                set_vimrc(vimrc, &path, errors, cx);
+            });
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to source file", window, cx, |_, _, _| None);
+    });
+
+    Vim::action(editor, cx, |_, action: &RunVimrcLine, window, cx| {
+        let mut vimrc = cx.try_global::<Vimrc>().cloned().unwrap_or_default();
+        match vimrc.run_line(&action.line) {
+            Ok(()) => {
+                let options = vimrc.options.clone();
+                cx.set_global(vimrc);
+                VimOption::override_settings(&options, cx);
+            }
+            Err(error) => {
+                let _ = window.prompt(
+                    gpui::PromptLevel::Critical,
+                    &error.to_string(),
+                    None,
+                    &["Cancel"],
+                    cx,
+                );
+            }
+        }
+    });
+
+    Vim::action(
+        editor,
+        cx,
+        |vim, action: &SendNoremapKeystrokes, window, cx| {
+            let Some(workspace) = vim.workspace(window) else {
+                return;
+            };
+            let keystrokes = action
+                .0
+                .split(' ')
+                .flat_map(|keystroke| Keystroke::parse(keystroke).log_err())
+                .map(|keystroke| {
+                    cx.keyboard_mapper()
+                        .map_key_equivalent(keystroke, false)
+                        .inner()
+                        .clone()
+                })
+                .collect();
+
+            // Key contexts are updated when the window is drawn, which happens before the next
+            // keystroke is dispatched once the window is refreshed.
+            Vim::globals(cx).dispatching_noremap = true;
+            window.refresh();
+            let dispatched = workspace.update(cx, |workspace, cx| {
+                workspace.send_keystrokes_impl(keystrokes, window, cx)
+            });
+            cx.spawn_in(window, async move |_, cx| {
+                dispatched.await;
+                cx.update(|window, cx| {
+                    Vim::globals(cx).dispatching_noremap = false;
+                    window.refresh();
+                })
+                .ok();
+            })
+            .detach();
+        },
+    );
+
+    Vim::action(editor, cx, |_, action: &RunUserCommand, window, cx| {
+        for action in &action.actions {
+            window.dispatch_action(action.boxed_clone(), cx);
+        }
+    });
+}
+
+fn resolve_path(filename: &str, workspace: &Workspace, cx: &App) -> PathBuf {
+    if let Some(rest) = filename.strip_prefix("~/") {
+        return util::paths::home_dir().join(rest);
+    }
+    let path = Path::new(filename);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    workspace
+        .project()
+        .read(cx)
+        .visible_worktrees(cx)
+        .next()
+        .map_or_else(
+            || util::paths::home_dir().join(path),
+            |worktree| worktree.read(cx).abs_path().join(path),
+        )
+}
+
+/// The action for a vimrc command typed on the command line, or for a user command.
+pub(crate) fn ex_command_action(
+    query: &str,
+    range_prefix: &str,
+    workspace: &WeakEntity<Workspace>,
+    cx: &mut App,
+) -> Option<Box<dyn Action>> {
+    let (command, args) = split_word(query);
+    let is_vimrc_command = parse_map_command(command).is_some()
+        || command == "let"
+        || is_command_command(command)
+        || is_abbreviation(command, "delc", "delcommand");
+    if is_vimrc_command && !args.is_empty() {
+        return Some(Box::new(RunVimrcLine {
+            line: query.to_string(),
+        }));
+    }
+
+    user_command_action(query, range_prefix, workspace, 0, cx)
+}
+
+/// Expands a user command into its actions. Each `|`-separated part of the replacement is
+/// either an action name, optionally followed by its JSON arguments, or an ex command.
+fn user_command_action(
+    query: &str,
+    range_prefix: &str,
+    workspace: &WeakEntity<Workspace>,
+    depth: usize,
+    cx: &mut App,
+) -> Option<Box<dyn Action>> {
+    let (name, args) = split_word(query);
+    let replacement = cx
+        .try_global::<Vimrc>()?
+        .commands
+        .get(name)?
+        .replace("<args>", args);
+    if depth >= MAX_USER_COMMAND_DEPTH {
+        log::error!("user command `{name}` expands too deeply");
+        return None;
+    }
+
+    let mut actions = Vec::new();
+    for part in replacement.split('|').map(str::trim) {
+        let part = part.trim_start_matches(':');
+        if part.is_empty() {
+            continue;
+        }
+        let (word, input) = split_word(part);
+        let action = if word.contains("::") {
+            let input = if input.is_empty() {
+                None
+            } else {
+                Some(serde_json::from_str(input).log_err()?)
+            };
+            cx.build_action(word, input).log_err()?
+        } else if cx
+            .try_global::<Vimrc>()
+            .is_some_and(|vimrc| vimrc.commands.contains_key(word))
+        {
+            user_command_action(part, range_prefix, workspace, depth + 1, cx)?
+        } else {
+            command_interceptor(&format!("{range_prefix}{part}"), workspace.clone(), cx)
+                .now_or_never()?
+                .results
+                .into_iter()
+                .next()?
+                .action
+        };
+        actions.push(action);
+    }
+
+    if actions.len() == 1 {
+        actions.pop()
+    } else {
+        Some(Box::new(RunUserCommand {
+            actions: actions.into_iter().map(WrappedAction).collect(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{state::Mode, test::VimTestContext};
+    use gpui::{TestAppContext, UpdateGlobal as _};
+    use indoc::indoc;
+
+    #[test]
+    fn test_key_notation() {
+        let mut vimrc = Vimrc::default();
+        assert_eq!(vimrc.keystrokes("gT"), "g shift-t");
+        assert_eq!(vimrc.keystrokes("<C-w>v"), "ctrl-w v");
+        assert_eq!(vimrc.keystrokes("<leader>f<CR>"), "\\ f enter");
+        assert_eq!(
+            vimrc.keystrokes("<lt>x<Space><C-S-Tab>"),
+            "< x space ctrl-shift-tab"
+        );
+        assert_eq!(vimrc.keystrokes("<<"), "< <");
+        assert_eq!(vimrc.keystrokes("<unknown>"), "< u n k n o w n >");
+
+        vimrc.run_line(r#"let mapleader = "\<Space>""#).unwrap();
+        assert_eq!(vimrc.keystrokes("<Leader>w"), "space w");
+        vimrc.run_line("let g:mapleader = ','").unwrap();
+        assert_eq!(vimrc.keystrokes("<leader>w"), ", w");
+    }
+
+    #[test]
+    fn test_source() {
+        let mut vimrc = Vimrc::default();
+        let errors = vimrc.source(indoc! {r#"
+            " comments and blank lines are skipped
+
+            let mapleader = ","
+            nnoremap <silent> <leader>w :w<CR>
+            vmap < <gv
+            inoremap jk <Esc>
+            map Q <Nop>
+            unmap Q
+            nun Q
+            set ignorecase smartcase scrolloff=8 " trailing comment
+            set noignorecase
+            command! -nargs=1 Grep /<args>
+            nnoremap <buffer> x y
+            set nosuchoption
+            autocmd BufWritePre * :Format
+        "#});
+
+        assert_eq!(
+            errors,
+            vec![
+                "line 9: no such mapping `Q`",
+                "line 13: <buffer> mappings aren't supported",
+                "line 14: unsupported option `nosuchoption`",
+                "line 15: unsupported command `autocmd`",
+            ]
+        );
+        assert_eq!(
+            vimrc.mappings,
+            vec![
+                Mapping {
+                    modes: vec![MapMode::Normal],
+                    keys: ", w".into(),
+                    keystrokes: Some(": w enter".into()),
+                    noremap: true,
+                },
+                Mapping {
+                    modes: vec![MapMode::Visual],
+                    keys: "<".into(),
+                    keystrokes: Some("< g v".into()),
+                    noremap: false,
+                },
+                Mapping {
+                    modes: vec![MapMode::Insert],
+                    keys: "j k".into(),
+                    keystrokes: Some("escape".into()),
+                    noremap: true,
+                },
+            ]
+        );
+        assert_eq!(
+            vimrc.options,
+            vec![
+                VimOption::SmartCase(true),
+                VimOption::ScrollOff(8),
+                VimOption::IgnoreCase(false),
+            ]
+        );
+        assert_eq!(vimrc.commands.get("Grep").unwrap(), "/<args>");
+        assert!(vimrc.run_line("command Grep x").is_err());
+        assert!(vimrc.run_line("command grep x").is_err());
+    }
+
+    #[gpui::test]
+    async fn test_vimrc_mappings(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update(|_, cx| {
+            let mut vimrc = Vimrc::default();
+            let errors = vimrc.source(indoc! {"
+                let mapleader = ' '
+                nnoremap <leader>d dd
+                inoremap jk <Esc>
+            "});
+            assert!(errors.is_empty());
+            let bindings = vimrc.key_bindings(cx);
+            cx.bind_keys(bindings);
+            cx.set_global(vimrc);
+        });
+
+        cx.set_state("ˇone\ntwo", Mode::Normal);
+        cx.simulate_keystrokes("space d");
+        cx.run_until_parked();
+        cx.assert_state("ˇtwo", Mode::Normal);
+
+        cx.simulate_keystrokes("i j k");
+        cx.run_until_parked();
+        cx.assert_state("ˇtwo", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_noremap_swaps_keys(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update(|_, cx| {
+            SettingsStore::update_global(cx, |settings, cx| {
+                settings.update_user_settings(cx, |settings| {
+                    settings.project.all_languages.defaults.soft_wrap =
+                        Some(SoftWrap::PreferredLineLength);
+                    settings
+                        .project
+                        .all_languages
+                        .defaults
+                        .preferred_line_length = Some(12);
+                });
+            });
+            let mut vimrc = Vimrc::default();
+            let errors = vimrc.source(indoc! {"
+                nnoremap j gj
+                nnoremap gj j
+                nmap K gj
+            "});
+            assert!(errors.is_empty());
+            let bindings = vimrc.key_bindings(cx);
+            cx.bind_keys(bindings);
+            cx.set_global(vimrc);
+        });
+
+        // `j` moves by display line, without `gj` being mapped back to `j`.
+        cx.set_state("tˇwelve char twelve char\ntwelve char", Mode::Normal);
+        cx.simulate_keystrokes("j");
+        cx.run_until_parked();
+        cx.assert_state("twelve char tˇwelve char\ntwelve char", Mode::Normal);
+
+        cx.set_state("tˇwelve char twelve char\ntwelve char", Mode::Normal);
+        cx.simulate_keystrokes("g j");
+        cx.run_until_parked();
+        cx.assert_state("twelve char twelve char\ntˇwelve char", Mode::Normal);
+
+        // Mappings apply to the keystrokes of `map`, so `K` runs the `gj` mapping.
+        cx.set_state("tˇwelve char twelve char\ntwelve char", Mode::Normal);
+        cx.simulate_keystrokes("shift-k");
+        cx.run_until_parked();
+        cx.assert_state("twelve char twelve char\ntˇwelve char", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_user_commands(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update(|_, cx| {
+            let mut vimrc = Vimrc::default();
+            let errors = vimrc.source(indoc! {"
+                command Bottom $
+                command -nargs=1 Find /<args>
+                command DownTwice editor::MoveDown | editor::MoveDown
+                command Loop Loop
+            "});
+            assert!(errors.is_empty());
+            cx.set_global(vimrc);
+        });
+
+        cx.set_state("ˇone\ntwo\nthree", Mode::Normal);
+        cx.simulate_keystrokes(": B o t t o m enter");
+        cx.assert_state("one\ntwo\nˇthree", Mode::Normal);
+
+        cx.simulate_keystrokes(": F i n d space o n e enter");
+        cx.run_until_parked();
+        cx.assert_state("ˇone\ntwo\nthree", Mode::Normal);
+
+        cx.simulate_keystrokes(": D o w n T w i c e enter");
+        cx.assert_state("one\ntwo\nˇthree", Mode::Normal);
+
+        cx.update(|_, cx| {
+            let action = user_command_action("Loop", "", &WeakEntity::new_invalid(), 0, cx);
+            assert!(action.is_none());
+        });
+
+        cx.simulate_keystrokes(": n n o r e m a p space Q space j enter");
+        cx.update(|_, cx| {
+            let vimrc = cx.global::<Vimrc>();
+            assert_eq!(
+                vimrc.mappings,
+                vec![Mapping {
+                    modes: vec![MapMode::Normal],
+                    keys: "shift-q".into(),
+                    keystrokes: Some("j".into()),
+                    noremap: true,
+                }]
+            );
+            assert!(vimrc.commands.contains_key("Bottom"));
+        });
+    }
+}
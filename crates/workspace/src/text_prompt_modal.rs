@@ -0,0 +1,176 @@
+//! A modal asking for a few lines of text, such as the name of something to create.
+
+use gpui::{AnyView, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Subscription};
+use menu::{Cancel, Confirm};
+use ui::{Headline, HeadlineSize, prelude::*};
+use ui_input::{ErasedEditorEvent, InputField};
+
+use crate::ModalView;
+
+/// Why the text entered into a [`TextPromptModal`] was refused.
+pub struct InvalidInput {
+    /// The index of the field to focus.
+    pub field: usize,
+    pub message: SharedString,
+}
+
+impl InvalidInput {
+    pub fn new(field: usize, message: impl Into<SharedString>) -> Self {
+        Self {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+type ConfirmFn = Box<dyn FnMut(Vec<String>, &mut Window, &mut App) -> Result<(), InvalidInput>>;
+
+/// Asks for the text of one or more fields, and passes it on once confirmed.
+pub struct TextPromptModal {
+    title: SharedString,
+    icon: Option<IconName>,
+    fields: Vec<Entity<InputField>>,
+    note: Option<SharedString>,
+    /// Further input shown below the fields, such as a dropdown.
+    accessory: Option<AnyView>,
+    /// Why the last confirmation was refused, cleared once the input changes.
+    error: Option<SharedString>,
+    on_confirm: ConfirmFn,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl TextPromptModal {
+    /// Creates a modal with a field for each of `placeholders`, of which there must be at least
+    /// one. `on_confirm` is called with the trimmed text of every field, and the modal is
+    /// dismissed unless it refuses the input.
+    pub fn new(
+        title: impl Into<SharedString>,
+        placeholders: &[&str],
+        on_confirm: impl FnMut(Vec<String>, &mut Window, &mut App) -> Result<(), InvalidInput> + 'static,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        debug_assert!(!placeholders.is_empty(), "a text prompt needs a field");
+        let fields = placeholders
+            .iter()
+            .map(|placeholder| cx.new(|cx| InputField::new(window, cx, placeholder)))
+            .collect::<Vec<_>>();
+        let _subscriptions = fields
+            .iter()
+            .map(|field| {
+                let this = cx.weak_entity();
+                let editor = field.read(cx).editor().clone();
+                editor.subscribe(
+                    Box::new(move |event, _, cx| {
+                        if event == ErasedEditorEvent::BufferEdited {
+                            this.update(cx, |this, cx| {
+                                if this.error.take().is_some() {
+                                    cx.notify();
+                                }
+                            })
+                            .ok();
+                        }
+                    }),
+                    window,
+                    cx,
+                )
+            })
+            .collect();
+        Self {
+            title: title.into(),
+            icon: None,
+            fields,
+            note: None,
+            accessory: None,
+            error: None,
+            on_confirm: Box::new(on_confirm),
+            _subscriptions,
+        }
+    }
+
+    pub fn icon(mut self, icon: IconName) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Text explaining what's asked for, shown below the fields.
+    pub fn note(mut self, note: impl Into<SharedString>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    pub fn accessory(mut self, accessory: impl Into<AnyView>) -> Self {
+        self.accessory = Some(accessory.into());
+        self
+    }
+
+    fn cancel(&mut self, _: &Cancel, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let texts = self
+            .fields
+            .iter()
+            .map(|field| field.read(cx).text(cx).trim().to_string())
+            .collect();
+        match (self.on_confirm)(texts, window, cx) {
+            Ok(()) => cx.emit(DismissEvent),
+            Err(InvalidInput { field, message }) => {
+                if let Some(field) = self.fields.get(field) {
+                    window.focus(&field.focus_handle(cx), cx);
+                }
+                self.error = Some(message);
+                cx.notify();
+            }
+        }
+    }
+}
+
+impl EventEmitter<DismissEvent> for TextPromptModal {}
+impl ModalView for TextPromptModal {}
+impl Focusable for TextPromptModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.fields[0].focus_handle(cx)
+    }
+}
+
+impl Render for TextPromptModal {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("TextPromptModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .elevation_2(cx)
+            .w(rems(34.))
+            .child(
+                h_flex()
+                    .px_3()
+                    .pt_2()
+                    .pb_1()
+                    .w_full()
+                    .gap_1p5()
+                    .children(self.icon.map(|icon| Icon::new(icon).size(IconSize::XSmall)))
+                    .child(Headline::new(self.title.clone()).size(HeadlineSize::XSmall)),
+            )
+            .child(
+                v_flex()
+                    .px_3()
+                    .pb_3()
+                    .gap_2()
+                    .w_full()
+                    .children(self.fields.iter().cloned())
+                    .children(self.accessory.clone())
+                    .children(
+                        self.note.clone().map(|note| {
+                            Label::new(note).size(LabelSize::Small).color(Color::Muted)
+                        }),
+                    )
+                    .children(
+                        self.error.clone().map(|error| {
+                            Label::new(error).size(LabelSize::Small).color(Color::Error)
+                        }),
+                    ),
+            )
+    }
+}
@@ -13,6 +13,7 @@ mod security_modal;
 pub mod shared_screen;
 mod status_bar;
 pub mod tasks;
+mod text_prompt_modal;
 mod theme_preview;
 mod toast_layer;
 mod toolbar;
@@ -117,6 +118,7 @@ use std::{
     time::Duration,
 };
 use task::{DebugScenario, SharedTaskContext, SpawnInTerminal};
+pub use text_prompt_modal::{InvalidInput, TextPromptModal};
 use theme::{ActiveTheme, GlobalTheme, SystemAppearance, ThemeSettings};
 pub use toolbar::{
     PaneSearchBarCallbacks, Toolbar, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView,
@@ -676,6 +678,7 @@ type BuildProjectItemForPathFn =
 struct ProjectItemRegistry {
     build_project_item_fns_by_type: HashMap<TypeId, BuildProjectItemFn>,
     build_project_item_for_path_fns: Vec<BuildProjectItemForPathFn>,
+    build_project_item_for_refused_path_fns: Vec<BuildProjectItemForPathFn>,
 }
 
 impl ProjectItemRegistry {
@@ -727,6 +730,31 @@ impl ProjectItemRegistry {
                             Ok((project_entry_id, build_workspace_item))
                         }
                         Err(e) => {
+                            // Files that couldn't be loaded may still open as another kind of
+                            // item, like binary ones in a hex editor.
+                            let open_refused = if is_file {
+                                cx.update(|window, cx| {
+                                    let open_refused_fns = cx
+                                        .try_global::<ProjectItemRegistry>()
+                                        .map(|registry| {
+                                            registry.build_project_item_for_refused_path_fns.clone()
+                                        })
+                                        .unwrap_or_default();
+                                    open_refused_fns.iter().rev().find_map(|open_refused| {
+                                        open_refused(&project, &project_path, window, cx)
+                                    })
+                                })?
+                            } else {
+                                None
+                            };
+                            if let Some(open_refused) = open_refused {
+                                match open_refused.await {
+                                    Ok(item) => return Ok(item),
+                                    Err(refused_error) => {
+                                        log::debug!("Failed to open a refused file: {refused_error:#}")
+                                    }
+                                }
+                            }
                             log::warn!("Failed to open a project item: {e:#}");
                             if e.error_code() == ErrorCode::Internal {
                                 if let Some(abs_path) =
@@ -754,6 +782,25 @@ impl ProjectItemRegistry {
                     }
                 }))
             });
+        self.build_project_item_for_refused_path_fns
+            .push(|project, project_path, window, cx| {
+                let project_item =
+                    <T::Item as project::ProjectItem>::try_open_refused(project, project_path, cx)?;
+                let project = project.clone();
+                Some(window.spawn(cx, async move |cx| {
+                    let project_item = project_item.await?;
+                    let project_entry_id: Option<ProjectEntryId> =
+                        project_item.read_with(cx, project::ProjectItem::entry_id);
+                    let build_workspace_item = Box::new(
+                        |pane: &mut Pane, window: &mut Window, cx: &mut Context<Pane>| {
+                            Box::new(cx.new(|cx| {
+                                T::for_project_item(project, Some(pane), project_item, window, cx)
+                            })) as Box<dyn ItemHandle>
+                        },
+                    ) as Box<_>;
+                    Ok((project_entry_id, build_workspace_item))
+                }))
+            });
     }
 
     fn open_path(
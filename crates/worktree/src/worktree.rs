@@ -5927,7 +5927,14 @@ impl fs::Watcher for NullWatcher {
     }
 }
 
-const FILE_ANALYSIS_BYTES: usize = 1024;
+/// How many bytes from the start of a file are examined to tell its kind and encoding.
+pub const FILE_ANALYSIS_BYTES: usize = 1024;
+
+/// Whether a file starting with `prefix` holds binary data rather than text in some encoding.
+/// Such files can't be loaded into a buffer.
+pub fn is_binary_file_prefix(prefix: &[u8]) -> bool {
+    decode_byte_header(prefix).1 == ByteContent::Binary
+}
 
 async fn decode_file_text(
     fs: &dyn Fs,
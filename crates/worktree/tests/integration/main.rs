@@ -2737,6 +2737,21 @@ async fn test_load_file_encoding(cx: &mut TestAppContext) {
     }
 }
 
+#[test]
+fn test_is_binary_file_prefix() {
+    assert!(worktree::is_binary_file_prefix(
+        b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"
+    ));
+    assert!(worktree::is_binary_file_prefix(&[
+        0x00, 0xFF, 0x12, 0x00, 0x99, 0x88, 0x77, 0x66, 0x00
+    ]));
+    assert!(!worktree::is_binary_file_prefix(b"fn main() {}\n"));
+    // UTF-16LE with BOM
+    assert!(!worktree::is_binary_file_prefix(&[
+        0xFF, 0xFE, 0x53, 0x30, 0x93, 0x30
+    ]));
+}
+
 #[gpui::test]
 async fn test_write_file_encoding(cx: &mut gpui::TestAppContext) {
     init_test(cx);
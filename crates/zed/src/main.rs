@@ -376,6 +376,11 @@ fn main() {
         fs.clone(),
         paths::keymap_file().clone(),
     );
+    let (vimrc_file_rx, vimrc_watcher) = watch_config_file(
+        &app.background_executor(),
+        fs.clone(),
+        paths::vimrc_file().clone(),
+    );
 
     let (shell_env_loaded_tx, shell_env_loaded_rx) = oneshot::channel();
     if !stdout_is_a_pty() {
@@ -442,6 +447,7 @@ fn main() {
             cx,
         );
         handle_keymap_file_changes(user_keymap_file_rx, user_keymap_watcher, cx);
+        vim::handle_vimrc_file_changes(vimrc_file_rx, vimrc_watcher, cx);
 
         let user_agent = format!(
             "Zed/{} ({}; {})",
@@ -641,6 +647,9 @@ fn main() {
 
         editor::init(cx);
         image_viewer::init(cx);
+        hex_editor::init(cx);
+        large_file_viewer::init(cx);
+        local_history::init(cx);
         repl::notebook::init(cx);
         diagnostics::init(cx);
 
@@ -671,6 +680,7 @@ fn main() {
         });
         vim::init(cx);
         terminal_view::init(cx);
+        terminal_view::shared_terminals::init(&app_state.client);
         editor_panel::init(cx);
         journal::init(app_state.clone(), cx);
         encoding_selector::init(cx);
@@ -1675,6 +1675,7 @@ pub fn handle_keymap_file_changes(
     let mut old_base_keymap = *BaseKeymap::get_global(cx);
     let mut old_vim_enabled = VimModeSetting::get_global(cx).0;
     let mut old_helix_enabled = vim_mode_setting::HelixModeSetting::get_global(cx).0;
+    let vimrc_changed_tx = base_keymap_tx.clone();
 
     cx.observe_global::<SettingsStore>(move |cx| {
         let new_base_keymap = *BaseKeymap::get_global(cx);
@@ -1694,6 +1695,11 @@ pub fn handle_keymap_file_changes(
     })
     .detach();
 
+    cx.observe_global::<vim::Vimrc>(move |_| {
+        vimrc_changed_tx.unbounded_send(()).ok();
+    })
+    .detach();
+
     #[cfg(target_os = "windows")]
     {
         let mut current_layout_id = cx.keyboard_layout().id().to_string();
@@ -1908,6 +1914,11 @@ pub fn load_default_keymap(cx: &mut App) {
         cx.bind_keys(
             KeymapFile::load_asset(VIM_KEYMAP_PATH, Some(KeybindSource::Vim), cx).unwrap(),
         );
+
+        if let Some(vimrc) = cx.try_global::<vim::Vimrc>() {
+            let vimrc_bindings = vimrc.key_bindings(cx);
+            cx.bind_keys(vimrc_bindings);
+        }
     }
 }
 
@@ -4863,6 +4874,7 @@ mod tests {
                 "git_panel",
                 "git_picker",
                 "go_to_line",
+                "hex_editor",
                 "highlights_tree_view",
                 "icon_theme_selector",
                 "image_viewer",
@@ -4871,8 +4883,10 @@ mod tests {
                 "keymap_editor",
                 "keystroke_input",
                 "language_selector",
+                "large_file_viewer",
                 "welcome",
                 "line_ending_selector",
+                "local_history",
                 "lsp_tool",
                 "markdown",
                 "menu",
@@ -5047,6 +5061,7 @@ mod tests {
             remote_explorer::init(cx);
             outline_panel::init(cx);
             terminal_view::init(cx);
+            terminal_view::shared_terminals::init(&app_state.client);
             copilot_chat::init(
                 app_state.fs.clone(),
                 app_state.client.http_client(),
@@ -5054,6 +5069,9 @@ mod tests {
                 cx,
             );
             image_viewer::init(cx);
+            hex_editor::init(cx);
+            large_file_viewer::init(cx);
+            local_history::init(cx);
             language_model::init(app_state.client.clone(), cx);
             language_models::init(app_state.user_store.clone(), app_state.client.clone(), cx);
             web_search::init(cx);
@@ -315,7 +315,9 @@ pub mod remote_explorer {
         remote_explorer,
         [
             /// Toggles focus on the remote explorer panel.
-            ToggleFocus
+            ToggleFocus,
+            /// Saves how the saved sessions in the terminal panel are tiled as a named layout.
+            SaveTerminalLayout
         ]
     );
 }